@@ -74,7 +74,7 @@ use syn::{
     parse::{Parse, ParseStream},
     parse_macro_input,
     punctuated::Punctuated,
-    Ident, ItemFn, ReturnType, Token,
+    Ident, ItemFn, LitInt, LitStr, ReturnType, Token,
 };
 
 /// Arguments to the benchmark attribute
@@ -82,6 +82,16 @@ struct BenchmarkArgs {
     setup: Option<Ident>,
     teardown: Option<Ident>,
     per_iteration: bool,
+    seeded: bool,
+    warmup_until_stable: bool,
+    sample_time_ms: Option<LitInt>,
+    adaptive: bool,
+    sample_size: Option<LitInt>,
+    warmup: Option<LitInt>,
+    group: Option<LitStr>,
+    name: Option<LitStr>,
+    /// `Some(None)` for a bare `ignore`, `Some(Some(reason))` for `ignore = "..."`.
+    ignore: Option<Option<LitStr>>,
 }
 
 impl Parse for BenchmarkArgs {
@@ -89,12 +99,30 @@ impl Parse for BenchmarkArgs {
         let mut setup = None;
         let mut teardown = None;
         let mut per_iteration = false;
+        let mut seeded = false;
+        let mut warmup_until_stable = false;
+        let mut sample_time_ms = None;
+        let mut adaptive = false;
+        let mut sample_size = None;
+        let mut warmup = None;
+        let mut group = None;
+        let mut name = None;
+        let mut ignore = None;
 
         if input.is_empty() {
             return Ok(Self {
                 setup,
                 teardown,
                 per_iteration,
+                seeded,
+                warmup_until_stable,
+                sample_time_ms,
+                adaptive,
+                sample_size,
+                warmup,
+                group,
+                name,
+                ignore,
             });
         }
 
@@ -121,6 +149,57 @@ impl Parse for BenchmarkArgs {
                 BenchmarkArg::PerIteration => {
                     per_iteration = true;
                 }
+                BenchmarkArg::Seeded => {
+                    seeded = true;
+                }
+                BenchmarkArg::WarmupUntilStable => {
+                    warmup_until_stable = true;
+                }
+                BenchmarkArg::SampleTimeMs(lit) => {
+                    if sample_time_ms.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            "duplicate sample_time_ms argument",
+                        ));
+                    }
+                    sample_time_ms = Some(lit);
+                }
+                BenchmarkArg::Adaptive => {
+                    adaptive = true;
+                }
+                BenchmarkArg::SampleSize(lit) => {
+                    if sample_size.is_some() {
+                        return Err(syn::Error::new_spanned(lit, "duplicate sample_size argument"));
+                    }
+                    sample_size = Some(lit);
+                }
+                BenchmarkArg::Warmup(lit) => {
+                    if warmup.is_some() {
+                        return Err(syn::Error::new_spanned(lit, "duplicate warmup argument"));
+                    }
+                    warmup = Some(lit);
+                }
+                BenchmarkArg::Group(lit) => {
+                    if group.is_some() {
+                        return Err(syn::Error::new_spanned(lit, "duplicate group argument"));
+                    }
+                    group = Some(lit);
+                }
+                BenchmarkArg::Name(lit) => {
+                    if name.is_some() {
+                        return Err(syn::Error::new_spanned(lit, "duplicate name argument"));
+                    }
+                    name = Some(lit);
+                }
+                BenchmarkArg::Ignore(reason) => {
+                    if ignore.is_some() {
+                        return Err(syn::Error::new(
+                            proc_macro2::Span::call_site(),
+                            "duplicate ignore argument",
+                        ));
+                    }
+                    ignore = Some(reason);
+                }
             }
         }
 
@@ -140,18 +219,95 @@ impl Parse for BenchmarkArgs {
             ));
         }
 
+        // Validate: the seeded RNG is only handed to per-iteration setup
+        if seeded && (setup.is_none() || !per_iteration) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "seeded requires setup with per_iteration",
+            ));
+        }
+
+        // Validate: only one sampling mode
+        if adaptive && sample_time_ms.is_some() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "adaptive and sample_time_ms are mutually exclusive",
+            ));
+        }
+
+        // Validate: a sample count means nothing when time or precision decides
+        if sample_size.is_some() && (adaptive || sample_time_ms.is_some()) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "sample_size cannot be combined with adaptive or sample_time_ms",
+            ));
+        }
+
         Ok(Self {
             setup,
             teardown,
             per_iteration,
+            seeded,
+            warmup_until_stable,
+            sample_time_ms,
+            adaptive,
+            sample_size,
+            warmup,
+            group,
+            name,
+            ignore,
         })
     }
 }
 
+impl BenchmarkArgs {
+    /// Arguments rendered for the registry's `params` metadata.
+    fn params(&self) -> Vec<String> {
+        let mut params = Vec::new();
+        if let Some(setup) = &self.setup {
+            params.push(format!("setup={}", setup));
+        }
+        if let Some(teardown) = &self.teardown {
+            params.push(format!("teardown={}", teardown));
+        }
+        if self.per_iteration {
+            params.push("per_iteration".to_string());
+        }
+        if self.seeded {
+            params.push("seeded".to_string());
+        }
+        if self.warmup_until_stable {
+            params.push("warmup_until_stable".to_string());
+        }
+        if let Some(ms) = &self.sample_time_ms {
+            params.push(format!("sample_time_ms={}", ms));
+        }
+        if self.adaptive {
+            params.push("adaptive".to_string());
+        }
+        if let Some(n) = &self.sample_size {
+            params.push(format!("sample_size={}", n.base10_digits()));
+        }
+        if let Some(n) = &self.warmup {
+            params.push(format!("warmup={}", n.base10_digits()));
+        }
+        params
+    }
+}
+
 enum BenchmarkArg {
     Setup(Ident),
     Teardown(Ident),
     PerIteration,
+    Seeded,
+    WarmupUntilStable,
+    SampleTimeMs(LitInt),
+    Adaptive,
+    SampleSize(LitInt),
+    Warmup(LitInt),
+    Group(LitStr),
+    Name(LitStr),
+    Ignore(Option<LitStr>),
 }
 
 impl Parse for BenchmarkArg {
@@ -170,9 +326,51 @@ impl Parse for BenchmarkArg {
                 Ok(BenchmarkArg::Teardown(value))
             }
             "per_iteration" => Ok(BenchmarkArg::PerIteration),
+            "seeded" => Ok(BenchmarkArg::Seeded),
+            "warmup_until_stable" => Ok(BenchmarkArg::WarmupUntilStable),
+            "sample_time_ms" => {
+                input.parse::<Token![=]>()?;
+                let value: LitInt = input.parse()?;
+                value.base10_parse::<u64>()?;
+                Ok(BenchmarkArg::SampleTimeMs(value))
+            }
+            "adaptive" => Ok(BenchmarkArg::Adaptive),
+            "sample_size" => {
+                input.parse::<Token![=]>()?;
+                let value: LitInt = input.parse()?;
+                if value.base10_parse::<u32>()? == 0 {
+                    return Err(syn::Error::new_spanned(value, "sample_size must be greater than zero"));
+                }
+                Ok(BenchmarkArg::SampleSize(value))
+            }
+            "warmup" => {
+                input.parse::<Token![=]>()?;
+                let value: LitInt = input.parse()?;
+                value.base10_parse::<u32>()?;
+                Ok(BenchmarkArg::Warmup(value))
+            }
+            "group" => {
+                input.parse::<Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                Ok(BenchmarkArg::Group(value))
+            }
+            "name" => {
+                input.parse::<Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                Ok(BenchmarkArg::Name(value))
+            }
+            "ignore" => {
+                if input.peek(Token![=]) {
+                    input.parse::<Token![=]>()?;
+                    let reason: LitStr = input.parse()?;
+                    Ok(BenchmarkArg::Ignore(Some(reason)))
+                } else {
+                    Ok(BenchmarkArg::Ignore(None))
+                }
+            }
             _ => Err(syn::Error::new_spanned(
                 name,
-                "expected 'setup', 'teardown', or 'per_iteration'",
+                "expected 'setup', 'teardown', 'per_iteration', 'seeded', 'warmup_until_stable', 'sample_time_ms', 'adaptive', 'sample_size', 'warmup', 'group', 'name', or 'ignore'",
             )),
         }
     }
@@ -227,6 +425,28 @@ impl Parse for BenchmarkArg {
 /// }
 /// ```
 ///
+/// # With Seeded Per-Iteration Setup
+///
+/// With `seeded`, the per-iteration setup function takes a
+/// `&mut mobench_sdk::SeededRng`. The generator is seeded from the spec's
+/// `seed` (or a hash of the benchmark name), and the seed is recorded in the
+/// report so a slow run can be reproduced with `cargo mobench run --seed`.
+///
+/// ```ignore
+/// use mobench_sdk::{benchmark, SeededRng};
+///
+/// fn random_vec(rng: &mut SeededRng) -> Vec<u32> {
+///     (0..1000).map(|_| rng.next_u32()).collect()
+/// }
+///
+/// #[benchmark(setup = random_vec, per_iteration, seeded)]
+/// fn sort_benchmark(data: Vec<u32>) {
+///     let mut data = data;
+///     data.sort();
+///     std::hint::black_box(data);
+/// }
+/// ```
+///
 /// # With Setup and Teardown
 ///
 /// ```ignore
@@ -241,6 +461,86 @@ impl Parse for BenchmarkArg {
 /// }
 /// ```
 ///
+/// # Warming Up Until Stable
+///
+/// By default the runner performs exactly `spec.warmup` warmup iterations.
+/// With `warmup_until_stable`, warmup continues until recent warmup samples
+/// have a low coefficient of variation, and the run fails if they never
+/// settle. A policy set explicitly on the spec takes precedence.
+///
+/// ```ignore
+/// use mobench_sdk::benchmark;
+///
+/// #[benchmark(warmup_until_stable)]
+/// fn jit_heavy_benchmark() {
+///     std::hint::black_box(run_workload());
+/// }
+/// ```
+///
+/// # Sampling Modes
+///
+/// By default exactly `spec.iterations` samples are measured. With
+/// `sample_time_ms = N` the runner keeps measuring for `N` milliseconds, and
+/// with `adaptive` it stops once the mean is precise (1% relative standard
+/// error, at least 10 samples, at most 10 seconds). Time budgets fit device
+/// farms that bill by the minute. A mode set explicitly on the spec takes
+/// precedence.
+///
+/// ```ignore
+/// use mobench_sdk::benchmark;
+///
+/// #[benchmark(sample_time_ms = 5000)]
+/// fn parse_large_json() {
+///     std::hint::black_box(parse_fixture());
+/// }
+/// ```
+///
+/// # Default Sample Size
+///
+/// `sample_size = N` and `warmup = N` set this benchmark's iteration and
+/// warmup counts when the spec leaves them at
+/// `mobench_sdk::runner::USE_BENCHMARK_DEFAULT`, which suits suites where some functions need far more samples than others.
+/// Precedence is: explicit CLI flags, then these defaults, then the global
+/// defaults (100 iterations, 10 warmup).
+///
+/// ```ignore
+/// use mobench_sdk::benchmark;
+///
+/// #[benchmark(sample_size = 1000, warmup = 50)]
+/// fn hash_small_input() {
+///     std::hint::black_box(hash_bytes(b"abc"));
+/// }
+/// ```
+///
+/// # Renaming
+///
+/// `name = "..."` registers the benchmark under a different final path
+/// component, e.g. `my_crate::sha256_1mb` instead of `my_crate::bench_sha`.
+///
+/// ```ignore
+/// use mobench_sdk::benchmark;
+///
+/// #[benchmark(name = "sha256_1mb")]
+/// fn bench_sha() {
+///     std::hint::black_box(hash_megabytes(1));
+/// }
+/// ```
+///
+/// # Grouping and Ignoring
+///
+/// `group = "..."` and `ignore` (optionally `ignore = "reason"`) are recorded
+/// in the registry for tooling such as `cargo mobench list --json`. Ignored
+/// benchmarks stay registered and can still be run explicitly by name.
+///
+/// ```ignore
+/// use mobench_sdk::benchmark;
+///
+/// #[benchmark(group = "hashing", ignore = "too slow for CI")]
+/// fn sha256_large() {
+///     std::hint::black_box(hash_megabytes(64));
+/// }
+/// ```
+///
 /// # Function Requirements
 ///
 /// **Without setup:**
@@ -272,6 +572,12 @@ pub fn benchmark(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let fn_name = &input_fn.sig.ident;
     let fn_name_str = fn_name.to_string();
+    // `name = "..."` overrides the registered name; the Rust function keeps its own
+    let registered_name = args
+        .name
+        .as_ref()
+        .map(LitStr::value)
+        .unwrap_or_else(|| fn_name.to_string());
     let vis = &input_fn.vis;
     let sig = &input_fn.sig;
     let block = &input_fn.block;
@@ -363,6 +669,25 @@ pub fn benchmark(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Generate the runner based on configuration
     let runner = generate_runner(fn_name, &args);
 
+    // Registry metadata
+    let group = match &args.group {
+        Some(lit) => quote! { ::std::option::Option::Some(#lit) },
+        None => quote! { ::std::option::Option::None },
+    };
+    let params = args.params();
+    let ignored = args.ignore.is_some();
+    let skip_reason = match &args.ignore {
+        Some(Some(reason)) => quote! { ::std::option::Option::Some(#reason) },
+        _ => quote! { ::std::option::Option::None },
+    };
+    let has_setup = args.setup.is_some();
+    let optional_count = |lit: &Option<LitInt>| match lit {
+        Some(n) => quote! { ::std::option::Option::Some(#n) },
+        None => quote! { ::std::option::Option::None },
+    };
+    let sample_size = optional_count(&args.sample_size);
+    let warmup = optional_count(&args.warmup);
+
     let expanded = quote! {
         // Preserve the original function
         #(#attrs)*
@@ -373,8 +698,15 @@ pub fn benchmark(attr: TokenStream, item: TokenStream) -> TokenStream {
         // Register the function with inventory
         ::inventory::submit! {
             ::mobench_sdk::registry::BenchFunction {
-                name: ::std::concat!(::std::module_path!(), "::", #fn_name_str),
+                name: ::std::concat!(::std::module_path!(), "::", #registered_name),
                 runner: #runner,
+                group: #group,
+                params: &[#(#params),*],
+                ignored: #ignored,
+                skip_reason: #skip_reason,
+                has_setup: #has_setup,
+                sample_size: #sample_size,
+                warmup: #warmup,
             }
         }
     };
@@ -383,10 +715,70 @@ pub fn benchmark(attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 fn generate_runner(fn_name: &Ident, args: &BenchmarkArgs) -> proc_macro2::TokenStream {
+    // Upgrade the default fixed warmup when requested; explicit policies win.
+    let warmup_prelude = if args.warmup_until_stable {
+        quote! {
+            if spec.warmup_policy == ::mobench_sdk::timing::WarmupPolicy::Fixed {
+                spec.warmup_policy = ::mobench_sdk::timing::WarmupPolicy::until_stable();
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Likewise for the sampling mode: only the default fixed count is replaced.
+    let sampling = if let Some(ms) = &args.sample_time_ms {
+        Some(quote! {
+            ::mobench_sdk::timing::SamplingMode::FixedTime(::std::time::Duration::from_millis(#ms))
+        })
+    } else if args.adaptive {
+        Some(quote! { ::mobench_sdk::timing::SamplingMode::adaptive() })
+    } else {
+        None
+    };
+    let sampling_prelude = match sampling {
+        Some(mode) => quote! {
+            if spec.sampling == ::mobench_sdk::timing::SamplingMode::FixedIterations {
+                spec.sampling = #mode;
+            }
+        },
+        None => quote! {},
+    };
+
+    let spec_prelude = if args.warmup_until_stable || args.sample_time_ms.is_some() || args.adaptive {
+        quote! {
+            let mut spec = spec;
+            #warmup_prelude
+            #sampling_prelude
+        }
+    } else {
+        quote! {}
+    };
+
+    if args.seeded {
+        let Some(setup) = &args.setup else {
+            return quote! { compile_error!("seeded requires setup with per_iteration") };
+        };
+        return quote! {
+            |spec: ::mobench_sdk::timing::BenchSpec| -> ::std::result::Result<::mobench_sdk::timing::BenchReport, ::mobench_sdk::timing::TimingError> {
+                #spec_prelude
+                ::mobench_sdk::timing::run_closure_with_seeded_setup_per_iter(
+                    spec,
+                    |rng| #setup(rng),
+                    |input| {
+                        #fn_name(input);
+                        Ok(())
+                    },
+                )
+            }
+        };
+    }
+
     match (&args.setup, &args.teardown, args.per_iteration) {
         // No setup - simple benchmark
         (None, None, _) => quote! {
             |spec: ::mobench_sdk::timing::BenchSpec| -> ::std::result::Result<::mobench_sdk::timing::BenchReport, ::mobench_sdk::timing::TimingError> {
+                #spec_prelude
                 ::mobench_sdk::timing::run_closure(spec, || {
                     #fn_name();
                     Ok(())
@@ -397,6 +789,7 @@ fn generate_runner(fn_name: &Ident, args: &BenchmarkArgs) -> proc_macro2::TokenS
         // Setup only, runs once before all iterations
         (Some(setup), None, false) => quote! {
             |spec: ::mobench_sdk::timing::BenchSpec| -> ::std::result::Result<::mobench_sdk::timing::BenchReport, ::mobench_sdk::timing::TimingError> {
+                #spec_prelude
                 ::mobench_sdk::timing::run_closure_with_setup(
                     spec,
                     || #setup(),
@@ -411,6 +804,7 @@ fn generate_runner(fn_name: &Ident, args: &BenchmarkArgs) -> proc_macro2::TokenS
         // Setup only, per iteration (for mutating benchmarks)
         (Some(setup), None, true) => quote! {
             |spec: ::mobench_sdk::timing::BenchSpec| -> ::std::result::Result<::mobench_sdk::timing::BenchReport, ::mobench_sdk::timing::TimingError> {
+                #spec_prelude
                 ::mobench_sdk::timing::run_closure_with_setup_per_iter(
                     spec,
                     || #setup(),
@@ -425,6 +819,7 @@ fn generate_runner(fn_name: &Ident, args: &BenchmarkArgs) -> proc_macro2::TokenS
         // Setup + teardown (per_iteration with teardown is rejected during parsing)
         (Some(setup), Some(teardown), false) => quote! {
             |spec: ::mobench_sdk::timing::BenchSpec| -> ::std::result::Result<::mobench_sdk::timing::BenchReport, ::mobench_sdk::timing::TimingError> {
+                #spec_prelude
                 ::mobench_sdk::timing::run_closure_with_setup_teardown(
                     spec,
                     || #setup(),
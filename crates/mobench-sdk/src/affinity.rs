@@ -0,0 +1,232 @@
+//! Pinning the benchmark thread to big or little cores.
+//!
+//! big.LITTLE SoCs mix fast and efficient cores. An unpinned benchmark thread
+//! migrates between them, which shows up as bimodal samples. With
+//! [`CorePinning::Big`] or [`CorePinning::Little`], [`pin_current_thread`]
+//! restricts the calling thread with `sched_setaffinity` to the cores with the
+//! highest or lowest maximum frequency, as read from
+//! `/sys/devices/system/cpu/cpu*/cpufreq/cpuinfo_max_freq`.
+//!
+//! Pinning is best effort. It needs the `core-pinning` feature on Linux or
+//! Android, and the kernel may still refuse it. The returned [`Affinity`]
+//! names the cores the thread ended up on, or why it was not pinned.
+//!
+//! ```
+//! use mobench_sdk::affinity::{pin_current_thread, Affinity, CorePinning};
+//!
+//! assert_eq!(pin_current_thread(CorePinning::None), Affinity::Unpinned);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Which cores to pin the benchmark thread to.
+///
+/// Serialized as a lowercase string (`"none"`, `"big"`, `"little"`), the
+/// `pin_cores` value in `bench_spec.json`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CorePinning {
+    /// Leave scheduling to the kernel.
+    #[default]
+    None,
+    /// The cores with the highest maximum frequency.
+    Big,
+    /// The cores with the lowest maximum frequency.
+    Little,
+}
+
+impl CorePinning {
+    /// Whether this leaves the thread unpinned.
+    pub fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+
+    /// The CPUs this pinning selects from `(cpu, max_freq)` pairs.
+    ///
+    /// On a homogeneous SoC every core is both big and little, so pinning
+    /// selects all of them. [`CorePinning::None`] selects nothing.
+    pub fn select(self, max_freqs: &[(usize, u64)]) -> Vec<usize> {
+        let freqs = max_freqs.iter().map(|&(_, freq)| freq);
+        let target = match self {
+            Self::None => return Vec::new(),
+            Self::Big => freqs.max(),
+            Self::Little => freqs.min(),
+        };
+        max_freqs
+            .iter()
+            .filter(|&&(_, freq)| Some(freq) == target)
+            .map(|&(cpu, _)| cpu)
+            .collect()
+    }
+}
+
+impl fmt::Display for CorePinning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Big => "big",
+            Self::Little => "little",
+        })
+    }
+}
+
+impl FromStr for CorePinning {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(Self::None),
+            "big" => Ok(Self::Big),
+            "little" => Ok(Self::Little),
+            other => Err(format!("unknown core pinning '{other}' (expected none, big or little)")),
+        }
+    }
+}
+
+/// The affinity a benchmark ran with, as reported by [`pin_current_thread`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Affinity {
+    /// Pinning was not requested.
+    Unpinned,
+    /// The thread runs only on `cpus`, as read back from the kernel.
+    Pinned {
+        /// The requested pinning.
+        pinning: CorePinning,
+        /// CPU indices in the effective affinity mask.
+        cpus: Vec<usize>,
+    },
+    /// Pinning was requested but could not be applied.
+    Denied {
+        /// The requested pinning.
+        pinning: CorePinning,
+        /// Why the thread was left unpinned.
+        reason: String,
+    },
+}
+
+/// Renders as `big cores 4-7`, `little cores denied: <reason>`, or `unpinned`.
+impl fmt::Display for Affinity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unpinned => f.write_str("unpinned"),
+            Self::Pinned { pinning, cpus } => write!(f, "{pinning} cores {}", format_cpus(cpus)),
+            Self::Denied { pinning, reason } => write!(f, "{pinning} cores denied: {reason}"),
+        }
+    }
+}
+
+/// Compact CPU list with ranges, like `/proc/self/status` (`0-3,6`).
+fn format_cpus(cpus: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Pin the calling thread per `pinning` and report the effective affinity.
+///
+/// The pin stays in place for the rest of the thread's life, so benchmarks
+/// run afterwards on the same thread share it.
+pub fn pin_current_thread(pinning: CorePinning) -> Affinity {
+    if pinning == CorePinning::None {
+        return Affinity::Unpinned;
+    }
+    match apply(pinning) {
+        Ok(cpus) => Affinity::Pinned { pinning, cpus },
+        Err(reason) => Affinity::Denied { pinning, reason },
+    }
+}
+
+#[cfg(all(feature = "core-pinning", any(target_os = "linux", target_os = "android")))]
+fn apply(pinning: CorePinning) -> Result<Vec<usize>, String> {
+    let cpus = pinning.select(&core_max_freqs()?);
+    if cpus.is_empty() {
+        return Err("no cores match".to_string());
+    }
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    // SAFETY: an all-zero cpu_set_t is an empty set; the CPU_* helpers and the
+    // affinity calls only access the set passed by reference, with its size.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in &cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, size, &set) != 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        let mut effective: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, size, &mut effective) != 0 {
+            return Ok(cpus);
+        }
+        Ok((0..libc::CPU_SETSIZE as usize)
+            .filter(|&cpu| libc::CPU_ISSET(cpu, &effective))
+            .collect())
+    }
+}
+
+#[cfg(not(all(feature = "core-pinning", any(target_os = "linux", target_os = "android"))))]
+fn apply(_pinning: CorePinning) -> Result<Vec<usize>, String> {
+    Err("unsupported on this platform or without mobench-sdk's `core-pinning` feature".to_string())
+}
+
+/// `(cpu, cpuinfo_max_freq)` for every CPU that reports a maximum frequency.
+#[cfg(all(feature = "core-pinning", any(target_os = "linux", target_os = "android")))]
+fn core_max_freqs() -> Result<Vec<(usize, u64)>, String> {
+    let root = std::path::Path::new("/sys/devices/system/cpu");
+    let entries = std::fs::read_dir(root).map_err(|err| format!("reading {}: {err}", root.display()))?;
+    let mut freqs: Vec<(usize, u64)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let cpu = entry.file_name().to_str()?.strip_prefix("cpu")?.parse().ok()?;
+            let freq = std::fs::read_to_string(entry.path().join("cpufreq/cpuinfo_max_freq")).ok()?;
+            Some((cpu, freq.trim().parse().ok()?))
+        })
+        .collect();
+    if freqs.is_empty() {
+        return Err("no CPU reports cpuinfo_max_freq".to_string());
+    }
+    freqs.sort_unstable();
+    Ok(freqs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_cores_by_max_frequency() {
+        let soc = [(0, 1_800_000), (1, 1_800_000), (2, 2_400_000), (3, 2_400_000), (4, 3_000_000)];
+        assert_eq!(CorePinning::Big.select(&soc), vec![4]);
+        assert_eq!(CorePinning::Little.select(&soc), vec![0, 1]);
+        assert!(CorePinning::None.select(&soc).is_empty());
+        let uniform = [(0, 2_000_000), (1, 2_000_000)];
+        assert_eq!(CorePinning::Big.select(&uniform), vec![0, 1]);
+
+        assert_eq!(format_cpus(&[0, 1, 2, 3, 6]), "0-3,6");
+        let pinned = Affinity::Pinned {
+            pinning: CorePinning::Big,
+            cpus: vec![4, 5, 6, 7],
+        };
+        assert_eq!(pinned.to_string(), "big cores 4-7");
+        assert_eq!("little".parse(), Ok(CorePinning::Little));
+        assert!("medium".parse::<CorePinning>().is_err());
+        assert_eq!(pin_current_thread(CorePinning::None), Affinity::Unpinned);
+        assert_ne!(pin_current_thread(CorePinning::Big), Affinity::Unpinned);
+    }
+}
@@ -14,6 +14,9 @@
 //! 5. **APK building** - Runs Gradle to build the app APK
 //! 6. **Test APK building** - Builds the androidTest APK for BrowserStack Espresso
 //!
+//! [`AndroidBuilder::package_aab`] additionally packages the app as an Android
+//! App Bundle (`.aab`) for BrowserStack and Play internal testing.
+//!
 //! ## Requirements
 //!
 //! - Android NDK (set `ANDROID_NDK_HOME` environment variable)
@@ -35,6 +38,8 @@
 //!     target: Target::Android,
 //!     profile: BuildProfile::Release,
 //!     incremental: true,
+//!     cache: true,
+//!     cargo_profile: None,
 //! };
 //!
 //! let result = builder.build(&config)?;
@@ -56,12 +61,22 @@
 //! ```
 
 use crate::types::{BenchError, BuildConfig, BuildProfile, BuildResult, Target};
-use super::common::{get_cargo_target_dir, host_lib_path, run_command, validate_project_root};
+use super::common::{
+    BuildCache, StripTool, check_crate_types, check_lib_size_budget, compute_build_fingerprint,
+    get_cargo_target_dir, host_lib_path, measure_native_lib, print_native_lib_sizes, resolve_program,
+    run_command, validate_project_root,
+};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// The Gradle wrapper script in the Android project.
+const GRADLE_WRAPPER: &str = if cfg!(windows) { "gradlew.bat" } else { "gradlew" };
+
+/// The wrapper as run from the Android project directory, for messages.
+const GRADLEW: &str = if cfg!(windows) { "gradlew.bat" } else { "./gradlew" };
+
 /// Android builder that handles the complete build pipeline.
 ///
 /// This builder automates the process of compiling Rust code to Android native
@@ -82,6 +97,8 @@ use std::process::Command;
 ///     target: Target::Android,
 ///     profile: BuildProfile::Release,
 ///     incremental: true,
+///     cache: true,
+///     cargo_profile: None,
 /// };
 ///
 /// let result = builder.build(&config)?;
@@ -100,6 +117,8 @@ pub struct AndroidBuilder {
     crate_dir: Option<PathBuf>,
     /// Whether to run in dry-run mode (print what would be done without making changes)
     dry_run: bool,
+    /// Largest allowed stripped `.so` size in kilobytes, if any
+    max_lib_size_kb: Option<u64>,
 }
 
 impl AndroidBuilder {
@@ -112,12 +131,13 @@ impl AndroidBuilder {
     pub fn new(project_root: impl Into<PathBuf>, crate_name: impl Into<String>) -> Self {
         let root = project_root.into();
         Self {
-            output_dir: root.join("target/mobench"),
+            output_dir: root.join("target").join("mobench"),
             project_root: root,
             crate_name: crate_name.into(),
             verbose: false,
             crate_dir: None,
             dry_run: false,
+            max_lib_size_kb: None,
         }
     }
 
@@ -159,6 +179,15 @@ impl AndroidBuilder {
         self
     }
 
+    /// Fails the build when any ABI's `.so` is larger than `max_kb` kilobytes
+    ///
+    /// The stripped size is checked when `llvm-strip` is available (it is
+    /// what ships in the APK), otherwise the unstripped size.
+    pub fn max_lib_size_kb(mut self, max_kb: Option<u64>) -> Self {
+        self.max_lib_size_kb = max_kb;
+        self
+    }
+
     /// Builds the Android app with the given configuration
     ///
     /// This performs the following steps:
@@ -190,25 +219,39 @@ impl AndroidBuilder {
             println!("  Step 0.5: Ensure Gradle wrapper exists (run 'gradle wrapper' if needed)");
             println!("  Step 1: Build Rust libraries for Android ABIs (arm64-v8a, armeabi-v7a, x86_64)");
             println!("    Command: cargo ndk --target <abi> --platform 24 build {}",
-                if matches!(config.profile, BuildProfile::Release) { "--release" } else { "" });
+                config.cargo_profile_args().join(" "));
+            if config.cache {
+                println!("    Skipped per ABI when the fingerprint in {:?} is unchanged",
+                    BuildCache::new(&self.output_dir).dir());
+            }
             println!("  Step 2: Generate UniFFI Kotlin bindings");
-            println!("    Output: {:?}", android_dir.join("app/src/main/java/uniffi"));
+            println!("    Output: {:?}", android_dir.join("app").join("src").join("main").join("java").join("uniffi"));
             println!("  Step 3: Copy .so files to jniLibs directories");
-            println!("    Destination: {:?}", android_dir.join("app/src/main/jniLibs"));
+            println!("    Destination: {:?}", android_dir.join("app").join("src").join("main").join("jniLibs"));
             println!("  Step 4: Build Android APK with Gradle");
-            println!("    Command: ./gradlew assemble{}", if profile_name == "release" { "Release" } else { "Debug" });
-            println!("    Output: {:?}", android_dir.join(format!("app/build/outputs/apk/{}/app-{}.apk", profile_name, profile_name)));
+            println!("    Command: {GRADLEW} assemble{}", if profile_name == "release" { "Release" } else { "Debug" });
+            println!("    Output: {:?}", apk_output_dir(&android_dir).join(profile_name).join(format!("app-{}.apk", profile_name)));
             println!("  Step 5: Build Android test APK");
-            println!("    Command: ./gradlew assemble{}AndroidTest", if profile_name == "release" { "Release" } else { "Debug" });
+            println!("    Command: {GRADLEW} assemble{}AndroidTest", if profile_name == "release" { "Release" } else { "Debug" });
 
             // Return a placeholder result for dry-run
             return Ok(BuildResult {
                 platform: Target::Android,
-                app_path: android_dir.join(format!("app/build/outputs/apk/{}/app-{}.apk", profile_name, profile_name)),
-                test_suite_path: Some(android_dir.join(format!("app/build/outputs/apk/androidTest/{}/app-{}-androidTest.apk", profile_name, profile_name))),
+                app_path: apk_output_dir(&android_dir).join(profile_name).join(format!("app-{}.apk", profile_name)),
+                test_suite_path: Some(
+                    apk_output_dir(&android_dir)
+                        .join("androidTest")
+                        .join(profile_name)
+                        .join(format!("app-{}-androidTest.apk", profile_name)),
+                ),
+                dsym_path: None,
+                native_libs: Vec::new(),
             });
         }
 
+        // The crate compiles without these, but the app cannot link it
+        check_crate_types(&self.find_crate_dir()?, &["cdylib"])?;
+
         // Step 0: Ensure Android project scaffolding exists
         // Pass project_root and crate_dir for better benchmark function detection
         crate::codegen::ensure_android_project_with_options(
@@ -242,23 +285,48 @@ impl AndroidBuilder {
         let test_suite_path = self.build_test_apk(config)?;
 
         // Step 6: Validate all expected artifacts exist
-        let result = BuildResult {
+        let mut result = BuildResult {
             platform: Target::Android,
             app_path: apk_path,
             test_suite_path: Some(test_suite_path),
+            dsym_path: None,
+            native_libs: Vec::new(),
         };
         self.validate_build_artifacts(&result, config)?;
 
+        // Step 7: Measure native library sizes and enforce the size budget
+        result.native_libs = self.measure_native_libraries()?;
+        if self.verbose {
+            print_native_lib_sizes(&result.native_libs);
+        }
+        if let Some(max_kb) = self.max_lib_size_kb {
+            check_lib_size_budget(&result.native_libs, max_kb)?;
+        }
+
         Ok(result)
     }
 
+    /// Measures the `.so` copied into `jniLibs/` for each ABI
+    fn measure_native_libraries(&self) -> Result<Vec<crate::NativeLibSize>, BenchError> {
+        let jni_libs_dir = self.output_dir.join("android").join("app").join("src").join("main").join("jniLibs");
+        let lib_name = format!("lib{}.so", self.crate_name.replace("-", "_"));
+        let scratch_dir = self.output_dir.join("size-check");
+        let strip = StripTool::android();
+        let mut libs = Vec::new();
+        for abi in ["arm64-v8a", "armeabi-v7a", "x86_64"] {
+            let lib_path = jni_libs_dir.join(abi).join(&lib_name);
+            if lib_path.exists() {
+                libs.push(measure_native_lib(abi, &lib_path, Some(&strip), &scratch_dir)?);
+            }
+        }
+        let _ = fs::remove_dir_all(&scratch_dir);
+        Ok(libs)
+    }
+
     /// Validates that all expected build artifacts exist after a successful build
     fn validate_build_artifacts(&self, result: &BuildResult, config: &BuildConfig) -> Result<(), BenchError> {
         let mut missing = Vec::new();
-        let profile_dir = match config.profile {
-            BuildProfile::Debug => "debug",
-            BuildProfile::Release => "release",
-        };
+        let profile_dir = config.target_profile_dir();
 
         // Check main APK
         if !result.app_path.exists() {
@@ -273,7 +341,7 @@ impl AndroidBuilder {
         }
 
         // Check that at least one native library exists in jniLibs
-        let jni_libs_dir = self.output_dir.join("android/app/src/main/jniLibs");
+        let jni_libs_dir = self.output_dir.join("android").join("app").join("src").join("main").join("jniLibs");
         let lib_name = format!("lib{}.so", self.crate_name.replace("-", "_"));
         let required_abis = ["arm64-v8a", "armeabi-v7a", "x86_64"];
         let mut found_libs = 0;
@@ -395,13 +463,33 @@ impl AndroidBuilder {
 
         // Android ABIs to build for
         let abis = vec!["arm64-v8a", "armeabi-v7a", "x86_64"];
-        let release_flag = if matches!(config.profile, BuildProfile::Release) {
-            "--release"
+        let profile_args = config.cargo_profile_args();
+        let profile_dir = config.target_profile_dir();
+        let cache = BuildCache::new(&self.output_dir);
+        let target_dir = if config.cache {
+            Some(get_cargo_target_dir(&crate_dir)?)
         } else {
-            ""
+            None
         };
+        let lib_name = format!("lib{}.so", self.crate_name.replace("-", "_"));
 
         for abi in abis {
+            let rust_target = android_abi_to_rust_target(abi);
+            let fingerprint = match target_dir {
+                Some(_) => Some(compute_build_fingerprint(&crate_dir, rust_target, profile_dir)?),
+                None => None,
+            };
+
+            if let (Some(target_dir), Some(fingerprint)) = (&target_dir, &fingerprint) {
+                let artifact = target_dir.join(rust_target).join(profile_dir).join(&lib_name);
+                if artifact.exists() && cache.is_fresh(rust_target, profile_dir, fingerprint) {
+                    if self.verbose {
+                        println!("  Cache hit for {} ({}), reusing {}", abi, profile_dir, artifact.display());
+                    }
+                    continue;
+                }
+            }
+
             if self.verbose {
                 println!("  Building for {}", abi);
             }
@@ -414,19 +502,17 @@ impl AndroidBuilder {
                 .arg("24") // minSdk
                 .arg("build");
 
-            // Add release flag if needed
-            if !release_flag.is_empty() {
-                cmd.arg(release_flag);
-            }
+            // Add cargo profile selection (--release / --profile <name>) if needed
+            cmd.args(&profile_args);
 
             // Set working directory
             cmd.current_dir(&crate_dir);
 
             // Execute build
-            let command_hint = if release_flag.is_empty() {
+            let command_hint = if profile_args.is_empty() {
                 format!("cargo ndk --target {} --platform 24 build", abi)
             } else {
-                format!("cargo ndk --target {} --platform 24 build {}", abi, release_flag)
+                format!("cargo ndk --target {} --platform 24 build {}", abi, profile_args.join(" "))
             };
             let output = cmd
                 .output()
@@ -447,17 +533,6 @@ impl AndroidBuilder {
             if !output.status.success() {
                 let stdout = String::from_utf8_lossy(&output.stdout);
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                let profile = if matches!(config.profile, BuildProfile::Release) {
-                    "release"
-                } else {
-                    "debug"
-                };
-                let rust_target = match abi {
-                    "arm64-v8a" => "aarch64-linux-android",
-                    "armeabi-v7a" => "armv7-linux-androideabi",
-                    "x86_64" => "x86_64-linux-android",
-                    _ => abi,
-                };
                 return Err(BenchError::Build(format!(
                     "cargo-ndk build failed for {} ({} profile).\n\n\
                      Command: {}\n\
@@ -471,7 +546,7 @@ impl AndroidBuilder {
                      - Compilation error in Rust code (see output above)\n\
                      - Incompatible native dependencies (some C libraries do not support Android)",
                     abi,
-                    profile,
+                    config.cargo_profile_name(),
                     command_hint,
                     crate_dir.display(),
                     output.status,
@@ -480,6 +555,10 @@ impl AndroidBuilder {
                     rust_target,
                 )));
             }
+
+            if let Some(fingerprint) = &fingerprint {
+                cache.store(rust_target, profile_dir, fingerprint)?;
+            }
         }
 
         Ok(())
@@ -609,14 +688,11 @@ impl AndroidBuilder {
     /// Copies .so files to Android jniLibs directories
     fn copy_native_libraries(&self, config: &BuildConfig) -> Result<(), BenchError> {
         let crate_dir = self.find_crate_dir()?;
-        let profile_dir = match config.profile {
-            BuildProfile::Debug => "debug",
-            BuildProfile::Release => "release",
-        };
+        let profile_dir = config.target_profile_dir();
 
         // Use cargo metadata to find the actual target directory (handles workspaces)
         let target_dir = get_cargo_target_dir(&crate_dir)?;
-        let jni_libs_dir = self.output_dir.join("android/app/src/main/jniLibs");
+        let jni_libs_dir = self.output_dir.join("android").join("app").join("src").join("main").join("jniLibs");
 
         // Create jniLibs directories if they don't exist
         std::fs::create_dir_all(&jni_libs_dir).map_err(|e| {
@@ -763,7 +839,7 @@ impl AndroidBuilder {
     /// If gradlew doesn't exist, this runs `gradle wrapper --gradle-version 8.5`
     /// to generate the wrapper files.
     fn ensure_gradle_wrapper(&self, android_dir: &Path) -> Result<(), BenchError> {
-        let gradlew = android_dir.join("gradlew");
+        let gradlew = android_dir.join(GRADLE_WRAPPER);
 
         // If gradlew already exists, we're good
         if gradlew.exists() {
@@ -773,28 +849,29 @@ impl AndroidBuilder {
         println!("Gradle wrapper not found, generating...");
 
         // Check if gradle is available
-        let gradle_available = Command::new("gradle")
+        let gradle_available = Command::new(resolve_program("gradle"))
             .arg("--version")
             .output()
             .map(|o| o.status.success())
             .unwrap_or(false);
 
         if !gradle_available {
-            return Err(BenchError::Build(
-                "Gradle wrapper (gradlew) not found and 'gradle' command is not available.\n\n\
+            return Err(BenchError::Build(format!(
+                "Gradle wrapper ({GRADLE_WRAPPER}) not found and 'gradle' command is not available.\n\n\
                  The Android project requires Gradle to build. You have two options:\n\n\
                  1. Install Gradle globally and run the build again (it will auto-generate the wrapper):\n\
                     - macOS: brew install gradle\n\
                     - Linux: sudo apt install gradle\n\
+                    - Windows: choco install gradle\n\
                     - Or download from https://gradle.org/install/\n\n\
                  2. Or generate the wrapper manually in the Android project directory:\n\
-                    cd target/mobench/android && gradle wrapper --gradle-version 8.5"
-                    .to_string(),
-            ));
+                    cd {} && gradle wrapper --gradle-version 8.5",
+                android_dir.display()
+            )));
         }
 
         // Run gradle wrapper to generate gradlew
-        let mut cmd = Command::new("gradle");
+        let mut cmd = Command::new(resolve_program("gradle"));
         cmd.arg("wrapper")
             .arg("--gradle-version")
             .arg("8.5")
@@ -864,8 +941,8 @@ impl AndroidBuilder {
         };
 
         // Run Gradle build
-        let mut cmd = Command::new("./gradlew");
-        cmd.arg(gradle_task).current_dir(&android_dir);
+        let mut cmd = gradle_wrapper_command(&android_dir);
+        cmd.arg(gradle_task);
 
         if self.verbose {
             cmd.arg("--info");
@@ -875,12 +952,12 @@ impl AndroidBuilder {
             .output()
             .map_err(|e| BenchError::Build(format!(
                 "Failed to run Gradle wrapper.\n\n\
-                 Command: ./gradlew {}\n\
+                 Command: {GRADLEW} {}\n\
                  Working directory: {}\n\
                  Error: {}\n\n\
                  Tips:\n\
-                 - Ensure ./gradlew is executable (chmod +x ./gradlew)\n\
-                 - Run ./gradlew --version in that directory to verify the wrapper",
+                 - Ensure {GRADLE_WRAPPER} exists and is executable\n\
+                 - Run {GRADLEW} --version in that directory to verify the wrapper",
                 gradle_task,
                 android_dir.display(),
                 e
@@ -891,14 +968,14 @@ impl AndroidBuilder {
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Err(BenchError::Build(format!(
                 "Gradle build failed.\n\n\
-                 Command: ./gradlew {}\n\
+                 Command: {GRADLEW} {}\n\
                  Working directory: {}\n\
                  Exit status: {}\n\n\
                  Stdout:\n{}\n\n\
                  Stderr:\n{}\n\n\
                  Tips:\n\
                  - Re-run with verbose mode to pass --info to Gradle\n\
-                 - Run ./gradlew {} --stacktrace for a full stack trace",
+                 - Run {GRADLEW} {} --stacktrace for a full stack trace",
                 gradle_task,
                 android_dir.display(),
                 output.status,
@@ -914,7 +991,7 @@ impl AndroidBuilder {
             BuildProfile::Release => "release",
         };
 
-        let apk_dir = android_dir.join("app/build/outputs/apk").join(profile_name);
+        let apk_dir = apk_output_dir(&android_dir).join(profile_name);
 
         // Try to find APK - check multiple possible filenames
         // Gradle produces different names depending on signing configuration:
@@ -982,7 +1059,7 @@ impl AndroidBuilder {
             "APK not found in {}.\n\n\
              Gradle task {} reported success but no APK was produced.\n\
              Searched for:\n{}\n\n\
-             Check the build output directory and rerun ./gradlew {} if needed.",
+             Check the build output directory and rerun {GRADLEW} {} if needed.",
             apk_dir.display(),
             gradle_task,
             candidates.iter().map(|c| format!("  - {}", c)).collect::<Vec<_>>().join("\n"),
@@ -1044,8 +1121,8 @@ impl AndroidBuilder {
             BuildProfile::Release => "assembleReleaseAndroidTest",
         };
 
-        let mut cmd = Command::new("./gradlew");
-        cmd.arg(gradle_task).current_dir(&android_dir);
+        let mut cmd = gradle_wrapper_command(&android_dir);
+        cmd.arg(gradle_task);
 
         if self.verbose {
             cmd.arg("--info");
@@ -1055,12 +1132,12 @@ impl AndroidBuilder {
             .output()
             .map_err(|e| BenchError::Build(format!(
                 "Failed to run Gradle wrapper.\n\n\
-                 Command: ./gradlew {}\n\
+                 Command: {GRADLEW} {}\n\
                  Working directory: {}\n\
                  Error: {}\n\n\
                  Tips:\n\
-                 - Ensure ./gradlew is executable (chmod +x ./gradlew)\n\
-                 - Run ./gradlew --version in that directory to verify the wrapper",
+                 - Ensure {GRADLE_WRAPPER} exists and is executable\n\
+                 - Run {GRADLEW} --version in that directory to verify the wrapper",
                 gradle_task,
                 android_dir.display(),
                 e
@@ -1071,14 +1148,14 @@ impl AndroidBuilder {
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Err(BenchError::Build(format!(
                 "Gradle test APK build failed.\n\n\
-                 Command: ./gradlew {}\n\
+                 Command: {GRADLEW} {}\n\
                  Working directory: {}\n\
                  Exit status: {}\n\n\
                  Stdout:\n{}\n\n\
                  Stderr:\n{}\n\n\
                  Tips:\n\
                  - Re-run with verbose mode to pass --info to Gradle\n\
-                 - Run ./gradlew {} --stacktrace for a full stack trace",
+                 - Run {GRADLEW} {} --stacktrace for a full stack trace",
                 gradle_task,
                 android_dir.display(),
                 output.status,
@@ -1093,8 +1170,8 @@ impl AndroidBuilder {
             BuildProfile::Release => "release",
         };
 
-        let test_apk_dir = android_dir
-            .join("app/build/outputs/apk/androidTest")
+        let test_apk_dir = apk_output_dir(&android_dir)
+            .join("androidTest")
             .join(profile_name);
 
         // Find the test APK - use similar logic to main APK
@@ -1139,7 +1216,7 @@ impl AndroidBuilder {
             "Android test APK not found in {}.\n\n\
              Gradle task {} reported success but no test APK was produced.\n\
              Expected: app-{}-androidTest.apk\n\n\
-             Check app/build/outputs/apk/androidTest/{} and rerun ./gradlew {} if needed.",
+             Check app/build/outputs/apk/androidTest/{} and rerun {GRADLEW} {} if needed.",
             apk_dir.display(),
             gradle_task,
             profile_name,
@@ -1149,6 +1226,212 @@ impl AndroidBuilder {
     }
 }
 
+impl AndroidBuilder {
+    /// Packages the Android app as an Android App Bundle (`.aab`)
+    ///
+    /// This requires the Android project to have been generated and built
+    /// first with `build()`, so the native libraries are already in place.
+    /// Runs `./gradlew bundle<Variant>` (e.g. `bundleRelease`) and returns the
+    /// path to the produced bundle.
+    ///
+    /// # Arguments
+    ///
+    /// * `variant` - The Gradle build variant to bundle (e.g., "release")
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PathBuf)` - Path to the generated `.aab` file
+    /// * `Err(BenchError)` - If the project cannot be bundled or Gradle fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use mobench_sdk::builders::AndroidBuilder;
+    ///
+    /// let builder = AndroidBuilder::new(".", "bench-mobile");
+    /// let aab_path = builder.package_aab("release")?;
+    /// println!("AAB created at: {:?}", aab_path);
+    /// # Ok::<(), mobench_sdk::BenchError>(())
+    /// ```
+    pub fn package_aab(&self, variant: &str) -> Result<PathBuf, BenchError> {
+        let android_dir = self.output_dir.join("android");
+        if !android_dir.exists() {
+            return Err(BenchError::Build(format!(
+                "Android project not found at {}.\n\n\
+                 Run `cargo mobench build --target android` first or check --output-dir.",
+                android_dir.display()
+            )));
+        }
+
+        self.check_bundle_support(&android_dir)?;
+        self.ensure_local_properties(&android_dir)?;
+        self.ensure_gradle_wrapper(&android_dir)?;
+
+        let gradle_task = bundle_task(variant);
+        println!("Bundling {} variant as AAB...", variant);
+
+        let mut cmd = gradle_wrapper_command(&android_dir);
+        cmd.arg(&gradle_task);
+        if self.verbose {
+            cmd.arg("--info");
+            println!("  Running: {GRADLEW} {}", gradle_task);
+        }
+
+        let output = cmd.output().map_err(|e| {
+            BenchError::Build(format!(
+                "Failed to run Gradle wrapper.\n\n\
+                 Command: {GRADLEW} {}\n\
+                 Working directory: {}\n\
+                 Error: {}\n\n\
+                 Tips:\n\
+                 - Ensure {GRADLE_WRAPPER} exists and is executable\n\
+                 - Run {GRADLEW} --version in that directory to verify the wrapper",
+                gradle_task,
+                android_dir.display(),
+                e
+            ))
+        })?;
+
+        if !output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains(&format!("'{}' not found", gradle_task)) {
+                return Err(BenchError::Build(format!(
+                    "Gradle project at {} has no {} task.\n\n\
+                     Check that the '{}' build variant exists in app/build.gradle and that the\n\
+                     Android Gradle Plugin supports App Bundles (3.2 or newer).",
+                    android_dir.display(),
+                    gradle_task,
+                    variant
+                )));
+            }
+            return Err(BenchError::Build(format!(
+                "Gradle bundle failed.\n\n\
+                 Command: {GRADLEW} {}\n\
+                 Working directory: {}\n\
+                 Exit status: {}\n\n\
+                 Stdout:\n{}\n\n\
+                 Stderr:\n{}\n\n\
+                 Tips:\n\
+                 - Re-run with verbose mode to pass --info to Gradle\n\
+                 - Run {GRADLEW} {} --stacktrace for a full stack trace",
+                gradle_task,
+                android_dir.display(),
+                output.status,
+                stdout,
+                stderr,
+                gradle_task,
+            )));
+        }
+
+        let bundle_dir = android_dir.join("app").join("build").join("outputs").join("bundle").join(variant);
+        let aab_path = find_aab(&bundle_dir, variant).ok_or_else(|| {
+            BenchError::Build(format!(
+                "AAB not found in {}.\n\n\
+                 Gradle task {} reported success but no .aab was produced.\n\
+                 Check the build output directory and rerun {GRADLEW} {} if needed.",
+                bundle_dir.display(),
+                gradle_task,
+                gradle_task
+            ))
+        })?;
+
+        if self.verbose {
+            println!("  Found AAB: {}", aab_path.display());
+        }
+        Ok(aab_path)
+    }
+
+    /// Verifies that the app module can be packaged as an App Bundle
+    ///
+    /// Only application modules (`com.android.application`) can produce an
+    /// `.aab`; library modules and projects without an `app` module cannot.
+    fn check_bundle_support(&self, android_dir: &Path) -> Result<(), BenchError> {
+        let candidates = [
+            android_dir.join("app").join("build.gradle"),
+            android_dir.join("app").join("build.gradle.kts"),
+        ];
+        let Some(build_file) = candidates.iter().find(|path| path.exists()) else {
+            return Err(BenchError::Build(format!(
+                "No app module found in {}.\n\n\
+                 Expected app/build.gradle or app/build.gradle.kts. App Bundles are built from the app module.",
+                android_dir.display()
+            )));
+        };
+
+        let contents = fs::read_to_string(build_file).map_err(|e| {
+            BenchError::Build(format!(
+                "Failed to read {}: {}",
+                build_file.display(),
+                e
+            ))
+        })?;
+        if !supports_bundling(&contents) {
+            return Err(BenchError::Build(format!(
+                "{} does not apply the com.android.application plugin.\n\n\
+                 Only application modules can be packaged as an Android App Bundle (.aab).",
+                build_file.display()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A command running the Gradle wrapper in `android_dir`.
+///
+/// The wrapper is spawned by absolute path because Windows resolves a relative
+/// program against the parent's working directory, not `current_dir`.
+fn gradle_wrapper_command(android_dir: &Path) -> Command {
+    let wrapper = android_dir.join(GRADLE_WRAPPER);
+    let mut cmd = Command::new(std::path::absolute(&wrapper).unwrap_or(wrapper));
+    cmd.current_dir(android_dir);
+    cmd
+}
+
+/// Gradle's APK output directory, `app/build/outputs/apk`.
+fn apk_output_dir(android_dir: &Path) -> PathBuf {
+    android_dir.join("app").join("build").join("outputs").join("apk")
+}
+
+/// Returns the Gradle task that bundles `variant` (e.g. "release" -> "bundleRelease").
+fn bundle_task(variant: &str) -> String {
+    let mut chars = variant.chars();
+    match chars.next() {
+        Some(first) => format!("bundle{}{}", first.to_uppercase(), chars.as_str()),
+        None => "bundle".to_string(),
+    }
+}
+
+/// Whether a module build script applies the Android application plugin.
+fn supports_bundling(build_script: &str) -> bool {
+    build_script.contains("com.android.application")
+}
+
+/// Finds the `.aab` produced for `variant`, preferring Gradle's default name.
+fn find_aab(bundle_dir: &Path, variant: &str) -> Option<PathBuf> {
+    let preferred = bundle_dir.join(format!("app-{}.aab", variant));
+    if preferred.exists() {
+        return Some(preferred);
+    }
+    let mut bundles: Vec<PathBuf> = fs::read_dir(bundle_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "aab"))
+        .collect();
+    bundles.sort();
+    bundles.into_iter().next()
+}
+
+/// Maps an Android ABI name to the Rust target triple cargo-ndk builds for it.
+fn android_abi_to_rust_target(abi: &str) -> &str {
+    match abi {
+        "arm64-v8a" => "aarch64-linux-android",
+        "armeabi-v7a" => "armv7-linux-androideabi",
+        "x86_64" => "x86_64-linux-android",
+        _ => abi,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1176,6 +1459,43 @@ mod tests {
         assert_eq!(builder.output_dir, PathBuf::from("/custom/output"));
     }
 
+    #[test]
+    fn test_bundle_task_name() {
+        assert_eq!(bundle_task("release"), "bundleRelease");
+        assert_eq!(bundle_task("debug"), "bundleDebug");
+    }
+
+    #[test]
+    fn test_gradle_wrapper_command() {
+        let cmd = gradle_wrapper_command(Path::new("target/mobench/android"));
+        let program = Path::new(cmd.get_program());
+        assert!(program.is_absolute());
+        assert!(program.ends_with(Path::new("target/mobench/android").join(GRADLE_WRAPPER)));
+        assert_eq!(cmd.get_current_dir(), Some(Path::new("target/mobench/android")));
+    }
+
+    #[test]
+    fn test_supports_bundling() {
+        assert!(supports_bundling("apply plugin: \"com.android.application\"\n"));
+        assert!(supports_bundling("plugins {\n    id(\"com.android.application\")\n}\n"));
+        assert!(!supports_bundling("apply plugin: \"com.android.library\"\n"));
+    }
+
+    #[test]
+    fn test_find_aab() {
+        let temp_dir = std::env::temp_dir().join("mobench-test-find-aab");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        assert_eq!(find_aab(&temp_dir, "release"), None);
+        std::fs::write(temp_dir.join("custom-name.aab"), b"").unwrap();
+        assert_eq!(find_aab(&temp_dir, "release"), Some(temp_dir.join("custom-name.aab")));
+        std::fs::write(temp_dir.join("app-release.aab"), b"").unwrap();
+        assert_eq!(find_aab(&temp_dir, "release"), Some(temp_dir.join("app-release.aab")));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
     #[test]
     fn test_parse_output_metadata_unsigned() {
         let builder = AndroidBuilder::new("/tmp/test-project", "test-bench-mobile");
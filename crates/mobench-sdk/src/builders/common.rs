@@ -8,7 +8,13 @@
 //! - **Workspace-aware target detection** - Correctly handles Cargo workspaces where
 //!   the target directory is at the workspace root
 //! - **Host library resolution** - Finds compiled libraries for UniFFI binding generation
+//! - **Crate type check** - Fails early when the benchmark crate does not build
+//!   the `cdylib`/`staticlib` the mobile build links
 //! - **Consistent error handling** - All errors include actionable fix suggestions
+//! - **Build fingerprint cache** - Skips Rust compiles when sources, lockfile,
+//!   target triple and profile are unchanged
+//! - **Library size budgets** - Measures stripped and unstripped native library
+//!   sizes and enforces `max_lib_size_kb`
 //!
 //! ## Error Messages
 //!
@@ -21,7 +27,7 @@ use std::env;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::types::BenchError;
+use crate::types::{BenchError, NativeLibSize};
 
 /// Validates that the project root is a valid directory for building.
 ///
@@ -57,7 +63,7 @@ pub fn validate_project_root(project_root: &Path, crate_name: &str) -> Result<()
 
     // Check for Cargo.toml in project root or standard crate locations
     let root_cargo = project_root.join("Cargo.toml");
-    let bench_mobile_cargo = project_root.join("bench-mobile/Cargo.toml");
+    let bench_mobile_cargo = project_root.join("bench-mobile").join("Cargo.toml");
     let crates_cargo = project_root.join(format!("crates/{}/Cargo.toml", crate_name));
 
     if !root_cargo.exists() && !bench_mobile_cargo.exists() && !crates_cargo.exists() {
@@ -77,6 +83,76 @@ pub fn validate_project_root(project_root: &Path, crate_name: &str) -> Result<()
     Ok(())
 }
 
+/// Checks that the benchmark crate's `[lib] crate-type` includes every type in `required`.
+///
+/// Without `cdylib` or `staticlib` the crate still compiles, and the build
+/// only fails later in cargo-ndk, UniFFI or xcodebuild with errors that do not
+/// point at the manifest. A `Cargo.toml` that cannot be read or parsed is left
+/// for cargo to report.
+///
+/// # Arguments
+/// * `crate_dir` - Path to the benchmark crate directory
+/// * `required` - Crate types the platform build links, e.g. `["cdylib"]`
+///
+/// # Returns
+/// `Ok(())` if all types are declared, or a `BenchError` naming the line to add.
+pub fn check_crate_types(crate_dir: &Path, required: &[&str]) -> Result<(), BenchError> {
+    let manifest_path = crate_dir.join("Cargo.toml");
+    let Ok(manifest) = std::fs::read_to_string(&manifest_path) else {
+        return Ok(());
+    };
+    match crate_type_fix(&manifest, required) {
+        Some((missing, line)) => Err(BenchError::Build(format!(
+            "The benchmark crate does not build a {} library.\n\n\
+             Manifest: {}\n\n\
+             mobench links the crate into the mobile app, so it needs these crate types.\n\
+             Add this to the crate's Cargo.toml:\n\n\
+             [lib]\n\
+             {}",
+            missing.join(" or "),
+            manifest_path.display(),
+            line
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The missing crate types and the `crate-type = [...]` line that adds them,
+/// keeping any types the manifest already declares. `None` when nothing is
+/// missing or the manifest does not parse.
+fn crate_type_fix<'a>(manifest: &str, required: &[&'a str]) -> Option<(Vec<&'a str>, String)> {
+    let table: toml::Table = manifest.parse().ok()?;
+    let declared: Vec<&str> = table
+        .get("lib")
+        .and_then(|lib| lib.get("crate-type"))
+        .and_then(|types| types.as_array())
+        .map(|types| types.iter().filter_map(|ty| ty.as_str()).collect())
+        .unwrap_or_default();
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|ty| !declared.contains(ty))
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+    let mut types: Vec<&str> = ["cdylib", "staticlib"]
+        .into_iter()
+        .filter(|ty| required.contains(ty) || declared.contains(ty))
+        .collect();
+    for ty in &declared {
+        if !types.contains(ty) {
+            types.push(ty);
+        }
+    }
+    // Keep the default rlib so host tests and other crates can still depend on it
+    if declared.is_empty() {
+        types.push("lib");
+    }
+    let quoted: Vec<String> = types.iter().map(|ty| format!("\"{}\"", ty)).collect();
+    Some((missing, format!("crate-type = [{}]", quoted.join(", "))))
+}
+
 /// Detects the actual Cargo target directory using `cargo metadata`.
 ///
 /// This correctly handles Cargo workspaces where the target directory
@@ -246,6 +322,34 @@ pub fn run_command(mut cmd: Command, description: &str) -> Result<(), BenchError
     Ok(())
 }
 
+/// Resolves a tool name to the program to spawn for it.
+///
+/// `Command` only appends `.exe` when searching `PATH` on Windows, so tools
+/// shipped as batch scripts (such as `gradle.bat`) are not found by their bare
+/// name. On Windows this searches `PATH` with each `PATHEXT` extension, like
+/// `where` does. Elsewhere, and when nothing matches, the bare name is returned
+/// and the spawn reports the missing tool.
+pub fn resolve_program(program: &str) -> PathBuf {
+    if cfg!(windows) {
+        let pathext = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+        let extensions: Vec<&str> = pathext.split(';').filter(|ext| !ext.is_empty()).collect();
+        if let Some(found) = env::var_os("PATH").and_then(|path| find_on_path(program, &path, &extensions)) {
+            return found;
+        }
+    }
+    PathBuf::from(program)
+}
+
+/// The first `<dir>/<program><ext>` that exists across the `PATH` entries in `path`.
+fn find_on_path(program: &str, path: &std::ffi::OsStr, extensions: &[&str]) -> Option<PathBuf> {
+    env::split_paths(path).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{program}{}", ext.to_ascii_lowercase())))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
 /// Reads the package name from a Cargo.toml file.
 ///
 /// This function parses the `[package]` section of a Cargo.toml and extracts
@@ -304,6 +408,161 @@ pub fn read_package_name(cargo_toml_path: &Path) -> Option<String> {
     None
 }
 
+/// Name of the directory (under the mobench output directory) holding build fingerprints.
+pub const BUILD_CACHE_DIR: &str = ".cache";
+
+/// Content-hash cache used to skip Rust compiles whose inputs have not changed.
+///
+/// A fingerprint covers every file under the crate's `src/` directory, its
+/// `Cargo.toml`, `build.rs` (if any), the nearest `Cargo.lock`, the target
+/// triple and the profile name. Fingerprints are stored as one file per
+/// `(target triple, profile)` pair under `{output_dir}/.cache`, so switching
+/// profiles never reuses artifacts from another profile.
+#[derive(Debug, Clone)]
+pub struct BuildCache {
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    /// Creates a cache rooted at `{output_dir}/.cache`.
+    pub fn new(output_dir: &Path) -> Self {
+        Self {
+            dir: output_dir.join(BUILD_CACHE_DIR),
+        }
+    }
+
+    /// Returns the directory fingerprints are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, target_triple: &str, profile: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}-{}.fingerprint", target_triple, profile))
+    }
+
+    /// Returns `true` if the stored fingerprint for this triple/profile matches.
+    pub fn is_fresh(&self, target_triple: &str, profile: &str, fingerprint: &str) -> bool {
+        std::fs::read_to_string(self.entry_path(target_triple, profile))
+            .map(|stored| stored.trim() == fingerprint)
+            .unwrap_or(false)
+    }
+
+    /// Records the fingerprint of a successful build.
+    pub fn store(&self, target_triple: &str, profile: &str, fingerprint: &str) -> Result<(), BenchError> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| {
+            BenchError::Build(format!(
+                "Failed to create build cache directory at {}: {}",
+                self.dir.display(),
+                e
+            ))
+        })?;
+        let path = self.entry_path(target_triple, profile);
+        std::fs::write(&path, fingerprint).map_err(|e| {
+            BenchError::Build(format!(
+                "Failed to write build fingerprint to {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Computes the content fingerprint of a crate build for the given triple and profile.
+///
+/// The hash is a 64-bit FNV-1a over the relative path and contents of each
+/// input file (in sorted order), so it is stable across runs and toolchains.
+pub fn compute_build_fingerprint(
+    crate_dir: &Path,
+    target_triple: &str,
+    profile: &str,
+) -> Result<String, BenchError> {
+    let mut files = Vec::new();
+    collect_files(&crate_dir.join("src"), &mut files)?;
+    for extra in ["Cargo.toml", "build.rs"] {
+        let path = crate_dir.join(extra);
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    if let Some(lockfile) = find_cargo_lock(crate_dir) {
+        files.push(lockfile);
+    }
+    files.sort();
+
+    let mut hash = Fnv1a::new();
+    hash.write(target_triple.as_bytes());
+    hash.write(&[0]);
+    hash.write(profile.as_bytes());
+    hash.write(&[0]);
+    for file in &files {
+        let contents = std::fs::read(file).map_err(|e| {
+            BenchError::Build(format!(
+                "Failed to read {} while computing build fingerprint: {}",
+                file.display(),
+                e
+            ))
+        })?;
+        let relative = file.strip_prefix(crate_dir).unwrap_or(file);
+        hash.write(relative.to_string_lossy().as_bytes());
+        hash.write(&[0]);
+        hash.write(&(contents.len() as u64).to_le_bytes());
+        hash.write(&contents);
+    }
+
+    Ok(format!("{:016x}", hash.finish()))
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), BenchError> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        BenchError::Build(format!(
+            "Failed to read {} while computing build fingerprint: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Finds the `Cargo.lock` governing a crate (its own, or the workspace root's).
+fn find_cargo_lock(crate_dir: &Path) -> Option<PathBuf> {
+    crate_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())
+}
+
+/// Minimal 64-bit FNV-1a hasher; unlike `DefaultHasher` its output is stable.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
 /// Embeds a bench spec JSON file into the Android assets and iOS bundle resources.
 ///
 /// This function writes a `bench_spec.json` file to the appropriate location for
@@ -333,7 +592,7 @@ pub fn embed_bench_spec<S: serde::Serialize>(output_dir: &Path, spec: &S) -> Res
     })?;
 
     // Android: Write to assets directory
-    let android_assets_dir = output_dir.join("android/app/src/main/assets");
+    let android_assets_dir = output_dir.join("android").join("app").join("src").join("main").join("assets");
     if output_dir.join("android").exists() {
         std::fs::create_dir_all(&android_assets_dir).map_err(|e| {
             BenchError::Build(format!(
@@ -353,8 +612,8 @@ pub fn embed_bench_spec<S: serde::Serialize>(output_dir: &Path, spec: &S) -> Res
     }
 
     // iOS: Write to Resources directory in the Xcode project
-    let ios_resources_dir = output_dir.join("ios/BenchRunner/BenchRunner/Resources");
-    if output_dir.join("ios/BenchRunner").exists() {
+    let ios_resources_dir = output_dir.join("ios").join("BenchRunner").join("BenchRunner").join("Resources");
+    if output_dir.join("ios").join("BenchRunner").exists() {
         std::fs::create_dir_all(&ios_resources_dir).map_err(|e| {
             BenchError::Build(format!(
                 "Failed to create iOS Resources directory at {}: {}",
@@ -387,6 +646,122 @@ pub struct EmbeddedBenchSpec {
     pub iterations: u32,
     /// Number of warmup iterations
     pub warmup: u32,
+    /// Sampling mode, when it differs from fixed iterations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<crate::SamplingMode>,
+    /// Warmup policy, when it differs from a fixed warmup count
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_policy: Option<crate::WarmupPolicy>,
+    /// Every function to run, in order, when the build runs a suite
+    ///
+    /// Empty for a single-function run; otherwise `function` is the first entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<String>,
+    /// Seed for seeded per-iteration setup, when set with `--seed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Wall-clock budget per benchmark, when set with `--per-benchmark-timeout`
+    ///
+    /// The runner stops a benchmark that runs out of it and reports the
+    /// samples measured so far with `timed_out` set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_benchmark_timeout_secs: Option<u64>,
+    /// Longest a single iteration may take, set with `--per-iteration-timeout`
+    ///
+    /// The runner fails the benchmark with a timeout error when the timed
+    /// part of an iteration runs longer (see [`crate::timing`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_iteration_timeout_ms: Option<u64>,
+    /// Measured samples to discard before the report, set with `--drop-first-n`
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub drop_first_n: u32,
+    /// Record warmup durations in the reports, set with `--capture-warmup`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub capture_warmup: bool,
+    /// Also measure activity startup time (`startup:cold`/`startup:warm`)
+    ///
+    /// Android only; set with `--startup-metrics`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub startup_metrics: bool,
+    /// Cores to pin the benchmark thread to, set with `--pin-cores`
+    ///
+    /// Android only; see [`crate::affinity`].
+    #[serde(default, skip_serializing_if = "crate::affinity::CorePinning::is_none")]
+    pub pin_cores: crate::affinity::CorePinning,
+    /// Run a suite round-robin, one sample per function per round, set with
+    /// `--sample-interleave`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interleave: bool,
+    /// Environment variables the runner sets before invoking the benchmarks,
+    /// from `[env]` in `mobench.toml` and `--env KEY=VALUE`
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, String>,
+}
+
+fn is_zero(count: &u32) -> bool {
+    *count == 0
+}
+
+impl EmbeddedBenchSpec {
+    /// Returns a short, stable hash of the spec.
+    ///
+    /// The hash is a 64-bit FNV-1a digest of the function name, iterations,
+    /// warmup and (when set) sampling mode, warmup policy, suite, seed,
+    /// per-benchmark and per-iteration timeouts, dropped samples, warmup
+    /// capture, startup metrics, core pinning, interleaving and environment,
+    /// rendered as 16 hex characters. It only needs to be stable
+    /// across hosts and runners, not cryptographically strong: the mobile
+    /// runners echo it back so a report can be matched to the build it came
+    /// from.
+    pub fn spec_hash(&self) -> String {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut canonical = format!("{}\n{}\n{}", self.function, self.iterations, self.warmup);
+        if let Some(sampling) = &self.sampling {
+            canonical.push('\n');
+            canonical.push_str(&serde_json::to_string(sampling).unwrap_or_default());
+        }
+        if let Some(policy) = &self.warmup_policy {
+            canonical.push_str("\nwarmup_policy=");
+            canonical.push_str(&serde_json::to_string(policy).unwrap_or_default());
+        }
+        if !self.functions.is_empty() {
+            canonical.push('\n');
+            canonical.push_str(&self.functions.join(","));
+        }
+        if let Some(seed) = self.seed {
+            canonical.push_str(&format!("\nseed={seed}"));
+        }
+        if let Some(secs) = self.per_benchmark_timeout_secs {
+            canonical.push_str(&format!("\ntimeout={secs}"));
+        }
+        if let Some(ms) = self.per_iteration_timeout_ms {
+            canonical.push_str(&format!("\niteration_timeout_ms={ms}"));
+        }
+        if self.drop_first_n > 0 {
+            canonical.push_str(&format!("\ndrop_first_n={}", self.drop_first_n));
+        }
+        if self.capture_warmup {
+            canonical.push_str("\ncapture_warmup");
+        }
+        if self.startup_metrics {
+            canonical.push_str("\nstartup_metrics");
+        }
+        if !self.pin_cores.is_none() {
+            canonical.push_str(&format!("\npin_cores={}", self.pin_cores));
+        }
+        if self.interleave {
+            canonical.push_str("\ninterleave");
+        }
+        for (key, value) in &self.env {
+            canonical.push_str(&format!("\nenv:{key}={value}"));
+        }
+        let hash = canonical.bytes().fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+        format!("{:016x}", hash)
+    }
 }
 
 /// Build metadata for artifact correlation and traceability.
@@ -397,6 +772,9 @@ pub struct EmbeddedBenchSpec {
 pub struct BenchMeta {
     /// Benchmark specification that was used
     pub spec: EmbeddedBenchSpec,
+    /// Hash of `spec` (see [`EmbeddedBenchSpec::spec_hash`])
+    #[serde(default)]
+    pub spec_hash: String,
     /// Git commit hash (if in a git repository)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commit_hash: Option<String>,
@@ -423,6 +801,47 @@ pub struct BenchMeta {
     pub host_os: String,
 }
 
+impl BenchMeta {
+    /// Compares the build metadata echoed in a device report against this
+    /// (local) metadata.
+    ///
+    /// The mobile runners copy `commit_hash` and `spec_hash` from the bundled
+    /// `bench_meta.json` into a `build_meta` object in their
+    /// `BENCH_REPORT_JSON` output. Returns one description per mismatching
+    /// field; an empty list means the report matches, or that the report
+    /// carries no build metadata to compare (runners built before the echo
+    /// was added).
+    pub fn mismatches(&self, report: &serde_json::Value) -> Vec<String> {
+        let Some(echoed) = report.get("build_meta") else {
+            return Vec::new();
+        };
+        let mut mismatches = Vec::new();
+
+        let device_spec_hash = echoed.get("spec_hash").and_then(|v| v.as_str());
+        if !self.spec_hash.is_empty()
+            && let Some(device) = device_spec_hash
+            && device != self.spec_hash
+        {
+            mismatches.push(format!(
+                "spec hash differs (local {}, device {})",
+                self.spec_hash, device
+            ));
+        }
+
+        let device_commit = echoed.get("commit_hash").and_then(|v| v.as_str());
+        if let (Some(local), Some(device)) = (self.commit_hash.as_deref(), device_commit)
+            && local != device
+        {
+            mismatches.push(format!(
+                "git commit differs (local {}, device {})",
+                local, device
+            ));
+        }
+
+        mismatches
+    }
+}
+
 /// Gets the current git commit hash (short form).
 pub fn get_git_commit() -> Option<String> {
     let output = Command::new("git")
@@ -516,6 +935,7 @@ pub fn create_bench_meta(spec: &EmbeddedBenchSpec, target: &str, profile: &str)
 
     BenchMeta {
         spec: spec.clone(),
+        spec_hash: spec.spec_hash(),
         commit_hash: get_git_commit(),
         branch: get_git_branch(),
         dirty: is_git_dirty(),
@@ -571,7 +991,7 @@ fn is_leap_year(year: i32) -> bool {
 /// Embeds build metadata (bench_meta.json) alongside bench_spec.json in mobile app bundles.
 ///
 /// This function creates a `bench_meta.json` file that contains:
-/// - The benchmark specification
+/// - The benchmark specification and its hash
 /// - Git commit hash and branch (if available)
 /// - Build timestamp
 /// - Target platform and profile
@@ -594,7 +1014,7 @@ pub fn embed_bench_meta(
     })?;
 
     // Android: Write to assets directory
-    let android_assets_dir = output_dir.join("android/app/src/main/assets");
+    let android_assets_dir = output_dir.join("android").join("app").join("src").join("main").join("assets");
     if output_dir.join("android").exists() {
         std::fs::create_dir_all(&android_assets_dir).map_err(|e| {
             BenchError::Build(format!(
@@ -614,8 +1034,8 @@ pub fn embed_bench_meta(
     }
 
     // iOS: Write to Resources directory in the Xcode project
-    let ios_resources_dir = output_dir.join("ios/BenchRunner/BenchRunner/Resources");
-    if output_dir.join("ios/BenchRunner").exists() {
+    let ios_resources_dir = output_dir.join("ios").join("BenchRunner").join("BenchRunner").join("Resources");
+    if output_dir.join("ios").join("BenchRunner").exists() {
         std::fs::create_dir_all(&ios_resources_dir).map_err(|e| {
             BenchError::Build(format!(
                 "Failed to create iOS Resources directory at {}: {}",
@@ -636,6 +1056,139 @@ pub fn embed_bench_meta(
     Ok(())
 }
 
+/// A `strip` invocation used to measure the stripped size of a library.
+///
+/// The library is never modified; a stripped copy is written to a scratch
+/// file, measured, and removed.
+#[derive(Debug, Clone)]
+pub struct StripTool {
+    program: PathBuf,
+    args: Vec<&'static str>,
+}
+
+impl StripTool {
+    /// The NDK's `llvm-strip` (from `ANDROID_NDK_HOME`), or `llvm-strip` on PATH.
+    pub fn android() -> Self {
+        let ndk_strip = env::var_os("ANDROID_NDK_HOME").and_then(|ndk| {
+            let prebuilt = PathBuf::from(ndk).join("toolchains").join("llvm").join("prebuilt");
+            let strip = format!("llvm-strip{}", env::consts::EXE_SUFFIX);
+            std::fs::read_dir(prebuilt)
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path().join("bin").join(&strip))
+                .find(|path| path.exists())
+        });
+        Self {
+            program: ndk_strip.unwrap_or_else(|| PathBuf::from("llvm-strip")),
+            args: vec!["--strip-unneeded"],
+        }
+    }
+
+    /// Xcode's `strip`, removing debug and local symbols.
+    pub fn apple() -> Self {
+        Self {
+            program: PathBuf::from("xcrun"),
+            args: vec!["strip", "-S", "-x"],
+        }
+    }
+
+    /// Size of `lib` after stripping, or `None` if the tool is unavailable or fails.
+    fn stripped_size(&self, lib: &Path, scratch_dir: &Path) -> Option<u64> {
+        std::fs::create_dir_all(scratch_dir).ok()?;
+        let scratch = scratch_dir.join(lib.file_name()?);
+        let status = Command::new(&self.program)
+            .args(&self.args)
+            .arg("-o")
+            .arg(&scratch)
+            .arg(lib)
+            .output()
+            .ok()?
+            .status;
+        let size = status
+            .success()
+            .then(|| std::fs::metadata(&scratch).ok().map(|meta| meta.len()))
+            .flatten();
+        let _ = std::fs::remove_file(&scratch);
+        size
+    }
+}
+
+/// Measures a built native library, plus a stripped copy when `strip` is given.
+///
+/// `scratch_dir` holds the temporary stripped copy.
+pub fn measure_native_lib(
+    slice: &str,
+    path: &Path,
+    strip: Option<&StripTool>,
+    scratch_dir: &Path,
+) -> Result<NativeLibSize, BenchError> {
+    let unstripped_bytes = std::fs::metadata(path)
+        .map_err(|e| {
+            BenchError::Build(format!(
+                "Failed to read native library size at {}: {}",
+                path.display(),
+                e
+            ))
+        })?
+        .len();
+    Ok(NativeLibSize {
+        slice: slice.to_string(),
+        path: path.to_path_buf(),
+        unstripped_bytes,
+        stripped_bytes: strip.and_then(|tool| tool.stripped_size(path, &scratch_dir.join(slice))),
+    })
+}
+
+/// Prints one line per library with its unstripped and stripped sizes.
+pub fn print_native_lib_sizes(libs: &[NativeLibSize]) {
+    println!("  Native library sizes:");
+    for lib in libs {
+        let stripped = lib
+            .stripped_bytes
+            .map_or_else(|| "n/a".to_string(), format_kb);
+        println!(
+            "    {:<28} {:>12} unstripped, {:>12} stripped",
+            lib.slice,
+            format_kb(lib.unstripped_bytes),
+            stripped
+        );
+    }
+}
+
+fn format_kb(bytes: u64) -> String {
+    format!("{:.1} KB", bytes as f64 / 1024.0)
+}
+
+/// Fails when any library's shipped size exceeds `max_kb` kilobytes.
+///
+/// The stripped size is compared when it could be measured, since that is
+/// what ships in the app; otherwise the unstripped size is used.
+pub fn check_lib_size_budget(libs: &[NativeLibSize], max_kb: u64) -> Result<(), BenchError> {
+    let over: Vec<String> = libs
+        .iter()
+        .filter(|lib| lib.shipped_bytes() > max_kb.saturating_mul(1024))
+        .map(|lib| {
+            format!(
+                "  - {} ({}): {}{}",
+                lib.slice,
+                lib.path.display(),
+                format_kb(lib.shipped_bytes()),
+                if lib.stripped_bytes.is_some() { " stripped" } else { " unstripped" }
+            )
+        })
+        .collect();
+    if over.is_empty() {
+        return Ok(());
+    }
+    Err(BenchError::Build(format!(
+        "Native library size budget exceeded (max_lib_size_kb = {}):\n{}\n\n\
+         Check for debug symbols in release builds (e.g. `debug = true` or\n\
+         `strip = false` in the cargo profile) or newly added dependencies.",
+        max_kb,
+        over.join("\n")
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -648,6 +1201,26 @@ mod tests {
         assert!(result.is_ok() || result.is_err());
     }
 
+    #[test]
+    fn test_crate_type_fix() {
+        let missing = "[package]\nname = \"bench\"\n";
+        let (types, line) = crate_type_fix(missing, &["staticlib", "cdylib"]).unwrap();
+        assert_eq!(types, vec!["staticlib", "cdylib"]);
+        assert_eq!(line, r#"crate-type = ["cdylib", "staticlib", "lib"]"#);
+
+        let rlib = "[lib]\ncrate-type = [\"rlib\"]\n";
+        let (types, line) = crate_type_fix(rlib, &["cdylib"]).unwrap();
+        assert_eq!(types, vec!["cdylib"]);
+        assert_eq!(line, r#"crate-type = ["cdylib", "rlib"]"#);
+
+        let complete = "[lib]\ncrate-type = [\"cdylib\", \"staticlib\", \"lib\"]\n";
+        assert!(crate_type_fix(complete, &["cdylib", "staticlib"]).is_none());
+        assert!(crate_type_fix("not = [toml", &["cdylib"]).is_none());
+
+        let err = check_crate_types(Path::new("/nonexistent/path"), &["cdylib"]);
+        assert!(err.is_ok());
+    }
+
     #[test]
     fn test_host_lib_path_not_found() {
         let result = host_lib_path(Path::new("/tmp"), "nonexistent-crate");
@@ -658,6 +1231,22 @@ mod tests {
         assert!(msg.contains("cargo build"));
     }
 
+    #[test]
+    fn test_find_on_path_tries_each_extension() {
+        let temp_dir = std::env::temp_dir().join("mobench-test-find-on-path");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let bin = temp_dir.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("gradle.bat"), "@echo off\n").unwrap();
+
+        let path = env::join_paths([temp_dir.join("empty"), bin.clone()]).unwrap();
+        assert_eq!(find_on_path("gradle", &path, &[".EXE", ".BAT"]), Some(bin.join("gradle.bat")));
+        assert_eq!(find_on_path("gradle", &path, &[".EXE"]), None);
+        assert_eq!(find_on_path("sdkmanager", &path, &[".BAT"]), None);
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
     #[test]
     fn test_run_command_not_found() {
         let cmd = Command::new("nonexistent-command-12345");
@@ -742,12 +1331,62 @@ members = ["crates/*"]
         std::fs::remove_dir_all(&temp_dir).unwrap();
     }
 
+    #[test]
+    fn test_build_fingerprint_tracks_sources_and_profile() {
+        let temp_dir = std::env::temp_dir().join("mobench-test-build-fingerprint");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(temp_dir.join("src")).unwrap();
+        std::fs::write(temp_dir.join("Cargo.toml"), "[package]\nname = \"fp\"\n").unwrap();
+        std::fs::write(temp_dir.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+
+        let debug = compute_build_fingerprint(&temp_dir, "aarch64-linux-android", "debug").unwrap();
+        let again = compute_build_fingerprint(&temp_dir, "aarch64-linux-android", "debug").unwrap();
+        let release =
+            compute_build_fingerprint(&temp_dir, "aarch64-linux-android", "release").unwrap();
+        assert_eq!(debug, again);
+        assert_ne!(debug, release);
+
+        std::fs::write(temp_dir.join("src/lib.rs"), "pub fn b() {}\n").unwrap();
+        let changed = compute_build_fingerprint(&temp_dir, "aarch64-linux-android", "debug").unwrap();
+        assert_ne!(debug, changed);
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_build_cache_roundtrip() {
+        let temp_dir = std::env::temp_dir().join("mobench-test-build-cache");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+
+        let cache = BuildCache::new(&temp_dir);
+        assert!(!cache.is_fresh("x86_64-linux-android", "debug", "abc"));
+        cache.store("x86_64-linux-android", "debug", "abc").unwrap();
+        assert!(cache.is_fresh("x86_64-linux-android", "debug", "abc"));
+        assert!(!cache.is_fresh("x86_64-linux-android", "release", "abc"));
+        assert!(!cache.is_fresh("x86_64-linux-android", "debug", "def"));
+        assert!(cache.dir().ends_with(BUILD_CACHE_DIR));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
     #[test]
     fn test_create_bench_meta() {
         let spec = EmbeddedBenchSpec {
             function: "test_crate::my_benchmark".to_string(),
             iterations: 100,
             warmup: 10,
+            sampling: None,
+            warmup_policy: None,
+            functions: Vec::new(),
+            seed: None,
+            per_benchmark_timeout_secs: None,
+            per_iteration_timeout_ms: None,
+            capture_warmup: false,
+            startup_metrics: false,
+            drop_first_n: 0,
+            pin_cores: Default::default(),
+            interleave: false,
+            env: Default::default(),
         };
 
         let meta = create_bench_meta(&spec, "android", "release");
@@ -800,6 +1439,18 @@ members = ["crates/*"]
             function: "my_func".to_string(),
             iterations: 50,
             warmup: 5,
+            sampling: None,
+            warmup_policy: None,
+            functions: Vec::new(),
+            seed: None,
+            per_benchmark_timeout_secs: None,
+            per_iteration_timeout_ms: None,
+            capture_warmup: false,
+            startup_metrics: false,
+            drop_first_n: 0,
+            pin_cores: Default::default(),
+            interleave: false,
+            env: Default::default(),
         };
 
         let meta = create_bench_meta(&spec, "ios", "debug");
@@ -811,5 +1462,76 @@ members = ["crates/*"]
         assert!(json.contains("debug"));
         assert!(json.contains("build_time"));
         assert!(json.contains("mobench_version"));
+        assert!(json.contains(&spec.spec_hash()));
+    }
+
+    #[test]
+    fn test_bench_meta_mismatches() {
+        let spec = EmbeddedBenchSpec {
+            function: "my_func".to_string(),
+            iterations: 50,
+            warmup: 5,
+            sampling: None,
+            warmup_policy: None,
+            functions: Vec::new(),
+            seed: None,
+            per_benchmark_timeout_secs: None,
+            per_iteration_timeout_ms: None,
+            capture_warmup: false,
+            startup_metrics: false,
+            drop_first_n: 0,
+            pin_cores: Default::default(),
+            interleave: false,
+            env: Default::default(),
+        };
+        let mut meta = create_bench_meta(&spec, "android", "release");
+        meta.commit_hash = Some("abc1234".to_string());
+
+        let matching = serde_json::json!({
+            "build_meta": { "commit_hash": "abc1234", "spec_hash": spec.spec_hash() }
+        });
+        assert!(meta.mismatches(&matching).is_empty());
+
+        // Reports from older runners carry no build metadata
+        assert!(meta.mismatches(&serde_json::json!({ "samples_ns": [] })).is_empty());
+
+        let stale_spec = EmbeddedBenchSpec {
+            iterations: 10,
+            ..spec.clone()
+        };
+        let stale = serde_json::json!({
+            "build_meta": { "commit_hash": "0ld0ld0", "spec_hash": stale_spec.spec_hash() }
+        });
+        let mismatches = meta.mismatches(&stale);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("spec hash"));
+        assert!(mismatches[1].contains("abc1234"));
+    }
+
+    #[test]
+    fn test_lib_size_budget() {
+        let dir = env::temp_dir().join(format!("mobench-sdk-lib-size-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let lib = dir.join("libbench.so");
+        std::fs::write(&lib, vec![0u8; 3 * 1024]).unwrap();
+
+        let measured = measure_native_lib("arm64-v8a", &lib, None, &dir.join("scratch")).unwrap();
+        assert_eq!(measured.unstripped_bytes, 3 * 1024);
+        assert_eq!(measured.stripped_bytes, None);
+        assert_eq!(measured.shipped_bytes(), 3 * 1024);
+        assert!(check_lib_size_budget(std::slice::from_ref(&measured), 3).is_ok());
+        let err = check_lib_size_budget(std::slice::from_ref(&measured), 2).unwrap_err();
+        assert!(err.to_string().contains("arm64-v8a"));
+        assert!(err.to_string().contains("3.0 KB unstripped"));
+
+        // The stripped size is what ships, so it is what the budget checks
+        let stripped = NativeLibSize {
+            stripped_bytes: Some(1024),
+            ..measured
+        };
+        assert!(check_lib_size_budget(&[stripped], 2).is_ok());
+
+        assert!(measure_native_lib("x86_64", &dir.join("missing.so"), None, &dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
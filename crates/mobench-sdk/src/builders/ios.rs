@@ -15,6 +15,11 @@
 //! 5. **Code signing** - Signs the xcframework for Xcode acceptance
 //! 6. **Xcode project generation** - Runs xcodegen if `project.yml` exists
 //!
+//! With [`BuildConfig::cache`](crate::BuildConfig::cache) set, steps 2-5 are
+//! skipped when the crate's content fingerprint (sources, `Cargo.lock`, the
+//! three targets and the profile) matches the last xcframework build and its
+//! artifacts are still on disk.
+//!
 //! ## Requirements
 //!
 //! - Xcode with command line tools (`xcode-select --install`)
@@ -36,6 +41,8 @@
 //!     target: Target::Ios,
 //!     profile: BuildProfile::Release,
 //!     incremental: true,
+//!     cache: true,
+//!     cargo_profile: None,
 //! };
 //!
 //! let result = builder.build(&config)?;
@@ -69,14 +76,31 @@
 //! // Development signing (requires Apple Developer account)
 //! let ipa = builder.package_ipa("BenchRunner", SigningMethod::Development)?;
 //! ```
+//!
+//! The device build also emits a `.dSYM`; [`IosBuilder::package_dsym`] zips it
+//! for symbolicating on-device crashes:
+//!
+//! ```ignore
+//! let dsym_zip = builder.package_dsym("BenchRunner")?;
+//! ```
 
-use crate::types::{BenchError, BuildConfig, BuildProfile, BuildResult, Target};
-use super::common::{get_cargo_target_dir, host_lib_path, run_command, validate_project_root};
+use crate::types::{BenchError, BuildConfig, BuildResult, Target};
+use super::common::{
+    BuildCache, StripTool, check_crate_types, check_lib_size_budget, compute_build_fingerprint, get_cargo_target_dir,
+    host_lib_path, measure_native_lib, print_native_lib_sizes, run_command, validate_project_root,
+};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Rust targets the xcframework is assembled from: device, Apple Silicon
+/// simulator and Intel simulator.
+const IOS_TARGETS: [&str; 3] = ["aarch64-apple-ios", "aarch64-apple-ios-sim", "x86_64-apple-ios"];
+
+/// [`BuildCache`] entry recording the fingerprint of the last xcframework build.
+const XCFRAMEWORK_CACHE_KEY: &str = "ios-xcframework";
+
 /// iOS builder that handles the complete build pipeline.
 ///
 /// This builder automates the process of compiling Rust code to iOS static
@@ -97,6 +121,8 @@ use std::process::Command;
 ///     target: Target::Ios,
 ///     profile: BuildProfile::Release,
 ///     incremental: true,
+///     cache: true,
+///     cargo_profile: None,
 /// };
 ///
 /// let result = builder.build(&config)?;
@@ -118,6 +144,10 @@ pub struct IosBuilder {
     crate_dir: Option<PathBuf>,
     /// Whether to run in dry-run mode (print what would be done without making changes)
     dry_run: bool,
+    /// Bundle id and Info.plist settings for the generated Xcode project
+    app: crate::codegen::IosAppOptions,
+    /// Largest allowed stripped static library size in kilobytes, if any
+    max_lib_size_kb: Option<u64>,
 }
 
 impl IosBuilder {
@@ -140,6 +170,8 @@ impl IosBuilder {
             verbose: false,
             crate_dir: None,
             dry_run: false,
+            app: Default::default(),
+            max_lib_size_kb: None,
         }
     }
 
@@ -181,6 +213,25 @@ impl IosBuilder {
         self
     }
 
+    /// Sets the bundle id and Info.plist entries of the generated Xcode project
+    ///
+    /// By default the app uses `dev.world.<crate>.BenchRunner`. Custom
+    /// settings are validated and, for an existing project, re-applied to
+    /// `project.yml` before xcodegen runs.
+    pub fn app_options(mut self, app: crate::codegen::IosAppOptions) -> Self {
+        self.app = app;
+        self
+    }
+
+    /// Fails the build when any target's static library is larger than `max_kb` kilobytes
+    ///
+    /// The size after `strip -S -x` is checked when Xcode's `strip` is
+    /// available, otherwise the unstripped size.
+    pub fn max_lib_size_kb(mut self, max_kb: Option<u64>) -> Self {
+        self.max_lib_size_kb = max_kb;
+        self
+    }
+
     /// Builds the iOS app with the given configuration
     ///
     /// This performs the following steps:
@@ -210,13 +261,17 @@ impl IosBuilder {
             println!("  Step 0: Check/generate iOS project scaffolding at {:?}", ios_dir.join("BenchRunner"));
             println!("  Step 1: Build Rust libraries for iOS targets");
             println!("    Command: cargo build --target aarch64-apple-ios --lib {}",
-                if matches!(config.profile, BuildProfile::Release) { "--release" } else { "" });
+                config.cargo_profile_args().join(" "));
             println!("    Command: cargo build --target aarch64-apple-ios-sim --lib {}",
-                if matches!(config.profile, BuildProfile::Release) { "--release" } else { "" });
+                config.cargo_profile_args().join(" "));
             println!("    Command: cargo build --target x86_64-apple-ios --lib {}",
-                if matches!(config.profile, BuildProfile::Release) { "--release" } else { "" });
+                config.cargo_profile_args().join(" "));
             println!("  Step 2: Generate UniFFI Swift bindings");
             println!("    Output: {:?}", ios_dir.join("BenchRunner/BenchRunner/Generated"));
+            if config.cache {
+                println!("    Steps 1-4 are skipped when the fingerprint in {:?} is unchanged",
+                    BuildCache::new(&self.output_dir).dir());
+            }
             println!("  Step 3: Create xcframework at {:?}", xcframework_path);
             println!("    - ios-arm64/{}.framework (device)", framework_name);
             println!("    - ios-arm64_x86_64-simulator/{}.framework (simulator - arm64 + x86_64 lipo)", framework_name);
@@ -230,33 +285,70 @@ impl IosBuilder {
                 platform: Target::Ios,
                 app_path: xcframework_path,
                 test_suite_path: None,
+                dsym_path: None,
+                native_libs: Vec::new(),
             });
         }
 
+        // The crate compiles without these, but the app cannot link it
+        check_crate_types(&self.find_crate_dir()?, &["staticlib", "cdylib"])?;
+
         // Step 0: Ensure iOS project scaffolding exists
         // Pass project_root and crate_dir for better benchmark function detection
-        crate::codegen::ensure_ios_project_with_options(
+        crate::codegen::ensure_ios_project_with_app(
             &self.output_dir,
             &self.crate_name,
             Some(&self.project_root),
             self.crate_dir.as_deref(),
+            &self.app,
         )?;
 
-        // Step 1: Build Rust libraries
-        println!("Building Rust libraries for iOS...");
-        self.build_rust_libraries(config)?;
+        // Steps 1-4 only depend on the Rust crate, so an unchanged fingerprint
+        // reuses the xcframework from the previous build
+        let cache = BuildCache::new(&self.output_dir);
+        let profile_dir = config.target_profile_dir();
+        let fingerprint = if config.cache {
+            Some(compute_build_fingerprint(
+                &self.find_crate_dir()?,
+                &IOS_TARGETS.join(","),
+                profile_dir,
+            )?)
+        } else {
+            None
+        };
+        let reuse = match &fingerprint {
+            Some(fingerprint) => xcframework_is_fresh(
+                &self.xcframework_artifacts(config)?,
+                &cache,
+                profile_dir,
+                fingerprint,
+            ),
+            None => false,
+        };
 
-        // Step 2: Generate UniFFI bindings
-        println!("Generating UniFFI Swift bindings...");
-        self.generate_uniffi_bindings()?;
+        if reuse {
+            println!("Rust inputs unchanged, reusing {}", xcframework_path.display());
+        } else {
+            // Step 1: Build Rust libraries
+            println!("Building Rust libraries for iOS...");
+            self.build_rust_libraries(config)?;
+
+            // Step 2: Generate UniFFI bindings
+            println!("Generating UniFFI Swift bindings...");
+            self.generate_uniffi_bindings()?;
+
+            // Step 3: Create xcframework
+            println!("Creating xcframework...");
+            self.create_xcframework(config)?;
 
-        // Step 3: Create xcframework
-        println!("Creating xcframework...");
-        let xcframework_path = self.create_xcframework(config)?;
+            // Step 4: Code-sign xcframework
+            println!("Code-signing xcframework...");
+            self.codesign_xcframework(&xcframework_path)?;
 
-        // Step 4: Code-sign xcframework
-        println!("Code-signing xcframework...");
-        self.codesign_xcframework(&xcframework_path)?;
+            if let Some(fingerprint) = &fingerprint {
+                cache.store(XCFRAMEWORK_CACHE_KEY, profile_dir, fingerprint)?;
+            }
+        }
 
         // Copy header to include/ for consumers (handy for CLI uploads)
         let header_src = self
@@ -287,24 +379,87 @@ impl IosBuilder {
         self.generate_xcode_project()?;
 
         // Step 6: Validate all expected artifacts exist
-        let result = BuildResult {
+        let mut result = BuildResult {
             platform: Target::Ios,
             app_path: xcframework_path,
             test_suite_path: None,
+            dsym_path: self.locate_dsym("BenchRunner"),
+            native_libs: Vec::new(),
         };
         self.validate_build_artifacts(&result, config)?;
+        if let Some(dsym) = &result.dsym_path
+            && self.verbose
+        {
+            println!("  Found dSYM from a previous app build: {}", dsym.display());
+        }
+
+        // Step 7: Measure static library sizes and enforce the size budget
+        result.native_libs = self.measure_native_libraries(config)?;
+        if self.verbose {
+            print_native_lib_sizes(&result.native_libs);
+        }
+        if let Some(max_kb) = self.max_lib_size_kb {
+            check_lib_size_budget(&result.native_libs, max_kb)?;
+        }
 
         Ok(result)
     }
 
+    /// Files a previous build must have left behind for its xcframework to be
+    /// reused: both framework slices, every static library and the Swift bindings.
+    fn xcframework_artifacts(&self, config: &BuildConfig) -> Result<Vec<PathBuf>, BenchError> {
+        let framework_name = self.crate_name.replace("-", "_");
+        let xcframework_path = self
+            .output_dir
+            .join("ios")
+            .join(format!("{}.xcframework", framework_name));
+        let target_dir = get_cargo_target_dir(&self.find_crate_dir()?)?;
+        let lib_name = format!("lib{}.a", framework_name);
+
+        let mut artifacts = vec![
+            xcframework_path.join(format!("ios-arm64/{}.framework", framework_name)),
+            xcframework_path.join(format!("ios-arm64_x86_64-simulator/{}.framework", framework_name)),
+            self.output_dir
+                .join("ios/BenchRunner/BenchRunner/Generated")
+                .join(format!("{}.swift", framework_name)),
+        ];
+        artifacts.extend(
+            IOS_TARGETS
+                .iter()
+                .map(|target| target_dir.join(target).join(config.target_profile_dir()).join(&lib_name)),
+        );
+        Ok(artifacts)
+    }
+
+    /// Measures the static library built for each iOS target
+    fn measure_native_libraries(
+        &self,
+        config: &BuildConfig,
+    ) -> Result<Vec<crate::NativeLibSize>, BenchError> {
+        let crate_dir = self.find_crate_dir()?;
+        let target_dir = get_cargo_target_dir(&crate_dir)?;
+        let lib_name = format!("lib{}.a", self.crate_name.replace("-", "_"));
+        let scratch_dir = self.output_dir.join("size-check");
+        let strip = StripTool::apple();
+        let mut libs = Vec::new();
+        for target in IOS_TARGETS {
+            let lib_path = target_dir
+                .join(target)
+                .join(config.target_profile_dir())
+                .join(&lib_name);
+            if lib_path.exists() {
+                libs.push(measure_native_lib(target, &lib_path, Some(&strip), &scratch_dir)?);
+            }
+        }
+        let _ = fs::remove_dir_all(&scratch_dir);
+        Ok(libs)
+    }
+
     /// Validates that all expected build artifacts exist after a successful build
     fn validate_build_artifacts(&self, result: &BuildResult, config: &BuildConfig) -> Result<(), BenchError> {
         let mut missing = Vec::new();
         let framework_name = self.crate_name.replace("-", "_");
-        let profile_dir = match config.profile {
-            BuildProfile::Debug => "debug",
-            BuildProfile::Release => "release",
-        };
+        let profile_dir = config.target_profile_dir();
 
         // Check xcframework exists
         if !result.app_path.exists() {
@@ -457,22 +612,11 @@ impl IosBuilder {
     fn build_rust_libraries(&self, config: &BuildConfig) -> Result<(), BenchError> {
         let crate_dir = self.find_crate_dir()?;
 
-        // iOS targets: device and simulator (both arm64 and x86_64 for Intel Macs)
-        let targets = vec![
-            "aarch64-apple-ios",     // Device (ARM64)
-            "aarch64-apple-ios-sim", // Simulator (Apple Silicon Macs)
-            "x86_64-apple-ios",      // Simulator (Intel Macs)
-        ];
-
         // Check if targets are installed
-        self.check_rust_targets(&targets)?;
-        let release_flag = if matches!(config.profile, BuildProfile::Release) {
-            "--release"
-        } else {
-            ""
-        };
+        self.check_rust_targets(&IOS_TARGETS)?;
+        let profile_args = config.cargo_profile_args();
 
-        for target in targets {
+        for target in IOS_TARGETS {
             if self.verbose {
                 println!("  Building for {}", target);
             }
@@ -480,19 +624,17 @@ impl IosBuilder {
             let mut cmd = Command::new("cargo");
             cmd.arg("build").arg("--target").arg(target).arg("--lib");
 
-            // Add release flag if needed
-            if !release_flag.is_empty() {
-                cmd.arg(release_flag);
-            }
+            // Add cargo profile selection (--release / --profile <name>) if needed
+            cmd.args(&profile_args);
 
             // Set working directory
             cmd.current_dir(&crate_dir);
 
             // Execute build
-            let command_hint = if release_flag.is_empty() {
+            let command_hint = if profile_args.is_empty() {
                 format!("cargo build --target {} --lib", target)
             } else {
-                format!("cargo build --target {} --lib {}", target, release_flag)
+                format!("cargo build --target {} --lib {}", target, profile_args.join(" "))
             };
             let output = cmd
                 .output()
@@ -677,10 +819,7 @@ impl IosBuilder {
 
     /// Creates an xcframework from the built libraries
     fn create_xcframework(&self, config: &BuildConfig) -> Result<PathBuf, BenchError> {
-        let profile_dir = match config.profile {
-            BuildProfile::Debug => "debug",
-            BuildProfile::Release => "release",
-        };
+        let profile_dir = config.target_profile_dir();
 
         let crate_dir = self.find_crate_dir()?;
         let target_dir = get_cargo_target_dir(&crate_dir)?;
@@ -1407,6 +1546,8 @@ impl IosBuilder {
             build_configuration,
             "-derivedDataPath",
             build_dir.to_str().unwrap(),
+            // Emit a dSYM so on-device crashes can be symbolicated later
+            "DEBUG_INFORMATION_FORMAT=dwarf-with-dsym",
             "build",
         ]);
 
@@ -1574,6 +1715,69 @@ impl IosBuilder {
         Ok(ipa_path)
     }
 
+    /// Returns the `.app.dSYM` bundle produced by the last device build of `scheme`.
+    ///
+    /// `package_ipa` builds with `DEBUG_INFORMATION_FORMAT=dwarf-with-dsym`, so
+    /// the dSYM sits next to the `.app` in the derived data products directory.
+    /// Release products are preferred over debug ones.
+    pub fn locate_dsym(&self, scheme: &str) -> Option<PathBuf> {
+        find_dsym(&self.output_dir.join("ios/build/Build/Products"), scheme)
+    }
+
+    /// Zips the app's `.dSYM` bundle for symbolicating on-device crashes.
+    ///
+    /// BrowserStack device logs contain unsymbolicated addresses when a
+    /// benchmark crashes; the zip can be fed to `atos` or uploaded to a crash
+    /// reporter alongside those logs. Requires `package_ipa` to have run first.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PathBuf)` - Path to `ios/<scheme>.app.dSYM.zip`
+    /// * `Err(BenchError)` - If no dSYM was found or zipping fails
+    pub fn package_dsym(&self, scheme: &str) -> Result<PathBuf, BenchError> {
+        let dsym_path = self.locate_dsym(scheme).ok_or_else(|| {
+            BenchError::Build(format!(
+                "No {}.app.dSYM found under {}.\n\n\
+                 Run `cargo mobench package-ipa` first; it builds with DEBUG_INFORMATION_FORMAT=dwarf-with-dsym.",
+                scheme,
+                self.output_dir.join("ios/build/Build/Products").display()
+            ))
+        })?;
+        let dsym_dir = dsym_path.parent().ok_or_else(|| {
+            BenchError::Build(format!("dSYM path has no parent: {}", dsym_path.display()))
+        })?;
+        let dsym_name = dsym_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                BenchError::Build(format!("Invalid dSYM path: {}", dsym_path.display()))
+            })?;
+
+        let zip_path = self.output_dir.join("ios").join(format!("{}.zip", dsym_name));
+        if zip_path.exists() {
+            fs::remove_file(&zip_path).map_err(|e| {
+                BenchError::Build(format!(
+                    "Failed to remove old dSYM zip at {}: {}. Check file permissions.",
+                    zip_path.display(),
+                    e
+                ))
+            })?;
+        }
+
+        let mut cmd = Command::new("zip");
+        cmd.arg("-qr")
+            .arg(&zip_path)
+            .arg(dsym_name)
+            .current_dir(dsym_dir);
+        if self.verbose {
+            println!("  Running: {:?}", cmd);
+        }
+        run_command(cmd, "zip dSYM")?;
+
+        println!("✓ dSYM packaged: {:?}", zip_path);
+        Ok(zip_path)
+    }
+
     /// Packages the XCUITest runner app into a zip for BrowserStack.
     ///
     /// This requires the app project to be generated first with `build()`.
@@ -1769,6 +1973,26 @@ impl IosBuilder {
     }
 }
 
+/// Finds `<scheme>.app.dSYM` in an Xcode products directory, preferring release builds.
+fn find_dsym(products_dir: &Path, scheme: &str) -> Option<PathBuf> {
+    ["Release-iphoneos", "Debug-iphoneos"]
+        .iter()
+        .map(|config| products_dir.join(config).join(format!("{}.app.dSYM", scheme)))
+        .find(|path| path.is_dir())
+}
+
+/// Whether the xcframework from a previous build can be reused: every
+/// artifact is still on disk and the stored fingerprint matches.
+fn xcframework_is_fresh(
+    artifacts: &[PathBuf],
+    cache: &BuildCache,
+    profile_dir: &str,
+    fingerprint: &str,
+) -> bool {
+    artifacts.iter().all(|path| path.exists())
+        && cache.is_fresh(XCFRAMEWORK_CACHE_KEY, profile_dir, fingerprint)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1783,6 +2007,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_xcframework_reused_only_when_fresh() {
+        let temp_dir = env::temp_dir().join("mobench-test-xcframework-cache");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let framework = temp_dir.join("ios/bench.xcframework/ios-arm64/bench.framework");
+        fs::create_dir_all(&framework).unwrap();
+        let cache = BuildCache::new(&temp_dir);
+        let artifacts = vec![framework.clone()];
+
+        // No fingerprint has been recorded yet
+        assert!(!xcframework_is_fresh(&artifacts, &cache, "release", "abc"));
+
+        cache.store(XCFRAMEWORK_CACHE_KEY, "release", "abc").unwrap();
+        assert!(xcframework_is_fresh(&artifacts, &cache, "release", "abc"));
+        assert!(!xcframework_is_fresh(&artifacts, &cache, "release", "def"));
+        assert!(!xcframework_is_fresh(&artifacts, &cache, "debug", "abc"));
+
+        fs::remove_dir_all(&framework).unwrap();
+        assert!(!xcframework_is_fresh(&artifacts, &cache, "release", "abc"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
     #[test]
     fn test_ios_builder_verbose() {
         let builder = IosBuilder::new("/tmp/test-project", "test-bench-mobile").verbose(true);
@@ -1796,6 +2043,23 @@ mod tests {
         assert_eq!(builder.output_dir, PathBuf::from("/custom/output"));
     }
 
+    #[test]
+    fn test_find_dsym_prefers_release() {
+        let temp_dir = std::env::temp_dir().join("mobench-ios-test-find-dsym");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        assert_eq!(find_dsym(&temp_dir, "BenchRunner"), None);
+
+        let debug = temp_dir.join("Debug-iphoneos/BenchRunner.app.dSYM");
+        std::fs::create_dir_all(&debug).unwrap();
+        assert_eq!(find_dsym(&temp_dir, "BenchRunner"), Some(debug));
+
+        let release = temp_dir.join("Release-iphoneos/BenchRunner.app.dSYM");
+        std::fs::create_dir_all(&release).unwrap();
+        assert_eq!(find_dsym(&temp_dir, "BenchRunner"), Some(release));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
     #[test]
     fn test_find_crate_dir_current_directory_is_crate() {
         // Test case 1: Current directory IS the crate with matching package name
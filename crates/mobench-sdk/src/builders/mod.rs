@@ -26,6 +26,7 @@
 //! - Workspace-aware Cargo target directory detection
 //! - Host library path resolution for UniFFI binding generation
 //! - Consistent command execution with actionable error messages
+//! - Content-hash build cache ([`BuildCache`]) for skipping unchanged Rust compiles
 //!
 //! ## Builder Options
 //!
@@ -36,6 +37,9 @@
 //! - **`output_dir(path)`** - Customize output location (default: `target/mobench/`)
 //! - **`crate_dir(path)`** - Override auto-detected crate location
 //!
+//! Setting [`BuildConfig::cache`](crate::BuildConfig::cache) lets the Android
+//! builder reuse previously compiled native libraries when nothing changed.
+//!
 //! ## Example
 //!
 //! ```ignore
@@ -55,6 +59,8 @@
 //!     target: Target::Android,
 //!     profile: BuildProfile::Release,
 //!     incremental: true,
+//!     cache: true,
+//!     cargo_profile: None,
 //! };
 //!
 //! android.build(&config)?;
@@ -69,3 +75,4 @@ pub mod common;
 pub use android::AndroidBuilder;
 pub use ios::{IosBuilder, SigningMethod};
 pub use common::{embed_bench_spec, embed_bench_meta, EmbeddedBenchSpec, BenchMeta, create_bench_meta};
+pub use common::{BuildCache, compute_build_fingerprint};
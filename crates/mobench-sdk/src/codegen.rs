@@ -4,8 +4,8 @@
 //! embedded templates. It handles template parameterization and file generation.
 
 use crate::types::{BenchError, InitConfig, Target};
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 use include_dir::{Dir, DirEntry, include_dir};
@@ -20,6 +20,74 @@ pub struct TemplateVar {
     pub value: String,
 }
 
+/// App identity and Info.plist settings for the generated iOS project
+///
+/// The defaults reproduce the stock project: bundle id
+/// `<bundle_prefix>.BenchRunner` and only the version keys in Info.plist.
+/// Set a unique `bundle_id` when several teams share one Apple account or
+/// device cloud, since those reject duplicate bundle ids.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IosAppOptions {
+    /// App bundle identifier; the UI test bundle uses `<bundle_id>.uitests`
+    pub bundle_id: Option<String>,
+    /// `CFBundleDisplayName` shown under the app icon
+    pub display_name: Option<String>,
+    /// Extra Info.plist entries; these win over the template's own keys
+    pub plist: BTreeMap<String, serde_json::Value>,
+}
+
+impl IosAppOptions {
+    /// Returns true if no setting differs from the stock project
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Checks the bundle id format and that every Info.plist entry has a value
+    pub fn validate(&self) -> Result<(), BenchError> {
+        if let Some(bundle_id) = &self.bundle_id {
+            validate_bundle_id(bundle_id)?;
+        }
+        for (key, value) in &self.plist {
+            if key.trim().is_empty() {
+                return Err(BenchError::Config(
+                    "Info.plist keys under [ios.plist] cannot be empty".to_string(),
+                ));
+            }
+            if value.is_null() {
+                return Err(BenchError::Config(format!(
+                    "Info.plist entry '{}' has no value",
+                    key
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validates an iOS bundle identifier
+///
+/// Apple allows only ASCII letters, digits, hyphens and periods. The id must
+/// also be in reverse-DNS form: at least two segments, none of them empty.
+pub fn validate_bundle_id(bundle_id: &str) -> Result<(), BenchError> {
+    if let Some(c) = bundle_id
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '.'))
+    {
+        return Err(BenchError::Config(format!(
+            "invalid iOS bundle id '{}': character '{}' is not allowed (use letters, digits, '-' and '.')",
+            bundle_id, c
+        )));
+    }
+    let segments: Vec<&str> = bundle_id.split('.').collect();
+    if segments.len() < 2 || segments.iter().any(|segment| segment.is_empty()) {
+        return Err(BenchError::Config(format!(
+            "invalid iOS bundle id '{}': expected reverse-DNS form such as 'com.example.bench'",
+            bundle_id
+        )));
+    }
+    Ok(())
+}
+
 /// Generates a new mobile benchmark project from templates
 ///
 /// Creates the necessary directory structure and files for benchmarking on
@@ -76,6 +144,10 @@ pub fn generate_project(config: &InitConfig) -> Result<PathBuf, BenchError> {
         generate_example_benchmarks(output_dir)?;
     }
 
+    if config.with_criterion {
+        generate_criterion_harness(output_dir, &project_slug)?;
+    }
+
     Ok(output_dir.clone())
 }
 
@@ -101,10 +173,17 @@ crate-type = ["cdylib", "staticlib", "rlib"]
 [dependencies]
 mobench-sdk = {{ path = ".." }}
 uniffi = "0.28"
+serde_json = "1"
 {} = {{ path = ".." }}
 
 [features]
-default = []
+default = ["core-pinning"]
+# Time iterations with the platform's high-resolution clock
+precise-clock = ["mobench-sdk/precise-clock"]
+# Also record each iteration's thread CPU time
+cpu-time = ["mobench-sdk/cpu-time"]
+# Honor `pin_cores` in bench_spec.json (Android only; a no-op elsewhere)
+core-pinning = ["mobench-sdk/core-pinning"]
 
 [build-dependencies]
 uniffi = {{ version = "0.28", features = ["build"] }}
@@ -148,17 +227,62 @@ pub struct BenchSpec {
     pub name: String,
     pub iterations: u32,
     pub warmup: u32,
+    /// JSON-encoded `mobench_sdk::SamplingMode`; `None` means fixed iterations.
+    #[uniffi(default = None)]
+    pub sampling: Option<String>,
+    /// Seed for seeded per-iteration setup; `None` derives it from `name`.
+    #[uniffi(default = None)]
+    pub seed: Option<u64>,
+    /// JSON-encoded `mobench_sdk::WarmupPolicy`; `None` means fixed warmup.
+    #[uniffi(default = None)]
+    pub warmup_policy: Option<String>,
+    /// Wall-clock budget for the whole benchmark; `None` runs to completion.
+    #[uniffi(default = None)]
+    pub per_benchmark_timeout_secs: Option<u64>,
+    /// Measured samples to discard before the report; `None` keeps them all.
+    #[uniffi(default = None)]
+    pub drop_first_n: Option<u32>,
+    /// Cores to pin the benchmark thread to (`mobench_sdk::affinity::CorePinning`);
+    /// `None` leaves it unpinned.
+    #[uniffi(default = None)]
+    pub pin_cores: Option<String>,
+    /// Longest the timed part of one iteration may take; `None` sets no limit.
+    #[uniffi(default = None)]
+    pub per_iteration_timeout_ms: Option<u64>,
+    /// Record warmup durations in the report's `warmup_samples`.
+    #[uniffi(default = false)]
+    pub capture_warmup: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, uniffi::Record)]
 pub struct BenchSample {
     pub duration_ns: u64,
+    /// Thread CPU time of the iteration, with mobench-sdk's `cpu-time` feature.
+    #[uniffi(default = None)]
+    pub cpu_ns: Option<u64>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, uniffi::Record)]
 pub struct BenchReport {
     pub spec: BenchSpec,
     pub samples: Vec<BenchSample>,
+    /// Warmup durations in execution order, when `capture_warmup` was set.
+    pub warmup_samples: Vec<BenchSample>,
+    /// Clock the samples were measured with (`mobench_sdk::ClockSource`).
+    #[uniffi(default = None)]
+    pub clock: Option<String>,
+    /// Seed of the RNG that generated the inputs, for seeded benchmarks.
+    #[uniffi(default = None)]
+    pub seed: Option<u64>,
+    /// Custom metrics recorded with `mobench_sdk::timing::record_metric`.
+    pub metrics: std::collections::HashMap<String, Vec<f64>>,
+    /// The per-benchmark timeout ran out; `samples` holds what was measured.
+    pub timed_out: bool,
+    /// Measured samples discarded per `drop_first_n`.
+    pub dropped_samples: u32,
+    /// Effective core affinity when `pin_cores` was set, or why pinning was denied.
+    #[uniffi(default = None)]
+    pub affinity: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error, uniffi::Error)]
@@ -181,24 +305,67 @@ impl From<mobench_sdk::BenchSpec> for BenchSpec {
             name: spec.name,
             iterations: spec.iterations,
             warmup: spec.warmup,
+            sampling: match spec.sampling {
+                mobench_sdk::SamplingMode::FixedIterations => None,
+                sampling => serde_json::to_string(&sampling).ok(),
+            },
+            seed: spec.seed,
+            warmup_policy: match spec.warmup_policy {
+                mobench_sdk::WarmupPolicy::Fixed => None,
+                policy => serde_json::to_string(&policy).ok(),
+            },
+            per_benchmark_timeout_secs: spec.timeout.map(|timeout| timeout.as_secs()),
+            drop_first_n: (spec.drop_first_n > 0).then_some(spec.drop_first_n),
+            pin_cores: None,
+            per_iteration_timeout_ms: spec
+                .per_iteration_timeout
+                .map(|timeout| timeout.as_millis() as u64),
+            capture_warmup: spec.capture_warmup,
         }
     }
 }
 
-impl From<BenchSpec> for mobench_sdk::BenchSpec {
-    fn from(spec: BenchSpec) -> Self {
-        Self {
+// Fails on a `sampling` or `warmup_policy` the SDK cannot parse, rather
+// than quietly running with fixed iterations or fixed warmup
+impl TryFrom<BenchSpec> for mobench_sdk::BenchSpec {
+    type Error = BenchError;
+
+    fn try_from(spec: BenchSpec) -> Result<Self, BenchError> {
+        Ok(Self {
+            warmup_policy: parse_spec_json(spec.warmup_policy.as_deref(), "warmup_policy")?,
+            sampling: parse_spec_json(spec.sampling.as_deref(), "sampling")?,
             name: spec.name,
             iterations: spec.iterations,
             warmup: spec.warmup,
-        }
+            per_iteration_timeout: spec
+                .per_iteration_timeout_ms
+                .map(std::time::Duration::from_millis),
+            timeout: spec.per_benchmark_timeout_secs.map(std::time::Duration::from_secs),
+            capture_warmup: spec.capture_warmup,
+            seed: spec.seed,
+            drop_first_n: spec.drop_first_n.unwrap_or(0),
+        })
     }
 }
 
+/// Decodes a JSON-encoded spec field, using the default when it is unset.
+fn parse_spec_json<T: serde::de::DeserializeOwned + Default>(
+    json: Option<&str>,
+    field: &str,
+) -> Result<T, BenchError> {
+    json.map(serde_json::from_str)
+        .transpose()
+        .map_err(|err| BenchError::ExecutionFailed {
+            reason: format!("invalid {field} in bench spec: {err}"),
+        })
+        .map(Option::unwrap_or_default)
+}
+
 impl From<mobench_sdk::BenchSample> for BenchSample {
     fn from(sample: mobench_sdk::BenchSample) -> Self {
         Self {
             duration_ns: sample.duration_ns,
+            cpu_ns: sample.cpu_ns,
         }
     }
 }
@@ -208,6 +375,13 @@ impl From<mobench_sdk::RunnerReport> for BenchReport {
         Self {
             spec: report.spec.into(),
             samples: report.samples.into_iter().map(Into::into).collect(),
+            warmup_samples: report.warmup_samples.into_iter().map(Into::into).collect(),
+            clock: Some(report.clock.to_string()),
+            seed: report.seed,
+            metrics: report.metrics.into_iter().collect(),
+            timed_out: report.timed_out,
+            dropped_samples: report.dropped_samples,
+            affinity: None,
         }
     }
 }
@@ -235,9 +409,101 @@ impl From<mobench_sdk::BenchError> for BenchError {
 /// This is the main FFI entry point called from mobile platforms.
 #[uniffi::export]
 pub fn run_benchmark(spec: BenchSpec) -> Result<BenchReport, BenchError> {
-    let sdk_spec: mobench_sdk::BenchSpec = spec.into();
+    let affinity = pin_cores(&spec)?;
+    let sdk_spec = mobench_sdk::BenchSpec::try_from(spec)?;
     let report = mobench_sdk::run_benchmark(sdk_spec)?;
-    Ok(report.into())
+    let mut report: BenchReport = report.into();
+    report.affinity = affinity;
+    Ok(report)
+}
+
+/// Runs several benchmarks round-robin, one sample each per round
+///
+/// Spreads thermal drift evenly across the benchmarks; see
+/// `mobench_sdk::run_benchmarks_interleaved`. The first spec's `pin_cores`
+/// applies to all of them.
+#[uniffi::export]
+pub fn run_benchmarks_interleaved(specs: Vec<BenchSpec>) -> Result<Vec<BenchReport>, BenchError> {
+    let affinity = match specs.first() {
+        Some(spec) => pin_cores(spec)?,
+        None => None,
+    };
+    let sdk_specs = specs
+        .into_iter()
+        .map(mobench_sdk::BenchSpec::try_from)
+        .collect::<Result<_, _>>()?;
+    let reports = mobench_sdk::run_benchmarks_interleaved(sdk_specs)?;
+    Ok(reports
+        .into_iter()
+        .map(|report| BenchReport {
+            affinity: affinity.clone(),
+            ..report.into()
+        })
+        .collect())
+}
+
+/// Receives each `BENCH_SAMPLE_JSON {...}` line as soon as a sample is measured
+///
+/// The mobile runners forward the lines to logcat / os_log, from which the
+/// mobench CLI rebuilds a report whose final block never made it to the log.
+#[uniffi::export(callback_interface)]
+pub trait SampleSink: Send + Sync {
+    fn on_line(&self, line: String);
+}
+
+/// Like `run_benchmark`, streaming every measured sample to `sink`
+#[uniffi::export]
+pub fn run_benchmark_streaming(spec: BenchSpec, sink: Box<dyn SampleSink>) -> Result<BenchReport, BenchError> {
+    mobench_sdk::timing::with_sample_stream(SinkWriter::new(sink), || run_benchmark(spec))
+}
+
+/// Like `run_benchmarks_interleaved`, streaming every measured sample to `sink`
+#[uniffi::export]
+pub fn run_benchmarks_interleaved_streaming(
+    specs: Vec<BenchSpec>,
+    sink: Box<dyn SampleSink>,
+) -> Result<Vec<BenchReport>, BenchError> {
+    mobench_sdk::timing::with_sample_stream(SinkWriter::new(sink), || run_benchmarks_interleaved(specs))
+}
+
+/// Hands the sample stream to a `SampleSink` one line at a time.
+struct SinkWriter {
+    sink: Box<dyn SampleSink>,
+    pending: Vec<u8>,
+}
+
+impl SinkWriter {
+    fn new(sink: Box<dyn SampleSink>) -> Self {
+        Self { sink, pending: Vec::new() }
+    }
+}
+
+impl std::io::Write for SinkWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.sink.on_line(String::from_utf8_lossy(&line[..end]).into_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Pin this thread per `spec.pin_cores`, returning the effective affinity if requested.
+fn pin_cores(spec: &BenchSpec) -> Result<Option<String>, BenchError> {
+    let pinning = spec
+        .pin_cores
+        .as_deref()
+        .map(str::parse::<mobench_sdk::affinity::CorePinning>)
+        .transpose()
+        .map_err(|reason| BenchError::ExecutionFailed { reason })?
+        .unwrap_or_default();
+    let affinity = mobench_sdk::affinity::pin_current_thread(pinning);
+    Ok((affinity != mobench_sdk::affinity::Affinity::Unpinned).then(|| affinity.to_string()))
 }
 
 // Generate UniFFI scaffolding
@@ -341,8 +607,9 @@ pub fn generate_android_project(
 /// `app/src/main/java/dev/world/my_project/`.
 ///
 /// This function moves:
-/// - MainActivity.kt from `app/src/main/java/` to `app/src/main/java/{package_path}/`
-/// - MainActivityTest.kt from `app/src/androidTest/java/` to `app/src/androidTest/java/{package_path}/`
+/// - MainActivity.kt and StartupActivity.kt from `app/src/main/java/` to `app/src/main/java/{package_path}/`
+/// - MainActivityTest.kt and StartupBenchmarkTest.kt from `app/src/androidTest/java/` to
+///   `app/src/androidTest/java/{package_path}/`
 fn move_kotlin_files_to_package_dir(android_dir: &Path, package_name: &str) -> Result<(), BenchError> {
     // Convert package name to directory path (e.g., "dev.world.my_project" -> "dev/world/my_project")
     let package_path = package_name.replace('.', "/");
@@ -351,11 +618,13 @@ fn move_kotlin_files_to_package_dir(android_dir: &Path, package_name: &str) -> R
     let main_java_dir = android_dir.join("app/src/main/java");
     let main_package_dir = main_java_dir.join(&package_path);
     move_kotlin_file(&main_java_dir, &main_package_dir, "MainActivity.kt")?;
+    move_kotlin_file(&main_java_dir, &main_package_dir, "StartupActivity.kt")?;
 
     // Move test source files
     let test_java_dir = android_dir.join("app/src/androidTest/java");
     let test_package_dir = test_java_dir.join(&package_path);
     move_kotlin_file(&test_java_dir, &test_package_dir, "MainActivityTest.kt")?;
+    move_kotlin_file(&test_java_dir, &test_package_dir, "StartupBenchmarkTest.kt")?;
 
     Ok(())
 }
@@ -415,7 +684,41 @@ pub fn generate_ios_project(
     bundle_prefix: &str,
     default_function: &str,
 ) -> Result<(), BenchError> {
+    generate_ios_project_with_app(
+        output_dir,
+        project_slug,
+        project_pascal,
+        bundle_prefix,
+        default_function,
+        &IosAppOptions::default(),
+    )
+}
+
+/// Generates iOS project structure with a custom bundle id and Info.plist
+///
+/// Same as [`generate_ios_project`], with `app` applied to `project.yml`.
+pub fn generate_ios_project_with_app(
+    output_dir: &Path,
+    project_slug: &str,
+    project_pascal: &str,
+    bundle_prefix: &str,
+    default_function: &str,
+    app: &IosAppOptions,
+) -> Result<(), BenchError> {
+    app.validate()?;
     let target_dir = output_dir.join("ios");
+    let vars = ios_template_vars(project_slug, project_pascal, bundle_prefix, default_function, app);
+    render_dir(&IOS_TEMPLATES, &target_dir, &vars)?;
+    Ok(())
+}
+
+fn ios_template_vars(
+    project_slug: &str,
+    project_pascal: &str,
+    bundle_prefix: &str,
+    default_function: &str,
+    app: &IosAppOptions,
+) -> Vec<TemplateVar> {
     // Sanitize bundle ID components to ensure they only contain alphanumeric characters
     // iOS bundle identifiers should not contain hyphens or underscores
     let sanitized_bundle_prefix = {
@@ -428,7 +731,7 @@ pub fn generate_ios_project(
     // Use the actual app name (project_pascal, e.g., "BenchRunner") for the bundle ID suffix,
     // not the crate name again. This prevents duplication like "dev.world.benchmobile.benchmobile"
     // and produces the correct "dev.world.benchmobile.BenchRunner"
-    let vars = vec![
+    vec![
         TemplateVar {
             name: "DEFAULT_FUNCTION",
             value: default_function.to_string(),
@@ -443,14 +746,74 @@ pub fn generate_ios_project(
         },
         TemplateVar {
             name: "BUNDLE_ID",
-            value: format!("{}.{}", sanitized_bundle_prefix, project_pascal),
+            value: app
+                .bundle_id
+                .clone()
+                .unwrap_or_else(|| format!("{}.{}", sanitized_bundle_prefix, project_pascal)),
         },
         TemplateVar {
             name: "LIBRARY_NAME",
             value: project_slug.replace('-', "_"),
         },
-    ];
-    render_dir(&IOS_TEMPLATES, &target_dir, &vars)?;
+        TemplateVar {
+            name: "INFO_PROPERTIES",
+            value: render_info_properties(app),
+        },
+    ]
+}
+
+/// Renders the `info.properties` entries of `project.yml`, one per line
+///
+/// Values are written as JSON, which YAML reads unchanged, so strings,
+/// numbers, booleans, arrays and dictionaries all keep their plist type.
+fn render_info_properties(app: &IosAppOptions) -> String {
+    let mut properties = BTreeMap::new();
+    properties.insert("CFBundleShortVersionString".to_string(), serde_json::json!("1.0.0"));
+    properties.insert("CFBundleVersion".to_string(), serde_json::json!("1"));
+    if let Some(display_name) = &app.display_name {
+        properties.insert("CFBundleDisplayName".to_string(), serde_json::json!(display_name));
+    }
+    properties.extend(app.plist.clone());
+    properties
+        .iter()
+        .map(|(key, value)| {
+            let plain = key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+            let key = if plain {
+                key.clone()
+            } else {
+                serde_json::Value::String(key.clone()).to_string()
+            };
+            format!("        {}: {}", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Re-renders `ios/BenchRunner/project.yml` so `app` takes effect in an
+/// existing project; other generated files are left alone
+fn sync_ios_project_yml(
+    output_dir: &Path,
+    project_slug: &str,
+    project_pascal: &str,
+    bundle_prefix: &str,
+    app: &IosAppOptions,
+) -> Result<(), BenchError> {
+    app.validate()?;
+    let template = IOS_TEMPLATES
+        .get_file("BenchRunner/project.yml.template")
+        .and_then(|file| file.contents_utf8())
+        .ok_or_else(|| BenchError::Build("embedded project.yml template is missing".to_string()))?;
+    // project.yml does not reference the default function
+    let vars = ios_template_vars(project_slug, project_pascal, bundle_prefix, "", app);
+    let rendered = render_template(template, &vars);
+    let path = output_dir.join("ios/BenchRunner/project.yml");
+    validate_no_unreplaced_placeholders(&rendered, &path)?;
+    if fs::read_to_string(&path).ok().as_deref() != Some(rendered.as_str()) {
+        fs::write(&path, rendered)?;
+        println!("  Updated {:?} with [ios] app settings", path);
+    }
     Ok(())
 }
 
@@ -540,6 +903,145 @@ fn fibonacci(n: u32) -> u64 {
     Ok(())
 }
 
+/// Name of the criterion bench target generated by `init-sdk --with-criterion`.
+pub const CRITERION_BENCH_NAME: &str = "mobench_criterion";
+
+/// Generates a criterion harness that runs the crate's `#[benchmark]` functions
+///
+/// Writes `benches/mobench_criterion.rs` and, when `output_dir` holds the
+/// crate's `Cargo.toml`, adds the `criterion` dev-dependency and a
+/// `harness = false` bench target to it. The harness only uses the registry
+/// and `BenchSpec`, which are available whenever `#[benchmark]` is, so it
+/// builds against the crate's existing `mobench-sdk` dependency.
+fn generate_criterion_harness(output_dir: &Path, project_name: &str) -> Result<(), BenchError> {
+    let manifest_path = output_dir.join("Cargo.toml");
+    let manifest = if manifest_path.exists() {
+        Some(fs::read_to_string(&manifest_path)?)
+    } else {
+        None
+    };
+    let lib_name = manifest
+        .as_deref()
+        .and_then(manifest_lib_name)
+        .unwrap_or_else(|| project_name.replace('-', "_"));
+
+    let harness_template = r#"//! Criterion harness for the `#[benchmark]` functions in this crate
+//!
+//! Generated by `cargo mobench init-sdk --with-criterion`. Every benchmark
+//! registered in the library runs here under `cargo bench`, and the same
+//! functions run on devices with `cargo mobench run`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::time::Duration;
+
+// Ensure the library is linked so benchmark registrations are pulled in.
+extern crate {{USER_CRATE}} as _bench_user_crate;
+
+fn mobench_benchmarks(c: &mut Criterion) {
+    for bench in mobench_sdk::discover_benchmarks() {
+        if bench.ignored {
+            continue;
+        }
+        c.bench_function(bench.name, |b| {
+            // The registered runner times each iteration itself, excluding
+            // setup and teardown; report the summed measured time.
+            b.iter_custom(|iters| {
+                let iterations = iters.clamp(1, u64::from(u32::MAX)) as u32;
+                let spec = mobench_sdk::BenchSpec::new(bench.name, iterations, 0)
+                    .expect("iterations is non-zero");
+                let report = (bench.runner)(spec)
+                    .unwrap_or_else(|err| panic!("{} failed: {}", bench.name, err));
+                report
+                    .samples
+                    .iter()
+                    .map(|sample| Duration::from_nanos(sample.duration_ns))
+                    .sum()
+            })
+        });
+    }
+}
+
+criterion_group!(benches, mobench_benchmarks);
+criterion_main!(benches);
+"#;
+    let harness = render_template(
+        harness_template,
+        &[TemplateVar {
+            name: "USER_CRATE",
+            value: lib_name,
+        }],
+    );
+    let benches_dir = output_dir.join("benches");
+    fs::create_dir_all(&benches_dir)?;
+    fs::write(benches_dir.join(format!("{}.rs", CRITERION_BENCH_NAME)), harness)?;
+
+    if let Some(manifest) = manifest {
+        let wired = wire_criterion_manifest(&manifest)?;
+        if wired != manifest {
+            fs::write(&manifest_path, wired)?;
+        }
+    }
+    Ok(())
+}
+
+/// Library crate name declared by a `Cargo.toml`, with `-` mapped to `_`.
+fn manifest_lib_name(manifest: &str) -> Option<String> {
+    let table: toml::Table = manifest.parse().ok()?;
+    let name = table
+        .get("lib")
+        .and_then(|lib| lib.get("name"))
+        .or_else(|| table.get("package").and_then(|pkg| pkg.get("name")))?
+        .as_str()?;
+    Some(name.replace('-', "_"))
+}
+
+/// Adds the `criterion` dev-dependency and the criterion bench target to a
+/// `Cargo.toml`, leaving existing entries (and formatting) untouched.
+fn wire_criterion_manifest(manifest: &str) -> Result<String, BenchError> {
+    let table: toml::Table = manifest
+        .parse()
+        .map_err(|e| BenchError::Config(format!("parsing Cargo.toml: {}", e)))?;
+    let mut wired = manifest.to_string();
+
+    let dev_deps = table.get("dev-dependencies").and_then(|deps| deps.as_table());
+    if !dev_deps.is_some_and(|deps| deps.contains_key("criterion")) {
+        const HEADER: &str = "[dev-dependencies]";
+        let header = if wired.starts_with(HEADER) {
+            Some(0)
+        } else {
+            wired.find(&format!("\n{}", HEADER)).map(|newline| newline + 1)
+        };
+        match header {
+            Some(start) => wired.insert_str(start + HEADER.len(), "\ncriterion = \"0.5\""),
+            None => {
+                if !wired.ends_with('\n') {
+                    wired.push('\n');
+                }
+                wired.push_str("\n[dev-dependencies]\ncriterion = \"0.5\"\n");
+            }
+        }
+    }
+
+    let has_bench = table
+        .get("bench")
+        .and_then(|benches| benches.as_array())
+        .is_some_and(|benches| {
+            benches.iter().any(|bench| {
+                bench.get("name").and_then(|name| name.as_str()) == Some(CRITERION_BENCH_NAME)
+            })
+        });
+    if !has_bench {
+        if !wired.ends_with('\n') {
+            wired.push('\n');
+        }
+        wired.push_str(&format!(
+            "\n[[bench]]\nname = \"{}\"\nharness = false\n",
+            CRITERION_BENCH_NAME
+        ));
+    }
+    Ok(wired)
+}
+
 /// File extensions that should be processed for template variable substitution
 const TEMPLATE_EXTENSIONS: &[&str] = &[
     "gradle", "xml", "kt", "java", "swift", "yml", "yaml", "json", "toml", "md", "txt", "h", "m",
@@ -745,50 +1247,63 @@ pub fn ios_project_exists(output_dir: &Path) -> bool {
 /// * `Some(String)` - The detected function name in format `crate_name::function_name`
 /// * `None` - If no benchmark functions are found or if the file cannot be read
 pub fn detect_default_function(crate_dir: &Path, crate_name: &str) -> Option<String> {
-    let lib_rs = crate_dir.join("src/lib.rs");
-    if !lib_rs.exists() {
-        return None;
-    }
-
-    let file = fs::File::open(&lib_rs).ok()?;
-    let reader = BufReader::new(file);
-
-    let mut found_benchmark_attr = false;
-    let crate_name_normalized = crate_name.replace('-', "_");
-
-    for line in reader.lines().map_while(Result::ok) {
-        let trimmed = line.trim();
+    detect_benchmarks_with_metadata(crate_dir, crate_name)
+        .into_iter()
+        .next()
+        .map(|bench| bench.name)
+}
 
-        // Check for #[benchmark] attribute
-        if trimmed == "#[benchmark]" || trimmed.starts_with("#[benchmark(") {
-            found_benchmark_attr = true;
-            continue;
-        }
+/// A benchmark function found by scanning source code
+///
+/// Mirrors the metadata the `#[benchmark]` macro records in
+/// [`BenchFunction`](crate::registry::BenchFunction), so tooling can describe
+/// benchmarks without building the crate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetectedBenchmark {
+    /// Registered name in format `crate_name::module::name`
+    ///
+    /// Uses the `name = "..."` override when present, matching what the
+    /// runtime registry reports.
+    pub name: String,
+    /// Path of the Rust function, e.g. `crate_name::module::function`
+    ///
+    /// Differs from [`name`](Self::name) only for renamed benchmarks.
+    pub function: String,
+    /// Group from `#[benchmark(group = "...")]`
+    pub group: Option<String>,
+    /// Macro arguments, e.g. `["setup=setup_data", "per_iteration"]`
+    pub params: Vec<String>,
+    /// Whether the benchmark is marked `ignore`
+    pub ignored: bool,
+    /// Reason from `ignore = "..."`
+    pub skip_reason: Option<String>,
+    /// Whether the benchmark has a setup function
+    pub has_setup: bool,
+    /// Whether setup runs before every iteration
+    pub per_iteration: bool,
+    /// Default iteration count from `sample_size = N`
+    pub sample_size: Option<u32>,
+    /// Default warmup count from `warmup = N`
+    pub warmup: Option<u32>,
+    /// Source file that defines the benchmark, e.g. `crate_dir/src/hashing.rs`
+    ///
+    /// Benchmarks in inline modules report the file that contains the module.
+    pub source_file: PathBuf,
+}
 
-        // If we found a benchmark attribute, look for the function definition
-        if found_benchmark_attr {
-            // Look for "fn function_name" or "pub fn function_name"
-            if let Some(fn_pos) = trimmed.find("fn ") {
-                let after_fn = &trimmed[fn_pos + 3..];
-                // Extract function name (until '(' or whitespace)
-                let fn_name: String = after_fn
-                    .chars()
-                    .take_while(|c| c.is_alphanumeric() || *c == '_')
-                    .collect();
-
-                if !fn_name.is_empty() {
-                    return Some(format!("{}::{}", crate_name_normalized, fn_name));
-                }
-            }
-            // Reset if we hit a line that's not a function definition
-            // (could be another attribute or comment)
-            if !trimmed.starts_with('#') && !trimmed.starts_with("//") && !trimmed.is_empty() {
-                found_benchmark_attr = false;
-            }
+impl DetectedBenchmark {
+    /// Returns true if `function_name` refers to this benchmark.
+    ///
+    /// Accepts the registered name, the Rust function path, or either one's
+    /// final path component.
+    pub fn matches(&self, function_name: &str) -> bool {
+        let short = |path: &str| path.rsplit("::").next().unwrap_or(path).to_string();
+        if function_name.contains("::") {
+            self.name == function_name || self.function == function_name
+        } else {
+            short(&self.name) == function_name || short(&self.function) == function_name
         }
     }
-
-    None
 }
 
 /// Detects all benchmark functions in a crate by scanning src/lib.rs for `#[benchmark]`
@@ -805,54 +1320,186 @@ pub fn detect_default_function(crate_dir: &Path, crate_name: &str) -> Option<Str
 ///
 /// A vector of benchmark function names in format `crate_name::function_name`
 pub fn detect_all_benchmarks(crate_dir: &Path, crate_name: &str) -> Vec<String> {
+    detect_benchmarks_with_metadata(crate_dir, crate_name)
+        .into_iter()
+        .map(|bench| bench.name)
+        .collect()
+}
+
+/// Detects all benchmark functions in a crate along with their attribute metadata
+///
+/// Parses `src/lib.rs` with `syn`, following inline modules and `mod foo;`
+/// declarations, and reads the `#[benchmark(...)]` arguments (`setup`,
+/// `teardown`, `per_iteration`, `name`, `group`, `ignore`, ...). Names are
+/// built the same way the macro builds them, so renamed benchmarks and
+/// benchmarks in submodules resolve to what the registry reports at runtime.
+pub fn detect_benchmarks_with_metadata(crate_dir: &Path, crate_name: &str) -> Vec<DetectedBenchmark> {
     let lib_rs = crate_dir.join("src/lib.rs");
-    if !lib_rs.exists() {
-        return Vec::new();
-    }
+    let crate_name_normalized = crate_name.replace('-', "_");
+    let mut benchmarks = Vec::new();
+    scan_module_file(&lib_rs, &crate_dir.join("src"), &crate_name_normalized, &mut benchmarks);
+    benchmarks
+}
 
-    let Ok(file) = fs::File::open(&lib_rs) else {
-        return Vec::new();
+/// Parses one module file and collects its benchmarks.
+///
+/// `module_dir` is where child `mod foo;` files live.
+fn scan_module_file(
+    file: &Path,
+    module_dir: &Path,
+    module_path: &str,
+    benchmarks: &mut Vec<DetectedBenchmark>,
+) {
+    let Ok(source) = fs::read_to_string(file) else {
+        return;
     };
-    let reader = BufReader::new(file);
+    let Ok(parsed) = syn::parse_file(&source) else {
+        return;
+    };
+    scan_items(&parsed.items, file, module_dir, module_path, benchmarks);
+}
 
-    let mut benchmarks = Vec::new();
-    let mut found_benchmark_attr = false;
-    let crate_name_normalized = crate_name.replace('-', "_");
+fn scan_items(
+    items: &[syn::Item],
+    file: &Path,
+    module_dir: &Path,
+    module_path: &str,
+    benchmarks: &mut Vec<DetectedBenchmark>,
+) {
+    for item in items {
+        match item {
+            syn::Item::Fn(item_fn) => {
+                let Some(attr) = item_fn.attrs.iter().find(|a| is_benchmark_attr(a)) else {
+                    continue;
+                };
+                let fn_name = item_fn.sig.ident.to_string();
+                if let Some(mut bench) = parse_benchmark_attr(attr, module_path, &fn_name) {
+                    bench.source_file = file.to_path_buf();
+                    benchmarks.push(bench);
+                }
+            }
+            syn::Item::Mod(item_mod) => {
+                // Skip test-only modules; their benchmarks are not in release builds
+                if item_mod.attrs.iter().any(is_cfg_test) {
+                    continue;
+                }
+                let mod_name = item_mod.ident.to_string();
+                let child_path = format!("{}::{}", module_path, mod_name);
+                let child_dir = module_dir.join(&mod_name);
+                match &item_mod.content {
+                    Some((_, items)) => {
+                        scan_items(items, file, &child_dir, &child_path, benchmarks)
+                    }
+                    None => {
+                        let flat = module_dir.join(format!("{}.rs", mod_name));
+                        let nested = child_dir.join("mod.rs");
+                        let child_file = if flat.exists() { flat } else { nested };
+                        scan_module_file(&child_file, &child_dir, &child_path, benchmarks);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
 
-    for line in reader.lines().map_while(Result::ok) {
-        let trimmed = line.trim();
+/// Matches `#[benchmark]` and `#[mobench_sdk::benchmark]`.
+fn is_benchmark_attr(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "benchmark")
+}
 
-        // Check for #[benchmark] attribute
-        if trimmed == "#[benchmark]" || trimmed.starts_with("#[benchmark(") {
-            found_benchmark_attr = true;
-            continue;
-        }
+fn is_cfg_test(attr: &syn::Attribute) -> bool {
+    attr.path().is_ident("cfg")
+        && attr
+            .parse_args::<syn::Ident>()
+            .is_ok_and(|ident| ident == "test")
+}
 
-        // If we found a benchmark attribute, look for the function definition
-        if found_benchmark_attr {
-            // Look for "fn function_name" or "pub fn function_name"
-            if let Some(fn_pos) = trimmed.find("fn ") {
-                let after_fn = &trimmed[fn_pos + 3..];
-                // Extract function name (until '(' or whitespace)
-                let fn_name: String = after_fn
-                    .chars()
-                    .take_while(|c| c.is_alphanumeric() || *c == '_')
-                    .collect();
-
-                if !fn_name.is_empty() {
-                    benchmarks.push(format!("{}::{}", crate_name_normalized, fn_name));
+fn parse_benchmark_attr(
+    attr: &syn::Attribute,
+    module_path: &str,
+    fn_name: &str,
+) -> Option<DetectedBenchmark> {
+    let mut bench = DetectedBenchmark {
+        function: format!("{}::{}", module_path, fn_name),
+        ..DetectedBenchmark::default()
+    };
+    let mut registered_name = fn_name.to_string();
+
+    if matches!(attr.meta, syn::Meta::List(_)) {
+        attr.parse_nested_meta(|meta| {
+            let key = meta
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            let value = if meta.input.peek(syn::Token![=]) {
+                Some(expr_to_string(&meta.value()?.parse::<syn::Expr>()?))
+            } else {
+                None
+            };
+            match (key.as_str(), value) {
+                ("name", Some(value)) => registered_name = value,
+                ("group", value) => bench.group = value,
+                ("ignore", value) => {
+                    bench.ignored = true;
+                    bench.skip_reason = value;
                 }
-                found_benchmark_attr = false;
-            }
-            // Reset if we hit a line that's not a function definition
-            // (could be another attribute or comment)
-            if !trimmed.starts_with('#') && !trimmed.starts_with("//") && !trimmed.is_empty() {
-                found_benchmark_attr = false;
+                ("setup", Some(value)) => {
+                    bench.has_setup = true;
+                    bench.params.push(format!("setup={}", value));
+                }
+                ("per_iteration", None) => {
+                    bench.per_iteration = true;
+                    bench.params.push(key);
+                }
+                ("sample_size", Some(value)) => {
+                    bench.sample_size = value.parse().ok();
+                    bench.params.push(format!("{}={}", key, value));
+                }
+                ("warmup", Some(value)) => {
+                    bench.warmup = value.parse().ok();
+                    bench.params.push(format!("{}={}", key, value));
+                }
+                (_, Some(value)) => bench.params.push(format!("{}={}", key, value)),
+                (_, None) => bench.params.push(key),
             }
-        }
+            Ok(())
+        })
+        .ok()?;
     }
 
-    benchmarks
+    bench.name = format!("{}::{}", module_path, registered_name);
+    Some(bench)
+}
+
+/// Renders an attribute value: string literals unquoted, paths joined with `::`.
+fn expr_to_string(expr: &syn::Expr) -> String {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit),
+            ..
+        }) => lit.value(),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_digits().to_string(),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Bool(lit),
+            ..
+        }) => lit.value.to_string(),
+        syn::Expr::Path(path) => path
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::"),
+        _ => "..".to_string(),
+    }
 }
 
 /// Validates that a benchmark function exists in the crate source
@@ -867,17 +1514,9 @@ pub fn detect_all_benchmarks(crate_dir: &Path, crate_name: &str) -> Vec<String>
 ///
 /// `true` if the function is found, `false` otherwise
 pub fn validate_benchmark_exists(crate_dir: &Path, crate_name: &str, function_name: &str) -> bool {
-    let benchmarks = detect_all_benchmarks(crate_dir, crate_name);
-    let crate_name_normalized = crate_name.replace('-', "_");
-
-    // Normalize the function name - add crate prefix if missing
-    let normalized_name = if function_name.contains("::") {
-        function_name.to_string()
-    } else {
-        format!("{}::{}", crate_name_normalized, function_name)
-    };
-
-    benchmarks.iter().any(|b| b == &normalized_name)
+    detect_benchmarks_with_metadata(crate_dir, crate_name)
+        .iter()
+        .any(|bench| bench.matches(function_name))
 }
 
 /// Resolves the default benchmark function for a project
@@ -1006,11 +1645,27 @@ pub fn ensure_ios_project_with_options(
     project_root: Option<&Path>,
     crate_dir: Option<&Path>,
 ) -> Result<(), BenchError> {
-    if ios_project_exists(output_dir) {
-        return Ok(());
-    }
+    ensure_ios_project_with_app(
+        output_dir,
+        crate_name,
+        project_root,
+        crate_dir,
+        &IosAppOptions::default(),
+    )
+}
 
-    println!("iOS project not found, generating scaffolding...");
+/// Auto-generates iOS project scaffolding with a custom bundle id and Info.plist
+///
+/// Same as [`ensure_ios_project_with_options`]. When `app` is not the
+/// default and the project already exists, `project.yml` is re-rendered so
+/// changed settings take effect on the next build.
+pub fn ensure_ios_project_with_app(
+    output_dir: &Path,
+    crate_name: &str,
+    project_root: Option<&Path>,
+    crate_dir: Option<&Path>,
+    app: &IosAppOptions,
+) -> Result<(), BenchError> {
     // Use fixed "BenchRunner" for project/scheme name to match template directory structure
     let project_pascal = "BenchRunner";
     // Derive library name and bundle prefix from crate name
@@ -1020,13 +1675,29 @@ pub fn ensure_ios_project_with_options(
     let bundle_id_component = sanitize_bundle_id_component(crate_name);
     let bundle_prefix = format!("dev.world.{}", bundle_id_component);
 
+    if ios_project_exists(output_dir) {
+        if !app.is_default() {
+            sync_ios_project_yml(output_dir, &library_name, project_pascal, &bundle_prefix, app)?;
+        }
+        return Ok(());
+    }
+
+    println!("iOS project not found, generating scaffolding...");
+
     // Resolve the default function by auto-detecting from source
     let effective_root = project_root.unwrap_or_else(|| {
         output_dir.parent().unwrap_or(output_dir)
     });
     let default_function = resolve_default_function(effective_root, crate_name, crate_dir);
 
-    generate_ios_project(output_dir, &library_name, project_pascal, &bundle_prefix, &default_function)?;
+    generate_ios_project_with_app(
+        output_dir,
+        &library_name,
+        project_pascal,
+        &bundle_prefix,
+        &default_function,
+        app,
+    )?;
     println!("  Generated iOS project at {:?}", output_dir.join("ios"));
     println!("  Default benchmark function: {}", default_function);
     Ok(())
@@ -1035,6 +1706,60 @@ pub fn ensure_ios_project_with_options(
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_validate_bundle_id() {
+        assert!(validate_bundle_id("com.example.bench").is_ok());
+        assert!(validate_bundle_id("com.my-team.Bench2").is_ok());
+        assert!(validate_bundle_id("bench").is_err());
+        assert!(validate_bundle_id("com..bench").is_err());
+        assert!(validate_bundle_id("com.example.").is_err());
+        assert!(validate_bundle_id("com.example.bench_app").is_err());
+        assert!(validate_bundle_id("com.example.bench app").is_err());
+    }
+
+    #[test]
+    fn test_ios_app_options_render_into_project_yml() {
+        let temp_dir = env::temp_dir().join("mobench-sdk-ios-app-options-test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let mut app = IosAppOptions {
+            bundle_id: Some("com.acme.perf".to_string()),
+            display_name: Some("Acme Bench".to_string()),
+            ..Default::default()
+        };
+        app.plist.insert("UIRequiresFullScreen".to_string(), serde_json::json!(true));
+        app.plist.insert("CFBundleVersion".to_string(), serde_json::json!("42"));
+        generate_ios_project_with_app(
+            &temp_dir,
+            "bench_mobile",
+            "BenchRunner",
+            "dev.world.benchmobile",
+            "bench_mobile::f",
+            &app,
+        )
+        .unwrap();
+
+        let yml_path = temp_dir.join("ios/BenchRunner/project.yml");
+        let yml = fs::read_to_string(&yml_path).unwrap();
+        assert!(yml.contains("PRODUCT_BUNDLE_IDENTIFIER: com.acme.perf\n"));
+        assert!(yml.contains("PRODUCT_BUNDLE_IDENTIFIER: com.acme.perf.uitests"));
+        assert!(yml.contains("        CFBundleDisplayName: \"Acme Bench\"\n"));
+        assert!(yml.contains("        UIRequiresFullScreen: true"));
+        assert!(yml.contains("        CFBundleVersion: \"42\""));
+        assert!(!yml.contains("CFBundleVersion: \"1\""));
+
+        // Changing the settings later rewrites project.yml in place
+        app.bundle_id = Some("com.acme.perf2".to_string());
+        ensure_ios_project_with_app(&temp_dir, "bench_mobile", None, None, &app).unwrap();
+        let yml = fs::read_to_string(&yml_path).unwrap();
+        assert!(yml.contains("PRODUCT_BUNDLE_IDENTIFIER: com.acme.perf2\n"));
+
+        app.bundle_id = Some("perf".to_string());
+        assert!(ensure_ios_project_with_app(&temp_dir, "bench_mobile", None, None, &app).is_err());
+
+        fs::remove_dir_all(&temp_dir).ok();
+    }
     use std::env;
 
     #[test]
@@ -1049,11 +1774,43 @@ mod tests {
         assert!(temp_dir.join("bench-mobile/Cargo.toml").exists());
         assert!(temp_dir.join("bench-mobile/src/lib.rs").exists());
         assert!(temp_dir.join("bench-mobile/build.rs").exists());
+        // The runners stream samples to the device log through this entry point
+        let lib_rs = fs::read_to_string(temp_dir.join("bench-mobile/src/lib.rs")).unwrap();
+        assert!(lib_rs.contains("pub fn run_benchmark_streaming("));
+        // Invalid sampling / warmup JSON is an error, not a silent default
+        assert!(lib_rs.contains("impl TryFrom<BenchSpec> for mobench_sdk::BenchSpec"));
 
         // Cleanup
         fs::remove_dir_all(&temp_dir).ok();
     }
 
+    #[test]
+    fn test_generate_criterion_harness_wires_manifest() {
+        let temp_dir = env::temp_dir().join("mobench-sdk-criterion-test");
+        fs::remove_dir_all(&temp_dir).ok();
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(
+            temp_dir.join("Cargo.toml"),
+            "[package]\nname = \"my-benches\"\n\n[dev-dependencies]\ntempfile = \"3\"\n",
+        )
+        .unwrap();
+
+        generate_criterion_harness(&temp_dir, "ignored-name").unwrap();
+        // A second run must not duplicate the wiring
+        generate_criterion_harness(&temp_dir, "ignored-name").unwrap();
+
+        let harness = fs::read_to_string(temp_dir.join("benches/mobench_criterion.rs")).unwrap();
+        assert!(harness.contains("extern crate my_benches as _bench_user_crate;"));
+        assert!(!harness.contains("{{"));
+        let manifest = fs::read_to_string(temp_dir.join("Cargo.toml")).unwrap();
+        assert_eq!(
+            manifest,
+            "[package]\nname = \"my-benches\"\n\n[dev-dependencies]\ncriterion = \"0.5\"\ntempfile = \"3\"\n\n[[bench]]\nname = \"mobench_criterion\"\nharness = false\n"
+        );
+
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
     #[test]
     fn test_generate_android_project_no_unreplaced_placeholders() {
         let temp_dir = env::temp_dir().join("mobench-sdk-android-test");
@@ -1147,6 +1904,16 @@ mod tests {
             "MainActivityTest.kt should not be in root java directory"
         );
 
+        // Startup benchmarks: the probe activity and its instrumentation test
+        let package_dir = "dev/world/mybenchproject";
+        assert!(android_dir.join(format!("app/src/main/java/{package_dir}/StartupActivity.kt")).exists());
+        assert!(
+            android_dir
+                .join(format!("app/src/androidTest/java/{package_dir}/StartupBenchmarkTest.kt"))
+                .exists()
+        );
+        assert!(manifest.contains("android:process=\":startup\""));
+
         // Cleanup
         fs::remove_dir_all(&temp_dir).ok();
     }
@@ -1258,6 +2025,91 @@ pub fn public_bench() {
         fs::remove_dir_all(&temp_dir).ok();
     }
 
+    #[test]
+    fn test_detect_benchmarks_with_metadata() {
+        let temp_dir = env::temp_dir().join("mobench-sdk-detect-metadata-test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("src")).unwrap();
+        fs::write(
+            temp_dir.join("src/lib.rs"),
+            r#"
+#[benchmark]
+pub fn plain() {}
+
+#[benchmark(setup = make_input, per_iteration, group = "sorting")]
+pub fn sort(input: Vec<u32>) {}
+
+#[benchmark(ignore = "needs GPU, skipped on CI")]
+pub fn gpu() {}
+
+#[benchmark(sample_size = 1000, warmup = 50)]
+pub fn hash_small() {}
+"#,
+        )
+        .unwrap();
+
+        let found = detect_benchmarks_with_metadata(&temp_dir, "my-crate");
+        assert_eq!(found.len(), 4);
+        assert_eq!(found[0].name, "my_crate::plain");
+        assert!(found[0].params.is_empty());
+
+        assert_eq!(found[1].group.as_deref(), Some("sorting"));
+        assert!(found[1].has_setup);
+        assert_eq!(found[1].params, vec!["setup=make_input", "per_iteration"]);
+
+        assert!(found[2].ignored);
+        assert_eq!(found[2].skip_reason.as_deref(), Some("needs GPU, skipped on CI"));
+
+        assert_eq!((found[3].sample_size, found[3].warmup), (Some(1000), Some(50)));
+        assert_eq!(found[3].params, vec!["sample_size=1000", "warmup=50"]);
+
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_detect_benchmarks_follows_modules_and_renames() {
+        let temp_dir = env::temp_dir().join("mobench-sdk-detect-modules-test");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("src")).unwrap();
+        fs::write(
+            temp_dir.join("src/lib.rs"),
+            r#"
+mod hashing;
+
+pub mod inline {
+    #[mobench_sdk::benchmark(name = "renamed")]
+    pub fn original() {}
+}
+
+#[cfg(test)]
+mod tests {
+    #[benchmark]
+    fn not_shipped() {}
+}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.join("src/hashing.rs"),
+            "#[benchmark(setup = make_input)]\npub fn sha(input: &[u8]) {}\n",
+        )
+        .unwrap();
+
+        let found = detect_benchmarks_with_metadata(&temp_dir, "my_crate");
+        let names: Vec<_> = found.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["my_crate::hashing::sha", "my_crate::inline::renamed"]);
+        assert_eq!(found[1].function, "my_crate::inline::original");
+        assert_eq!(found[0].source_file, temp_dir.join("src/hashing.rs"));
+        assert_eq!(found[1].source_file, temp_dir.join("src/lib.rs"));
+
+        assert!(validate_benchmark_exists(&temp_dir, "my_crate", "renamed"));
+        assert!(validate_benchmark_exists(&temp_dir, "my_crate", "my_crate::inline::renamed"));
+        assert!(validate_benchmark_exists(&temp_dir, "my_crate", "sha"));
+        assert!(!validate_benchmark_exists(&temp_dir, "my_crate", "not_shipped"));
+
+        fs::remove_dir_all(&temp_dir).ok();
+    }
+
     #[test]
     fn test_resolve_default_function_fallback() {
         let temp_dir = env::temp_dir().join("mobench-sdk-resolve-test");
@@ -0,0 +1,172 @@
+//! C ABI entry points for hosts that cannot use UniFFI.
+//!
+//! Some harnesses (Flutter via `dart:ffi`, React Native JSI modules, plain C
+//! test drivers) can call a C function but cannot consume UniFFI bindings.
+//! This module exposes the benchmark registry through two `extern "C"`
+//! functions that exchange JSON strings:
+//!
+//! - [`run_benchmark_c`] takes a JSON [`BenchSpec`] and returns a JSON
+//!   [`RunnerReport`](crate::RunnerReport), or an error envelope.
+//! - [`mobench_free_string`] releases a string returned by [`run_benchmark_c`].
+//!
+//! ## Error Envelope
+//!
+//! On failure the returned JSON has a single `error` object:
+//!
+//! ```json
+//! {"error": {"kind": "unknown_function", "message": "unknown benchmark function: ..."}}
+//! ```
+//!
+//! `kind` is one of `invalid_argument`, `invalid_spec`, `runner`,
+//! `unknown_function`, `execution`, `io`, `serialization`, `config`, `build`,
+//! or `panic`.
+//!
+//! ## Example (C)
+//!
+//! ```c
+//! char *report = run_benchmark_c("{\"name\":\"my_crate::fib\",\"iterations\":50,\"warmup\":5}");
+//! /* ... parse report ... */
+//! mobench_free_string(report);
+//! ```
+
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+
+use serde_json::json;
+
+use crate::{BenchError, BenchSpec};
+
+/// Runs a registered benchmark described by a JSON [`BenchSpec`].
+///
+/// Returns a newly allocated, NUL-terminated JSON string containing either
+/// the benchmark report or an error envelope. The result is never null and
+/// must be released with [`mobench_free_string`].
+///
+/// # Safety
+///
+/// `spec_json` must be null or point to a valid NUL-terminated string that
+/// stays alive for the duration of the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn run_benchmark_c(spec_json: *const c_char) -> *mut c_char {
+    let input = if spec_json.is_null() {
+        None
+    } else {
+        // SAFETY: the caller guarantees a valid NUL-terminated string.
+        Some(unsafe { CStr::from_ptr(spec_json) })
+    };
+
+    let response = panic::catch_unwind(AssertUnwindSafe(|| run_from_json(input)))
+        .unwrap_or_else(|payload| error_envelope("panic", &panic_message(&payload)));
+
+    into_c_string(response)
+}
+
+/// Frees a string returned by [`run_benchmark_c`].
+///
+/// Passing null is a no-op.
+///
+/// # Safety
+///
+/// `ptr` must be null or a pointer previously returned by [`run_benchmark_c`]
+/// that has not already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mobench_free_string(ptr: *mut c_char) {
+    if ptr.is_null() {
+        return;
+    }
+    // SAFETY: the caller guarantees `ptr` came from `CString::into_raw`.
+    drop(unsafe { CString::from_raw(ptr) });
+}
+
+fn run_from_json(input: Option<&CStr>) -> String {
+    let Some(input) = input else {
+        return error_envelope("invalid_argument", "spec_json is null");
+    };
+    let Ok(text) = input.to_str() else {
+        return error_envelope("invalid_argument", "spec_json is not valid UTF-8");
+    };
+    let spec: BenchSpec = match serde_json::from_str(text) {
+        Ok(spec) => spec,
+        Err(err) => return error_envelope("invalid_spec", &err.to_string()),
+    };
+
+    let result = crate::run_benchmark(spec)
+        .and_then(|report| serde_json::to_string(&report).map_err(BenchError::from));
+    match result {
+        Ok(json) => json,
+        Err(err) => error_envelope(error_kind(&err), &err.to_string()),
+    }
+}
+
+fn error_kind(err: &BenchError) -> &'static str {
+    match err {
+        BenchError::Runner(_) => "runner",
+        BenchError::UnknownFunction(..) => "unknown_function",
+        BenchError::Execution(_) => "execution",
+        BenchError::Io(_) => "io",
+        BenchError::Serialization(_) => "serialization",
+        BenchError::Config(_) => "config",
+        BenchError::Build(_) => "build",
+    }
+}
+
+fn error_envelope(kind: &str, message: &str) -> String {
+    json!({ "error": { "kind": kind, "message": message } }).to_string()
+}
+
+fn panic_message(payload: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        format!("benchmark panicked: {msg}")
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        format!("benchmark panicked: {msg}")
+    } else {
+        "benchmark panicked".to_string()
+    }
+}
+
+fn into_c_string(text: String) -> *mut c_char {
+    // JSON output escapes control characters, so interior NULs only appear if
+    // something upstream is badly broken; fall back to a static envelope then.
+    CString::new(text)
+        .unwrap_or_else(|_| {
+            CString::new(error_envelope("serialization", "response contained a NUL byte"))
+                .expect("static envelope has no NUL bytes")
+        })
+        .into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(input: Option<&str>) -> serde_json::Value {
+        let owned = input.map(|s| CString::new(s).unwrap());
+        let ptr = owned.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+        unsafe {
+            let out = run_benchmark_c(ptr);
+            assert!(!out.is_null());
+            let value = serde_json::from_str(CStr::from_ptr(out).to_str().unwrap()).unwrap();
+            mobench_free_string(out);
+            value
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        let value = call(Some("not json"));
+        assert_eq!(value["error"]["kind"], "invalid_spec");
+    }
+
+    #[test]
+    fn reports_unknown_function() {
+        let value = call(Some(r#"{"name":"nope::missing","iterations":3,"warmup":0}"#));
+        assert_eq!(value["error"]["kind"], "unknown_function");
+        assert!(value["error"]["message"].as_str().unwrap().contains("nope::missing"));
+    }
+
+    #[test]
+    fn handles_null_pointers() {
+        assert_eq!(call(None)["error"]["kind"], "invalid_argument");
+        unsafe { mobench_free_string(std::ptr::null_mut()) };
+    }
+}
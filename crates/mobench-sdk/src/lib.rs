@@ -115,10 +115,13 @@
 //! | Module | Description |
 //! |--------|-------------|
 //! | [`timing`] | Core timing infrastructure (always available) |
+//! | [`affinity`] | Big/little core pinning for the benchmark thread (always available) |
+//! | [`stats`] | Mean, median, percentiles, CV and median of means over samples (always available) |
 //! | [`registry`] | Runtime discovery of `#[benchmark]` functions (requires `full` feature) |
 //! | [`runner`] | Benchmark execution engine (requires `full` feature) |
 //! | [`builders`] | Android and iOS build automation (requires `full` feature) |
 //! | [`codegen`] | Mobile app template generation (requires `full` feature) |
+//! | [`orchestrate`] | Upload, schedule and fetch runs on a device farm (requires `full` feature) |
 //! | [`types`] | Common types and error definitions |
 //!
 //! ## Crate Ecosystem
@@ -137,6 +140,10 @@
 //! |---------|---------|-------------|
 //! | `full` | Yes | Full SDK with build automation, templates, and registry |
 //! | `runner-only` | No | Minimal timing-only mode for mobile binaries |
+//! | `c-abi` | No | `extern "C"` JSON entry points in the [`ffi`] module for non-UniFFI hosts |
+//! | `precise-clock` | No | Time iterations with `CLOCK_MONOTONIC_RAW` (Android) or `mach_absolute_time` (iOS) instead of `Instant` |
+//! | `cpu-time` | No | Record each iteration's thread CPU time next to wall-clock time |
+//! | `core-pinning` | No | Pin the benchmark thread to big or little cores on Linux/Android (see [`affinity`]) |
 //!
 //! For mobile binaries where binary size matters, use `runner-only`:
 //!
@@ -173,6 +180,13 @@
 //!     name: "my_benchmark".to_string(),
 //!     iterations: 50,
 //!     warmup: 5,
+//!     warmup_policy: Default::default(),
+//!     sampling: Default::default(),
+//!     per_iteration_timeout: None,
+//!     timeout: None,
+//!     capture_warmup: false,
+//!     seed: None,
+//!     drop_first_n: 0,
 //! };
 //!
 //! let report = run_benchmark(spec)?;
@@ -215,6 +229,8 @@
 //!     target: Target::Android,
 //!     profile: BuildProfile::Release,
 //!     incremental: true,
+//!     cache: true,
+//!     cargo_profile: None,
 //! };
 //!
 //! let result = builder.build(&config)?;
@@ -234,6 +250,8 @@
 //!     target: Target::Ios,
 //!     profile: BuildProfile::Release,
 //!     incremental: true,
+//!     cache: true,
+//!     cargo_profile: None,
 //! };
 //!
 //! let result = builder.build(&config)?;
@@ -319,6 +337,8 @@
 //! - **Warmup**: 5-10 iterations to warm CPU caches and JIT
 //! - **Iterations**: 50-100 for stable statistics
 //! - Mobile devices may have more variance than desktop
+//! - Benchmarks that need more samples can say so with
+//!   `#[benchmark(sample_size = 1000)]`; explicit counts still take precedence
 //!
 //! ## License
 //!
@@ -327,6 +347,8 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 // Core timing module - always available
+pub mod affinity;
+pub mod stats;
 pub mod timing;
 pub mod types;
 
@@ -343,10 +365,16 @@ pub mod builders;
 pub mod codegen;
 #[cfg(feature = "full")]
 #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
+pub mod orchestrate;
+#[cfg(feature = "full")]
+#[cfg_attr(docsrs, doc(cfg(feature = "full")))]
 pub mod registry;
 #[cfg(feature = "full")]
 #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
 pub mod runner;
+#[cfg(feature = "c-abi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "c-abi")))]
+pub mod ffi;
 
 // Re-export the benchmark macro from bench-macros (only with full feature)
 #[cfg(feature = "full")]
@@ -356,10 +384,13 @@ pub use mobench_macros::benchmark;
 // Re-export key types for convenience (full feature)
 #[cfg(feature = "full")]
 #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
-pub use registry::{BenchFunction, discover_benchmarks, find_benchmark, list_benchmark_names};
+pub use registry::{
+    BenchFunction, ExecutionOrder, discover_benchmarks, discover_benchmarks_ordered, find_benchmark,
+    list_benchmark_names,
+};
 #[cfg(feature = "full")]
 #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
-pub use runner::{BenchmarkBuilder, run_benchmark};
+pub use runner::{BenchmarkBuilder, USE_BENCHMARK_DEFAULT, run_benchmark, run_benchmarks_interleaved};
 
 // Re-export types that are always available
 pub use types::{BenchError, BenchSample, BenchSpec, RunnerReport};
@@ -367,10 +398,13 @@ pub use types::{BenchError, BenchSample, BenchSpec, RunnerReport};
 // Re-export types that require full feature
 #[cfg(feature = "full")]
 #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
-pub use types::{BuildConfig, BuildProfile, BuildResult, InitConfig, Target};
+pub use types::{BuildConfig, BuildProfile, BuildResult, InitConfig, NativeLibSize, Target};
 
 // Re-export timing types at the crate root for convenience
-pub use timing::{run_closure, TimingError};
+pub use timing::{
+    record_metric, run_closure, CiTarget, Clock, ClockSource, MedianCi, SamplingMode, SeededRng,
+    TimingError, WarmupPolicy,
+};
 
 /// Library version, matching `Cargo.toml`.
 ///
@@ -0,0 +1,620 @@
+//! Device-farm orchestration: upload, schedule, wait, fetch.
+//!
+//! [`run_on_devices`] drives a complete remote run through a [`DeviceBackend`]:
+//! it uploads the app and test suite, schedules them on the requested devices,
+//! waits for the build, fetches each device's `BENCH_REPORT_JSON` and
+//! re-schedules devices that finished without one. The backend supplies the
+//! service-specific API calls; the `mobench` crate implements it for
+//! BrowserStack App Automate as `mobench::browserstack::BrowserStackBackend`.
+//!
+//! The two halves are also available separately: [`start_run`] uploads and
+//! schedules, and [`collect_results`] waits for an already scheduled run, so a
+//! caller can start a run now and collect it later from the stored
+//! [`ScheduledRun`].
+//!
+//! By default the first failure aborts the run. With
+//! [`RunOptions::fail_fast`] off, [`start_run_each`] falls back to scheduling
+//! devices one at a time and [`collect_results`] records failed fetches as
+//! missing devices, so one bad device does not cost the results of the rest.
+//! A failed re-schedule never aborts [`collect_results`], since the results
+//! already fetched would be lost; it is recorded in
+//! [`RunSummary::unscheduled`].
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use mobench_sdk::orchestrate::{DeviceRunSpec, RunArtifacts, RunOptions, run_on_devices};
+//!
+//! let spec = DeviceRunSpec {
+//!     devices: vec!["Google Pixel 7-13.0".into()],
+//!     artifacts: RunArtifacts {
+//!         app: "app-debug.apk".into(),
+//!         test_suite: "app-debug-androidTest.apk".into(),
+//!     },
+//! };
+//! let summary = run_on_devices(&spec, &my_backend, &RunOptions::default())?;
+//! for (device, reports) in &summary.results.benchmarks {
+//!     println!("{device}: {} report(s)", reports.len());
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// App and test-suite bundles to upload (APK/AAB and test APK on Android,
+/// IPA and XCUITest runner zip on iOS).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunArtifacts {
+    /// The app under test.
+    pub app: PathBuf,
+    /// The instrumentation or XCUITest suite that drives the app.
+    pub test_suite: PathBuf,
+}
+
+/// What to run and where, for [`run_on_devices`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceRunSpec {
+    /// Device specs understood by the backend, e.g. `Google Pixel 7-13.0`.
+    pub devices: Vec<String>,
+    /// Bundles to upload.
+    pub artifacts: RunArtifacts,
+}
+
+/// Backend references to uploaded artifacts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadedArtifacts {
+    /// Handle of the uploaded app (e.g. `bs://...`).
+    pub app_url: String,
+    /// Handle of the uploaded test suite.
+    pub test_suite_url: String,
+}
+
+/// A run scheduled on the backend; enough to collect it or re-schedule devices.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledRun {
+    /// Handle of the uploaded app.
+    pub app_url: String,
+    /// Handle of the uploaded test suite. Devices are only re-scheduled when
+    /// this is non-empty.
+    pub test_suite_url: String,
+    /// Backend build identifier.
+    pub build_id: String,
+}
+
+/// Wait and retry settings for a remote run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunOptions {
+    /// How long to wait for each build to finish.
+    pub timeout: Duration,
+    /// How often to poll the build status.
+    pub poll_interval: Duration,
+    /// How many times to re-schedule devices that finished without a report.
+    pub device_retries: u32,
+    /// Abort on the first failed fetch (the default). When false, failures
+    /// are reported as events and the affected devices end up in
+    /// [`RunSummary::missing`].
+    pub fail_fast: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(300),
+            poll_interval: Duration::from_secs(5),
+            device_retries: 0,
+            fail_fast: true,
+        }
+    }
+}
+
+/// Per-device results fetched from a finished build, keyed by the device name
+/// the backend reported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceResults<M> {
+    /// Benchmark reports (`BENCH_REPORT_JSON` payloads) from each device.
+    pub benchmarks: BTreeMap<String, Vec<Value>>,
+    /// Backend-specific metrics, e.g. memory and CPU snapshots.
+    pub metrics: BTreeMap<String, M>,
+    /// Hardware and thermal context for each device.
+    pub device_meta: BTreeMap<String, serde_json::Map<String, Value>>,
+}
+
+impl<M> Default for DeviceResults<M> {
+    fn default() -> Self {
+        Self {
+            benchmarks: BTreeMap::new(),
+            metrics: BTreeMap::new(),
+            device_meta: BTreeMap::new(),
+        }
+    }
+}
+
+impl<M> DeviceResults<M> {
+    /// Adds `other`'s devices, replacing entries for devices already present.
+    pub fn merge(&mut self, other: DeviceResults<M>) {
+        self.benchmarks.extend(other.benchmarks);
+        self.metrics.extend(other.metrics);
+        self.device_meta.extend(other.device_meta);
+    }
+}
+
+/// Result of [`run_on_devices`] or [`collect_results`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary<M> {
+    /// The initial run.
+    pub run: ScheduledRun,
+    /// Results merged across the initial run and any retries.
+    pub results: DeviceResults<M>,
+    /// Re-scheduled devices, with the number of retries each took (or used
+    /// up, if it never reported).
+    pub retried: BTreeMap<String, u32>,
+    /// Requested devices that never produced a report.
+    pub missing: Vec<String>,
+    /// Missing devices whose re-scheduling failed, with the backend's error.
+    pub unscheduled: BTreeMap<String, String>,
+}
+
+/// Builds started by [`start_run_each`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StartedRuns {
+    /// Each build with the devices it was scheduled on.
+    pub runs: Vec<(ScheduledRun, Vec<String>)>,
+    /// Devices that could not be scheduled, with the backend's error.
+    pub unscheduled: BTreeMap<String, String>,
+}
+
+/// Progress reported to [`DeviceBackend::on_event`].
+#[non_exhaustive]
+pub enum RunEvent<'a> {
+    /// Fetching a build's results failed; `attempt` is 0 for the initial run.
+    FetchFailed {
+        attempt: u32,
+        build_id: &'a str,
+        error: &'a dyn Display,
+    },
+    /// Scheduling `devices` failed and the run carries on without them.
+    ScheduleFailed {
+        devices: &'a [String],
+        error: &'a dyn Display,
+    },
+    /// Devices without a report were scheduled again as `build_id`.
+    Rescheduled {
+        attempt: u32,
+        retries: u32,
+        build_id: &'a str,
+        devices: &'a [String],
+    },
+    /// Re-scheduling `devices` failed; the run keeps the results it has.
+    RescheduleFailed {
+        attempt: u32,
+        devices: &'a [String],
+        error: &'a dyn Display,
+    },
+}
+
+/// Service-specific steps of a remote run.
+///
+/// Implementations translate each step into API calls; [`run_on_devices`]
+/// decides when to call them.
+pub trait DeviceBackend {
+    /// Extra per-device data returned by [`Self::fetch`].
+    type Metrics;
+    /// Error returned by every step.
+    type Error: Display;
+
+    /// Uploads the app and test suite.
+    fn upload(&self, artifacts: &RunArtifacts) -> Result<UploadedArtifacts, Self::Error>;
+
+    /// Schedules the uploaded artifacts on `devices`, returning the build ID.
+    fn schedule(
+        &self,
+        devices: &[String],
+        uploaded: &UploadedArtifacts,
+    ) -> Result<String, Self::Error>;
+
+    /// Blocks until `build_id` finishes, failing on timeout or cancellation.
+    fn wait(&self, build_id: &str, options: &RunOptions) -> Result<(), Self::Error>;
+
+    /// Fetches and extracts the results of a finished build.
+    fn fetch(&self, build_id: &str) -> Result<DeviceResults<Self::Metrics>, Self::Error>;
+
+    /// Called as the run progresses; ignores events by default.
+    fn on_event(&self, event: RunEvent<'_>) {
+        let _ = event;
+    }
+}
+
+/// Uploads `spec.artifacts` and schedules them on `spec.devices`.
+pub fn start_run<B: DeviceBackend>(
+    spec: &DeviceRunSpec,
+    backend: &B,
+) -> Result<ScheduledRun, B::Error> {
+    let uploaded = backend.upload(&spec.artifacts)?;
+    let build_id = backend.schedule(&spec.devices, &uploaded)?;
+    Ok(ScheduledRun {
+        app_url: uploaded.app_url,
+        test_suite_url: uploaded.test_suite_url,
+        build_id,
+    })
+}
+
+/// Uploads `spec.artifacts` once and schedules every device that can be, as
+/// in [`schedule_each`]. Only a failed upload is an error, since nothing can
+/// run without it.
+pub fn start_run_each<B: DeviceBackend>(
+    spec: &DeviceRunSpec,
+    backend: &B,
+) -> Result<StartedRuns, B::Error> {
+    let uploaded = backend.upload(&spec.artifacts)?;
+    Ok(schedule_each(&spec.devices, &uploaded, backend))
+}
+
+/// Schedules already uploaded artifacts on every device that can take them.
+///
+/// All devices are first scheduled as one build. If the backend rejects
+/// that, each device is scheduled as its own build and the ones that still
+/// fail are returned in [`StartedRuns::unscheduled`].
+pub fn schedule_each<B: DeviceBackend>(
+    devices: &[String],
+    uploaded: &UploadedArtifacts,
+    backend: &B,
+) -> StartedRuns {
+    let scheduled = |build_id: String| ScheduledRun {
+        app_url: uploaded.app_url.clone(),
+        test_suite_url: uploaded.test_suite_url.clone(),
+        build_id,
+    };
+    let mut started = StartedRuns::default();
+    let error = match backend.schedule(devices, uploaded) {
+        Ok(build_id) => {
+            started.runs.push((scheduled(build_id), devices.to_vec()));
+            return started;
+        }
+        Err(error) => error,
+    };
+    if devices.len() == 1 {
+        started.unscheduled.insert(devices[0].clone(), error.to_string());
+        return started;
+    }
+    backend.on_event(RunEvent::ScheduleFailed {
+        devices,
+        error: &error,
+    });
+    for device in devices {
+        let single = std::slice::from_ref(device);
+        match backend.schedule(single, uploaded) {
+            Ok(build_id) => started.runs.push((scheduled(build_id), single.to_vec())),
+            Err(error) => {
+                started.unscheduled.insert(device.clone(), error.to_string());
+            }
+        }
+    }
+    started
+}
+
+/// Waits for `run` and fetches its results, re-scheduling devices that
+/// finished without a report up to `options.device_retries` times.
+///
+/// A failed fetch is an error only when no retry can follow it and
+/// `options.fail_fast` is set; otherwise it is reported as
+/// [`RunEvent::FetchFailed`] and the affected devices end up in
+/// [`RunSummary::missing`]. A failed re-schedule is reported as
+/// [`RunEvent::RescheduleFailed`] and recorded per device in
+/// [`RunSummary::unscheduled`], keeping the results fetched so far.
+pub fn collect_results<B: DeviceBackend>(
+    devices: &[String],
+    run: &ScheduledRun,
+    backend: &B,
+    options: &RunOptions,
+) -> Result<RunSummary<B::Metrics>, B::Error> {
+    let can_retry = options.device_retries > 0 && !run.test_suite_url.is_empty();
+    let fetch = |build_id: &str| {
+        backend.wait(build_id, options)?;
+        backend.fetch(build_id)
+    };
+
+    let mut results = match fetch(&run.build_id) {
+        Ok(results) => results,
+        // Every device failed; retrying all of them may still rescue the run
+        Err(error) if can_retry || !options.fail_fast => {
+            backend.on_event(RunEvent::FetchFailed {
+                attempt: 0,
+                build_id: &run.build_id,
+                error: &error,
+            });
+            DeviceResults::default()
+        }
+        Err(error) => return Err(error),
+    };
+
+    let uploaded = UploadedArtifacts {
+        app_url: run.app_url.clone(),
+        test_suite_url: run.test_suite_url.clone(),
+    };
+    let mut retried = BTreeMap::new();
+    let mut unscheduled = BTreeMap::new();
+    for attempt in 1..=options.device_retries {
+        let missing = missing_devices(devices, &results.benchmarks);
+        if missing.is_empty() || !can_retry {
+            break;
+        }
+        let build_id = match backend.schedule(&missing, &uploaded) {
+            Ok(build_id) => build_id,
+            Err(error) => {
+                backend.on_event(RunEvent::RescheduleFailed {
+                    attempt,
+                    devices: &missing,
+                    error: &error,
+                });
+                for device in &missing {
+                    unscheduled.insert(device.clone(), error.to_string());
+                }
+                break;
+            }
+        };
+        backend.on_event(RunEvent::Rescheduled {
+            attempt,
+            retries: options.device_retries,
+            build_id: &build_id,
+            devices: &missing,
+        });
+        for device in &missing {
+            retried.insert(device.clone(), attempt);
+        }
+        match fetch(&build_id) {
+            Ok(retry_results) => results.merge(retry_results),
+            Err(error) => backend.on_event(RunEvent::FetchFailed {
+                attempt,
+                build_id: &build_id,
+                error: &error,
+            }),
+        }
+    }
+
+    Ok(RunSummary {
+        run: run.clone(),
+        missing: missing_devices(devices, &results.benchmarks),
+        results,
+        retried,
+        unscheduled,
+    })
+}
+
+/// Runs `spec` end to end: [`start_run`] followed by [`collect_results`].
+pub fn run_on_devices<B: DeviceBackend>(
+    spec: &DeviceRunSpec,
+    backend: &B,
+    options: &RunOptions,
+) -> Result<RunSummary<B::Metrics>, B::Error> {
+    let run = start_run(spec, backend)?;
+    collect_results(&spec.devices, &run, backend, options)
+}
+
+/// Whether two device names refer to the same device.
+///
+/// Backends may report names with an OS version suffix (`Google Pixel 7-13.0`)
+/// that the requested spec lacks, or vice versa.
+pub fn device_names_match(a: &str, b: &str) -> bool {
+    let suffixed = |long: &str, short: &str| {
+        long.strip_prefix(short)
+            .is_some_and(|rest| rest.starts_with('-'))
+    };
+    a == b || suffixed(a, b) || suffixed(b, a)
+}
+
+/// Requested devices with no entry in `reported`, matched with
+/// [`device_names_match`].
+pub fn missing_devices<V>(requested: &[String], reported: &BTreeMap<String, V>) -> Vec<String> {
+    requested
+        .iter()
+        .filter(|device| !reported.keys().any(|name| device_names_match(name, device)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Device the fake backend refuses to schedule.
+    const RETIRED: &str = "Retired Phone-9.0";
+
+    /// Reports every device, except `flaky` unless it is scheduled alone.
+    /// Refuses any build that includes [`RETIRED`].
+    struct FakeBackend {
+        flaky: &'static str,
+        builds: RefCell<Vec<Vec<String>>>,
+        events: RefCell<Vec<String>>,
+    }
+
+    impl DeviceBackend for FakeBackend {
+        type Metrics = ();
+        type Error = String;
+
+        fn upload(&self, artifacts: &RunArtifacts) -> Result<UploadedArtifacts, String> {
+            Ok(UploadedArtifacts {
+                app_url: format!("bs://{}", artifacts.app.display()),
+                test_suite_url: format!("bs://{}", artifacts.test_suite.display()),
+            })
+        }
+
+        fn schedule(&self, devices: &[String], _: &UploadedArtifacts) -> Result<String, String> {
+            if devices.iter().any(|device| device == RETIRED) {
+                return Err(format!("unknown device {}", RETIRED));
+            }
+            let mut builds = self.builds.borrow_mut();
+            builds.push(devices.to_vec());
+            Ok(format!("build-{}", builds.len()))
+        }
+
+        fn wait(&self, _: &str, _: &RunOptions) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn fetch(&self, build_id: &str) -> Result<DeviceResults<()>, String> {
+            let index: usize = build_id.trim_start_matches("build-").parse().unwrap();
+            let mut results = DeviceResults::default();
+            let devices = &self.builds.borrow()[index - 1];
+            for device in devices {
+                if devices.len() == 1 || device != self.flaky {
+                    results
+                        .benchmarks
+                        .insert(device.clone(), vec![serde_json::json!({"samples": [1]})]);
+                }
+            }
+            Ok(results)
+        }
+
+        fn on_event(&self, event: RunEvent<'_>) {
+            if let RunEvent::Rescheduled { build_id, devices, .. } = event {
+                self.events
+                    .borrow_mut()
+                    .push(format!("{}: {}", build_id, devices.join(", ")));
+            }
+        }
+    }
+
+    #[test]
+    fn run_on_devices_reschedules_unreported_devices() {
+        let backend = FakeBackend {
+            flaky: "Samsung Galaxy S23-13.0",
+            builds: RefCell::new(Vec::new()),
+            events: RefCell::new(Vec::new()),
+        };
+        let spec = DeviceRunSpec {
+            devices: vec!["Google Pixel 7-13.0".into(), "Samsung Galaxy S23-13.0".into()],
+            artifacts: RunArtifacts {
+                app: "app.apk".into(),
+                test_suite: "test.apk".into(),
+            },
+        };
+
+        let summary = run_on_devices(&spec, &backend, &RunOptions::default()).unwrap();
+        assert_eq!(summary.run.build_id, "build-1");
+        assert_eq!(summary.run.app_url, "bs://app.apk");
+        assert_eq!(summary.missing, vec!["Samsung Galaxy S23-13.0"]);
+        assert!(summary.retried.is_empty());
+
+        let options = RunOptions {
+            device_retries: 2,
+            ..RunOptions::default()
+        };
+        let summary = run_on_devices(&spec, &backend, &options).unwrap();
+        assert_eq!(summary.results.benchmarks.len(), 2);
+        assert!(summary.missing.is_empty());
+        assert_eq!(
+            summary.retried,
+            BTreeMap::from([("Samsung Galaxy S23-13.0".to_string(), 1)])
+        );
+        assert_eq!(*backend.events.borrow(), vec!["build-3: Samsung Galaxy S23-13.0"]);
+    }
+
+    #[test]
+    fn start_run_each_schedules_devices_separately_after_batch_failure() {
+        let backend = FakeBackend {
+            flaky: "",
+            builds: RefCell::new(Vec::new()),
+            events: RefCell::new(Vec::new()),
+        };
+        let spec = DeviceRunSpec {
+            devices: vec!["Google Pixel 7-13.0".into(), RETIRED.into(), "iPhone 15-17".into()],
+            artifacts: RunArtifacts {
+                app: "app.apk".into(),
+                test_suite: "test.apk".into(),
+            },
+        };
+        assert!(start_run(&spec, &backend).is_err());
+
+        let started = start_run_each(&spec, &backend).unwrap();
+        let builds: Vec<(&str, &[String])> = started
+            .runs
+            .iter()
+            .map(|(run, devices)| (run.build_id.as_str(), devices.as_slice()))
+            .collect();
+        assert_eq!(
+            builds,
+            vec![
+                ("build-1", &["Google Pixel 7-13.0".to_string()][..]),
+                ("build-2", &["iPhone 15-17".to_string()][..]),
+            ]
+        );
+        assert_eq!(started.unscheduled[RETIRED], "unknown device Retired Phone-9.0");
+
+        // Later rounds schedule the same uploads again
+        let (first, _) = &started.runs[0];
+        let uploaded = UploadedArtifacts {
+            app_url: first.app_url.clone(),
+            test_suite_url: first.test_suite_url.clone(),
+        };
+        let again = schedule_each(&spec.devices[..1], &uploaded, &backend);
+        assert_eq!(again.runs[0].0.build_id, "build-3");
+        assert_eq!(again.runs[0].0.app_url, "bs://app.apk");
+
+        // Without fail-fast, a failed fetch leaves the devices missing
+        struct Unreachable;
+        impl DeviceBackend for Unreachable {
+            type Metrics = ();
+            type Error = String;
+            fn upload(&self, _: &RunArtifacts) -> Result<UploadedArtifacts, String> {
+                unreachable!()
+            }
+            fn schedule(&self, _: &[String], _: &UploadedArtifacts) -> Result<String, String> {
+                unreachable!()
+            }
+            fn wait(&self, _: &str, _: &RunOptions) -> Result<(), String> {
+                Err("timed out".into())
+            }
+            fn fetch(&self, _: &str) -> Result<DeviceResults<()>, String> {
+                unreachable!()
+            }
+        }
+        let (run, devices) = &started.runs[0];
+        assert!(collect_results(devices, run, &Unreachable, &RunOptions::default()).is_err());
+        let options = RunOptions {
+            fail_fast: false,
+            ..RunOptions::default()
+        };
+        let summary = collect_results(devices, run, &Unreachable, &options).unwrap();
+        assert_eq!(summary.missing, *devices);
+    }
+
+    #[test]
+    fn collect_results_keeps_fetched_results_when_a_retry_cannot_be_scheduled() {
+        let backend = FakeBackend {
+            flaky: "",
+            builds: RefCell::new(vec![vec!["Google Pixel 7-13.0".to_string()]]),
+            events: RefCell::new(Vec::new()),
+        };
+        let run = ScheduledRun {
+            app_url: "bs://app.apk".into(),
+            test_suite_url: "bs://test.apk".into(),
+            build_id: "build-1".into(),
+        };
+        let devices = vec!["Google Pixel 7-13.0".to_string(), RETIRED.to_string()];
+        let options = RunOptions {
+            device_retries: 1,
+            ..RunOptions::default()
+        };
+        assert!(options.fail_fast);
+
+        let summary = collect_results(&devices, &run, &backend, &options).unwrap();
+        assert!(summary.results.benchmarks.contains_key("Google Pixel 7-13.0"));
+        assert_eq!(summary.missing, vec![RETIRED]);
+        assert_eq!(summary.unscheduled[RETIRED], "unknown device Retired Phone-9.0");
+        assert!(summary.retried.is_empty());
+    }
+
+    #[test]
+    fn missing_devices_matches_os_suffixes() {
+        let reported = BTreeMap::from([("Google Pixel 7".to_string(), ())]);
+        let requested = vec!["Google Pixel 7-13.0".to_string(), "iPhone 15-17".to_string()];
+        assert_eq!(missing_devices(&requested, &reported), vec!["iPhone 15-17"]);
+        assert!(device_names_match("iPhone 15", "iPhone 15"));
+        assert!(!device_names_match("iPhone 15", "iPhone 15 Pro"));
+    }
+}
@@ -3,7 +3,12 @@
 //! This module provides runtime discovery of benchmark functions that have been
 //! marked with the `#[benchmark]` attribute macro.
 
-use crate::timing::{BenchReport, BenchSpec, TimingError};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::timing::{BenchReport, BenchSpec, SeededRng, TimingError};
 
 /// A registered benchmark function
 ///
@@ -18,6 +23,33 @@ pub struct BenchFunction {
     /// Takes a BenchSpec and returns a BenchReport directly.
     /// The runner handles setup/teardown internally.
     pub runner: fn(BenchSpec) -> Result<BenchReport, TimingError>,
+
+    /// Group from `#[benchmark(group = "...")]`, if any
+    pub group: Option<&'static str>,
+
+    /// Macro arguments, e.g. `["setup=setup_data", "per_iteration"]`
+    pub params: &'static [&'static str],
+
+    /// Whether the benchmark is marked `#[benchmark(ignore)]`
+    ///
+    /// Ignored benchmarks remain registered and runnable by name; suite
+    /// runners and tooling should skip them by default.
+    pub ignored: bool,
+
+    /// Reason given via `#[benchmark(ignore = "...")]`
+    pub skip_reason: Option<&'static str>,
+
+    /// Whether the benchmark has a setup function
+    pub has_setup: bool,
+
+    /// Default iteration count from `#[benchmark(sample_size = N)]`
+    ///
+    /// Used by [`run_benchmark`](crate::runner::run_benchmark) when the spec
+    /// leaves `iterations` at [`USE_BENCHMARK_DEFAULT`](crate::runner::USE_BENCHMARK_DEFAULT).
+    pub sample_size: Option<u32>,
+
+    /// Default warmup count from `#[benchmark(warmup = N)]`
+    pub warmup: Option<u32>,
 }
 
 // Register the BenchFunction type with inventory
@@ -96,6 +128,133 @@ pub fn list_benchmark_names() -> Vec<&'static str> {
     names
 }
 
+/// Order in which a suite of benchmarks runs.
+///
+/// Earlier benchmarks heat the device for later ones, so the order shapes the
+/// results. Shuffling with a recorded seed reproduces an order exactly and
+/// exposes order-dependent results across runs.
+///
+/// Parsed from and serialized as `declared`, `alpha`, `shuffle` or
+/// `shuffle:<seed>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum ExecutionOrder {
+    /// The order the functions were given or declared in.
+    #[default]
+    Declared,
+    /// Sorted by function name.
+    Alpha,
+    /// Shuffled with this seed; `None` until [`ExecutionOrder::seeded`] picks one.
+    Shuffle(Option<u64>),
+}
+
+impl ExecutionOrder {
+    /// Whether this keeps the given order.
+    pub fn is_declared(&self) -> bool {
+        *self == Self::Declared
+    }
+
+    /// This order with a seed for an unseeded shuffle, taken from the clock,
+    /// so the order that actually ran can be recorded and replayed.
+    pub fn seeded(self) -> Self {
+        match self {
+            Self::Shuffle(None) => {
+                let nanos = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+                Self::Shuffle(Some(SeededRng::new(nanos).next_u64()))
+            }
+            other => other,
+        }
+    }
+
+    /// Reorders `items`, named by `name`, in place.
+    ///
+    /// A shuffle without a seed uses seed 0; call [`ExecutionOrder::seeded`]
+    /// first to get a fresh order.
+    pub fn arrange<T>(&self, items: &mut [T], name: impl Fn(&T) -> &str) {
+        match *self {
+            Self::Declared => {}
+            Self::Alpha => items.sort_by(|a, b| name(a).cmp(name(b))),
+            Self::Shuffle(seed) => {
+                // Fisher-Yates, from the back
+                let mut rng = SeededRng::new(seed.unwrap_or_default());
+                for i in (1..items.len()).rev() {
+                    items.swap(i, rng.gen_range(0..i as u64 + 1) as usize);
+                }
+            }
+        }
+    }
+}
+
+/// Renders as `declared`, `alpha`, `shuffle` or `shuffle:<seed>`.
+impl fmt::Display for ExecutionOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Declared => f.write_str("declared"),
+            Self::Alpha => f.write_str("alpha"),
+            Self::Shuffle(None) => f.write_str("shuffle"),
+            Self::Shuffle(Some(seed)) => write!(f, "shuffle:{seed}"),
+        }
+    }
+}
+
+impl FromStr for ExecutionOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "declared" => Ok(Self::Declared),
+            "alpha" => Ok(Self::Alpha),
+            "shuffle" => Ok(Self::Shuffle(None)),
+            other => other
+                .strip_prefix("shuffle:")
+                .and_then(|seed| seed.parse().ok())
+                .map(|seed| Self::Shuffle(Some(seed)))
+                .ok_or_else(|| {
+                    format!("unknown order '{other}' (expected declared, alpha, shuffle or shuffle:<seed>)")
+                }),
+        }
+    }
+}
+
+impl From<ExecutionOrder> for String {
+    fn from(order: ExecutionOrder) -> Self {
+        order.to_string()
+    }
+}
+
+impl TryFrom<String> for ExecutionOrder {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Discovers all registered benchmark functions in `order`
+///
+/// [`ExecutionOrder::Declared`] is registration order, which follows the
+/// order the linker placed the `#[benchmark]` functions in rather than the
+/// source; use [`ExecutionOrder::Alpha`] for an order stable across builds.
+///
+/// # Example
+///
+/// ```ignore
+/// use mobench_sdk::registry::{discover_benchmarks_ordered, ExecutionOrder};
+///
+/// let order = ExecutionOrder::Shuffle(None).seeded();
+/// println!("Running in {order} order");
+/// for bench in discover_benchmarks_ordered(order) {
+///     println!("{}", bench.name);
+/// }
+/// ```
+pub fn discover_benchmarks_ordered(order: ExecutionOrder) -> Vec<&'static BenchFunction> {
+    let mut benchmarks = discover_benchmarks();
+    order.arrange(&mut benchmarks, |bench| bench.name);
+    benchmarks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +275,30 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_execution_order() {
+        let names = ["c", "a", "d", "b", "e"];
+        let mut alpha = names;
+        ExecutionOrder::Alpha.arrange(&mut alpha, |name| name);
+        assert_eq!(alpha, ["a", "b", "c", "d", "e"]);
+
+        let shuffled = |seed| {
+            let mut items = names;
+            ExecutionOrder::Shuffle(Some(seed)).arrange(&mut items, |name| name);
+            items
+        };
+        assert_eq!(shuffled(7), shuffled(7));
+        let mut sorted = shuffled(7);
+        sorted.sort();
+        assert_eq!(sorted, alpha);
+
+        assert_eq!("shuffle:42".parse(), Ok(ExecutionOrder::Shuffle(Some(42))));
+        assert_eq!(ExecutionOrder::Shuffle(Some(42)).to_string(), "shuffle:42");
+        assert!("random".parse::<ExecutionOrder>().is_err());
+        assert!(matches!(ExecutionOrder::Shuffle(None).seeded(), ExecutionOrder::Shuffle(Some(_))));
+        assert_eq!(serde_json::to_string(&ExecutionOrder::Alpha).unwrap(), r#""alpha""#);
+    }
+
     #[test]
     fn test_list_benchmark_names() {
         // Validates that the function returns successfully
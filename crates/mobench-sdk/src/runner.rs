@@ -3,15 +3,36 @@
 //! This module provides the execution engine that runs registered benchmarks
 //! and collects timing data.
 
-use crate::registry::{find_benchmark, list_benchmark_names};
-use crate::timing::BenchSpec;
+use crate::registry::{BenchFunction, find_benchmark, list_benchmark_names};
+use std::time::{Duration, Instant};
+
+use crate::timing::{BenchSpec, SamplingMode, TimingError, WarmupPolicy, with_iteration_offset, with_sample_stream};
 use crate::types::{BenchError, RunnerReport};
 
+/// A registered benchmark's runner (see [`BenchFunction::runner`]).
+type Runner = fn(BenchSpec) -> Result<RunnerReport, TimingError>;
+
+/// Iteration or warmup count meaning "use the benchmark's own default".
+///
+/// When a spec's `iterations` or `warmup` is set to this value,
+/// [`run_benchmark`] substitutes the benchmark's `#[benchmark(sample_size = N)]`
+/// or `#[benchmark(warmup = N)]` value, falling back to [`DEFAULT_ITERATIONS`]
+/// and [`DEFAULT_WARMUP`]. Precedence is therefore: an explicit count (e.g.
+/// from `--iterations`), then the macro default, then the global default.
+pub const USE_BENCHMARK_DEFAULT: u32 = u32::MAX;
+
+/// Iterations used when neither the spec nor the benchmark sets a count.
+pub const DEFAULT_ITERATIONS: u32 = 100;
+
+/// Warmup iterations used when neither the spec nor the benchmark sets a count.
+pub const DEFAULT_WARMUP: u32 = 10;
+
 /// Runs a benchmark by name
 ///
 /// Looks up the benchmark function in the registry and executes it with the
 /// given specification. The benchmark's runner handles all timing, including
-/// any setup/teardown logic.
+/// any setup/teardown logic. Counts left at [`USE_BENCHMARK_DEFAULT`] are
+/// resolved from the benchmark's macro arguments first.
 ///
 /// # Arguments
 ///
@@ -31,27 +52,178 @@ use crate::types::{BenchError, RunnerReport};
 ///     name: "my_benchmark".to_string(),
 ///     iterations: 100,
 ///     warmup: 10,
+///     warmup_policy: Default::default(),
+///     sampling: Default::default(),
+///     per_iteration_timeout: None,
+///     timeout: None,
+///     capture_warmup: false,
+///     seed: None,
+///     drop_first_n: 0,
 /// };
 ///
 /// let report = run_benchmark(spec)?;
 /// println!("Mean: {} ns", report.mean());
 /// ```
 pub fn run_benchmark(spec: BenchSpec) -> Result<RunnerReport, BenchError> {
-    // Find the benchmark function in the registry
-    let bench_fn = find_benchmark(&spec.name).ok_or_else(|| {
+    let bench_fn = lookup(&spec.name)?;
+
+    // Call the runner directly - it handles setup/teardown and timing internally
+    let report = (bench_fn.runner)(resolve_default_counts(spec, bench_fn))?;
+
+    Ok(report)
+}
+
+/// Runs several benchmarks round-robin, one measured iteration each per round
+///
+/// Run one after another, later benchmarks measure a hotter, possibly
+/// throttled device than earlier ones. Interleaving cycles A, B, C, A, B,
+/// C, ... so thermal drift affects every benchmark equally. Each benchmark
+/// warms up per its own spec in the first round; a benchmark with fewer
+/// iterations than the others simply drops out of the later rounds.
+///
+/// Interleaving assumes the benchmarks are independent and free of side
+/// effects: caches, allocations or global state one benchmark leaves behind
+/// carry over into the next one's sample. Every round is a separate call to
+/// the benchmark's runner, so setup runs (untimed) once per round. Round `r`
+/// is seeded with [`BenchSpec::effective_seed`] plus `r`, so seeded
+/// benchmarks draw fresh inputs every round while the report's seed still
+/// reproduces the run, and streamed samples are numbered across rounds.
+///
+/// Only [`SamplingMode::FixedIterations`] is supported. `drop_first_n`
+/// discards each benchmark's first rounds, and `timeout` stops a benchmark
+/// once the rounds it ran have used up its budget.
+///
+/// # Returns
+///
+/// * `Ok(reports)` - One report per spec, in the order given
+/// * `Err(BenchError)` - If a function is not found, a spec is not fixed
+///   iterations, or an iteration fails
+pub fn run_benchmarks_interleaved(specs: Vec<BenchSpec>) -> Result<Vec<RunnerReport>, BenchError> {
+    let runs = specs
+        .into_iter()
+        .map(|spec| {
+            let bench_fn = lookup(&spec.name)?;
+            Ok((resolve_default_counts(spec, bench_fn), bench_fn.runner))
+        })
+        .collect::<Result<Vec<_>, BenchError>>()?;
+    interleave(runs)
+}
+
+/// Finds `name` in the registry, listing what is available if it is missing.
+fn lookup(name: &str) -> Result<&'static BenchFunction, BenchError> {
+    find_benchmark(name).ok_or_else(|| {
         let available = list_benchmark_names()
             .into_iter()
             .map(String::from)
             .collect();
-        BenchError::UnknownFunction(spec.name.clone(), available)
-    })?;
+        BenchError::UnknownFunction(name.to_string(), available)
+    })
+}
 
-    // Call the runner directly - it handles setup/teardown and timing internally
-    let report = (bench_fn.runner)(spec)?;
+/// One benchmark's progress through [`interleave`].
+struct InterleavedRun {
+    spec: BenchSpec,
+    runner: Runner,
+    report: RunnerReport,
+    elapsed: Duration,
+}
 
+/// Run `round` of `spec` as a single iteration, warming up only in round 0.
+///
+/// `elapsed` is the time the benchmark's earlier rounds took, charged
+/// against its `timeout`. The round's sample is streamed as the iteration it
+/// is in the whole run; rounds dropped per `drop_first_n` are not streamed.
+fn run_round(spec: &BenchSpec, runner: Runner, round: u32, elapsed: Duration) -> Result<RunnerReport, BenchError> {
+    let mut once = spec.clone();
+    once.iterations = 1;
+    once.drop_first_n = 0;
+    once.timeout = spec.timeout.map(|budget| budget.saturating_sub(elapsed));
+    once.seed = Some(spec.effective_seed().wrapping_add(u64::from(round)));
+    if round > 0 {
+        once.warmup = 0;
+        once.warmup_policy = WarmupPolicy::Fixed;
+        once.capture_warmup = false;
+    }
+    let mut report = match round.checked_sub(spec.drop_first_n) {
+        Some(iteration) => with_iteration_offset(iteration, || runner(once))?,
+        None => with_sample_stream(std::io::sink(), || runner(once))?,
+    };
+    if round < spec.drop_first_n {
+        report.dropped_samples = report.samples.len() as u32;
+        report.samples.clear();
+        report.metrics.clear();
+    }
     Ok(report)
 }
 
+/// The round-robin loop behind [`run_benchmarks_interleaved`].
+fn interleave(runs: Vec<(BenchSpec, Runner)>) -> Result<Vec<RunnerReport>, BenchError> {
+    for (spec, _) in &runs {
+        if spec.sampling != SamplingMode::FixedIterations {
+            return Err(BenchError::Config(format!(
+                "interleaved runs need fixed iterations, but {} uses {:?} sampling",
+                spec.name, spec.sampling
+            )));
+        }
+        if spec.iterations <= spec.drop_first_n {
+            return Err(TimingError::NoIterations {
+                count: spec.iterations.saturating_sub(spec.drop_first_n),
+            }
+            .into());
+        }
+    }
+
+    // Round 0 also warms every benchmark up
+    let mut active = Vec::with_capacity(runs.len());
+    for (spec, runner) in runs {
+        let started = Instant::now();
+        let report = run_round(&spec, runner, 0, Duration::ZERO)?;
+        active.push(InterleavedRun {
+            spec,
+            runner,
+            report,
+            elapsed: started.elapsed(),
+        });
+    }
+
+    let rounds = active.iter().map(|run| run.spec.iterations).max().unwrap_or(0);
+    for round in 1..rounds {
+        for run in &mut active {
+            if round >= run.spec.iterations || run.report.timed_out {
+                continue;
+            }
+            let started = Instant::now();
+            let report = run_round(&run.spec, run.runner, round, run.elapsed)?;
+            run.elapsed += started.elapsed();
+            run.report.samples.extend(report.samples);
+            for (name, values) in report.metrics {
+                run.report.metrics.entry(name).or_default().extend(values);
+            }
+            run.report.dropped_samples += report.dropped_samples;
+            run.report.timed_out |= report.timed_out;
+        }
+    }
+
+    Ok(active
+        .into_iter()
+        .map(|run| RunnerReport {
+            spec: run.spec,
+            ..run.report
+        })
+        .collect())
+}
+
+/// Replaces [`USE_BENCHMARK_DEFAULT`] counts with the benchmark's defaults.
+fn resolve_default_counts(mut spec: BenchSpec, bench_fn: &BenchFunction) -> BenchSpec {
+    if spec.iterations == USE_BENCHMARK_DEFAULT {
+        spec.iterations = bench_fn.sample_size.unwrap_or(DEFAULT_ITERATIONS);
+    }
+    if spec.warmup == USE_BENCHMARK_DEFAULT {
+        spec.warmup = bench_fn.warmup.unwrap_or(DEFAULT_WARMUP);
+    }
+    spec
+}
+
 /// Builder for constructing and running benchmarks
 ///
 /// Provides a fluent interface for configuring benchmark parameters.
@@ -71,6 +243,14 @@ pub struct BenchmarkBuilder {
     function: String,
     iterations: u32,
     warmup: u32,
+    warmup_policy: WarmupPolicy,
+    sampling: SamplingMode,
+    per_iteration_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    capture_warmup: bool,
+    seed: Option<u64>,
+    drop_first_n: u32,
+    stream_samples: bool,
 }
 
 impl BenchmarkBuilder {
@@ -82,13 +262,23 @@ impl BenchmarkBuilder {
     pub fn new(function: impl Into<String>) -> Self {
         Self {
             function: function.into(),
-            iterations: 100, // Default
-            warmup: 10,      // Default
+            iterations: USE_BENCHMARK_DEFAULT,
+            warmup: USE_BENCHMARK_DEFAULT,
+            warmup_policy: WarmupPolicy::Fixed,
+            sampling: SamplingMode::FixedIterations,
+            per_iteration_timeout: None,
+            timeout: None,
+            capture_warmup: false,
+            seed: None,
+            drop_first_n: 0,
+            stream_samples: false,
         }
     }
 
     /// Sets the number of iterations
     ///
+    /// Without this, the benchmark's `sample_size` or [`DEFAULT_ITERATIONS`] is used.
+    ///
     /// # Arguments
     ///
     /// * `n` - Number of times to run the benchmark (after warmup)
@@ -99,6 +289,8 @@ impl BenchmarkBuilder {
 
     /// Sets the number of warmup iterations
     ///
+    /// Without this, the benchmark's `warmup` or [`DEFAULT_WARMUP`] is used.
+    ///
     /// # Arguments
     ///
     /// * `n` - Number of warmup runs (not measured)
@@ -107,6 +299,97 @@ impl BenchmarkBuilder {
         self
     }
 
+    /// Sets the warmup policy
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Fixed warmup count or warmup until samples are stable
+    pub fn warmup_policy(mut self, policy: WarmupPolicy) -> Self {
+        self.warmup_policy = policy;
+        self
+    }
+
+    /// Sets the sampling mode
+    ///
+    /// # Arguments
+    ///
+    /// * `sampling` - Fixed iteration count, a fixed time budget, or adaptive sampling
+    pub fn sampling_mode(mut self, sampling: SamplingMode) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Sets the per-iteration timeout
+    ///
+    /// An iteration that runs longer fails the benchmark with a timeout error.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum wall-clock time for a single iteration
+    pub fn per_iteration_timeout(mut self, timeout: Duration) -> Self {
+        self.per_iteration_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a wall-clock budget for the whole benchmark
+    ///
+    /// Once it runs out no further iterations start, and the report keeps the
+    /// samples measured so far with `timed_out` set.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum wall-clock time for warmup and measurement together
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Records warmup durations in the report's `warmup_samples`
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to keep warmup samples (off by default)
+    pub fn capture_warmup(mut self, enabled: bool) -> Self {
+        self.capture_warmup = enabled;
+        self
+    }
+
+    /// Seeds the RNG passed to seeded per-iteration setup
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed to use instead of the one derived from the function name
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Discards the first measured samples, after warmup
+    ///
+    /// With fixed iterations the dropped samples count toward `iterations`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Number of measured samples to discard
+    pub fn drop_first_n(mut self, n: u32) -> Self {
+        self.drop_first_n = n;
+        self
+    }
+
+    /// Streams each sample to stdout as it is measured
+    ///
+    /// Each measured iteration is printed as a `BENCH_SAMPLE_JSON {...}` line,
+    /// which the mobench CLI can use to rebuild a report from device logs when
+    /// the final report block is truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to stream samples
+    pub fn stream_samples(mut self, enabled: bool) -> Self {
+        self.stream_samples = enabled;
+        self
+    }
+
     /// Runs the benchmark and returns the report
     ///
     /// # Returns
@@ -118,9 +401,20 @@ impl BenchmarkBuilder {
             name: self.function,
             iterations: self.iterations,
             warmup: self.warmup,
+            warmup_policy: self.warmup_policy,
+            sampling: self.sampling,
+            per_iteration_timeout: self.per_iteration_timeout,
+            timeout: self.timeout,
+            capture_warmup: self.capture_warmup,
+            seed: self.seed,
+            drop_first_n: self.drop_first_n,
         };
 
-        run_benchmark(spec)
+        if self.stream_samples {
+            with_sample_stream(std::io::stdout(), || run_benchmark(spec))
+        } else {
+            run_benchmark(spec)
+        }
     }
 }
 
@@ -131,8 +425,112 @@ mod tests {
     #[test]
     fn test_builder_defaults() {
         let builder = BenchmarkBuilder::new("test_fn");
-        assert_eq!(builder.iterations, 100);
-        assert_eq!(builder.warmup, 10);
+        assert_eq!(builder.iterations, USE_BENCHMARK_DEFAULT);
+        assert_eq!(builder.warmup, USE_BENCHMARK_DEFAULT);
+    }
+
+    #[test]
+    fn test_default_counts_precedence() {
+        fn runner(_: BenchSpec) -> Result<crate::timing::BenchReport, crate::timing::TimingError> {
+            unreachable!()
+        }
+        let bench_fn = BenchFunction {
+            name: "suite::hash_small",
+            runner,
+            group: None,
+            params: &["sample_size=1000"],
+            ignored: false,
+            skip_reason: None,
+            has_setup: false,
+            sample_size: Some(1000),
+            warmup: None,
+        };
+        let spec = |iterations, warmup| BenchSpec::new("hash_small", iterations, warmup).unwrap();
+
+        // Macro default beats the global default; the global default fills gaps
+        let resolved = resolve_default_counts(spec(USE_BENCHMARK_DEFAULT, USE_BENCHMARK_DEFAULT), &bench_fn);
+        assert_eq!((resolved.iterations, resolved.warmup), (1000, DEFAULT_WARMUP));
+
+        // Explicit counts beat both
+        let resolved = resolve_default_counts(spec(20, 0), &bench_fn);
+        assert_eq!((resolved.iterations, resolved.warmup), (20, 0));
+    }
+
+    /// Stream sink shared with the test.
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn interleaves_one_sample_per_benchmark_per_round() {
+        use std::sync::Mutex;
+        static ORDER: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+        fn record(spec: BenchSpec, name: &'static str) -> Result<RunnerReport, TimingError> {
+            crate::timing::run_closure(spec, || {
+                ORDER.lock().unwrap().push(name);
+                Ok(())
+            })
+        }
+        fn bench_a(spec: BenchSpec) -> Result<RunnerReport, TimingError> {
+            record(spec, "a")
+        }
+        fn bench_b(spec: BenchSpec) -> Result<RunnerReport, TimingError> {
+            record(spec, "b")
+        }
+
+        let a = BenchSpec::new("a", 3, 1).unwrap();
+        let b = BenchSpec::new("b", 2, 0).unwrap().with_drop_first_n(1);
+        let reports = interleave(vec![(a, bench_a as Runner), (b, bench_b as Runner)]).unwrap();
+        // a warms up once, then a and b alternate until b runs out of iterations
+        assert_eq!(*ORDER.lock().unwrap(), ["a", "a", "b", "a", "b", "a"]);
+        assert_eq!(reports[0].samples.len(), 3);
+        assert_eq!((reports[0].spec.iterations, reports[0].warmup_iterations), (3, 1));
+        assert_eq!((reports[1].samples.len(), reports[1].dropped_samples), (1, 1));
+
+        let timed = BenchSpec::new("a", 3, 0)
+            .unwrap()
+            .with_sampling_mode(SamplingMode::FixedTime(Duration::from_secs(1)));
+        assert!(matches!(
+            interleave(vec![(timed, bench_a as Runner)]),
+            Err(BenchError::Config(_))
+        ));
+
+        // Seeded inputs differ per round, and streamed samples are numbered
+        // across rounds, skipping the dropped ones
+        static INPUTS: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+        fn seeded(spec: BenchSpec) -> Result<RunnerReport, TimingError> {
+            crate::timing::run_closure_with_seeded_setup_per_iter(spec, |rng| rng.next_u64(), |input| {
+                INPUTS.lock().unwrap().push(input);
+                Ok(())
+            })
+        }
+        let stream = SharedBuf::default();
+        let spec = BenchSpec::new("seeded", 4, 0).unwrap().with_drop_first_n(1);
+        let reports = with_sample_stream(stream.clone(), || {
+            interleave(vec![(spec.clone(), seeded as Runner)])
+        })
+        .unwrap();
+        let inputs = INPUTS.lock().unwrap().clone();
+        assert_eq!(inputs.iter().collect::<std::collections::BTreeSet<_>>().len(), 4);
+        assert_eq!(reports[0].seed, Some(spec.effective_seed()));
+        let output = String::from_utf8(stream.0.lock().unwrap().clone()).unwrap();
+        let iterations: Vec<u32> = output
+            .lines()
+            .map(|line| {
+                let json = line.strip_prefix("BENCH_SAMPLE_JSON ").unwrap();
+                serde_json::from_str::<crate::timing::StreamedSample>(json).unwrap().iteration
+            })
+            .collect();
+        assert_eq!(iterations, [0, 1, 2]);
     }
 
     #[test]
@@ -0,0 +1,246 @@
+//! Summary statistics over benchmark samples.
+//!
+//! The single implementation of the mean, median, percentiles, standard
+//! deviation, coefficient of variation and median of means used across
+//! mobench: [`BenchReport::stats`](crate::timing::BenchReport::stats) on the
+//! device or host, and the `mobench` CLI when it summarizes device reports.
+//! Sharing it keeps the numbers a runner prints and the ones in the run
+//! summary from drifting apart.
+//!
+//! Conventions:
+//!
+//! - Percentiles use the nearest-rank method: the `p`-th percentile of `n`
+//!   sorted samples is the one at rank `⌈p·n⌉`, so it is always an observed
+//!   sample.
+//! - The median of an even count is the midpoint of the two middle samples,
+//!   rounded down. It is therefore not always the 50th percentile, which is
+//!   the lower of the two.
+//! - Standard deviation and CV are population statistics (divided by `n`).
+//!
+//! ```
+//! use mobench_sdk::stats::SampleStats;
+//!
+//! let stats = SampleStats::from_samples(&[120, 100, 110, 105, 400]).unwrap();
+//! assert_eq!(stats.median_ns, 110);
+//! assert_eq!(stats.p95_ns, 400);
+//! assert_eq!(stats.min_ns, 100);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Summary statistics of a set of nanosecond samples.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SampleStats {
+    /// Number of samples.
+    pub samples: usize,
+    /// Arithmetic mean, rounded down.
+    pub mean_ns: u64,
+    /// Median; the rounded-down midpoint for an even count.
+    pub median_ns: u64,
+    /// 95th percentile (nearest rank).
+    pub p95_ns: u64,
+    /// 99th percentile (nearest rank).
+    pub p99_ns: u64,
+    /// Fastest sample.
+    pub min_ns: u64,
+    /// Slowest sample.
+    pub max_ns: u64,
+    /// Population standard deviation.
+    pub stddev_ns: f64,
+    /// Coefficient of variation (stddev / mean) in percent.
+    pub cv_pct: f64,
+    /// Median of means; see [`median_of_means`].
+    pub mom_ns: u64,
+}
+
+impl SampleStats {
+    /// Statistics of `samples`, given in collection order; `None` when empty.
+    ///
+    /// Collection order matters only for [`median_of_means`], which groups
+    /// neighbouring samples.
+    pub fn from_samples(samples: &[u64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let len = sorted.len();
+
+        let as_f64: Vec<f64> = sorted.iter().map(|&v| v as f64).collect();
+        Some(Self {
+            samples: len,
+            mean_ns: (sorted.iter().map(|&v| u128::from(v)).sum::<u128>() / len as u128) as u64,
+            median_ns: median(&sorted)?,
+            p95_ns: percentile(&sorted, 0.95)?,
+            p99_ns: percentile(&sorted, 0.99)?,
+            min_ns: sorted[0],
+            max_ns: sorted[len - 1],
+            stddev_ns: std_dev(&as_f64)?,
+            cv_pct: cv_pct(&as_f64),
+            mom_ns: median_of_means(samples),
+        })
+    }
+}
+
+/// Index of the nearest-rank `percentile` (`0.0..=1.0`) in `len` sorted
+/// values: rank `⌈percentile·len⌉`, clamped to the valid indices.
+pub fn percentile_index(len: usize, percentile: f64) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let rank = (percentile * len as f64).ceil() as usize;
+    rank.saturating_sub(1).min(len - 1)
+}
+
+/// The nearest-rank `percentile` (`0.0..=1.0`) of `sorted`; `None` when empty.
+pub fn percentile<T: Copy>(sorted: &[T], percentile: f64) -> Option<T> {
+    sorted.get(percentile_index(sorted.len(), percentile)).copied()
+}
+
+/// Median of `sorted`: the middle value, or the rounded-down midpoint of the
+/// two middle values for an even count. `None` when empty.
+pub fn median(sorted: &[u64]) -> Option<u64> {
+    let len = sorted.len();
+    let upper = *sorted.get(len / 2)?;
+    if len % 2 == 1 {
+        return Some(upper);
+    }
+    Some(midpoint(sorted[len / 2 - 1], upper))
+}
+
+/// [`median`] for floating-point values, e.g. custom metrics.
+pub fn median_f64(sorted: &[f64]) -> Option<f64> {
+    let len = sorted.len();
+    let upper = *sorted.get(len / 2)?;
+    if len % 2 == 1 {
+        return Some(upper);
+    }
+    Some((sorted[len / 2 - 1] + upper) / 2.0)
+}
+
+/// Rounded-down mean of two values, without overflowing.
+pub fn midpoint(a: u64, b: u64) -> u64 {
+    a / 2 + b / 2 + (a % 2 + b % 2) / 2
+}
+
+/// Arithmetic mean of `values`; `None` when empty.
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Population standard deviation of `values`; `None` when empty.
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    let mean = mean(values)?;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Some(variance.sqrt())
+}
+
+/// Coefficient of variation of `values` in percent: the population standard
+/// deviation over the absolute mean.
+///
+/// Zero for an empty set or a zero mean (e.g. samples below a coarse clock's
+/// resolution), which callers treat as perfectly stable.
+pub fn cv_pct(values: &[f64]) -> f64 {
+    match (mean(values), std_dev(values)) {
+        (Some(mean), Some(stddev)) if mean != 0.0 => stddev / mean.abs() * 100.0,
+        _ => 0.0,
+    }
+}
+
+/// Median of the means of `⌊√n⌋` contiguous sample groups, rounded to the
+/// nearest nanosecond; 0 when empty.
+///
+/// Groups follow collection order, so a burst of slow iterations (a GC pause,
+/// a thermal blip) lands in one group and is voted out by the median, while
+/// the group means keep the magnitude information a plain median discards.
+/// The first `n mod groups` groups take one extra sample.
+pub fn median_of_means(samples: &[u64]) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    let groups = (samples.len() as f64).sqrt().floor().max(1.0) as usize;
+    let base = samples.len() / groups;
+    let extra = samples.len() % groups;
+
+    let mut means = Vec::with_capacity(groups);
+    let mut start = 0;
+    for index in 0..groups {
+        let size = base + usize::from(index < extra);
+        let group = &samples[start..start + size];
+        start += size;
+        means.push(group.iter().map(|&v| v as f64).sum::<f64>() / size as f64);
+    }
+
+    means.sort_by(f64::total_cmp);
+    median_f64(&means).map_or(0, |median| median.round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::SeededRng;
+
+    /// Random sample sets of 1 to 200 heavy-tailed durations.
+    fn sample_sets() -> impl Iterator<Item = Vec<u64>> {
+        (0..500u64).map(|seed| {
+            let mut rng = SeededRng::new(seed);
+            let len = rng.gen_range(1..201) as usize;
+            (0..len)
+                .map(|_| {
+                    let base = rng.gen_range(1_000..2_000);
+                    if rng.gen_range(0..20) == 0 { base * 10 } else { base }
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn stats_hold_their_invariants() {
+        for samples in sample_sets() {
+            let stats = SampleStats::from_samples(&samples).unwrap();
+            let mut sorted = samples.clone();
+            sorted.sort_unstable();
+
+            // Percentiles never decrease with the percentile
+            let percentiles: Vec<u64> = (0..=100)
+                .map(|p| percentile(&sorted, f64::from(p) / 100.0).unwrap())
+                .collect();
+            assert!(percentiles.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!(percentiles[0], stats.min_ns);
+            assert_eq!(percentiles[100], stats.max_ns);
+
+            // The median is p50 for odd counts and lies between the middle pair otherwise
+            let p50 = percentile(&sorted, 0.5).unwrap();
+            if samples.len() % 2 == 1 {
+                assert_eq!(stats.median_ns, p50);
+            } else {
+                assert!(p50 <= stats.median_ns && stats.median_ns <= sorted[samples.len() / 2]);
+            }
+
+            assert!(stats.min_ns <= stats.median_ns && stats.median_ns <= stats.p95_ns);
+            assert!(stats.p95_ns <= stats.p99_ns && stats.p99_ns <= stats.max_ns);
+            assert!(stats.min_ns <= stats.mean_ns && stats.mean_ns <= stats.max_ns);
+            assert!(stats.min_ns <= stats.mom_ns && stats.mom_ns <= stats.max_ns);
+            assert!(stats.cv_pct >= 0.0);
+            assert_eq!(stats.samples, samples.len());
+        }
+    }
+
+    #[test]
+    fn summarizes_known_samples() {
+        let stats = SampleStats::from_samples(&[4, 1, 3, 2]).unwrap();
+        assert_eq!((stats.mean_ns, stats.median_ns, stats.p95_ns), (2, 2, 4));
+        assert!((stats.stddev_ns - 1.25f64.sqrt()).abs() < 1e-9);
+        assert!(SampleStats::from_samples(&[]).is_none());
+        assert_eq!(SampleStats::from_samples(&[7; 3]).unwrap().cv_pct, 0.0);
+
+        // No overflow at the top of the range
+        assert_eq!(median(&[u64::MAX - 1, u64::MAX]), Some(u64::MAX - 1));
+        assert_eq!(percentile_index(100, 0.95), 94);
+        assert_eq!(percentile_index(0, 0.95), 0);
+        assert_eq!(cv_pct(&[]), 0.0);
+    }
+}
@@ -10,7 +10,7 @@
 //! ## Overview
 //!
 //! The timing module executes benchmark functions with:
-//! - Configurable warmup iterations
+//! - Configurable warmup iterations, optionally captured for diagnostics
 //! - Precise nanosecond-resolution timing
 //! - Simple, serializable results
 //!
@@ -47,9 +47,144 @@
 //! | Type | Description |
 //! |------|-------------|
 //! | [`BenchSpec`] | Benchmark configuration (name, iterations, warmup) |
+//! | [`WarmupPolicy`] | How warmup is run (fixed count, time budget, or until stable) |
+//! | [`SamplingMode`] | How many samples are measured (fixed count, time budget, adaptive, or median CI) |
+//! | [`CiTarget`] | Precision goal for [`SamplingMode::MedianCi`] |
+//! | [`MedianCi`] | Bootstrap confidence interval for the median |
+//! | [`StreamedSample`] | One sample as emitted by [`with_sample_stream`] |
+//! | [`record_metric`] | Attach a custom measurement to the current report |
 //! | [`BenchSample`] | Single timing measurement in nanoseconds |
 //! | [`BenchReport`] | Complete results with all samples |
 //! | [`TimingError`] | Error conditions during benchmarking |
+//! | [`Clock`] | Time source used to measure iterations |
+//! | [`ClockSource`] | Which clock a report was measured with |
+//! | [`SeededRng`] | Deterministic RNG for generating benchmark inputs |
+//!
+//! ## Clock Source
+//!
+//! Iterations are timed with [`SystemClock`], which reads
+//! [`std::time::Instant`] by default. `Instant` is monotonic but coarse on
+//! some devices; the `precise-clock` feature switches to
+//! `clock_gettime(CLOCK_MONOTONIC_RAW)` on Linux and Android and
+//! `mach_absolute_time` on iOS and macOS, falling back to `Instant` where the
+//! call is unavailable. The clock that was used is recorded in
+//! [`BenchReport::clock`] so reports from different devices can be compared
+//! with their timer resolution in mind. [`run_closure_with_clock`] accepts any
+//! other [`Clock`] implementation.
+//!
+//! ## CPU Time
+//!
+//! Wall-clock time on a phone includes every moment the benchmark thread was
+//! preempted. With the `cpu-time` feature each iteration also reads the
+//! thread's CPU clock (`CLOCK_THREAD_CPUTIME_ID` on Linux, Android, iOS and
+//! macOS) and records it in [`BenchSample::cpu_ns`]. A CPU time well below
+//! the wall time points at contention rather than a slower function. Where
+//! the clock is unavailable samples carry wall time only.
+//!
+//! ## Seeded Inputs
+//!
+//! Per-iteration setup often generates random inputs, which makes a slow run
+//! hard to reproduce. [`run_closure_with_seeded_setup_per_iter`] hands the
+//! setup closure a [`SeededRng`] seeded from [`BenchSpec::seed`], or from a
+//! hash of the benchmark name when no seed is given, and records the seed in
+//! [`BenchReport::seed`]. Re-running with that seed regenerates the same
+//! inputs:
+//!
+//! ```
+//! use mobench_sdk::timing::{BenchSpec, run_closure_with_seeded_setup_per_iter};
+//!
+//! let spec = BenchSpec::new("sort", 10, 2)?.with_seed(42);
+//! let report = run_closure_with_seeded_setup_per_iter(
+//!     spec,
+//!     |rng| (0..100).map(|_| rng.next_u32()).collect::<Vec<_>>(),
+//!     |mut data| {
+//!         data.sort_unstable();
+//!         std::hint::black_box(data);
+//!         Ok(())
+//!     },
+//! )?;
+//! assert_eq!(report.seed, Some(42));
+//! # Ok::<(), mobench_sdk::timing::TimingError>(())
+//! ```
+//!
+//! ## Streaming Samples
+//!
+//! Long device runs can report progress by streaming each sample as soon as it
+//! is measured. Inside [`with_sample_stream`], every runner on the current
+//! thread writes one `BENCH_SAMPLE_JSON {...}` line per measured iteration:
+//!
+//! ```
+//! use mobench_sdk::timing::{BenchSpec, run_closure, with_sample_stream};
+//!
+//! let spec = BenchSpec::new("streamed", 3, 0)?;
+//! let report = with_sample_stream(std::io::stdout(), || run_closure(spec, || Ok(())))?;
+//! assert_eq!(report.samples.len(), 3);
+//! # Ok::<(), mobench_sdk::timing::TimingError>(())
+//! ```
+//!
+//! ## Custom Metrics
+//!
+//! Latency is not the only number worth tracking. A benchmark can report any
+//! other per-iteration measurement (proof size, gas used, bytes allocated)
+//! by calling [`record_metric`] from inside the closure. Values recorded
+//! during the measured iterations end up in [`BenchReport::metrics`], keyed
+//! by name; values recorded during warmup are discarded:
+//!
+//! ```
+//! use mobench_sdk::timing::{BenchSpec, record_metric, run_closure};
+//!
+//! let spec = BenchSpec::new("prove", 3, 1)?;
+//! let report = run_closure(spec, || {
+//!     let proof = vec![0u8; 192];
+//!     record_metric("proof_bytes", proof.len() as f64);
+//!     Ok(())
+//! })?;
+//! assert_eq!(report.metrics["proof_bytes"], vec![192.0; 3]);
+//! # Ok::<(), mobench_sdk::timing::TimingError>(())
+//! ```
+//!
+//! ## Panics and Timeouts
+//!
+//! Every iteration runs under [`std::panic::catch_unwind`], so a panicking
+//! benchmark yields [`TimingError::Execution`] with the panic message instead
+//! of unwinding across the FFI boundary into the host app.
+//!
+//! [`BenchSpec::per_iteration_timeout`] additionally fails the run with
+//! [`TimingError::Timeout`] when an iteration takes longer than the limit.
+//! Only the timed part of an iteration counts; per-iteration setup does not. A
+//! watchdog thread logs the overrun as it happens and aborts the process if
+//! the iteration is still running [`HANG_GRACE`] past the limit, since a hung
+//! iteration cannot be interrupted from safe Rust.
+//!
+//! ```
+//! use std::time::Duration;
+//! use mobench_sdk::timing::{BenchSpec, run_closure, TimingError};
+//!
+//! let spec = BenchSpec::new("slow", 3, 0)?
+//!     .with_per_iteration_timeout(Duration::from_millis(1));
+//! let err = run_closure(spec, || {
+//!     std::thread::sleep(Duration::from_millis(20));
+//!     Ok(())
+//! })
+//! .unwrap_err();
+//! assert!(matches!(err, TimingError::Timeout { .. }));
+//! # Ok::<(), TimingError>(())
+//! ```
+//!
+//! [`BenchSpec::timeout`] is a budget for the whole benchmark instead. When
+//! it runs out, no further iterations start; the run still succeeds with the
+//! samples measured so far and [`BenchReport::timed_out`] set, so one slow
+//! function does not cost the results of the rest of a suite.
+//!
+//! ## Dropping Ramp-Up Samples
+//!
+//! Even after warmup, the first measured iterations on a phone often still
+//! carry cache or JIT ramp-up. [`BenchSpec::drop_first_n`] measures and then
+//! discards that many samples at the start of the measurement phase; the
+//! report keeps the rest and records the count in
+//! [`BenchReport::dropped_samples`]. Unlike extra warmup this does not add
+//! iterations to a fixed-iteration run: the dropped samples come out of
+//! `iterations`.
 //!
 //! ## Feature Flags
 //!
@@ -62,7 +197,15 @@
 //! mobench-sdk = { version = "0.1", default-features = false, features = ["runner-only"] }
 //! ```
 
+use crate::stats::{self, SampleStats};
 use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 use thiserror::Error;
 
@@ -96,6 +239,13 @@ use thiserror::Error;
 ///     name: "my_bench".to_string(),
 ///     iterations: 50,
 ///     warmup: 5,
+///     warmup_policy: Default::default(),
+///     sampling: Default::default(),
+///     per_iteration_timeout: None,
+///     timeout: None,
+///     capture_warmup: false,
+///     seed: None,
+///     drop_first_n: 0,
 /// };
 ///
 /// let json = serde_json::to_string(&spec)?;
@@ -120,7 +270,62 @@ pub struct BenchSpec {
     ///
     /// Warmup iterations are not recorded. They allow CPU caches to warm
     /// and any JIT compilation to complete. Can be zero.
+    ///
+    /// With [`WarmupPolicy::UntilStable`] this is the minimum number of
+    /// warmup iterations rather than an exact count.
     pub warmup: u32,
+
+    /// How the warmup phase decides when to stop.
+    ///
+    /// Defaults to [`WarmupPolicy::Fixed`], which runs exactly `warmup`
+    /// iterations. Older specs without this field deserialize to the default.
+    #[serde(default)]
+    pub warmup_policy: WarmupPolicy,
+
+    /// How many samples the measurement phase collects.
+    ///
+    /// Defaults to [`SamplingMode::FixedIterations`], which measures exactly
+    /// `iterations` samples. Older specs without this field deserialize to
+    /// the default.
+    #[serde(default)]
+    pub sampling: SamplingMode,
+
+    /// Maximum wall-clock time for a single iteration.
+    ///
+    /// `None` (the default) disables the limit. See the
+    /// [module docs](self#panics-and-timeouts) for how overruns are handled.
+    #[serde(default)]
+    pub per_iteration_timeout: Option<Duration>,
+
+    /// Wall-clock budget for the whole benchmark, warmup included.
+    ///
+    /// `None` (the default) disables the budget. See the
+    /// [module docs](self#panics-and-timeouts) for what happens when it runs out.
+    #[serde(default)]
+    pub timeout: Option<Duration>,
+
+    /// Record warmup durations in [`BenchReport::warmup_samples`].
+    ///
+    /// Off by default to keep reports small. Useful for spotting JIT or
+    /// thermal ramp-up that the warmup count does not cover.
+    #[serde(default)]
+    pub capture_warmup: bool,
+
+    /// Seed for the [`SeededRng`] passed to seeded per-iteration setup.
+    ///
+    /// `None` (the default) derives the seed from [`name`](Self::name), so
+    /// inputs stay the same from run to run. See [`BenchSpec::effective_seed`].
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// Measured samples to discard at the start of the measurement phase.
+    ///
+    /// With [`SamplingMode::FixedIterations`] they count toward
+    /// [`iterations`](Self::iterations), so at least one iteration must be
+    /// left over; the other sampling modes drop them before they start
+    /// counting. Defaults to 0.
+    #[serde(default)]
+    pub drop_first_n: u32,
 }
 
 impl BenchSpec {
@@ -158,10 +363,519 @@ impl BenchSpec {
             name: name.into(),
             iterations,
             warmup,
+            warmup_policy: WarmupPolicy::default(),
+            sampling: SamplingMode::default(),
+            per_iteration_timeout: None,
+            timeout: None,
+            capture_warmup: false,
+            seed: None,
+            drop_first_n: 0,
+        })
+    }
+
+    /// Sets the warmup policy for this specification.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mobench_sdk::timing::{BenchSpec, WarmupPolicy};
+    ///
+    /// let spec = BenchSpec::new("test", 100, 10)?
+    ///     .with_warmup_policy(WarmupPolicy::until_stable());
+    /// assert!(matches!(spec.warmup_policy, WarmupPolicy::UntilStable { .. }));
+    /// # Ok::<(), mobench_sdk::timing::TimingError>(())
+    /// ```
+    pub fn with_warmup_policy(mut self, policy: WarmupPolicy) -> Self {
+        self.warmup_policy = policy;
+        self
+    }
+
+    /// Sets the sampling mode for this specification.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use mobench_sdk::timing::{BenchSpec, SamplingMode};
+    ///
+    /// let spec = BenchSpec::new("test", 100, 10)?
+    ///     .with_sampling_mode(SamplingMode::FixedTime(Duration::from_secs(5)));
+    /// assert!(matches!(spec.sampling, SamplingMode::FixedTime(_)));
+    /// # Ok::<(), mobench_sdk::timing::TimingError>(())
+    /// ```
+    pub fn with_sampling_mode(mut self, sampling: SamplingMode) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Fails the run when a single iteration takes longer than `timeout`.
+    /// Untimed per-iteration setup does not count toward it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use mobench_sdk::timing::BenchSpec;
+    ///
+    /// let spec = BenchSpec::new("test", 100, 10)?
+    ///     .with_per_iteration_timeout(Duration::from_secs(2));
+    /// assert_eq!(spec.per_iteration_timeout, Some(Duration::from_secs(2)));
+    /// # Ok::<(), mobench_sdk::timing::TimingError>(())
+    /// ```
+    pub fn with_per_iteration_timeout(mut self, timeout: Duration) -> Self {
+        self.per_iteration_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a wall-clock budget for the whole benchmark.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use mobench_sdk::timing::{BenchSpec, run_closure};
+    ///
+    /// let spec = BenchSpec::new("slow", 1_000, 0)?.with_timeout(Duration::from_millis(20));
+    /// let report = run_closure(spec, || {
+    ///     std::thread::sleep(Duration::from_millis(5));
+    ///     Ok(())
+    /// })?;
+    /// assert!(report.timed_out);
+    /// assert!(!report.samples.is_empty() && report.samples.len() < 1_000);
+    /// # Ok::<(), mobench_sdk::timing::TimingError>(())
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Records warmup durations alongside the measured samples.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mobench_sdk::timing::{BenchSpec, run_closure};
+    ///
+    /// let spec = BenchSpec::new("test", 5, 3)?.with_warmup_capture(true);
+    /// let report = run_closure(spec, || Ok(()))?;
+    /// assert_eq!(report.warmup_samples.len(), 3);
+    /// # Ok::<(), mobench_sdk::timing::TimingError>(())
+    /// ```
+    pub fn with_warmup_capture(mut self, capture: bool) -> Self {
+        self.capture_warmup = capture;
+        self
+    }
+
+    /// Seeds the [`SeededRng`] used by seeded per-iteration setup.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mobench_sdk::timing::BenchSpec;
+    ///
+    /// let spec = BenchSpec::new("test", 100, 10)?.with_seed(7);
+    /// assert_eq!(spec.effective_seed(), 7);
+    /// # Ok::<(), mobench_sdk::timing::TimingError>(())
+    /// ```
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Discards the first `n` measured samples.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mobench_sdk::timing::{BenchSpec, run_closure};
+    ///
+    /// let spec = BenchSpec::new("test", 10, 0)?.with_drop_first_n(3);
+    /// let report = run_closure(spec, || Ok(()))?;
+    /// assert_eq!(report.samples.len(), 7);
+    /// assert_eq!(report.dropped_samples, 3);
+    /// # Ok::<(), mobench_sdk::timing::TimingError>(())
+    /// ```
+    pub fn with_drop_first_n(mut self, n: u32) -> Self {
+        self.drop_first_n = n;
+        self
+    }
+
+    /// The seed a seeded run uses: [`seed`](Self::seed) when set, otherwise a
+    /// 64-bit FNV-1a hash of the benchmark name.
+    pub fn effective_seed(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        self.seed.unwrap_or_else(|| {
+            self.name.bytes().fold(FNV_OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
         })
     }
 }
 
+/// Deterministic pseudo-random number generator for benchmark inputs.
+///
+/// A xorshift64* generator: small, fast and identical on every platform, so
+/// a seed reproduces the same inputs on a laptop and on a device. It is not
+/// suitable for cryptography.
+///
+/// # Example
+///
+/// ```
+/// use mobench_sdk::timing::SeededRng;
+///
+/// let mut a = SeededRng::new(42);
+/// let mut b = SeededRng::new(42);
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// assert!(a.gen_range(10..20) < 20);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Creates a generator from `seed`. Every seed, including zero, is valid.
+    pub fn new(seed: u64) -> Self {
+        // One splitmix64 round spreads nearby seeds apart and never yields the
+        // all-zero state that xorshift cannot leave.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        Self {
+            state: if z == 0 { 0x9e37_79b9_7f4a_7c15 } else { z },
+        }
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns the next 32 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a float uniformly distributed in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a value in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    pub fn gen_range(&mut self, range: std::ops::Range<u64>) -> u64 {
+        assert!(range.start < range.end, "gen_range called with an empty range");
+        let span = range.end - range.start;
+        range.start + ((u128::from(self.next_u64()) * u128::from(span)) >> 64) as u64
+    }
+
+    /// Fills `dest` with random bytes.
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// Controls how many warmup iterations run before measurement starts.
+///
+/// # Example
+///
+/// ```
+/// use mobench_sdk::timing::{BenchSpec, WarmupPolicy, run_closure};
+///
+/// let spec = BenchSpec::new("stable", 10, 5)?.with_warmup_policy(WarmupPolicy::UntilStable {
+///     window: 5,
+///     max_cv: 0.5,
+///     max_warmup: 500,
+/// });
+///
+/// let report = run_closure(spec, || {
+///     std::hint::black_box((0..1000u64).sum::<u64>());
+///     Ok(())
+/// })?;
+///
+/// assert!(report.warmup_iterations >= 5);
+/// # Ok::<(), mobench_sdk::timing::TimingError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum WarmupPolicy {
+    /// Run exactly [`BenchSpec::warmup`] iterations.
+    #[default]
+    Fixed,
+
+    /// Keep warming up until the last `window` warmup samples are stable.
+    ///
+    /// Warmup runs at least [`BenchSpec::warmup`] iterations (and at least
+    /// `window`), then stops as soon as the coefficient of variation of the
+    /// most recent `window` samples is at or below `max_cv`. If that does not
+    /// happen within `max_warmup` iterations the run fails with
+    /// [`TimingError::WarmupUnstable`].
+    UntilStable {
+        /// Number of trailing warmup samples used to compute the CV.
+        window: u32,
+        /// Maximum coefficient of variation (stddev / mean) considered stable.
+        max_cv: f64,
+        /// Upper bound on warmup iterations before giving up.
+        max_warmup: u32,
+    },
+
+    /// Keep warming up until this much wall-clock time has passed, like
+    /// criterion's `warm_up_time`.
+    ///
+    /// At least one warmup iteration always runs, and at most
+    /// [`SamplingMode::MAX_SAMPLES`]. [`BenchSpec::warmup`] is ignored.
+    FixedTime(Duration),
+}
+
+impl WarmupPolicy {
+    /// Default window size for [`WarmupPolicy::until_stable`].
+    pub const DEFAULT_WINDOW: u32 = 10;
+    /// Default CV threshold for [`WarmupPolicy::until_stable`].
+    pub const DEFAULT_MAX_CV: f64 = 0.05;
+    /// Default warmup cap for [`WarmupPolicy::until_stable`].
+    pub const DEFAULT_MAX_WARMUP: u32 = 1_000;
+
+    /// Returns an [`WarmupPolicy::UntilStable`] policy with default settings
+    /// (window of 10 samples, 5% CV, at most 1000 warmup iterations).
+    pub fn until_stable() -> Self {
+        Self::UntilStable {
+            window: Self::DEFAULT_WINDOW,
+            max_cv: Self::DEFAULT_MAX_CV,
+            max_warmup: Self::DEFAULT_MAX_WARMUP,
+        }
+    }
+}
+
+/// Controls how many samples the measurement phase collects.
+///
+/// Device farms bill by the minute, so a fixed iteration count is a poor
+/// budget for benchmarks whose speed varies widely across devices: the same
+/// count can take seconds on a flagship and minutes on a low-end phone.
+/// [`SamplingMode::FixedTime`] bounds each benchmark's measurement time
+/// instead, [`SamplingMode::Adaptive`] stops as soon as the mean is
+/// precise enough, and [`SamplingMode::MedianCi`] stops once the median's
+/// bootstrap confidence interval is narrow enough.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use mobench_sdk::timing::{BenchSpec, SamplingMode, run_closure};
+///
+/// let spec = BenchSpec::new("adaptive", 1, 0)?.with_sampling_mode(SamplingMode::Adaptive {
+///     min_samples: 10,
+///     max_time: Duration::from_millis(200),
+///     target_rse: 0.05,
+/// });
+///
+/// let report = run_closure(spec, || {
+///     std::hint::black_box((0..1000u64).sum::<u64>());
+///     Ok(())
+/// })?;
+///
+/// assert!(report.samples.len() >= 10);
+/// # Ok::<(), mobench_sdk::timing::TimingError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SamplingMode {
+    /// Measure exactly [`BenchSpec::iterations`] samples.
+    #[default]
+    FixedIterations,
+
+    /// Keep measuring until this much wall-clock time has passed.
+    ///
+    /// At least one sample is always taken, and at most
+    /// [`SamplingMode::MAX_SAMPLES`] to bound memory for very fast functions.
+    /// [`BenchSpec::iterations`] is ignored.
+    FixedTime(Duration),
+
+    /// Keep measuring until the mean is precise enough.
+    ///
+    /// Sampling stops once at least `min_samples` samples have been taken and
+    /// the relative standard error of the mean (standard error / mean) is at
+    /// or below `target_rse`, or when `max_time` of wall-clock time has
+    /// passed, whichever comes first. [`BenchSpec::iterations`] is ignored.
+    Adaptive {
+        /// Minimum number of samples before the stopping rule is checked.
+        min_samples: u32,
+        /// Upper bound on measurement wall-clock time.
+        max_time: Duration,
+        /// Target relative standard error of the mean, e.g. `0.01` for 1%.
+        target_rse: f64,
+    },
+
+    /// Keep measuring until the 95% bootstrap confidence interval around the
+    /// median is narrow enough.
+    ///
+    /// Sampling stops once at least `min_samples` samples have been taken and
+    /// the interval's half-width meets `target`, or after `max_samples`
+    /// samples. The interval is re-estimated as samples accumulate (roughly
+    /// every 10% more samples), not after every sample. The achieved interval
+    /// is recorded in [`BenchReport::median_ci`]. [`BenchSpec::iterations`] is
+    /// ignored; warmup runs as usual before measurement.
+    MedianCi {
+        /// Minimum number of samples before the stopping rule is checked.
+        min_samples: u32,
+        /// Upper bound on the number of samples.
+        max_samples: u32,
+        /// Required half-width of the confidence interval.
+        target: CiTarget,
+    },
+}
+
+/// Precision goal for [`SamplingMode::MedianCi`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CiTarget {
+    /// Maximum half-width in nanoseconds.
+    Nanos(u64),
+    /// Maximum half-width as a percentage of the median, e.g. `1.0` for 1%.
+    PercentOfMedian(f64),
+}
+
+impl CiTarget {
+    /// Whether `ci` is at least as precise as this target.
+    pub fn is_met(&self, ci: &MedianCi) -> bool {
+        match *self {
+            CiTarget::Nanos(max) => ci.half_width_ns() <= max,
+            CiTarget::PercentOfMedian(max) => ci.half_width_pct() <= max,
+        }
+    }
+}
+
+/// A bootstrap confidence interval for the median sample duration.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MedianCi {
+    /// Median of the measured samples.
+    pub median_ns: u64,
+    /// Lower bound of the interval.
+    pub lower_ns: u64,
+    /// Upper bound of the interval.
+    pub upper_ns: u64,
+    /// Confidence level, e.g. `0.95`.
+    pub confidence: f64,
+}
+
+impl MedianCi {
+    /// Half the interval's width in nanoseconds.
+    pub fn half_width_ns(&self) -> u64 {
+        (self.upper_ns - self.lower_ns) / 2
+    }
+
+    /// Half the interval's width as a percentage of the median.
+    pub fn half_width_pct(&self) -> f64 {
+        if self.median_ns == 0 {
+            return 0.0;
+        }
+        self.half_width_ns() as f64 / self.median_ns as f64 * 100.0
+    }
+}
+
+/// Number of bootstrap resamples used by [`bootstrap_median_ci`].
+const BOOTSTRAP_RESAMPLES: usize = 200;
+
+/// Estimates a percentile bootstrap confidence interval for the median of
+/// `samples` at the given `confidence` level (e.g. `0.95`).
+///
+/// Resampling uses a fixed-seed generator, so the same samples always give
+/// the same interval. Returns `None` for fewer than two samples.
+///
+/// # Example
+///
+/// ```
+/// use mobench_sdk::timing::bootstrap_median_ci;
+///
+/// let samples: Vec<u64> = (0..100).map(|i| 1_000 + i % 7).collect();
+/// let ci = bootstrap_median_ci(&samples, 0.95).unwrap();
+/// assert!(ci.lower_ns <= ci.median_ns && ci.median_ns <= ci.upper_ns);
+/// ```
+pub fn bootstrap_median_ci(samples: &[u64], confidence: f64) -> Option<MedianCi> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let mut scratch = samples.to_vec();
+    let median_ns = median_in_place(&mut scratch);
+
+    // SplitMix64: small, fast, and good enough for resampling indices
+    let mut state = 0x9e37_79b9_7f4a_7c15_u64 ^ samples.len() as u64;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+
+    let mut medians = Vec::with_capacity(BOOTSTRAP_RESAMPLES);
+    for _ in 0..BOOTSTRAP_RESAMPLES {
+        for slot in scratch.iter_mut() {
+            *slot = samples[(next() % samples.len() as u64) as usize];
+        }
+        medians.push(median_in_place(&mut scratch));
+    }
+    medians.sort_unstable();
+
+    let tail = (1.0 - confidence.clamp(0.0, 1.0)) / 2.0;
+    let last = (medians.len() - 1) as f64;
+    let lower_ns = medians[(tail * last).floor() as usize];
+    let upper_ns = medians[((1.0 - tail) * last).ceil() as usize];
+    Some(MedianCi {
+        median_ns,
+        lower_ns: lower_ns.min(median_ns),
+        upper_ns: upper_ns.max(median_ns),
+        confidence,
+    })
+}
+
+/// Median of `values`, reordering them in the process.
+fn median_in_place(values: &mut [u64]) -> u64 {
+    let len = values.len();
+    let (lower, upper, _) = values.select_nth_unstable(len / 2);
+    let upper = *upper;
+    if len % 2 == 1 {
+        upper
+    } else {
+        let below = *lower.iter().max().expect("even length >= 2 has a lower half");
+        stats::midpoint(below, upper)
+    }
+}
+
+impl SamplingMode {
+    /// Hard cap on samples collected by the time-bounded modes.
+    pub const MAX_SAMPLES: u32 = 1_000_000;
+    /// Default minimum sample count for [`SamplingMode::adaptive`].
+    pub const DEFAULT_MIN_SAMPLES: u32 = 10;
+    /// Default time limit for [`SamplingMode::adaptive`].
+    pub const DEFAULT_MAX_TIME: Duration = Duration::from_secs(10);
+    /// Default target relative standard error for [`SamplingMode::adaptive`].
+    pub const DEFAULT_TARGET_RSE: f64 = 0.01;
+
+    /// Returns an [`SamplingMode::Adaptive`] mode with default settings
+    /// (at least 10 samples, 1% relative standard error, at most 10 seconds).
+    pub fn adaptive() -> Self {
+        Self::Adaptive {
+            min_samples: Self::DEFAULT_MIN_SAMPLES,
+            max_time: Self::DEFAULT_MAX_TIME,
+            target_rse: Self::DEFAULT_TARGET_RSE,
+        }
+    }
+}
+
 /// A single timing sample from a benchmark iteration.
 ///
 /// Contains the elapsed time in nanoseconds for one execution of the
@@ -172,7 +886,7 @@ impl BenchSpec {
 /// ```
 /// use mobench_sdk::timing::BenchSample;
 ///
-/// let sample = BenchSample { duration_ns: 1_500_000 };
+/// let sample = BenchSample { duration_ns: 1_500_000, cpu_ns: None };
 ///
 /// // Convert to milliseconds
 /// let ms = sample.duration_ns as f64 / 1_000_000.0;
@@ -182,93 +896,1016 @@ impl BenchSpec {
 pub struct BenchSample {
     /// Duration of the iteration in nanoseconds.
     ///
-    /// Measured using [`std::time::Instant`] for monotonic, high-resolution timing.
+    /// Measured with the clock recorded in [`BenchReport::clock`].
     pub duration_ns: u64,
+
+    /// CPU time the benchmark thread spent in the iteration, in nanoseconds.
+    ///
+    /// Only recorded with the `cpu-time` feature on platforms exposing a
+    /// per-thread CPU clock; see the [module docs](self#cpu-time).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_ns: Option<u64>,
+}
+
+impl From<Duration> for BenchSample {
+    /// A wall-clock-only sample.
+    fn from(duration: Duration) -> Self {
+        Self {
+            duration_ns: duration.as_nanos() as u64,
+            cpu_ns: None,
+        }
+    }
+}
+
+/// Times one call of `f` with `clock`, also reading the thread CPU clock
+/// when it is available.
+fn time_iteration<C, F>(clock: &C, f: F) -> Result<BenchSample, TimingError>
+where
+    C: Clock + ?Sized,
+    F: FnOnce() -> Result<(), TimingError>,
+{
+    start_timed_section();
+    let cpu_start = thread_cpu_ns();
+    let start = clock.now_ns();
+    f()?;
+    let elapsed = clock.elapsed_since(start);
+    let cpu_ns = cpu_start
+        .zip(thread_cpu_ns())
+        .map(|(start, end)| end.saturating_sub(start));
+    Ok(BenchSample {
+        duration_ns: elapsed.as_nanos() as u64,
+        cpu_ns,
+    })
+}
+
+/// CPU time consumed by the calling thread, in nanoseconds.
+#[cfg(all(feature = "cpu-time", any(target_os = "linux", target_os = "android")))]
+fn thread_cpu_ns() -> Option<u64> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid, writable timespec for the duration of the call.
+    let rc = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) };
+    (rc == 0).then(|| ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64)
+}
+
+/// CPU time consumed by the calling thread, in nanoseconds.
+#[cfg(all(feature = "cpu-time", target_vendor = "apple"))]
+fn thread_cpu_ns() -> Option<u64> {
+    /// `CLOCK_THREAD_CPUTIME_ID` in `<time.h>`.
+    const CLOCK_THREAD_CPUTIME_ID: u32 = 16;
+    unsafe extern "C" {
+        fn clock_gettime_nsec_np(clock_id: u32) -> u64;
+    }
+    // SAFETY: takes a clock id by value and has no other preconditions.
+    let ns = unsafe { clock_gettime_nsec_np(CLOCK_THREAD_CPUTIME_ID) };
+    // Returns 0 on failure
+    (ns != 0).then_some(ns)
+}
+
+/// Without a per-thread CPU clock, samples carry wall time only.
+#[cfg(not(all(
+    feature = "cpu-time",
+    any(target_os = "linux", target_os = "android", target_vendor = "apple")
+)))]
+fn thread_cpu_ns() -> Option<u64> {
+    None
+}
+
+/// Identifies the clock a report was measured with.
+///
+/// Serialized as a snake_case string (`"instant"`, `"monotonic_raw"`,
+/// `"mach_absolute_time"`). Reports from runners that predate this field
+/// deserialize as [`ClockSource::Instant`], which is what they used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockSource {
+    /// [`std::time::Instant`], the portable default.
+    #[default]
+    Instant,
+    /// `clock_gettime(CLOCK_MONOTONIC_RAW)` on Linux and Android, which is
+    /// not slewed by NTP adjustments.
+    MonotonicRaw,
+    /// `mach_absolute_time` on iOS and macOS, scaled by `mach_timebase_info`.
+    MachAbsoluteTime,
+    /// A caller-supplied [`Clock`] passed to [`run_closure_with_clock`].
+    Custom,
+}
+
+impl ClockSource {
+    /// The serialized name of this source.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Instant => "instant",
+            Self::MonotonicRaw => "monotonic_raw",
+            Self::MachAbsoluteTime => "mach_absolute_time",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+impl std::fmt::Display for ClockSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A monotonic time source used to time benchmark iterations.
+///
+/// # Example
+///
+/// ```
+/// use mobench_sdk::timing::{BenchSpec, Clock, ClockSource, run_closure_with_clock};
+/// use std::cell::Cell;
+///
+/// /// A fake clock that advances 100ns per reading.
+/// struct Ticker(Cell<u64>);
+///
+/// impl Clock for Ticker {
+///     fn now_ns(&self) -> u64 {
+///         self.0.set(self.0.get() + 100);
+///         self.0.get()
+///     }
+///
+///     fn source(&self) -> ClockSource {
+///         ClockSource::Custom
+///     }
+/// }
+///
+/// let spec = BenchSpec::new("ticks", 3, 0)?;
+/// let report = run_closure_with_clock(spec, &Ticker(Cell::new(0)), || Ok(()))?;
+/// assert!(report.samples.iter().all(|s| s.duration_ns == 100));
+/// assert_eq!(report.clock, ClockSource::Custom);
+/// # Ok::<(), mobench_sdk::timing::TimingError>(())
+/// ```
+pub trait Clock {
+    /// Current reading in nanoseconds, relative to an arbitrary fixed origin.
+    fn now_ns(&self) -> u64;
+
+    /// Which source this clock reads, recorded in [`BenchReport::clock`].
+    fn source(&self) -> ClockSource;
+
+    /// Time elapsed since an earlier [`Clock::now_ns`] reading.
+    fn elapsed_since(&self, start_ns: u64) -> Duration {
+        Duration::from_nanos(self.now_ns().saturating_sub(start_ns))
+    }
+}
+
+/// The highest-resolution clock available on this platform.
+///
+/// Without the `precise-clock` feature this always reads
+/// [`std::time::Instant`]. With it, [`SystemClock::new`] picks
+/// `CLOCK_MONOTONIC_RAW` on Linux and Android or `mach_absolute_time` on
+/// Apple platforms, and falls back to `Instant` when the platform call fails.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    inner: SystemClockInner,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SystemClockInner {
+    Instant(Instant),
+    #[cfg(all(feature = "precise-clock", any(target_os = "linux", target_os = "android")))]
+    MonotonicRaw,
+    #[cfg(all(feature = "precise-clock", target_vendor = "apple"))]
+    MachAbsoluteTime { numer: u32, denom: u32 },
+}
+
+impl SystemClock {
+    /// Selects the best clock available on this platform.
+    pub fn new() -> Self {
+        #[cfg(all(feature = "precise-clock", any(target_os = "linux", target_os = "android")))]
+        if precise::monotonic_raw_ns().is_some() {
+            return Self {
+                inner: SystemClockInner::MonotonicRaw,
+            };
+        }
+        #[cfg(all(feature = "precise-clock", target_vendor = "apple"))]
+        if let Some((numer, denom)) = precise::mach_timebase() {
+            return Self {
+                inner: SystemClockInner::MachAbsoluteTime { numer, denom },
+            };
+        }
+        Self::instant()
+    }
+
+    /// A clock reading [`std::time::Instant`], regardless of features.
+    pub fn instant() -> Self {
+        Self {
+            inner: SystemClockInner::Instant(Instant::now()),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now_ns(&self) -> u64 {
+        match self.inner {
+            SystemClockInner::Instant(epoch) => epoch.elapsed().as_nanos() as u64,
+            #[cfg(all(feature = "precise-clock", any(target_os = "linux", target_os = "android")))]
+            // `new` verified the call succeeds; CLOCK_MONOTONIC_RAW cannot start failing later.
+            SystemClockInner::MonotonicRaw => precise::monotonic_raw_ns().unwrap_or_default(),
+            #[cfg(all(feature = "precise-clock", target_vendor = "apple"))]
+            SystemClockInner::MachAbsoluteTime { numer, denom } => {
+                (precise::mach_ticks() as u128 * numer as u128 / denom as u128) as u64
+            }
+        }
+    }
+
+    fn source(&self) -> ClockSource {
+        match self.inner {
+            SystemClockInner::Instant(_) => ClockSource::Instant,
+            #[cfg(all(feature = "precise-clock", any(target_os = "linux", target_os = "android")))]
+            SystemClockInner::MonotonicRaw => ClockSource::MonotonicRaw,
+            #[cfg(all(feature = "precise-clock", target_vendor = "apple"))]
+            SystemClockInner::MachAbsoluteTime { .. } => ClockSource::MachAbsoluteTime,
+        }
+    }
+}
+
+#[cfg(all(feature = "precise-clock", any(target_os = "linux", target_os = "android")))]
+mod precise {
+    /// Reads `CLOCK_MONOTONIC_RAW`, or `None` if the kernel rejects it.
+    pub(super) fn monotonic_raw_ns() -> Option<u64> {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `ts` is a valid, writable timespec for the duration of the call.
+        let rc = unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_RAW, &mut ts) };
+        (rc == 0).then(|| ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64)
+    }
+}
+
+#[cfg(all(feature = "precise-clock", target_vendor = "apple"))]
+mod precise {
+    #[repr(C)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+
+    unsafe extern "C" {
+        fn mach_absolute_time() -> u64;
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+    }
+
+    /// The tick-to-nanosecond ratio, or `None` if the kernel call fails.
+    pub(super) fn mach_timebase() -> Option<(u32, u32)> {
+        let mut info = MachTimebaseInfo { numer: 0, denom: 0 };
+        // SAFETY: `info` is a valid, writable struct with the C layout the call expects.
+        let rc = unsafe { mach_timebase_info(&mut info) };
+        (rc == 0 && info.denom != 0).then_some((info.numer, info.denom))
+    }
+
+    pub(super) fn mach_ticks() -> u64 {
+        // SAFETY: takes no arguments and has no preconditions.
+        unsafe { mach_absolute_time() }
+    }
+}
+
+/// Complete benchmark report with all timing samples.
+///
+/// Contains the original specification and all collected samples.
+/// Can be serialized to JSON for storage or transmission.
+///
+/// # Example
+///
+/// ```
+/// use mobench_sdk::timing::{BenchSpec, run_closure};
+///
+/// let spec = BenchSpec::new("example", 50, 5)?;
+/// let report = run_closure(spec, || {
+///     std::hint::black_box(42);
+///     Ok(())
+/// })?;
+///
+/// // Calculate statistics
+/// let stats = report.stats().unwrap();
+/// println!("Min: {} ns, Max: {} ns, Mean: {} ns", stats.min_ns, stats.max_ns, stats.mean_ns);
+/// # Ok::<(), mobench_sdk::timing::TimingError>(())
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// Format version of the serialized report; [`BENCH_REPORT_SCHEMA_VERSION`]
+    /// for reports built by this SDK.
+    ///
+    /// Reports serialized before the field existed deserialize as version 1.
+    #[serde(default = "first_schema_version")]
+    pub schema_version: u32,
+
+    /// The specification used for this benchmark run.
+    pub spec: BenchSpec,
+
+    /// All collected timing samples.
+    ///
+    /// With [`SamplingMode::FixedIterations`] the length equals
+    /// `spec.iterations`; the other modes decide the count at run time.
+    /// Samples are in execution order.
+    pub samples: Vec<BenchSample>,
+
+    /// Number of warmup iterations actually executed before measurement.
+    ///
+    /// Equals `spec.warmup` for [`WarmupPolicy::Fixed`]; for
+    /// [`WarmupPolicy::UntilStable`] it is the count needed to converge, and
+    /// for [`WarmupPolicy::FixedTime`] the count that fit in the budget.
+    #[serde(default)]
+    pub warmup_iterations: u32,
+
+    /// The 95% confidence interval achieved for the median, for
+    /// [`SamplingMode::MedianCi`] runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_ci: Option<MedianCi>,
+
+    /// Warmup durations in execution order, when
+    /// [`BenchSpec::capture_warmup`] is set; empty otherwise.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warmup_samples: Vec<BenchSample>,
+
+    /// The clock the samples were measured with.
+    #[serde(default)]
+    pub clock: ClockSource,
+
+    /// Seed of the [`SeededRng`] that generated the inputs, for runs made
+    /// with [`run_closure_with_seeded_setup_per_iter`].
+    ///
+    /// Set it as [`BenchSpec::seed`] to reproduce the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Custom measurements recorded with [`record_metric`] during the
+    /// measured iterations, keyed by metric name in recording order.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, Vec<f64>>,
+
+    /// Whether [`BenchSpec::timeout`] ran out, leaving fewer samples than
+    /// the spec asked for.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+
+    /// Measured samples discarded per [`BenchSpec::drop_first_n`]; they are
+    /// not in [`samples`](Self::samples).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub dropped_samples: u32,
+}
+
+impl BenchReport {
+    /// Summary statistics of [`samples`](Self::samples); `None` without samples.
+    ///
+    /// Computed with [`crate::stats`], the same code the `mobench` CLI uses
+    /// for its run summaries.
+    pub fn stats(&self) -> Option<SampleStats> {
+        let durations: Vec<u64> = self.samples.iter().map(|sample| sample.duration_ns).collect();
+        SampleStats::from_samples(&durations)
+    }
+}
+
+fn is_zero(count: &u32) -> bool {
+    *count == 0
+}
+
+/// Version of the [`BenchReport`] format this SDK serializes.
+///
+/// Bumped when a field changes meaning or a required field is added, so
+/// readers can upgrade older reports and reject newer ones.
+pub const BENCH_REPORT_SCHEMA_VERSION: u32 = 1;
+
+fn first_schema_version() -> u32 {
+    1
+}
+
+/// Log line marker that prefixes each streamed sample.
+///
+/// Lines look like `BENCH_SAMPLE_JSON {"name":"...","iteration":0,"duration_ns":1234}`
+/// and can coexist with the `BENCH_JSON` / `BENCH_REPORT_JSON_START` report markers.
+pub const SAMPLE_JSON_MARKER: &str = "BENCH_SAMPLE_JSON";
+
+/// A single measured sample tagged with its benchmark and iteration index.
+///
+/// This is the payload written after [`SAMPLE_JSON_MARKER`] while streaming.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StreamedSample {
+    /// Name of the benchmark that produced the sample.
+    pub name: String,
+    /// Zero-based index of the measured iteration (warmup is not counted).
+    pub iteration: u32,
+    /// Duration of the iteration in nanoseconds.
+    pub duration_ns: u64,
+    /// Thread CPU time of the iteration, see [`BenchSample::cpu_ns`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_ns: Option<u64>,
+}
+
+thread_local! {
+    static SAMPLE_STREAM: RefCell<Option<Box<dyn Write>>> = RefCell::new(None);
+    static METRICS: RefCell<BTreeMap<String, Vec<f64>>> = const { RefCell::new(BTreeMap::new()) };
+    static BUDGET: Cell<Budget> = const { Cell::new(Budget { deadline: None, exhausted: false }) };
+    static ITERATION_OFFSET: Cell<u32> = const { Cell::new(0) };
+    static TIMED_SECTION: RefCell<TimedSection> = const { RefCell::new(TimedSection { watchdog: None, started: None }) };
+}
+
+/// The timed part of the guarded iteration running on this thread, so
+/// [`BenchSpec::per_iteration_timeout`] does not count untimed setup.
+struct TimedSection {
+    watchdog: Option<Arc<WatchdogState>>,
+    started: Option<Instant>,
+}
+
+/// Marks the start of the timed part of an iteration, starting its
+/// per-iteration timeout.
+fn start_timed_section() {
+    TIMED_SECTION.with_borrow_mut(|section| {
+        section.started = Some(Instant::now());
+        if let Some(watchdog) = &section.watchdog {
+            watchdog.begin();
+        }
+    });
+}
+
+/// State of [`BenchSpec::timeout`] for the benchmark running on this thread.
+#[derive(Clone, Copy)]
+struct Budget {
+    deadline: Option<Instant>,
+    exhausted: bool,
+}
+
+/// Starts the [`BenchSpec::timeout`] clock; called before warmup.
+fn start_budget(spec: &BenchSpec) {
+    let deadline = spec.timeout.map(|timeout| Instant::now() + timeout);
+    BUDGET.with(|budget| {
+        budget.set(Budget {
+            deadline,
+            exhausted: false,
+        })
+    });
+}
+
+/// Whether the budget has run out, remembering it for [`take_timed_out`].
+fn budget_exhausted() -> bool {
+    BUDGET.with(|budget| {
+        let mut state = budget.get();
+        if state.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            state.exhausted = true;
+            budget.set(state);
+        }
+        state.exhausted
+    })
+}
+
+/// Whether the budget ran out, clearing it for the next benchmark.
+fn take_timed_out() -> bool {
+    BUDGET.with(|budget| {
+        budget
+            .replace(Budget {
+                deadline: None,
+                exhausted: false,
+            })
+            .exhausted
+    })
+}
+
+/// Records one value of a custom metric for the running benchmark.
+///
+/// Call this from the benchmark closure to report measurements other than
+/// time, such as proof size or gas used. Each call appends `value` to
+/// [`BenchReport::metrics`]`[name]` of the report being produced on the
+/// current thread; calls made during warmup or outside a runner are dropped.
+pub fn record_metric(name: impl Into<String>, value: f64) {
+    METRICS.with(|metrics| {
+        metrics
+            .borrow_mut()
+            .entry(name.into())
+            .or_default()
+            .push(value)
+    });
+}
+
+/// Discards metrics recorded so far on the current thread.
+fn clear_metrics() {
+    METRICS.with(|metrics| metrics.borrow_mut().clear());
+}
+
+/// Takes the metrics recorded since the last [`clear_metrics`].
+fn take_metrics() -> BTreeMap<String, Vec<f64>> {
+    METRICS.with(|metrics| std::mem::take(&mut *metrics.borrow_mut()))
+}
+
+/// Runs `f` with samples streamed to `writer` as newline-delimited JSON.
+///
+/// Every benchmark run on the current thread inside `f` writes one
+/// `BENCH_SAMPLE_JSON {...}` line per measured iteration as soon as it is
+/// recorded, and flushes immediately so progress is visible in device logs.
+/// Write errors are ignored; streaming never fails a benchmark. The previous
+/// stream (if any) is restored when `f` returns.
+pub fn with_sample_stream<W, R>(writer: W, f: impl FnOnce() -> R) -> R
+where
+    W: Write + 'static,
+{
+    let previous = SAMPLE_STREAM.with(|stream| stream.replace(Some(Box::new(writer))));
+    let result = f();
+    SAMPLE_STREAM.with(|stream| *stream.borrow_mut() = previous);
+    result
+}
+
+/// Runs `f` with streamed iteration indices starting at `offset` instead of
+/// zero, for runs split across several runner calls.
+#[cfg(feature = "full")]
+pub(crate) fn with_iteration_offset<R>(offset: u32, f: impl FnOnce() -> R) -> R {
+    let previous = ITERATION_OFFSET.with(|current| current.replace(offset));
+    let result = f();
+    ITERATION_OFFSET.with(|current| current.set(previous));
+    result
+}
+
+/// Writes `sample` to the active sample stream, if any.
+fn emit_sample(spec: &BenchSpec, iteration: u32, sample: &BenchSample) {
+    SAMPLE_STREAM.with(|stream| {
+        let mut stream = stream.borrow_mut();
+        let Some(writer) = stream.as_mut() else {
+            return;
+        };
+        let streamed = StreamedSample {
+            name: spec.name.clone(),
+            iteration: ITERATION_OFFSET.with(Cell::get) + iteration,
+            duration_ns: sample.duration_ns,
+            cpu_ns: sample.cpu_ns,
+        };
+        if let Ok(json) = serde_json::to_string(&streamed) {
+            let _ = writeln!(writer, "{} {}", SAMPLE_JSON_MARKER, json);
+            let _ = writer.flush();
+        }
+    });
+}
+
+/// Errors that can occur during benchmark execution.
+///
+/// # Example
+///
+/// ```
+/// use mobench_sdk::timing::{BenchSpec, TimingError};
+///
+/// // Zero iterations produces an error
+/// let result = BenchSpec::new("test", 0, 10);
+/// assert!(matches!(result, Err(TimingError::NoIterations { .. })));
+/// ```
+#[derive(Debug, Error)]
+pub enum TimingError {
+    /// The iteration count was zero or invalid.
+    ///
+    /// At least one iteration is required to produce a measurement. Also
+    /// returned, with the count left over, when [`BenchSpec::drop_first_n`]
+    /// would discard every fixed iteration.
+    /// The error includes the actual value provided for diagnostic purposes.
+    #[error("iterations must be greater than zero (got {count}). Minimum recommended: 10")]
+    NoIterations {
+        /// The invalid iteration count that was provided.
+        count: u32,
+    },
+
+    /// The benchmark function failed during execution.
+    ///
+    /// Contains a description of the failure. Panics inside an iteration are
+    /// reported here as `"benchmark panicked: <message>"`.
+    #[error("benchmark function failed: {0}")]
+    Execution(String),
+
+    /// An iteration exceeded [`BenchSpec::per_iteration_timeout`].
+    #[error("iteration took {elapsed:?}, exceeding the per-iteration timeout of {limit:?}")]
+    Timeout {
+        /// Wall-clock time the iteration took.
+        elapsed: Duration,
+        /// The configured limit.
+        limit: Duration,
+    },
+
+    /// Warmup did not stabilize within the configured cap.
+    ///
+    /// Only produced by [`WarmupPolicy::UntilStable`]. Measurements taken
+    /// after an unstable warmup are unlikely to be representative, so the
+    /// run is aborted instead.
+    #[error(
+        "warmup did not stabilize after {iterations} iterations (last CV {cv:.4} > {max_cv:.4}). Increase max_warmup or relax max_cv"
+    )]
+    WarmupUnstable {
+        /// Number of warmup iterations executed.
+        iterations: u32,
+        /// Coefficient of variation of the final warmup window.
+        cv: f64,
+        /// Threshold that was not reached.
+        max_cv: f64,
+    },
+}
+
+/// Runs [`run_warmup`], also returning the warmup samples when
+/// `spec.capture_warmup` is set.
+fn run_warmup_phase<F, S>(spec: &BenchSpec, mut step: F) -> Result<(u32, Vec<BenchSample>), TimingError>
+where
+    F: FnMut() -> Result<S, TimingError>,
+    S: Into<BenchSample>,
+{
+    start_budget(spec);
+    let mut captured = Vec::new();
+    let iterations = run_warmup(spec, || {
+        let sample = step()?.into();
+        let elapsed = Duration::from_nanos(sample.duration_ns);
+        if spec.capture_warmup {
+            captured.push(sample);
+        }
+        Ok(elapsed)
+    })?;
+    Ok((iterations, captured))
+}
+
+/// Runs the warmup phase according to `spec.warmup_policy`.
+///
+/// `step` executes one warmup iteration and returns the duration of its
+/// timed section. Returns the number of warmup iterations executed.
+fn run_warmup<F>(spec: &BenchSpec, step: F) -> Result<u32, TimingError>
+where
+    F: FnMut() -> Result<Duration, TimingError>,
+{
+    let mut step = guard_iterations(spec, step);
+    match spec.warmup_policy {
+        WarmupPolicy::Fixed => {
+            for executed in 0..spec.warmup {
+                if budget_exhausted() {
+                    return Ok(executed);
+                }
+                step()?;
+            }
+            Ok(spec.warmup)
+        }
+        WarmupPolicy::UntilStable {
+            window,
+            max_cv,
+            max_warmup,
+        } => {
+            let window = window.max(2) as usize;
+            let min_warmup = spec.warmup.max(window as u32);
+            let max_warmup = max_warmup.max(min_warmup);
+            let mut recent: Vec<f64> = Vec::with_capacity(window);
+            let mut cv = f64::INFINITY;
+
+            for i in 0..max_warmup {
+                // Out of time: measure what we can rather than fail as unstable
+                if budget_exhausted() {
+                    return Ok(i);
+                }
+                let elapsed = step()?;
+                if recent.len() == window {
+                    recent.remove(0);
+                }
+                recent.push(elapsed.as_nanos() as f64);
+
+                let executed = i + 1;
+                if recent.len() == window {
+                    cv = coefficient_of_variation(&recent);
+                    if executed >= min_warmup && cv <= max_cv {
+                        return Ok(executed);
+                    }
+                }
+            }
+
+            Err(TimingError::WarmupUnstable {
+                iterations: max_warmup,
+                cv,
+                max_cv,
+            })
+        }
+        WarmupPolicy::FixedTime(budget) => {
+            let started = Instant::now();
+            let mut executed = 0;
+            loop {
+                step()?;
+                executed += 1;
+                if started.elapsed() >= budget
+                    || executed >= SamplingMode::MAX_SAMPLES
+                    || budget_exhausted()
+                {
+                    return Ok(executed);
+                }
+            }
+        }
+    }
+}
+
+/// Rejects a spec that would leave no measured samples.
+fn check_iterations(spec: &BenchSpec) -> Result<(), TimingError> {
+    let kept = match spec.sampling {
+        SamplingMode::FixedIterations => spec.iterations.saturating_sub(spec.drop_first_n),
+        _ => spec.iterations,
+    };
+    if kept == 0 {
+        return Err(TimingError::NoIterations { count: kept });
+    }
+    Ok(())
+}
+
+/// Runs the measurement phase according to `spec.sampling`.
+///
+/// `step` executes one iteration and returns the duration of its timed
+/// section. Each sample is streamed (see [`with_sample_stream`]) as it is
+/// recorded. Stops early, after at least one sample, once
+/// [`BenchSpec::timeout`] runs out. Returns the samples and how many were
+/// dropped per [`BenchSpec::drop_first_n`].
+fn run_measurement<F, S>(spec: &BenchSpec, step: F) -> Result<(Vec<BenchSample>, u32), TimingError>
+where
+    F: FnMut() -> Result<S, TimingError>,
+    S: Into<BenchSample>,
+{
+    // Only metrics from measured iterations belong in the report
+    clear_metrics();
+    let mut step = guard_iterations(spec, step);
+    let mut dropped = 0;
+    let mut record = |samples: &mut Vec<BenchSample>| -> Result<(), TimingError> {
+        // The first call also runs the dropped iterations; neither they nor
+        // their metrics are streamed or kept
+        while dropped < spec.drop_first_n && !budget_exhausted() {
+            step()?;
+            dropped += 1;
+            clear_metrics();
+        }
+        let sample = step()?.into();
+        emit_sample(spec, samples.len() as u32, &sample);
+        samples.push(sample);
+        Ok(())
+    };
+
+    match spec.sampling {
+        SamplingMode::FixedIterations => {
+            // Dropped samples count toward the fixed iterations
+            let kept = spec.iterations.saturating_sub(spec.drop_first_n);
+            let mut samples = Vec::with_capacity(kept as usize);
+            for _ in 0..kept {
+                record(&mut samples)?;
+                if budget_exhausted() {
+                    break;
+                }
+            }
+            Ok((samples, dropped))
+        }
+        SamplingMode::FixedTime(budget) => {
+            let started = Instant::now();
+            let mut samples = Vec::new();
+            loop {
+                record(&mut samples)?;
+                if started.elapsed() >= budget
+                    || samples.len() as u32 >= SamplingMode::MAX_SAMPLES
+                    || budget_exhausted()
+                {
+                    return Ok((samples, dropped));
+                }
+            }
+        }
+        SamplingMode::Adaptive {
+            min_samples,
+            max_time,
+            target_rse,
+        } => {
+            let started = Instant::now();
+            let min_samples = min_samples.max(2) as usize;
+            let mut samples = Vec::with_capacity(min_samples);
+            let mut stats = RunningStats::default();
+            loop {
+                record(&mut samples)?;
+                if let Some(last) = samples.last() {
+                    stats.push(last.duration_ns as f64);
+                }
+                if samples.len() >= min_samples && stats.relative_standard_error() <= target_rse {
+                    return Ok((samples, dropped));
+                }
+                if started.elapsed() >= max_time
+                    || samples.len() as u32 >= SamplingMode::MAX_SAMPLES
+                    || budget_exhausted()
+                {
+                    return Ok((samples, dropped));
+                }
+            }
+        }
+        SamplingMode::MedianCi {
+            min_samples,
+            max_samples,
+            target,
+        } => {
+            let min_samples = min_samples.max(2) as usize;
+            let max_samples = (max_samples as usize).max(min_samples);
+            let mut samples = Vec::with_capacity(min_samples);
+            let mut next_check = min_samples;
+            while samples.len() < max_samples {
+                record(&mut samples)?;
+                if budget_exhausted() {
+                    break;
+                }
+                if samples.len() >= next_check {
+                    let durations: Vec<u64> = samples.iter().map(|s| s.duration_ns).collect();
+                    if bootstrap_median_ci(&durations, MEDIAN_CI_CONFIDENCE)
+                        .is_some_and(|ci| target.is_met(&ci))
+                    {
+                        break;
+                    }
+                    next_check = samples.len() + (samples.len() / 10).max(1);
+                }
+            }
+            Ok((samples, dropped))
+        }
+    }
+}
+
+/// Confidence level used by [`SamplingMode::MedianCi`].
+const MEDIAN_CI_CONFIDENCE: f64 = 0.95;
+
+/// The interval recorded in [`BenchReport::median_ci`], if `spec` asked for one.
+fn achieved_median_ci(spec: &BenchSpec, samples: &[BenchSample]) -> Option<MedianCi> {
+    if !matches!(spec.sampling, SamplingMode::MedianCi { .. }) {
+        return None;
+    }
+    let durations: Vec<u64> = samples.iter().map(|s| s.duration_ns).collect();
+    bootstrap_median_ci(&durations, MEDIAN_CI_CONFIDENCE)
+}
+
+/// How long past [`BenchSpec::per_iteration_timeout`] an iteration may keep
+/// running before the watchdog aborts the process.
+pub const HANG_GRACE: Duration = Duration::from_secs(5);
+
+/// Wraps an iteration step with panic catching and, when the spec sets one,
+/// the per-iteration timeout.
+///
+/// The timeout runs from [`start_timed_section`], so per-iteration setup done
+/// before it is not counted; steps that never mark one are timed whole.
+fn guard_iterations<F, S>(spec: &BenchSpec, mut step: F) -> impl FnMut() -> Result<S, TimingError>
+where
+    F: FnMut() -> Result<S, TimingError>,
+{
+    let limit = spec.per_iteration_timeout;
+    let watchdog = limit.map(|limit| Watchdog::start(spec.name.clone(), limit));
+
+    move || {
+        TIMED_SECTION.with_borrow_mut(|section| {
+            section.watchdog = watchdog.as_ref().map(|watchdog| Arc::clone(&watchdog.state));
+            section.started = None;
+        });
+        let started = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(&mut step));
+        let timed_from = TIMED_SECTION.with_borrow_mut(|section| {
+            if let Some(watchdog) = section.watchdog.take() {
+                watchdog.end();
+            }
+            section.started.take()
+        });
+        let elapsed = timed_from.unwrap_or(started).elapsed();
+
+        let sample = result.map_err(|payload| {
+            TimingError::Execution(format!("benchmark panicked: {}", panic_message(&*payload)))
+        })??;
+        match limit {
+            Some(limit) if elapsed > limit => Err(TimingError::Timeout { elapsed, limit }),
+            _ => Ok(sample),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Background thread watching the iteration in flight.
+///
+/// Logs an iteration to stderr once it exceeds `limit` and aborts the process
+/// if it is still running [`HANG_GRACE`] later. Stopped and joined on drop.
+struct Watchdog {
+    state: Arc<WatchdogState>,
+    handle: Option<JoinHandle<()>>,
+}
+
+struct WatchdogState {
+    epoch: Instant,
+    /// Start of the current iteration in nanoseconds since `epoch`, plus one;
+    /// zero while no iteration is running.
+    started: AtomicU64,
+    stop: AtomicBool,
 }
 
-impl BenchSample {
-    /// Creates a sample from a [`Duration`].
-    fn from_duration(duration: Duration) -> Self {
-        Self {
-            duration_ns: duration.as_nanos() as u64,
+impl Watchdog {
+    fn start(name: String, limit: Duration) -> Self {
+        let state = Arc::new(WatchdogState {
+            epoch: Instant::now(),
+            started: AtomicU64::new(0),
+            stop: AtomicBool::new(false),
+        });
+        let poll = (limit / 4).clamp(Duration::from_millis(1), Duration::from_millis(100));
+        let watched = Arc::clone(&state);
+        // If the thread cannot be spawned the timeout is still enforced after
+        // each iteration; only hang detection is lost.
+        let handle = thread::Builder::new()
+            .name("mobench-watchdog".to_string())
+            .spawn(move || {
+                let mut reported = 0;
+                while !watched.stop.load(Ordering::Acquire) {
+                    let started = watched.started.load(Ordering::Acquire);
+                    if started != 0 {
+                        let elapsed = watched
+                            .epoch
+                            .elapsed()
+                            .saturating_sub(Duration::from_nanos(started - 1));
+                        if elapsed > limit + HANG_GRACE {
+                            eprintln!(
+                                "mobench: iteration of {} still running after {:?} (timeout {:?}); aborting",
+                                name, elapsed, limit
+                            );
+                            std::process::abort();
+                        }
+                        if elapsed > limit && reported != started {
+                            reported = started;
+                            eprintln!("mobench: iteration of {} exceeded its {:?} timeout", name, limit);
+                        }
+                    }
+                    thread::park_timeout(poll);
+                }
+            })
+            .ok();
+        Self { state, handle }
+    }
+}
+
+impl WatchdogState {
+    fn begin(&self) {
+        let now = self.epoch.elapsed().as_nanos() as u64;
+        self.started.store(now + 1, Ordering::Release);
+    }
+
+    fn end(&self) {
+        self.started.store(0, Ordering::Release);
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.state.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
         }
     }
 }
 
-/// Complete benchmark report with all timing samples.
-///
-/// Contains the original specification and all collected samples.
-/// Can be serialized to JSON for storage or transmission.
-///
-/// # Example
-///
-/// ```
-/// use mobench_sdk::timing::{BenchSpec, run_closure};
-///
-/// let spec = BenchSpec::new("example", 50, 5)?;
-/// let report = run_closure(spec, || {
-///     std::hint::black_box(42);
-///     Ok(())
-/// })?;
-///
-/// // Calculate statistics
-/// let samples: Vec<u64> = report.samples.iter()
-///     .map(|s| s.duration_ns)
-///     .collect();
-///
-/// let min = samples.iter().min().unwrap();
-/// let max = samples.iter().max().unwrap();
-/// let mean = samples.iter().sum::<u64>() / samples.len() as u64;
-///
-/// println!("Min: {} ns, Max: {} ns, Mean: {} ns", min, max, mean);
-/// # Ok::<(), mobench_sdk::timing::TimingError>(())
-/// ```
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct BenchReport {
-    /// The specification used for this benchmark run.
-    pub spec: BenchSpec,
+/// Running mean and variance (Welford's algorithm), so adaptive sampling can
+/// check its stopping rule after every sample in constant time.
+#[derive(Default)]
+struct RunningStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
 
-    /// All collected timing samples.
+impl RunningStats {
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Standard error of the mean divided by the mean.
     ///
-    /// The length equals `spec.iterations`. Samples are in execution order.
-    pub samples: Vec<BenchSample>,
+    /// Like [`coefficient_of_variation`], a zero mean is treated as precise.
+    fn relative_standard_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+        if self.mean == 0.0 {
+            return 0.0;
+        }
+        let n = self.count as f64;
+        let stddev = (self.m2 / (n - 1.0)).sqrt();
+        stddev / n.sqrt() / self.mean
+    }
 }
 
-/// Errors that can occur during benchmark execution.
-///
-/// # Example
-///
-/// ```
-/// use mobench_sdk::timing::{BenchSpec, TimingError};
+/// Coefficient of variation (population stddev / mean) of `values`.
 ///
-/// // Zero iterations produces an error
-/// let result = BenchSpec::new("test", 0, 10);
-/// assert!(matches!(result, Err(TimingError::NoIterations { .. })));
-/// ```
-#[derive(Debug, Error)]
-pub enum TimingError {
-    /// The iteration count was zero or invalid.
-    ///
-    /// At least one iteration is required to produce a measurement.
-    /// The error includes the actual value provided for diagnostic purposes.
-    #[error("iterations must be greater than zero (got {count}). Minimum recommended: 10")]
-    NoIterations {
-        /// The invalid iteration count that was provided.
-        count: u32,
-    },
-
-    /// The benchmark function failed during execution.
-    ///
-    /// Contains a description of the failure.
-    #[error("benchmark function failed: {0}")]
-    Execution(String),
+/// A window whose mean is zero (e.g. on coarse clocks) is treated as stable.
+fn coefficient_of_variation(values: &[f64]) -> f64 {
+    stats::cv_pct(values) / 100.0
 }
 
 /// Runs a benchmark by executing a closure repeatedly.
 ///
 /// This is the core benchmarking function. It:
 ///
-/// 1. Executes the closure `spec.warmup` times without recording
+/// 1. Executes the closure `spec.warmup` times without recording (or until
+///    stable, see [`WarmupPolicy`])
 /// 2. Executes the closure `spec.iterations` times, recording each duration
 /// 3. Returns a [`BenchReport`] with all samples
 ///
@@ -323,32 +1960,48 @@ pub enum TimingError {
 ///
 /// # Timing Precision
 ///
-/// Uses [`std::time::Instant`] for timing, which provides monotonic,
-/// nanosecond-resolution measurements on most platforms.
-pub fn run_closure<F>(spec: BenchSpec, mut f: F) -> Result<BenchReport, TimingError>
+/// Uses [`SystemClock`], which reads [`std::time::Instant`] unless the
+/// `precise-clock` feature selects a higher-resolution platform clock. See
+/// [`run_closure_with_clock`] to supply a different [`Clock`].
+pub fn run_closure<F>(spec: BenchSpec, f: F) -> Result<BenchReport, TimingError>
 where
     F: FnMut() -> Result<(), TimingError>,
 {
-    if spec.iterations == 0 {
-        return Err(TimingError::NoIterations {
-            count: spec.iterations,
-        });
-    }
+    run_closure_with_clock(spec, &SystemClock::new(), f)
+}
 
-    // Warmup phase - not measured
-    for _ in 0..spec.warmup {
-        f()?;
-    }
+/// Runs a benchmark like [`run_closure`], timing iterations with `clock`.
+pub fn run_closure_with_clock<C, F>(
+    spec: BenchSpec,
+    clock: &C,
+    mut f: F,
+) -> Result<BenchReport, TimingError>
+where
+    C: Clock + ?Sized,
+    F: FnMut() -> Result<(), TimingError>,
+{
+    check_iterations(&spec)?;
+
+    // Warmup phase - not recorded
+    let (warmup_iterations, warmup_samples) =
+        run_warmup_phase(&spec, || time_iteration(clock, &mut f))?;
 
     // Measurement phase
-    let mut samples = Vec::with_capacity(spec.iterations as usize);
-    for _ in 0..spec.iterations {
-        let start = Instant::now();
-        f()?;
-        samples.push(BenchSample::from_duration(start.elapsed()));
-    }
+    let (samples, dropped_samples) = run_measurement(&spec, || time_iteration(clock, &mut f))?;
 
-    Ok(BenchReport { spec, samples })
+    Ok(BenchReport {
+        schema_version: BENCH_REPORT_SCHEMA_VERSION,
+        median_ci: achieved_median_ci(&spec, &samples),
+        spec,
+        samples,
+        warmup_iterations,
+        warmup_samples,
+        clock: clock.source(),
+        seed: None,
+        metrics: take_metrics(),
+        timed_out: take_timed_out(),
+        dropped_samples,
+    })
 }
 
 /// Runs a benchmark with setup that executes once before all iterations.
@@ -387,29 +2040,35 @@ where
     S: FnOnce() -> T,
     F: FnMut(&T) -> Result<(), TimingError>,
 {
-    if spec.iterations == 0 {
-        return Err(TimingError::NoIterations {
-            count: spec.iterations,
-        });
-    }
+    check_iterations(&spec)?;
+    let clock = SystemClock::new();
 
     // Setup phase - not timed
     let input = setup();
 
     // Warmup phase - not recorded
-    for _ in 0..spec.warmup {
-        f(&input)?;
-    }
+    let (warmup_iterations, warmup_samples) = run_warmup_phase(&spec, || {
+        time_iteration(&clock, || f(&input))
+    })?;
 
     // Measurement phase
-    let mut samples = Vec::with_capacity(spec.iterations as usize);
-    for _ in 0..spec.iterations {
-        let start = Instant::now();
-        f(&input)?;
-        samples.push(BenchSample::from_duration(start.elapsed()));
-    }
+    let (samples, dropped_samples) = run_measurement(&spec, || {
+        time_iteration(&clock, || f(&input))
+    })?;
 
-    Ok(BenchReport { spec, samples })
+    Ok(BenchReport {
+        schema_version: BENCH_REPORT_SCHEMA_VERSION,
+        median_ci: achieved_median_ci(&spec, &samples),
+        spec,
+        samples,
+        warmup_iterations,
+        warmup_samples,
+        clock: clock.source(),
+        seed: None,
+        metrics: take_metrics(),
+        timed_out: take_timed_out(),
+        dropped_samples,
+    })
 }
 
 /// Runs a benchmark with per-iteration setup.
@@ -449,29 +2108,107 @@ where
     S: FnMut() -> T,
     F: FnMut(T) -> Result<(), TimingError>,
 {
-    if spec.iterations == 0 {
-        return Err(TimingError::NoIterations {
-            count: spec.iterations,
-        });
-    }
+    check_iterations(&spec)?;
+    let clock = SystemClock::new();
 
     // Warmup phase
-    for _ in 0..spec.warmup {
+    let (warmup_iterations, warmup_samples) = run_warmup_phase(&spec, || {
         let input = setup();
-        f(input)?;
-    }
+        time_iteration(&clock, || f(input))
+    })?;
 
     // Measurement phase
-    let mut samples = Vec::with_capacity(spec.iterations as usize);
-    for _ in 0..spec.iterations {
+    let (samples, dropped_samples) = run_measurement(&spec, || {
         let input = setup(); // Not timed
 
-        let start = Instant::now();
-        f(input)?; // Only this is timed
-        samples.push(BenchSample::from_duration(start.elapsed()));
-    }
+        time_iteration(&clock, || f(input)) // Only this is timed
+    })?;
+
+    Ok(BenchReport {
+        schema_version: BENCH_REPORT_SCHEMA_VERSION,
+        median_ci: achieved_median_ci(&spec, &samples),
+        spec,
+        samples,
+        warmup_iterations,
+        warmup_samples,
+        clock: clock.source(),
+        seed: None,
+        metrics: take_metrics(),
+        timed_out: take_timed_out(),
+        dropped_samples,
+    })
+}
+
+/// Runs a benchmark with per-iteration setup driven by a [`SeededRng`].
+///
+/// Like [`run_closure_with_setup_per_iter`], but `setup` receives a generator
+/// seeded with [`BenchSpec::effective_seed`], and the seed is recorded in
+/// [`BenchReport::seed`]. Warmup draws from a separate generator, so the
+/// measured inputs depend only on the seed, not on how many warmup
+/// iterations ran.
+///
+/// # Example
+///
+/// ```
+/// use mobench_sdk::timing::{BenchSpec, run_closure_with_seeded_setup_per_iter};
+///
+/// let spec = BenchSpec::new("shuffle", 20, 0)?;
+/// let report = run_closure_with_seeded_setup_per_iter(
+///     spec,
+///     |rng| {
+///         let mut buf = vec![0u8; 1024];
+///         rng.fill_bytes(&mut buf);
+///         buf
+///     },
+///     |buf| {
+///         std::hint::black_box(buf.iter().map(|b| u64::from(*b)).sum::<u64>());
+///         Ok(())
+///     },
+/// )?;
+/// assert!(report.seed.is_some());
+/// # Ok::<(), mobench_sdk::timing::TimingError>(())
+/// ```
+pub fn run_closure_with_seeded_setup_per_iter<S, T, F>(
+    spec: BenchSpec,
+    mut setup: S,
+    mut f: F,
+) -> Result<BenchReport, TimingError>
+where
+    S: FnMut(&mut SeededRng) -> T,
+    F: FnMut(T) -> Result<(), TimingError>,
+{
+    check_iterations(&spec)?;
+    let clock = SystemClock::new();
+    let seed = spec.effective_seed();
+    let mut warmup_rng = SeededRng::new(!seed);
+    let mut rng = SeededRng::new(seed);
+
+    // Warmup phase
+    let (warmup_iterations, warmup_samples) = run_warmup_phase(&spec, || {
+        let input = setup(&mut warmup_rng);
+        time_iteration(&clock, || f(input))
+    })?;
 
-    Ok(BenchReport { spec, samples })
+    // Measurement phase
+    let (samples, dropped_samples) = run_measurement(&spec, || {
+        let input = setup(&mut rng); // Not timed
+
+        time_iteration(&clock, || f(input)) // Only this is timed
+    })?;
+
+    Ok(BenchReport {
+        schema_version: BENCH_REPORT_SCHEMA_VERSION,
+        median_ci: achieved_median_ci(&spec, &samples),
+        spec,
+        samples,
+        warmup_iterations,
+        warmup_samples,
+        clock: clock.source(),
+        seed: Some(seed),
+        metrics: take_metrics(),
+        timed_out: take_timed_out(),
+        dropped_samples,
+    })
 }
 
 /// Runs a benchmark with setup and teardown.
@@ -513,32 +2250,38 @@ where
     F: FnMut(&T) -> Result<(), TimingError>,
     D: FnOnce(T),
 {
-    if spec.iterations == 0 {
-        return Err(TimingError::NoIterations {
-            count: spec.iterations,
-        });
-    }
+    check_iterations(&spec)?;
+    let clock = SystemClock::new();
 
     // Setup phase - not timed
     let input = setup();
 
     // Warmup phase
-    for _ in 0..spec.warmup {
-        f(&input)?;
-    }
+    let (warmup_iterations, warmup_samples) = run_warmup_phase(&spec, || {
+        time_iteration(&clock, || f(&input))
+    })?;
 
     // Measurement phase
-    let mut samples = Vec::with_capacity(spec.iterations as usize);
-    for _ in 0..spec.iterations {
-        let start = Instant::now();
-        f(&input)?;
-        samples.push(BenchSample::from_duration(start.elapsed()));
-    }
+    let (samples, dropped_samples) = run_measurement(&spec, || {
+        time_iteration(&clock, || f(&input))
+    })?;
 
     // Teardown phase - not timed
     teardown(input);
 
-    Ok(BenchReport { spec, samples })
+    Ok(BenchReport {
+        schema_version: BENCH_REPORT_SCHEMA_VERSION,
+        median_ci: achieved_median_ci(&spec, &samples),
+        spec,
+        samples,
+        warmup_iterations,
+        warmup_samples,
+        clock: clock.source(),
+        seed: None,
+        metrics: take_metrics(),
+        timed_out: take_timed_out(),
+        dropped_samples,
+    })
 }
 
 #[cfg(test)]
@@ -555,6 +2298,105 @@ mod tests {
         assert!(non_zero >= 1);
     }
 
+    #[test]
+    fn seeded_setup_is_reproducible() {
+        let inputs = |spec: BenchSpec| {
+            let mut seen = Vec::new();
+            let report = run_closure_with_seeded_setup_per_iter(
+                spec,
+                |rng| rng.next_u64(),
+                |value| {
+                    seen.push(value);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            (report.seed, seen)
+        };
+
+        let spec = BenchSpec::new("seeded", 5, 2).unwrap();
+        let (seed, first) = inputs(spec.clone());
+        assert_eq!(seed, Some(spec.effective_seed()));
+        // Warmup count does not shift the measured inputs
+        let (_, again) = inputs(BenchSpec::new("seeded", 5, 7).unwrap());
+        assert_eq!(first[2..], again[7..]);
+
+        let (seed, other) = inputs(spec.with_seed(1));
+        assert_eq!(seed, Some(1));
+        assert_ne!(first[2..], other[2..]);
+
+        let mut rng = SeededRng::new(0);
+        assert!((0..1000).all(|_| rng.gen_range(5..8) < 8));
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.next_f64())));
+    }
+
+    #[test]
+    fn records_cpu_time_when_enabled() {
+        let spec = BenchSpec::new("spin", 3, 0).unwrap();
+        let report = run_closure(spec, || {
+            std::hint::black_box((0..10_000u64).sum::<u64>());
+            Ok(())
+        })
+        .unwrap();
+        let supported = cfg!(all(
+            feature = "cpu-time",
+            any(target_os = "linux", target_os = "android", target_vendor = "apple")
+        ));
+        assert!(report.samples.iter().all(|s| s.cpu_ns.is_some() == supported));
+
+        // Wall-only samples serialize exactly as before
+        let wall_only = BenchSample::from(Duration::from_nanos(10));
+        assert_eq!(serde_json::to_value(&wall_only).unwrap(), serde_json::json!({"duration_ns": 10}));
+    }
+
+    #[test]
+    fn records_clock_source() {
+        let spec = BenchSpec::new("noop", 2, 0).unwrap();
+        let report = run_closure(spec, || Ok(())).unwrap();
+        let expected = if cfg!(all(
+            feature = "precise-clock",
+            any(target_os = "linux", target_os = "android")
+        )) {
+            ClockSource::MonotonicRaw
+        } else if cfg!(all(feature = "precise-clock", target_vendor = "apple")) {
+            ClockSource::MachAbsoluteTime
+        } else {
+            ClockSource::Instant
+        };
+        assert_eq!(report.clock, expected);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["clock"], expected.as_str());
+
+        // Reports from older runners have no clock field
+        let legacy: BenchReport = serde_json::from_value(serde_json::json!({
+            "spec": report.spec,
+            "samples": [{"duration_ns": 10}],
+        }))
+        .unwrap();
+        assert_eq!(legacy.clock, ClockSource::Instant);
+
+        let clock = SystemClock::new();
+        let start = clock.now_ns();
+        assert!(clock.now_ns() >= start);
+    }
+
+    #[test]
+    fn records_report_schema_version() {
+        let report = run_closure(BenchSpec::new("noop", 1, 0).unwrap(), || Ok(())).unwrap();
+        assert_eq!(report.schema_version, BENCH_REPORT_SCHEMA_VERSION);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["schema_version"], BENCH_REPORT_SCHEMA_VERSION);
+
+        // Reports serialized before versioning are the first version
+        let unversioned: BenchReport = serde_json::from_value(serde_json::json!({
+            "spec": report.spec,
+            "samples": [],
+        }))
+        .unwrap();
+        assert_eq!(unversioned.schema_version, 1);
+    }
+
     #[test]
     fn rejects_zero_iterations() {
         let result = BenchSpec::new("test", 0, 10);
@@ -570,6 +2412,68 @@ mod tests {
         assert_eq!(report.samples.len(), 5);
     }
 
+    #[test]
+    fn catches_panicking_iteration() {
+        let spec = BenchSpec::new("panics", 3, 1).unwrap();
+        let mut calls = 0;
+        let err = run_closure(spec, || {
+            calls += 1;
+            if calls == 2 {
+                panic!("iteration {calls} blew up");
+            }
+            Ok(())
+        })
+        .unwrap_err();
+
+        match err {
+            TimingError::Execution(msg) => assert_eq!(msg, "benchmark panicked: iteration 2 blew up"),
+            other => panic!("expected execution error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn times_out_slow_iteration() {
+        let spec = BenchSpec::new("slow", 3, 0)
+            .unwrap()
+            .with_per_iteration_timeout(Duration::from_millis(10));
+        let err = run_closure(spec, || {
+            std::thread::sleep(Duration::from_millis(30));
+            Ok(())
+        })
+        .unwrap_err();
+
+        match err {
+            TimingError::Timeout { elapsed, limit } => {
+                assert_eq!(limit, Duration::from_millis(10));
+                assert!(elapsed >= Duration::from_millis(30));
+            }
+            other => panic!("expected timeout, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn setup_does_not_count_toward_timeout() {
+        let spec = BenchSpec::new("slow_setup", 2, 1)
+            .unwrap()
+            .with_per_iteration_timeout(Duration::from_millis(10));
+        let report = run_closure_with_setup_per_iter(
+            spec,
+            || std::thread::sleep(Duration::from_millis(30)),
+            |()| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(report.samples.len(), 2);
+    }
+
+    #[test]
+    fn fast_iterations_pass_timeout() {
+        let spec = BenchSpec::new("fast", 5, 1)
+            .unwrap()
+            .with_per_iteration_timeout(Duration::from_secs(1));
+        let report = run_closure(spec, || Ok(())).unwrap();
+        assert_eq!(report.samples.len(), 5);
+    }
+
     #[test]
     fn serializes_to_json() {
         let spec = BenchSpec::new("test", 10, 2).unwrap();
@@ -660,4 +2564,291 @@ mod tests {
         assert_eq!(TEARDOWN_COUNT.load(Ordering::SeqCst), 1);
         assert_eq!(report.samples.len(), 3);
     }
+
+    #[test]
+    fn fixed_warmup_records_iterations() {
+        let spec = BenchSpec::new("test", 3, 4).unwrap();
+        let report = run_closure(spec, || Ok(())).unwrap();
+        assert_eq!(report.warmup_iterations, 4);
+        assert!(report.warmup_samples.is_empty());
+    }
+
+    #[test]
+    fn median_ci_sampling_stops_once_precise() {
+        let target = CiTarget::PercentOfMedian(1.0);
+        let spec = BenchSpec::new("test", 1, 0)
+            .unwrap()
+            .with_sampling_mode(SamplingMode::MedianCi {
+                min_samples: 20,
+                max_samples: 500,
+                target,
+            });
+        let mut durations = [1_000u64, 1_010, 990].into_iter().cycle();
+        let (samples, _) = run_measurement(&spec, || {
+            Ok(Duration::from_nanos(durations.next().unwrap()))
+        })
+        .unwrap();
+        assert_eq!(samples.len(), 20);
+        let ci = achieved_median_ci(&spec, &samples).unwrap();
+        assert_eq!(ci.median_ns, 1_000);
+        assert!(target.is_met(&ci));
+
+        // Two equally common values keep the median's interval wide
+        let spec = spec.with_sampling_mode(SamplingMode::MedianCi {
+            min_samples: 20,
+            max_samples: 60,
+            target: CiTarget::Nanos(1),
+        });
+        let mut toggle = false;
+        let (samples, _) = run_measurement(&spec, || {
+            toggle = !toggle;
+            Ok(Duration::from_nanos(if toggle { 1_000 } else { 2_000 }))
+        })
+        .unwrap();
+        assert_eq!(samples.len(), 60);
+    }
+
+    #[test]
+    fn captures_warmup_samples_when_enabled() {
+        let spec = BenchSpec::new("test", 3, 4).unwrap().with_warmup_capture(true);
+        let report = run_closure(spec, || Ok(())).unwrap();
+        assert_eq!(report.warmup_samples.len(), 4);
+        assert_eq!(report.samples.len(), 3);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["warmup_samples"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn until_stable_warmup_stops_once_converged() {
+        let spec = BenchSpec::new("test", 3, 0)
+            .unwrap()
+            .with_warmup_policy(WarmupPolicy::UntilStable {
+                window: 4,
+                max_cv: 0.0,
+                max_warmup: 100,
+            });
+        let mut durations = [9u64, 1, 7, 3].into_iter().chain(std::iter::repeat(5));
+        let warmed = run_warmup(&spec, || {
+            Ok(Duration::from_nanos(durations.next().unwrap()))
+        })
+        .unwrap();
+
+        // Samples 5..=8 are the first identical window.
+        assert_eq!(warmed, 8);
+    }
+
+    #[test]
+    fn fixed_time_warmup_runs_until_budget() {
+        let spec = BenchSpec::new("test", 3, 50)
+            .unwrap()
+            .with_warmup_policy(WarmupPolicy::FixedTime(Duration::from_millis(20)));
+        let started = Instant::now();
+        let warmed = run_warmup(&spec, || {
+            std::thread::sleep(Duration::from_millis(2));
+            Ok(Duration::from_millis(2))
+        })
+        .unwrap();
+
+        // The time budget wins over the `warmup` count
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert!((1..50).contains(&warmed), "{warmed}");
+
+        let spec = spec.with_warmup_policy(WarmupPolicy::FixedTime(Duration::ZERO));
+        assert_eq!(run_warmup(&spec, || Ok(Duration::ZERO)).unwrap(), 1);
+    }
+
+    #[test]
+    fn until_stable_warmup_fails_when_never_stable() {
+        let spec = BenchSpec::new("test", 3, 0)
+            .unwrap()
+            .with_warmup_policy(WarmupPolicy::UntilStable {
+                window: 3,
+                max_cv: 0.01,
+                max_warmup: 20,
+            });
+        let mut toggle = false;
+        let result = run_warmup(&spec, || {
+            toggle = !toggle;
+            Ok(Duration::from_nanos(if toggle { 10 } else { 1_000 }))
+        });
+
+        assert!(matches!(
+            result,
+            Err(TimingError::WarmupUnstable { iterations: 20, .. })
+        ));
+    }
+
+    #[test]
+    fn streams_each_sample_as_ndjson() {
+        use std::rc::Rc;
+
+        #[derive(Clone, Default)]
+        struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+        impl Write for SharedBuf {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.borrow_mut().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buf = SharedBuf::default();
+        let spec = BenchSpec::new("streamed", 3, 2).unwrap();
+        let report = with_sample_stream(buf.clone(), || run_closure(spec, || Ok(()))).unwrap();
+
+        let output = String::from_utf8(buf.0.borrow().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for (i, line) in lines.iter().enumerate() {
+            let json = line.strip_prefix("BENCH_SAMPLE_JSON ").unwrap();
+            let sample: StreamedSample = serde_json::from_str(json).unwrap();
+            assert_eq!(sample.name, "streamed");
+            assert_eq!(sample.iteration, i as u32);
+            assert_eq!(sample.duration_ns, report.samples[i].duration_ns);
+        }
+
+        // Outside the scope nothing is streamed.
+        run_closure(BenchSpec::new("quiet", 2, 0).unwrap(), || Ok(())).unwrap();
+        assert_eq!(buf.0.borrow().iter().filter(|b| **b == b'\n').count(), 3);
+    }
+
+    #[test]
+    fn deserializes_spec_without_warmup_policy() {
+        let spec: BenchSpec =
+            serde_json::from_str(r#"{"name":"legacy","iterations":5,"warmup":1}"#).unwrap();
+        assert_eq!(spec.warmup_policy, WarmupPolicy::Fixed);
+        assert_eq!(spec.sampling, SamplingMode::FixedIterations);
+    }
+
+    #[test]
+    fn fixed_time_sampling_ignores_iterations() {
+        let spec = BenchSpec::new("timed", 1, 0)
+            .unwrap()
+            .with_sampling_mode(SamplingMode::FixedTime(Duration::from_millis(20)));
+        let report = run_closure(spec, || {
+            std::thread::sleep(Duration::from_millis(1));
+            Ok(())
+        })
+        .unwrap();
+        assert!(report.samples.len() > 1);
+    }
+
+    #[test]
+    fn adaptive_sampling_stops_once_precise() {
+        let spec = BenchSpec::new("adaptive", 1, 0)
+            .unwrap()
+            .with_sampling_mode(SamplingMode::Adaptive {
+                min_samples: 5,
+                max_time: Duration::from_secs(60),
+                target_rse: 0.5,
+            });
+        // Constant durations converge as soon as min_samples is reached
+        let (samples, _) = run_measurement(&spec, || Ok(Duration::from_nanos(1_000))).unwrap();
+        assert_eq!(samples.len(), 5);
+    }
+
+    #[test]
+    fn adaptive_sampling_respects_max_time() {
+        let spec = BenchSpec::new("adaptive", 1, 0)
+            .unwrap()
+            .with_sampling_mode(SamplingMode::Adaptive {
+                min_samples: 1_000_000,
+                max_time: Duration::from_millis(10),
+                target_rse: 0.0,
+            });
+        let report = run_closure(spec, || {
+            std::thread::sleep(Duration::from_millis(1));
+            Ok(())
+        })
+        .unwrap();
+        assert!(!report.samples.is_empty());
+        assert!(report.samples.len() < 1_000);
+    }
+
+    #[test]
+    fn sampling_mode_round_trips_through_json() {
+        let spec = BenchSpec::new("json", 1, 0)
+            .unwrap()
+            .with_sampling_mode(SamplingMode::FixedTime(Duration::from_secs(3)));
+        let json = serde_json::to_string(&spec).unwrap();
+        assert!(json.contains(r#""mode":"fixed_time""#), "{json}");
+        let restored: BenchSpec = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.sampling, spec.sampling);
+    }
+
+    #[test]
+    fn records_custom_metrics_from_measured_iterations() {
+        let spec = BenchSpec::new("metrics", 4, 2).unwrap();
+        let mut calls = 0;
+        let report = run_closure(spec, || {
+            calls += 1;
+            record_metric("gas_used", calls as f64);
+            Ok(())
+        })
+        .unwrap();
+        // Warmup calls 1 and 2 are dropped
+        assert_eq!(report.metrics["gas_used"], vec![3.0, 4.0, 5.0, 6.0]);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""metrics":{"gas_used":[3.0,4.0,5.0,6.0]}"#), "{json}");
+        let plain = run_closure(BenchSpec::new("plain", 1, 0).unwrap(), || Ok(())).unwrap();
+        assert!(plain.metrics.is_empty());
+        assert!(!serde_json::to_string(&plain).unwrap().contains("metrics"));
+    }
+
+    #[test]
+    fn timeout_keeps_partial_samples() {
+        let spec = BenchSpec::new("runaway", 1_000, 2)
+            .unwrap()
+            .with_timeout(Duration::from_millis(30));
+        let report = run_closure(spec, || {
+            thread::sleep(Duration::from_millis(5));
+            Ok(())
+        })
+        .unwrap();
+        assert!(report.timed_out);
+        assert!(!report.samples.is_empty() && report.samples.len() < 1_000);
+        assert!(serde_json::to_string(&report).unwrap().contains(r#""timed_out":true"#));
+
+        // The budget does not leak into the next benchmark on this thread
+        let next = run_closure(BenchSpec::new("next", 3, 0).unwrap(), || Ok(())).unwrap();
+        assert!(!next.timed_out);
+        assert_eq!(next.samples.len(), 3);
+        assert!(!serde_json::to_string(&next).unwrap().contains("timed_out"));
+    }
+
+    #[test]
+    fn drop_first_n_discards_ramp_up_samples() {
+        let mut call = 0u64;
+        let spec = BenchSpec::new("ramp", 8, 0).unwrap().with_drop_first_n(3);
+        let report = run_closure(spec, || {
+            call += 1;
+            record_metric("call", call as f64);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(report.samples.len(), 5);
+        assert_eq!(report.dropped_samples, 3);
+        // Metrics from dropped iterations go with them
+        assert_eq!(report.metrics["call"], vec![4.0, 5.0, 6.0, 7.0, 8.0]);
+        assert!(serde_json::to_string(&report).unwrap().contains(r#""dropped_samples":3"#));
+
+        // Dropping every fixed iteration leaves nothing to report
+        let spec = BenchSpec::new("ramp", 3, 0).unwrap().with_drop_first_n(3);
+        assert!(matches!(
+            run_closure(spec, || Ok(())),
+            Err(TimingError::NoIterations { count: 0 })
+        ));
+
+        // Time-based sampling drops before it starts counting
+        let spec = BenchSpec::new("ramp", 1, 0)
+            .unwrap()
+            .with_sampling_mode(SamplingMode::FixedTime(Duration::ZERO))
+            .with_drop_first_n(2);
+        let report = run_closure(spec, || Ok(())).unwrap();
+        assert_eq!((report.samples.len(), report.dropped_samples), (1, 2));
+    }
 }
@@ -37,6 +37,13 @@ use std::path::PathBuf;
 ///     name: "nonexistent".to_string(),
 ///     iterations: 10,
 ///     warmup: 1,
+///     warmup_policy: Default::default(),
+///     sampling: Default::default(),
+///     per_iteration_timeout: None,
+///     timeout: None,
+///     capture_warmup: false,
+///     seed: None,
+///     drop_first_n: 0,
 /// };
 ///
 /// match run_benchmark(spec) {
@@ -161,6 +168,7 @@ impl Target {
 ///     project_name: "my-benchmarks".to_string(),
 ///     output_dir: PathBuf::from("./bench-mobile"),
 ///     generate_examples: true,
+///     with_criterion: false,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -173,6 +181,9 @@ pub struct InitConfig {
     pub output_dir: PathBuf,
     /// Whether to generate example benchmark functions.
     pub generate_examples: bool,
+    /// Whether to generate a criterion harness (`benches/mobench_criterion.rs`)
+    /// that runs the same `#[benchmark]` functions under `cargo bench`.
+    pub with_criterion: bool,
 }
 
 /// Configuration for building mobile apps.
@@ -190,6 +201,8 @@ pub struct InitConfig {
 ///     target: Target::Android,
 ///     profile: BuildProfile::Release,
 ///     incremental: true,
+///     cache: true,  // Reuse native libraries when sources are unchanged
+///     cargo_profile: None,
 /// };
 ///
 /// // Debug build for iOS
@@ -197,7 +210,20 @@ pub struct InitConfig {
 ///     target: Target::Ios,
 ///     profile: BuildProfile::Debug,
 ///     incremental: false,  // Force rebuild
+///     cache: false,
+///     cargo_profile: None,
 /// };
+///
+/// // Custom `[profile.bench-mobile]` from Cargo.toml, packaged as a release app
+/// let custom = BuildConfig {
+///     target: Target::Android,
+///     profile: BuildProfile::Release,
+///     incremental: true,
+///     cache: true,
+///     cargo_profile: Some("bench-mobile".to_string()),
+/// };
+/// assert_eq!(custom.cargo_profile_args(), vec!["--profile", "bench-mobile"]);
+/// assert_eq!(custom.target_profile_dir(), "bench-mobile");
 /// ```
 #[derive(Debug, Clone)]
 pub struct BuildConfig {
@@ -207,6 +233,53 @@ pub struct BuildConfig {
     pub profile: BuildProfile,
     /// If `true`, skip rebuilding if artifacts already exist.
     pub incremental: bool,
+    /// If `true`, skip the Rust compile step when the content fingerprint
+    /// (sources, `Cargo.lock`, target triple and profile) matches the one
+    /// recorded under `target/mobench/.cache` by the previous build. On iOS
+    /// the xcframework assembly and signing are skipped as well.
+    pub cache: bool,
+    /// Named cargo profile (e.g. `bench-mobile` for `[profile.bench-mobile]`).
+    ///
+    /// When set, cargo is invoked with `--profile <name>` and libraries are
+    /// read from `target/<triple>/<name>/`. `profile` still selects the app
+    /// variant (Gradle/Xcode configuration). When `None`, the cargo profile
+    /// follows `profile`.
+    pub cargo_profile: Option<String>,
+}
+
+impl BuildConfig {
+    /// Returns the cargo profile name used for the Rust compile step.
+    ///
+    /// [`BuildProfile::Debug`] and [`BuildProfile::Release`] map to cargo's
+    /// built-in `dev` and `release` profiles.
+    pub fn cargo_profile_name(&self) -> &str {
+        self.cargo_profile
+            .as_deref()
+            .unwrap_or_else(|| self.profile.cargo_profile_name())
+    }
+
+    /// Returns the arguments selecting the cargo profile (e.g. `["--release"]`).
+    pub fn cargo_profile_args(&self) -> Vec<&str> {
+        match self.cargo_profile.as_deref() {
+            Some(name) => vec!["--profile", name],
+            None => match self.profile {
+                BuildProfile::Debug => Vec::new(),
+                BuildProfile::Release => vec!["--release"],
+            },
+        }
+    }
+
+    /// Returns the directory name cargo writes artifacts to under `target/<triple>/`.
+    ///
+    /// Cargo stores the `dev` (and `test`) profile in `debug/`; every other
+    /// profile uses a directory named after the profile.
+    pub fn target_profile_dir(&self) -> &str {
+        match self.cargo_profile_name() {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            name => name,
+        }
+    }
 }
 
 /// Build profile controlling optimization and debug info.
@@ -252,6 +325,14 @@ impl BuildProfile {
             BuildProfile::Release => "release",
         }
     }
+
+    /// Returns the cargo profile this build profile is sugar for (`dev` or `release`).
+    pub fn cargo_profile_name(&self) -> &'static str {
+        match self {
+            BuildProfile::Debug => "dev",
+            BuildProfile::Release => "release",
+        }
+    }
 }
 
 /// Result of a successful build operation.
@@ -285,4 +366,77 @@ pub struct BuildResult {
     /// - Android: Path to the androidTest APK (for Espresso)
     /// - iOS: Path to the XCUITest runner zip
     pub test_suite_path: Option<PathBuf>,
+    /// Path to the app's debug symbols, if any were found.
+    ///
+    /// - Android: Always `None`
+    /// - iOS: Path to the `.app.dSYM` bundle generated by `package_ipa`
+    pub dsym_path: Option<PathBuf>,
+    /// Sizes of the native libraries that were built, one per ABI or slice.
+    ///
+    /// - Android: `lib<crate>.so` for each ABI in `jniLibs/`
+    /// - iOS: `lib<crate>.a` for each target triple
+    ///
+    /// Empty for dry runs.
+    pub native_libs: Vec<NativeLibSize>,
+}
+
+/// Size of one native library produced by a build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeLibSize {
+    /// ABI (`arm64-v8a`) or target triple (`aarch64-apple-ios`) of the library.
+    pub slice: String,
+    /// Path to the library as built.
+    pub path: PathBuf,
+    /// Size on disk in bytes, including debug symbols.
+    pub unstripped_bytes: u64,
+    /// Size after stripping debug symbols, when a strip tool was available.
+    pub stripped_bytes: Option<u64>,
+}
+
+impl NativeLibSize {
+    /// The size that ships in the app: stripped when known, else unstripped.
+    ///
+    /// Gradle strips `.so` files when packaging an APK and Xcode strips the
+    /// linked binary, so the stripped size is what users download.
+    pub fn shipped_bytes(&self) -> u64 {
+        self.stripped_bytes.unwrap_or(self.unstripped_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(profile: BuildProfile, cargo_profile: Option<&str>) -> BuildConfig {
+        BuildConfig {
+            target: Target::Android,
+            profile,
+            incremental: true,
+            cache: false,
+            cargo_profile: cargo_profile.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn builtin_profiles_resolve_to_standard_target_dirs() {
+        let debug = config(BuildProfile::Debug, None);
+        assert_eq!(debug.cargo_profile_name(), "dev");
+        assert!(debug.cargo_profile_args().is_empty());
+        assert_eq!(debug.target_profile_dir(), "debug");
+
+        let release = config(BuildProfile::Release, None);
+        assert_eq!(release.cargo_profile_args(), vec!["--release"]);
+        assert_eq!(release.target_profile_dir(), "release");
+    }
+
+    #[test]
+    fn named_profile_resolves_to_its_own_target_dir() {
+        let custom = config(BuildProfile::Release, Some("bench-mobile"));
+        assert_eq!(custom.cargo_profile_name(), "bench-mobile");
+        assert_eq!(custom.cargo_profile_args(), vec!["--profile", "bench-mobile"]);
+        assert_eq!(custom.target_profile_dir(), "bench-mobile");
+
+        let dev = config(BuildProfile::Release, Some("dev"));
+        assert_eq!(dev.target_profile_dir(), "debug");
+    }
 }
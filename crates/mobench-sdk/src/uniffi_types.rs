@@ -77,6 +77,13 @@
 //!             name: self.name.clone(),
 //!             iterations: self.iterations,
 //!             warmup: self.warmup,
+//!             warmup_policy: Default::default(),
+//!             sampling: Default::default(),
+//!             per_iteration_timeout: None,
+//!             timeout: None,
+//!             capture_warmup: false,
+//!             seed: None,
+//!             drop_first_n: 0,
 //!         }
 //!     }
 //! }
@@ -151,6 +158,12 @@ pub struct BenchSpecTemplate {
     pub iterations: u32,
     /// Number of warmup iterations before measurement.
     pub warmup: u32,
+    /// Longest the timed part of one iteration may take, in milliseconds.
+    #[serde(default)]
+    pub per_iteration_timeout_ms: Option<u64>,
+    /// Record warmup durations in the report's `warmup_samples`.
+    #[serde(default)]
+    pub capture_warmup: bool,
 }
 
 impl From<crate::BenchSpec> for BenchSpecTemplate {
@@ -159,6 +172,10 @@ impl From<crate::BenchSpec> for BenchSpecTemplate {
             name: spec.name,
             iterations: spec.iterations,
             warmup: spec.warmup,
+            per_iteration_timeout_ms: spec
+                .per_iteration_timeout
+                .map(|timeout| timeout.as_millis() as u64),
+            capture_warmup: spec.capture_warmup,
         }
     }
 }
@@ -169,6 +186,15 @@ impl From<BenchSpecTemplate> for crate::BenchSpec {
             name: spec.name,
             iterations: spec.iterations,
             warmup: spec.warmup,
+            warmup_policy: Default::default(),
+            sampling: Default::default(),
+            per_iteration_timeout: spec
+                .per_iteration_timeout_ms
+                .map(std::time::Duration::from_millis),
+            timeout: None,
+            capture_warmup: spec.capture_warmup,
+            seed: None,
+            drop_first_n: 0,
         }
     }
 }
@@ -181,12 +207,16 @@ impl From<BenchSpecTemplate> for crate::BenchSpec {
 pub struct BenchSampleTemplate {
     /// Duration of the iteration in nanoseconds.
     pub duration_ns: u64,
+    /// Thread CPU time of the iteration in nanoseconds, when measured.
+    #[serde(default)]
+    pub cpu_ns: Option<u64>,
 }
 
 impl From<crate::BenchSample> for BenchSampleTemplate {
     fn from(sample: crate::BenchSample) -> Self {
         Self {
             duration_ns: sample.duration_ns,
+            cpu_ns: sample.cpu_ns,
         }
     }
 }
@@ -195,6 +225,7 @@ impl From<BenchSampleTemplate> for crate::BenchSample {
     fn from(sample: BenchSampleTemplate) -> Self {
         Self {
             duration_ns: sample.duration_ns,
+            cpu_ns: sample.cpu_ns,
         }
     }
 }
@@ -209,6 +240,9 @@ pub struct BenchReportTemplate {
     pub spec: BenchSpecTemplate,
     /// All collected timing samples.
     pub samples: Vec<BenchSampleTemplate>,
+    /// Warmup durations, when the spec set `capture_warmup`.
+    #[serde(default)]
+    pub warmup_samples: Vec<BenchSampleTemplate>,
 }
 
 impl From<crate::RunnerReport> for BenchReportTemplate {
@@ -216,6 +250,7 @@ impl From<crate::RunnerReport> for BenchReportTemplate {
         Self {
             spec: report.spec.into(),
             samples: report.samples.into_iter().map(Into::into).collect(),
+            warmup_samples: report.warmup_samples.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -248,6 +283,12 @@ impl From<crate::types::BenchError> for BenchErrorVariant {
                 crate::timing::TimingError::Execution(msg) => {
                     BenchErrorVariant::ExecutionFailed { reason: msg }
                 }
+                err @ (crate::timing::TimingError::WarmupUnstable { .. }
+                | crate::timing::TimingError::Timeout { .. }) => {
+                    BenchErrorVariant::ExecutionFailed {
+                        reason: err.to_string(),
+                    }
+                }
             },
             crate::types::BenchError::UnknownFunction(name, _available) => {
                 BenchErrorVariant::UnknownFunction { name }
@@ -276,6 +317,12 @@ impl From<crate::timing::TimingError> for BenchErrorVariant {
             crate::timing::TimingError::Execution(msg) => {
                 BenchErrorVariant::ExecutionFailed { reason: msg }
             }
+            err @ (crate::timing::TimingError::WarmupUnstable { .. }
+            | crate::timing::TimingError::Timeout { .. }) => {
+                BenchErrorVariant::ExecutionFailed {
+                    reason: err.to_string(),
+                }
+            }
         }
     }
 }
@@ -314,6 +361,13 @@ mod tests {
             name: "test".to_string(),
             iterations: 100,
             warmup: 10,
+            warmup_policy: Default::default(),
+            sampling: Default::default(),
+            per_iteration_timeout: Some(std::time::Duration::from_millis(250)),
+            timeout: None,
+            capture_warmup: true,
+            seed: None,
+            drop_first_n: 0,
         };
 
         let template: BenchSpecTemplate = sdk_spec.clone().into();
@@ -325,13 +379,19 @@ mod tests {
         assert_eq!(back.name, sdk_spec.name);
         assert_eq!(back.iterations, sdk_spec.iterations);
         assert_eq!(back.warmup, sdk_spec.warmup);
+        assert_eq!(back.per_iteration_timeout, sdk_spec.per_iteration_timeout);
+        assert!(back.capture_warmup);
     }
 
     #[test]
     fn test_bench_sample_template_conversion() {
-        let sdk_sample = crate::BenchSample { duration_ns: 12345 };
+        let sdk_sample = crate::BenchSample {
+            duration_ns: 12345,
+            cpu_ns: Some(12000),
+        };
         let template: BenchSampleTemplate = sdk_sample.into();
         assert_eq!(template.duration_ns, 12345);
+        assert_eq!(template.cpu_ns, Some(12000));
     }
 
     #[test]
@@ -0,0 +1,141 @@
+//! On-disk store of baseline run summaries.
+//!
+//! The store lets `mobench run` manage baselines without manual artifact
+//! shuffling in CI: each run is compared against the stored baseline for its
+//! target and function, and a run on the promotion branch that passes every
+//! gate replaces the entry of each function all its devices completed.
+//!
+//! ## Layout
+//!
+//! ```text
+//! <store>/
+//! ├── android/
+//! │   └── sample_fns__fibonacci.json
+//! └── ios/
+//!     └── sample_fns__fibonacci.json
+//! ```
+//!
+//! Each file is a run summary as written by `mobench run --output`.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A directory of baseline summaries keyed by target and function.
+#[derive(Debug, Clone)]
+pub(crate) struct BaselineStore {
+    dir: PathBuf,
+}
+
+impl BaselineStore {
+    pub(crate) fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Path of the baseline summary for `target` and `function`.
+    pub(crate) fn path_for(&self, target: &str, function: &str) -> PathBuf {
+        self.dir
+            .join(sanitize_key(target))
+            .join(format!("{}.json", sanitize_key(function)))
+    }
+
+    /// Returns the stored baseline path if one exists.
+    pub(crate) fn find(&self, target: &str, function: &str) -> Option<PathBuf> {
+        let path = self.path_for(target, function);
+        path.is_file().then_some(path)
+    }
+
+    /// Replace the baseline for `target` and `function` with `summary`.
+    ///
+    /// The new baseline is written to a temporary file in the same directory
+    /// and renamed into place, so readers never observe a partial file.
+    pub(crate) fn promote(&self, target: &str, function: &str, summary: &Path) -> Result<PathBuf> {
+        let dest = self.path_for(target, function);
+        let parent = dest
+            .parent()
+            .context("baseline path has no parent directory")?;
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating baseline store dir {:?}", parent))?;
+
+        let contents =
+            fs::read(summary).with_context(|| format!("reading run summary {:?}", summary))?;
+        let tmp = parent.join(format!(
+            ".{}.tmp-{}",
+            dest.file_name().and_then(|n| n.to_str()).unwrap_or("baseline"),
+            std::process::id()
+        ));
+        fs::write(&tmp, contents).with_context(|| format!("writing {:?}", tmp))?;
+        if let Err(err) = fs::rename(&tmp, &dest) {
+            let _ = fs::remove_file(&tmp);
+            return Err(err).with_context(|| format!("moving baseline into place at {:?}", dest));
+        }
+        Ok(dest)
+    }
+}
+
+/// Turn a target or function name into a safe file name component.
+pub(crate) fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Determine the current git branch.
+///
+/// CI checkouts are often in detached-HEAD state, so this falls back to
+/// `GITHUB_REF_NAME` when git reports `HEAD`.
+pub(crate) fn current_git_branch(repo: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(repo)
+        .output()
+        .ok()?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !branch.is_empty() && branch != "HEAD" {
+        return Some(branch);
+    }
+    std::env::var("GITHUB_REF_NAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_baselines_by_target_and_function() {
+        let store = BaselineStore::new("/store");
+        assert_eq!(
+            store.path_for("android", "sample_fns::fibonacci"),
+            PathBuf::from("/store/android/sample_fns__fibonacci.json")
+        );
+    }
+
+    #[test]
+    fn promote_replaces_baseline() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = BaselineStore::new(temp.path().join("store"));
+        assert!(store.find("ios", "my::bench").is_none());
+
+        let summary = temp.path().join("run-summary.json");
+        fs::write(&summary, r#"{"run":1}"#).unwrap();
+        let dest = store.promote("ios", "my::bench", &summary).unwrap();
+        assert_eq!(store.find("ios", "my::bench"), Some(dest.clone()));
+
+        fs::write(&summary, r#"{"run":2}"#).unwrap();
+        store.promote("ios", "my::bench", &summary).unwrap();
+        assert_eq!(fs::read_to_string(&dest).unwrap(), r#"{"run":2}"#);
+
+        // No temp files left behind
+        let entries = fs::read_dir(dest.parent().unwrap()).unwrap().count();
+        assert_eq!(entries, 1);
+    }
+}
@@ -1,6 +1,6 @@
 fn main() {
     if let Err(err) = mobench::run() {
         eprintln!("{err:#}");
-        std::process::exit(1);
+        std::process::exit(mobench::exit_code(&err));
     }
 }
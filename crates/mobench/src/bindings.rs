@@ -0,0 +1,343 @@
+//! Drift check between the FFI crate and its generated UniFFI bindings, for
+//! `verify --check-bindings`.
+//!
+//! `build` reuses Kotlin and Swift bindings that already exist, so after the
+//! FFI crate's `BenchSpec`/`BenchReport` or `run_benchmark` change, stale
+//! bindings still compile but fail at runtime on the device (usually as a
+//! UniFFI checksum mismatch). This compares the shape both sides declare:
+//!
+//! - the fields of the `BenchSpec`, `BenchSample` and `BenchReport` records
+//! - `run_benchmark`'s parameters, return type, and whether it can fail
+//!
+//! Only the generated source is read; nothing is rebuilt. Field names are
+//! compared in snake_case, so Kotlin and Swift camelCase matches Rust.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Records the mobile runners exchange with `run_benchmark`.
+const RECORDS: [&str; 3] = ["BenchSpec", "BenchSample", "BenchReport"];
+
+/// `run_benchmark` as declared on one side of the FFI boundary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FnSignature {
+    params: String,
+    returns: String,
+    fallible: bool,
+}
+
+impl fmt::Display for FnSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "run_benchmark({}) -> {}", self.params, self.returns)?;
+        if self.fallible {
+            f.write_str(" (throws)")?;
+        }
+        Ok(())
+    }
+}
+
+/// What one side of the FFI boundary declares.
+#[derive(Debug, Default)]
+pub(crate) struct FfiShape {
+    /// Field names per record in [`RECORDS`] order; `None` if the record is missing.
+    records: Vec<Option<Vec<String>>>,
+    run_benchmark: Option<FnSignature>,
+}
+
+impl FfiShape {
+    /// Whether neither the records nor `run_benchmark` were found.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.run_benchmark.is_none() && self.records.iter().all(Option::is_none)
+    }
+}
+
+/// Read the shape from the FFI crate's `src/lib.rs`.
+pub(crate) fn rust_shape(source: &str) -> FfiShape {
+    let records = RECORDS
+        .iter()
+        .map(|name| {
+            let body = block_after(source, &format!("pub struct {name} {{"), "}")?;
+            Some(
+                body.lines()
+                    .filter_map(|line| line.trim().strip_prefix("pub "))
+                    .filter_map(|field| field.split_once(':'))
+                    .map(|(field, _)| field.trim().to_string())
+                    .collect(),
+            )
+        })
+        .collect();
+    let run_benchmark = signature_line(source, "fn run_benchmark(").and_then(|line| {
+        let (params, rest) = split_params(line)?;
+        let returns = rest.split_once("->")?.1.trim_end_matches('{').trim();
+        let (returns, fallible) = match returns.strip_prefix("Result<") {
+            Some(result) => (result.split(',').next()?.trim(), true),
+            None => (returns, false),
+        };
+        Some(FnSignature {
+            params: params.to_string(),
+            returns: returns.to_string(),
+            fallible,
+        })
+    });
+    FfiShape {
+        records,
+        run_benchmark,
+    }
+}
+
+/// Read the shape from UniFFI's generated Kotlin.
+pub(crate) fn kotlin_shape(source: &str) -> FfiShape {
+    let records = RECORDS
+        .iter()
+        .map(|name| {
+            let body = block_after(source, &format!("data class {name} ("), ")")?;
+            Some(
+                body.lines()
+                    .filter_map(|line| {
+                        let line = line.trim();
+                        line.strip_prefix("var ").or_else(|| line.strip_prefix("val "))
+                    })
+                    .filter_map(|field| field.split_once(':'))
+                    .map(|(field, _)| camel_to_snake(field.trim().trim_matches('`')))
+                    .collect(),
+            )
+        })
+        .collect();
+    let run_benchmark = signature_line(source, "fun `runBenchmark`(").and_then(|line| {
+        let (params, rest) = split_params(line)?;
+        let returns = rest.trim_start_matches(':').trim_end_matches('{').trim();
+        Some(FnSignature {
+            params: params.replace('`', ""),
+            returns: returns.to_string(),
+            fallible: line.contains("@Throws("),
+        })
+    });
+    FfiShape {
+        records,
+        run_benchmark,
+    }
+}
+
+/// Read the shape from UniFFI's generated Swift.
+pub(crate) fn swift_shape(source: &str) -> FfiShape {
+    let records = RECORDS
+        .iter()
+        .map(|name| {
+            let body = block_after(source, &format!("public struct {name} {{"), "}")?;
+            Some(
+                body.lines()
+                    .take_while(|line| !line.contains("init("))
+                    .filter_map(|line| {
+                        let line = line.trim();
+                        line.strip_prefix("public var ").or_else(|| line.strip_prefix("public let "))
+                    })
+                    .filter_map(|field| field.split_once(':'))
+                    .map(|(field, _)| camel_to_snake(field.trim().trim_matches('`')))
+                    .collect(),
+            )
+        })
+        .collect();
+    let run_benchmark = signature_line(source, "func runBenchmark(").and_then(|line| {
+        let (params, rest) = split_params(line)?;
+        let returns = rest.split_once("->")?.1.trim_end_matches('{').trim();
+        Some(FnSignature {
+            params: params.to_string(),
+            returns: returns.to_string(),
+            fallible: rest.contains("throws"),
+        })
+    });
+    FfiShape {
+        records,
+        run_benchmark,
+    }
+}
+
+/// Differences of `bindings` from `expected`, one line each: `- x` for what
+/// the bindings lack, `+ x` for what they have extra. Empty when they match.
+pub(crate) fn diff(expected: &FfiShape, bindings: &FfiShape) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (index, name) in RECORDS.iter().enumerate() {
+        let want = expected.records.get(index).cloned().flatten();
+        let have = bindings.records.get(index).cloned().flatten();
+        match (want, have) {
+            (Some(_), None) => lines.push(format!("- record {name} (missing from bindings)")),
+            (None, Some(_)) => lines.push(format!("+ record {name} (not in the FFI crate)")),
+            (Some(want), Some(have)) => {
+                let want_set: BTreeSet<&String> = want.iter().collect();
+                let have_set: BTreeSet<&String> = have.iter().collect();
+                for field in want.iter().filter(|field| !have_set.contains(field)) {
+                    lines.push(format!("- {name}.{field}"));
+                }
+                for field in have.iter().filter(|field| !want_set.contains(field)) {
+                    lines.push(format!("+ {name}.{field}"));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    match (&expected.run_benchmark, &bindings.run_benchmark) {
+        (Some(want), Some(have)) if want != have => {
+            lines.push(format!("- {want}"));
+            lines.push(format!("+ {have}"));
+        }
+        (Some(want), None) => lines.push(format!("- {want} (missing from bindings)")),
+        (None, Some(have)) => lines.push(format!("+ {have} (not in the FFI crate)")),
+        _ => {}
+    }
+    lines
+}
+
+/// The lines after the one containing `opener`, up to the next line that
+/// starts (after indentation) with `closer`.
+fn block_after(source: &str, opener: &str, closer: &str) -> Option<String> {
+    let start = source.find(opener)?;
+    let lines: Vec<&str> = source[start..]
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with(closer))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// The declaration starting at `needle`, up to its opening brace, on one line.
+fn signature_line<'a>(source: &'a str, needle: &str) -> Option<&'a str> {
+    let start = source.find(needle)?;
+    // Include attributes such as Kotlin's @Throws on the same line
+    let line_start = source[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let end = source[start..].find('{').map_or(source.len(), |brace| start + brace + 1);
+    Some(&source[line_start..end])
+}
+
+/// Split `name(params) rest` into the trimmed parameter list and the rest.
+fn split_params(line: &str) -> Option<(&str, &str)> {
+    let open = line.find('(')?;
+    let close = open + line[open..].find(')')?;
+    // Kotlin's @Throws(...) comes before the function's own parentheses
+    if line[..open].contains("@Throws") {
+        return split_params(&line[close + 1..]);
+    }
+    Some((line[open + 1..close].trim(), line[close + 1..].trim()))
+}
+
+fn camel_to_snake(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for ch in name.chars() {
+        if ch.is_ascii_uppercase() {
+            if !snake.is_empty() {
+                snake.push('_');
+            }
+            snake.push(ch.to_ascii_lowercase());
+        } else {
+            snake.push(ch);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST: &str = r#"
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct BenchSpec {
+    pub name: String,
+    /// Measured samples to discard before the report; `None` keeps them all.
+    #[uniffi(default = None)]
+    pub drop_first_n: Option<u32>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct BenchSample {
+    pub duration_ns: u64,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct BenchReport {
+    pub spec: BenchSpec,
+    pub samples: Vec<BenchSample>,
+}
+
+#[uniffi::export]
+pub fn run_benchmark(spec: BenchSpec) -> Result<BenchReport, BenchError> {
+    todo!()
+}
+"#;
+
+    const KOTLIN: &str = r#"
+data class BenchReport (
+    var `spec`: BenchSpec,
+    var `samples`: List<BenchSample>
+) {
+    companion object
+}
+
+data class BenchSample (
+    var `durationNs`: kotlin.ULong
+) {
+    companion object
+}
+
+data class BenchSpec (
+    var `name`: kotlin.String,
+    var `dropFirstN`: kotlin.UInt? = null
+) {
+    companion object
+}
+
+    @Throws(BenchException::class) fun `runBenchmark`(`spec`: BenchSpec): BenchReport {
+"#;
+
+    const SWIFT: &str = r#"
+public struct BenchReport {
+    public var spec: BenchSpec
+    public var samples: [BenchSample]
+
+    public init(spec: BenchSpec, samples: [BenchSample]) {
+        self.spec = spec
+    }
+}
+
+public struct BenchSample {
+    public var durationNs: UInt64
+
+    public init(durationNs: UInt64) {
+    }
+}
+
+public struct BenchSpec {
+    public var name: String
+
+    public init(name: String) {
+    }
+}
+
+public func runBenchmark(spec: BenchSpec)throws  -> BenchReport {
+"#;
+
+    #[test]
+    fn reports_binding_drift_as_a_diff() {
+        let rust = rust_shape(RUST);
+        assert_eq!(
+            rust.run_benchmark.as_ref().unwrap().to_string(),
+            "run_benchmark(spec: BenchSpec) -> BenchReport (throws)"
+        );
+        assert!(diff(&rust, &kotlin_shape(KOTLIN)).is_empty());
+
+        // The Swift bindings predate drop_first_n
+        assert_eq!(diff(&rust, &swift_shape(SWIFT)), vec!["- BenchSpec.drop_first_n"]);
+
+        let infallible = KOTLIN.replace("@Throws(BenchException::class) ", "");
+        assert_eq!(
+            diff(&rust, &kotlin_shape(&infallible)),
+            vec![
+                "- run_benchmark(spec: BenchSpec) -> BenchReport (throws)",
+                "+ run_benchmark(spec: BenchSpec) -> BenchReport",
+            ]
+        );
+        let unrelated = kotlin_shape("class Other");
+        assert!(unrelated.is_empty());
+        assert_eq!(diff(&rust, &unrelated).len(), 4);
+        assert_eq!(camel_to_snake("perBenchmarkTimeoutSecs"), "per_benchmark_timeout_secs");
+    }
+}
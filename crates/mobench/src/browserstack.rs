@@ -1,14 +1,119 @@
+//! BrowserStack App Automate client.
+//!
+//! [`BrowserStackClient`] wraps the REST API: uploads, Espresso and XCUITest
+//! builds, build status, device lists and session logs. [`BrowserStackBackend`]
+//! implements [`DeviceBackend`] on top of it, so SDK users can drive a
+//! BrowserStack run with [`mobench_sdk::orchestrate`] the same way `run` does.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! use mobench::browserstack::{BrowserStackAuth, BrowserStackBackend, BrowserStackClient};
+//! use mobench_sdk::orchestrate::{DeviceRunSpec, RunArtifacts, RunOptions, run_on_devices};
+//!
+//! let auth = BrowserStackAuth {
+//!     username: std::env::var("BROWSERSTACK_USERNAME")?,
+//!     access_key: std::env::var("BROWSERSTACK_ACCESS_KEY")?,
+//! };
+//! let client = BrowserStackClient::new(auth, Some("my-project".into()))?;
+//! let backend = BrowserStackBackend::new(&client, "espresso");
+//! let spec = DeviceRunSpec {
+//!     devices: vec!["Google Pixel 7-13.0".into()],
+//!     artifacts: RunArtifacts {
+//!         app: "app-debug.apk".into(),
+//!         test_suite: "app-debug-androidTest.apk".into(),
+//!     },
+//! };
+//! let summary = run_on_devices(&spec, &backend, &RunOptions::default())?;
+//! ```
+
+use crate::error::MobenchError;
 use anyhow::{Context, Result, anyhow};
 use reqwest::blocking::multipart::Form;
+use reqwest::StatusCode;
 use reqwest::blocking::{Client, Response};
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
-type BrowserStackResults = (
+use mobench_sdk::orchestrate::{self, DeviceBackend, RunArtifacts, RunEvent, RunOptions, UploadedArtifacts};
+pub(crate) type BrowserStackResults = (
     std::collections::HashMap<String, Vec<Value>>,
     std::collections::HashMap<String, PerformanceMetrics>,
+    std::collections::HashMap<String, DeviceMeta>,
 );
-use std::path::Path;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Set while a poll loop is waiting, so SIGINT cancels the wait instead of
+/// killing the process.
+static WAITING: AtomicBool = AtomicBool::new(false);
+/// Set by the SIGINT handler when a wait should stop.
+static WAIT_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Returned when SIGINT interrupts a wait. The build keeps running on
+/// BrowserStack and can be fetched later with `mobench fetch`.
+#[derive(Debug)]
+pub struct WaitCancelled {
+    pub build_id: String,
+}
+
+impl std::fmt::Display for WaitCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "wait for build {} cancelled; the build keeps running on BrowserStack",
+            self.build_id
+        )
+    }
+}
+
+impl std::error::Error for WaitCancelled {}
+
+/// Marks a cancellable wait; SIGINT outside one exits as usual.
+struct InterruptibleWait;
+
+impl InterruptibleWait {
+    fn start() -> Self {
+        static HANDLER: Once = Once::new();
+        HANDLER.call_once(|| {
+            // Best effort: without a handler Ctrl-C simply kills the process
+            let _ = ctrlc::set_handler(|| {
+                if WAITING.load(Ordering::SeqCst) {
+                    WAIT_CANCELLED.store(true, Ordering::SeqCst);
+                } else {
+                    std::process::exit(130);
+                }
+            });
+        });
+        WAIT_CANCELLED.store(false, Ordering::SeqCst);
+        WAITING.store(true, Ordering::SeqCst);
+        Self
+    }
+
+    fn cancelled(&self) -> bool {
+        WAIT_CANCELLED.load(Ordering::SeqCst)
+    }
+
+    /// Sleep for `duration`, returning early (and `true`) on cancellation.
+    fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while !self.cancelled() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            std::thread::sleep(remaining.min(Duration::from_millis(200)));
+        }
+        true
+    }
+}
+
+impl Drop for InterruptibleWait {
+    fn drop(&mut self) {
+        WAITING.store(false, Ordering::SeqCst);
+    }
+}
 
 /// Format a file size in human-readable format (MB or KB).
 fn format_file_size(bytes: u64) -> String {
@@ -48,6 +153,64 @@ impl BrowserStackDevice {
     }
 }
 
+/// How long a cached device list is used before it is fetched again.
+pub const DEFAULT_DEVICE_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// On-disk copy of one platform's device list, in
+/// `<dir>/devices-<platform>.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedDeviceList {
+    /// Unix time (seconds) the list was fetched.
+    fetched_at: u64,
+    /// TTL the list was cached with, in seconds.
+    ttl_secs: u64,
+    devices: Vec<BrowserStackDevice>,
+}
+
+/// Where and for how long [`BrowserStackClient`] caches device lists.
+#[derive(Debug, Clone)]
+struct DeviceCache {
+    dir: PathBuf,
+    ttl: Duration,
+    /// Ignore any cached list, but still write the fresh one.
+    refresh: bool,
+}
+
+impl DeviceCache {
+    fn path(&self, platform: &str) -> PathBuf {
+        self.dir.join(format!("devices-{platform}.json"))
+    }
+
+    /// The cached list, if it was fetched less than the TTL ago.
+    fn read(&self, platform: &str, now: u64) -> Option<Vec<BrowserStackDevice>> {
+        let contents = std::fs::read(self.path(platform)).ok()?;
+        let cached: CachedDeviceList = serde_json::from_slice(&contents).ok()?;
+        let ttl = cached.ttl_secs.min(self.ttl.as_secs());
+        let age = now.checked_sub(cached.fetched_at)?;
+        (age < ttl).then_some(cached.devices)
+    }
+
+    fn write(&self, platform: &str, now: u64, devices: &[BrowserStackDevice]) -> Result<()> {
+        let cached = CachedDeviceList {
+            fetched_at: now,
+            ttl_secs: self.ttl.as_secs(),
+            devices: devices.to_vec(),
+        };
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("creating device cache directory {:?}", self.dir))?;
+        let path = self.path(platform);
+        std::fs::write(&path, serde_json::to_vec_pretty(&cached)?)
+            .with_context(|| format!("writing device cache {:?}", path))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
 /// Result of device validation.
 #[derive(Debug)]
 pub struct DeviceValidationResult {
@@ -55,6 +218,8 @@ pub struct DeviceValidationResult {
     pub valid: Vec<String>,
     /// Invalid device specs with suggestions.
     pub invalid: Vec<DeviceValidationError>,
+    /// Valid specs whose device BrowserStack currently reports as unavailable.
+    pub unavailable: Vec<String>,
 }
 
 /// Error details for an invalid device specification.
@@ -70,6 +235,12 @@ pub struct DeviceValidationError {
 
 const DEFAULT_BASE_URL: &str = "https://api-cloud.browserstack.com";
 const USER_AGENT: &str = "mobile-bench-rs/0.1";
+/// Attempts per GET request before a transient failure is returned.
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry; doubled after each further attempt.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Device sessions whose logs are fetched at the same time.
+pub(crate) const DEFAULT_FETCH_CONCURRENCY: usize = 4;
 
 #[derive(Debug, Clone)]
 pub struct BrowserStackAuth {
@@ -84,23 +255,53 @@ pub struct BrowserStackClient {
     auth: BrowserStackAuth,
     base_url: String,
     project: Option<String>,
+    max_attempts: u32,
+    retry_delay: Duration,
+    fetch_concurrency: usize,
+    device_cache: Option<DeviceCache>,
+}
+
+/// HTTP client with the mobench user agent, shared by the BrowserStack API
+/// and `--notify-webhook`.
+pub(crate) fn http_client() -> Result<Client> {
+    Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .context("building HTTP client")
 }
 
 impl BrowserStackClient {
     pub fn new(auth: BrowserStackAuth, project: Option<String>) -> Result<Self> {
-        let http = Client::builder()
-            .user_agent(USER_AGENT)
-            .build()
-            .context("building HTTP client")?;
-
         Ok(Self {
-            http,
+            http: http_client()?,
             auth,
             base_url: DEFAULT_BASE_URL.to_string(),
             project,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            fetch_concurrency: DEFAULT_FETCH_CONCURRENCY,
+            device_cache: None,
         })
     }
 
+    /// Cache device lists in `dir` for `ttl`, so repeated runs skip the slow,
+    /// rate-limited device endpoints. `refresh` fetches a new list even when
+    /// the cached one is still fresh.
+    pub fn with_device_cache(mut self, dir: impl Into<PathBuf>, ttl: Duration, refresh: bool) -> Self {
+        self.device_cache = Some(DeviceCache {
+            dir: dir.into(),
+            ttl,
+            refresh,
+        });
+        self
+    }
+
+    /// Fetch at most `limit` device sessions at a time (at least one).
+    pub fn with_fetch_concurrency(mut self, limit: usize) -> Self {
+        self.fetch_concurrency = limit.max(1);
+        self
+    }
+
     #[cfg(test)]
     #[allow(dead_code)] // Used in tests to verify URL construction
     pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
@@ -108,14 +309,30 @@ impl BrowserStackClient {
         self
     }
 
+    #[cfg(test)]
+    pub fn with_retry(mut self, max_attempts: u32, retry_delay: Duration) -> Self {
+        self.max_attempts = max_attempts;
+        self.retry_delay = retry_delay;
+        self
+    }
+
     /// Upload an Espresso app-under-test APK to BrowserStack.
     pub fn upload_espresso_app(&self, artifact: &Path) -> Result<AppUpload> {
         if !artifact.exists() {
-            return Err(anyhow!("app artifact not found at {:?}", artifact));
+            return Err(MobenchError::ArtifactMissing(format!(
+                "app artifact not found at {:?}",
+                artifact
+            ))
+            .into());
         }
 
+        let kind = if artifact.extension().is_some_and(|ext| ext == "aab") {
+            "Android App Bundle"
+        } else {
+            "Android APK"
+        };
         let file_size = get_file_size(artifact);
-        println!("Uploading Android APK ({})...", format_file_size(file_size));
+        println!("Uploading {} ({})...", kind, format_file_size(file_size));
         let start = Instant::now();
 
         let form = Form::new().file("file", artifact)?;
@@ -125,11 +342,17 @@ impl BrowserStackClient {
             .basic_auth(&self.auth.username, Some(&self.auth.access_key))
             .multipart(form)
             .send()
-            .context("uploading app to BrowserStack")?;
+            .map_err(|err| network_error(err, "uploading app to BrowserStack"))?;
 
-        let result = parse_response(resp, "app upload")?;
+        let result = parse_response(resp, "app upload").map_err(upload_error)?;
         let elapsed = start.elapsed().as_secs();
-        println!("  Uploaded Android APK (took {}s)", elapsed);
+        println!("  Uploaded {} (took {}s)", kind, elapsed);
+        tracing::info!(
+            event = "upload",
+            kind = kind,
+            bytes = file_size,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+        );
 
         Ok(result)
     }
@@ -137,7 +360,11 @@ impl BrowserStackClient {
     /// Upload an Espresso test-suite APK to BrowserStack.
     pub fn upload_espresso_test_suite(&self, artifact: &Path) -> Result<TestSuiteUpload> {
         if !artifact.exists() {
-            return Err(anyhow!("test suite artifact not found at {:?}", artifact));
+            return Err(MobenchError::ArtifactMissing(format!(
+                "test suite artifact not found at {:?}",
+                artifact
+            ))
+            .into());
         }
 
         let file_size = get_file_size(artifact);
@@ -151,18 +378,28 @@ impl BrowserStackClient {
             .basic_auth(&self.auth.username, Some(&self.auth.access_key))
             .multipart(form)
             .send()
-            .context("uploading test suite to BrowserStack")?;
+            .map_err(|err| network_error(err, "uploading test suite to BrowserStack"))?;
 
-        let result = parse_response(resp, "test suite upload")?;
+        let result = parse_response(resp, "test suite upload").map_err(upload_error)?;
         let elapsed = start.elapsed().as_secs();
         println!("  Uploaded Android test APK (took {}s)", elapsed);
+        tracing::info!(
+            event = "upload",
+            kind = "Android test APK",
+            bytes = file_size,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+        );
 
         Ok(result)
     }
 
     pub fn upload_xcuitest_app(&self, artifact: &Path) -> Result<AppUpload> {
         if !artifact.exists() {
-            return Err(anyhow!("iOS app artifact not found at {:?}", artifact));
+            return Err(MobenchError::ArtifactMissing(format!(
+                "iOS app artifact not found at {:?}",
+                artifact
+            ))
+            .into());
         }
 
         let file_size = get_file_size(artifact);
@@ -176,21 +413,28 @@ impl BrowserStackClient {
             .basic_auth(&self.auth.username, Some(&self.auth.access_key))
             .multipart(form)
             .send()
-            .context("uploading iOS app to BrowserStack")?;
+            .map_err(|err| network_error(err, "uploading iOS app to BrowserStack"))?;
 
-        let result = parse_response(resp, "iOS app upload")?;
+        let result = parse_response(resp, "iOS app upload").map_err(upload_error)?;
         let elapsed = start.elapsed().as_secs();
         println!("  Uploaded iOS app IPA (took {}s)", elapsed);
+        tracing::info!(
+            event = "upload",
+            kind = "iOS app IPA",
+            bytes = file_size,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+        );
 
         Ok(result)
     }
 
     pub fn upload_xcuitest_test_suite(&self, artifact: &Path) -> Result<TestSuiteUpload> {
         if !artifact.exists() {
-            return Err(anyhow!(
+            return Err(MobenchError::ArtifactMissing(format!(
                 "iOS XCUITest suite artifact not found at {:?}",
                 artifact
-            ));
+            ))
+            .into());
         }
 
         let file_size = get_file_size(artifact);
@@ -204,11 +448,17 @@ impl BrowserStackClient {
             .basic_auth(&self.auth.username, Some(&self.auth.access_key))
             .multipart(form)
             .send()
-            .context("uploading iOS XCUITest suite to BrowserStack")?;
+            .map_err(|err| network_error(err, "uploading iOS XCUITest suite to BrowserStack"))?;
 
-        let result = parse_response(resp, "iOS XCUITest suite upload")?;
+        let result = parse_response(resp, "iOS XCUITest suite upload").map_err(upload_error)?;
         let elapsed = start.elapsed().as_secs();
         println!("  Uploaded iOS XCUITest runner (took {}s)", elapsed);
+        tracing::info!(
+            event = "upload",
+            kind = "iOS XCUITest runner",
+            bytes = file_size,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+        );
 
         Ok(result)
     }
@@ -229,14 +479,7 @@ impl BrowserStackClient {
             return Err(anyhow!("test_suite_url is empty"));
         }
 
-        let body = BuildRequest {
-            app: app_url.to_string(),
-            test_suite: test_suite_url.to_string(),
-            devices: devices.to_vec(),
-            device_logs: true,
-            disable_animations: true,
-            build_name: self.project.clone(),
-        };
+        let body = BuildRequest::new(self.project.clone(), devices, app_url, test_suite_url);
 
         let resp = self
             .http
@@ -244,9 +487,15 @@ impl BrowserStackClient {
             .basic_auth(&self.auth.username, Some(&self.auth.access_key))
             .json(&body)
             .send()
-            .context("scheduling BrowserStack Espresso run")?;
-
-        let build: BuildResponse = parse_response(resp, "schedule run")?;
+            .map_err(|err| network_error(err, "scheduling BrowserStack Espresso run"))?;
+
+        let build = parse_schedule_response(resp)?;
+        tracing::info!(
+            event = "schedule",
+            platform = "espresso",
+            build_id = %build.build_id,
+            devices = devices.len(),
+        );
         Ok(ScheduledRun {
             build_id: build.build_id,
         })
@@ -268,17 +517,8 @@ impl BrowserStackClient {
             return Err(anyhow!("test_suite_url is empty"));
         }
 
-        let body = XcuitestBuildRequest {
-            app: app_url.to_string(),
-            test_suite: test_suite_url.to_string(),
-            devices: devices.to_vec(),
-            device_logs: true,
-            build_name: self.project.clone(),
-            // Specify the test method to run (required by BrowserStack for XCUITest)
-            only_testing: Some(vec![
-                "BenchRunnerUITests/BenchRunnerUITests/testLaunchAndCaptureBenchmarkReport".to_string(),
-            ]),
-        };
+        let body =
+            XcuitestBuildRequest::new(self.project.clone(), devices, app_url, test_suite_url);
 
         let resp = self
             .http
@@ -286,9 +526,15 @@ impl BrowserStackClient {
             .basic_auth(&self.auth.username, Some(&self.auth.access_key))
             .json(&body)
             .send()
-            .context("scheduling BrowserStack XCUITest run")?;
-
-        let build: BuildResponse = parse_response(resp, "schedule run")?;
+            .map_err(|err| network_error(err, "scheduling BrowserStack XCUITest run"))?;
+
+        let build = parse_schedule_response(resp)?;
+        tracing::info!(
+            event = "schedule",
+            platform = "xcuitest",
+            build_id = %build.build_id,
+            devices = devices.len(),
+        );
         Ok(ScheduledRun {
             build_id: build.build_id,
         })
@@ -302,33 +548,64 @@ impl BrowserStackClient {
         )
     }
 
+    /// Send a GET request, retrying transport errors, 429 and 5xx responses
+    /// with exponential backoff.
+    ///
+    /// The last response is returned even when its status is an error, so
+    /// callers report it as they would without retries.
+    fn get_with_retry(&self, url: &str, context: &str) -> Result<Response> {
+        let mut delay = self.retry_delay;
+        for attempt in 1..=self.max_attempts {
+            let last = attempt == self.max_attempts;
+            let failure = match self
+                .http
+                .get(url)
+                .basic_auth(&self.auth.username, Some(&self.auth.access_key))
+                .send()
+            {
+                Ok(resp) if last || !is_transient_status(resp.status()) => return Ok(resp),
+                Ok(resp) => format!("status {}", resp.status()),
+                Err(err) if last => return Err(network_error(err, context)),
+                Err(err) => err.to_string(),
+            };
+            tracing::warn!(
+                event = "http_retry",
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                error = %failure,
+                "{}",
+                context,
+            );
+            std::thread::sleep(delay);
+            delay *= 2;
+        }
+        Err(anyhow!("{}: no request attempts configured", context))
+    }
+
     pub fn get_json(&self, path: &str) -> Result<Value> {
-        let resp = self
-            .http
-            .get(self.api(path))
-            .basic_auth(&self.auth.username, Some(&self.auth.access_key))
-            .send()
-            .with_context(|| format!("requesting BrowserStack API {}", path))?;
+        let resp = self.get_with_retry(
+            &self.api(path),
+            &format!("requesting BrowserStack API {}", path),
+        )?;
 
         parse_response(resp, path)
     }
 
     pub fn download_url(&self, url: &str, dest: &Path) -> Result<()> {
-        let resp = self
-            .http
-            .get(url)
-            .basic_auth(&self.auth.username, Some(&self.auth.access_key))
-            .send()
-            .with_context(|| format!("downloading BrowserStack asset {}", url))?;
+        let resp =
+            self.get_with_retry(url, &format!("downloading BrowserStack asset {}", url))?;
         let status = resp.status();
         let bytes = resp
             .bytes()
             .with_context(|| format!("reading BrowserStack asset body {}", url))?;
         if !status.is_success() {
-            return Err(anyhow!(
-                "BrowserStack asset download failed (status {}): {}",
+            return Err(status_error(
                 status,
-                String::from_utf8_lossy(&bytes)
+                format!(
+                    "BrowserStack asset download failed (status {}): {}",
+                    status,
+                    String::from_utf8_lossy(&bytes)
+                ),
             ));
         }
         std::fs::write(dest, bytes)
@@ -338,14 +615,43 @@ impl BrowserStackClient {
 
     /// List available Android devices for Espresso testing.
     pub fn list_espresso_devices(&self) -> Result<Vec<BrowserStackDevice>> {
-        let json = self.get_json("app-automate/espresso/v2/devices")?;
-        parse_device_list(json, "espresso")
+        self.cached_devices("android", || {
+            let json = self.get_json("app-automate/espresso/v2/devices")?;
+            parse_device_list(json, "espresso")
+        })
     }
 
     /// List available iOS devices for XCUITest testing.
     pub fn list_xcuitest_devices(&self) -> Result<Vec<BrowserStackDevice>> {
-        let json = self.get_json("app-automate/xcuitest/v2/devices")?;
-        parse_device_list(json, "xcuitest")
+        self.cached_devices("ios", || {
+            let json = self.get_json("app-automate/xcuitest/v2/devices")?;
+            parse_device_list(json, "xcuitest")
+        })
+    }
+
+    /// `fetch` the device list for `platform`, going through the device
+    /// cache when one is configured. Cache read and write failures only
+    /// cost the cache.
+    fn cached_devices(
+        &self,
+        platform: &str,
+        fetch: impl FnOnce() -> Result<Vec<BrowserStackDevice>>,
+    ) -> Result<Vec<BrowserStackDevice>> {
+        let Some(cache) = &self.device_cache else {
+            return fetch();
+        };
+        let now = unix_now();
+        if !cache.refresh
+            && let Some(devices) = cache.read(platform, now)
+        {
+            tracing::debug!(platform, devices = devices.len(), "using cached device list");
+            return Ok(devices);
+        }
+        let devices = fetch()?;
+        if let Err(err) = cache.write(platform, now, &devices) {
+            tracing::debug!("not caching the {platform} device list: {err:#}");
+        }
+        Ok(devices)
     }
 
     /// List all available devices (both Android and iOS).
@@ -385,15 +691,27 @@ impl BrowserStackClient {
 
         let mut valid = Vec::new();
         let mut invalid = Vec::new();
+        let mut unavailable = Vec::new();
 
         for spec in specs {
             match validate_device_spec(spec, &available) {
-                Ok(matched) => valid.push(matched),
+                Ok(matched) => {
+                    if available.iter().any(|device| {
+                        device.available == Some(false) && device.identifier() == matched
+                    }) {
+                        unavailable.push(spec.clone());
+                    }
+                    valid.push(matched);
+                }
                 Err(error) => invalid.push(error),
             }
         }
 
-        Ok(DeviceValidationResult { valid, invalid })
+        Ok(DeviceValidationResult {
+            valid,
+            invalid,
+            unavailable,
+        })
     }
 
     /// Get the status of an Espresso build
@@ -410,25 +728,27 @@ impl BrowserStackClient {
         build_status_from_value(json).context("parsing build status response")
     }
 
-    /// Poll for build completion with timeout
+    /// Poll for build completion, calling `on_status` after every poll.
     ///
-    /// # Arguments
-    /// * `build_id` - The build ID to poll
-    /// * `platform` - "espresso" or "xcuitest"
-    /// * `timeout_secs` - Maximum time to wait in seconds (default: 600)
-    /// * `poll_interval_secs` - How often to check status in seconds (default: 10)
-    pub fn poll_build_completion(
+    /// SIGINT stops the wait with a [`WaitCancelled`] error; the build itself
+    /// keeps running on BrowserStack.
+    pub fn poll_build_completion_with_progress(
         &self,
         build_id: &str,
         platform: &str,
         timeout_secs: u64,
         poll_interval_secs: u64,
+        mut on_status: impl FnMut(&BuildStatus),
     ) -> Result<BuildStatus> {
-        use std::time::{Duration, Instant};
-
         let start = Instant::now();
         let timeout = Duration::from_secs(timeout_secs);
         let poll_interval = Duration::from_secs(poll_interval_secs);
+        let wait = InterruptibleWait::start();
+        let cancelled = || {
+            anyhow::Error::new(WaitCancelled {
+                build_id: build_id.to_string(),
+            })
+        };
 
         loop {
             let status = match platform {
@@ -436,26 +756,32 @@ impl BrowserStackClient {
                 "xcuitest" => self.get_xcuitest_build_status(build_id)?,
                 _ => return Err(anyhow!("unsupported platform: {}", platform)),
             };
+            on_status(&status);
+            if wait.cancelled() {
+                return Err(cancelled());
+            }
 
             match status.status.to_lowercase().as_str() {
                 "done" | "passed" | "completed" => return Ok(status),
                 "failed" | "error" | "timeout" => {
-                    return Err(anyhow!(
+                    return Err(MobenchError::ResultsUnavailable(format!(
                         "Build {} failed with status: {}",
-                        build_id,
-                        status.status
-                    ));
+                        build_id, status.status
+                    ))
+                    .into());
                 }
                 _ => {
                     // Still running
                     if start.elapsed() >= timeout {
-                        return Err(anyhow!(
+                        return Err(MobenchError::ResultsUnavailable(format!(
                             "Timeout waiting for build {} to complete (waited {} seconds)",
-                            build_id,
-                            timeout_secs
-                        ));
+                            build_id, timeout_secs
+                        ))
+                        .into());
+                    }
+                    if wait.sleep(poll_interval) {
+                        return Err(cancelled());
                     }
-                    std::thread::sleep(poll_interval);
                 }
             }
         }
@@ -480,36 +806,61 @@ impl BrowserStackClient {
             _ => return Err(anyhow!("unsupported platform: {}", platform)),
         };
 
-        let resp = self
-            .http
-            .get(self.api(&path))
-            .basic_auth(&self.auth.username, Some(&self.auth.access_key))
-            .send()
-            .with_context(|| format!("fetching device logs for session {}", session_id))?;
+        let resp = self.get_with_retry(
+            &self.api(&path),
+            &format!("fetching device logs for session {}", session_id),
+        )?;
 
         let status = resp.status();
         let text = resp.text().context("reading device logs response")?;
 
         if !status.is_success() {
-            return Err(anyhow!(
-                "Failed to fetch device logs (status {}): {}",
+            return Err(status_error(
                 status,
-                text
+                format!("Failed to fetch device logs (status {}): {}", status, text),
             ));
         }
 
         Ok(text)
     }
 
+    /// Get the session JSON for a single device session
+    pub fn get_session_details(
+        &self,
+        build_id: &str,
+        session_id: &str,
+        platform: &str,
+    ) -> Result<Value> {
+        let path = match platform {
+            "espresso" => format!(
+                "app-automate/espresso/v2/builds/{}/sessions/{}",
+                build_id, session_id
+            ),
+            "xcuitest" => format!(
+                "app-automate/xcuitest/v2/builds/{}/sessions/{}",
+                build_id, session_id
+            ),
+            _ => return Err(anyhow!("unsupported platform: {}", platform)),
+        };
+        self.get_json(&path)
+    }
+
     /// Extract benchmark results from device logs
     /// Looks for JSON output matching BenchReport format
     /// Supports both Android (BENCH_JSON) and iOS (BENCH_REPORT_JSON_START/END) formats
     pub fn extract_benchmark_results(&self, logs: &str) -> Result<Vec<Value>> {
-        let mut results = Vec::new();
+        let mut results: Vec<Value> = Vec::new();
+        // Android logs every report both as BENCH_JSON and between markers
+        let mut push_unique = |json: Value| {
+            if !results.contains(&json) {
+                results.push(json);
+            }
+        };
 
-        // First, try iOS-style markers: BENCH_REPORT_JSON_START ... BENCH_REPORT_JSON_END
-        if let Some(json) = Self::extract_ios_bench_json(logs) {
-            results.push(json);
+        // First, try iOS-style markers: BENCH_REPORT_JSON_START ... BENCH_REPORT_JSON_END.
+        // Suites log one block per function.
+        for json in Self::extract_all_ios_bench_json(logs) {
+            push_unique(json);
         }
 
         // Also look for Android-style BENCH_JSON marker
@@ -522,7 +873,7 @@ impl BrowserStackClient {
                         || json.get("samples").is_some()
                         || json.get("spec").is_some()
                     {
-                        results.push(json);
+                        push_unique(json);
                     }
                 }
             }
@@ -555,22 +906,29 @@ impl BrowserStackClient {
         }
     }
 
-    /// Extract benchmark JSON from iOS logs using START/END markers.
+    /// Extract every benchmark JSON block from iOS logs using START/END markers.
     /// iOS uses NSLog which may split the JSON across multiple log lines.
-    fn extract_ios_bench_json(logs: &str) -> Option<Value> {
+    fn extract_all_ios_bench_json(logs: &str) -> Vec<Value> {
         let start_marker = "BENCH_REPORT_JSON_START";
         let end_marker = "BENCH_REPORT_JSON_END";
 
-        // Find the last occurrence of start marker (in case of multiple runs)
-        let start_pos = logs.rfind(start_marker)?;
-        let after_start = &logs[start_pos + start_marker.len()..];
-
-        // Find the end marker after the start
-        let end_pos = after_start.find(end_marker)?;
-        let json_section = &after_start[..end_pos];
+        logs.match_indices(start_marker)
+            .filter_map(|(start_pos, _)| {
+                let after_start = &logs[start_pos + start_marker.len()..];
+                // A block cut off before its end marker must not swallow the next one
+                let json_section = &after_start[..after_start.find(end_marker)?];
+                if json_section.contains(start_marker) {
+                    return None;
+                }
+                Self::extract_json_from_ios_log_section(json_section)
+            })
+            .collect()
+    }
 
-        // Try to extract valid JSON from the section
-        Self::extract_json_from_ios_log_section(json_section)
+    /// Extract the last benchmark JSON block from iOS logs.
+    #[cfg(test)]
+    fn extract_ios_bench_json(logs: &str) -> Option<Value> {
+        Self::extract_all_ios_bench_json(logs).pop()
     }
 
     /// Extract valid JSON from an iOS log section that may contain log prefixes/timestamps.
@@ -709,17 +1067,75 @@ impl BrowserStackClient {
         timeout_secs: Option<u64>,
         poll_interval_secs: Option<u64>,
     ) -> Result<BrowserStackResults> {
-        let timeout = timeout_secs.unwrap_or(300);
-        let poll_interval = poll_interval_secs.unwrap_or(5);
+        self.wait_and_fetch_all_results_with_progress(
+            build_id,
+            platform,
+            timeout_secs,
+            poll_interval_secs,
+            |_| {},
+        )
+    }
 
+    /// Like [`Self::wait_and_fetch_all_results_with_poll`], calling
+    /// `on_status` with each polled build status so callers can show
+    /// per-device session states while waiting.
+    pub fn wait_and_fetch_all_results_with_progress(
+        &self,
+        build_id: &str,
+        platform: &str,
+        timeout_secs: Option<u64>,
+        poll_interval_secs: Option<u64>,
+        on_status: impl FnMut(&BuildStatus),
+    ) -> Result<BrowserStackResults> {
+        let build_status = self.wait_for_completion(
+            build_id,
+            platform,
+            timeout_secs.unwrap_or(300),
+            poll_interval_secs.unwrap_or(5),
+            on_status,
+        )?;
+        self.fetch_all_results(build_id, platform, &build_status)
+    }
+
+    /// [`Self::poll_build_completion_with_progress`] with progress messages
+    /// before and after the wait.
+    fn wait_for_completion(
+        &self,
+        build_id: &str,
+        platform: &str,
+        timeout_secs: u64,
+        poll_interval_secs: u64,
+        on_status: impl FnMut(&BuildStatus),
+    ) -> Result<BuildStatus> {
         println!(
-            "Waiting for build {} to complete (timeout: {}s, poll: {}s)...",
-            build_id, timeout, poll_interval
+            "Waiting for build {} to complete (timeout: {}s, poll: {}s; Ctrl-C stops waiting)...",
+            build_id, timeout_secs, poll_interval_secs
         );
-        let build_status =
-            self.poll_build_completion(build_id, platform, timeout, poll_interval)?;
-
+        let build_status = self.poll_build_completion_with_progress(
+            build_id,
+            platform,
+            timeout_secs,
+            poll_interval_secs,
+            on_status,
+        )?;
         println!("Build completed with status: {}", build_status.status);
+        Ok(build_status)
+    }
+
+    /// Fetch benchmark reports, performance metrics and device metadata for
+    /// every session of a finished build.
+    pub fn fetch_all_results(
+        &self,
+        build_id: &str,
+        platform: &str,
+        build_status: &BuildStatus,
+    ) -> Result<BrowserStackResults> {
+        tracing::info!(
+            event = "fetch",
+            build_id,
+            status = %build_status.status,
+            devices = build_status.devices.len(),
+        );
         println!(
             "Fetching results from {} device(s)...",
             build_status.devices.len()
@@ -727,14 +1143,30 @@ impl BrowserStackClient {
 
         let mut benchmark_results = std::collections::HashMap::new();
         let mut performance_metrics = std::collections::HashMap::new();
+        let mut device_meta = std::collections::HashMap::new();
+
+        // Sessions are fetched in parallel, but reported and collected in the
+        // order BrowserStack listed them
+        let fetched = map_bounded(&build_status.devices, self.fetch_concurrency, |device| {
+            // Device metadata is best-effort context; never fail the fetch over it
+            let meta = self
+                .get_session_details(build_id, &device.session_id, platform)
+                .map(|session| extract_device_meta(&session))
+                .unwrap_or_default();
+            let logs = self.get_device_logs(build_id, &device.session_id, platform);
+            (meta, logs)
+        });
 
-        for device in &build_status.devices {
+        for (device, (meta, logs)) in build_status.devices.iter().zip(fetched) {
             println!(
-                "  Fetching logs for {} (session: {})...",
+                "  Fetched logs for {} (session: {})",
                 device.device, device.session_id
             );
+            if !meta.is_empty() {
+                device_meta.insert(device.device.clone(), meta);
+            }
 
-            match self.get_device_logs(build_id, &device.session_id, platform) {
+            match logs {
                 Ok(logs) => {
                     // Extract benchmark results
                     match self.extract_benchmark_results(&logs) {
@@ -771,11 +1203,259 @@ impl BrowserStackClient {
         }
 
         if benchmark_results.is_empty() {
-            Err(anyhow!("No benchmark results found from any device"))
+            Err(MobenchError::ResultsUnavailable(
+                "No benchmark results found from any device".into(),
+            )
+            .into())
         } else {
-            Ok((benchmark_results, performance_metrics))
+            Ok((benchmark_results, performance_metrics, device_meta))
+        }
+    }
+}
+
+/// Apply `f` to every item with at most `limit` calls running at once.
+///
+/// Results are returned in the order of `items`, whichever call finishes
+/// first.
+fn map_bounded<T, R, F>(items: &[T], limit: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..limit.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = f(item);
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item is processed"))
+        .collect()
+}
+
+/// [`DeviceBackend`] running Espresso or XCUITest builds on BrowserStack App
+/// Automate, printing progress as it goes.
+pub struct BrowserStackBackend<'a> {
+    client: &'a BrowserStackClient,
+    /// `espresso` or `xcuitest`.
+    platform: &'static str,
+    /// Receives each device's reports as they are fetched, for `--json-lines`,
+    /// and decides what of them the run keeps.
+    json_lines: Option<&'a crate::json_lines::JsonLinesWriter>,
+}
+
+impl<'a> BrowserStackBackend<'a> {
+    /// Runs builds through `client`; `platform` is `espresso` for Android
+    /// and anything else (conventionally `xcuitest`) for iOS.
+    pub fn new(client: &'a BrowserStackClient, platform: &'static str) -> Self {
+        Self {
+            client,
+            platform,
+            json_lines: None,
+        }
+    }
+
+    /// Stream fetched reports to `writer`.
+    pub(crate) fn with_json_lines(mut self, writer: Option<&'a crate::json_lines::JsonLinesWriter>) -> Self {
+        self.json_lines = writer;
+        self
+    }
+}
+
+impl DeviceBackend for BrowserStackBackend<'_> {
+    type Metrics = PerformanceMetrics;
+    type Error = anyhow::Error;
+
+    fn upload(&self, artifacts: &RunArtifacts) -> Result<UploadedArtifacts> {
+        let (app_url, test_suite_url) = match self.platform {
+            "espresso" => (
+                self.client.upload_espresso_app(&artifacts.app)?.app_url,
+                self.client.upload_espresso_test_suite(&artifacts.test_suite)?.test_suite_url,
+            ),
+            _ => (
+                self.client.upload_xcuitest_app(&artifacts.app)?.app_url,
+                self.client.upload_xcuitest_test_suite(&artifacts.test_suite)?.test_suite_url,
+            ),
+        };
+        Ok(UploadedArtifacts {
+            app_url,
+            test_suite_url,
+        })
+    }
+
+    fn schedule(&self, devices: &[String], uploaded: &UploadedArtifacts) -> Result<String> {
+        let run = match self.platform {
+            "espresso" => self.client.schedule_espresso_run(
+                devices,
+                &uploaded.app_url,
+                &uploaded.test_suite_url,
+            )?,
+            _ => self.client.schedule_xcuitest_run(
+                devices,
+                &uploaded.app_url,
+                &uploaded.test_suite_url,
+            )?,
+        };
+        Ok(run.build_id)
+    }
+
+    fn wait(&self, build_id: &str, options: &RunOptions) -> Result<()> {
+        let mut sessions = std::collections::BTreeMap::new();
+        self.client.wait_for_completion(
+            build_id,
+            self.platform,
+            options.timeout.as_secs(),
+            options.poll_interval.as_secs(),
+            |status| {
+                for line in crate::session_transitions(&mut sessions, status) {
+                    println!("  {}", line);
+                }
+            },
+        )?;
+        Ok(())
+    }
+
+    fn fetch(&self, build_id: &str) -> Result<orchestrate::DeviceResults<PerformanceMetrics>> {
+        let status = match self.platform {
+            "espresso" => self.client.get_espresso_build_status(build_id)?,
+            _ => self.client.get_xcuitest_build_status(build_id)?,
+        };
+        let (benchmarks, metrics, device_meta) =
+            self.client.fetch_all_results(build_id, self.platform, &status)?;
+        let benchmarks = match self.json_lines {
+            Some(writer) => benchmarks
+                .into_iter()
+                .map(|(device, reports)| {
+                    let reports = writer.stream(&device, Some(build_id), reports);
+                    (device, reports)
+                })
+                .collect(),
+            None => benchmarks.into_iter().collect(),
+        };
+        Ok(orchestrate::DeviceResults {
+            benchmarks,
+            metrics: metrics.into_iter().collect(),
+            device_meta: device_meta.into_iter().collect(),
+        })
+    }
+
+    fn on_event(&self, event: RunEvent<'_>) {
+        match event {
+            RunEvent::FetchFailed { attempt: 0, error, .. } => println!("\nWarning: {}", error),
+            RunEvent::FetchFailed { attempt, error, .. } => {
+                println!("  Warning: retry {} produced no results: {}", attempt, error)
+            }
+            RunEvent::Rescheduled {
+                attempt,
+                retries,
+                build_id,
+                devices,
+            } => {
+                println!(
+                    "\nRe-scheduled {} device(s) with no benchmark report (retry {}/{}): {}",
+                    devices.len(),
+                    attempt,
+                    retries,
+                    devices.join(", ")
+                );
+                println!("  Retry build ID: {}", build_id);
+                tracing::info!(
+                    event = "device_retry",
+                    attempt,
+                    build_id = %build_id,
+                    devices = devices.len(),
+                );
+            }
+            RunEvent::ScheduleFailed { devices, error } => {
+                println!(
+                    "\nWarning: could not schedule {}: {}",
+                    devices.join(", "),
+                    error
+                );
+                if devices.len() > 1 {
+                    println!("  Scheduling each device separately (--no-fail-fast)");
+                }
+            }
+            RunEvent::RescheduleFailed { attempt, devices, error } => {
+                println!(
+                    "\nWarning: could not re-schedule {} (retry {}): {}",
+                    devices.join(", "),
+                    attempt,
+                    error
+                );
+                println!("  Keeping the results fetched so far");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Device metadata captured from a BrowserStack session (CPU, RAM, battery/thermal hints).
+pub type DeviceMeta = serde_json::Map<String, Value>;
+
+/// Substrings of session JSON keys that describe the device hardware or its state.
+const DEVICE_META_KEY_HINTS: &[&str] = &[
+    "cpu",
+    "processor",
+    "chipset",
+    "soc",
+    "ram",
+    "memory",
+    "battery",
+    "thermal",
+    "temperature",
+    "os_version",
+    "osversion",
+];
+
+/// Extract device metadata from a BrowserStack session JSON.
+///
+/// Scalar fields whose names hint at hardware or thermal state are collected
+/// from the session object and any nested objects (e.g. `device_details`).
+/// Nested keys are flattened with a `.` separator.
+pub fn extract_device_meta(session: &Value) -> DeviceMeta {
+    fn visit(value: &Value, prefix: &str, depth: usize, meta: &mut DeviceMeta) {
+        let Some(object) = value.as_object() else {
+            return;
+        };
+        for (key, value) in object {
+            let name = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match value {
+                Value::Object(_) if depth < 2 => visit(value, &name, depth + 1, meta),
+                Value::String(_) | Value::Number(_) | Value::Bool(_) => {
+                    let lower = key.to_lowercase();
+                    if DEVICE_META_KEY_HINTS.iter().any(|hint| lower.contains(hint)) {
+                        meta.insert(name, value.clone());
+                    }
+                }
+                _ => {}
+            }
         }
     }
+
+    let mut meta = DeviceMeta::new();
+    // Session endpoints wrap the payload as {"automation_session": {...}} in some API versions
+    let root = session.get("automation_session").unwrap_or(session);
+    visit(root, "", 0, &mut meta);
+    meta
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -1026,7 +1706,7 @@ impl From<BuildStatusResponse> for BuildStatus {
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct BuildRequest {
+pub(crate) struct BuildRequest {
     app: String,
     test_suite: String,
     devices: Vec<String>,
@@ -1036,9 +1716,28 @@ struct BuildRequest {
     build_name: Option<String>,
 }
 
+impl BuildRequest {
+    /// Body POSTed to schedule an Espresso build.
+    pub(crate) fn new(
+        project: Option<String>,
+        devices: &[String],
+        app_url: &str,
+        test_suite_url: &str,
+    ) -> Self {
+        Self {
+            app: app_url.to_string(),
+            test_suite: test_suite_url.to_string(),
+            devices: devices.to_vec(),
+            device_logs: true,
+            disable_animations: true,
+            build_name: project,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct XcuitestBuildRequest {
+pub(crate) struct XcuitestBuildRequest {
     app: String,
     test_suite: String,
     devices: Vec<String>,
@@ -1049,28 +1748,143 @@ struct XcuitestBuildRequest {
     only_testing: Option<Vec<String>>,
 }
 
+impl XcuitestBuildRequest {
+    /// Body POSTed to schedule an XCUITest build.
+    pub(crate) fn new(
+        project: Option<String>,
+        devices: &[String],
+        app_url: &str,
+        test_suite_url: &str,
+    ) -> Self {
+        Self {
+            app: app_url.to_string(),
+            test_suite: test_suite_url.to_string(),
+            devices: devices.to_vec(),
+            device_logs: true,
+            build_name: project,
+            // Specify the test method to run (required by BrowserStack for XCUITest)
+            only_testing: Some(vec![
+                "BenchRunnerUITests/BenchRunnerUITests/testLaunchAndCaptureBenchmarkReport"
+                    .to_string(),
+            ]),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct BuildResponse {
     #[serde(alias = "build_id", alias = "buildId")]
     build_id: String,
 }
 
+/// Wraps a transport failure (DNS, TLS, connection reset, timeout) as
+/// [`MobenchError::Network`].
+fn network_error(err: reqwest::Error, context: impl std::fmt::Display) -> anyhow::Error {
+    MobenchError::Network(format!("{context}: {err}")).into()
+}
+
+/// Whether a response status is worth retrying: rate limiting or a server
+/// error that may clear up on its own.
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Error for a non-success BrowserStack response; 401 and 403 become
+/// [`MobenchError::AuthFailed`].
+fn status_error(status: StatusCode, message: String) -> anyhow::Error {
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        return MobenchError::AuthFailed(format!(
+            "{message}\nCheck BROWSERSTACK_USERNAME and BROWSERSTACK_ACCESS_KEY."
+        ))
+        .into();
+    }
+    anyhow!(message)
+}
+
+/// Marks a rejected upload as [`MobenchError::UploadFailed`], keeping
+/// authentication failures as they are.
+fn upload_error(err: anyhow::Error) -> anyhow::Error {
+    if err.downcast_ref::<MobenchError>().is_some() {
+        return err;
+    }
+    MobenchError::UploadFailed(format!("{err:#}")).into()
+}
+
 fn parse_response<T: DeserializeOwned>(resp: Response, context: &str) -> Result<T> {
     let status = resp.status();
     let text = resp
         .text()
         .with_context(|| format!("reading BrowserStack API response body for {}", context))?;
+    parse_body(status, &text, context)
+}
+
+/// Like [`parse_response`] for build scheduling, where a full parallel
+/// queue is reported as [`MobenchError::QuotaExceeded`].
+fn parse_schedule_response(resp: Response) -> Result<BuildResponse> {
+    let status = resp.status();
+    let text = resp
+        .text()
+        .context("reading BrowserStack API response body for schedule run")?;
+    if !status.is_success()
+        && let Some(err) = quota_error(&text)
+    {
+        return Err(err.into());
+    }
+    parse_body(status, &text, "schedule run")
+}
 
+/// Error codes and message fragments BrowserStack uses when the account has
+/// no parallel sessions left or its queue is full.
+const QUOTA_MARKERS: &[&str] = &[
+    "all_parallels_in_use",
+    "queue_size_exceeded",
+    "parallel limit",
+    "parallel sessions",
+    "parallels in use",
+    "queue size",
+    "quota",
+];
+
+/// Recognise a parallel-limit or quota response body, pulling out the
+/// account's limit when the payload states it.
+fn quota_error(body: &str) -> Option<MobenchError> {
+    let json: Value = serde_json::from_str(body).ok()?;
+    let text = ["error", "code", "message", "reason"]
+        .iter()
+        .filter_map(|key| json.get(key).and_then(Value::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let lowered = text.to_ascii_lowercase();
+    if !QUOTA_MARKERS.iter().any(|marker| lowered.contains(marker)) {
+        return None;
+    }
+    let limit = ["parallel_limit", "max_parallel_sessions", "parallels", "limit"]
+        .iter()
+        .find_map(|key| json.get(key).and_then(Value::as_u64))
+        .or_else(|| {
+            // e.g. "You have reached the parallel limit of 5 sessions"
+            let (_, rest) = lowered.split_once("limit of ")?;
+            rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+        })
+        .and_then(|limit| u32::try_from(limit).ok());
+    Some(MobenchError::QuotaExceeded {
+        limit,
+        message: text,
+    })
+}
+
+fn parse_body<T: DeserializeOwned>(status: StatusCode, text: &str, context: &str) -> Result<T> {
     if !status.is_success() {
-        return Err(anyhow!(
-            "BrowserStack API {} failed (status {}): {}",
-            context,
+        return Err(status_error(
             status,
-            text
+            format!(
+                "BrowserStack API {} failed (status {}): {}",
+                context, status, text
+            ),
         ));
     }
 
-    serde_json::from_str(&text)
+    serde_json::from_str(text)
         .with_context(|| format!("parsing BrowserStack API response for {}", context))
 }
 
@@ -1295,16 +2109,142 @@ pub fn format_credentials_error(_missing_username: bool, _missing_access_key: bo
     message.push_str("     BROWSERSTACK_USERNAME=your_username\n");
     message.push_str("     BROWSERSTACK_ACCESS_KEY=your_access_key\n\n");
 
+    message.push_str("  4. Credentials file (~/.config/mobench/credentials.toml):\n");
+    message.push_str("     [default.browserstack]\n");
+    message.push_str("     username = \"your_username\"\n");
+    message.push_str("     access_key = \"your_access_key\"\n\n");
+
     message.push_str("Get credentials: https://app-automate.browserstack.com/\n");
     message.push_str("(Navigate to Settings -> Access Key)\n");
 
     message
 }
 
+/// Minimal HTTP server for exercising the client against scripted responses.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Serve `respond(path) -> (status, body)` on a local port until the test
+    /// process exits. Returns the base URL.
+    pub(crate) fn serve(respond: impl Fn(&str) -> (u16, String) + Send + 'static) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                // Skip headers, then drain any body so the client sees the response
+                let mut header = String::new();
+                let mut content_length = 0;
+                while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                    header.clear();
+                }
+                let mut body = vec![0; content_length];
+                let _ = reader.read_exact(&mut body);
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let (status, body) = respond(path);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+        base_url
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn rejected_credentials_are_auth_failures() {
+        let err = status_error(StatusCode::UNAUTHORIZED, "BrowserStack API x failed".into());
+        assert!(matches!(
+            err.downcast_ref::<MobenchError>(),
+            Some(MobenchError::AuthFailed(_))
+        ));
+        let err = status_error(StatusCode::INTERNAL_SERVER_ERROR, "BrowserStack API x failed".into());
+        assert!(err.downcast_ref::<MobenchError>().is_none());
+
+        let err = upload_error(anyhow!("BrowserStack API app upload failed (status 422)"));
+        assert_eq!(crate::error::exit_code(&err), 7);
+    }
+
+    #[test]
+    fn get_json_retries_transient_failures() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicUsize;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let base_url = mock::serve(move |path| match path {
+            // Fails twice, then recovers
+            "/flaky" if counter.fetch_add(1, Ordering::SeqCst) < 2 => {
+                (503, "try again".into())
+            }
+            "/flaky" => (200, r#"{"status":"done"}"#.into()),
+            "/asset" => (200, "log contents".into()),
+            "/missing" => (404, "not found".into()),
+            _ => (500, "down".into()),
+        });
+        let client = BrowserStackClient::new(
+            BrowserStackAuth {
+                username: "user".into(),
+                access_key: "key".into(),
+            },
+            None,
+        )
+        .unwrap()
+        .with_base_url(base_url.clone())
+        .with_retry(3, Duration::ZERO);
+
+        assert_eq!(client.get_json("flaky").unwrap()["status"], "done");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // Client errors are returned without retrying; persistent server
+        // errors once the attempts run out
+        let err = client.get_json("missing").unwrap_err();
+        assert!(err.to_string().contains("status 404"), "{err}");
+        let err = client.get_json("down").unwrap_err();
+        assert!(err.to_string().contains("status 500"), "{err}");
+
+        let dest = std::env::temp_dir().join(format!("mobench-asset-{}", std::process::id()));
+        client.download_url(&format!("{base_url}/asset"), &dest).unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "log contents");
+        let _ = std::fs::remove_file(&dest);
+    }
+
+    #[test]
+    fn map_bounded_limits_concurrency_and_keeps_order() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items: Vec<u64> = (0..12).collect();
+        let doubled = map_bounded(&items, 3, |item| {
+            let running = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(running, Ordering::SeqCst);
+            // Later items finish first
+            std::thread::sleep(Duration::from_millis(12 - item));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            item * 2
+        });
+        assert_eq!(doubled, items.iter().map(|item| item * 2).collect::<Vec<_>>());
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(map_bounded(&[] as &[u64], 4, |item| *item).is_empty());
+    }
+
     #[test]
     fn rejects_missing_artifact() {
         let client = BrowserStackClient::new(
@@ -1402,6 +2342,89 @@ mod tests {
         assert_eq!(url, "https://test.example.com/endpoint");
     }
 
+    #[test]
+    fn device_lists_are_cached_until_the_ttl_expires() {
+        use std::sync::Arc;
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let base_url = mock::serve(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (200, r#"[{"device":"Google Pixel 7","os":"android","os_version":"13.0"}]"#.into())
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let client = |refresh| {
+            BrowserStackClient::new(
+                BrowserStackAuth {
+                    username: "user".into(),
+                    access_key: "key".into(),
+                },
+                None,
+            )
+            .unwrap()
+            .with_base_url(base_url.clone())
+            .with_device_cache(dir.path(), DEFAULT_DEVICE_CACHE_TTL, refresh)
+        };
+
+        assert_eq!(client(false).list_espresso_devices().unwrap().len(), 1);
+        assert_eq!(client(false).list_espresso_devices().unwrap()[0].device, "Google Pixel 7");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        let cached: Value =
+            serde_json::from_slice(&std::fs::read(dir.path().join("devices-android.json")).unwrap()).unwrap();
+        assert_eq!(cached["ttl_secs"], 3600);
+        assert!(cached["fetched_at"].as_u64().unwrap() > 0);
+
+        // --refresh-devices bypasses a fresh cache
+        client(true).list_espresso_devices().unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // An expired list is fetched again
+        let mut stale = cached;
+        stale["fetched_at"] = serde_json::json!(unix_now() - 2 * 3600);
+        std::fs::write(dir.path().join("devices-android.json"), stale.to_string()).unwrap();
+        client(false).list_espresso_devices().unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn parallel_limit_responses_are_quota_errors() {
+        let base_url = mock::serve(|path| match path {
+            "/app-automate/espresso/v2/build" => (
+                429,
+                r#"{"error":"BROWSERSTACK_ALL_PARALLELS_IN_USE","message":"All parallel sessions are in use","parallel_limit":5}"#.into(),
+            ),
+            _ => (422, r#"{"message":"Invalid device"}"#.into()),
+        });
+        let client = BrowserStackClient::new(
+            BrowserStackAuth {
+                username: "user".into(),
+                access_key: "key".into(),
+            },
+            None,
+        )
+        .unwrap()
+        .with_base_url(base_url);
+        let devices = ["Google Pixel 7-13.0".to_string()];
+
+        let err = client.schedule_espresso_run(&devices, "bs://app", "bs://suite").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<MobenchError>(),
+            Some(MobenchError::QuotaExceeded { limit: Some(5), .. })
+        ));
+        assert_eq!(crate::error::exit_code(&err), 12);
+        assert!(err.to_string().contains("account limit: 5"), "{err}");
+        assert!(err.to_string().contains("fewer devices"), "{err}");
+
+        // The limit may only be stated in the message
+        let err = quota_error(r#"{"message":"You have reached the parallel limit of 2 sessions"}"#);
+        assert!(matches!(err, Some(MobenchError::QuotaExceeded { limit: Some(2), .. })));
+
+        // Other scheduling failures stay plain errors
+        let err = client.schedule_xcuitest_run(&devices, "bs://app", "bs://suite").unwrap_err();
+        assert!(err.downcast_ref::<MobenchError>().is_none());
+        assert!(err.to_string().contains("Invalid device"), "{err}");
+    }
+
     #[test]
     fn schedule_espresso_run_rejects_empty_devices() {
         let client = BrowserStackClient::new(
@@ -1472,6 +2495,27 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("empty"));
     }
 
+    #[test]
+    fn build_requests_serialize_browserstack_field_names() {
+        let devices = vec!["iPhone 14-16".to_string()];
+        let xcuitest = serde_json::to_value(XcuitestBuildRequest::new(
+            Some("bench".into()),
+            &devices,
+            "bs://app",
+            "bs://suite",
+        ))
+        .unwrap();
+        assert_eq!(xcuitest["testSuite"], "bs://suite");
+        assert_eq!(xcuitest["buildName"], "bench");
+        assert!(xcuitest["only-testing"].is_array());
+
+        let espresso =
+            serde_json::to_value(BuildRequest::new(None, &devices, "bs://app", "bs://suite"))
+                .unwrap();
+        assert_eq!(espresso["disableAnimations"], true);
+        assert!(espresso.get("buildName").is_none());
+    }
+
     #[test]
     fn upload_xcuitest_app_rejects_missing_artifact() {
         let client = BrowserStackClient::new(
@@ -1678,6 +2722,35 @@ Test completed
         assert_eq!(session.session_id, "xyz789");
     }
 
+    #[test]
+    fn extract_device_meta_collects_hardware_and_thermal_hints() {
+        use serde_json::json;
+
+        let session = json!({
+            "automation_session": {
+                "name": "bench",
+                "device": "Google Pixel 7",
+                "os_version": "13.0",
+                "device_details": {
+                    "cpu": "Google Tensor G2",
+                    "ram_gb": 8,
+                    "battery_level": 87,
+                    "thermal_status": "nominal"
+                },
+                "logs": "https://example.com/logs"
+            }
+        });
+
+        let meta = extract_device_meta(&session);
+        assert_eq!(meta.get("os_version"), Some(&json!("13.0")));
+        assert_eq!(meta.get("device_details.cpu"), Some(&json!("Google Tensor G2")));
+        assert_eq!(meta.get("device_details.ram_gb"), Some(&json!(8)));
+        assert_eq!(meta.get("device_details.battery_level"), Some(&json!(87)));
+        assert_eq!(meta.get("device_details.thermal_status"), Some(&json!("nominal")));
+        assert!(!meta.contains_key("name"));
+        assert!(!meta.contains_key("logs"));
+    }
+
     #[test]
     fn extract_performance_metrics_finds_memory_and_cpu() {
         let client = BrowserStackClient::new(
@@ -0,0 +1,150 @@
+//! Changed-benchmark selection for `--since <git-ref>`.
+//!
+//! On large repositories running every benchmark on every PR is wasteful.
+//! This module asks git which files changed since a ref and maps them onto
+//! the benchmarks found by [`detect_benchmarks_with_metadata`], using each
+//! benchmark's defining source file.
+//!
+//! ## Heuristic
+//!
+//! Within a benchmark crate:
+//!
+//! - A change to the file that defines a benchmark selects that benchmark.
+//! - A change to any other file under `src/` (a helper module), or to
+//!   `Cargo.toml` / `build.rs`, selects every benchmark in the crate, since the
+//!   helper may be used by any of them.
+//! - Changes outside `src/` (README, `tests/`, `benches/`) are ignored.
+//!
+//! Dependencies are not followed: a change in another workspace crate that a
+//! benchmark calls into does not select the benchmark. Pass a broader ref or
+//! drop `--since` when shared crates change.
+//!
+//! [`detect_benchmarks_with_metadata`]: mobench_sdk::codegen::detect_benchmarks_with_metadata
+
+use anyhow::{Context, Result, bail};
+use mobench_sdk::codegen::DetectedBenchmark;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Files changed since a git ref, as absolute paths.
+#[derive(Debug, Clone)]
+pub(crate) struct ChangedFiles {
+    pub(crate) since: String,
+    files: BTreeSet<PathBuf>,
+}
+
+impl ChangedFiles {
+    /// Collect files changed between the merge base of `git_ref` and `HEAD`,
+    /// plus uncommitted changes in the working tree.
+    pub(crate) fn since(repo: &Path, git_ref: &str) -> Result<Self> {
+        let toplevel = git(repo, &["rev-parse", "--show-toplevel"])?;
+        let toplevel = canonical(Path::new(toplevel.trim()));
+        let range = format!("{}...HEAD", git_ref);
+        let mut files = BTreeSet::new();
+        for args in [
+            &["diff", "--name-only", range.as_str()][..],
+            &["diff", "--name-only", "HEAD"][..],
+            &["ls-files", "--others", "--exclude-standard"][..],
+        ] {
+            let output = git(&toplevel, args)?;
+            files.extend(
+                output
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(|line| toplevel.join(line)),
+            );
+        }
+        Ok(Self {
+            since: git_ref.to_string(),
+            files,
+        })
+    }
+
+    /// Benchmarks from the crate at `crate_dir` affected by these changes.
+    pub(crate) fn affected<'a>(
+        &self,
+        crate_dir: &Path,
+        benchmarks: &'a [DetectedBenchmark],
+    ) -> Vec<&'a DetectedBenchmark> {
+        let crate_dir = canonical(crate_dir);
+        let src_dir = crate_dir.join("src");
+        let defining: BTreeSet<PathBuf> = benchmarks
+            .iter()
+            .map(|bench| canonical(&bench.source_file))
+            .collect();
+
+        let crate_wide = self.files.iter().any(|file| {
+            (file.starts_with(&src_dir) && !defining.contains(file))
+                || *file == crate_dir.join("Cargo.toml")
+                || *file == crate_dir.join("build.rs")
+        });
+        if crate_wide {
+            return benchmarks.iter().collect();
+        }
+        benchmarks
+            .iter()
+            .filter(|bench| self.files.contains(&canonical(&bench.source_file)))
+            .collect()
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("running git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Resolve symlinks so git's paths and scanned paths compare equal; deleted
+/// files keep their path as given.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mobench_sdk::codegen::detect_benchmarks_with_metadata;
+
+    #[test]
+    fn selects_benchmarks_by_defining_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let crate_dir = canonical(temp.path());
+        fs::create_dir_all(crate_dir.join("src")).unwrap();
+        fs::write(
+            crate_dir.join("src/lib.rs"),
+            "mod hashing;\nmod util;\n#[benchmark]\npub fn sort() {}\n",
+        )
+        .unwrap();
+        fs::write(crate_dir.join("src/hashing.rs"), "#[benchmark]\npub fn sha() {}\n").unwrap();
+        fs::write(crate_dir.join("src/util.rs"), "pub fn helper() {}\n").unwrap();
+        let benchmarks = detect_benchmarks_with_metadata(&crate_dir, "my_crate");
+        let changed = |paths: &[&str]| ChangedFiles {
+            since: "main".into(),
+            files: paths.iter().map(|p| crate_dir.join(p)).collect(),
+        };
+        let names = |changes: &ChangedFiles| -> Vec<String> {
+            changes
+                .affected(&crate_dir, &benchmarks)
+                .iter()
+                .map(|bench| bench.name.clone())
+                .collect()
+        };
+
+        assert_eq!(names(&changed(&["src/hashing.rs"])), vec!["my_crate::hashing::sha"]);
+        assert_eq!(names(&changed(&["README.md", "tests/it.rs"])), Vec::<String>::new());
+        assert_eq!(names(&changed(&["src/util.rs"])).len(), 2);
+        assert_eq!(names(&changed(&["Cargo.toml"])).len(), 2);
+    }
+}
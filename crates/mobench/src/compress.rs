@@ -0,0 +1,122 @@
+//! Optional zstd compression of fetched artifacts.
+//!
+//! BrowserStack device logs and session JSON add up quickly for large device
+//! matrices. With `--compress`, [`write`] stores them as `<name>.zst`;
+//! [`read`] accepts either the plain path or its compressed sibling, so
+//! readers such as `fetch --extract-only` and `summary` work with both.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File extension appended to compressed files.
+pub(crate) const EXTENSION: &str = "zst";
+
+/// zstd level; favors speed, since artifacts are written once per fetch.
+const LEVEL: i32 = 3;
+
+/// `path` with [`EXTENSION`] appended, e.g. `session.json.zst`.
+pub(crate) fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Whether `path` names a compressed file.
+pub(crate) fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == EXTENSION)
+}
+
+/// File name of `path` without a trailing [`EXTENSION`], e.g.
+/// `device_log.log` for `session-1/device_log.log.zst`.
+pub(crate) fn logical_file_name(path: &Path) -> &Path {
+    let name = if is_compressed(path) {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+    Path::new(name.unwrap_or_default())
+}
+
+/// Write `contents` to `path`, or zstd-compressed to `<path>.zst` when
+/// `compress` is set. A leftover file in the other format is removed so
+/// readers never see two versions. Returns the path written.
+pub(crate) fn write(path: &Path, contents: &[u8], compress: bool) -> Result<PathBuf> {
+    let (dest, other) = if compress {
+        (compressed_path(path), path.to_path_buf())
+    } else {
+        (path.to_path_buf(), compressed_path(path))
+    };
+    if compress {
+        let encoded = zstd::encode_all(contents, LEVEL)
+            .with_context(|| format!("compressing {:?}", path))?;
+        fs::write(&dest, encoded).with_context(|| format!("writing {:?}", dest))?;
+    } else {
+        fs::write(&dest, contents).with_context(|| format!("writing {:?}", dest))?;
+    }
+    if other.exists() {
+        fs::remove_file(&other).with_context(|| format!("removing {:?}", other))?;
+    }
+    Ok(dest)
+}
+
+/// Compress an existing file in place, replacing it with `<path>.zst`.
+pub(crate) fn compress_file(path: &Path) -> Result<PathBuf> {
+    let contents = fs::read(path).with_context(|| format!("reading {:?}", path))?;
+    write(path, &contents, true)
+}
+
+/// Read `path`, decompressing it if it ends in `.zst`. When `path` does not
+/// exist but `<path>.zst` does, the compressed sibling is read instead.
+pub(crate) fn read(path: &Path) -> Result<Vec<u8>> {
+    let compressed = compressed_path(path);
+    let source = if !path.exists() && compressed.exists() {
+        compressed.as_path()
+    } else {
+        path
+    };
+    let contents = fs::read(source).with_context(|| format!("reading {:?}", source))?;
+    if is_compressed(source) {
+        zstd::decode_all(contents.as_slice()).with_context(|| format!("decompressing {:?}", source))
+    } else {
+        Ok(contents)
+    }
+}
+
+/// [`read`] as UTF-8 text.
+pub(crate) fn read_to_string(path: &Path) -> Result<String> {
+    let contents = read(path)?;
+    String::from_utf8(contents).with_context(|| format!("{:?} is not valid UTF-8", path))
+}
+
+/// Whether `path` or its compressed sibling exists.
+pub(crate) fn exists(path: &Path) -> bool {
+    path.exists() || compressed_path(path).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_files_read_back_through_the_plain_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let body = br#"{"status":"done"}"#.repeat(100);
+
+        let written = write(&path, &body, true).unwrap();
+        assert_eq!(written, dir.path().join("session.json.zst"));
+        assert!(!path.exists());
+        assert!(fs::metadata(&written).unwrap().len() < body.len() as u64);
+        assert!(exists(&path));
+        assert_eq!(read(&path).unwrap(), body);
+        assert_eq!(read(&written).unwrap(), body);
+        assert_eq!(logical_file_name(&written), Path::new("session.json"));
+
+        // Rewriting uncompressed replaces the compressed copy
+        write(&path, b"{}", false).unwrap();
+        assert!(!written.exists());
+        assert_eq!(read_to_string(&path).unwrap(), "{}");
+    }
+}
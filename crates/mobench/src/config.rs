@@ -23,17 +23,144 @@
 //!
 //! [ios]
 //! bundle_id = "com.example.bench"
+//! display_name = "Bench"
 //! deployment_target = "15.0"
 //!
+//! [ios.plist]
+//! UIRequiresFullScreen = true
+//!
 //! [benchmarks]
 //! default_function = "my_crate::my_benchmark"
 //! default_iterations = 100
 //! default_warmup = 10
+//!
+//! [build]
+//! max_lib_size_kb = 4096
+//!
+//! [budgets."my_crate::my_benchmark"]
+//! max_median_ms = 2.0
+//! max_p95_ms = 3.5
+//!
+//! [budgets."my_crate::my_benchmark".devices."Google Pixel 7"]
+//! max_median_ms = 1.5
+//!
+//! [backend]
+//! provider = "browserstack"
+//!
+//! [profiles.smoke]
+//! devices = ["Google Pixel 7-13.0"]
+//! iterations = 20
+//! warmup = 2
+//!
+//! [profiles.nightly]
+//! device_matrix = "device-matrix.yaml"
+//! device_tags = ["nightly"]
+//! iterations = 200
+//!
+//! [browserstack]
+//! username = "${BROWSERSTACK_USERNAME}"
+//! access_key = "${BROWSERSTACK_ACCESS_KEY}"
+//! project = "my-benchmarks"
+//!
+//! [env]
+//! MY_FEATURE_FLAG = "1"
 //! ```
-
-use anyhow::{Context, Result};
+//!
+//! ## Budgets
+//!
+//! The `[budgets]` section sets hard limits per benchmark function. `mobench
+//! run` and `mobench summary` fail when a device's median or p95 exceeds its
+//! budget, without needing a baseline. Limits can be given in `_ns` or `_ms`
+//! (nanoseconds win if both are set), and per-device overrides under
+//! `devices` replace the function-wide limit for that metric. A device key
+//! matches the exact device name or a name followed by `-<os version>`, so
+//! `"Google Pixel 7"` covers `"Google Pixel 7-13.0"`.
+//!
+//! ## Library Size Budget
+//!
+//! `[build] max_lib_size_kb` fails `mobench build` (and the build step of
+//! `mobench run`) when any ABI's `.so` or any iOS target's `.a` is larger
+//! than the limit. The stripped size is checked when `llvm-strip` / Xcode's
+//! `strip` is available, since that is what ships; verbose builds print both
+//! sizes. This catches debug symbols shipped in release builds early.
+//!
+//! ## iOS App Identity
+//!
+//! `[ios] bundle_id` and `display_name` are templated into the generated
+//! Xcode project, and every `[ios.plist]` entry is added to the app's
+//! Info.plist (overriding the template's keys of the same name). Use a
+//! unique bundle id when several teams share one Apple account or device
+//! cloud. Without a bundle id the app is `dev.world.<crate>.BenchRunner`.
+//! The bundle id must be reverse-DNS with only letters, digits, `-` and `.`.
+//!
+//! ## Backend
+//!
+//! `[backend] provider` selects where `mobench run` executes benchmarks:
+//! `"browserstack"` (the default) or `"local"` for devices attached to this
+//! machine over `adb` / `ios-deploy`. `--devices local` selects the local
+//! backend for a single run.
+//!
+//! ## Profiles
+//!
+//! `[profiles.<name>]` sections are named run configurations selected with
+//! `mobench run --profile <name>`. A profile sets any of `devices`,
+//! `device_matrix` with `device_tags` (devices from the matrix carrying one
+//! of the tags; every matrix device without tags), `iterations` and `warmup`.
+//! Its values replace the base `[benchmarks]` defaults, and CLI flags win
+//! over both. `device_matrix` is relative to `mobench.toml`. Selecting a
+//! profile that is not defined is an error.
+//!
+//! ## Benchmark Environment
+//!
+//! `[env]` entries are written to `bench_spec.json` and set with `setenv` by
+//! the Android and iOS runners before the benchmarks start, so benchmark code
+//! can read them with `std::env::var`. `mobench run --env KEY=VALUE` adds to
+//! or replaces them for one run. Keys are letters, digits and `_`, not
+//! starting with a digit; `MOBENCH_*` and variables the process or dynamic
+//! loader relies on (`PATH`, `HOME`, `TMPDIR`, `LD_*`, `DYLD_*`) are
+//! reserved. The variables used are recorded in the run summary's spec and
+//! echoed as `env` in every device report.
+//!
+//! ## Environment Overrides
+//!
+//! Every scalar field can be overridden with a `MOBENCH_<SECTION>_<KEY>`
+//! environment variable, so CI can adjust settings without editing the file:
+//!
+//! | Variable | Field |
+//! |----------|-------|
+//! | `MOBENCH_PROJECT_CRATE_NAME` | `project.crate` |
+//! | `MOBENCH_PROJECT_LIBRARY_NAME` | `project.library_name` |
+//! | `MOBENCH_PROJECT_OUTPUT_DIR` | `project.output_dir` |
+//! | `MOBENCH_ANDROID_PACKAGE` | `android.package` |
+//! | `MOBENCH_ANDROID_MIN_SDK` | `android.min_sdk` |
+//! | `MOBENCH_ANDROID_TARGET_SDK` | `android.target_sdk` |
+//! | `MOBENCH_ANDROID_ABIS` | `android.abis` (comma-separated) |
+//! | `MOBENCH_IOS_BUNDLE_ID` | `ios.bundle_id` |
+//! | `MOBENCH_IOS_DISPLAY_NAME` | `ios.display_name` |
+//! | `MOBENCH_IOS_DEPLOYMENT_TARGET` | `ios.deployment_target` |
+//! | `MOBENCH_IOS_TEAM_ID` | `ios.team_id` |
+//! | `MOBENCH_BENCHMARKS_DEFAULT_FUNCTION` | `benchmarks.default_function` |
+//! | `MOBENCH_BENCHMARKS_DEFAULT_ITERATIONS` | `benchmarks.default_iterations` |
+//! | `MOBENCH_BENCHMARKS_DEFAULT_WARMUP` | `benchmarks.default_warmup` |
+//! | `MOBENCH_BUILD_MAX_LIB_SIZE_KB` | `build.max_lib_size_kb` |
+//! | `MOBENCH_BACKEND_PROVIDER` | `backend.provider` |
+//! | `MOBENCH_BROWSERSTACK_USERNAME` | `browserstack.username` |
+//! | `MOBENCH_BROWSERSTACK_ACCESS_KEY` | `browserstack.access_key` |
+//! | `MOBENCH_BROWSERSTACK_PROJECT` | `browserstack.project` |
+//! | `MOBENCH_BROWSERSTACK_DEVICE_CACHE_TTL_SECS` | `browserstack.device_cache_ttl_secs` |
+//!
+//! Budgets, profiles, `[env]` and `[ios.plist]` entries are keyed by name and can
+//! only be set in the file. Empty
+//! variables are ignored. Precedence, highest first: CLI flag, environment
+//! variable, `mobench.toml`, built-in default. The legacy
+//! `BROWSERSTACK_USERNAME` / `BROWSERSTACK_ACCESS_KEY` / `BROWSERSTACK_PROJECT`
+//! variables still apply when nothing else sets the credentials.
+
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 /// The default configuration file name.
 pub const CONFIG_FILE_NAME: &str = "mobench.toml";
@@ -56,6 +183,108 @@ pub struct MobenchConfig {
 
     /// Benchmark execution defaults.
     pub benchmarks: BenchmarksConfig,
+
+    /// Build checks.
+    #[serde(skip_serializing_if = "BuildSettings::is_empty")]
+    pub build: BuildSettings,
+
+    /// Performance budgets keyed by benchmark function.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub budgets: BTreeMap<String, FunctionBudget>,
+
+    /// Device backend used by `mobench run`.
+    pub backend: BackendConfig,
+
+    /// Named run configurations for `mobench run --profile`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, RunProfile>,
+
+    /// BrowserStack account defaults.
+    #[serde(skip_serializing_if = "BrowserStackSettings::is_empty")]
+    pub browserstack: BrowserStackSettings,
+
+    /// Environment variables set on the device before the benchmarks run.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// BrowserStack account defaults.
+///
+/// Values may be `${VAR}` references. A `--config` run file's
+/// `[browserstack]` section takes precedence over these.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserStackSettings {
+    /// App Automate username.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// App Automate access key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_key: Option<String>,
+
+    /// BrowserStack project name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+
+    /// Seconds a cached device list stays fresh (default: 3600; 0 disables
+    /// the cache).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_cache_ttl_secs: Option<u64>,
+}
+
+impl BrowserStackSettings {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Build checks applied by `mobench build`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildSettings {
+    /// Largest allowed native library size in kilobytes, per ABI or target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_lib_size_kb: Option<u64>,
+}
+
+impl BuildSettings {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Device backend configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendConfig {
+    /// Where benchmarks run.
+    ///
+    /// Defaults to BrowserStack.
+    pub provider: BackendProvider,
+}
+
+/// Where `mobench run` executes benchmarks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendProvider {
+    /// BrowserStack App Automate device cloud.
+    #[default]
+    BrowserStack,
+    /// Devices attached to this machine, driven via `adb` / `ios-deploy`.
+    Local,
+}
+
+impl FromStr for BackendProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "browserstack" => Ok(Self::BrowserStack),
+            "local" => Ok(Self::Local),
+            other => bail!("unknown backend provider {:?}; expected \"browserstack\" or \"local\"", other),
+        }
+    }
 }
 
 /// Project-level configuration.
@@ -121,10 +350,13 @@ impl Default for AndroidConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct IosConfig {
-    /// iOS bundle identifier (e.g., "com.example.bench").
+    /// iOS bundle identifier of the benchmark app (e.g., "com.example.bench").
     ///
-    /// Defaults to "dev.world.bench" if not specified.
-    pub bundle_id: String,
+    /// Defaults to `dev.world.<crate>.BenchRunner` if not specified.
+    pub bundle_id: Option<String>,
+
+    /// App name shown under the icon (`CFBundleDisplayName`).
+    pub display_name: Option<String>,
 
     /// iOS deployment target version.
     ///
@@ -135,14 +367,20 @@ pub struct IosConfig {
     ///
     /// If not specified, ad-hoc signing is used.
     pub team_id: Option<String>,
+
+    /// Extra Info.plist entries for the benchmark app.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub plist: BTreeMap<String, serde_json::Value>,
 }
 
 impl Default for IosConfig {
     fn default() -> Self {
         Self {
-            bundle_id: "dev.world.bench".to_string(),
+            bundle_id: None,
+            display_name: None,
             deployment_target: "15.0".to_string(),
             team_id: None,
+            plist: BTreeMap::new(),
         }
     }
 }
@@ -177,6 +415,112 @@ impl Default for BenchmarksConfig {
     }
 }
 
+/// Upper bounds on a benchmark's timing statistics.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetLimits {
+    /// Maximum median in nanoseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_median_ns: Option<u64>,
+
+    /// Maximum median in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_median_ms: Option<f64>,
+
+    /// Maximum p95 in nanoseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_p95_ns: Option<u64>,
+
+    /// Maximum p95 in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_p95_ms: Option<f64>,
+}
+
+impl BudgetLimits {
+    /// Maximum median in nanoseconds, if any.
+    pub fn median_ns(&self) -> Option<u64> {
+        self.max_median_ns.or(self.max_median_ms.map(ms_to_ns))
+    }
+
+    /// Maximum p95 in nanoseconds, if any.
+    pub fn p95_ns(&self) -> Option<u64> {
+        self.max_p95_ns.or(self.max_p95_ms.map(ms_to_ns))
+    }
+}
+
+fn ms_to_ns(ms: f64) -> u64 {
+    (ms * 1_000_000.0).round() as u64
+}
+
+/// Budget for one benchmark function, with optional per-device overrides.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FunctionBudget {
+    /// Limits applied on every device.
+    #[serde(flatten)]
+    pub limits: BudgetLimits,
+
+    /// Per-device limits, keyed by device name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub devices: BTreeMap<String, BudgetLimits>,
+}
+
+impl FunctionBudget {
+    /// Effective `(max_median_ns, max_p95_ns)` for `device`.
+    ///
+    /// A matching device override wins per metric; metrics it leaves unset
+    /// fall back to the function-wide limits.
+    pub fn limits_for(&self, device: &str) -> (Option<u64>, Option<u64>) {
+        let device_limits = self
+            .devices
+            .iter()
+            .find(|(key, _)| device_matches(key, device))
+            .map(|(_, limits)| limits);
+        let median = device_limits
+            .and_then(BudgetLimits::median_ns)
+            .or_else(|| self.limits.median_ns());
+        let p95 = device_limits
+            .and_then(BudgetLimits::p95_ns)
+            .or_else(|| self.limits.p95_ns());
+        (median, p95)
+    }
+}
+
+/// A named run configuration from `[profiles.<name>]`.
+///
+/// Unset fields fall back to the base config; CLI flags override all of them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunProfile {
+    /// Devices to run on.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<String>,
+
+    /// Device matrix to add devices from, relative to `mobench.toml`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_matrix: Option<PathBuf>,
+
+    /// Only add matrix devices carrying one of these tags.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub device_tags: Vec<String>,
+
+    /// Benchmark iterations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<u32>,
+
+    /// Warmup iterations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<u32>,
+}
+
+/// Whether budget key `key` refers to `device` (`"Pixel 7"` matches `"Pixel 7-13.0"`).
+fn device_matches(key: &str, device: &str) -> bool {
+    device == key
+        || device
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
 impl MobenchConfig {
     /// Creates a new configuration with default values.
     pub fn new() -> Self {
@@ -271,6 +615,82 @@ impl MobenchConfig {
         Ok(())
     }
 
+    /// Applies `MOBENCH_<SECTION>_<KEY>` overrides read through `lookup`.
+    ///
+    /// Returns whether any variable was set. See the module docs for the
+    /// variable names.
+    pub fn apply_env_overrides(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<bool> {
+        let var = |key: &str| lookup(key).filter(|value| !value.trim().is_empty());
+        let mut applied = false;
+        let mut set_string = |key: &str, field: &mut String| {
+            if let Some(value) = var(key) {
+                *field = value;
+                applied = true;
+            }
+        };
+        set_string("MOBENCH_ANDROID_PACKAGE", &mut self.android.package);
+        set_string("MOBENCH_IOS_DEPLOYMENT_TARGET", &mut self.ios.deployment_target);
+
+        let mut set_option = |key: &str, field: &mut Option<String>| {
+            if let Some(value) = var(key) {
+                *field = Some(value);
+                applied = true;
+            }
+        };
+        set_option("MOBENCH_PROJECT_CRATE_NAME", &mut self.project.crate_name);
+        set_option("MOBENCH_PROJECT_LIBRARY_NAME", &mut self.project.library_name);
+        set_option("MOBENCH_IOS_BUNDLE_ID", &mut self.ios.bundle_id);
+        set_option("MOBENCH_IOS_DISPLAY_NAME", &mut self.ios.display_name);
+        set_option("MOBENCH_IOS_TEAM_ID", &mut self.ios.team_id);
+        set_option("MOBENCH_BENCHMARKS_DEFAULT_FUNCTION", &mut self.benchmarks.default_function);
+        set_option("MOBENCH_BROWSERSTACK_USERNAME", &mut self.browserstack.username);
+        set_option("MOBENCH_BROWSERSTACK_ACCESS_KEY", &mut self.browserstack.access_key);
+        set_option("MOBENCH_BROWSERSTACK_PROJECT", &mut self.browserstack.project);
+
+        if let Some(value) = var("MOBENCH_PROJECT_OUTPUT_DIR") {
+            self.project.output_dir = Some(PathBuf::from(value));
+            applied = true;
+        }
+        if let Some(value) = var("MOBENCH_ANDROID_ABIS") {
+            self.android.abis = Some(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|abi| !abi.is_empty())
+                    .map(String::from)
+                    .collect(),
+            );
+            applied = true;
+        }
+
+        for (key, field) in [
+            ("MOBENCH_ANDROID_MIN_SDK", &mut self.android.min_sdk),
+            ("MOBENCH_ANDROID_TARGET_SDK", &mut self.android.target_sdk),
+            ("MOBENCH_BENCHMARKS_DEFAULT_ITERATIONS", &mut self.benchmarks.default_iterations),
+            ("MOBENCH_BENCHMARKS_DEFAULT_WARMUP", &mut self.benchmarks.default_warmup),
+        ] {
+            if let Some(value) = var(key) {
+                *field = parse_env(key, &value)?;
+                applied = true;
+            }
+        }
+        if let Some(value) = var("MOBENCH_BUILD_MAX_LIB_SIZE_KB") {
+            self.build.max_lib_size_kb = Some(parse_env("MOBENCH_BUILD_MAX_LIB_SIZE_KB", &value)?);
+            applied = true;
+        }
+        if let Some(value) = var("MOBENCH_BROWSERSTACK_DEVICE_CACHE_TTL_SECS") {
+            self.browserstack.device_cache_ttl_secs =
+                Some(parse_env("MOBENCH_BROWSERSTACK_DEVICE_CACHE_TTL_SECS", &value)?);
+            applied = true;
+        }
+        if let Some(value) = var("MOBENCH_BACKEND_PROVIDER") {
+            self.backend.provider = parse_env("MOBENCH_BACKEND_PROVIDER", &value)?;
+            applied = true;
+        }
+
+        Ok(applied)
+    }
+
     /// Returns the library name, either from config or derived from crate name.
     pub fn library_name(&self) -> Option<String> {
         self.project
@@ -281,6 +701,9 @@ impl MobenchConfig {
 
     /// Generates a starter configuration with sensible defaults.
     ///
+    /// The Android package and iOS bundle id are `dev.world.<crate>`, with
+    /// the crate name reduced to ASCII letters and digits so both are valid.
+    ///
     /// # Arguments
     ///
     /// * `crate_name` - Name of the benchmark crate
@@ -290,7 +713,7 @@ impl MobenchConfig {
     /// A new `MobenchConfig` with the provided crate name and default values.
     pub fn starter(crate_name: &str) -> Self {
         let library_name = crate_name.replace('-', "_");
-        let package = format!("dev.world.{}", library_name.replace('_', ""));
+        let package = starter_package(crate_name);
 
         Self {
             project: ProjectConfig {
@@ -305,15 +728,23 @@ impl MobenchConfig {
                 abis: None,
             },
             ios: IosConfig {
-                bundle_id: package,
+                bundle_id: Some(package),
+                display_name: None,
                 deployment_target: "15.0".to_string(),
                 team_id: None,
+                plist: BTreeMap::new(),
             },
             benchmarks: BenchmarksConfig {
                 default_function: Some(format!("{}::my_benchmark", library_name)),
                 default_iterations: 100,
                 default_warmup: 10,
             },
+            build: BuildSettings::default(),
+            budgets: BTreeMap::new(),
+            backend: BackendConfig::default(),
+            profiles: BTreeMap::new(),
+            browserstack: BrowserStackSettings::default(),
+            env: BTreeMap::new(),
         }
     }
 
@@ -330,7 +761,7 @@ impl MobenchConfig {
     /// A formatted TOML string suitable for writing to `mobench.toml`.
     pub fn generate_starter_toml(crate_name: &str) -> String {
         let library_name = crate_name.replace('-', "_");
-        let package = format!("dev.world.{}", library_name.replace('_', ""));
+        let package = starter_package(crate_name);
 
         format!(
             r#"# mobench configuration file
@@ -361,15 +792,22 @@ target_sdk = 34
 # abis = ["arm64-v8a", "armeabi-v7a", "x86_64"]
 
 [ios]
-# iOS bundle identifier
+# iOS bundle identifier of the benchmark app; must be unique per Apple account
 bundle_id = "{package}"
 
+# App name shown under the icon (optional)
+# display_name = "Bench"
+
 # iOS deployment target version (default: 15.0)
 deployment_target = "15.0"
 
 # Development team ID for code signing (optional, uses ad-hoc signing if not set)
 # team_id = "YOUR_TEAM_ID"
 
+# Extra Info.plist entries for the benchmark app (optional)
+# [ios.plist]
+# UIRequiresFullScreen = true
+
 [benchmarks]
 # Default benchmark function to run
 default_function = "{library_name}::my_benchmark"
@@ -379,6 +817,47 @@ default_iterations = 100
 
 # Default number of warmup iterations (can be overridden with --warmup)
 default_warmup = 10
+
+# Fail the build when a native library (stripped .so / .a) exceeds this size
+# [build]
+# max_lib_size_kb = 4096
+
+# Performance budgets: `run` and `summary` fail when a device exceeds them
+# [budgets."{library_name}::my_benchmark"]
+# max_median_ms = 2.0
+# max_p95_ms = 3.5
+#
+# [budgets."{library_name}::my_benchmark".devices."Google Pixel 7"]
+# max_median_ms = 1.5
+
+[backend]
+# Where `run` executes benchmarks: "browserstack" or "local" (adb / ios-deploy)
+provider = "browserstack"
+
+# Named run configurations, selected with `cargo mobench run --profile <name>`
+# [profiles.smoke]
+# devices = ["Google Pixel 7-13.0"]
+# iterations = 20
+# warmup = 2
+#
+# [profiles.nightly]
+# device_matrix = "device-matrix.yaml"
+# device_tags = ["nightly"]
+# iterations = 200
+
+# BrowserStack account (optional; BROWSERSTACK_USERNAME / BROWSERSTACK_ACCESS_KEY are used otherwise)
+# [browserstack]
+# username = "${{BROWSERSTACK_USERNAME}}"
+# access_key = "${{BROWSERSTACK_ACCESS_KEY}}"
+# project = "{crate_name}-benchmarks"
+
+# Environment variables set on the device before the benchmarks run
+# (`cargo mobench run --env KEY=VALUE` adds to or overrides them)
+# [env]
+# MY_FEATURE_FLAG = "1"
+
+# Any field above can be overridden with MOBENCH_<SECTION>_<KEY> environment
+# variables, e.g. MOBENCH_PROJECT_OUTPUT_DIR or MOBENCH_BROWSERSTACK_PROJECT.
 "#,
             crate_name = crate_name,
             library_name = library_name,
@@ -387,9 +866,26 @@ default_warmup = 10
     }
 }
 
+/// Reverse-DNS placeholder for the starter Android package and iOS bundle id.
+///
+/// Keeps only the ASCII letters and digits of `crate_name`, since iOS
+/// rejects `_` and Android segments must start with a letter.
+fn starter_package(crate_name: &str) -> String {
+    let mut segment: String = crate_name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if !segment.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        segment.insert_str(0, "bench");
+    }
+    format!("dev.world.{}", segment)
+}
+
 /// Configuration resolver that merges config file values with CLI arguments.
 ///
-/// CLI arguments always take precedence over config file values.
+/// CLI arguments take precedence over `MOBENCH_*` environment variables,
+/// which take precedence over config file values.
 #[derive(Debug, Default)]
 pub struct ConfigResolver {
     /// Loaded configuration, if any.
@@ -405,16 +901,29 @@ impl ConfigResolver {
     /// If no config file is found, the resolver will use default values
     /// which can be overridden by CLI arguments.
     pub fn new() -> Result<Self> {
-        match MobenchConfig::discover()? {
-            Some((config, path)) => Ok(Self {
-                config: Some(config),
-                config_path: Some(path),
-            }),
-            None => Ok(Self {
-                config: None,
-                config_path: None,
-            }),
-        }
+        Self::with_env(MobenchConfig::discover()?, |key| std::env::var(key).ok())
+    }
+
+    /// Creates a resolver from an already-discovered config, applying
+    /// environment overrides read through `lookup`.
+    ///
+    /// Without a config file, the resolver only holds a config when some
+    /// override is set.
+    pub fn with_env(
+        discovered: Option<(MobenchConfig, PathBuf)>,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let (mut config, config_path) = match discovered {
+            Some((config, path)) => (config, Some(path)),
+            None => (MobenchConfig::default(), None),
+        };
+        let overridden = config
+            .apply_env_overrides(lookup)
+            .context("applying MOBENCH_* environment overrides")?;
+        Ok(Self {
+            config: (config_path.is_some() || overridden).then_some(config),
+            config_path,
+        })
     }
 
     /// Returns the crate name from config, or None if not configured.
@@ -475,6 +984,94 @@ impl ConfigResolver {
             .unwrap_or_default()
     }
 
+    /// Returns the validated `[ios]` app identity for the generated Xcode project.
+    pub fn ios_app_options(&self) -> Result<mobench_sdk::codegen::IosAppOptions> {
+        let ios = self.ios();
+        let options = mobench_sdk::codegen::IosAppOptions {
+            bundle_id: ios.bundle_id,
+            display_name: ios.display_name,
+            plist: ios.plist,
+        };
+        options.validate().context("invalid [ios] settings")?;
+        Ok(options)
+    }
+
+    /// Returns the native library size budget from `[build]`, in kilobytes.
+    pub fn max_lib_size_kb(&self) -> Option<u64> {
+        self.config.as_ref().and_then(|c| c.build.max_lib_size_kb)
+    }
+
+    /// Returns the configured performance budgets.
+    pub fn budgets(&self) -> BTreeMap<String, FunctionBudget> {
+        self.config
+            .as_ref()
+            .map(|c| c.budgets.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the `[env]` variables for the device run.
+    pub fn env(&self) -> BTreeMap<String, String> {
+        self.config
+            .as_ref()
+            .map(|c| c.env.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the BrowserStack account defaults.
+    pub fn browserstack(&self) -> BrowserStackSettings {
+        self.config
+            .as_ref()
+            .map(|c| c.browserstack.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the `[profiles.<name>]` run configuration, with its
+    /// `device_matrix` resolved against the directory of `mobench.toml`.
+    pub fn profile(&self, name: &str) -> Result<RunProfile> {
+        let profiles = self.config.as_ref().map(|c| &c.profiles);
+        let Some(profile) = profiles.and_then(|profiles| profiles.get(name)) else {
+            let available: Vec<&str> = profiles
+                .into_iter()
+                .flat_map(|profiles| profiles.keys().map(String::as_str))
+                .collect();
+            match &self.config_path {
+                Some(path) if !available.is_empty() => bail!(
+                    "profile '{}' is not defined in {}; available profiles: {}",
+                    name,
+                    path.display(),
+                    available.join(", ")
+                ),
+                Some(path) => bail!(
+                    "profile '{}' is not defined in {}; add a [profiles.{}] section",
+                    name,
+                    path.display(),
+                    name
+                ),
+                None => bail!(
+                    "profile '{}' requested but no {} was found",
+                    name,
+                    CONFIG_FILE_NAME
+                ),
+            }
+        };
+        let mut profile = profile.clone();
+        if let (Some(matrix), Some(dir)) = (
+            profile.device_matrix.as_mut(),
+            self.config_path.as_deref().and_then(Path::parent),
+        ) {
+            *matrix = dir.join(&*matrix);
+        }
+        Ok(profile)
+    }
+
+    /// Returns the configured device backend.
+    pub fn backend_provider(&self) -> BackendProvider {
+        self.config
+            .as_ref()
+            .map(|c| c.backend.provider)
+            .unwrap_or_default()
+    }
+
     /// Resolves a CLI value, using config as fallback.
     ///
     /// # Arguments
@@ -496,6 +1093,17 @@ impl ConfigResolver {
     }
 }
 
+fn parse_env<T>(key: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|err| anyhow::anyhow!("invalid {}={:?}: {}", key, value, err))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -518,7 +1126,7 @@ mod tests {
         assert_eq!(config.project.crate_name, Some("my-bench".to_string()));
         assert_eq!(config.project.library_name, Some("my_bench".to_string()));
         assert_eq!(config.android.package, "dev.world.mybench");
-        assert_eq!(config.ios.bundle_id, "dev.world.mybench");
+        assert_eq!(config.ios.bundle_id.as_deref(), Some("dev.world.mybench"));
     }
 
     #[test]
@@ -544,6 +1152,9 @@ deployment_target = "14.0"
 default_function = "test_bench::test_fn"
 default_iterations = 50
 default_warmup = 5
+
+[env]
+BENCH_INPUT = "large"
 "#;
 
         let mut file = std::fs::File::create(&config_path).unwrap();
@@ -556,7 +1167,7 @@ default_warmup = 5
         assert_eq!(config.android.package, "com.test.bench");
         assert_eq!(config.android.min_sdk, 21);
         assert_eq!(config.android.target_sdk, 33);
-        assert_eq!(config.ios.bundle_id, "com.test.bench");
+        assert_eq!(config.ios.bundle_id.as_deref(), Some("com.test.bench"));
         assert_eq!(config.ios.deployment_target, "14.0");
         assert_eq!(
             config.benchmarks.default_function,
@@ -564,6 +1175,29 @@ default_warmup = 5
         );
         assert_eq!(config.benchmarks.default_iterations, 50);
         assert_eq!(config.benchmarks.default_warmup, 5);
+        assert_eq!(config.env["BENCH_INPUT"], "large");
+    }
+
+    #[test]
+    fn test_budgets() {
+        let toml_content = r#"
+[budgets."bench::checksum"]
+max_median_ms = 2.0
+max_p95_ns = 3000000
+
+[budgets."bench::checksum".devices."Google Pixel 7"]
+max_median_ns = 1500000
+"#;
+        let config: MobenchConfig = toml::from_str(toml_content).unwrap();
+        let budget = &config.budgets["bench::checksum"];
+
+        assert_eq!(budget.limits_for("iPhone 14-16"), (Some(2_000_000), Some(3_000_000)));
+        assert_eq!(
+            budget.limits_for("Google Pixel 7-13.0"),
+            (Some(1_500_000), Some(3_000_000))
+        );
+        // Only whole names match, not prefixes of a longer model
+        assert_eq!(budget.limits_for("Google Pixel 7 Pro-13.0").0, Some(2_000_000));
     }
 
     #[test]
@@ -616,6 +1250,154 @@ crate = "discovered-bench"
         assert_eq!(result, 100);
     }
 
+    #[test]
+    fn test_profiles() {
+        let config: MobenchConfig = toml::from_str(
+            r#"
+[profiles.smoke]
+devices = ["Google Pixel 7-13.0"]
+iterations = 20
+
+[profiles.nightly]
+device_matrix = "matrices/devices.yaml"
+device_tags = ["nightly"]
+warmup = 5
+"#,
+        )
+        .unwrap();
+        let resolver = ConfigResolver {
+            config: Some(config),
+            config_path: Some(PathBuf::from("/repo/mobench.toml")),
+        };
+
+        let smoke = resolver.profile("smoke").unwrap();
+        assert_eq!(smoke.devices, vec!["Google Pixel 7-13.0"]);
+        assert_eq!((smoke.iterations, smoke.warmup), (Some(20), None));
+
+        let nightly = resolver.profile("nightly").unwrap();
+        assert_eq!(
+            nightly.device_matrix.as_deref(),
+            Some(Path::new("/repo/matrices/devices.yaml"))
+        );
+        assert_eq!(nightly.device_tags, vec!["nightly"]);
+
+        let err = resolver.profile("release").unwrap_err().to_string();
+        assert!(err.contains("available profiles: nightly, smoke"), "{err}");
+        let err = ConfigResolver::default().profile("smoke").unwrap_err().to_string();
+        assert!(err.contains("no mobench.toml was found"), "{err}");
+    }
+
+    #[test]
+    fn test_env_overrides_config_file() {
+        let file: MobenchConfig = toml::from_str(
+            r#"
+[project]
+crate = "file-bench"
+output_dir = "target/from-file"
+
+[benchmarks]
+default_iterations = 50
+
+[browserstack]
+username = "file-user"
+access_key = "file-key"
+"#,
+        )
+        .unwrap();
+        let env: BTreeMap<&str, &str> = [
+            ("MOBENCH_PROJECT_CRATE_NAME", "env-bench"),
+            ("MOBENCH_PROJECT_OUTPUT_DIR", "target/from-env"),
+            ("MOBENCH_BROWSERSTACK_ACCESS_KEY", "env-key"),
+            ("MOBENCH_BROWSERSTACK_PROJECT", "env-project"),
+            ("MOBENCH_BENCHMARKS_DEFAULT_WARMUP", "3"),
+            ("MOBENCH_IOS_TEAM_ID", ""),
+        ]
+        .into_iter()
+        .collect();
+        let lookup = |key: &str| env.get(key).map(|value| value.to_string());
+
+        let resolver =
+            ConfigResolver::with_env(Some((file, PathBuf::from("mobench.toml"))), lookup).unwrap();
+
+        // env > TOML
+        assert_eq!(resolver.crate_name(), Some("env-bench"));
+        assert_eq!(resolver.output_dir(), Some(Path::new("target/from-env")));
+        assert_eq!(resolver.default_warmup(), 3);
+        let browserstack = resolver.browserstack();
+        assert_eq!(browserstack.access_key.as_deref(), Some("env-key"));
+        assert_eq!(browserstack.project.as_deref(), Some("env-project"));
+        // TOML > default where no variable is set; empty variables are ignored
+        assert_eq!(browserstack.username.as_deref(), Some("file-user"));
+        assert_eq!(resolver.default_iterations(), 50);
+        assert_eq!(resolver.ios().team_id, None);
+        // CLI > env
+        let output_dir = resolver.resolve(
+            Some(PathBuf::from("cli-out")),
+            |c| c.project.output_dir.clone(),
+            PathBuf::from("target/mobench"),
+        );
+        assert_eq!(output_dir, PathBuf::from("cli-out"));
+    }
+
+    #[test]
+    fn test_ios_app_options() {
+        let config: MobenchConfig = toml::from_str(
+            r#"
+[ios]
+bundle_id = "com.acme.perf"
+display_name = "Acme Bench"
+
+[ios.plist]
+UIRequiresFullScreen = true
+LSApplicationCategoryType = "public.app-category.developer-tools"
+"#,
+        )
+        .unwrap();
+        let resolver = ConfigResolver::with_env(Some((config, PathBuf::from("mobench.toml"))), |_| None)
+            .unwrap();
+        let options = resolver.ios_app_options().unwrap();
+        assert_eq!(options.bundle_id.as_deref(), Some("com.acme.perf"));
+        assert_eq!(options.display_name.as_deref(), Some("Acme Bench"));
+        assert_eq!(options.plist["UIRequiresFullScreen"], serde_json::json!(true));
+
+        let resolver = ConfigResolver::with_env(None, |key| {
+            (key == "MOBENCH_IOS_BUNDLE_ID").then(|| "bench_app".to_string())
+        })
+        .unwrap();
+        let err = resolver.ios_app_options().unwrap_err();
+        assert!(format!("{err:#}").contains("invalid iOS bundle id 'bench_app'"));
+
+        // No settings keeps the stock project
+        let resolver = ConfigResolver::with_env(None, |_| None).unwrap();
+        assert!(resolver.ios_app_options().unwrap().is_default());
+    }
+
+    #[test]
+    fn test_env_overrides_without_config_file() {
+        let resolver = ConfigResolver::with_env(None, |_| None).unwrap();
+        assert!(resolver.config.is_none());
+
+        let resolver = ConfigResolver::with_env(None, |key| {
+            (key == "MOBENCH_BACKEND_PROVIDER").then(|| "local".to_string())
+        })
+        .unwrap();
+        assert_eq!(resolver.backend_provider(), BackendProvider::Local);
+        assert_eq!(resolver.default_iterations(), 100);
+        assert_eq!(resolver.max_lib_size_kb(), None);
+
+        let resolver = ConfigResolver::with_env(None, |key| {
+            (key == "MOBENCH_BUILD_MAX_LIB_SIZE_KB").then(|| "2048".to_string())
+        })
+        .unwrap();
+        assert_eq!(resolver.max_lib_size_kb(), Some(2048));
+
+        let err = ConfigResolver::with_env(None, |key| {
+            (key == "MOBENCH_ANDROID_MIN_SDK").then(|| "twenty".to_string())
+        })
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("MOBENCH_ANDROID_MIN_SDK"));
+    }
+
     #[test]
     fn test_generate_starter_toml() {
         let toml = MobenchConfig::generate_starter_toml("my-bench");
@@ -626,5 +1408,25 @@ crate = "discovered-bench"
         assert!(toml.contains("deployment_target = \"15.0\""));
         assert!(toml.contains("default_iterations = 100"));
         assert!(toml.contains("default_warmup = 10"));
+
+        let config: MobenchConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(config.backend.provider, BackendProvider::BrowserStack);
+        let local: MobenchConfig = toml::from_str("[backend]\nprovider = \"local\"\n").unwrap();
+        assert_eq!(local.backend.provider, BackendProvider::Local);
+
+        // Crate names that are not valid identifiers as-is still give a
+        // bundle id that passes validation
+        for (crate_name, package) in [
+            ("my_bench", "dev.world.mybench"),
+            ("My_Bench-2", "dev.world.mybench2"),
+            ("2fa_bench", "dev.world.bench2fabench"),
+            ("_", "dev.world.bench"),
+        ] {
+            let config: MobenchConfig =
+                toml::from_str(&MobenchConfig::generate_starter_toml(crate_name)).unwrap();
+            assert_eq!(config.android.package, package);
+            assert_eq!(config.ios.bundle_id.as_deref(), Some(package));
+            mobench_sdk::codegen::validate_bundle_id(package).unwrap();
+        }
     }
 }
@@ -0,0 +1,172 @@
+//! Per-user BrowserStack credentials outside the project.
+//!
+//! `~/.config/mobench/credentials.toml` (or `$XDG_CONFIG_HOME/mobench/...`,
+//! or the file named by `MOBENCH_CREDENTIALS_FILE`) holds one table per
+//! profile, so secrets need not be exported into every shell session:
+//!
+//! ```toml
+//! [default.browserstack]
+//! username = "alice"
+//! access_key = "..."
+//!
+//! [work.browserstack]
+//! username = "alice-work"
+//! access_key = "..."
+//! project = "crypto-benches"
+//! ```
+//!
+//! `MOBENCH_CREDENTIALS_PROFILE` selects the profile (default: `default`).
+//! With the `keychain` feature on macOS, a `mobench-browserstack` generic
+//! password in the login Keychain is consulted after the file.
+//!
+//! Both sources rank below the project config and the `BROWSERSTACK_*`
+//! environment variables; see `resolve_browserstack_credentials`.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Profile read when `MOBENCH_CREDENTIALS_PROFILE` is unset.
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+
+/// BrowserStack credentials from a credentials file profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct StoredBrowserStack {
+    pub(crate) username: Option<String>,
+    pub(crate) access_key: Option<String>,
+    pub(crate) project: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    #[serde(default)]
+    browserstack: StoredBrowserStack,
+}
+
+/// Default location of the credentials file, if a home directory is known.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("MOBENCH_CREDENTIALS_FILE").filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("mobench").join("credentials.toml"))
+}
+
+/// Profile selected by `MOBENCH_CREDENTIALS_PROFILE`.
+pub(crate) fn selected_profile() -> String {
+    env::var("MOBENCH_CREDENTIALS_PROFILE")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// Read `profile` from the credentials file at `path`.
+///
+/// A missing file yields empty credentials. A missing profile is an error
+/// only when it was chosen explicitly, i.e. is not [`DEFAULT_PROFILE`].
+pub(crate) fn load(path: &Path, profile: &str) -> Result<StoredBrowserStack> {
+    if !path.exists() {
+        return Ok(StoredBrowserStack::default());
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("reading credentials file {}", path.display()))?;
+    let mut profiles: BTreeMap<String, Profile> = toml::from_str(&contents)
+        .with_context(|| format!("parsing credentials file {}", path.display()))?;
+    match profiles.remove(profile) {
+        Some(found) => Ok(found.browserstack),
+        None if profile == DEFAULT_PROFILE => Ok(StoredBrowserStack::default()),
+        None => anyhow::bail!(
+            "credentials profile '{}' not found in {} (available: {})",
+            profile,
+            path.display(),
+            profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+/// Credentials from the selected profile of the default credentials file.
+pub(crate) fn load_default() -> Result<StoredBrowserStack> {
+    match default_path() {
+        Some(path) => load(&path, &selected_profile()),
+        None => Ok(StoredBrowserStack::default()),
+    }
+}
+
+/// Keychain service holding the access key, with the username as account.
+#[cfg(all(feature = "keychain", target_os = "macos"))]
+pub(crate) const KEYCHAIN_SERVICE: &str = "mobench-browserstack";
+
+/// Look up `(username, access_key)` in the login Keychain.
+///
+/// Add the entry with
+/// `security add-generic-password -s mobench-browserstack -a <username> -w`.
+#[cfg(all(feature = "keychain", target_os = "macos"))]
+pub(crate) fn keychain() -> Option<(String, String)> {
+    use std::process::Command;
+
+    let security = |args: &[&str]| -> Option<String> {
+        let output = Command::new("security").args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    // Without `-w` the item's attributes are printed, the account as `"acct"`
+    let attributes = security(&["find-generic-password", "-s", KEYCHAIN_SERVICE])?;
+    let username = attributes.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("\"acct\"<blob>=")?;
+        Some(value.trim_matches('"').to_string())
+    })?;
+    let access_key = security(&["find-generic-password", "-s", KEYCHAIN_SERVICE, "-w"])?;
+    let access_key = access_key.trim().to_string();
+    (!username.is_empty() && !access_key.is_empty()).then_some((username, access_key))
+}
+
+/// Keychain lookups need the `keychain` feature on macOS.
+#[cfg(not(all(feature = "keychain", target_os = "macos")))]
+pub(crate) fn keychain() -> Option<(String, String)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.toml");
+        assert_eq!(load(&path, "work").unwrap(), StoredBrowserStack::default());
+
+        fs::write(
+            &path,
+            r#"
+[default.browserstack]
+username = "alice"
+access_key = "secret"
+
+[work.browserstack]
+username = "alice-work"
+access_key = "work-secret"
+project = "crypto"
+"#,
+        )
+        .unwrap();
+        assert_eq!(load(&path, DEFAULT_PROFILE).unwrap().username.as_deref(), Some("alice"));
+        let work = load(&path, "work").unwrap();
+        assert_eq!(work.access_key.as_deref(), Some("work-secret"));
+        assert_eq!(work.project.as_deref(), Some("crypto"));
+
+        let err = load(&path, "ci").unwrap_err().to_string();
+        assert!(err.contains("'ci' not found"), "{err}");
+        assert!(err.contains("default, work"), "{err}");
+    }
+}
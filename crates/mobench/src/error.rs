@@ -0,0 +1,132 @@
+//! Structured errors for failures that wrappers need to tell apart.
+//!
+//! Most commands return `anyhow::Error`. The failures below are raised as a
+//! [`MobenchError`] inside that error, and [`exit_code`] maps them to a
+//! distinct process exit status so CI can branch on it:
+//!
+//! | Code | Variant | Raised when |
+//! |------|---------|-------------|
+//! | 1 | - | Any other error |
+//! | 2 | - | Invalid command-line arguments (reported by clap) |
+//! | 3 | `AuthFailed` | BrowserStack credentials are missing or rejected |
+//! | 4 | `DeviceInvalid` | A device spec or tag matches no available device |
+//! | 5 | `BuildFailed` | Building the Rust library or mobile app failed |
+//! | 6 | `ArtifactMissing` | A required APK, IPA or test suite is not on disk |
+//! | 7 | `UploadFailed` | BrowserStack rejected an artifact upload |
+//! | 8 | `Network` | The BrowserStack API could not be reached |
+//! | 9 | `ResultsUnavailable` | A device run failed, timed out or produced no report, or `--strict` found too few samples |
+//! | 10 | `ThresholdExceeded` | A regression, budget or suspicious-improvement gate tripped |
+//! | 11 | `StaleBuild` | `--fail-on-stale-build` found reports from another build |
+//! | 12 | `QuotaExceeded` | The BrowserStack account has no parallel sessions left |
+//!
+//! Errors not listed here stay plain `anyhow` errors and exit with 1.
+
+use std::fmt::Display;
+use thiserror::Error;
+
+/// A failure with its own process exit code.
+#[derive(Debug, Error)]
+pub enum MobenchError {
+    /// BrowserStack credentials are missing or were rejected (HTTP 401/403).
+    #[error("{0}")]
+    AuthFailed(String),
+    /// A requested device is unknown or unavailable.
+    #[error("{0}")]
+    DeviceInvalid(String),
+    /// The Rust library or mobile app failed to build.
+    #[error("build failed: {0}")]
+    BuildFailed(String),
+    /// A required artifact is missing on disk.
+    #[error("{0}")]
+    ArtifactMissing(String),
+    /// BrowserStack did not accept an artifact upload.
+    #[error("{0}")]
+    UploadFailed(String),
+    /// The BrowserStack API could not be reached.
+    #[error("{0}")]
+    Network(String),
+    /// The device run finished without usable benchmark results, or with
+    /// fewer samples than `--strict` requires.
+    #[error("{0}")]
+    ResultsUnavailable(String),
+    /// A regression, budget or suspicious-improvement check failed.
+    #[error("{0}")]
+    ThresholdExceeded(String),
+    /// Device reports came from a different build than the local one.
+    #[error("{0}")]
+    StaleBuild(String),
+    /// BrowserStack refused to schedule because every parallel session of
+    /// the account is in use or its queue is full.
+    #[error("{}", quota_message(*.limit, message))]
+    QuotaExceeded {
+        /// The account's parallel session limit, when BrowserStack reported it.
+        limit: Option<u32>,
+        message: String,
+    },
+}
+
+fn quota_message(limit: Option<u32>, message: &str) -> String {
+    let limit = limit
+        .map(|limit| format!(" (account limit: {limit} parallel sessions)"))
+        .unwrap_or_default();
+    format!(
+        "BrowserStack parallel session limit reached{limit}: {message}\n\
+         Run on fewer devices at once (e.g. --device-strategy one-per-os) or wait for \
+         running sessions to finish and try again."
+    )
+}
+
+impl MobenchError {
+    /// Wraps an SDK build error, for use with `map_err`.
+    pub fn build_failed(err: impl Display) -> Self {
+        Self::BuildFailed(err.to_string())
+    }
+
+    /// Process exit code for this failure (see the [module docs](self)).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::AuthFailed(_) => 3,
+            Self::DeviceInvalid(_) => 4,
+            Self::BuildFailed(_) => 5,
+            Self::ArtifactMissing(_) => 6,
+            Self::UploadFailed(_) => 7,
+            Self::Network(_) => 8,
+            Self::ResultsUnavailable(_) => 9,
+            Self::ThresholdExceeded(_) => 10,
+            Self::StaleBuild(_) => 11,
+            Self::QuotaExceeded { .. } => 12,
+        }
+    }
+}
+
+/// Exit code for an error returned by [`crate::run`].
+///
+/// Uses the first [`MobenchError`] in the error's cause chain, so context
+/// added with `anyhow::Context` does not hide it; anything else exits with 1.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<MobenchError>())
+        .map_or(1, MobenchError::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn exit_code_finds_structured_error_under_context() {
+        let err = Err::<(), _>(MobenchError::AuthFailed("bad key".into()))
+            .context("fetching build status")
+            .unwrap_err();
+        assert_eq!(exit_code(&err), 3);
+        assert_eq!(format!("{err:#}"), "fetching build status: bad key");
+
+        let err = Err::<(), _>(MobenchError::build_failed("cargo exited with 101"))
+            .context("building Android APK")
+            .unwrap_err();
+        assert_eq!(exit_code(&err), 5);
+
+        assert_eq!(exit_code(&anyhow::anyhow!("something else")), 1);
+    }
+}
@@ -0,0 +1,140 @@
+//! ASCII histograms of raw samples for `summary --histogram`.
+//!
+//! Mean and median hide the shape of a distribution. Buckets here are
+//! log-scaled between the fastest and slowest sample, so a long tail does not
+//! squash everything else into one bar, and a bimodal run (typically a device
+//! switching between full speed and thermal throttling) shows two separate
+//! peaks.
+
+use crate::sample_out::RawSample;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// Bucket count used when `--bins` is not given.
+pub(crate) const DEFAULT_BINS: usize = 10;
+
+/// Width in characters of the longest bar.
+const BAR_WIDTH: usize = 40;
+
+/// Samples falling in `[lower_ns, upper_ns)` (the last bucket is closed).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Bucket {
+    pub(crate) lower_ns: u64,
+    pub(crate) upper_ns: u64,
+    pub(crate) count: usize,
+}
+
+/// Split `durations` into `bins` log-scaled buckets.
+///
+/// Returns a single bucket when every sample has the same duration, and no
+/// buckets for an empty slice.
+pub(crate) fn buckets(durations: &[u64], bins: usize) -> Vec<Bucket> {
+    let (Some(&min), Some(&max)) = (durations.iter().min(), durations.iter().max()) else {
+        return Vec::new();
+    };
+    if min == max || bins <= 1 {
+        return vec![Bucket {
+            lower_ns: min,
+            upper_ns: max,
+            count: durations.len(),
+        }];
+    }
+    // Durations of 0ns have no logarithm; clamp the scale to start at 1ns
+    let log_min = (min.max(1) as f64).ln();
+    let log_span = (max as f64).ln() - log_min;
+    let edge = |i: usize| (log_min + log_span * i as f64 / bins as f64).exp().round() as u64;
+
+    let mut buckets: Vec<Bucket> = (0..bins)
+        .map(|i| Bucket {
+            lower_ns: if i == 0 { min } else { edge(i) },
+            upper_ns: if i + 1 == bins { max } else { edge(i + 1) },
+            count: 0,
+        })
+        .collect();
+    for &ns in durations {
+        let position = ((ns.max(1) as f64).ln() - log_min) / log_span * bins as f64;
+        let index = (position.max(0.0) as usize).min(bins - 1);
+        buckets[index].count += 1;
+    }
+    buckets
+}
+
+/// Render one histogram per function and device.
+pub(crate) fn render(samples: &[RawSample], bins: usize) -> String {
+    let mut groups: BTreeMap<(&str, &str), Vec<u64>> = BTreeMap::new();
+    for sample in samples {
+        groups
+            .entry((sample.function.as_str(), sample.device.as_str()))
+            .or_default()
+            .push(sample.duration_ns);
+    }
+
+    let mut output = String::new();
+    if groups.is_empty() {
+        output.push_str("No raw samples in this report; histograms need per-iteration samples.\n");
+        return output;
+    }
+    for ((function, device), durations) in groups {
+        let _ = writeln!(output, "\n{} on {} ({} samples)", function, device, durations.len());
+        let buckets = buckets(&durations, bins);
+        let labels: Vec<String> = buckets
+            .iter()
+            .map(|bucket| {
+                format!(
+                    "{} - {}",
+                    crate::format_duration_smart(bucket.lower_ns),
+                    crate::format_duration_smart(bucket.upper_ns)
+                )
+            })
+            .collect();
+        let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+        let tallest = buckets.iter().map(|bucket| bucket.count).max().unwrap_or(0).max(1);
+        for (bucket, label) in buckets.iter().zip(&labels) {
+            // Any non-empty bucket gets at least one mark so outliers stay visible
+            let width = (bucket.count * BAR_WIDTH).div_ceil(tallest);
+            let _ = writeln!(
+                output,
+                "  {:>label_width$} | {:<BAR_WIDTH$} {}",
+                label,
+                "#".repeat(width),
+                bucket.count
+            );
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bimodal_samples_show_two_peaks() {
+        // 1ms at full speed, 4ms while throttled
+        let durations: Vec<u64> = [1_000_000; 30].into_iter().chain([4_000_000; 20]).collect();
+        let bimodal = buckets(&durations, 4);
+        let counts: Vec<usize> = bimodal.iter().map(|bucket| bucket.count).collect();
+        assert_eq!(counts, vec![30, 0, 0, 20]);
+        // Log scale: each bucket spans the same ratio, here sqrt(2)
+        assert_eq!(bimodal[1].lower_ns, 1_414_214);
+        assert_eq!(bimodal[3].upper_ns, 4_000_000);
+
+        let samples: Vec<RawSample> = durations
+            .iter()
+            .enumerate()
+            .map(|(index, &duration_ns)| RawSample {
+                device: "Pixel 7".into(),
+                function: "sample_fns::fibonacci".into(),
+                iteration_index: index as u64,
+                duration_ns,
+            })
+            .collect();
+        let text = render(&samples, 4);
+        assert!(text.contains("sample_fns::fibonacci on Pixel 7 (50 samples)"), "{text}");
+        assert!(text.contains(&format!("| {} 30", "#".repeat(BAR_WIDTH))), "{text}");
+        assert!(text.contains("1.000ms - 1.414ms"), "{text}");
+
+        // Identical samples collapse into a single bucket
+        assert_eq!(buckets(&[5, 5, 5], DEFAULT_BINS).len(), 1);
+    }
+}
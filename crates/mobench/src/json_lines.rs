@@ -0,0 +1,163 @@
+//! Streaming device reports for `run --json-lines`.
+//!
+//! With hundreds of functions across many devices, the run summary holds every
+//! raw report and is written once, at the end. This module instead appends each
+//! device report to a file as soon as it is fetched, one JSON object per line,
+//! so downstream tools can process results incrementally and a run cut short
+//! still leaves the reports received so far.
+//!
+//! ## Schema
+//!
+//! One line per benchmark report:
+//!
+//! | Field | Type | Description |
+//! |-------|------|-------------|
+//! | `device` | string | Device name as reported by the backend |
+//! | `function` | string or null | Benchmark function, from `function` or `spec.name` |
+//! | `build_id` | string | BrowserStack build the report came from; absent for local devices |
+//! | `report` | object | The report, as stored in the summary's `benchmark_results` |
+//!
+//! Reports are written as they arrive: a device re-scheduled by
+//! `--device-retries` appears under its retry build, and with `--repeat` every
+//! round is written before the rounds are pooled.
+//!
+//! With `--no-summary-json` the run keeps only each report's summary
+//! statistics once it is streamed ([`Retain::Stats`]), so memory no longer
+//! grows with the raw samples of every device.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// What the run keeps of a report once it has been streamed.
+#[derive(Debug, Clone, Default)]
+pub(crate) enum Retain {
+    /// The whole report, for the aggregate JSON summary.
+    #[default]
+    Full,
+    /// Only its summary statistics (see [`crate::compact_report`]), computed
+    /// for the run's sampling mode.
+    Stats(Option<mobench_sdk::SamplingMode>),
+}
+
+/// Appends device reports to a JSON Lines file.
+pub(crate) struct JsonLinesWriter {
+    path: PathBuf,
+    out: Mutex<(BufWriter<File>, usize)>,
+    retain: Retain,
+}
+
+impl JsonLinesWriter {
+    /// Create (or truncate) `path`.
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        crate::ensure_parent_dir(path)?;
+        let file = File::create(path).with_context(|| format!("creating {:?}", path))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            out: Mutex::new((BufWriter::new(file), 0)),
+            retain: Retain::Full,
+        })
+    }
+
+    /// Keep only `retain` of each streamed report.
+    pub(crate) fn retaining(mut self, retain: Retain) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of records written so far.
+    pub(crate) fn records(&self) -> usize {
+        self.out.lock().unwrap().1
+    }
+
+    /// Write one record per report of `device` and flush, so readers
+    /// tailing the file see the device as a whole.
+    pub(crate) fn write_reports(&self, device: &str, build_id: Option<&str>, reports: &[Value]) -> Result<()> {
+        let mut guard = self.out.lock().unwrap();
+        let (out, records) = &mut *guard;
+        for report in reports {
+            let mut record = json!({
+                "device": device,
+                "function": crate::report_function_name(report),
+            });
+            if let Some(build_id) = build_id {
+                record["build_id"] = json!(build_id);
+            }
+            record["report"] = report.clone();
+            serde_json::to_writer(&mut *out, &record)?;
+            out.write_all(b"\n")?;
+            *records += 1;
+        }
+        out.flush().with_context(|| format!("writing {:?}", self.path))
+    }
+
+    /// Write `device`'s reports and return what the run keeps of them.
+    ///
+    /// The `env` values a report echoes are redacted first. Reports that
+    /// could not be written are kept whole, so a full disk costs memory
+    /// rather than results.
+    pub(crate) fn stream(&self, device: &str, build_id: Option<&str>, mut reports: Vec<Value>) -> Vec<Value> {
+        reports.iter_mut().for_each(crate::redact_report_env);
+        if let Err(error) = self.write_reports(device, build_id, &reports) {
+            println!("Warning: could not stream {} to --json-lines: {:#}", device, error);
+            return reports;
+        }
+        match &self.retain {
+            Retain::Full => reports,
+            Retain::Stats(sampling) => reports
+                .iter()
+                .map(|report| crate::compact_report(report, sampling.as_ref()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_one_record_per_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/results.jsonl");
+        let writer = JsonLinesWriter::create(&path).unwrap();
+        let reports = [
+            json!({ "function": "sample_fns::fibonacci", "samples": [10, 12] }),
+            json!({ "spec": { "name": "sample_fns::checksum" }, "samples": [7] }),
+        ];
+        writer.write_reports("Google Pixel 7-13.0", Some("build-1"), &reports).unwrap();
+        writer.write_reports("pixel-local", None, &reports[..1]).unwrap();
+        assert_eq!(writer.records(), 3);
+
+        // Flushed per device, so the file is readable while the writer is open
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["build_id"], "build-1");
+        assert_eq!(records[0]["report"]["samples"], json!([10, 12]));
+        assert_eq!(records[1]["function"], "sample_fns::checksum");
+        assert_eq!(records[2]["device"], "pixel-local");
+        assert!(records[2].get("build_id").is_none());
+
+        // With --no-summary-json only the statistics stay in memory
+        let compacting = JsonLinesWriter::create(&dir.path().join("compact.jsonl"))
+            .unwrap()
+            .retaining(Retain::Stats(None));
+        let kept = compacting.stream("pixel-local", None, reports[..1].to_vec());
+        assert!(kept[0].get("samples").is_none());
+        assert_eq!(kept[0]["summary_stats"]["samples"], 2);
+        assert_eq!(kept[0]["summary_stats"]["median_ns"], 11);
+        assert_eq!(kept[0]["function"], "sample_fns::fibonacci");
+        assert_eq!(compacting.records(), 1);
+    }
+}
@@ -110,6 +110,8 @@
 //! ## Modules
 //!
 //! - [`config`] - Configuration file support for `mobench.toml`
+//! - [`browserstack`] - BrowserStack App Automate client and the
+//!   [`DeviceBackend`](mobench_sdk::orchestrate::DeviceBackend) that `run` drives
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
@@ -126,10 +128,35 @@ use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use time::OffsetDateTime;
 use time::format_description::well_known::Rfc3339;
 
-use browserstack::{BrowserStackAuth, BrowserStackClient};
-
-mod browserstack;
+use browserstack::{BrowserStackAuth, BrowserStackBackend, BrowserStackClient};
+use mobench_sdk::ExecutionOrder;
+use mobench_sdk::affinity::CorePinning;
+use mobench_sdk::orchestrate::{
+    self, DeviceBackend, RunArtifacts, RunOptions, UploadedArtifacts, device_names_match,
+};
+use mobench_sdk::stats::{self, SampleStats};
+use mobench_sdk::timing::BENCH_REPORT_SCHEMA_VERSION;
+pub use error::{MobenchError, exit_code};
+
+mod baseline_store;
+mod bindings;
+pub mod browserstack;
+mod changed;
+mod compress;
 pub mod config;
+mod credentials;
+mod error;
+mod histogram;
+mod json_lines;
+mod local_device;
+mod logging;
+#[cfg(feature = "metrics-export")]
+mod metrics_export;
+mod notify;
+mod report;
+mod sample_out;
+mod term;
+mod units;
 
 /// CLI orchestrator for building, packaging, and executing Rust benchmarks on mobile.
 #[derive(Parser, Debug)]
@@ -143,34 +170,151 @@ struct Cli {
     #[arg(long, short = 'v', global = true)]
     verbose: bool,
 
+    /// Format for structured lifecycle events on stderr (filter with RUST_LOG)
+    #[arg(long, value_enum, global = true, default_value_t = logging::LogFormat::Text)]
+    log_format: logging::LogFormat,
+
+    /// Use plain ASCII status markers instead of Unicode symbols (implied by
+    /// NO_COLOR or when stdout is not a terminal)
+    #[arg(long, visible_alias = "ascii", global = true)]
+    no_color: bool,
+
+    /// Render every duration in summary and comparison tables in this unit,
+    /// named in the header (CSV and JSON always use raw nanoseconds)
+    #[arg(long, value_enum, global = true, default_value_t = units::TimeUnit::Auto)]
+    unit: units::TimeUnit,
+
+    /// Fetch the BrowserStack device list again instead of using the copy
+    /// cached under target/mobench/.cache
+    #[arg(long, global = true)]
+    refresh_devices: bool,
+
     #[command(subcommand)]
     command: Command,
 }
 
 #[derive(Subcommand, Debug)]
+// Parsed once at startup; boxing the `run` flags would only add noise.
+#[allow(clippy::large_enum_variant)]
 enum Command {
     /// Run a benchmark against a target platform (mobile integration stub for now).
     Run {
         #[arg(long, value_enum)]
         target: MobileTarget,
-        #[arg(long, help = "Fully-qualified Rust function to benchmark")]
-        function: String,
-        #[arg(long, default_value_t = 100)]
-        iterations: u32,
-        #[arg(long, default_value_t = 10)]
-        warmup: u32,
-        #[arg(long, help = "Device identifiers or labels (BrowserStack devices)")]
+        #[arg(
+            long,
+            help = "Fully-qualified Rust function to benchmark; repeat to run several in one build"
+        )]
+        function: Vec<String>,
+        #[arg(
+            long,
+            conflicts_with = "function",
+            help = "Run every #[benchmark] function found in the benchmark crate in one build"
+        )]
+        all: bool,
+        #[arg(
+            long,
+            help = "Benchmark iterations [default: the --profile's, else the function's #[benchmark(sample_size)], else [benchmarks] default_iterations in mobench.toml, else 100]"
+        )]
+        iterations: Option<u32>,
+        #[arg(
+            long,
+            help = "Warmup iterations [default: the --profile's, else the function's #[benchmark(warmup)], else [benchmarks] default_warmup in mobench.toml, else 10]"
+        )]
+        warmup: Option<u32>,
+        #[arg(
+            long,
+            help = "Device identifiers or labels (BrowserStack devices), or `local` for every device attached via adb/ios-deploy"
+        )]
         devices: Vec<String>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            conflicts_with = "config",
+            help = "Use the devices, iterations and warmup of [profiles.NAME] in mobench.toml; other flags still override it"
+        )]
+        profile: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = DeviceStrategy::All,
+            help = "Narrow the resolved devices: all of them, the first available one per device tag, or one per OS version"
+        )]
+        device_strategy: DeviceStrategy,
+        #[arg(
+            long,
+            help = "Drop devices that fail validation (with a warning) instead of aborting; fails only if none are valid"
+        )]
+        keep_going: bool,
+        #[arg(
+            long,
+            overrides_with = "no_fail_fast",
+            help = "Abort the run when a device fails to schedule or run (default)"
+        )]
+        fail_fast: bool,
+        #[arg(
+            long,
+            overrides_with = "fail_fast",
+            help = "Schedule every device even if some fail; list per-device failures in the summary and exit non-zero at the end"
+        )]
+        no_fail_fast: bool,
         #[arg(long, help = "Optional path to config file")]
         config: Option<PathBuf>,
         #[arg(long, help = "Optional output path for JSON report")]
         output: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Output directory for mobile artifacts (default: target/mobench); give concurrent runs separate directories"
+        )]
+        output_dir: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SUMMARY",
+            conflicts_with_all = ["devices", "local_only", "repeat", "ab_self"],
+            help = "Resume a partial run: schedule only the devices this run summary has no results for and merge their results into it (written back to SUMMARY unless --output is given)"
+        )]
+        resume_from: Option<PathBuf>,
         #[arg(long, help = "Write CSV summary alongside JSON")]
         summary_csv: bool,
+        #[arg(
+            long,
+            help = "Write the JSON summary minified instead of pretty-printed; much smaller for runs with many devices and samples"
+        )]
+        compact_json: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Stream every device report to this file as one JSON object per line (JSON Lines) as soon as it is fetched"
+        )]
+        json_lines: Option<PathBuf>,
+        #[arg(
+            long,
+            requires = "json_lines",
+            conflicts_with_all = [
+                "ab_self",
+                "baseline_store",
+                "baseline_from_browserstack",
+                "repeat",
+                "sample_out",
+                "resume_from",
+            ],
+            help = "With --json-lines, keep only summary statistics of streamed reports and do not write the aggregate JSON summary; the Markdown summary and checks still run"
+        )]
+        no_summary_json: bool,
         #[arg(long, help = "Skip mobile builds and only run the host harness")]
         local_only: bool,
+        #[arg(
+            long,
+            help = "Also run the benchmarks on this machine and report them as a `host:<os>-<arch>` device in the summary"
+        )]
+        include_host: bool,
         #[arg(long, help = "Build in release mode (recommended for BrowserStack to reduce APK size and upload time)")]
         release: bool,
+        #[arg(
+            long,
+            help = "Named cargo profile for the Rust build (e.g. bench-mobile for [profile.bench-mobile]); --release still selects the app variant"
+        )]
+        cargo_profile: Option<String>,
         #[arg(
             long,
             help = "Path to iOS app bundle (.ipa or zipped .app) for BrowserStack XCUITest"
@@ -178,16 +322,253 @@ enum Command {
         ios_app: Option<PathBuf>,
         #[arg(long, help = "Path to iOS XCUITest test suite package (.zip or .ipa)")]
         ios_test_suite: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Path to an Android App Bundle (.aab) to upload to BrowserStack instead of the built APK"
+        )]
+        android_aab: Option<PathBuf>,
+        #[arg(
+            long,
+            requires = "skip_build",
+            help = "Prebuilt Android app APK to upload (with --skip-build)"
+        )]
+        prebuilt_apk: Option<PathBuf>,
+        #[arg(
+            long,
+            requires = "skip_build",
+            help = "Prebuilt Android Espresso test APK to upload (with --skip-build)"
+        )]
+        prebuilt_test_apk: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with = "local_only",
+            help = "Do not build; upload the prebuilt artifacts from --prebuilt-apk/--prebuilt-test-apk (Android) or --ios-app/--ios-test-suite (iOS)"
+        )]
+        skip_build: bool,
         #[arg(long, help = "Fetch BrowserStack artifacts after the run completes")]
         fetch: bool,
         #[arg(long, default_value = "target/browserstack")]
         fetch_output_dir: PathBuf,
+        #[arg(
+            long = "compress",
+            help = "With --fetch, store the fetched BrowserStack artifacts zstd-compressed (*.zst)"
+        )]
+        fetch_compress: bool,
         #[arg(long, default_value_t = 5)]
         fetch_poll_interval_secs: u64,
         #[arg(long, default_value_t = 300)]
         fetch_timeout_secs: u64,
+        #[arg(
+            long,
+            default_value_t = browserstack::DEFAULT_FETCH_CONCURRENCY,
+            help = "Maximum number of device sessions whose results are fetched at once"
+        )]
+        fetch_concurrency: usize,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            help = "Refuse runs projected (from a local single-iteration measurement) to take longer than this per device [default: --fetch-timeout-secs]"
+        )]
+        max_run_time: Option<String>,
+        #[arg(long, help = "Only warn when the projected run time exceeds --max-run-time")]
+        force: bool,
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "With --fetch, re-schedule devices that finished without a benchmark report up to this many times"
+        )]
+        device_retries: u32,
+        #[arg(
+            long,
+            value_name = "R",
+            default_value_t = 1,
+            value_parser = clap::value_parser!(u32).range(1..),
+            conflicts_with = "ab_self",
+            help = "Run the device run R times without rebuilding and pool each device's samples per function across the repeats"
+        )]
+        repeat: u32,
+        #[arg(
+            long,
+            requires = "max_samples",
+            help = "Sample until the median's 95% confidence interval meets --ci-target, taking at least this many samples (replaces --iterations)"
+        )]
+        min_samples: Option<u32>,
+        #[arg(
+            long,
+            requires = "min_samples",
+            help = "Stop after this many samples even if --ci-target is not met"
+        )]
+        max_samples: Option<u32>,
+        #[arg(
+            long,
+            default_value = "1%",
+            value_name = "NS|PCT%",
+            requires = "min_samples",
+            help = "Target half-width of the median's 95% confidence interval, in ns or percent of the median (e.g. 2%)"
+        )]
+        ci_target: String,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            help = "Warm up for this long (e.g. 1s, 500ms) instead of --warmup iterations"
+        )]
+        warmup_time: Option<String>,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            conflicts_with_all = ["min_samples", "max_samples"],
+            help = "Measure for this long (e.g. 3s) instead of --iterations"
+        )]
+        measure_time: Option<String>,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            help = "Stop each benchmark after this long on the device (e.g. 90s) and keep its partial samples, marked incomplete"
+        )]
+        per_benchmark_timeout: Option<String>,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            help = "Fail a benchmark whose single iteration runs longer than this on the device (e.g. 500ms); setup is not counted"
+        )]
+        per_iteration_timeout: Option<String>,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Discard the first N measured samples of each benchmark (after warmup); with fixed iterations they count toward --iterations"
+        )]
+        drop_first_n: Option<u32>,
+        #[arg(
+            long,
+            help = "Record each warmup iteration's duration in the reports' warmup_samples, for checking that warmup is long enough"
+        )]
+        capture_warmup: bool,
+        #[arg(
+            long,
+            help = "Also measure cold and warm activity startup (startup:cold, startup:warm) with am start -W; Android only"
+        )]
+        startup_metrics: bool,
+        #[arg(
+            long,
+            value_name = "big|little|none",
+            default_value = "none",
+            help = "Pin the benchmark thread to the big or little cores with sched_setaffinity; Android only"
+        )]
+        pin_cores: CorePinning,
+        #[arg(
+            long,
+            conflicts_with_all = ["min_samples", "max_samples", "measure_time"],
+            help = "Run a suite round-robin, one sample per function per round, so thermal drift hits every function alike; assumes independent, side-effect-free benchmarks"
+        )]
+        sample_interleave: bool,
+        #[arg(
+            long,
+            value_name = "declared|alpha|shuffle[:SEED]",
+            default_value = "declared",
+            help = "Order a suite's functions run in: as given (source order for --all), alphabetical, or shuffled; the order and shuffle seed are recorded in the summary"
+        )]
+        order: ExecutionOrder,
+        #[arg(
+            long = "env",
+            value_name = "KEY=VALUE",
+            help = "Set an environment variable on the device before the benchmarks run; repeatable, and adds to or overrides [env] in mobench.toml"
+        )]
+        env_vars: Vec<String>,
         #[arg(long, help = "Show simplified step-by-step progress output")]
         progress: bool,
+        #[arg(
+            long,
+            help = "Directory of baseline summaries keyed by target and function; the run is compared against the stored baseline"
+        )]
+        baseline_store: Option<PathBuf>,
+        #[arg(
+            long,
+            requires = "baseline_store",
+            help = "Promote this run to the stored baseline when on this git branch and no regression is found"
+        )]
+        promote_on: Option<String>,
+        #[arg(
+            long,
+            value_name = "BUILD_ID",
+            conflicts_with = "baseline_store",
+            help = "Compare the run against the results of this earlier BrowserStack build (cached under target/mobench/baselines)"
+        )]
+        baseline_from_browserstack: Option<String>,
+        #[command(flatten)]
+        regression: RegressionThresholds,
+        #[arg(long, help = "Exit with an error if any regression against the baseline is found")]
+        fail_on_regression: bool,
+        #[arg(
+            long,
+            conflicts_with = "all",
+            help = "Run the functions twice in the same build and compare the two passes (an A/A test) to measure the noise floor against the regression threshold; with --fail-on-regression, a spurious regression fails the run"
+        )]
+        ab_self: bool,
+        #[arg(
+            long,
+            default_value_t = DEFAULT_NOISY_CV_PCT,
+            help = "Flag benchmarks whose coefficient of variation (percent) exceeds this as noisy in the summary"
+        )]
+        noisy_cv_threshold: f64,
+        #[arg(
+            long,
+            help = "Exit with an error if a device report's build metadata does not match the local build"
+        )]
+        fail_on_stale_build: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["min_samples", "max_samples", "measure_time"],
+            help = "Exit with an error if any device returned fewer samples than requested (--iterations less --drop-first-n), listing the shortfalls"
+        )]
+        strict: bool,
+        #[arg(
+            long,
+            help = "Write every raw sample (device, function, iteration_index, duration_ns) to this CSV, or Parquet for .parquet paths"
+        )]
+        sample_out: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Also write the summary as Google Benchmark JSON here, named <function>/<device>"
+        )]
+        gbench_json: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "GIT_REF",
+            help = "Skip the run unless the benchmark's defining crate/module changed since this git ref"
+        )]
+        since: Option<String>,
+        #[arg(
+            long,
+            help = "Seed for benchmarks with seeded per-iteration setup (default: derived from the function name)"
+        )]
+        seed: Option<u64>,
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "POST the run verdict and regression deltas to this webhook when the run finishes (printed instead with --dry-run)"
+        )]
+        notify_webhook: Option<String>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = notify::NotifyFormat::Json,
+            requires = "notify_webhook",
+            help = "Payload shape for --notify-webhook; slack sends a Slack incoming-webhook message"
+        )]
+        notify_format: notify::NotifyFormat,
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "Export the summary statistics as OTLP/HTTP JSON gauges to this collector (needs the metrics-export feature)"
+        )]
+        otlp_endpoint: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the summary statistics as Prometheus text-format gauges, e.g. for a Pushgateway (needs the metrics-export feature)"
+        )]
+        prometheus_out: Option<PathBuf>,
     },
     /// Scaffold a base config file for the CLI.
     Init {
@@ -196,17 +577,45 @@ enum Command {
         #[arg(long, value_enum, default_value_t = MobileTarget::Android)]
         target: MobileTarget,
     },
-    /// Generate a sample device matrix file.
+    /// Generate a device matrix file.
+    ///
+    /// Writes a sample matrix with placeholder devices by default. With
+    /// --from-browserstack, the matrix lists devices currently available on
+    /// BrowserStack instead: the newest OS version of each model, newest
+    /// first, tagged by platform and model family. The newest few devices of
+    /// each platform are also tagged `default`.
+    ///
+    /// Examples:
+    ///   cargo mobench plan
+    ///   cargo mobench plan --from-browserstack --platform android --top 10
     Plan {
         #[arg(long, default_value = "device-matrix.yaml")]
         output: PathBuf,
+        #[arg(long, help = "Build the matrix from devices currently available on BrowserStack")]
+        from_browserstack: bool,
+        #[arg(
+            long,
+            value_enum,
+            requires = "from_browserstack",
+            help = "Only include devices for this platform (default: both)"
+        )]
+        platform: Option<DevicePlatform>,
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "from_browserstack",
+            help = "Keep the N models with the newest OS versions per platform"
+        )]
+        top: Option<usize>,
+        #[arg(long, short = 'y', help = "Overwrite the output file if it already exists")]
+        yes: bool,
     },
     /// Fetch BrowserStack build artifacts (logs, session JSON) for CI.
     Fetch {
-        #[arg(long, value_enum)]
-        target: MobileTarget,
-        #[arg(long)]
-        build_id: String,
+        #[arg(long, value_enum, required_unless_present = "extract_only")]
+        target: Option<MobileTarget>,
+        #[arg(long, required_unless_present = "extract_only")]
+        build_id: Option<String>,
         #[arg(long, default_value = "target/browserstack")]
         output_dir: PathBuf,
         #[arg(long, default_value_t = true)]
@@ -215,6 +624,29 @@ enum Command {
         poll_interval_secs: u64,
         #[arg(long, default_value_t = 1800)]
         timeout_secs: u64,
+        #[arg(
+            long,
+            help = "Local bench_meta.json to check device reports against (defaults to the one embedded by the last build)"
+        )]
+        bench_meta: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Exit with an error if a device report's build metadata does not match the local build"
+        )]
+        fail_on_stale_build: bool,
+        #[arg(
+            long,
+            value_name = "DIR",
+            conflicts_with = "build_id",
+            help = "Re-extract reports from logs previously fetched into DIR (e.g. target/browserstack/<build-id>) without contacting BrowserStack"
+        )]
+        extract_only: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with = "extract_only",
+            help = "Store build/session JSON, device logs and benchmark-results.json zstd-compressed (*.zst)"
+        )]
+        compress: bool,
     },
     /// Compare two run summaries for regressions.
     Compare {
@@ -224,6 +656,34 @@ enum Command {
         candidate: PathBuf,
         #[arg(long, help = "Optional output path for markdown report")]
         output: Option<PathBuf>,
+        #[command(flatten)]
+        regression: RegressionThresholds,
+        #[arg(long, help = "Write a JUnit XML report for the candidate run to this path")]
+        junit: Option<PathBuf>,
+        #[arg(long, help = "Exit with an error if any regression is found")]
+        fail_on_regression: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only show the N worst rows in the report (regression checks still cover every row)"
+        )]
+        top: Option<usize>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = TopSort::Regression,
+            requires = "top",
+            help = "Rank --top rows by candidate median, candidate p95, or regression (primary metric delta)"
+        )]
+        sort_by: TopSort,
+        #[command(flatten)]
+        delta_colors: DeltaColorArgs,
+        #[arg(
+            long,
+            value_name = "FUNCTION",
+            help = "Add a table of this function's candidate median per device model and OS version, with Δ% vs the baseline"
+        )]
+        platform_version_matrix: Option<String>,
     },
     /// Initialize a new benchmark project with SDK (Phase 1 MVP).
     InitSdk {
@@ -235,6 +695,11 @@ enum Command {
         output_dir: PathBuf,
         #[arg(long, help = "Generate example benchmarks")]
         examples: bool,
+        #[arg(
+            long,
+            help = "Also generate a criterion harness (benches/mobench_criterion.rs) running the same #[benchmark] functions under cargo bench"
+        )]
+        with_criterion: bool,
     },
     /// Build mobile artifacts (Phase 1 MVP).
     Build {
@@ -242,12 +707,19 @@ enum Command {
         target: SdkTarget,
         #[arg(long, help = "Build in release mode")]
         release: bool,
+        #[arg(
+            long,
+            help = "Named cargo profile for the Rust build (e.g. bench-mobile for [profile.bench-mobile]); --release still selects the app variant"
+        )]
+        cargo_profile: Option<String>,
         #[arg(long, help = "Output directory for mobile artifacts (default: target/mobench)")]
         output_dir: Option<PathBuf>,
         #[arg(long, help = "Path to the benchmark crate (default: auto-detect bench-mobile/ or crates/{crate})")]
         crate_path: Option<PathBuf>,
         #[arg(long, help = "Show simplified step-by-step progress output")]
         progress: bool,
+        #[arg(long, help = "Always recompile Rust libraries, ignoring the build fingerprint cache")]
+        no_cache: bool,
     },
     /// Package iOS app as IPA for distribution or testing.
     PackageIpa {
@@ -258,6 +730,18 @@ enum Command {
         #[arg(long, help = "Output directory for mobile artifacts (default: target/mobench)")]
         output_dir: Option<PathBuf>,
     },
+    /// Package Android app as an App Bundle (.aab) for BrowserStack or Play testing.
+    PackageAab {
+        #[arg(
+            long,
+            alias = "scheme",
+            default_value = "release",
+            help = "Gradle build variant to bundle"
+        )]
+        variant: String,
+        #[arg(long, help = "Output directory for mobile artifacts (default: target/mobench)")]
+        output_dir: Option<PathBuf>,
+    },
     /// Package XCUITest runner for BrowserStack testing.
     ///
     /// Builds the XCUITest runner using xcodebuild and zips the resulting
@@ -270,7 +754,19 @@ enum Command {
         output_dir: Option<PathBuf>,
     },
     /// List all discovered benchmark functions (Phase 1 MVP).
-    List,
+    List {
+        #[arg(
+            long,
+            help = "Output as JSON with group, params, ignore status, and setup flag"
+        )]
+        json: bool,
+        #[arg(
+            long,
+            value_name = "GIT_REF",
+            help = "Only list benchmarks whose defining crate/module changed since this git ref"
+        )]
+        since: Option<String>,
+    },
     /// Verify benchmark setup: registry, spec, artifacts, and optional smoke test.
     ///
     /// This command validates:
@@ -278,6 +774,7 @@ enum Command {
     /// - Spec file exists and is valid (if --spec-path provided)
     /// - Artifacts are present and consistent (if --check-artifacts)
     /// - Runs a local smoke test (if --smoke-test and function is specified)
+    /// - Runs every registered benchmark once (if --smoke-all)
     Verify {
         #[arg(long, value_enum, help = "Target platform to verify artifacts for")]
         target: Option<SdkTarget>,
@@ -287,6 +784,17 @@ enum Command {
         check_artifacts: bool,
         #[arg(long, help = "Run a local smoke test with minimal iterations")]
         smoke_test: bool,
+        #[arg(
+            long,
+            help = "Check that the generated UniFFI bindings match the FFI crate's BenchSpec/BenchReport and run_benchmark"
+        )]
+        check_bindings: bool,
+        #[arg(
+            long,
+            conflicts_with = "function",
+            help = "Run every registered benchmark for one iteration and report pass/fail per function"
+        )]
+        smoke_all: bool,
         #[arg(long, help = "Function name to verify/smoke test")]
         function: Option<String>,
         #[arg(long, help = "Output directory for mobile artifacts (default: target/mobench)")]
@@ -296,31 +804,143 @@ enum Command {
     ///
     /// Prints avg/min/max/median, sample count, device, and OS version
     /// from the specified report file.
+    ///
+    /// With --merge, combines several run summaries (e.g. separate Android and
+    /// iOS jobs) into one, preferring later files when the same device and
+    /// function appear more than once.
     Summary {
-        #[arg(help = "Path to the benchmark report JSON file")]
-        report: PathBuf,
+        #[arg(
+            help = "Path to the benchmark report JSON file",
+            required_unless_present = "merge"
+        )]
+        report: Option<PathBuf>,
         #[arg(long, help = "Output format: text (default), json, or csv")]
         format: Option<SummaryFormat>,
-    },
-    /// List available BrowserStack devices for testing.
-    ///
-    /// Fetches and displays the list of available devices from BrowserStack
-    /// that can be used with the --devices flag in the run command.
-    ///
-    /// Examples:
-    ///   mobench devices                    # List all devices
-    ///   mobench devices --platform android # List Android devices only
-    ///   mobench devices --json             # Output as JSON
-    ///   mobench devices --validate "Google Pixel 7-13.0"  # Validate a device spec
-    Devices {
-        #[arg(long, value_enum, help = "Filter by platform (android or ios)")]
-        platform: Option<DevicePlatform>,
-        #[arg(long, help = "Output as JSON")]
-        json: bool,
-        #[arg(long, help = "Validate device specs against available devices")]
-        validate: Vec<String>,
-    },
-    /// Check prerequisites for building mobile artifacts.
+        #[arg(
+            long,
+            num_args = 2..,
+            conflicts_with = "report",
+            help = "Merge these run summary JSON files into one summary"
+        )]
+        merge: Vec<PathBuf>,
+        #[arg(
+            long,
+            requires = "merge",
+            help = "Write the merged run summary JSON here (markdown and CSV are written alongside)"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, requires = "output", help = "Write the merged run summary JSON minified")]
+        compact_json: bool,
+        #[arg(
+            long,
+            help = "Write every raw sample (device, function, iteration_index, duration_ns) to this CSV, or Parquet for .parquet paths"
+        )]
+        sample_out: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Also write the summary as Google Benchmark JSON here, named <function>/<device>"
+        )]
+        gbench_json: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only show the N slowest entries (budget checks still cover every entry)"
+        )]
+        top: Option<usize>,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = TopSort::Median,
+            requires = "top",
+            help = "Rank --top entries by median or p95; regression ranking needs a baseline (see `compare --top`)"
+        )]
+        sort_by: TopSort,
+        #[arg(
+            long,
+            help = "Print a log-scale ASCII histogram of the raw samples for each function and device (text output only)"
+        )]
+        histogram: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = histogram::DEFAULT_BINS,
+            requires = "histogram",
+            help = "Number of --histogram buckets"
+        )]
+        bins: usize,
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with = "merge",
+            help = "Print the summary as one table with median and p95 Δ% against this earlier run summary (text output only)"
+        )]
+        baseline: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FUNCTION",
+            conflicts_with_all = ["top", "baseline"],
+            help = "Also print this function's median per device model and OS version, with Δ% vs each model's oldest OS version (text output only)"
+        )]
+        platform_version_matrix: Option<String>,
+    },
+    /// Render a run summary as a self-contained HTML report.
+    ///
+    /// Writes a median bar chart per function and the full statistics table
+    /// to `<summary>.html`. With --serve, also serves the report and the raw
+    /// JSON on 127.0.0.1 until Ctrl-C.
+    ///
+    /// Examples:
+    ///   cargo mobench report target/mobench/summary.json
+    ///   cargo mobench report target/mobench/summary.json --serve --port 8080
+    Report {
+        #[arg(help = "Path to the run summary JSON (e.g. target/mobench/summary.json)")]
+        summary: PathBuf,
+        #[arg(long, help = "Write the HTML report here (default: the summary path with .html)")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Serve the report and raw JSON on 127.0.0.1 until Ctrl-C")]
+        serve: bool,
+        #[arg(
+            long,
+            default_value_t = 0,
+            requires = "serve",
+            help = "Port for --serve (0 picks a free port)"
+        )]
+        port: u16,
+    },
+    /// List available BrowserStack devices for testing.
+    ///
+    /// Fetches and displays the list of available devices from BrowserStack
+    /// that can be used with the --devices flag in the run command.
+    ///
+    /// Examples:
+    ///   mobench devices                    # List all devices
+    ///   mobench devices --platform android # List Android devices only
+    ///   mobench devices --json             # Output as JSON
+    ///   mobench devices --validate "Google Pixel 7-13.0"  # Validate a device spec
+    ///   mobench devices --matrix device-matrix.yaml --tags default  # Check tagged matrix devices
+    Devices {
+        #[arg(long, value_enum, help = "Filter by platform (android or ios)")]
+        platform: Option<DevicePlatform>,
+        #[arg(long, help = "Output as JSON")]
+        json: bool,
+        #[arg(long, help = "Validate device specs against available devices")]
+        validate: Vec<String>,
+        #[arg(
+            long,
+            help = "Device matrix (YAML, TOML or JSON) whose devices are checked for BrowserStack availability"
+        )]
+        matrix: Option<PathBuf>,
+        #[arg(
+            long,
+            alias = "filter-by-tag",
+            value_delimiter = ',',
+            requires = "matrix",
+            help = "Only check matrix devices with these tags (comma-separated)"
+        )]
+        tags: Vec<String>,
+    },
+    /// Check prerequisites for building mobile artifacts.
     ///
     /// Validates that all required tools and configurations are in place
     /// before attempting a build. This includes checking for:
@@ -329,10 +949,21 @@ enum Command {
     /// - iOS: Xcode, xcodegen, Rust targets
     /// - Both: cargo, rustup
     ///
+    /// Missing optional tools (xcodegen) are warnings; the command only
+    /// fails when a required prerequisite is missing. `--format json` gives
+    /// each check a `severity` (error/warning/info) and a `docs_url`, plus an
+    /// overall `status` of `ok`, `warnings` or `errors`.
+    ///
+    /// With --fix, missing Rust targets and cargo-ndk are installed after
+    /// confirmation. Xcode and the Android NDK are never installed
+    /// automatically; guidance is printed instead.
+    ///
     /// Examples:
     ///   cargo mobench check --target android
     ///   cargo mobench check --target ios
     ///   cargo mobench check --target android --format json
+    ///   cargo mobench doctor --target android --fix --yes
+    #[command(visible_alias = "doctor")]
     Check {
         /// Target platform (android or ios)
         #[arg(long, short, value_enum)]
@@ -340,9 +971,27 @@ enum Command {
         /// Output format (text or json)
         #[arg(long, default_value = "text")]
         format: CheckOutputFormat,
+        /// Install missing prerequisites that can be fixed automatically
+        #[arg(long)]
+        fix: bool,
+        /// Run fix commands without prompting for confirmation
+        #[arg(long, short = 'y', alias = "non-interactive", requires = "fix")]
+        yes: bool,
     },
 }
 
+/// How `run --device-strategy` narrows the resolved BrowserStack devices.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+enum DeviceStrategy {
+    /// Every resolved device
+    #[default]
+    All,
+    /// The first available device per `device_tags` entry, or overall
+    FirstAvailable,
+    /// The first available device per OS version
+    OnePerOs,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 #[clap(rename_all = "lowercase")]
 enum DevicePlatform {
@@ -358,6 +1007,18 @@ enum SummaryFormat {
     Csv,
 }
 
+/// Ranking metric for `summary --top` and `compare --top`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+enum TopSort {
+    /// Slowest median first
+    Median,
+    /// Slowest p95 first
+    P95,
+    /// Largest slowdown against the baseline first
+    Regression,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 #[clap(rename_all = "lowercase")]
 enum CheckOutputFormat {
@@ -435,17 +1096,99 @@ struct BenchConfig {
     ios_xcuitest: Option<IosXcuitestArtifacts>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 struct DeviceEntry {
     name: String,
     os: String,
+    #[serde(default)]
     os_version: String,
+    /// Run on each of these OS versions; [`load_device_matrix`] expands the
+    /// entry into one `<name>-<version>` device per version.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_os_versions",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    os_versions: Vec<String>,
     tags: Option<Vec<String>>,
 }
 
+/// Accept `os_versions: [12, 13.0, "14"]`: bare numbers are the natural way
+/// to write versions in YAML and TOML.
+fn deserialize_os_versions<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Version {
+        Text(String),
+        Integer(u64),
+        // `{:?}` keeps the `.0` of `13.0`, which BrowserStack expects
+        Float(f64),
+    }
+    let versions = Vec::<Version>::deserialize(deserializer)?;
+    Ok(versions
+        .into_iter()
+        .map(|version| match version {
+            Version::Text(text) => text.trim().to_string(),
+            Version::Integer(number) => number.to_string(),
+            Version::Float(number) => format!("{:?}", number),
+        })
+        .collect())
+}
+
+/// Expand entries with `os_versions` into one device per OS version, so each
+/// version is selected, validated and summarized as a distinct device.
+fn expand_os_versions(devices: Vec<DeviceEntry>) -> Result<Vec<DeviceEntry>> {
+    let mut expanded = Vec::with_capacity(devices.len());
+    for device in devices {
+        if device.os_versions.is_empty() {
+            expanded.push(device);
+            continue;
+        }
+        if !device.os_version.is_empty() {
+            bail!(MobenchError::DeviceInvalid(format!(
+                "device matrix entry '{}' sets both os_version and os_versions; use one",
+                device.name
+            )));
+        }
+        for version in &device.os_versions {
+            // BrowserStack lists Android versions with a minor part (`13.0`)
+            // and iOS ones without (`17`)
+            let version = if device.os.eq_ignore_ascii_case("android") && !version.contains('.') {
+                format!("{version}.0")
+            } else {
+                version.clone()
+            };
+            expanded.push(DeviceEntry {
+                name: format!("{}-{}", device.name, version),
+                os_version: version,
+                os_versions: Vec::new(),
+                ..device.clone()
+            });
+        }
+    }
+    Ok(expanded)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct DeviceMatrix {
     devices: Vec<DeviceEntry>,
+    /// Device tag -> group label. Each group gets a `group:<label>` rollup in
+    /// the run summary.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    groups: BTreeMap<String, String>,
+}
+
+/// Prefix of the synthetic `DeviceSummary` entries that roll up a device group.
+const GROUP_PREFIX: &str = "group:";
+
+/// A labelled set of devices from the matrix `groups` mapping.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct DeviceGroup {
+    label: String,
+    devices: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -459,6 +1202,119 @@ struct RunSpec {
     browserstack: Option<BrowserStackConfig>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     ios_xcuitest: Option<IosXcuitestArtifacts>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    device_groups: Vec<DeviceGroup>,
+    /// Set by `--min-samples`/`--max-samples` or `--measure-time`; absent
+    /// means fixed iterations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sampling: Option<mobench_sdk::SamplingMode>,
+    /// Set by `--warmup-time`; absent means a fixed warmup count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    warmup_policy: Option<mobench_sdk::WarmupPolicy>,
+    /// Every function run by this build, in order, when it runs more than
+    /// one; `function` is the first entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    functions: Vec<String>,
+    /// Set by `--seed`; absent means each benchmark derives its own seed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    /// Set by `--per-benchmark-timeout`; absent means benchmarks run to completion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    per_benchmark_timeout_secs: Option<u64>,
+    /// Set by `--per-iteration-timeout`; absent means iterations may run as
+    /// long as they take.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    per_iteration_timeout_ms: Option<u64>,
+    /// Set by `--drop-first-n`; measured samples discarded before the report.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    drop_first_n: u32,
+    /// Set by `--capture-warmup`; reports also carry `warmup_samples`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    capture_warmup: bool,
+    /// Set by `--startup-metrics`; Android runs also report `startup:cold`
+    /// and `startup:warm`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    startup_metrics: bool,
+    /// Set by `--pin-cores`; Android runners pin the benchmark thread.
+    #[serde(default, skip_serializing_if = "CorePinning::is_none")]
+    pin_cores: CorePinning,
+    /// Set by `--sample-interleave`; suites run round-robin, one sample per
+    /// function per round.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    sample_interleave: bool,
+    /// Set by `--order`; how `functions` was arranged, with the shuffle seed
+    /// used so the order can be replayed.
+    #[serde(default, skip_serializing_if = "ExecutionOrder::is_declared")]
+    order: ExecutionOrder,
+    /// `[env]` from `mobench.toml` with `--env` applied; the runners set
+    /// these variables before the benchmarks start. Run summaries keep only
+    /// the names (see [`redact_env`]).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    /// `#[benchmark(sample_size, warmup)]` defaults of a suite's functions,
+    /// for the counts no flag or profile set. The runners resolve these per
+    /// function (see [`RunSpec::embedded_counts`]); `iterations` and `warmup`
+    /// apply to the functions that set none.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    benchmark_defaults: BTreeMap<String, BenchmarkDefaults>,
+}
+
+/// A function's `#[benchmark(sample_size = N, warmup = N)]` counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct BenchmarkDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sample_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    warmup: Option<u32>,
+}
+
+fn is_zero(count: &u32) -> bool {
+    *count == 0
+}
+
+impl RunSpec {
+    /// Functions the device runner executes, in order.
+    fn all_functions(&self) -> Vec<&str> {
+        if self.functions.is_empty() {
+            vec![self.function.as_str()]
+        } else {
+            self.functions.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Iterations `function` runs on the device.
+    fn iterations_for(&self, function: &str) -> u32 {
+        self.benchmark_defaults
+            .get(function)
+            .and_then(|defaults| defaults.sample_size)
+            .unwrap_or(self.iterations)
+    }
+
+    /// Warmup iterations `function` runs on the device.
+    fn warmup_for(&self, function: &str) -> u32 {
+        self.benchmark_defaults
+            .get(function)
+            .and_then(|defaults| defaults.warmup)
+            .unwrap_or(self.warmup)
+    }
+
+    /// Iterations and warmup written to `bench_spec.json`:
+    /// [`USE_BENCHMARK_DEFAULT`](mobench_sdk::USE_BENCHMARK_DEFAULT) for the
+    /// counts left to each function's `#[benchmark]` defaults.
+    fn embedded_counts(&self) -> (u32, u32) {
+        let defaults = self.benchmark_defaults.values();
+        let iterations = if defaults.clone().any(|defaults| defaults.sample_size.is_some()) {
+            mobench_sdk::USE_BENCHMARK_DEFAULT
+        } else {
+            self.iterations
+        };
+        let warmup = if defaults.clone().any(|defaults| defaults.warmup.is_some()) {
+            mobench_sdk::USE_BENCHMARK_DEFAULT
+        } else {
+            self.warmup
+        };
+        (iterations, warmup)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -468,26 +1324,56 @@ enum MobileArtifacts {
         apk: PathBuf,
     },
     Ios {
-        xcframework: PathBuf,
-        header: PathBuf,
+        /// Absent for `run --skip-build`, which uploads prebuilt artifacts.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        xcframework: Option<PathBuf>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        header: Option<PathBuf>,
         #[serde(skip_serializing_if = "Option::is_none")]
         app: Option<PathBuf>,
         #[serde(skip_serializing_if = "Option::is_none")]
         test_suite: Option<PathBuf>,
+        /// Zipped `.app.dSYM` for symbolicating on-device crashes.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dsym: Option<PathBuf>,
     },
 }
 
+/// Version of the run summary format this binary writes.
+///
+/// Version 1 is the unversioned format from before `schema_version` was
+/// recorded. [`load_run_summary`] upgrades older summaries and refuses newer
+/// ones.
+const RUN_SUMMARY_SCHEMA_VERSION: u32 = 2;
+
+/// A summary without `schema_version` predates it, as in [`migrate_run_summary`].
+fn first_schema_version() -> u32 {
+    1
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct RunSummary {
+    #[serde(default = "first_schema_version")]
+    schema_version: u32,
     spec: RunSpec,
     artifacts: Option<MobileArtifacts>,
     local_report: Value,
     remote_run: Option<RemoteRun>,
+    /// Every BrowserStack build of the run when it started more than one:
+    /// per-device fallback builds and later `--repeat` rounds follow the
+    /// first, which is also `remote_run`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    remote_runs: Vec<RemoteRun>,
     summary: SummaryReport,
     #[serde(skip_serializing_if = "Option::is_none")]
     benchmark_results: Option<BTreeMap<String, Vec<Value>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     performance_metrics: Option<BTreeMap<String, browserstack::PerformanceMetrics>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device_meta: Option<BTreeMap<String, browserstack::DeviceMeta>>,
+    /// `bench_meta.json` of the build that was run, when mobench built it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    build_meta: Option<mobench_sdk::builders::BenchMeta>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -500,14 +1386,120 @@ struct SummaryReport {
     warmup: u32,
     devices: Vec<String>,
     device_summaries: Vec<DeviceSummary>,
+    /// Benchmarks with a CV above this percentage are flagged as noisy.
+    #[serde(default = "default_noisy_cv_pct")]
+    noisy_cv_threshold_pct: f64,
+    /// Benchmarks that exceeded a `[budgets]` limit from `mobench.toml`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    budget_violations: Vec<BudgetViolation>,
+    /// Devices re-scheduled by `--device-retries`, with the number of retries
+    /// each needed (or used up, if it never reported).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    device_retries: BTreeMap<String, u32>,
+    /// Device runs pooled into these stats by `--repeat`.
+    #[serde(default = "default_repeat_count")]
+    repeat_count: u32,
+    /// Devices dropped by `--keep-going` because they failed validation,
+    /// with the reason.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    skipped_devices: BTreeMap<String, String>,
+    /// Devices that failed during a `--no-fail-fast` run.
+    #[serde(default, skip_serializing_if = "DeviceFailures::is_empty")]
+    device_failures: DeviceFailures,
+    /// Whether each device the run scheduled reported results, for
+    /// `--resume-from`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    device_status: BTreeMap<String, DeviceStatus>,
+}
+
+/// Completion of one device in a run.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum DeviceStatus {
+    /// The device reported benchmark results.
+    Completed,
+    /// The device was scheduled (or meant to be) but reported nothing.
+    Missing,
+}
+
+/// Per-device failures that `--no-fail-fast` collected instead of aborting.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+struct DeviceFailures {
+    /// Devices that could not be scheduled, with the error.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    unscheduled: BTreeMap<String, String>,
+    /// Devices that were scheduled but returned no benchmark report, with the
+    /// reason.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    no_results: BTreeMap<String, String>,
+}
+
+impl DeviceFailures {
+    fn is_empty(&self) -> bool {
+        self.unscheduled.is_empty() && self.no_results.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.unscheduled.len() + self.no_results.len()
+    }
+
+    fn extend(&mut self, other: DeviceFailures) {
+        self.unscheduled.extend(other.unscheduled);
+        self.no_results.extend(other.no_results);
+    }
+}
+
+/// Default CV (percent) above which a benchmark is considered too noisy to trust.
+const DEFAULT_NOISY_CV_PCT: f64 = 15.0;
+
+fn default_noisy_cv_pct() -> f64 {
+    DEFAULT_NOISY_CV_PCT
+}
+
+fn default_repeat_count() -> u32 {
+    1
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct DeviceSummary {
     device: String,
+    /// Platform this device ran on, set by `summary --merge` when the merged
+    /// runs mix targets; otherwise [`SummaryReport::target`] applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<MobileTarget>,
+    /// Hardware and thermal context reported by BrowserStack for this device.
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    device_meta: browserstack::DeviceMeta,
+    /// Devices rolled up into this entry; empty for real devices.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    members: Vec<String>,
     benchmarks: Vec<BenchmarkStats>,
 }
 
+impl DeviceSummary {
+    /// Whether this is a `group:<label>` rollup rather than a real device.
+    fn is_group(&self) -> bool {
+        self.device.starts_with(GROUP_PREFIX)
+    }
+}
+
+impl SummaryReport {
+    /// The run's target, or every target of a merged summary that mixes them.
+    fn target_label(&self) -> String {
+        let mut targets = vec![self.target];
+        for target in self.device_summaries.iter().filter_map(|device| device.target) {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+        targets
+            .iter()
+            .map(|target| format!("{target:?}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct BenchmarkStats {
     function: String,
@@ -515,15 +1507,87 @@ struct BenchmarkStats {
     mean_ns: Option<u64>,
     median_ns: Option<u64>,
     p95_ns: Option<u64>,
+    /// Absent in summaries written before p99 was recorded.
+    #[serde(default)]
+    p99_ns: Option<u64>,
     min_ns: Option<u64>,
     max_ns: Option<u64>,
+    /// Coefficient of variation (stddev / mean) in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cv_pct: Option<f64>,
+    /// Median-of-means estimate, robust to the heavy tails of mobile timings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mom_ns: Option<u64>,
+    /// For group rollups, the member device with the slowest median.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    worst_device: Option<String>,
+    /// Warmup durations in execution order, when the report captured them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warmup_ns: Vec<u64>,
+    /// Half-width of the median's 95% confidence interval in percent, for
+    /// `--min-samples`/`--max-samples` runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    median_ci_pct: Option<f64>,
+    /// Median thread CPU time per iteration, when the runner measured it
+    /// (mobench-sdk's `cpu-time` feature).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cpu_median_ns: Option<u64>,
+    /// Custom metrics recorded with `timing::record_metric`, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metrics: BTreeMap<String, MetricStats>,
+    /// The runner stopped at `--per-benchmark-timeout`, so the stats cover
+    /// only the iterations that finished.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    timed_out: bool,
+    /// Effective core affinity from `--pin-cores`, or why pinning was denied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    affinity: Option<String>,
+    /// Coefficient of variation of the per-repeat medians in percent, for
+    /// `--repeat` runs: how far the repeats disagree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repeat_cv_pct: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Statistics of one custom metric, in the unit the benchmark recorded.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct MetricStats {
+    samples: usize,
+    mean: f64,
+    median: f64,
+    p95: f64,
+    p99: f64,
+    min: f64,
+    max: f64,
+    /// Coefficient of variation (stddev / mean) in percent.
+    cv_pct: f64,
+}
+
+impl BenchmarkStats {
+    fn is_noisy(&self, threshold_pct: f64) -> bool {
+        self.cv_pct.is_some_and(|cv| cv > threshold_pct)
+    }
+
+    /// Whether wall time is far above CPU time, i.e. the thread spent much
+    /// of each iteration waiting rather than running.
+    fn is_contended(&self) -> bool {
+        match (self.median_ns, self.cpu_median_ns) {
+            (Some(wall), Some(cpu)) => wall as f64 > cpu as f64 * CONTENTION_WALL_TO_CPU_RATIO,
+            _ => false,
+        }
+    }
+}
+
+/// Wall-to-CPU median ratio above which a benchmark is flagged as contended.
+const CONTENTION_WALL_TO_CPU_RATIO: f64 = 1.5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "platform", rename_all = "lowercase")]
 enum RemoteRun {
     Android {
         app_url: String,
+        /// Needed to re-schedule devices; absent in older run summaries.
+        #[serde(default)]
+        test_suite_url: String,
         build_id: String,
     },
     Ios {
@@ -533,47 +1597,397 @@ enum RemoteRun {
     },
 }
 
+impl RemoteRun {
+    /// A run scheduled through the SDK orchestrator, for `target`.
+    fn from_scheduled(target: MobileTarget, run: &orchestrate::ScheduledRun) -> Self {
+        let orchestrate::ScheduledRun {
+            app_url,
+            test_suite_url,
+            build_id,
+        } = run.clone();
+        match target {
+            MobileTarget::Android => RemoteRun::Android {
+                app_url,
+                test_suite_url,
+                build_id,
+            },
+            MobileTarget::Ios => RemoteRun::Ios {
+                app_url,
+                test_suite_url,
+                build_id,
+            },
+        }
+    }
+
+    /// The BrowserStack platform path segment and the run as the SDK sees it.
+    fn scheduled(&self) -> (&'static str, orchestrate::ScheduledRun) {
+        let (platform, app_url, test_suite_url, build_id) = match self {
+            RemoteRun::Android {
+                app_url,
+                test_suite_url,
+                build_id,
+            } => ("espresso", app_url, test_suite_url, build_id),
+            RemoteRun::Ios {
+                app_url,
+                test_suite_url,
+                build_id,
+            } => ("xcuitest", app_url, test_suite_url, build_id),
+        };
+        let run = orchestrate::ScheduledRun {
+            app_url: app_url.clone(),
+            test_suite_url: test_suite_url.clone(),
+            build_id: build_id.clone(),
+        };
+        (platform, run)
+    }
+}
+
 pub fn run() -> Result<()> {
     load_dotenv();
     let cli = Cli::parse();
+    logging::init(cli.log_format);
+    term::init(cli.no_color);
+    units::init(cli.unit);
     match cli.command {
         Command::Run {
             target,
             function,
+            all,
             iterations,
             warmup,
             devices,
+            profile,
+            device_strategy,
+            keep_going,
+            fail_fast: _,
+            no_fail_fast,
             config,
             output,
+            output_dir,
+            resume_from,
             summary_csv,
+            compact_json,
+            json_lines,
+            no_summary_json,
             local_only,
+            include_host,
             release,
+            cargo_profile,
             ios_app,
             ios_test_suite,
+            android_aab,
+            prebuilt_apk,
+            prebuilt_test_apk,
+            skip_build,
             fetch,
             fetch_output_dir,
+            fetch_compress,
             fetch_poll_interval_secs,
             fetch_timeout_secs,
+            fetch_concurrency,
+            max_run_time,
+            force,
+            device_retries,
+            repeat,
+            min_samples,
+            max_samples,
+            ci_target,
+            warmup_time,
+            measure_time,
+            per_benchmark_timeout,
+            per_iteration_timeout,
+            drop_first_n,
+            capture_warmup,
+            startup_metrics,
+            pin_cores,
+            sample_interleave,
+            order,
+            env_vars,
             progress,
+            baseline_store,
+            promote_on,
+            baseline_from_browserstack,
+            regression,
+            fail_on_regression,
+            ab_self,
+            noisy_cv_threshold,
+            fail_on_stale_build,
+            strict,
+            sample_out,
+            gbench_json,
+            since,
+            seed,
+            notify_webhook,
+            notify_format,
+            otlp_endpoint,
+            prometheus_out,
         } => {
-            let spec = resolve_run_spec(
+            // Fail before the run if export was requested from a build that cannot do it
+            #[cfg(not(feature = "metrics-export"))]
+            if otlp_endpoint.is_some() || prometheus_out.is_some() {
+                bail!(
+                    "--otlp-endpoint and --prometheus-out require mobench built with the `metrics-export` feature (cargo install mobench --features metrics-export)"
+                );
+            }
+            let root = repo_root()?;
+            let resolver = config::ConfigResolver::new().context("loading mobench.toml")?;
+            // Same precedence as `build`: the flag, then [project] output_dir
+            let output_dir = output_dir
+                .or_else(|| resolver.output_dir().map(Path::to_path_buf))
+                .unwrap_or_else(|| root.join("target/mobench"));
+            let profile = match profile.as_deref() {
+                Some(name) => resolver.profile(name)?,
+                None => config::RunProfile::default(),
+            };
+            // Profile tags only narrow the devices the profile selected
+            let devices_from_profile = devices.is_empty();
+            let devices = if devices_from_profile {
+                profile_devices(&profile)?
+            } else {
+                devices
+            };
+            let mut functions = if all {
+                discover_all_functions(&root)?
+            } else {
+                function
+            };
+            // A function's #[benchmark(sample_size, warmup)] ranks between
+            // explicit counts and the global default. A single function's is
+            // resolved here; a suite's are left to the device, per function
+            let explicit_iterations = iterations.or(profile.iterations);
+            let explicit_warmup = warmup.or(profile.warmup);
+            let bench_defaults = match functions.as_slice() {
+                [function] if config.is_none() => find_detected_benchmark(&root, function),
+                _ => None,
+            }
+            .unwrap_or_default();
+            let iterations = explicit_iterations
+                .or(bench_defaults.sample_size)
+                .unwrap_or_else(|| resolver.default_iterations());
+            let warmup = explicit_warmup
+                .or(bench_defaults.warmup)
+                .unwrap_or_else(|| resolver.default_warmup());
+            let mut spec = resolve_run_spec(
                 target,
-                function,
+                functions.first().cloned().unwrap_or_default(),
                 iterations,
                 warmup,
                 devices,
                 config.as_deref(),
                 ios_app,
                 ios_test_suite,
-                local_only,
+                // Dry runs and prebuilt runs must not package iOS artifacts
+                // while resolving the spec
+                local_only || skip_build || cli.dry_run,
                 release,
+                cargo_profile.as_deref(),
+                &output_dir,
             )?;
-            let summary_paths = resolve_summary_paths(output.as_deref())?;
-            let root = repo_root()?;
-            let output_dir = root.join("target/mobench");
+            spec.sampling = resolve_sampling_mode(min_samples, max_samples, &ci_target)?;
+            if let Some(raw) = measure_time.as_deref() {
+                let budget = parse_duration_arg("--measure-time", raw)?;
+                spec.sampling = Some(mobench_sdk::SamplingMode::FixedTime(budget));
+            }
+            if let Some(raw) = warmup_time.as_deref() {
+                let budget = parse_duration_arg("--warmup-time", raw)?;
+                spec.warmup_policy = Some(mobench_sdk::WarmupPolicy::FixedTime(budget));
+            }
+            spec.seed = seed;
+            if let Some(raw) = per_benchmark_timeout.as_deref() {
+                let budget = parse_duration_arg("--per-benchmark-timeout", raw)?;
+                // The runners take whole seconds; round up so a budget never shrinks to 0
+                spec.per_benchmark_timeout_secs = Some(budget.as_secs_f64().ceil().max(1.0) as u64);
+            }
+            spec.per_iteration_timeout_ms = per_iteration_timeout.as_deref().map(iteration_timeout_ms).transpose()?;
+            spec.drop_first_n = drop_first_n.unwrap_or(0);
+            spec.capture_warmup = capture_warmup;
+            if startup_metrics && spec.target != MobileTarget::Android {
+                bail!("--startup-metrics is only supported for --target android");
+            }
+            spec.startup_metrics = startup_metrics;
+            if !pin_cores.is_none() && spec.target != MobileTarget::Android {
+                bail!("--pin-cores is only supported for --target android (iOS does not expose thread affinity)");
+            }
+            spec.pin_cores = pin_cores;
+            spec.env = resolve_bench_env(resolver.env(), &env_vars)?;
+            let max_run_time = match max_run_time.as_deref() {
+                Some(raw) => parse_duration_arg("--max-run-time", raw)?,
+                None => Duration::from_secs(fetch_timeout_secs),
+            };
+            // A custom artifact directory also keeps the summary apart from other runs
+            let summary_paths = match output.as_deref().or(resume_from.as_deref()) {
+                None if output_dir != root.join("target/mobench") => {
+                    resolve_summary_paths(Some(&output_dir.join("run-summary.json")))?
+                }
+                output => resolve_summary_paths(output)?,
+            };
+            let local_devices = uses_local_devices(&spec.devices, resolver.backend_provider())?;
+
+            let _run_span = tracing::info_span!(
+                "run",
+                target = ?spec.target,
+                function = %spec.function,
+                devices = spec.devices.len()
+            )
+            .entered();
+
+            if let Some(git_ref) = since.as_deref() {
+                let changes = changed::ChangedFiles::since(&root, git_ref)?;
+                if config.is_some() {
+                    functions = vec![spec.function.clone()];
+                }
+                let (changed, unchanged): (Vec<String>, Vec<String>) = functions
+                    .into_iter()
+                    .partition(|function| function_changed(&root, function, &changes) != Some(false));
+                if changed.is_empty() {
+                    println!(
+                        "Note: {} unchanged since {}; skipping run.",
+                        unchanged.join(", "),
+                        changes.since
+                    );
+                    return Ok(());
+                }
+                if !unchanged.is_empty() {
+                    println!(
+                        "Note: skipping {} (unchanged since {})",
+                        unchanged.join(", "),
+                        changes.since
+                    );
+                }
+                functions = changed;
+                spec.function = functions[0].clone();
+            }
+            // A config file names a single function
+            if config.is_none() && functions.len() > 1 {
+                spec.order = order.seeded();
+                spec.order.arrange(&mut functions, String::as_str);
+                spec.function = functions[0].clone();
+                spec.functions = functions;
+                spec.benchmark_defaults = suite_benchmark_defaults(
+                    &root,
+                    &spec.functions,
+                    explicit_iterations.is_none() && resolver.default_iterations() == mobench_sdk::runner::DEFAULT_ITERATIONS,
+                    explicit_warmup.is_none() && resolver.default_warmup() == mobench_sdk::runner::DEFAULT_WARMUP,
+                );
+            }
+            if let Some(n) = drop_first_n {
+                let fixed = matches!(spec.sampling, None | Some(mobench_sdk::SamplingMode::FixedIterations));
+                let fewest = spec.all_functions().into_iter().map(|function| spec.iterations_for(function)).min();
+                if fixed && let Some(fewest) = fewest.filter(|&fewest| n >= fewest) {
+                    bail!(
+                        "--drop-first-n {} would discard all {} iterations; raise --iterations",
+                        n,
+                        fewest
+                    );
+                }
+            }
+            if include_host {
+                check_host_benchmarks_linked(&spec)?;
+            }
+            if ab_self {
+                // Both passes run back to back in the same app
+                let pass: Vec<String> = spec.all_functions().into_iter().map(str::to_string).collect();
+                spec.functions = [pass.clone(), pass].concat();
+            }
+            if sample_interleave && spec.functions.is_empty() {
+                bail!("--sample-interleave needs more than one function; pass several --function values or --all");
+            }
+            spec.sample_interleave = sample_interleave;
+            if repeat > 1 && (spec.devices.is_empty() || local_only) {
+                bail!("--repeat needs devices to run on; pass --devices without --local-only");
+            }
+            if repeat > 1 && !fetch && !local_devices {
+                // Each BrowserStack round starts once the previous one finished
+                bail!("--repeat on BrowserStack needs --fetch to start each round after the previous one");
+            }
+            let resumed = match resume_from.as_deref() {
+                Some(path) => {
+                    let previous = load_run_summary(path)?;
+                    check_resumable(&previous.spec, &spec)
+                        .with_context(|| format!("cannot resume {:?}", path))?;
+                    // Replay the earlier run's order, shuffled or not
+                    spec.function = previous.spec.function.clone();
+                    spec.functions = previous.spec.functions.clone();
+                    spec.order = previous.spec.order;
+                    let missing = missing_devices(&previous);
+                    if missing.is_empty() {
+                        println!("Every device in {:?} already reported; nothing to resume.", path);
+                        return Ok(());
+                    }
+                    println!(
+                        "Resuming {:?}: running the {} device(s) without results: {}",
+                        path,
+                        missing.len(),
+                        missing.join(", ")
+                    );
+                    spec.devices = missing;
+                    Some(previous)
+                }
+                None => None,
+            };
+
+            let narrow_devices = device_strategy != DeviceStrategy::All
+                && !spec.devices.is_empty()
+                && !local_only
+                && !local_devices;
+            let tag_candidates = if narrow_devices {
+                device_tag_candidates(config.as_deref(), devices_from_profile.then_some(&profile))?
+            } else {
+                Vec::new()
+            };
+            if cli.dry_run {
+                if narrow_devices {
+                    apply_device_strategy(&mut spec, device_strategy, &tag_candidates, &BTreeSet::new())?;
+                }
+                print_run_dry_run_plan(
+                    &spec,
+                    release,
+                    cargo_profile.as_deref(),
+                    local_only,
+                    skip_build,
+                    local_devices,
+                    &output_dir,
+                    &summary_paths,
+                )?;
+                if repeat > 1 {
+                    println!();
+                    println!("Would run on the devices {} times and pool the samples per device and function", repeat);
+                }
+                if let Some(path) = &json_lines {
+                    println!();
+                    println!("Would stream device reports to {:?} as JSON Lines", path);
+                }
+                if let Some(url) = &notify_webhook {
+                    let payload = run_notification(&spec, &[], &Ok(())).payload(notify_format);
+                    println!();
+                    println!("Would POST to {}:", url);
+                    println!("{}", serde_json::to_string_pretty(&payload)?);
+                }
+                return Ok(());
+            }
 
-            // Validate device specs early to catch errors before building (C2: Device validation)
             if !spec.devices.is_empty() && !local_only {
+                check_projected_run_time(&spec, max_run_time, force)?;
+            }
+            if no_summary_json && !spec.device_groups.is_empty() {
+                bail!(
+                    "--no-summary-json keeps only per-device statistics, so it cannot roll up the device groups in the device matrix; drop --no-summary-json or the groups"
+                );
+            }
+            let retain = if no_summary_json {
+                json_lines::Retain::Stats(spec.sampling.clone())
+            } else {
+                json_lines::Retain::Full
+            };
+            let json_lines = json_lines
+                .as_deref()
+                .map(|path| json_lines::JsonLinesWriter::create(path).map(|writer| writer.retaining(retain)))
+                .transpose()?;
+
+            // Validate device specs early to catch errors before building (C2: Device validation)
+            let mut unavailable = BTreeSet::new();
+            let mut skipped_devices = BTreeMap::new();
+            if !spec.devices.is_empty() && !local_only && !local_devices {
                 if let Ok(creds) = resolve_browserstack_credentials(spec.browserstack.as_ref()) {
                     let client = BrowserStackClient::new(
                         BrowserStackAuth {
@@ -582,6 +1996,7 @@ pub fn run() -> Result<()> {
                         },
                         creds.project,
                     )?;
+                    let client = with_device_cache(client, cli.refresh_devices)?;
 
                     let platform_str = match spec.target {
                         MobileTarget::Android => Some("android"),
@@ -605,30 +2020,57 @@ pub fn run() -> Result<()> {
                         }
                         println!();
                         println!("Use 'cargo mobench devices' to see available devices.");
-                        bail!(
-                            "{} of {} device specs are invalid. Fix them before running.",
-                            validation.invalid.len(),
+                        if !keep_going {
+                            bail!(MobenchError::DeviceInvalid(format!(
+                                "{} of {} device specs are invalid. Fix them before running.",
+                                validation.invalid.len(),
+                                spec.devices.len()
+                            )));
+                        }
+                        skipped_devices = drop_invalid_devices(&mut spec, &validation.invalid)?;
+                        println!(
+                            "Warning: --keep-going: skipping {} invalid device(s), continuing with {}",
+                            skipped_devices.len(),
                             spec.devices.len()
                         );
+                    } else {
+                        println!("  All {} device(s) validated successfully.", validation.valid.len());
                     }
-                    println!("  All {} device(s) validated successfully.", validation.valid.len());
+                    unavailable.extend(validation.unavailable);
                 }
             }
+            if narrow_devices {
+                apply_device_strategy(&mut spec, device_strategy, &tag_candidates, &unavailable)?;
+            }
 
             // Print resolved spec summary (A5: Better CLI output)
             if !progress {
                 println!();
                 println!("=== Benchmark Run Configuration ===");
                 println!("  Target:      {:?}", spec.target);
-                println!("  Function:    {}", spec.function);
-                println!("  Iterations:  {}", spec.iterations);
-                println!("  Warmup:      {}", spec.warmup);
+                if spec.functions.is_empty() {
+                    println!("  Function:    {}", spec.function);
+                } else {
+                    println!("  Functions:   {}", spec.functions.join(", "));
+                }
+                let (iterations, warmup) = describe_counts(&spec);
+                println!("  Iterations:  {}", iterations);
+                println!("  Warmup:      {}", warmup);
                 println!("  Profile:     {}", if release { "release" } else { "debug" });
+                if let Some(name) = cargo_profile.as_deref() {
+                    println!("  Cargo:       --profile {}", name);
+                }
                 if !spec.devices.is_empty() {
                     println!("  Devices:     {}", spec.devices.join(", "));
                 } else {
                     println!("  Devices:     (none - local build only)");
                 }
+                if repeat > 1 {
+                    println!("  Repeats:     {}", repeat);
+                }
+                if !spec.env.is_empty() {
+                    println!("  Env:         {}", describe_env(&spec.env));
+                }
                 println!();
 
                 // Print artifact locations
@@ -648,7 +2090,14 @@ pub fn run() -> Result<()> {
                         }
                     }
                 }
-                println!("  JSON summary:    {}", summary_paths.json.display());
+                if no_summary_json {
+                    println!("  JSON summary:    (skipped, --no-summary-json)");
+                } else {
+                    println!("  JSON summary:    {}", summary_paths.json.display());
+                }
+                if let Some(writer) = &json_lines {
+                    println!("  JSON Lines:      {}", writer.path().display());
+                }
                 println!("  Markdown:        {}", summary_paths.markdown.display());
                 if summary_csv {
                     println!("  CSV:             {}", summary_paths.csv.display());
@@ -658,17 +2107,26 @@ pub fn run() -> Result<()> {
 
             // A2: Validate that the requested benchmark function exists (if we can detect it)
             if !progress {
-                validate_benchmark_function(&root, &spec.function)?;
+                for function in spec.all_functions() {
+                    validate_benchmark_function(&root, function)?;
+                }
             }
 
-            // Persist the spec and metadata to mobile app bundles
+            // Persist the spec and metadata to mobile app bundles. Prebuilt
+            // artifacts already embed the spec they were built with.
             if progress {
                 println!("[1/4] Preparing benchmark spec...");
             }
-            persist_mobile_spec(&spec, release)?;
-
-            // Skip local smoke test - sample-fns uses direct dispatch, not inventory registry
-            // Benchmarks will run on the actual mobile device
+            if skip_build {
+                println!(
+                    "Note: --skip-build set; the prebuilt artifacts run the bench_spec.json embedded when they were built"
+                );
+            } else {
+                persist_mobile_spec(&spec, release, &output_dir)?;
+            }
+
+            // Skip local smoke test - sample-fns uses direct dispatch, not inventory registry
+            // Benchmarks will run on the actual mobile device
             if !progress {
                 println!("Skipping local smoke test - benchmarks will run on mobile device");
             }
@@ -676,7 +2134,19 @@ pub fn run() -> Result<()> {
                 "skipped": true,
                 "reason": "Local smoke test disabled - benchmarks run on mobile device only"
             });
+            let fail_fast = !no_fail_fast;
             let mut remote_run = None;
+            let mut started = orchestrate::StartedRuns::default();
+            // Builds of the second and later `--repeat` rounds, started one at
+            // a time by `start_repeat` while fetching
+            let mut repeat_starts = Vec::new();
+            let mut start_repeat: Option<Box<dyn Fn() -> Result<orchestrate::StartedRuns>>> = None;
+            let mut device_failures = DeviceFailures::default();
+            let mut local_results = None;
+            let local_backend = local_device::LocalDeviceBackend::new(
+                spec.target,
+                Duration::from_secs(fetch_timeout_secs),
+            );
             let artifacts = if local_only {
                 if !progress {
                     println!("Skipping mobile build: --local-only set");
@@ -685,54 +2155,149 @@ pub fn run() -> Result<()> {
             } else {
                 match spec.target {
                     MobileTarget::Android => {
-                        if progress {
-                            println!("[2/4] Building Android APK...");
+                        let (apk, test_suite_path) = if skip_build {
+                            if progress {
+                                println!("[2/4] Using prebuilt Android artifacts...");
+                            } else {
+                                println!("Skipping Android build: --skip-build set");
+                            }
+                            let (apk, test_apk) = prebuilt_android_artifacts(
+                                prebuilt_apk,
+                                prebuilt_test_apk,
+                                android_aab,
+                                !spec.devices.is_empty(),
+                            )?;
+                            (apk, test_apk)
                         } else {
-                            println!("Building for Android...");
-                            println!("  Building Rust library for Android targets...");
-                        }
-                        let ndk = std::env::var("ANDROID_NDK_HOME").context(
-                            "ANDROID_NDK_HOME must be set for Android builds. Example: export ANDROID_NDK_HOME=$ANDROID_SDK_ROOT/ndk/<version>",
-                        )?;
-                        let build = run_android_build(&ndk, release)?;
-                        let apk = build.app_path;
-                        if !progress {
-                            println!("\u{2713} Built Android APK at {:?}", apk);
-                        }
+                            if progress {
+                                println!("[2/4] Building Android APK...");
+                            } else {
+                                println!("Building for Android...");
+                                println!("  Building Rust library for Android targets...");
+                            }
+                            let ndk = std::env::var("ANDROID_NDK_HOME").context(
+                                "ANDROID_NDK_HOME must be set for Android builds. Example: export ANDROID_NDK_HOME=$ANDROID_SDK_ROOT/ndk/<version>",
+                            )?;
+                            let build =
+                                run_android_build(&ndk, release, cargo_profile.as_deref(), &output_dir, false)?;
+                            let mut apk = build.app_path;
+                            if !progress {
+                                println!("{} Built Android APK at {:?}", term::ok(), apk);
+                            }
+                            if let Some(aab) = android_aab {
+                                if !progress {
+                                    println!("Using Android App Bundle {:?} instead of the built APK", aab);
+                                }
+                                apk = aab;
+                            }
+                            (apk, build.test_suite_path)
+                        };
                         if spec.devices.is_empty() {
                             if !progress {
                                 println!("Skipping BrowserStack upload/run: no devices provided");
                             }
                             Some(MobileArtifacts::Android { apk })
+                        } else if local_devices {
+                            if progress {
+                                println!("[3/4] Running on local devices...");
+                            }
+                            let test_apk = test_suite_path.as_ref().with_context(|| format!(
+                                "Android test suite APK missing. Run `cargo mobench build --target android` or `{} assembleDebugAndroidTest` in {}",
+                                if cfg!(windows) { "gradlew.bat" } else { "./gradlew" },
+                                output_dir.join("android").display()
+                            ))?;
+                            let app_id = local_device::android_application_id(
+                                &output_dir.join("android").join("app").join("build.gradle"),
+                            )?;
+                            let devices = local_backend.select(&spec.devices)?;
+                            local_results = Some(run_local_repeats(
+                                &local_backend,
+                                &devices,
+                                &local_device::LocalArtifacts::Android {
+                                    apk: &apk,
+                                    test_apk,
+                                    app_id: &app_id,
+                                },
+                                fail_fast,
+                                repeat,
+                                &mut device_failures,
+                                json_lines.as_ref(),
+                            )?);
+                            Some(MobileArtifacts::Android { apk })
                         } else {
                             if progress {
                                 println!("[3/4] Uploading to BrowserStack...");
                             }
-                            let test_apk = build.test_suite_path.as_ref().context(
-                                "Android test suite APK missing. Run `cargo mobench build --target android` or `./gradlew assembleDebugAndroidTest` in target/mobench/android",
-                            )?;
-                            let run = trigger_browserstack_espresso(&spec, &apk, test_apk)?;
-                            remote_run = Some(run);
+                            let test_apk = test_suite_path.as_ref().with_context(|| format!(
+                                "Android test suite APK missing. Run `cargo mobench build --target android` or `{} assembleDebugAndroidTest` in {}",
+                                if cfg!(windows) { "gradlew.bat" } else { "./gradlew" },
+                                output_dir.join("android").display()
+                            ))?;
+                            let uploaded;
+                            (uploaded, started) =
+                                trigger_browserstack_espresso(&spec, &apk, test_apk, fail_fast)?;
+                            if repeat > 1 {
+                                let (client, spec) = (browserstack_client(&spec)?, spec.clone());
+                                start_repeat = Some(Box::new(move || {
+                                    schedule_browserstack_run(&client, &spec, "espresso", &uploaded, fail_fast)
+                                }));
+                            }
+                            remote_run = started
+                                .runs
+                                .first()
+                                .map(|(run, _)| RemoteRun::from_scheduled(MobileTarget::Android, run));
                             Some(MobileArtifacts::Android { apk })
                         }
                     }
                     MobileTarget::Ios => {
-                        if progress {
-                            println!("[2/4] Building iOS xcframework...");
+                        let (xcframework, header) = if skip_build {
+                            if progress {
+                                println!("[2/4] Using prebuilt iOS artifacts...");
+                            } else {
+                                println!("Skipping iOS build: --skip-build set");
+                            }
+                            let xcui = spec.ios_xcuitest.as_ref().context(
+                                "--skip-build for iOS requires prebuilt artifacts; provide --ios-app and --ios-test-suite or set ios_xcuitest in the config",
+                            )?;
+                            validate_artifacts_for_browserstack(MobileTarget::Ios, None, None, Some(xcui))?;
+                            (None, None)
                         } else {
-                            println!("Building for iOS...");
-                            println!("  Building Rust library for iOS targets...");
-                        }
-                        let (xcframework, header) = run_ios_build(release)?;
-                        if !progress {
-                            println!("\u{2713} Built iOS xcframework at {:?}", xcframework);
-                        }
+                            if progress {
+                                println!("[2/4] Building iOS xcframework...");
+                            } else {
+                                println!("Building for iOS...");
+                                println!("  Building Rust library for iOS targets...");
+                            }
+                            let (xcframework, header) =
+                                run_ios_build(release, cargo_profile.as_deref(), &output_dir, false)?;
+                            if !progress {
+                                println!("{} Built iOS xcframework at {:?}", term::ok(), xcframework);
+                            }
+                            (Some(xcframework), Some(header))
+                        };
                         let ios_xcuitest = spec.ios_xcuitest.clone();
 
                         if spec.devices.is_empty() {
                             if !progress {
                                 println!("Skipping BrowserStack upload/run: no devices provided");
                             }
+                        } else if local_devices {
+                            if progress {
+                                println!("[3/4] Running on local devices...");
+                            }
+                            let xcui = spec.ios_xcuitest.as_ref().context(
+                                "an iOS app is required for local devices; provide --ios-app and --ios-test-suite or set ios_xcuitest in the config",
+                            )?;
+                            let devices = local_backend.select(&spec.devices)?;
+                            local_results = Some(run_local_repeats(
+                                &local_backend,
+                                &devices,
+                                &local_device::LocalArtifacts::Ios { app: &xcui.app },
+                                fail_fast,
+                                repeat,
+                                &mut device_failures,
+                                json_lines.as_ref(),
+                            )?);
                         } else {
                             if progress {
                                 println!("[3/4] Uploading to BrowserStack...");
@@ -740,22 +2305,45 @@ pub fn run() -> Result<()> {
                             let xcui = spec.ios_xcuitest.as_ref().context(
                                 "iOS XCUITest artifacts required when targeting BrowserStack devices; provide --ios-app and --ios-test-suite or set ios_xcuitest in the config",
                             )?;
-                            let run = trigger_browserstack_xcuitest(&spec, xcui)?;
-                            remote_run = Some(run);
+                            let uploaded;
+                            (uploaded, started) = trigger_browserstack_xcuitest(&spec, xcui, fail_fast)?;
+                            if repeat > 1 {
+                                let (client, spec) = (browserstack_client(&spec)?, spec.clone());
+                                start_repeat = Some(Box::new(move || {
+                                    schedule_browserstack_run(&client, &spec, "xcuitest", &uploaded, fail_fast)
+                                }));
+                            }
+                            remote_run = started
+                                .runs
+                                .first()
+                                .map(|(run, _)| RemoteRun::from_scheduled(MobileTarget::Ios, run));
                         }
 
+                        // BrowserStack App Automate has no dSYM upload, so the zip
+                        // is kept with the run artifacts for post-mortem symbolication.
+                        // Prebuilt runs leave the dSYM to the job that built the app.
+                        let dsym = ios_xcuitest
+                            .as_ref()
+                            .filter(|_| !skip_build)
+                            .and_then(|_| package_ios_dsym(&output_dir));
                         Some(MobileArtifacts::Ios {
                             xcframework,
                             header,
                             app: ios_xcuitest.as_ref().map(|a| a.app.clone()),
                             test_suite: ios_xcuitest.map(|a| a.test_suite),
+                            dsym,
                         })
                     }
                 }
             };
 
+            device_failures
+                .unscheduled
+                .extend(std::mem::take(&mut started.unscheduled));
+
             let summary_placeholder = empty_summary(&spec);
             let mut run_summary = RunSummary {
+                schema_version: RUN_SUMMARY_SCHEMA_VERSION,
                 spec,
                 artifacts,
                 local_report,
@@ -763,8 +2351,25 @@ pub fn run() -> Result<()> {
                 summary: summary_placeholder,
                 benchmark_results: None,
                 performance_metrics: None,
+                device_meta: None,
+                build_meta: None,
+                remote_runs: Vec::new(),
             };
+            // Prebuilt apps carry their own metadata, echoed in device reports
+            if !local_only && !skip_build {
+                let meta_path = bench_meta_path(&output_dir, run_summary.spec.target);
+                if meta_path.exists() {
+                    run_summary.build_meta = Some(load_bench_meta(&meta_path)?);
+                }
+            }
+            if let Some(results) = local_results {
+                // Record the devices that actually ran rather than `local`
+                run_summary.spec.devices = results.keys().cloned().collect();
+                run_summary.benchmark_results = Some(results);
+            }
 
+            let mut stale_reports = 0;
+            let mut retried_devices = BTreeMap::new();
             if fetch && let Some(remote) = &run_summary.remote_run {
                 let build_id = match remote {
                     RemoteRun::Android { build_id, .. } => build_id,
@@ -778,12 +2383,8 @@ pub fn run() -> Result<()> {
                         access_key: creds.access_key,
                     },
                     creds.project,
-                )?;
-
-                let platform = match run_summary.spec.target {
-                    MobileTarget::Android => "espresso",
-                    MobileTarget::Ios => "xcuitest",
-                };
+                )?
+                .with_fetch_concurrency(fetch_concurrency);
 
                 let dashboard_url = format!(
                     "https://app-automate.browserstack.com/dashboard/v2/builds/{}",
@@ -793,15 +2394,48 @@ pub fn run() -> Result<()> {
                 println!("Waiting for build {} to complete...", build_id);
                 println!("Dashboard: {}", dashboard_url);
 
-                match client.wait_and_fetch_all_results_with_poll(
-                    build_id,
-                    platform,
-                    Some(fetch_timeout_secs),
-                    Some(fetch_poll_interval_secs),
+                match fetch_repeated_runs(
+                    &client,
+                    remote.scheduled().0,
+                    &started.runs,
+                    Repeats {
+                        count: repeat,
+                        start: start_repeat.as_deref(),
+                        started: &mut repeat_starts,
+                    },
+                    &RunOptions {
+                        timeout: Duration::from_secs(fetch_timeout_secs),
+                        poll_interval: Duration::from_secs(fetch_poll_interval_secs),
+                        device_retries,
+                        fail_fast,
+                    },
+                    json_lines.as_ref(),
                 ) {
-                    Ok((bench_results, perf_metrics)) => {
+                    Ok(orchestrate::RunSummary {
+                        results:
+                            orchestrate::DeviceResults {
+                                benchmarks: bench_results,
+                                metrics: perf_metrics,
+                                device_meta,
+                            },
+                        retried,
+                        missing,
+                        unscheduled,
+                        ..
+                    }) => {
+                        retried_devices = retried;
+                        if !fail_fast {
+                            for device in missing.into_iter().filter(|device| !unscheduled.contains_key(device)) {
+                                device_failures
+                                    .no_results
+                                    .insert(device, "no benchmark report".to_string());
+                            }
+                        }
+                        // Fails the run once the summary is written, even with fail-fast
+                        device_failures.unscheduled.extend(unscheduled);
                         println!(
-                            "\n✓ Successfully fetched results from {} device(s)",
+                            "\n{} Successfully fetched results from {} device(s)",
+                            term::ok(),
                             bench_results.len()
                         );
 
@@ -847,8 +2481,33 @@ pub fn run() -> Result<()> {
                         }
 
                         println!("\n  View full results: {}", dashboard_url);
-                        run_summary.benchmark_results = Some(bench_results.into_iter().collect());
-                        run_summary.performance_metrics = Some(perf_metrics.into_iter().collect());
+                        let meta_path = bench_meta_path(&output_dir, run_summary.spec.target);
+                        if meta_path.exists() {
+                            let local_meta = load_bench_meta(&meta_path)?;
+                            stale_reports = check_stale_reports(
+                                &local_meta,
+                                bench_results.iter().flat_map(|(device, results)| {
+                                    results.iter().map(move |report| (device.as_str(), report))
+                                }),
+                            );
+                        }
+                        run_summary.benchmark_results = Some(bench_results);
+                        run_summary.performance_metrics = Some(perf_metrics);
+                        if !device_meta.is_empty() {
+                            run_summary.device_meta = Some(device_meta);
+                        }
+                    }
+                    Err(e) if e.is::<browserstack::WaitCancelled>() => {
+                        println!("\nStopped waiting; build {} keeps running on BrowserStack.", build_id);
+                        println!("Dashboard: {}", dashboard_url);
+                        let target = match run_summary.spec.target {
+                            MobileTarget::Android => "android",
+                            MobileTarget::Ios => "ios",
+                        };
+                        for (run, _) in started.runs.iter().chain(repeat_starts.iter().flat_map(|start| &start.runs)) {
+                            println!("Resume with: cargo mobench fetch --target {} --build-id {}", target, run.build_id);
+                        }
+                        return Err(e);
                     }
                     Err(e) => {
                         println!("\nWarning: Failed to fetch results: {}", e);
@@ -856,46 +2515,230 @@ pub fn run() -> Result<()> {
                     }
                 }
 
-                // Also save detailed artifacts to separate directory
-                let output_root = fetch_output_dir.join(build_id);
-                if let Err(e) = fetch_browserstack_artifacts(
-                    &client,
-                    run_summary.spec.target,
-                    build_id,
-                    &output_root,
-                    false, // Don't wait again, we already did
-                    fetch_poll_interval_secs,
-                    fetch_timeout_secs,
-                ) {
-                    println!("Warning: Failed to fetch detailed artifacts: {}", e);
+                // Also save detailed artifacts to separate directories
+                for (run, _) in started.runs.iter().chain(repeat_starts.iter().flat_map(|s| &s.runs)) {
+                    let output_root = fetch_output_dir.join(&run.build_id);
+                    if let Err(e) = fetch_browserstack_artifacts(
+                        &client,
+                        run_summary.spec.target,
+                        &run.build_id,
+                        &output_root,
+                        false, // Don't wait again, we already did
+                        fetch_poll_interval_secs,
+                        fetch_timeout_secs,
+                        None, // Reports were already checked against the local build above
+                        fetch_compress,
+                    ) {
+                        println!("Warning: Failed to fetch detailed artifacts: {}", e);
+                    }
                 }
-            } else if fetch {
+            } else if fetch && run_summary.benchmark_results.is_none() {
                 println!("No BrowserStack run to fetch (devices not provided?)");
             }
+            let builds: Vec<&orchestrate::ScheduledRun> = started
+                .runs
+                .iter()
+                .chain(repeat_starts.iter().flat_map(|start| &start.runs))
+                .map(|(run, _)| run)
+                .collect();
+            if builds.len() > 1 {
+                let target = run_summary.spec.target;
+                run_summary.remote_runs =
+                    builds.into_iter().map(|run| RemoteRun::from_scheduled(target, run)).collect();
+            }
+            // Later rounds only fail a device that no round reported
+            for repeat_start in &mut repeat_starts {
+                for (device, reason) in std::mem::take(&mut repeat_start.unscheduled) {
+                    let reported = run_summary
+                        .benchmark_results
+                        .iter()
+                        .flat_map(BTreeMap::keys)
+                        .any(|reported| device_names_match(&device, reported));
+                    if !reported {
+                        device_failures.unscheduled.entry(device).or_insert(reason);
+                    }
+                }
+            }
+
+            if let Some(previous) = resumed {
+                merge_resumed_run(&mut run_summary, previous);
+            }
+            if include_host {
+                add_host_results(&mut run_summary);
+            }
+            redact_env(&mut run_summary);
+            let ab_second_pass = ab_self.then(|| split_ab_self(&mut run_summary));
 
             run_summary.summary = build_summary(&run_summary)?;
-            write_summary(&run_summary, &summary_paths, summary_csv)?;
+            run_summary.summary.noisy_cv_threshold_pct = noisy_cv_threshold;
+            run_summary.summary.device_retries = retried_devices;
+            run_summary.summary.repeat_count = repeat;
+            run_summary.summary.skipped_devices = skipped_devices;
+            run_summary.summary.device_failures = device_failures;
+            run_summary.summary.device_status = device_status(
+                &run_summary.spec.devices,
+                run_summary.benchmark_results.as_ref(),
+                &run_summary.summary.device_failures,
+            );
+            run_summary.summary.budget_violations =
+                check_budgets(&load_budgets()?, summary_budget_entries(&run_summary.summary));
+            write_summary(
+                &run_summary,
+                &summary_paths,
+                SummaryFiles {
+                    json: !no_summary_json,
+                    csv: summary_csv,
+                    compact_json,
+                },
+            )?;
+            if let Some(path) = &sample_out {
+                let results = json!({ "benchmark_results": run_summary.benchmark_results });
+                sample_out::write_sample_out(path, &sample_out::collect_raw_samples(&results))?;
+            }
+            if let Some(path) = &gbench_json {
+                let summary = json!({ "summary": run_summary.summary });
+                write_gbench_json(path, &extract_summary_data(&summary)?)?;
+            }
+            #[cfg(feature = "metrics-export")]
+            metrics_export::export(
+                &run_summary,
+                metrics_export::ExportTargets {
+                    otlp_endpoint: otlp_endpoint.as_deref(),
+                    prometheus_out: prometheus_out.as_deref(),
+                },
+            )?;
 
             // Print clear completion summary
             println!();
-            println!("\u{2713} Benchmark complete!");
+            println!("{} Benchmark complete!", term::ok());
             println!();
             println!("Results saved to:");
-            println!("  * {} (machine-readable)", summary_paths.json.display());
+            if !no_summary_json {
+                println!("  * {} (machine-readable)", summary_paths.json.display());
+            }
+            if let Some(writer) = &json_lines {
+                println!("  * {} ({} device report(s), one per line)", writer.path().display(), writer.records());
+            }
             println!("  * {} (human-readable)", summary_paths.markdown.display());
             if summary_csv {
                 println!("  * {} (spreadsheet)", summary_paths.csv.display());
             }
-            println!();
-            println!("View results: cat {} | jq '.summary'", summary_paths.json.display());
+            if !no_summary_json {
+                println!();
+                println!("View results: cat {} | jq '.summary'", summary_paths.json.display());
+            }
+            let ab_findings = match ab_second_pass {
+                Some(second_pass) => report_ab_self(&run_summary, second_pass, &summary_paths.json, &regression)?,
+                None => Vec::new(),
+            };
+            if !run_summary.summary.device_failures.is_empty() {
+                println!();
+                println!("Device outcomes:");
+                for line in device_outcome_lines(&run_summary.summary) {
+                    println!("  {}", line);
+                }
+            }
+
+            // The gates decide the exit status, which the notification reports
+            let mut findings = Vec::new();
+            let verdict = (|| -> Result<()> {
+                let store = baseline_store.map(baseline_store::BaselineStore::new);
+                if let Some(store) = &store {
+                    let suspicious;
+                    (findings, suspicious) =
+                        compare_with_baseline_store(store, &run_summary, &summary_paths.json, &regression)?;
+                    if fail_on_regression && !findings.is_empty() {
+                        bail!(MobenchError::ThresholdExceeded(format!(
+                            "{} regression(s) against the stored baseline exceeded the {} threshold",
+                            findings.len(),
+                            regression.describe()
+                        )));
+                    }
+                    fail_on_suspicious_improvements(&suspicious)?;
+                }
+                if let Some(build_id) = &baseline_from_browserstack {
+                    let baseline_path = fetch_browserstack_baseline(
+                        &run_summary.spec,
+                        build_id,
+                        &output_dir.join("baselines"),
+                        fetch_timeout_secs,
+                        fetch_poll_interval_secs,
+                    )?;
+                    println!();
+                    println!("Comparing against BrowserStack build {} ({:?})", build_id, baseline_path);
+                    let suspicious;
+                    (findings, suspicious) =
+                        compare_against_baseline(&baseline_path, &summary_paths.json, &regression)?;
+                    if fail_on_regression && !findings.is_empty() {
+                        bail!(MobenchError::ThresholdExceeded(format!(
+                            "{} regression(s) against BrowserStack build {} exceeded the {} threshold",
+                            findings.len(),
+                            build_id,
+                            regression.describe()
+                        )));
+                    }
+                    fail_on_suspicious_improvements(&suspicious)?;
+                }
+                if fail_on_regression && !ab_findings.is_empty() {
+                    bail!(MobenchError::ThresholdExceeded(format!(
+                        "{} spurious regression(s) between the two --ab-self passes exceeded the {} threshold",
+                        ab_findings.len(),
+                        regression.describe()
+                    )));
+                }
+
+                if fail_on_stale_build && stale_reports > 0 {
+                    bail!(MobenchError::StaleBuild(format!(
+                        "{} device report(s) came from a different build than the local one",
+                        stale_reports
+                    )));
+                }
+                fail_on_device_failures(&run_summary.summary.device_failures)?;
+                if strict {
+                    fail_on_sample_shortfalls(&sample_shortfalls(&run_summary.spec, &run_summary.summary))?;
+                }
+                fail_on_budget_violations(&run_summary.summary.budget_violations)?;
+
+                // Only a run that passed every gate may become the baseline
+                if let (Some(store), Some(branch)) = (&store, &promote_on) {
+                    promote_to_baseline_store(store, &run_summary, &summary_paths.json, &root, branch, &findings)?;
+                }
+                Ok(())
+            })();
+            // A/A findings measure noise between identical passes, not the
+            // baseline the promotion above compared against, so they join late
+            findings.extend(ab_findings);
+            if let Some(url) = &notify_webhook {
+                let notification = run_notification(&run_summary.spec, &findings, &verdict);
+                match notify::send(url, &notification.payload(notify_format)) {
+                    Ok(()) => println!("Sent run notification to {}", url),
+                    Err(e) => println!("Warning: Failed to send run notification: {:#}", e),
+                }
+            }
+            verdict?;
         }
         Command::Init { output, target } => {
             write_config_template(&output, target)?;
             println!("Wrote starter config to {:?}", output);
         }
-        Command::Plan { output } => {
-            write_device_matrix_template(&output)?;
-            println!("Wrote sample device matrix to {:?}", output);
+        Command::Plan {
+            output,
+            from_browserstack,
+            platform,
+            top,
+            yes,
+        } => {
+            if !yes && output.exists() {
+                bail!("refusing to overwrite existing file: {:?} (pass --yes to replace it)", output);
+            }
+            ensure_parent_dir(&output)?;
+            if from_browserstack {
+                let count = write_browserstack_device_matrix(&output, platform, top, cli.refresh_devices)?;
+                println!("Wrote {} BrowserStack device(s) to {:?}", count, output);
+            } else {
+                write_device_matrix_template(&output)?;
+                println!("Wrote sample device matrix to {:?}", output);
+            }
         }
         Command::Fetch {
             target,
@@ -904,82 +2747,237 @@ pub fn run() -> Result<()> {
             wait,
             poll_interval_secs,
             timeout_secs,
+            bench_meta,
+            fail_on_stale_build,
+            extract_only,
+            compress,
         } => {
-            let creds = resolve_browserstack_credentials(None)?;
-            let client = BrowserStackClient::new(
-                BrowserStackAuth {
-                    username: creds.username,
-                    access_key: creds.access_key,
-                },
-                creds.project,
-            )?;
-            let output_root = output_dir.join(&build_id);
-            fetch_browserstack_artifacts(
-                &client,
-                target,
-                &build_id,
-                &output_root,
-                wait,
-                poll_interval_secs,
-                timeout_secs,
-            )?;
+            let meta_path = match (bench_meta, target) {
+                (Some(path), _) => Some(path),
+                (None, Some(target)) => {
+                    let default = bench_meta_path(&repo_root()?.join("target/mobench"), target);
+                    default.exists().then_some(default)
+                }
+                (None, None) => None,
+            };
+            let local_meta = meta_path.as_deref().map(load_bench_meta).transpose()?;
+            let stale_reports = match extract_only {
+                Some(dir) => reextract_fetched_artifacts(&dir, local_meta.as_ref())?,
+                None => {
+                    let target = target.context("--target is required")?;
+                    let build_id = build_id.context("--build-id is required")?;
+                    let creds = resolve_browserstack_credentials(None)?;
+                    let client = BrowserStackClient::new(
+                        BrowserStackAuth {
+                            username: creds.username,
+                            access_key: creds.access_key,
+                        },
+                        creds.project,
+                    )?;
+                    let output_root = output_dir.join(&build_id);
+                    fetch_browserstack_artifacts(
+                        &client,
+                        target,
+                        &build_id,
+                        &output_root,
+                        wait,
+                        poll_interval_secs,
+                        timeout_secs,
+                        local_meta.as_ref(),
+                        compress,
+                    )?
+                }
+            };
+            if fail_on_stale_build && stale_reports > 0 {
+                bail!(MobenchError::StaleBuild(format!(
+                    "{} device report(s) came from a different build than the local one",
+                    stale_reports
+                )));
+            }
         }
         Command::Compare {
             baseline,
             candidate,
             output,
+            regression,
+            junit,
+            fail_on_regression,
+            top,
+            sort_by,
+            delta_colors,
+            platform_version_matrix,
         } => {
-            let report = compare_summaries(&baseline, &candidate)?;
-            write_compare_report(&report, output.as_deref())?;
+            let mut report = compare_summaries(&baseline, &candidate)?;
+            report_compare_warnings(&report);
+            // Pivot before --top drops rows
+            let matrix = platform_version_matrix
+                .as_deref()
+                .map(|function| render_compare_platform_matrix(&report, function));
+            let findings = detect_regressions(&report, &regression);
+            let suspicious = detect_suspicious_improvements(&report, &regression);
+            if let Some(n) = top {
+                let total = report.rows.len();
+                let primary = regression.primary_metric;
+                top_entries(&mut report.rows, n, |row| match sort_by {
+                    TopSort::Median => row.candidate_median_ns.map(|ns| ns as f64),
+                    TopSort::P95 => row.candidate_p95_ns.map(|ns| ns as f64),
+                    TopSort::Regression => match primary {
+                        PrimaryMetric::Median => row.median_delta_pct,
+                        PrimaryMetric::Mom => row.mom_delta_pct,
+                    },
+                });
+                println!(
+                    "Showing top {} of {} row(s) by {}",
+                    report.rows.len(),
+                    total,
+                    sort_by.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+                );
+            }
+            write_compare_report(
+                &report,
+                &findings,
+                &suspicious,
+                delta_colors.markers(regression.pct).as_ref(),
+                matrix.as_deref(),
+                output.as_deref(),
+            )?;
+            report_regressions(&findings);
+            report_suspicious_improvements(&suspicious);
+            if let Some(path) = junit {
+                let candidate_summary = load_run_summary(&candidate)?;
+                let xml = render_junit_report(&candidate_summary.summary, &findings, &suspicious);
+                ensure_parent_dir(&path)?;
+                write_file(&path, xml.as_bytes())?;
+                println!("Wrote JUnit report to {:?}", path);
+            }
+            if fail_on_regression && !findings.is_empty() {
+                bail!(MobenchError::ThresholdExceeded(format!(
+                    "{} regression(s) exceeded the {} threshold",
+                    findings.len(),
+                    regression.describe()
+                )));
+            }
+            fail_on_suspicious_improvements(&suspicious)?;
         }
         Command::InitSdk {
             target,
             project_name,
             output_dir,
             examples,
+            with_criterion,
         } => {
-            cmd_init_sdk(target, project_name, output_dir, examples)?;
+            cmd_init_sdk(target, project_name, output_dir, examples, with_criterion)?;
         }
         Command::Build {
             target,
             release,
+            cargo_profile,
             output_dir,
             crate_path,
             progress,
+            no_cache,
         } => {
-            cmd_build(target, release, output_dir, crate_path, cli.dry_run, cli.verbose, progress)?;
+            let _span = tracing::info_span!("build", target = ?target, release).entered();
+            logging::step("build", || {
+                cmd_build(
+                    target,
+                    release,
+                    cargo_profile,
+                    output_dir,
+                    crate_path,
+                    cli.dry_run,
+                    cli.verbose,
+                    progress,
+                    !no_cache,
+                )
+            })?;
         }
         Command::PackageIpa { scheme, method, output_dir } => {
             cmd_package_ipa(&scheme, method, output_dir)?;
         }
+        Command::PackageAab { variant, output_dir } => {
+            cmd_package_aab(&variant, output_dir)?;
+        }
         Command::PackageXcuitest { scheme, output_dir } => {
             cmd_package_xcuitest(&scheme, output_dir)?;
         }
-        Command::List => {
-            cmd_list()?;
+        Command::List { json, since } => {
+            cmd_list(json, since.as_deref())?;
         }
         Command::Verify {
             target,
             spec_path,
             check_artifacts,
             smoke_test,
+            check_bindings,
+            smoke_all,
             function,
             output_dir,
         } => {
-            cmd_verify(target, spec_path, check_artifacts, smoke_test, function, output_dir)?;
+            cmd_verify(
+                target,
+                spec_path,
+                check_artifacts,
+                check_bindings,
+                smoke_test,
+                smoke_all,
+                function,
+                output_dir,
+            )?;
+        }
+        Command::Summary {
+            report,
+            format,
+            merge,
+            output,
+            compact_json,
+            sample_out,
+            gbench_json,
+            top,
+            sort_by,
+            histogram,
+            bins,
+            baseline,
+            platform_version_matrix,
+        } => {
+            let top = top.map(|n| (n, sort_by));
+            let outputs = SummaryOutputs {
+                sample_out: sample_out.as_deref(),
+                gbench_json: gbench_json.as_deref(),
+                histogram_bins: histogram.then_some(bins),
+                platform_version_matrix: platform_version_matrix.as_deref(),
+            };
+            if merge.is_empty() {
+                let report = report.context("a report path is required")?;
+                cmd_summary(&report, format, outputs, top, baseline.as_deref())?;
+            } else {
+                cmd_summary_merge(&merge, output.as_deref(), compact_json, format, outputs, top)?;
+            }
         }
-        Command::Summary { report, format } => {
-            cmd_summary(&report, format)?;
+        Command::Report {
+            summary,
+            output,
+            serve,
+            port,
+        } => {
+            cmd_report(&summary, output.as_deref(), serve, port)?;
         }
         Command::Devices {
             platform,
             json,
             validate,
+            matrix,
+            tags,
         } => {
-            cmd_devices(platform, json, validate)?;
+            cmd_devices(platform, json, validate, matrix.as_deref(), &tags, cli.refresh_devices)?;
         }
-        Command::Check { target, format } => {
-            cmd_check(target, format)?;
+        Command::Check {
+            target,
+            format,
+            fix,
+            yes,
+        } => {
+            cmd_check(target, format, fix, yes)?;
         }
     }
 
@@ -1018,7 +3016,7 @@ fn write_config_template(path: &Path, target: MobileTarget) -> Result<()> {
 }
 
 fn write_device_matrix_template(path: &Path) -> Result<()> {
-    ensure_can_write(path)?;
+    ensure_parent_dir(path)?;
 
     let matrix = DeviceMatrix {
         devices: vec![
@@ -1026,21 +3024,146 @@ fn write_device_matrix_template(path: &Path) -> Result<()> {
                 name: "Pixel 7".into(),
                 os: "android".into(),
                 os_version: "13.0".into(),
+                os_versions: Vec::new(),
                 tags: Some(vec!["default".into(), "pixel".into()]),
             },
             DeviceEntry {
                 name: "iPhone 14".into(),
                 os: "ios".into(),
                 os_version: "16".into(),
+                os_versions: Vec::new(),
                 tags: Some(vec!["default".into(), "iphone".into()]),
             },
         ],
+        groups: BTreeMap::from([
+            ("pixel".into(), "android".into()),
+            ("iphone".into(), "ios".into()),
+        ]),
     };
 
     let contents = serde_yaml::to_string(&matrix)?;
     write_file(path, contents.as_bytes())
 }
 
+/// Devices per platform tagged `default` in a `plan --from-browserstack` matrix.
+const PLAN_DEFAULT_DEVICES: usize = 3;
+
+/// Write a device matrix of the devices BrowserStack currently offers.
+/// Returns the number of devices written.
+fn write_browserstack_device_matrix(
+    path: &Path,
+    platform: Option<DevicePlatform>,
+    top: Option<usize>,
+    refresh_devices: bool,
+) -> Result<usize> {
+    let creds = resolve_browserstack_credentials(None)?;
+    let client = BrowserStackClient::new(
+        BrowserStackAuth {
+            username: creds.username,
+            access_key: creds.access_key,
+        },
+        creds.project,
+    )?;
+    let client = with_device_cache(client, refresh_devices)?;
+    let mut devices = Vec::new();
+    if platform != Some(DevicePlatform::Ios) {
+        devices.extend(client.list_espresso_devices().context("listing Espresso devices")?);
+    }
+    if platform != Some(DevicePlatform::Android) {
+        devices.extend(client.list_xcuitest_devices().context("listing XCUITest devices")?);
+    }
+
+    let matrix = device_matrix_from_browserstack(devices, top);
+    if matrix.devices.is_empty() {
+        bail!(MobenchError::DeviceInvalid(
+            "BrowserStack returned no available devices for this platform".into()
+        ));
+    }
+    let contents = serde_yaml::to_string(&matrix)?;
+    write_file(path, contents.as_bytes())?;
+    Ok(matrix.devices.len())
+}
+
+/// Build a matrix from live BrowserStack devices: the newest OS version of
+/// each model, newest first, and at most `top` models per platform.
+///
+/// Every device is tagged with its platform and model family (`pixel`,
+/// `galaxy`, `iphone`, ...); the newest [`PLAN_DEFAULT_DEVICES`] of each
+/// platform are also tagged `default`. Each platform becomes a group.
+fn device_matrix_from_browserstack(
+    devices: Vec<browserstack::BrowserStackDevice>,
+    top: Option<usize>,
+) -> DeviceMatrix {
+    // (os, model) -> newest version
+    let mut newest: BTreeMap<(String, String), String> = BTreeMap::new();
+    for device in devices {
+        if device.available == Some(false) {
+            continue;
+        }
+        let os = device.os.to_ascii_lowercase();
+        let version = newest.entry((os, device.device)).or_default();
+        if version.is_empty() || os_version_key(&device.os_version) > os_version_key(version) {
+            *version = device.os_version;
+        }
+    }
+
+    let mut by_os: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    for ((os, model), version) in newest {
+        by_os.entry(os).or_default().push((model, version));
+    }
+    let mut matrix = DeviceMatrix {
+        devices: Vec::new(),
+        groups: BTreeMap::new(),
+    };
+    for (os, mut models) in by_os {
+        // Newest OS first; models sharing a version stay alphabetical
+        models.sort_by_key(|(_, version)| std::cmp::Reverse(os_version_key(version)));
+        models.truncate(top.unwrap_or(usize::MAX));
+        for (index, (name, os_version)) in models.into_iter().enumerate() {
+            let mut tags = Vec::new();
+            if index < PLAN_DEFAULT_DEVICES {
+                tags.push("default".to_string());
+            }
+            tags.push(os.clone());
+            if let Some(family) = model_family(&name).filter(|family| *family != os) {
+                tags.push(family);
+            }
+            matrix.devices.push(DeviceEntry {
+                name,
+                os: os.clone(),
+                os_version,
+                os_versions: Vec::new(),
+                tags: Some(tags),
+            });
+        }
+        matrix.groups.insert(os.clone(), os);
+    }
+    matrix
+}
+
+/// Numeric components of an OS version, so `"13.0"` sorts after `"9"`.
+fn os_version_key(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Model family used as a matrix tag, e.g. `pixel` for "Google Pixel 7".
+fn model_family(model: &str) -> Option<String> {
+    const VENDORS: &[&str] = &["apple", "google", "samsung", "motorola", "xiaomi", "huawei"];
+    model
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_ascii_lowercase()
+        })
+        .find(|word| !word.is_empty() && !VENDORS.contains(&word.as_str()))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn fetch_browserstack_artifacts(
     client: &BrowserStackClient,
     target: MobileTarget,
@@ -1049,7 +3172,9 @@ fn fetch_browserstack_artifacts(
     wait: bool,
     poll_interval_secs: u64,
     timeout_secs: u64,
-) -> Result<()> {
+    local_meta: Option<&mobench_sdk::builders::BenchMeta>,
+    compress: bool,
+) -> Result<usize> {
     fs::create_dir_all(output_root)
         .with_context(|| format!("creating output dir {:?}", output_root))?;
 
@@ -1062,13 +3187,13 @@ fn fetch_browserstack_artifacts(
     }
 
     let build_json = client.get_json(&build_path)?;
-    write_json(output_root.join("build.json"), &build_json)?;
+    write_artifact_json(&output_root.join("build.json"), &build_json, compress)?;
 
     let mut session_ids = extract_session_ids(&build_json);
     if session_ids.is_empty() {
         match client.get_json(&sessions_path) {
             Ok(value) => {
-                write_json(output_root.join("sessions.json"), &value)?;
+                write_artifact_json(&output_root.join("sessions.json"), &value, compress)?;
                 session_ids = extract_session_ids(&value);
             }
             Err(err) => {
@@ -1080,84 +3205,394 @@ fn fetch_browserstack_artifacts(
 
     if session_ids.is_empty() {
         println!("No sessions found for build {}", build_id);
-        return Ok(());
+        return Ok(0);
     }
 
+    let session_devices = session_device_names(&build_json);
+    let session_count = session_ids.len();
+    let mut stale_reports = 0;
+    let mut failures: Vec<String> = Vec::new();
+    let mut failed_sessions = 0;
+    let mut benchmark_results: BTreeMap<String, Vec<Value>> = BTreeMap::new();
     for session_id in session_ids {
         let session_path = format!("{base}/builds/{build_id}/sessions/{session_id}");
-        let session_json = client.get_json(&session_path)?;
+        // Keep what the other sessions produce; rejected credentials fail
+        // every request, so there is no point in carrying on
+        let session_json = match client.get_json(&session_path) {
+            Ok(value) => value,
+            Err(err) if matches!(err.downcast_ref(), Some(MobenchError::AuthFailed(_))) => {
+                return Err(err);
+            }
+            Err(err) => {
+                println!("Skipping session {session_id}: {}", shorten_html_error(&err.to_string()));
+                failures.push(format!("session {session_id}: {err}"));
+                failed_sessions += 1;
+                continue;
+            }
+        };
         let session_dir = output_root.join(format!("session-{}", session_id));
         fs::create_dir_all(&session_dir)
             .with_context(|| format!("creating session dir {:?}", session_dir))?;
-        write_json(session_dir.join("session.json"), &session_json)?;
+        write_artifact_json(&session_dir.join("session.json"), &session_json, compress)?;
+        let device_meta = browserstack::extract_device_meta(&session_json);
+        if !device_meta.is_empty() {
+            write_json(session_dir.join("device-meta.json"), &Value::Object(device_meta))?;
+        }
 
-        let mut bench_report: Option<Value> = None;
+        let mut bench_reports: Vec<Value> = Vec::new();
         for (key, url) in extract_url_fields(&session_json) {
             let file_name = filename_for_url(&key, &url);
             let dest = session_dir.join(file_name);
             if let Err(err) = client.download_url(&url, &dest) {
                 println!("Skipping download for {key}: {err}");
+                failures.push(format!("session {session_id} {key}: {err}"));
                 continue;
             }
-            if (key.contains("device_log")
-                || key.contains("instrumentation_log")
-                || key.contains("app_log"))
+            if is_bench_log_key(&key)
                 && let Ok(contents) = fs::read_to_string(&dest)
-                && let Some(parsed) = extract_bench_json(&contents)
             {
-                bench_report = Some(parsed);
+                let parsed = extract_all_bench_json(&contents);
+                if !parsed.is_empty() {
+                    bench_reports = parsed;
+                }
+            }
+            if compress {
+                compress::compress_file(&dest)?;
             }
         }
 
-        if let Some(report) = bench_report {
-            write_json(session_dir.join("bench-report.json"), &report)?;
-        }
+        stale_reports += record_session_reports(
+            &session_dir,
+            &session_id,
+            &session_json,
+            &session_devices,
+            bench_reports,
+            local_meta,
+            &mut benchmark_results,
+        )?;
     }
+    write_benchmark_results(output_root, &benchmark_results, compress)?;
 
+    if !failures.is_empty() {
+        println!(
+            "{} BrowserStack request(s) failed after retries; the artifacts below are missing:",
+            failures.len()
+        );
+        for failure in &failures {
+            println!("  - {}", shorten_html_error(failure));
+        }
+    }
+    if failed_sessions == session_count {
+        return Err(MobenchError::ResultsUnavailable(format!(
+            "could not fetch any of the {} session(s) of build {}",
+            session_count, build_id
+        ))
+        .into());
+    }
     println!("Fetched BrowserStack artifacts to {:?}", output_root);
-    Ok(())
+    Ok(stale_reports)
 }
 
-fn browserstack_base_path(target: MobileTarget) -> &'static str {
-    match target {
-        MobileTarget::Android => "app-automate/espresso/v2",
-        MobileTarget::Ios => "app-automate/xcuitest/v2",
+/// Re-run report extraction over a directory written by `fetch`, without
+/// contacting BrowserStack.
+///
+/// Every `session-*` directory's saved device logs are parsed again and its
+/// `bench-report.json` / `bench-reports.json` rewritten, so an extractor fix
+/// can be applied to historical artifacts. Returns the number of stale
+/// reports, as [`fetch_browserstack_artifacts`] does.
+fn reextract_fetched_artifacts(
+    output_root: &Path,
+    local_meta: Option<&mobench_sdk::builders::BenchMeta>,
+) -> Result<usize> {
+    let entries = fs::read_dir(output_root)
+        .with_context(|| format!("reading fetched artifacts in {:?}", output_root))?;
+    let mut session_dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("session-"))
+        })
+        .collect();
+    if session_dirs.is_empty() {
+        bail!(
+            "no session-* directories in {:?}; pass the per-build directory written by `fetch`",
+            output_root
+        );
     }
-}
-
-fn wait_for_build(
-    client: &BrowserStackClient,
-    build_path: &str,
-    poll_interval_secs: u64,
-    timeout_secs: u64,
-) -> Result<()> {
-    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
-    loop {
-        let build_json = client.get_json(build_path)?;
-        if let Some(status) = build_json
-            .get("status")
-            .and_then(|val| val.as_str())
-            .map(|val| val.to_lowercase())
-        {
-            if status == "failed" || status == "error" {
-                println!("Build status: {status}");
-                return Ok(());
+    session_dirs.sort();
+
+    let build_json = read_json_if_exists(&output_root.join("build.json"))?.unwrap_or(Value::Null);
+    let session_devices = session_device_names(&build_json);
+    // Keep the results in the format the original fetch wrote
+    let compress =
+        compress::compressed_path(&output_root.join(BENCHMARK_RESULTS_FILE)).exists();
+    let mut stale_reports = 0;
+    let mut benchmark_results: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for session_dir in &session_dirs {
+        let dir_name = session_dir.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let session_id = dir_name.trim_start_matches("session-");
+        let session_json =
+            read_json_if_exists(&session_dir.join("session.json"))?.unwrap_or(Value::Null);
+
+        let mut logs: Vec<PathBuf> = fs::read_dir(session_dir)
+            .with_context(|| format!("reading session dir {:?}", session_dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                let name = compress::logical_file_name(path);
+                name.extension().is_none_or(|ext| ext != "json")
+                    && name
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .is_some_and(is_bench_log_key)
+            })
+            .collect();
+        logs.sort();
+
+        let mut bench_reports: Vec<Value> = Vec::new();
+        for log in &logs {
+            let contents = compress::read(log).with_context(|| format!("reading log {:?}", log))?;
+            let parsed = extract_all_bench_json(&String::from_utf8_lossy(&contents));
+            if !parsed.is_empty() {
+                bench_reports = parsed;
             }
-            if status == "done" || status == "passed" || status == "completed" {
-                println!("Build status: {status}");
-                return Ok(());
+        }
+        if bench_reports.is_empty() {
+            println!("{}: no benchmark report in {} log file(s)", dir_name, logs.len());
+            // Drop reports from a previous extraction that no longer parse
+            for stale in ["bench-report.json", "bench-reports.json"] {
+                let path = session_dir.join(stale);
+                if path.exists() {
+                    fs::remove_file(&path).with_context(|| format!("removing {:?}", path))?;
+                }
             }
-            println!("Build status: {status} (waiting)");
-        } else {
-            println!("Build status missing; continuing without wait");
-            return Ok(());
+            continue;
         }
+        println!("{}: {} benchmark report(s)", dir_name, bench_reports.len());
+        stale_reports += record_session_reports(
+            session_dir,
+            session_id,
+            &session_json,
+            &session_devices,
+            bench_reports,
+            local_meta,
+            &mut benchmark_results,
+        )?;
+    }
+    let results_path = write_benchmark_results(output_root, &benchmark_results, compress)?;
+
+    let reports: usize = benchmark_results.values().map(Vec::len).sum();
+    println!(
+        "Re-extracted {} report(s) from {} session(s) into {:?}",
+        reports,
+        session_dirs.len(),
+        results_path
+    );
+    if reports > 0 {
+        print_summary_text(&extract_summary_data(&json!({
+            "benchmark_results": benchmark_results
+        }))?);
+    }
+    Ok(stale_reports)
+}
 
-        if Instant::now() >= deadline {
-            println!("Timed out waiting for build status");
-            return Ok(());
-        }
-        std::thread::sleep(Duration::from_secs(poll_interval_secs));
+/// Whether a session artifact key (or saved file stem) names a device log
+/// that may contain benchmark reports.
+fn is_bench_log_key(key: &str) -> bool {
+    key.contains("device_log") || key.contains("instrumentation_log") || key.contains("app_log")
+}
+
+/// Write a session's extracted reports, check them against the local build,
+/// and add them to `benchmark_results` under the session's device.
+///
+/// `bench-report.json` keeps the last report; suites also get every report
+/// in `bench-reports.json`. Returns the number of stale reports.
+fn record_session_reports(
+    session_dir: &Path,
+    session_id: &str,
+    session_json: &Value,
+    session_devices: &BTreeMap<String, String>,
+    bench_reports: Vec<Value>,
+    local_meta: Option<&mobench_sdk::builders::BenchMeta>,
+    benchmark_results: &mut BTreeMap<String, Vec<Value>>,
+) -> Result<usize> {
+    let Some(report) = bench_reports.last() else {
+        return Ok(0);
+    };
+    write_json(session_dir.join("bench-report.json"), report)?;
+    let all_reports = session_dir.join("bench-reports.json");
+    if bench_reports.len() > 1 {
+        write_json(all_reports, &Value::Array(bench_reports.clone()))?;
+    } else if all_reports.exists() {
+        fs::remove_file(&all_reports).with_context(|| format!("removing {:?}", all_reports))?;
+    }
+
+    let mut stale_reports = 0;
+    if let Some(meta) = local_meta {
+        let label = format!("session {}", session_id);
+        stale_reports += check_stale_reports(
+            meta,
+            bench_reports.iter().map(|report| (label.as_str(), report)),
+        );
+    }
+
+    let device = session_devices
+        .get(session_id)
+        .cloned()
+        .or_else(|| {
+            let root = session_json.get("automation_session").unwrap_or(session_json);
+            root.get("device").and_then(Value::as_str).map(String::from)
+        })
+        .unwrap_or_else(|| format!("session-{}", session_id));
+    benchmark_results.entry(device).or_default().extend(bench_reports);
+    Ok(stale_reports)
+}
+
+/// Map session ids to device names using a build status response.
+fn session_device_names(build_json: &Value) -> BTreeMap<String, String> {
+    let mut devices = BTreeMap::new();
+    for entry in build_json
+        .get("devices")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(device) = entry.get("device").and_then(Value::as_str) else {
+            continue;
+        };
+        for session in entry
+            .get("sessions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let id = session
+                .get("id")
+                .or_else(|| session.get("session_id"))
+                .or_else(|| session.get("sessionId"))
+                .and_then(Value::as_str);
+            if let Some(id) = id {
+                devices.insert(id.to_string(), device.to_string());
+            }
+        }
+    }
+    devices
+}
+
+/// Name of the merged results file in a `fetch` output directory.
+const BENCHMARK_RESULTS_FILE: &str = "benchmark-results.json";
+
+/// Write every extracted report, keyed by device, to `benchmark-results.json`
+/// in the format `mobench summary` reads.
+fn write_benchmark_results(
+    output_root: &Path,
+    benchmark_results: &BTreeMap<String, Vec<Value>>,
+    compress: bool,
+) -> Result<PathBuf> {
+    write_artifact_json(
+        &output_root.join(BENCHMARK_RESULTS_FILE),
+        &json!({ "benchmark_results": benchmark_results }),
+        compress,
+    )
+}
+
+/// Write a fetched artifact as pretty JSON, zstd-compressed with `--compress`.
+fn write_artifact_json(path: &Path, value: &Value, compress: bool) -> Result<PathBuf> {
+    let contents = serde_json::to_string_pretty(value)?;
+    compress::write(path, contents.as_bytes(), compress)
+}
+
+/// Read a JSON file, or its `.zst` compressed sibling, if either exists.
+fn read_json_if_exists(path: &Path) -> Result<Option<Value>> {
+    if !compress::exists(path) {
+        return Ok(None);
+    }
+    let contents = compress::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .with_context(|| format!("parsing {:?}", path))
+}
+
+/// Location of the `bench_meta.json` embedded into the app for `target`.
+fn bench_meta_path(output_dir: &Path, target: MobileTarget) -> PathBuf {
+    match target {
+        MobileTarget::Android => output_dir.join("android/app/src/main/assets/bench_meta.json"),
+        MobileTarget::Ios => output_dir.join("ios/BenchRunner/BenchRunner/Resources/bench_meta.json"),
+    }
+}
+
+fn load_bench_meta(path: &Path) -> Result<mobench_sdk::builders::BenchMeta> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("reading bench meta {:?}", path))?;
+    serde_json::from_str(&contents).with_context(|| format!("parsing bench meta {:?}", path))
+}
+
+/// Warn loudly about device reports that were produced by a different build
+/// than the local one (the classic "uploaded an old APK" mistake).
+///
+/// Returns the number of mismatching reports.
+fn check_stale_reports<'a>(
+    local: &mobench_sdk::builders::BenchMeta,
+    reports: impl IntoIterator<Item = (&'a str, &'a Value)>,
+) -> usize {
+    let mut stale = 0;
+    for (device, report) in reports {
+        let mismatches = local.mismatches(report);
+        if mismatches.is_empty() {
+            continue;
+        }
+        stale += 1;
+        eprintln!(
+            "WARNING: {} ran a different build than the local one ({}); was an old app uploaded?",
+            device,
+            mismatches.join("; ")
+        );
+    }
+    stale
+}
+
+fn browserstack_base_path(target: MobileTarget) -> &'static str {
+    match target {
+        MobileTarget::Android => "app-automate/espresso/v2",
+        MobileTarget::Ios => "app-automate/xcuitest/v2",
+    }
+}
+
+fn wait_for_build(
+    client: &BrowserStackClient,
+    build_path: &str,
+    poll_interval_secs: u64,
+    timeout_secs: u64,
+) -> Result<()> {
+    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+    loop {
+        let build_json = client.get_json(build_path)?;
+        if let Some(status) = build_json
+            .get("status")
+            .and_then(|val| val.as_str())
+            .map(|val| val.to_lowercase())
+        {
+            if status == "failed" || status == "error" {
+                println!("Build status: {status}");
+                return Ok(());
+            }
+            if status == "done" || status == "passed" || status == "completed" {
+                println!("Build status: {status}");
+                return Ok(());
+            }
+            println!("Build status: {status} (waiting)");
+        } else {
+            println!("Build status missing; continuing without wait");
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            println!("Timed out waiting for build status");
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_secs(poll_interval_secs));
     }
 }
 
@@ -1245,51 +3680,263 @@ fn filename_for_url(key: &str, url: &str) -> String {
     format!("{}.{}", safe, ext)
 }
 
+/// The last benchmark report in a device log.
+#[cfg(test)]
 fn extract_bench_json(contents: &str) -> Option<Value> {
-    // First, try iOS-style markers: BENCH_REPORT_JSON_START ... BENCH_REPORT_JSON_END
-    // This allows multi-line JSON and is more robust for iOS NSLog output
-    if let Some(json) = extract_bench_json_ios_markers(contents) {
-        return Some(json);
-    }
+    extract_all_bench_json(contents).pop()
+}
+
+/// Every benchmark report in a device log, one per function.
+///
+/// A suite run logs one report per function. When a function reports more
+/// than once (e.g. the app was relaunched), the last report wins, and the
+/// result is ordered by each function's last report.
+fn extract_all_bench_json(contents: &str) -> Vec<Value> {
+    // First, try START/END markers: BENCH_REPORT_JSON_START ... BENCH_REPORT_JSON_END
+    // This allows multi-line JSON and survives iOS NSLog splitting as well as
+    // Android logcat line wrapping and interleaving
+    let mut reports = extract_all_bench_json_markers(contents);
 
     // Fall back to Android-style single-line marker: BENCH_JSON {...}
-    let marker = "BENCH_JSON ";
-    for line in contents.lines().rev() {
-        if let Some(idx) = line.find(marker) {
-            let json_part = &line[idx + marker.len()..];
-            if let Ok(value) = serde_json::from_str::<Value>(json_part) {
-                return Some(value);
-            }
+    if reports.is_empty() {
+        let marker = "BENCH_JSON ";
+        reports = contents
+            .lines()
+            .filter_map(|line| {
+                let idx = line.find(marker)?;
+                serde_json::from_str::<Value>(&line[idx + marker.len()..]).ok()
+            })
+            .collect();
+    }
+
+    // Last resort: the final report was truncated or never written, but the
+    // runner may have streamed individual samples as it went.
+    if reports.is_empty() {
+        return reconstruct_report_from_samples(contents).into_iter().collect();
+    }
+
+    let mut latest: Vec<Value> = Vec::new();
+    for report in reports {
+        let name = report_function_name(&report);
+        if name.is_some() {
+            latest.retain(|seen| report_function_name(seen) != name);
         }
+        latest.push(report);
     }
-    None
+    latest
+}
+
+/// Function name of a device report, from `function` or `spec.name`.
+fn report_function_name(report: &Value) -> Option<&str> {
+    report
+        .get("function")
+        .or_else(|| report.get("spec").and_then(|spec| spec.get("name")))
+        .and_then(Value::as_str)
+}
+
+/// Rebuild a partial report from streamed `BENCH_SAMPLE_JSON {...}` lines.
+///
+/// Only samples for the most recently streamed benchmark are used. Duplicate
+/// iteration indices (e.g. from log replays) keep the last value seen.
+fn reconstruct_report_from_samples(contents: &str) -> Option<Value> {
+    let marker = format!("{} ", mobench_sdk::timing::SAMPLE_JSON_MARKER);
+    let streamed: Vec<mobench_sdk::timing::StreamedSample> = contents
+        .lines()
+        .filter_map(|line| {
+            let idx = line.find(&marker)?;
+            serde_json::from_str(line[idx + marker.len()..].trim()).ok()
+        })
+        .collect();
+
+    let name = streamed.last()?.name.clone();
+    let mut by_iteration = BTreeMap::new();
+    for sample in streamed.into_iter().filter(|s| s.name == name) {
+        let recorded = mobench_sdk::BenchSample {
+            duration_ns: sample.duration_ns,
+            cpu_ns: sample.cpu_ns,
+        };
+        by_iteration.insert(sample.iteration, recorded);
+    }
+    let samples: Vec<Value> = by_iteration.values().map(|sample| json!(sample)).collect();
+
+    Some(json!({
+        "function": name,
+        "spec": { "name": name, "iterations": samples.len() },
+        "samples": samples,
+        "reconstructed_from_samples": true,
+    }))
 }
 
-/// Extract benchmark JSON from iOS logs using START/END markers.
-/// iOS uses NSLog which may split the JSON across multiple log lines,
-/// so we need to capture everything between the markers.
-fn extract_bench_json_ios_markers(contents: &str) -> Option<Value> {
-    let start_marker = "BENCH_REPORT_JSON_START";
-    let end_marker = "BENCH_REPORT_JSON_END";
+const BENCH_REPORT_START: &str = "BENCH_REPORT_JSON_START";
+const BENCH_REPORT_END: &str = "BENCH_REPORT_JSON_END";
+
+/// Extract every benchmark JSON block from device logs, in log order.
+fn extract_all_bench_json_markers(contents: &str) -> Vec<Value> {
+    contents
+        .match_indices(BENCH_REPORT_START)
+        .filter_map(|(start_pos, _)| extract_bench_json_block(contents, start_pos))
+        .collect()
+}
 
-    // Find the last occurrence of start marker (in case of multiple runs)
-    let start_pos = contents.rfind(start_marker)?;
+/// Extract benchmark JSON from device logs using START/END markers.
+/// iOS NSLog and Android logcat may both split the JSON across multiple log
+/// lines, so we need to capture everything between the markers. On Android,
+/// lines from other processes or tags can be interleaved with the report; only
+/// lines logged by the same process and tag as the START marker are kept.
+fn extract_bench_json_block(contents: &str, start_pos: usize) -> Option<Value> {
+    let start_marker = BENCH_REPORT_START;
     let after_start = &contents[start_pos + start_marker.len()..];
 
-    // Find the end marker after the start
-    let end_pos = after_start.find(end_marker)?;
+    // Find the end marker after the start; a block cut off before its END
+    // marker must not swallow the next block
+    let end_pos = after_start.find(BENCH_REPORT_END)?;
     let json_section = &after_start[..end_pos];
+    if json_section.contains(start_marker) {
+        return None;
+    }
 
     // The JSON might be on the next line or have log prefixes, so we need to clean it up
     // iOS NSLog format often looks like: "2026-01-20 12:34:56.789 BenchRunner[1234:5678] {"key": "value"}"
+    // Android logcat looks like: "01-20 12:34:56.789  1234  5678 I BenchRunner: {"key": "value"}"
     // or just the raw JSON on its own line
+    let line_start = contents[..start_pos].rfind('\n').map_or(0, |i| i + 1);
+    let start_line = parse_logcat_line(&contents[line_start..start_pos + start_marker.len()]);
+    let lines = strip_logcat_prefixes(json_section, start_line.as_ref());
+
+    // Logcat splits long payloads into fixed-size chunks, so rejoin them verbatim
+    if start_line.is_some()
+        && let Ok(value) = serde_json::from_str::<Value>(lines.concat().trim())
+    {
+        return Some(value);
+    }
 
     // Try to find valid JSON in the section
-    let json_str = extract_json_from_log_section(json_section)?;
+    let json_str = extract_json_from_log_section(&lines.join("\n"))?;
 
     serde_json::from_str::<Value>(&json_str).ok()
 }
 
+/// A single Android logcat line split into its source and message.
+#[derive(Debug, PartialEq)]
+struct LogcatLine<'a> {
+    pid: Option<&'a str>,
+    tag: &'a str,
+    message: &'a str,
+}
+
+/// Parse an Android logcat line in any of the common output formats:
+///
+/// - `threadtime`: `01-20 12:34:56.789  1234  5678 I BenchRunner: message`
+/// - Android Studio: `01-20 12:34:56.789 1234-5678/com.example I/BenchRunner: message`
+/// - `time` / `brief`: `01-20 12:34:56.789 I/BenchRunner( 1234): message`
+///
+/// Returns `None` for lines that are not logcat output (e.g. iOS NSLog lines).
+fn parse_logcat_line(line: &str) -> Option<LogcatLine<'_>> {
+    let mut rest = line.trim_start();
+
+    // Optional "MM-DD" or "YYYY-MM-DD" date followed by an "HH:MM:SS.mmm" time
+    if let Some((date, after)) = rest.split_once(' ')
+        && is_log_date(date)
+    {
+        let (time, after) = after.trim_start().split_once(' ')?;
+        if !time.starts_with(|c: char| c.is_ascii_digit()) || !time.contains(':') {
+            return None;
+        }
+        rest = after.trim_start();
+    }
+
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    // threadtime: "PID TID P Tag: message"
+    if let Some((pid, after)) = rest.split_once(' ')
+        && is_number(pid)
+    {
+        let (tid, after) = after.trim_start().split_once(' ')?;
+        let (priority, after) = after.trim_start().split_once(' ')?;
+        if !is_number(tid) || !is_log_priority(priority) {
+            return None;
+        }
+        let (tag, message) = split_logcat_tag(after)?;
+        return Some(LogcatLine {
+            pid: Some(pid),
+            tag: tag.trim(),
+            message,
+        });
+    }
+
+    // Android Studio: "PID-TID/package P/Tag: message"
+    let mut pid = None;
+    if let Some((source, after)) = rest.split_once(' ')
+        && let Some((ids, _package)) = source.split_once('/')
+        && let Some((source_pid, tid)) = ids.split_once('-')
+        && is_number(source_pid)
+        && is_number(tid)
+    {
+        pid = Some(source_pid);
+        rest = after.trim_start();
+    }
+
+    // "P/Tag: message" or "P/Tag( PID): message"
+    let (priority, after) = rest.split_once('/')?;
+    if !is_log_priority(priority) {
+        return None;
+    }
+    let (tag, message) = split_logcat_tag(after)?;
+    let (tag, pid) = match tag.split_once('(') {
+        Some((tag, paren)) => (tag, paren.strip_suffix(')').map(str::trim).or(pid)),
+        None => (tag, pid),
+    };
+    Some(LogcatLine {
+        pid,
+        tag: tag.trim(),
+        message,
+    })
+}
+
+fn is_log_date(token: &str) -> bool {
+    let parts: Vec<&str> = token.split('-').collect();
+    (parts.len() == 2 || parts.len() == 3)
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_log_priority(token: &str) -> bool {
+    matches!(token, "V" | "D" | "I" | "W" | "E" | "F" | "A")
+}
+
+/// Split "Tag: message" into its parts. The message may be empty.
+fn split_logcat_tag(s: &str) -> Option<(&str, &str)> {
+    if let Some((tag, message)) = s.split_once(": ") {
+        return Some((tag, message));
+    }
+    s.trim_end().strip_suffix(':').map(|tag| (tag, ""))
+}
+
+/// Replace logcat lines in `section` with just their message, dropping lines
+/// that came from a different process or tag than `source`. Non-logcat lines
+/// (iOS or raw output) are passed through unchanged.
+fn strip_logcat_prefixes<'a>(section: &'a str, source: Option<&LogcatLine<'_>>) -> Vec<&'a str> {
+    section
+        .lines()
+        .filter_map(|line| match parse_logcat_line(line) {
+            Some(parsed) => {
+                if let Some(source) = source {
+                    let same_pid = match (source.pid, parsed.pid) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => true,
+                    };
+                    if !same_pid || source.tag != parsed.tag {
+                        return None;
+                    }
+                }
+                Some(parsed.message)
+            }
+            None => Some(line),
+        })
+        .collect()
+}
+
 /// Extract valid JSON from a log section that may contain log prefixes/timestamps.
 /// Handles both raw JSON and JSON embedded in log lines.
 fn extract_json_from_log_section(section: &str) -> Option<String> {
@@ -1416,14 +4063,17 @@ fn resolve_run_spec(
     ios_test_suite: Option<PathBuf>,
     local_only: bool,
     release: bool,
+    cargo_profile: Option<&str>,
+    output_dir: &Path,
 ) -> Result<RunSpec> {
     if let Some(cfg_path) = config {
         let cfg = load_config(cfg_path)?;
         let matrix = load_device_matrix(&cfg.device_matrix)?;
         let device_names = match &cfg.device_tags {
-            Some(tags) if !tags.is_empty() => filter_devices_by_tags(matrix.devices, tags)?,
-            _ => matrix.devices.into_iter().map(|d| d.name).collect(),
+            Some(tags) if !tags.is_empty() => filter_devices_by_tags(matrix.devices.clone(), tags)?,
+            _ => matrix.devices.iter().map(|d| d.name.clone()).collect(),
         };
+        let device_groups = resolve_device_groups(&matrix, &device_names);
         return Ok(RunSpec {
             target: cfg.target,
             function: cfg.function,
@@ -1432,6 +4082,21 @@ fn resolve_run_spec(
             devices: device_names,
             browserstack: Some(cfg.browserstack),
             ios_xcuitest: cfg.ios_xcuitest,
+            device_groups,
+            sampling: None,
+            warmup_policy: None,
+            functions: Vec::new(),
+            seed: None,
+            per_benchmark_timeout_secs: None,
+            per_iteration_timeout_ms: None,
+            capture_warmup: false,
+            startup_metrics: false,
+            drop_first_n: 0,
+            pin_cores: CorePinning::None,
+            sample_interleave: false,
+            order: ExecutionOrder::Declared,
+            env: BTreeMap::new(),
+            benchmark_defaults: BTreeMap::new(),
         });
     }
 
@@ -1450,7 +4115,7 @@ fn resolve_run_spec(
         && !devices.is_empty()
         && ios_xcuitest.is_none()
     {
-        Some(package_ios_xcuitest_artifacts(release)?)
+        Some(package_ios_xcuitest_artifacts(release, cargo_profile, output_dir)?)
     } else {
         ios_xcuitest
     };
@@ -1463,19 +4128,350 @@ fn resolve_run_spec(
         devices,
         browserstack: None,
         ios_xcuitest,
+        device_groups: Vec::new(),
+        sampling: None,
+        warmup_policy: None,
+        functions: Vec::new(),
+        seed: None,
+        per_benchmark_timeout_secs: None,
+        per_iteration_timeout_ms: None,
+        capture_warmup: false,
+        startup_metrics: false,
+        drop_first_n: 0,
+        pin_cores: CorePinning::None,
+        sample_interleave: false,
+        order: ExecutionOrder::Declared,
+        env: BTreeMap::new(),
+        benchmark_defaults: BTreeMap::new(),
     })
 }
 
+/// Devices selected by a `[profiles.<name>]` entry: its `devices`, then the
+/// matrix devices matching its `device_tags` (all of them without tags).
+fn profile_devices(profile: &config::RunProfile) -> Result<Vec<String>> {
+    let mut devices = profile.devices.clone();
+    if let Some(path) = &profile.device_matrix {
+        let matrix = load_device_matrix(path)?;
+        let from_matrix = if profile.device_tags.is_empty() {
+            matrix.devices.into_iter().map(|d| d.name).collect()
+        } else {
+            filter_devices_by_tags(matrix.devices, &profile.device_tags)?
+        };
+        for device in from_matrix {
+            if !devices.contains(&device) {
+                devices.push(device);
+            }
+        }
+    } else if !profile.device_tags.is_empty() {
+        bail!("profile device_tags need a device_matrix to select devices from");
+    }
+    Ok(devices)
+}
+
 fn load_config(path: &Path) -> Result<BenchConfig> {
     let contents =
         fs::read_to_string(path).with_context(|| format!("reading config {:?}", path))?;
     toml::from_str(&contents).with_context(|| format!("parsing config {:?}", path))
 }
 
+/// Load a device matrix, picking the format from the file extension:
+/// `.toml`, `.json`, or YAML for `.yaml`/`.yml` and anything else.
+///
+/// Entries with `os_versions` are expanded into one device per version.
 fn load_device_matrix(path: &Path) -> Result<DeviceMatrix> {
     let contents =
         fs::read_to_string(path).with_context(|| format!("reading device matrix {:?}", path))?;
-    serde_yaml::from_str(&contents).with_context(|| format!("parsing device matrix {:?}", path))
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let parsed = match extension.as_deref() {
+        Some("toml") => toml::from_str(&contents).map_err(anyhow::Error::from),
+        Some("json") => serde_json::from_str(&contents).map_err(anyhow::Error::from),
+        _ => serde_yaml::from_str(&contents).map_err(anyhow::Error::from),
+    };
+    let mut matrix: DeviceMatrix =
+        parsed.with_context(|| format!("parsing device matrix {:?}", path))?;
+    matrix.devices = expand_os_versions(matrix.devices)
+        .with_context(|| format!("expanding device matrix {:?}", path))?;
+    Ok(matrix)
+}
+
+/// Resolve the matrix `groups` mapping (tag -> label) to the selected devices
+/// in each group, in matrix order. Tags mapping to the same label are
+/// combined; groups with no selected device are dropped.
+fn resolve_device_groups(matrix: &DeviceMatrix, selected: &[String]) -> Vec<DeviceGroup> {
+    let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for device in matrix.devices.iter().filter(|d| selected.contains(&d.name)) {
+        let tags: Vec<String> = device
+            .tags
+            .iter()
+            .flatten()
+            .map(|tag| tag.trim().to_lowercase())
+            .collect();
+        for (tag, label) in &matrix.groups {
+            let members = groups.entry(label.as_str()).or_default();
+            if tags.contains(&tag.trim().to_lowercase()) && !members.contains(&device.name) {
+                members.push(device.name.clone());
+            }
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|(_, devices)| !devices.is_empty())
+        .map(|(label, devices)| DeviceGroup {
+            label: label.to_string(),
+            devices,
+        })
+        .collect()
+}
+
+/// The matrix devices for each `device_tags` entry of the `--config` file or,
+/// without one, of the run profile, for [`select_devices`].
+fn device_tag_candidates(
+    config: Option<&Path>,
+    profile: Option<&config::RunProfile>,
+) -> Result<Vec<(String, Vec<String>)>> {
+    let (matrix_path, tags) = match (config, profile) {
+        (Some(path), _) => {
+            let cfg = load_config(path)?;
+            (cfg.device_matrix, cfg.device_tags.unwrap_or_default())
+        }
+        (None, Some(config::RunProfile { device_matrix: Some(matrix), device_tags, .. })) => {
+            (matrix.clone(), device_tags.clone())
+        }
+        _ => return Ok(Vec::new()),
+    };
+    if tags.is_empty() {
+        return Ok(Vec::new());
+    }
+    let matrix = load_device_matrix(&matrix_path)?;
+    Ok(tags
+        .into_iter()
+        .map(|tag| {
+            let devices = filter_devices_by_tags(matrix.devices.clone(), std::slice::from_ref(&tag))
+                .unwrap_or_default();
+            (tag, devices)
+        })
+        .collect())
+}
+
+/// Narrows `spec.devices` according to `strategy`, skipping devices that
+/// BrowserStack reported as `unavailable`.
+fn apply_device_strategy(
+    spec: &mut RunSpec,
+    strategy: DeviceStrategy,
+    tag_candidates: &[(String, Vec<String>)],
+    unavailable: &BTreeSet<String>,
+) -> Result<()> {
+    let selected = select_devices(strategy, &spec.devices, tag_candidates, unavailable);
+    if selected.is_empty() {
+        bail!(MobenchError::DeviceInvalid(format!(
+            "--device-strategy {}: none of the {} resolved device(s) is currently available",
+            strategy.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default(),
+            spec.devices.len()
+        )));
+    }
+    println!(
+        "Device strategy selected {} of {} device(s): {}",
+        selected.len(),
+        spec.devices.len(),
+        selected.join(", ")
+    );
+    for group in &mut spec.device_groups {
+        group.devices.retain(|device| selected.contains(device));
+    }
+    spec.device_groups.retain(|group| !group.devices.is_empty());
+    spec.devices = selected;
+    Ok(())
+}
+
+/// Which devices reported, failed to schedule, or returned no results, for
+/// `--no-fail-fast` runs.
+fn device_outcome_lines(summary: &SummaryReport) -> Vec<String> {
+    let failures = &summary.device_failures;
+    let succeeded: Vec<&str> = summary
+        .device_summaries
+        .iter()
+        .filter(|device| !device.is_group())
+        .map(|device| device.device.as_str())
+        .collect();
+    let mut lines = vec![format!(
+        "Succeeded ({}): {}",
+        succeeded.len(),
+        if succeeded.is_empty() { "none".to_string() } else { succeeded.join(", ") }
+    )];
+    for (label, devices) in [
+        ("Failed to schedule", &failures.unscheduled),
+        ("No results", &failures.no_results),
+    ] {
+        if devices.is_empty() {
+            continue;
+        }
+        let listed: Vec<String> = devices
+            .iter()
+            .map(|(device, reason)| format!("{} ({})", device, reason))
+            .collect();
+        lines.push(format!("{} ({}): {}", label, devices.len(), listed.join(", ")));
+    }
+    lines
+}
+
+/// Fails a run once everything else has been reported if any device failed
+/// to schedule or returned no results (recorded with `--no-fail-fast`, or
+/// when a device retry could not be scheduled).
+fn fail_on_device_failures(failures: &DeviceFailures) -> Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    let devices: Vec<&str> = failures
+        .unscheduled
+        .keys()
+        .chain(failures.no_results.keys())
+        .map(String::as_str)
+        .collect();
+    bail!(MobenchError::ResultsUnavailable(format!(
+        "{} device(s) failed to schedule or returned no results: {}",
+        failures.len(),
+        devices.join(", ")
+    )))
+}
+
+/// `--strict`: each device/function that returned fewer samples than the run
+/// requested, as `device / function: got of expected samples`.
+///
+/// A device is expected to return `--iterations` less `--drop-first-n`
+/// samples per function, times `--repeat`. Requested devices without any
+/// summary fall short by every function, whether or not they were recorded
+/// as device failures. Only fixed-iteration runs have a sample count to check.
+fn sample_shortfalls(spec: &RunSpec, summary: &SummaryReport) -> Vec<String> {
+    if !matches!(spec.sampling, None | Some(mobench_sdk::SamplingMode::FixedIterations)) {
+        return Vec::new();
+    }
+    let expected = |function: &str| {
+        spec.iterations_for(function).saturating_sub(spec.drop_first_n) as usize * summary.repeat_count.max(1) as usize
+    };
+    let reports = |bench: &BenchmarkStats, function: &str| {
+        bench.function == function || bench.function.ends_with(&format!("::{function}"))
+    };
+    let mut functions = spec.all_functions();
+    functions.dedup();
+    let mut shortfalls = Vec::new();
+    for device in summary.device_summaries.iter().filter(|device| !device.is_group()) {
+        for bench in &device.benchmarks {
+            let function = functions
+                .iter()
+                .find(|function| reports(bench, function))
+                .copied()
+                .unwrap_or(bench.function.as_str());
+            if bench.samples < expected(function) {
+                shortfalls.push(format!(
+                    "{} / {}: {} of {} samples",
+                    device.device,
+                    bench.function,
+                    bench.samples,
+                    expected(function)
+                ));
+            }
+        }
+        for function in &functions {
+            if !device.benchmarks.iter().any(|bench| reports(bench, function)) {
+                shortfalls.push(format!("{} / {}: 0 of {} samples", device.device, function, expected(function)));
+            }
+        }
+    }
+    for device in &spec.devices {
+        let summarized = summary
+            .device_summaries
+            .iter()
+            .any(|summary| !summary.is_group() && device_names_match(device, &summary.device));
+        if !summarized {
+            for function in &functions {
+                shortfalls.push(format!("{} / {}: 0 of {} samples", device, function, expected(function)));
+            }
+        }
+    }
+    shortfalls
+}
+
+/// Fails a `--strict` run once everything else has been reported if any
+/// device under-delivered.
+fn fail_on_sample_shortfalls(shortfalls: &[String]) -> Result<()> {
+    if shortfalls.is_empty() {
+        return Ok(());
+    }
+    bail!(MobenchError::ResultsUnavailable(format!(
+        "--strict: {} benchmark(s) returned fewer samples than requested: {}",
+        shortfalls.len(),
+        shortfalls.join(", ")
+    )))
+}
+
+/// Removes device specs that failed validation for `--keep-going`, returning
+/// each dropped spec with its reason.
+///
+/// Fails when no valid device is left, since there is nothing to run.
+fn drop_invalid_devices(
+    spec: &mut RunSpec,
+    invalid: &[browserstack::DeviceValidationError],
+) -> Result<BTreeMap<String, String>> {
+    let skipped: BTreeMap<String, String> = invalid
+        .iter()
+        .map(|error| (error.spec.clone(), error.reason.clone()))
+        .collect();
+    spec.devices.retain(|device| !skipped.contains_key(device));
+    if spec.devices.is_empty() {
+        bail!(MobenchError::DeviceInvalid(format!(
+            "all {} device specs are invalid; nothing left to run",
+            skipped.len()
+        )));
+    }
+    for group in &mut spec.device_groups {
+        group.devices.retain(|device| !skipped.contains_key(device));
+    }
+    spec.device_groups.retain(|group| !group.devices.is_empty());
+    Ok(skipped)
+}
+
+/// Picks devices for a [`DeviceStrategy`], keeping the resolved order.
+///
+/// `tag_candidates` lists the matrix devices for each configured tag; without
+/// tags, `FirstAvailable` picks a single device from `devices`. `OnePerOs`
+/// groups devices by the OS version suffix of their identifier
+/// (`"Google Pixel 7-13.0"` -> `13.0`).
+fn select_devices(
+    strategy: DeviceStrategy,
+    devices: &[String],
+    tag_candidates: &[(String, Vec<String>)],
+    unavailable: &BTreeSet<String>,
+) -> Vec<String> {
+    let usable = |device: &&String| devices.contains(device) && !unavailable.contains(*device);
+    let mut selected: Vec<String> = Vec::new();
+    match strategy {
+        DeviceStrategy::All => return devices.to_vec(),
+        DeviceStrategy::FirstAvailable if tag_candidates.is_empty() => {
+            selected.extend(devices.iter().find(usable).cloned());
+        }
+        DeviceStrategy::FirstAvailable => {
+            for (_, candidates) in tag_candidates {
+                if let Some(device) = candidates.iter().find(usable)
+                    && !selected.contains(device)
+                {
+                    selected.push(device.clone());
+                }
+            }
+        }
+        DeviceStrategy::OnePerOs => {
+            let mut versions = BTreeSet::new();
+            for device in devices.iter().filter(usable) {
+                let version = device.rsplit_once('-').map_or("", |(_, version)| version);
+                if versions.insert(version) {
+                    selected.push(device.clone());
+                }
+            }
+        }
+    }
+    selected
 }
 
 fn filter_devices_by_tags(devices: Vec<DeviceEntry>, tags: &[String]) -> Result<Vec<String>> {
@@ -1511,26 +4507,37 @@ fn filter_devices_by_tags(devices: Vec<DeviceEntry>, tags: &[String]) -> Result<
 
     if matched.is_empty() {
         if available_tags.is_empty() {
-            bail!(
+            bail!(MobenchError::DeviceInvalid(format!(
                 "no devices matched tags [{}] in device matrix; no tag metadata found in the matrix",
                 wanted.join(", ")
-            );
+            )));
         }
         let available = available_tags.into_iter().collect::<Vec<_>>().join(", ");
-        bail!(
+        bail!(MobenchError::DeviceInvalid(format!(
             "no devices matched tags [{}] in device matrix. Available tags: {}",
             wanted.join(", "),
             available
-        );
+        )));
     }
     Ok(matched)
 }
 
-fn run_ios_build(release: bool) -> Result<(PathBuf, PathBuf)> {
+fn run_ios_build(
+    release: bool,
+    cargo_profile: Option<&str>,
+    output_dir: &Path,
+    dry_run: bool,
+) -> Result<(PathBuf, PathBuf)> {
     let root = repo_root()?;
     let crate_name =
         detect_bench_mobile_crate_name(&root).unwrap_or_else(|_| "bench-mobile".to_string());
-    let builder = mobench_sdk::builders::IosBuilder::new(&root, crate_name).verbose(true);
+    let resolver = config::ConfigResolver::new().context("loading mobench.toml")?;
+    let builder = mobench_sdk::builders::IosBuilder::new(&root, crate_name)
+        .output_dir(output_dir)
+        .verbose(true)
+        .dry_run(dry_run)
+        .app_options(resolver.ios_app_options()?)
+        .max_lib_size_kb(resolver.max_lib_size_kb());
     let profile = if release {
         mobench_sdk::BuildProfile::Release
     } else {
@@ -1540,8 +4547,11 @@ fn run_ios_build(release: bool) -> Result<(PathBuf, PathBuf)> {
         target: mobench_sdk::Target::Ios,
         profile,
         incremental: true,
+        cache: true,
+        cargo_profile: cargo_profile.map(str::to_string),
     };
-    let result = builder.build(&cfg)?;
+    let _span = tracing::info_span!("build", target = "ios", release).entered();
+    let result = logging::step("build", || Ok(builder.build(&cfg).map_err(MobenchError::build_failed)?))?;
     let header = root.join("target/ios/include").join(format!(
         "{}.h",
         result
@@ -1553,11 +4563,20 @@ fn run_ios_build(release: bool) -> Result<(PathBuf, PathBuf)> {
     Ok((result.app_path, header))
 }
 
-fn package_ios_xcuitest_artifacts(release: bool) -> Result<IosXcuitestArtifacts> {
+fn package_ios_xcuitest_artifacts(
+    release: bool,
+    cargo_profile: Option<&str>,
+    output_dir: &Path,
+) -> Result<IosXcuitestArtifacts> {
     let root = repo_root()?;
     let crate_name =
         detect_bench_mobile_crate_name(&root).unwrap_or_else(|_| "bench-mobile".to_string());
-    let builder = mobench_sdk::builders::IosBuilder::new(&root, crate_name).verbose(true);
+    let resolver = config::ConfigResolver::new().context("loading mobench.toml")?;
+    let builder = mobench_sdk::builders::IosBuilder::new(&root, crate_name)
+        .output_dir(output_dir)
+        .verbose(true)
+        .app_options(resolver.ios_app_options()?)
+        .max_lib_size_kb(resolver.max_lib_size_kb());
     let profile = if release {
         mobench_sdk::BuildProfile::Release
     } else {
@@ -1567,9 +4586,12 @@ fn package_ios_xcuitest_artifacts(release: bool) -> Result<IosXcuitestArtifacts>
         target: mobench_sdk::Target::Ios,
         profile,
         incremental: true,
+        cache: true,
+        cargo_profile: cargo_profile.map(str::to_string),
     };
     builder
         .build(&cfg)
+        .map_err(MobenchError::build_failed)
         .context("Failed to build iOS xcframework before packaging")?;
     let app = builder
         .package_ipa("BenchRunner", mobench_sdk::builders::SigningMethod::AdHoc)
@@ -1580,6 +4602,21 @@ fn package_ios_xcuitest_artifacts(release: bool) -> Result<IosXcuitestArtifacts>
     Ok(IosXcuitestArtifacts { app, test_suite })
 }
 
+/// Zip the app's dSYM, if the last device build produced one.
+fn package_ios_dsym(output_dir: &Path) -> Option<PathBuf> {
+    let root = repo_root().ok()?;
+    let crate_name =
+        detect_bench_mobile_crate_name(&root).unwrap_or_else(|_| "bench-mobile".to_string());
+    let builder = mobench_sdk::builders::IosBuilder::new(&root, crate_name).output_dir(output_dir);
+    match builder.package_dsym("BenchRunner") {
+        Ok(path) => Some(path),
+        Err(err) => {
+            println!("Note: no dSYM packaged; on-device crashes will be unsymbolicated ({err})");
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ResolvedBrowserStack {
     username: String,
@@ -1671,91 +4708,512 @@ fn validate_artifacts_for_browserstack(
             missing_artifacts: missing,
             target,
         };
-        bail!("{}", error.format_error());
+        bail!(MobenchError::ArtifactMissing(error.format_error()));
     }
 
     Ok(())
 }
 
-fn trigger_browserstack_espresso(spec: &RunSpec, apk: &Path, test_apk: &Path) -> Result<RemoteRun> {
-    // Validate artifacts exist before attempting upload
-    validate_artifacts_for_browserstack(MobileTarget::Android, Some(apk), Some(test_apk), None)?;
-
-    let creds = resolve_browserstack_credentials(spec.browserstack.as_ref())?;
-    let client = BrowserStackClient::new(
-        BrowserStackAuth {
-            username: creds.username.clone(),
-            access_key: creds.access_key.clone(),
-        },
-        creds.project.clone(),
-    )?;
-
-    // Upload the app-under-test APK.
-    let upload = client.upload_espresso_app(apk)?;
-
-    // Upload the Espresso test-suite APK produced by Gradle.
-    let test_upload = client.upload_espresso_test_suite(test_apk)?;
+/// Whether `run` should use devices attached to this machine instead of
+/// BrowserStack: `--devices local`, or any devices with `[backend] provider =
+/// "local"`.
+fn uses_local_devices(devices: &[String], provider: config::BackendProvider) -> Result<bool> {
+    let explicit = devices.iter().any(|d| d == local_device::ALL_LOCAL_DEVICES);
+    if explicit && devices.len() > 1 {
+        bail!("--devices local selects every attached device and cannot be combined with other devices");
+    }
+    Ok(!devices.is_empty() && (explicit || provider == config::BackendProvider::Local))
+}
 
-    // Schedule the Espresso build with both app and testSuite, as required by BrowserStack.
-    let run = client.schedule_espresso_run(
-        &spec.devices,
-        &upload.app_url,
-        &test_upload.test_suite_url,
-    )?;
+/// Renders the resolved spec and the `bench_spec.json` payload for `--dry-run`,
+/// with every `[env]` value redacted (see [`redacted_env_value`]).
+fn render_dry_run_spec(spec: &RunSpec) -> Result<String> {
+    let mut shown = spec.clone();
+    redact_env_values(&mut shown.env);
+    Ok(format!(
+        "[dry-run] Resolved run spec:\n{}\n\n[dry-run] Would write bench_spec.json:\n{}\n",
+        serde_json::to_string_pretty(&shown)?,
+        serde_json::to_string_pretty(&mobile_spec_payload(&shown)?)?,
+    ))
+}
+
+/// Print everything `run` would do without building, uploading, or calling
+/// BrowserStack.
+#[allow(clippy::too_many_arguments)]
+fn print_run_dry_run_plan(
+    spec: &RunSpec,
+    release: bool,
+    cargo_profile: Option<&str>,
+    local_only: bool,
+    skip_build: bool,
+    local_devices: bool,
+    output_dir: &Path,
+    summary_paths: &SummaryPaths,
+) -> Result<()> {
+    print!("{}", render_dry_run_spec(spec)?);
+
+    if local_only {
+        println!();
+        println!("[dry-run] --local-only set: no mobile build or BrowserStack run");
+    } else if skip_build {
+        println!();
+        println!("[dry-run] --skip-build set: no build; prebuilt artifacts would be validated and uploaded");
+    } else {
+        // The SDK builders print their exact cargo/gradle/xcodebuild plan in dry-run mode
+        match spec.target {
+            MobileTarget::Android => {
+                let ndk = env::var("ANDROID_NDK_HOME").unwrap_or_default();
+                run_android_build(&ndk, release, cargo_profile, output_dir, true)?;
+            }
+            MobileTarget::Ios => {
+                run_ios_build(release, cargo_profile, output_dir, true)?;
+            }
+        }
+
+        println!();
+        if spec.devices.is_empty() {
+            println!("[dry-run] Devices: (none - BrowserStack upload/run skipped)");
+        } else if local_devices {
+            println!("[dry-run] Local devices: {}", spec.devices.join(", "));
+            match spec.target {
+                MobileTarget::Android => println!(
+                    "  adb install -r -t <apk> <test apk>, then adb shell am instrument -w <app id>.test/androidx.test.runner.AndroidJUnitRunner"
+                ),
+                MobileTarget::Ios => {
+                    println!("  ios-deploy --id <udid> --bundle <app> --noninteractive")
+                }
+            }
+        } else {
+            println!("[dry-run] Devices:");
+            for device in &spec.devices {
+                println!("  - {}", device);
+            }
+            // Credentials are only read locally for the project name; nothing is sent
+            let project = resolve_browserstack_credentials(spec.browserstack.as_ref())
+                .ok()
+                .and_then(|creds| creds.project);
+            let (platform, body) = match spec.target {
+                MobileTarget::Android => (
+                    "espresso",
+                    serde_json::to_value(browserstack::BuildRequest::new(
+                        project,
+                        &spec.devices,
+                        "<app_url from upload>",
+                        "<test_suite_url from upload>",
+                    ))?,
+                ),
+                MobileTarget::Ios => (
+                    "xcuitest",
+                    serde_json::to_value(browserstack::XcuitestBuildRequest::new(
+                        project,
+                        &spec.devices,
+                        "<app_url from upload>",
+                        "<test_suite_url from upload>",
+                    ))?,
+                ),
+            };
+            println!();
+            println!("[dry-run] BrowserStack requests:");
+            println!("  POST app-automate/{}/v2/app", platform);
+            println!("  POST app-automate/{}/v2/test-suite", platform);
+            if spec.target == MobileTarget::Ios && spec.ios_xcuitest.is_none() {
+                println!(
+                    "  (the app and XCUITest runner would first be packaged with xcodebuild, as in `package-ipa` and `package-xcuitest`)"
+                );
+            }
+            println!("  POST app-automate/{}/v2/build", platform);
+            println!("{}", serde_json::to_string_pretty(&body)?);
+        }
+    }
 
-    // Print dashboard link early so users can monitor progress
-    println!();
-    println!("BrowserStack build started!");
-    println!("  Build ID: {}", run.build_id);
-    println!("  Devices:  {}", spec.devices.join(", "));
-    println!("  Dashboard: https://app-automate.browserstack.com/dashboard/v2/builds/{}", run.build_id);
     println!();
-    println!("Waiting for results...");
+    println!("[dry-run] Would write summaries:");
+    println!("  {}", summary_paths.json.display());
+    println!("  {}", summary_paths.markdown.display());
+    Ok(())
+}
 
-    Ok(RemoteRun::Android {
-        app_url: upload.app_url,
-        build_id: run.build_id,
-    })
+/// Resolve the Android artifacts for `run --skip-build`.
+///
+/// The app is the `--android-aab` bundle when given, otherwise
+/// `--prebuilt-apk`. The test APK is required only when uploading to devices.
+fn prebuilt_android_artifacts(
+    prebuilt_apk: Option<PathBuf>,
+    prebuilt_test_apk: Option<PathBuf>,
+    android_aab: Option<PathBuf>,
+    needs_test_suite: bool,
+) -> Result<(PathBuf, Option<PathBuf>)> {
+    let apk = android_aab.or(prebuilt_apk).context(
+        "--skip-build for Android requires --prebuilt-apk <APK> (or --android-aab <AAB>)",
+    )?;
+    if needs_test_suite && prebuilt_test_apk.is_none() {
+        bail!("--skip-build with BrowserStack devices requires --prebuilt-test-apk <APK> (the Espresso test suite)");
+    }
+    validate_artifacts_for_browserstack(
+        MobileTarget::Android,
+        Some(&apk),
+        prebuilt_test_apk.as_deref(),
+        None,
+    )?;
+    Ok((apk, prebuilt_test_apk))
+}
+
+/// Describe device sessions whose state changed since the last poll, e.g.
+/// `Google Pixel 7-13.0: queued -> running`. `last` maps session IDs to the
+/// state seen on the previous poll.
+fn session_transitions(
+    last: &mut BTreeMap<String, String>,
+    status: &browserstack::BuildStatus,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for session in &status.devices {
+        let state = session.status.to_lowercase();
+        match last.insert(session.session_id.clone(), state.clone()) {
+            Some(previous) if previous == state => {}
+            Some(previous) => lines.push(format!("{}: {} -> {}", session.device, previous, state)),
+            None => lines.push(format!("{}: {}", session.device, state)),
+        }
+    }
+    lines
+}
+
+/// Wait for scheduled builds and fetch their results, re-scheduling devices
+/// that finished without a `BENCH_REPORT_JSON` up to `options.device_retries`
+/// times.
+///
+/// `runs` holds one build per scheduling call: a single build normally, one
+/// per device when `--no-fail-fast` had to schedule devices separately.
+/// Returns the merged results and, for each re-scheduled device, the number
+/// of retries it took (or used up, if it never reported).
+fn fetch_with_device_retries(
+    client: &BrowserStackClient,
+    platform: &'static str,
+    runs: &[(orchestrate::ScheduledRun, Vec<String>)],
+    options: &RunOptions,
+    json_lines: Option<&json_lines::JsonLinesWriter>,
+) -> Result<orchestrate::RunSummary<browserstack::PerformanceMetrics>> {
+    let backend = BrowserStackBackend::new(client, platform).with_json_lines(json_lines);
+    let mut merged: Option<orchestrate::RunSummary<browserstack::PerformanceMetrics>> = None;
+    for (run, devices) in runs {
+        let summary = orchestrate::collect_results(devices, run, &backend, options)?;
+        match &mut merged {
+            None => merged = Some(summary),
+            Some(merged) => {
+                merged.results.merge(summary.results);
+                merged.retried.extend(summary.retried);
+                merged.missing.extend(summary.missing);
+                merged.unscheduled.extend(summary.unscheduled);
+            }
+        }
+    }
+    let summary = merged.context("no BrowserStack build to fetch")?;
+    // Without fail-fast, devices with no results are reported per device
+    if options.fail_fast && summary.results.benchmarks.is_empty() {
+        bail!(MobenchError::ResultsUnavailable(
+            "No benchmark results found from any device".into()
+        ));
+    }
+    Ok(summary)
+}
+
+/// The second and later rounds of a `--repeat` run on BrowserStack.
+struct Repeats<'a> {
+    /// Total number of rounds, the first included.
+    count: u32,
+    /// Starts the builds of one more round.
+    start: Option<&'a dyn Fn() -> Result<orchestrate::StartedRuns>>,
+    /// The rounds started so far, for their build ids and unscheduled devices.
+    started: &'a mut Vec<orchestrate::StartedRuns>,
+}
+
+/// Fetch a `--repeat` run: the first round's builds in `runs`, then each
+/// later round, started only once the previous one finished so that the
+/// rounds never compete for parallel sessions. Every device's samples are
+/// pooled with [`aggregate_repeats`].
+///
+/// Performance metrics and device metadata come from the first round that
+/// reported them, retries add up across rounds, and a device counts as
+/// missing only when no round reported it.
+fn fetch_repeated_runs(
+    client: &BrowserStackClient,
+    platform: &'static str,
+    runs: &[(orchestrate::ScheduledRun, Vec<String>)],
+    repeats: Repeats<'_>,
+    options: &RunOptions,
+    json_lines: Option<&json_lines::JsonLinesWriter>,
+) -> Result<orchestrate::RunSummary<browserstack::PerformanceMetrics>> {
+    let mut summary = fetch_with_device_retries(client, platform, runs, options, json_lines)?;
+    let Some(start) = repeats.start.filter(|_| repeats.count > 1) else {
+        return Ok(summary);
+    };
+    let mut rounds = vec![std::mem::take(&mut summary.results.benchmarks)];
+    for index in 2..=repeats.count {
+        println!("Starting repeat {} of {}...", index, repeats.count);
+        let repeat = start()?;
+        repeats.started.push(repeat);
+        let repeat = repeats.started.last().expect("just pushed");
+        if repeat.runs.is_empty() {
+            continue;
+        }
+        println!("Fetching repeat {} of {}...", index, repeats.count);
+        let round = fetch_with_device_retries(client, platform, &repeat.runs, options, json_lines)?;
+        rounds.push(round.results.benchmarks);
+        for (device, metrics) in round.results.metrics {
+            summary.results.metrics.entry(device).or_insert(metrics);
+        }
+        for (device, meta) in round.results.device_meta {
+            summary.results.device_meta.entry(device).or_insert(meta);
+        }
+        for (device, retries) in round.retried {
+            *summary.retried.entry(device).or_default() += retries;
+        }
+        summary.missing.extend(round.missing);
+        for (device, reason) in round.unscheduled {
+            summary.unscheduled.entry(device).or_insert(reason);
+        }
+    }
+    summary.results.benchmarks = aggregate_repeats(rounds);
+    summary.missing.sort();
+    summary.missing.dedup();
+    summary.missing.retain(|device| {
+        !summary
+            .results
+            .benchmarks
+            .keys()
+            .any(|reported| device_names_match(device, reported))
+    });
+    let missing = &summary.missing;
+    summary.unscheduled.retain(|device, _| missing.contains(device));
+    Ok(summary)
+}
+
+/// Run on local devices `repeat` times and pool the rounds with
+/// [`aggregate_repeats`].
+///
+/// A device counts as failed only when no round reported it; failures of
+/// later rounds name the round.
+fn run_local_repeats(
+    backend: &local_device::LocalDeviceBackend,
+    devices: &[local_device::LocalDevice],
+    artifacts: &local_device::LocalArtifacts<'_>,
+    fail_fast: bool,
+    repeat: u32,
+    failures: &mut DeviceFailures,
+    json_lines: Option<&json_lines::JsonLinesWriter>,
+) -> Result<BTreeMap<String, Vec<Value>>> {
+    let mut rounds = Vec::new();
+    let mut round_failures = BTreeMap::new();
+    for round in 1..=repeat {
+        if repeat > 1 {
+            println!("Repeat {} of {}", round, repeat);
+        }
+        let local_run = backend.run(devices, artifacts, fail_fast, json_lines)?;
+        for (device, reason) in local_run.failures {
+            let reason = if repeat > 1 {
+                format!("repeat {}: {}", round, reason)
+            } else {
+                reason
+            };
+            round_failures.entry(device).or_insert(reason);
+        }
+        rounds.push(local_run.results);
+    }
+    let pooled = aggregate_repeats(rounds);
+    for (device, reason) in round_failures {
+        if !pooled.keys().any(|reported| device_names_match(&device, reported)) {
+            failures.no_results.entry(device).or_insert(reason);
+        }
+    }
+    Ok(pooled)
+}
+
+/// Pool the device results of `--repeat` rounds, given in round order.
+///
+/// A device's reports from later rounds are appended to its first report of
+/// the same function: their `samples`, `cpu_samples_ns` and `warmup_samples`
+/// are concatenated, so are the series of each custom metric, and
+/// `warmup_iterations` and `dropped_samples` add up. `repeat_medians_ns`
+/// lists each round's median for [`BenchmarkStats::repeat_cv_pct`]. Other
+/// fields keep the first round's values. A single round is returned
+/// unchanged.
+fn aggregate_repeats(rounds: Vec<BTreeMap<String, Vec<Value>>>) -> BTreeMap<String, Vec<Value>> {
+    if rounds.len() <= 1 {
+        return rounds.into_iter().next().unwrap_or_default();
+    }
+    let mut pooled: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for results in rounds {
+        for (device, entries) in results {
+            let reports = pooled.entry(device).or_default();
+            for entry in entries {
+                let median = SampleStats::from_samples(&extract_samples(&entry)).map(|stats| stats.median_ns);
+                let same_function = reports
+                    .iter()
+                    .position(|report| report.get("function") == entry.get("function"));
+                match same_function {
+                    Some(index) => {
+                        let Some(report) = reports[index].as_object_mut() else {
+                            continue;
+                        };
+                        for key in ["samples", "cpu_samples_ns", "warmup_samples"] {
+                            append_series(report, key, entry.get(key));
+                        }
+                        if let Some(Value::Object(from)) = entry.get("metrics") {
+                            let metrics = report.entry("metrics").or_insert_with(|| json!({}));
+                            if let Some(metrics) = metrics.as_object_mut() {
+                                for (name, values) in from {
+                                    append_series(metrics, name, Some(values));
+                                }
+                            }
+                        }
+                        for key in ["warmup_iterations", "dropped_samples"] {
+                            if let Some(count) = entry.get(key).and_then(Value::as_u64) {
+                                let total = report.get(key).and_then(Value::as_u64).unwrap_or(0) + count;
+                                report.insert(key.to_string(), json!(total));
+                            }
+                        }
+                        if let Some(Value::Array(medians)) = report.get_mut("repeat_medians_ns") {
+                            medians.push(json!(median));
+                        }
+                    }
+                    None => {
+                        let mut report = entry;
+                        if let Some(fields) = report.as_object_mut() {
+                            fields.insert("repeat_medians_ns".to_string(), json!([median]));
+                        }
+                        reports.push(report);
+                    }
+                }
+            }
+        }
+    }
+    pooled
+}
+
+/// Append the array `from` to the array at `key`, creating it if missing.
+fn append_series(into: &mut serde_json::Map<String, Value>, key: &str, from: Option<&Value>) {
+    let Some(Value::Array(from)) = from else {
+        return;
+    };
+    if let Value::Array(series) = into.entry(key).or_insert_with(|| json!([])) {
+        series.extend(from.iter().cloned());
+    }
+}
+
+/// Coefficient of variation of the per-round medians that
+/// [`aggregate_repeats`] recorded, in percent.
+fn repeat_cv_pct(entry: &Value) -> Option<f64> {
+    let medians: Vec<f64> = entry
+        .get("repeat_medians_ns")?
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_u64)
+        .map(|median| median as f64)
+        .collect();
+    if medians.len() < 2 {
+        return None;
+    }
+    compute_metric_stats(&medians).map(|stats| stats.cv_pct)
+}
+
+fn trigger_browserstack_espresso(
+    spec: &RunSpec,
+    apk: &Path,
+    test_apk: &Path,
+    fail_fast: bool,
+) -> Result<(UploadedArtifacts, orchestrate::StartedRuns)> {
+    // Validate artifacts exist before attempting upload
+    validate_artifacts_for_browserstack(MobileTarget::Android, Some(apk), Some(test_apk), None)?;
+
+    start_browserstack_run(spec, "espresso", apk, test_apk, fail_fast)
 }
 
 fn trigger_browserstack_xcuitest(
     spec: &RunSpec,
     artifacts: &IosXcuitestArtifacts,
-) -> Result<RemoteRun> {
+    fail_fast: bool,
+) -> Result<(UploadedArtifacts, orchestrate::StartedRuns)> {
     // Validate artifacts exist before attempting upload
     validate_artifacts_for_browserstack(MobileTarget::Ios, None, None, Some(artifacts))?;
 
-    let creds = resolve_browserstack_credentials(spec.browserstack.as_ref())?;
-    let client = BrowserStackClient::new(
-        BrowserStackAuth {
-            username: creds.username.clone(),
-            access_key: creds.access_key.clone(),
-        },
-        creds.project.clone(),
-    )?;
+    start_browserstack_run(spec, "xcuitest", &artifacts.app, &artifacts.test_suite, fail_fast)
+}
 
-    let app_upload = client.upload_xcuitest_app(&artifacts.app)?;
-    let test_upload = client.upload_xcuitest_test_suite(&artifacts.test_suite)?;
-    let run = client.schedule_xcuitest_run(
-        &spec.devices,
-        &app_upload.app_url,
-        &test_upload.test_suite_url,
-    )?;
+/// Upload the app and test suite and schedule them on `spec.devices` with
+/// [`schedule_browserstack_run`].
+///
+/// The uploads are returned so later rounds of `--repeat` can schedule them
+/// again without uploading the same artifacts.
+fn start_browserstack_run(
+    spec: &RunSpec,
+    platform: &'static str,
+    app: &Path,
+    test_suite: &Path,
+    fail_fast: bool,
+) -> Result<(UploadedArtifacts, orchestrate::StartedRuns)> {
+    let client = browserstack_client(spec)?;
+    let uploaded = BrowserStackBackend::new(&client, platform).upload(&RunArtifacts {
+        app: app.to_path_buf(),
+        test_suite: test_suite.to_path_buf(),
+    })?;
+    let started = schedule_browserstack_run(&client, spec, platform, &uploaded, fail_fast)?;
+    Ok((uploaded, started))
+}
+
+/// Schedule already uploaded artifacts on `spec.devices`.
+///
+/// Without `fail_fast`, devices that cannot be scheduled together are
+/// scheduled one by one, and those that still fail are returned as
+/// unscheduled rather than aborting the run.
+fn schedule_browserstack_run(
+    client: &BrowserStackClient,
+    spec: &RunSpec,
+    platform: &'static str,
+    uploaded: &UploadedArtifacts,
+    fail_fast: bool,
+) -> Result<orchestrate::StartedRuns> {
+    let backend = BrowserStackBackend::new(client, platform);
+    let started = if fail_fast {
+        let run = orchestrate::ScheduledRun {
+            app_url: uploaded.app_url.clone(),
+            test_suite_url: uploaded.test_suite_url.clone(),
+            build_id: backend.schedule(&spec.devices, uploaded)?,
+        };
+        orchestrate::StartedRuns {
+            runs: vec![(run, spec.devices.clone())],
+            unscheduled: BTreeMap::new(),
+        }
+    } else {
+        orchestrate::schedule_each(&spec.devices, uploaded, &backend)
+    };
+    if started.runs.is_empty() {
+        println!();
+        println!("No device could be scheduled on BrowserStack.");
+        return Ok(started);
+    }
 
     // Print dashboard link early so users can monitor progress
     println!();
     println!("BrowserStack build started!");
-    println!("  Build ID: {}", run.build_id);
-    println!("  Devices:  {}", spec.devices.join(", "));
-    println!("  Dashboard: https://app-automate.browserstack.com/dashboard/v2/builds/{}", run.build_id);
+    for (run, devices) in &started.runs {
+        println!("  Build ID: {}", run.build_id);
+        println!("  Devices:  {}", devices.join(", "));
+        println!("  Dashboard: https://app-automate.browserstack.com/dashboard/v2/builds/{}", run.build_id);
+    }
     println!();
     println!("Waiting for results...");
+    Ok(started)
+}
 
-    Ok(RemoteRun::Ios {
-        app_url: app_upload.app_url,
-        test_suite_url: test_upload.test_suite_url,
-        build_id: run.build_id,
-    })
+/// A BrowserStack client for the credentials and project resolved for `spec`.
+fn browserstack_client(spec: &RunSpec) -> Result<BrowserStackClient> {
+    let creds = resolve_browserstack_credentials(spec.browserstack.as_ref())?;
+    BrowserStackClient::new(
+        BrowserStackAuth {
+            username: creds.username,
+            access_key: creds.access_key,
+        },
+        creds.project,
+    )
 }
 
 fn resolve_browserstack_credentials(
@@ -1775,6 +5233,21 @@ fn resolve_browserstack_credentials(
             .transpose()?;
     }
 
+    // mobench.toml [browserstack], with MOBENCH_BROWSERSTACK_* overrides applied
+    let defaults = config::ConfigResolver::new()
+        .context("loading mobench.toml")?
+        .browserstack();
+    let unset = |value: &Option<String>| value.as_deref().map(str::is_empty).unwrap_or(true);
+    if unset(&username) {
+        username = defaults.username.as_deref().map(expand_env_var).transpose()?;
+    }
+    if unset(&access_key) {
+        access_key = defaults.access_key.as_deref().map(expand_env_var).transpose()?;
+    }
+    if project.is_none() {
+        project = defaults.project.as_deref().map(expand_env_var).transpose()?;
+    }
+
     if username.as_deref().map(str::is_empty).unwrap_or(true)
         && let Ok(val) = env::var("BROWSERSTACK_USERNAME")
         && !val.is_empty()
@@ -1794,13 +5267,34 @@ fn resolve_browserstack_credentials(
         project = Some(val);
     }
 
+    // Per-user credentials file, then the macOS Keychain
+    if unset(&username) || unset(&access_key) {
+        let stored = credentials::load_default()?;
+        if unset(&username) {
+            username = stored.username;
+        }
+        if unset(&access_key) {
+            access_key = stored.access_key;
+        }
+        if project.is_none() {
+            project = stored.project;
+        }
+    }
+    if unset(&username)
+        && unset(&access_key)
+        && let Some((keychain_username, keychain_access_key)) = credentials::keychain()
+    {
+        username = Some(keychain_username);
+        access_key = Some(keychain_access_key);
+    }
+
     // Check what's missing and provide helpful error message
     let missing_username = username.as_deref().map(str::is_empty).unwrap_or(true);
     let missing_access_key = access_key.as_deref().map(str::is_empty).unwrap_or(true);
 
     if missing_username || missing_access_key {
         let error_msg = browserstack::format_credentials_error(missing_username, missing_access_key);
-        bail!("{}", error_msg);
+        bail!(MobenchError::AuthFailed(error_msg));
     }
 
     Ok(ResolvedBrowserStack {
@@ -1827,10 +5321,17 @@ fn run_local_smoke(spec: &RunSpec) -> Result<Value> {
         name: spec.function.clone(),
         iterations: spec.iterations,
         warmup: spec.warmup,
+        warmup_policy: Default::default(),
+        sampling: Default::default(),
+        per_iteration_timeout: None,
+        timeout: None,
+        capture_warmup: false,
+        seed: None,
+        drop_first_n: 0,
     };
 
-    let report =
-        mobench_sdk::run_benchmark(bench_spec).map_err(|e| anyhow!("benchmark failed: {e}"))?;
+    let report = with_bench_env(&spec.env, || mobench_sdk::run_benchmark(bench_spec))
+        .map_err(|e| anyhow!("benchmark failed: {e}"))?;
 
     serde_json::to_value(&report).context("serializing benchmark report")
 }
@@ -1865,20 +5366,18 @@ fn validate_benchmark_function(project_root: &Path, function_name: &str) -> Resu
         let dir_crate_name = crate_name.as_deref().unwrap_or(function_crate);
 
         // Detect all benchmarks in this directory
-        let benchmarks = mobench_sdk::codegen::detect_all_benchmarks(dir, dir_crate_name);
+        let benchmarks = mobench_sdk::codegen::detect_benchmarks_with_metadata(dir, dir_crate_name);
 
         if !benchmarks.is_empty() {
             found_any_benchmarks = true;
 
-            // Check if our function is in the list
-            if benchmarks.iter().any(|b| b == function_name) {
-                found_function = true;
-                break;
-            }
-
-            // Also check without crate prefix (in case user specified just the function name)
+            // Match registered names (including `name = "..."` renames and
+            // submodule paths), Rust function paths, or bare function names
             let simple_name = function_name.split("::").last().unwrap_or(function_name);
-            if benchmarks.iter().any(|b| b.ends_with(&format!("::{}", simple_name))) {
+            if benchmarks
+                .iter()
+                .any(|b| b.matches(function_name) || b.matches(simple_name))
+            {
                 found_function = true;
                 break;
             }
@@ -1918,35 +5417,79 @@ fn validate_benchmark_function(project_root: &Path, function_name: &str) -> Resu
     Ok(())
 }
 
-fn persist_mobile_spec(spec: &RunSpec, release: bool) -> Result<()> {
-    let root = repo_root()?;
-    let payload = json!({
+/// The `bench_spec.json` payload read by the mobile runners.
+fn mobile_spec_payload(spec: &RunSpec) -> Result<Value> {
+    let (iterations, warmup) = spec.embedded_counts();
+    let mut payload = json!({
         "function": spec.function,
-        "iterations": spec.iterations,
-        "warmup": spec.warmup,
+        "iterations": iterations,
+        "warmup": warmup,
     });
-    let contents = serde_json::to_string_pretty(&payload)?;
+    if let Some(sampling) = &spec.sampling {
+        payload["sampling"] = serde_json::to_value(sampling)?;
+    }
+    if let Some(policy) = &spec.warmup_policy {
+        payload["warmup_policy"] = serde_json::to_value(policy)?;
+    }
+    if !spec.functions.is_empty() {
+        payload["functions"] = json!(spec.functions);
+    }
+    if let Some(seed) = spec.seed {
+        payload["seed"] = json!(seed);
+    }
+    if let Some(secs) = spec.per_benchmark_timeout_secs {
+        payload["per_benchmark_timeout_secs"] = json!(secs);
+    }
+    if let Some(ms) = spec.per_iteration_timeout_ms {
+        payload["per_iteration_timeout_ms"] = json!(ms);
+    }
+    if spec.drop_first_n > 0 {
+        payload["drop_first_n"] = json!(spec.drop_first_n);
+    }
+    if spec.capture_warmup {
+        payload["capture_warmup"] = json!(true);
+    }
+    if spec.startup_metrics {
+        payload["startup_metrics"] = json!(true);
+    }
+    if !spec.pin_cores.is_none() {
+        payload["pin_cores"] = json!(spec.pin_cores);
+    }
+    if spec.sample_interleave {
+        payload["interleave"] = json!(true);
+    }
+    if !spec.env.is_empty() {
+        payload["env"] = json!(spec.env);
+    }
+    Ok(payload)
+}
 
-    // Write to legacy mobile-spec locations for backward compatibility
-    let legacy_targets = [
-        root.join("target/mobile-spec/android/bench_spec.json"),
-        root.join("target/mobile-spec/ios/bench_spec.json"),
-    ];
-    for path in legacy_targets {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("creating directory {:?}", parent))?;
+fn persist_mobile_spec(spec: &RunSpec, release: bool, mobench_output_dir: &Path) -> Result<()> {
+    let root = repo_root()?;
+    let contents = serde_json::to_string_pretty(&mobile_spec_payload(spec)?)?;
+
+    // Write to legacy mobile-spec locations for backward compatibility. They
+    // are shared by every run, so runs with their own --output-dir skip them.
+    if mobench_output_dir == root.join("target/mobench") {
+        let legacy_targets = [
+            root.join("target/mobile-spec/android/bench_spec.json"),
+            root.join("target/mobile-spec/ios/bench_spec.json"),
+        ];
+        for path in legacy_targets {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("creating directory {:?}", parent))?;
+            }
+            write_file(&path, contents.as_bytes())?;
         }
-        write_file(&path, contents.as_bytes())?;
     }
 
     // IMPORTANT: Also embed the spec directly into the mobile app bundles
     // This ensures the requested benchmark function is always used, even when
     // the app is run via BrowserStack where file paths are different.
-    let mobench_output_dir = root.join("target/mobench");
     let apps_exist = mobench_output_dir.join("android").exists() || mobench_output_dir.join("ios").exists();
 
-    if let Err(e) = embed_spec_into_apps(&mobench_output_dir, spec) {
+    if let Err(e) = embed_spec_into_apps(mobench_output_dir, spec) {
         // Only warn if the apps don't exist yet - they'll be created during build
         if apps_exist {
             println!("Warning: Failed to embed bench spec into app bundles: {}", e);
@@ -1962,7 +5505,7 @@ fn persist_mobile_spec(spec: &RunSpec, release: bool) -> Result<()> {
         MobileTarget::Ios => "ios",
     };
 
-    if let Err(e) = embed_meta_into_apps(&mobench_output_dir, spec, target_str, profile) {
+    if let Err(e) = embed_meta_into_apps(mobench_output_dir, spec, target_str, profile) {
         if apps_exist {
             println!("Warning: Failed to embed bench meta into app bundles: {}", e);
         }
@@ -1975,10 +5518,23 @@ fn persist_mobile_spec(spec: &RunSpec, release: bool) -> Result<()> {
 
 /// Embeds the benchmark spec into Android assets and iOS bundle resources.
 fn embed_spec_into_apps(output_dir: &Path, spec: &RunSpec) -> Result<()> {
+    let (iterations, warmup) = spec.embedded_counts();
     let embedded_spec = mobench_sdk::builders::EmbeddedBenchSpec {
         function: spec.function.clone(),
-        iterations: spec.iterations,
-        warmup: spec.warmup,
+        iterations,
+        warmup,
+        sampling: spec.sampling.clone(),
+        warmup_policy: spec.warmup_policy.clone(),
+        functions: spec.functions.clone(),
+        seed: spec.seed,
+        per_benchmark_timeout_secs: spec.per_benchmark_timeout_secs,
+        per_iteration_timeout_ms: spec.per_iteration_timeout_ms,
+        capture_warmup: spec.capture_warmup,
+        drop_first_n: spec.drop_first_n,
+        startup_metrics: spec.startup_metrics,
+        pin_cores: spec.pin_cores,
+        interleave: spec.sample_interleave,
+        env: spec.env.clone(),
     };
     mobench_sdk::builders::embed_bench_spec(output_dir, &embedded_spec)
         .map_err(|e| anyhow!("Failed to embed bench spec: {}", e))
@@ -1986,10 +5542,23 @@ fn embed_spec_into_apps(output_dir: &Path, spec: &RunSpec) -> Result<()> {
 
 /// Embeds build metadata (bench_meta.json) into Android assets and iOS bundle resources.
 fn embed_meta_into_apps(output_dir: &Path, spec: &RunSpec, target: &str, profile: &str) -> Result<()> {
+    let (iterations, warmup) = spec.embedded_counts();
     let embedded_spec = mobench_sdk::builders::EmbeddedBenchSpec {
         function: spec.function.clone(),
-        iterations: spec.iterations,
-        warmup: spec.warmup,
+        iterations,
+        warmup,
+        sampling: spec.sampling.clone(),
+        warmup_policy: spec.warmup_policy.clone(),
+        functions: spec.functions.clone(),
+        seed: spec.seed,
+        per_benchmark_timeout_secs: spec.per_benchmark_timeout_secs,
+        per_iteration_timeout_ms: spec.per_iteration_timeout_ms,
+        capture_warmup: spec.capture_warmup,
+        drop_first_n: spec.drop_first_n,
+        startup_metrics: spec.startup_metrics,
+        pin_cores: spec.pin_cores,
+        interleave: spec.sample_interleave,
+        env: spec.env.clone(),
     };
     mobench_sdk::builders::embed_bench_meta(output_dir, &embedded_spec, target, profile)
         .map_err(|e| anyhow!("Failed to embed bench meta: {}", e))
@@ -2025,7 +5594,73 @@ fn empty_summary(spec: &RunSpec) -> SummaryReport {
         warmup: spec.warmup,
         devices: spec.devices.clone(),
         device_summaries: Vec::new(),
+        noisy_cv_threshold_pct: DEFAULT_NOISY_CV_PCT,
+        budget_violations: Vec::new(),
+        device_retries: BTreeMap::new(),
+        repeat_count: 1,
+        skipped_devices: BTreeMap::new(),
+        device_failures: DeviceFailures::default(),
+        device_status: BTreeMap::new(),
+    }
+}
+
+/// Summary statistics of one device report.
+fn benchmark_stats(entry: &Value, sampling: Option<&mobench_sdk::SamplingMode>) -> BenchmarkStats {
+    let function = entry
+        .get("function")
+        .and_then(|f| f.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let samples = extract_samples(entry);
+    let stats = SampleStats::from_samples(&samples);
+    let mean_ns = stats
+        .as_ref()
+        .map(|s| s.mean_ns)
+        .or_else(|| entry.get("mean_ns").and_then(|m| m.as_u64()));
+
+    BenchmarkStats {
+        function,
+        samples: samples.len(),
+        mean_ns,
+        median_ns: stats.as_ref().map(|s| s.median_ns),
+        p95_ns: stats.as_ref().map(|s| s.p95_ns),
+        p99_ns: stats.as_ref().map(|s| s.p99_ns),
+        min_ns: stats.as_ref().map(|s| s.min_ns),
+        max_ns: stats.as_ref().map(|s| s.max_ns),
+        cv_pct: stats.as_ref().map(|s| s.cv_pct),
+        mom_ns: stats.as_ref().map(|s| s.mom_ns),
+        worst_device: None,
+        warmup_ns: extract_warmup_samples(entry),
+        median_ci_pct: median_ci_pct(entry, &samples, sampling),
+        cpu_median_ns: cpu_median_ns(entry),
+        metrics: summarize_metrics(extract_metrics(entry)),
+        timed_out: report_timed_out(entry),
+        affinity: report_affinity(entry),
+        repeat_cv_pct: repeat_cv_pct(entry),
+    }
+}
+
+/// Key under which [`compact_report`] stores a report's [`BenchmarkStats`].
+const STREAMED_STATS_KEY: &str = "summary_stats";
+
+/// Report fields that grow with the number of samples.
+const RAW_SAMPLE_FIELDS: [&str; 4] = ["samples", "cpu_samples_ns", "warmup_samples", "metrics"];
+
+/// A streamed report reduced to what the run summary needs: its statistics
+/// under [`STREAMED_STATS_KEY`] and every field except the raw samples.
+///
+/// Used by `run --json-lines --no-summary-json`, which has already written
+/// the full report out, so the run holds one device's samples at a time.
+fn compact_report(report: &Value, sampling: Option<&mobench_sdk::SamplingMode>) -> Value {
+    let mut compact = report.clone();
+    if let Some(fields) = compact.as_object_mut() {
+        for field in RAW_SAMPLE_FIELDS {
+            fields.remove(field);
+        }
+        let stats = serde_json::to_value(benchmark_stats(report, sampling)).unwrap_or(Value::Null);
+        fields.insert(STREAMED_STATS_KEY.to_string(), stats);
     }
+    compact
 }
 
 fn build_summary(run_summary: &RunSummary) -> Result<SummaryReport> {
@@ -2043,37 +5678,39 @@ fn build_summary(run_summary: &RunSummary) -> Result<SummaryReport> {
         for (device, entries) in results {
             let mut benchmarks = Vec::new();
             for entry in entries {
-                let function = entry
-                    .get("function")
-                    .and_then(|f| f.as_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-                let samples = extract_samples(entry);
-                let stats = compute_sample_stats(&samples);
-                let mean_ns = stats
-                    .as_ref()
-                    .map(|s| s.mean_ns)
-                    .or_else(|| entry.get("mean_ns").and_then(|m| m.as_u64()));
-
-                benchmarks.push(BenchmarkStats {
-                    function,
-                    samples: samples.len(),
-                    mean_ns,
-                    median_ns: stats.as_ref().map(|s| s.median_ns),
-                    p95_ns: stats.as_ref().map(|s| s.p95_ns),
-                    min_ns: stats.as_ref().map(|s| s.min_ns),
-                    max_ns: stats.as_ref().map(|s| s.max_ns),
-                });
+                let stats = match entry.get(STREAMED_STATS_KEY) {
+                    Some(stats) => serde_json::from_value(stats.clone())
+                        .with_context(|| format!("reading streamed statistics of {}", device))?,
+                    None => benchmark_stats(entry, run_summary.spec.sampling.as_ref()),
+                };
+                benchmarks.push(stats);
             }
 
             benchmarks.sort_by(|a, b| a.function.cmp(&b.function));
+            let device_meta = run_summary
+                .device_meta
+                .as_ref()
+                .and_then(|meta| meta.get(device))
+                .cloned()
+                .unwrap_or_default();
             device_summaries.push(DeviceSummary {
                 device: device.clone(),
+                device_meta,
+                members: Vec::new(),
                 benchmarks,
+                target: None,
             });
         }
     }
 
+    if let Some(results) = &run_summary.benchmark_results {
+        for group in &run_summary.spec.device_groups {
+            if let Some(rollup) = summarize_device_group(group, results) {
+                device_summaries.push(rollup);
+            }
+        }
+    }
+
     if device_summaries.is_empty()
         && let Some(local_summary) = summarize_local_report(run_summary)
     {
@@ -2089,21 +5726,46 @@ fn build_summary(run_summary: &RunSummary) -> Result<SummaryReport> {
         warmup: run_summary.spec.warmup,
         devices: run_summary.spec.devices.clone(),
         device_summaries,
+        noisy_cv_threshold_pct: DEFAULT_NOISY_CV_PCT,
+        budget_violations: Vec::new(),
+        device_retries: BTreeMap::new(),
+        skipped_devices: BTreeMap::new(),
+        device_failures: DeviceFailures::default(),
+        repeat_count: 1,
+        device_status: BTreeMap::new(),
     })
 }
 
-fn write_summary(summary: &RunSummary, paths: &SummaryPaths, summary_csv: bool) -> Result<()> {
-    let json = serde_json::to_string_pretty(summary)?;
-    ensure_parent_dir(&paths.json)?;
-    write_file(&paths.json, json.as_bytes())?;
-    println!("Wrote run summary to {:?}", paths.json);
+/// Which summary files [`write_summary`] writes besides the Markdown.
+#[derive(Debug, Clone, Copy)]
+struct SummaryFiles {
+    /// The aggregate JSON summary; off for `--no-summary-json`.
+    json: bool,
+    /// `--summary-csv`.
+    csv: bool,
+    /// `--compact-json`: minify the JSON, which holds every device's raw samples.
+    compact_json: bool,
+}
+
+/// Write the Markdown summary and the JSON and CSV ones `files` asks for.
+fn write_summary(summary: &RunSummary, paths: &SummaryPaths, files: SummaryFiles) -> Result<()> {
+    if files.json {
+        let json = if files.compact_json {
+            serde_json::to_string(summary)?
+        } else {
+            serde_json::to_string_pretty(summary)?
+        };
+        ensure_parent_dir(&paths.json)?;
+        write_file(&paths.json, json.as_bytes())?;
+        println!("Wrote run summary to {:?}", paths.json);
+    }
 
     let markdown = render_markdown_summary(&summary.summary);
     ensure_parent_dir(&paths.markdown)?;
     write_file(&paths.markdown, markdown.as_bytes())?;
     println!("Wrote markdown summary to {:?}", paths.markdown);
 
-    if summary_csv {
+    if files.csv {
         let csv = render_csv_summary(&summary.summary);
         ensure_parent_dir(&paths.csv)?;
         write_file(&paths.csv, csv.as_bytes())?;
@@ -2201,6 +5863,12 @@ fn print_run_completion_summary(
             if ios_dir.exists() {
                 println!("  iOS Framework:    {}/", ios_dir.display());
             }
+            if let Some(MobileArtifacts::Ios {
+                dsym: Some(dsym), ..
+            }) = &summary.artifacts
+            {
+                println!("  iOS dSYM:         {}", dsym.display());
+            }
         }
     }
 
@@ -2215,12 +5883,7 @@ fn print_run_completion_summary(
         println!("  Bench Spec:       {}", spec_path.display());
     }
 
-    let meta_path = match summary.spec.target {
-        MobileTarget::Android => output_dir.join("android/app/src/main/assets/bench_meta.json"),
-        MobileTarget::Ios => {
-            output_dir.join("ios/BenchRunner/BenchRunner/Resources/bench_meta.json")
-        }
-    };
+    let meta_path = bench_meta_path(output_dir, summary.spec.target);
     if meta_path.exists() {
         println!("  Bench Meta:       {}", meta_path.display());
     }
@@ -2244,6 +5907,12 @@ fn ensure_parent_dir(path: &Path) -> Result<()> {
 struct CompareReport {
     baseline: PathBuf,
     candidate: PathBuf,
+    /// Git commit the baseline was built from, if recorded.
+    baseline_commit: Option<String>,
+    /// Git commit the candidate was built from, if recorded.
+    candidate_commit: Option<String>,
+    /// Reasons the comparison may not be meaningful.
+    warnings: Vec<String>,
     rows: Vec<CompareRow>,
 }
 
@@ -2257,33 +5926,278 @@ struct CompareRow {
     baseline_p95_ns: Option<u64>,
     candidate_p95_ns: Option<u64>,
     p95_delta_pct: Option<f64>,
+    baseline_mom_ns: Option<u64>,
+    candidate_mom_ns: Option<u64>,
+    mom_delta_pct: Option<f64>,
 }
 
-fn compare_summaries(baseline: &Path, candidate: &Path) -> Result<CompareReport> {
-    let baseline_summary = load_run_summary(baseline)?;
-    let candidate_summary = load_run_summary(candidate)?;
-
-    let baseline_map = summary_lookup(&baseline_summary.summary);
-    let candidate_map = summary_lookup(&candidate_summary.summary);
-
-    let mut rows = Vec::new();
-    let mut devices: BTreeMap<String, ()> = BTreeMap::new();
-    devices.extend(baseline_map.keys().map(|k| (k.clone(), ())));
-    devices.extend(candidate_map.keys().map(|k| (k.clone(), ())));
+/// Split the results of an `--ab-self` run, in which every function ran
+/// twice, into the first pass (kept in `run_summary`) and the second pass.
+fn split_ab_self(run_summary: &mut RunSummary) -> BTreeMap<String, Vec<Value>> {
+    let functions = &mut run_summary.spec.functions;
+    functions.truncate(functions.len() / 2);
+    if functions.len() == 1 {
+        functions.clear();
+    }
+    let mut second_pass = BTreeMap::new();
+    for (device, entries) in run_summary.benchmark_results.iter_mut().flatten() {
+        let mut seen = BTreeSet::new();
+        let (first, repeated): (Vec<Value>, Vec<Value>) =
+            std::mem::take(entries).into_iter().partition(|entry| {
+                seen.insert(entry.get("function").and_then(Value::as_str).map(str::to_string))
+            });
+        *entries = first;
+        if !repeated.is_empty() {
+            second_pass.insert(device.clone(), repeated);
+        }
+    }
+    second_pass
+}
 
-    for device in devices.keys() {
-        let mut functions: BTreeMap<String, ()> = BTreeMap::new();
-        if let Some(entry) = baseline_map.get(device) {
-            functions.extend(entry.keys().map(|k| (k.clone(), ())));
+/// Compare the two passes of an `--ab-self` run and print the noise floor.
+///
+/// The second pass is written next to the run summary as `*.ab-self.json`
+/// and the comparison as `*.ab-self.compare.md`. Any regression found
+/// between identical passes is spurious: the threshold sits below the
+/// measurement noise. Returns those findings without failing the run.
+fn report_ab_self(
+    run_summary: &RunSummary,
+    second_pass: BTreeMap<String, Vec<Value>>,
+    summary_path: &Path,
+    thresholds: &RegressionThresholds,
+) -> Result<Vec<RegressionFinding>> {
+    println!();
+    if second_pass.is_empty() {
+        println!("A/A check: no device reported a second pass; nothing to compare");
+        return Ok(Vec::new());
+    }
+    let mut second = RunSummary {
+        schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+        spec: run_summary.spec.clone(),
+        artifacts: None,
+        local_report: json!({ "skipped": true, "reason": "--ab-self second pass" }),
+        remote_run: None,
+        summary: empty_summary(&run_summary.spec),
+        benchmark_results: Some(second_pass),
+        performance_metrics: None,
+        device_meta: run_summary.device_meta.clone(),
+        build_meta: run_summary.build_meta.clone(),
+        remote_runs: Vec::new(),
+    };
+    second.summary = build_summary(&second)?;
+    let second_path = summary_path.with_extension("ab-self.json");
+    write_file(&second_path, serde_json::to_string_pretty(&second)?.as_bytes())?;
+
+    let report = compare_summaries(summary_path, &second_path)?;
+    let findings = detect_regressions(&report, thresholds);
+    println!("A/A check (same build, two passes):");
+    let mut noise_floor: Option<f64> = None;
+    for row in &report.rows {
+        let (metric, delta) = match thresholds.primary_metric {
+            PrimaryMetric::Median => ("median", row.median_delta_pct),
+            PrimaryMetric::Mom => ("mom", row.mom_delta_pct),
+        };
+        if let Some(delta) = delta {
+            noise_floor = Some(noise_floor.unwrap_or(0.0).max(delta.abs()));
         }
-        if let Some(entry) = candidate_map.get(device) {
-            functions.extend(entry.keys().map(|k| (k.clone(), ())));
+        println!(
+            "  {} / {}: {} {}, p95 {}",
+            row.device,
+            row.function,
+            metric,
+            format_delta(delta),
+            format_delta(row.p95_delta_pct)
+        );
+    }
+    if let Some(floor) = noise_floor {
+        println!("Noise floor: {:.2}% (largest change between identical passes)", floor);
+    }
+    if findings.is_empty() {
+        println!("No spurious regressions above {}", thresholds.describe());
+    } else {
+        println!(
+            "Warning: {} spurious regression(s) between identical passes; the {} threshold is below the measurement noise",
+            findings.len(),
+            thresholds.describe()
+        );
+        for finding in &findings {
+            println!("  {}", finding.describe());
         }
+    }
+    write_compare_report(
+        &report,
+        &findings,
+        &[],
+        None,
+        None,
+        Some(&summary_path.with_extension("ab-self.compare.md")),
+    )?;
+    Ok(findings)
+}
 
-        for function in functions.keys() {
-            let baseline_stats = baseline_map
-                .get(device)
-                .and_then(|entry| entry.get(function));
+/// Compare the run summary at `summary_path` against `baseline_path`, writing
+/// the Markdown report next to the summary and printing what was found.
+fn compare_against_baseline(
+    baseline_path: &Path,
+    summary_path: &Path,
+    thresholds: &RegressionThresholds,
+) -> Result<(Vec<RegressionFinding>, Vec<SuspiciousImprovement>)> {
+    compare_against_baseline_at(
+        baseline_path,
+        summary_path,
+        None,
+        thresholds,
+        &summary_path.with_extension("compare.md"),
+    )
+}
+
+/// [`compare_against_baseline`], limited to `function` when given and
+/// writing the Markdown report to `compare_path`.
+fn compare_against_baseline_at(
+    baseline_path: &Path,
+    summary_path: &Path,
+    function: Option<&str>,
+    thresholds: &RegressionThresholds,
+    compare_path: &Path,
+) -> Result<(Vec<RegressionFinding>, Vec<SuspiciousImprovement>)> {
+    let mut report = compare_summaries(baseline_path, summary_path)?;
+    if let Some(function) = function {
+        report.rows.retain(|row| {
+            row.function == function || row.function.ends_with(&format!("::{function}"))
+        });
+    }
+    report_compare_warnings(&report);
+    let findings = detect_regressions(&report, thresholds);
+    let suspicious = detect_suspicious_improvements(&report, thresholds);
+    write_compare_report(&report, &findings, &suspicious, None, None, Some(compare_path))?;
+    if findings.is_empty() {
+        println!("No regressions above {}", thresholds.describe());
+    }
+    report_regressions(&findings);
+    report_suspicious_improvements(&suspicious);
+    Ok((findings, suspicious))
+}
+
+/// Materialize the results of an earlier BrowserStack build as a run summary
+/// in `cache_dir`, fetching it only when it is not cached yet.
+///
+/// Finished builds never change, so the cached summary is reused as is.
+fn fetch_browserstack_baseline(
+    spec: &RunSpec,
+    build_id: &str,
+    cache_dir: &Path,
+    timeout_secs: u64,
+    poll_interval_secs: u64,
+) -> Result<PathBuf> {
+    let key: String = build_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let target = format!("{:?}", spec.target).to_lowercase();
+    let path = cache_dir.join("browserstack").join(target).join(format!("{key}.json"));
+    if path.exists() {
+        println!("Using cached BrowserStack baseline {:?}", path);
+        return Ok(path);
+    }
+
+    let creds = resolve_browserstack_credentials(spec.browserstack.as_ref())?;
+    let client = BrowserStackClient::new(
+        BrowserStackAuth {
+            username: creds.username,
+            access_key: creds.access_key,
+        },
+        creds.project,
+    )?;
+    let platform = match spec.target {
+        MobileTarget::Android => "espresso",
+        MobileTarget::Ios => "xcuitest",
+    };
+    println!("Fetching baseline results from BrowserStack build {}...", build_id);
+    let results = client
+        .wait_and_fetch_all_results_with_poll(
+            build_id,
+            platform,
+            Some(timeout_secs),
+            Some(poll_interval_secs),
+        )
+        .with_context(|| format!("fetching baseline build {build_id}"))?;
+    let baseline = browserstack_baseline_summary(spec, results)?;
+    if baseline.benchmark_results.as_ref().is_none_or(BTreeMap::is_empty) {
+        bail!(MobenchError::ResultsUnavailable(format!(
+            "BrowserStack build {build_id} has no benchmark results to compare against"
+        )));
+    }
+    ensure_parent_dir(&path)?;
+    fs::write(&path, serde_json::to_string_pretty(&baseline)?)
+        .with_context(|| format!("writing {:?}", path))?;
+    Ok(path)
+}
+
+/// Run summary for the results of an earlier build, described by the
+/// candidate's `spec` with the devices and counts the build actually used.
+fn browserstack_baseline_summary(
+    spec: &RunSpec,
+    (benchmarks, metrics, device_meta): browserstack::BrowserStackResults,
+) -> Result<RunSummary> {
+    let benchmarks: BTreeMap<String, Vec<Value>> = benchmarks.into_iter().collect();
+    let mut spec = spec.clone();
+    spec.devices = benchmarks.keys().cloned().collect();
+    if let Some(report_spec) = benchmarks.values().flatten().find_map(|entry| entry.get("spec")) {
+        if let Some(iterations) = report_spec.get("iterations").and_then(Value::as_u64) {
+            spec.iterations = iterations as u32;
+        }
+        if let Some(warmup) = report_spec.get("warmup").and_then(Value::as_u64) {
+            spec.warmup = warmup as u32;
+        }
+    }
+    let mut summary = RunSummary {
+        schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+        summary: empty_summary(&spec),
+        spec,
+        artifacts: None,
+        local_report: json!({ "skipped": true, "reason": "BrowserStack baseline" }),
+        remote_run: None,
+        benchmark_results: Some(benchmarks),
+        performance_metrics: Some(metrics.into_iter().collect()),
+        device_meta: (!device_meta.is_empty()).then(|| device_meta.into_iter().collect()),
+        build_meta: None,
+        remote_runs: Vec::new(),
+    };
+    summary.summary = build_summary(&summary)?;
+    Ok(summary)
+}
+
+fn compare_summaries(baseline: &Path, candidate: &Path) -> Result<CompareReport> {
+    let baseline_summary = load_run_summary(baseline)?;
+    let candidate_summary = load_run_summary(candidate)?;
+    let baseline_build = summary_build(&baseline_summary);
+    let candidate_build = summary_build(&candidate_summary);
+    let warnings = comparability_warnings(
+        (&baseline_summary.spec, &baseline_build),
+        (&candidate_summary.spec, &candidate_build),
+    );
+
+    let baseline_map = summary_lookup(&baseline_summary.summary);
+    let candidate_map = summary_lookup(&candidate_summary.summary);
+
+    let mut rows = Vec::new();
+    let mut devices: BTreeMap<String, ()> = BTreeMap::new();
+    devices.extend(baseline_map.keys().map(|k| (k.clone(), ())));
+    devices.extend(candidate_map.keys().map(|k| (k.clone(), ())));
+
+    for device in devices.keys() {
+        let mut functions: BTreeMap<String, ()> = BTreeMap::new();
+        if let Some(entry) = baseline_map.get(device) {
+            functions.extend(entry.keys().map(|k| (k.clone(), ())));
+        }
+        if let Some(entry) = candidate_map.get(device) {
+            functions.extend(entry.keys().map(|k| (k.clone(), ())));
+        }
+
+        for function in functions.keys() {
+            let baseline_stats = baseline_map
+                .get(device)
+                .and_then(|entry| entry.get(function));
             let candidate_stats = candidate_map
                 .get(device)
                 .and_then(|entry| entry.get(function));
@@ -2296,6 +6210,10 @@ fn compare_summaries(baseline: &Path, candidate: &Path) -> Result<CompareReport>
             let candidate_p95 = candidate_stats.and_then(|s| s.p95_ns);
             let p95_delta = percent_delta(baseline_p95, candidate_p95);
 
+            let baseline_mom = baseline_stats.and_then(|s| s.mom_ns);
+            let candidate_mom = candidate_stats.and_then(|s| s.mom_ns);
+            let mom_delta = percent_delta(baseline_mom, candidate_mom);
+
             rows.push(CompareRow {
                 device: device.clone(),
                 function: function.clone(),
@@ -2305,6 +6223,9 @@ fn compare_summaries(baseline: &Path, candidate: &Path) -> Result<CompareReport>
                 baseline_p95_ns: baseline_p95,
                 candidate_p95_ns: candidate_p95,
                 p95_delta_pct: p95_delta,
+                baseline_mom_ns: baseline_mom,
+                candidate_mom_ns: candidate_mom,
+                mom_delta_pct: mom_delta,
             });
         }
     }
@@ -2312,1970 +6233,8022 @@ fn compare_summaries(baseline: &Path, candidate: &Path) -> Result<CompareReport>
     Ok(CompareReport {
         baseline: baseline.to_path_buf(),
         candidate: candidate.to_path_buf(),
+        baseline_commit: baseline_build.commit,
+        candidate_commit: candidate_build.commit,
+        warnings,
         rows,
     })
 }
 
-fn load_run_summary(path: &Path) -> Result<RunSummary> {
-    let contents = fs::read_to_string(path).with_context(|| format!("reading {:?}", path))?;
-    serde_json::from_str(&contents).with_context(|| format!("parsing summary {:?}", path))
+/// Where a run summary's app came from, as far as its metadata tells.
+#[derive(Debug, Default)]
+struct SummaryBuild {
+    commit: Option<String>,
+    dirty: bool,
+    /// Clock sources the summary's reports were timed with.
+    clocks: BTreeSet<String>,
 }
 
-fn summary_lookup(summary: &SummaryReport) -> BTreeMap<String, BTreeMap<String, BenchmarkStats>> {
-    let mut map = BTreeMap::new();
-    for device in &summary.device_summaries {
-        let mut functions = BTreeMap::new();
-        for bench in &device.benchmarks {
-            functions.insert(bench.function.clone(), bench.clone());
-        }
-        map.insert(device.device.clone(), functions);
+/// The commit a summary was built from: its recorded `build_meta`, or else
+/// the `build_meta` echoed by the first device report that has one. Also
+/// collects the clock each report was timed with.
+fn summary_build(summary: &RunSummary) -> SummaryBuild {
+    let device_reports = || {
+        summary
+            .benchmark_results
+            .iter()
+            .flat_map(|results| results.values().flatten())
+    };
+    // Runners that predate the `clock` field timed with `Instant`
+    let clocks = device_reports()
+        .chain(summary.local_report.get("spec").map(|_| &summary.local_report))
+        .map(|report| {
+            report
+                .get("clock")
+                .and_then(Value::as_str)
+                .unwrap_or(mobench_sdk::ClockSource::Instant.as_str())
+                .to_string()
+        })
+        .collect();
+    if let Some(meta) = &summary.build_meta {
+        return SummaryBuild {
+            commit: meta.commit_hash.clone(),
+            dirty: meta.dirty.unwrap_or(false),
+            clocks,
+        };
+    }
+    let commit = device_reports().find_map(|report| {
+        report
+            .pointer("/build_meta/commit_hash")
+            .and_then(Value::as_str)
+            .map(String::from)
+    });
+    SummaryBuild {
+        commit,
+        dirty: false,
+        clocks,
     }
-    map
 }
 
-fn percent_delta(baseline: Option<u64>, candidate: Option<u64>) -> Option<f64> {
-    let baseline = baseline? as f64;
-    let candidate = candidate? as f64;
-    if baseline == 0.0 {
-        return None;
-    }
-    Some(((candidate - baseline) / baseline) * 100.0)
+/// Reasons a baseline/candidate pair should not be treated as authoritative.
+fn comparability_warnings(
+    (baseline, baseline_build): (&RunSpec, &SummaryBuild),
+    (candidate, candidate_build): (&RunSpec, &SummaryBuild),
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let (Some(base), Some(cand)) = (&baseline_build.commit, &candidate_build.commit)
+        && base == cand
+        && !baseline_build.dirty
+        && !candidate_build.dirty
+    {
+        warnings.push(format!(
+            "baseline and candidate were built from the same commit ({}); differences are run-to-run noise",
+            short_sha(base)
+        ));
+    }
+    if baseline.function != candidate.function {
+        warnings.push(format!(
+            "different functions: baseline ran {}, candidate ran {}",
+            baseline.function, candidate.function
+        ));
+    }
+    if baseline.iterations != candidate.iterations || baseline.warmup != candidate.warmup {
+        warnings.push(format!(
+            "different settings: baseline used {} iterations / {} warmup, candidate used {} / {}",
+            baseline.iterations, baseline.warmup, candidate.iterations, candidate.warmup
+        ));
+    }
+    if !baseline_build.clocks.is_empty()
+        && !candidate_build.clocks.is_empty()
+        && baseline_build.clocks != candidate_build.clocks
+    {
+        let join = |clocks: &BTreeSet<String>| clocks.iter().cloned().collect::<Vec<_>>().join(", ");
+        warnings.push(format!(
+            "different clocks: baseline timed with {}, candidate with {}; timer resolution may differ",
+            join(&baseline_build.clocks),
+            join(&candidate_build.clocks)
+        ));
+    }
+    warnings
 }
 
-fn write_compare_report(report: &CompareReport, output: Option<&Path>) -> Result<()> {
-    let markdown = render_compare_markdown(report);
-    if let Some(path) = output {
-        ensure_parent_dir(path)?;
-        write_file(path, markdown.as_bytes())?;
-        println!("Wrote compare report to {:?}", path);
-    } else {
-        println!("{markdown}");
-    }
-    Ok(())
+/// First 12 characters of a commit SHA.
+fn short_sha(sha: &str) -> &str {
+    sha.get(..12).unwrap_or(sha)
 }
 
-fn render_compare_markdown(report: &CompareReport) -> String {
-    let mut output = String::new();
-    let _ = writeln!(output, "# Benchmark Comparison");
-    let _ = writeln!(output);
-    let _ = writeln!(output, "- Baseline: {}", report.baseline.display());
-    let _ = writeln!(output, "- Candidate: {}", report.candidate.display());
-    let _ = writeln!(output);
-    let _ = writeln!(
-        output,
-        "| Device | Function | Median (base ms) | Median (cand ms) | Median Δ% | P95 (base ms) | P95 (cand ms) | P95 Δ% |"
-    );
-    let _ = writeln!(
-        output,
-        "| --- | --- | ---: | ---: | ---: | ---: | ---: | ---: |"
-    );
-    for row in &report.rows {
-        let _ = writeln!(
-            output,
-            "| {} | {} | {} | {} | {} | {} | {} | {} |",
-            row.device,
-            row.function,
-            format_ms(row.baseline_median_ns),
-            format_ms(row.candidate_median_ns),
-            format_delta(row.median_delta_pct),
-            format_ms(row.baseline_p95_ns),
-            format_ms(row.candidate_p95_ns),
-            format_delta(row.p95_delta_pct)
-        );
+fn report_compare_warnings(report: &CompareReport) {
+    for warning in &report.warnings {
+        eprintln!("WARNING: {}", warning);
     }
-    output
 }
 
-fn format_delta(value: Option<f64>) -> String {
-    value
-        .map(|delta| format!("{:+.2}%", delta))
-        .unwrap_or_else(|| "-".to_string())
+fn load_run_summary(path: &Path) -> Result<RunSummary> {
+    let contents = compress::read_to_string(path)?;
+    let value: Value =
+        serde_json::from_str(&contents).with_context(|| format!("parsing summary {:?}", path))?;
+    let value = migrate_run_summary(value).with_context(|| format!("loading summary {:?}", path))?;
+    serde_json::from_value(value).with_context(|| format!("parsing summary {:?}", path))
 }
 
-fn summarize_local_report(run_summary: &RunSummary) -> Option<DeviceSummary> {
-    let samples = extract_samples(&run_summary.local_report);
-    if samples.is_empty() {
-        return None;
+/// Upgrade a run summary to [`RUN_SUMMARY_SCHEMA_VERSION`], one version at a
+/// time, filling defaults for fields older writers left out.
+fn migrate_run_summary(mut value: Value) -> Result<Value> {
+    let version = match value.get("schema_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .with_context(|| format!("invalid schema_version {}", version))?,
+    };
+    if version > RUN_SUMMARY_SCHEMA_VERSION {
+        bail!(
+            "run summary schema version {} is newer than this mobench supports (up to {}); upgrade mobench to read it",
+            version,
+            RUN_SUMMARY_SCHEMA_VERSION
+        );
     }
-    let stats = compute_sample_stats(&samples)?;
-    let function = run_summary
-        .local_report
-        .get("spec")
-        .and_then(|spec| spec.get("name"))
-        .and_then(|name| name.as_str())
-        .unwrap_or(&run_summary.spec.function)
-        .to_string();
-
-    Some(DeviceSummary {
-        device: "local".to_string(),
-        benchmarks: vec![BenchmarkStats {
-            function,
-            samples: samples.len(),
-            mean_ns: Some(stats.mean_ns),
-            median_ns: Some(stats.median_ns),
-            p95_ns: Some(stats.p95_ns),
-            min_ns: Some(stats.min_ns),
-            max_ns: Some(stats.max_ns),
-        }],
-    })
+    let newest_report = value
+        .get("benchmark_results")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|devices| devices.values())
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(|report| report.get("schema_version")?.as_u64())
+        .max();
+    if let Some(report_version) = newest_report
+        && report_version > u64::from(BENCH_REPORT_SCHEMA_VERSION)
+    {
+        bail!(
+            "benchmark report schema version {} is newer than this mobench supports (up to {}); upgrade mobench to read it",
+            report_version,
+            BENCH_REPORT_SCHEMA_VERSION
+        );
+    }
+    let fields = value.as_object_mut().context("run summary is not a JSON object")?;
+    if version < 2 {
+        // Summaries assembled by hand or by early releases could omit these
+        fields.entry("local_report").or_insert(Value::Null);
+        if let Some(summary) = fields.get_mut("summary").and_then(Value::as_object_mut) {
+            summary.entry("generated_at_unix").or_insert(json!(0));
+            let devices: Vec<Value> = summary
+                .get("device_summaries")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|device| device.get("device").cloned())
+                .collect();
+            summary.entry("devices").or_insert(Value::Array(devices));
+        }
+    }
+    fields.insert("schema_version".to_string(), json!(RUN_SUMMARY_SCHEMA_VERSION));
+    Ok(value)
 }
 
-#[derive(Clone, Debug)]
-struct SampleStats {
-    mean_ns: u64,
-    median_ns: u64,
-    p95_ns: u64,
-    min_ns: u64,
-    max_ns: u64,
+/// Completion of every device a run asked for: the spec's devices plus any
+/// that failed, which local runs leave out of the spec.
+fn device_status(
+    devices: &[String],
+    results: Option<&BTreeMap<String, Vec<Value>>>,
+    failures: &DeviceFailures,
+) -> BTreeMap<String, DeviceStatus> {
+    let reported = |device: &str| {
+        results.into_iter().flatten().any(|(reporter, entries)| {
+            !entries.is_empty() && device_names_match(device, reporter)
+        })
+    };
+    devices
+        .iter()
+        .chain(failures.unscheduled.keys())
+        .chain(failures.no_results.keys())
+        .map(|device| {
+            let status = if reported(device) {
+                DeviceStatus::Completed
+            } else {
+                DeviceStatus::Missing
+            };
+            (device.clone(), status)
+        })
+        .collect()
 }
 
-fn compute_sample_stats(samples: &[u64]) -> Option<SampleStats> {
-    if samples.is_empty() {
-        return None;
-    }
-
-    let mut sorted = samples.to_vec();
-    sorted.sort_unstable();
-    let len = sorted.len();
-
-    let mean_ns = (sorted.iter().map(|v| *v as u128).sum::<u128>() / len as u128) as u64;
-    let median_ns = if len % 2 == 1 {
-        sorted[len / 2]
+/// Devices of an earlier run that have no results, in the run's order.
+///
+/// Summaries written before `device_status` was recorded are checked
+/// against their benchmark results instead.
+fn missing_devices(previous: &RunSummary) -> Vec<String> {
+    let status = if previous.summary.device_status.is_empty() {
+        device_status(
+            &previous.spec.devices,
+            previous.benchmark_results.as_ref(),
+            &previous.summary.device_failures,
+        )
     } else {
-        let lower = sorted[(len / 2) - 1];
-        let upper = sorted[len / 2];
-        (lower + upper) / 2
+        previous.summary.device_status.clone()
     };
-    let p95_index = percentile_index(len, 0.95);
-    let p95_ns = sorted[p95_index];
-    let min_ns = sorted[0];
-    let max_ns = sorted[len - 1];
-
-    Some(SampleStats {
-        mean_ns,
-        median_ns,
-        p95_ns,
-        min_ns,
-        max_ns,
-    })
-}
-
-fn percentile_index(len: usize, percentile: f64) -> usize {
-    if len == 0 {
-        return 0;
+    let mut missing: Vec<String> = previous
+        .spec
+        .devices
+        .iter()
+        .filter(|device| status.get(*device) != Some(&DeviceStatus::Completed))
+        .cloned()
+        .collect();
+    for (device, device_status) in &status {
+        if *device_status == DeviceStatus::Missing && !missing.contains(device) {
+            missing.push(device.clone());
+        }
     }
-    let rank = (percentile * len as f64).ceil() as usize;
-    let index = rank.saturating_sub(1);
-    index.min(len - 1)
+    missing
 }
 
-fn extract_samples(value: &Value) -> Vec<u64> {
-    let Some(samples) = value.get("samples").and_then(|s| s.as_array()) else {
-        return Vec::new();
-    };
-    let mut durations = Vec::with_capacity(samples.len());
-    for sample in samples {
-        if let Some(duration) = sample
-            .get("duration_ns")
-            .and_then(|duration| duration.as_u64())
-        {
-            durations.push(duration);
-        } else if let Some(duration) = sample.as_u64() {
-            durations.push(duration);
+/// Refuse to resume a run whose results would not be comparable with this one.
+fn check_resumable(previous: &RunSpec, spec: &RunSpec) -> Result<()> {
+    if previous.target != spec.target {
+        bail!("it ran --target {:?}, not {:?}", previous.target, spec.target);
+    }
+    // The resumed run replays the earlier order, so only the set must match
+    fn sorted(spec: &RunSpec) -> Vec<&str> {
+        let mut functions = spec.all_functions();
+        functions.sort_unstable();
+        functions
+    }
+    if sorted(previous) != sorted(spec) {
+        bail!(
+            "it ran {}, not {}",
+            previous.all_functions().join(", "),
+            spec.all_functions().join(", ")
+        );
+    }
+    if (previous.iterations, previous.warmup) != (spec.iterations, spec.warmup) {
+        bail!(
+            "it ran iterations={} warmup={}, not iterations={} warmup={}",
+            previous.iterations,
+            previous.warmup,
+            spec.iterations,
+            spec.warmup
+        );
+    }
+    if previous.benchmark_defaults != spec.benchmark_defaults {
+        bail!("its functions' #[benchmark] defaults have changed");
+    }
+    if !previous.env.keys().eq(spec.env.keys()) {
+        bail!("it ran with different --env / [env] variables");
+    }
+    // Summaries keep a digest of each value, not the value itself
+    for (key, value) in &spec.env {
+        let recorded = &previous.env[key];
+        if recorded == REDACTED_ENV_VALUE {
+            bail!("it recorded no digest of {key}'s value, so it cannot be compared");
+        }
+        if *recorded != *value && *recorded != redacted_env_value(key, value) {
+            bail!("it ran with a different value of {key}");
         }
     }
-    durations
+    Ok(())
 }
 
-fn render_markdown_summary(summary: &SummaryReport) -> String {
-    let mut output = String::new();
-    let devices = if summary.devices.is_empty() {
-        "none".to_string()
-    } else {
-        summary.devices.join(", ")
+/// Fold a `--resume-from` run into the summary it resumed. Reports from the
+/// resumed devices replace any earlier ones, and the device list becomes the
+/// earlier run's again.
+fn merge_resumed_run(run_summary: &mut RunSummary, previous: RunSummary) {
+    fn merged<V>(earlier: Option<BTreeMap<String, V>>, later: Option<BTreeMap<String, V>>) -> Option<BTreeMap<String, V>> {
+        match (earlier, later) {
+            (Some(mut earlier), Some(later)) => {
+                earlier.extend(later);
+                Some(earlier)
+            }
+            (earlier, later) => later.or(earlier),
+        }
+    }
+    run_summary.benchmark_results =
+        merged(previous.benchmark_results, run_summary.benchmark_results.take());
+    run_summary.performance_metrics =
+        merged(previous.performance_metrics, run_summary.performance_metrics.take());
+    run_summary.device_meta = merged(previous.device_meta, run_summary.device_meta.take());
+    // Every build of both runs, listed when there are several
+    let builds = |remote_run: Option<&RemoteRun>, remote_runs: Vec<RemoteRun>| {
+        if remote_runs.is_empty() {
+            remote_run.into_iter().cloned().collect()
+        } else {
+            remote_runs
+        }
     };
-
-    let _ = writeln!(output, "# Benchmark Summary");
-    let _ = writeln!(output);
-    let _ = writeln!(output, "- Generated: {}", summary.generated_at);
-    let _ = writeln!(output, "- Target: {:?}", summary.target);
-    let _ = writeln!(output, "- Function: {}", summary.function);
-    let _ = writeln!(
-        output,
-        "- Iterations/Warmup: {} / {}",
-        summary.iterations, summary.warmup
-    );
-    let _ = writeln!(output, "- Devices: {}", devices);
-    let _ = writeln!(output);
-
-    if summary.device_summaries.is_empty() {
-        let _ = writeln!(output, "No benchmark samples were collected.");
-        return output;
+    let mut remote_runs = builds(previous.remote_run.as_ref(), previous.remote_runs);
+    remote_runs.extend(builds(run_summary.remote_run.as_ref(), std::mem::take(&mut run_summary.remote_runs)));
+    if remote_runs.len() > 1 {
+        run_summary.remote_runs = remote_runs;
+    }
+    if run_summary.remote_run.is_none() {
+        run_summary.remote_run = previous.remote_run;
+    }
+    let mut devices = previous.spec.devices;
+    for device in std::mem::take(&mut run_summary.spec.devices) {
+        if !devices.contains(&device) {
+            devices.push(device);
+        }
+    }
+    run_summary.spec.devices = devices;
+    if run_summary.build_meta.is_none() {
+        run_summary.build_meta = previous.build_meta;
     }
+}
 
+fn summary_lookup(summary: &SummaryReport) -> BTreeMap<String, BTreeMap<String, BenchmarkStats>> {
+    let mut map = BTreeMap::new();
     for device in &summary.device_summaries {
-        let _ = writeln!(output, "## Device: {}", device.device);
-        let _ = writeln!(output);
-        let _ = writeln!(
-            output,
-            "| Function | Samples | Mean (ms) | Median (ms) | P95 (ms) | Min (ms) | Max (ms) |"
-        );
-        let _ = writeln!(output, "| --- | ---: | ---: | ---: | ---: | ---: | ---: |");
+        let mut functions = BTreeMap::new();
         for bench in &device.benchmarks {
-            let _ = writeln!(
-                output,
-                "| {} | {} | {} | {} | {} | {} | {} |",
-                bench.function,
-                bench.samples,
-                format_ms(bench.mean_ns),
-                format_ms(bench.median_ns),
-                format_ms(bench.p95_ns),
-                format_ms(bench.min_ns),
-                format_ms(bench.max_ns)
-            );
+            functions.insert(bench.function.clone(), bench.clone());
         }
-        let _ = writeln!(output);
+        map.insert(device.device.clone(), functions);
     }
-
-    output
+    map
 }
 
-fn render_csv_summary(summary: &SummaryReport) -> String {
-    let mut output = String::new();
-    let _ = writeln!(
-        output,
-        "device,function,samples,mean_ns,median_ns,p95_ns,min_ns,max_ns"
-    );
-    for device in &summary.device_summaries {
-        for bench in &device.benchmarks {
-            let _ = writeln!(
-                output,
-                "{},{},{},{},{},{},{},{}",
-                device.device,
-                bench.function,
-                bench.samples,
-                bench.mean_ns.map_or(String::from(""), |v| v.to_string()),
-                bench.median_ns.map_or(String::from(""), |v| v.to_string()),
-                bench.p95_ns.map_or(String::from(""), |v| v.to_string()),
-                bench.min_ns.map_or(String::from(""), |v| v.to_string()),
-                bench.max_ns.map_or(String::from(""), |v| v.to_string())
-            );
+/// Merge summary reports in order, later reports winning on device+function conflicts.
+///
+/// When the reports ran on different targets, each device records its own in
+/// [`DeviceSummary::target`]; the merged [`SummaryReport::target`] is the
+/// first report's.
+///
+/// Fails if two reports ran the same function with different iterations or
+/// warmup, since their numbers are not comparable. Each report comes with its
+/// run's `#[benchmark]` defaults, which set the counts of the functions that
+/// have them.
+fn merge_summary_reports(
+    reports: &[(PathBuf, SummaryReport, BTreeMap<String, BenchmarkDefaults>)],
+) -> Result<SummaryReport> {
+    let (_, first, _) = reports.first().context("no summaries to merge")?;
+
+    let mut settings: BTreeMap<String, (u32, u32, &Path)> = BTreeMap::new();
+    let mut lookup: BTreeMap<String, BTreeMap<String, BenchmarkStats>> = BTreeMap::new();
+    let mut device_meta: BTreeMap<String, browserstack::DeviceMeta> = BTreeMap::new();
+    let mut device_targets: BTreeMap<String, MobileTarget> = BTreeMap::new();
+    let mut group_members: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut device_order: Vec<String> = Vec::new();
+    let mut devices: Vec<String> = Vec::new();
+    let mut functions: Vec<String> = Vec::new();
+
+    for (path, report, defaults) in reports {
+        let counts = |function: &str| {
+            let defaults = defaults.get(function).copied().unwrap_or_default();
+            (
+                defaults.sample_size.unwrap_or(report.iterations),
+                defaults.warmup.unwrap_or(report.warmup),
+            )
+        };
+        for device in &report.device_summaries {
+            for bench in &device.benchmarks {
+                let (bench_iterations, bench_warmup) = counts(&bench.function);
+                match settings.get(&bench.function) {
+                    Some((iterations, warmup, other))
+                        if (*iterations, *warmup) != (bench_iterations, bench_warmup) =>
+                    {
+                        bail!(
+                            "cannot merge {:?}: function '{}' ran with iterations={} warmup={} there but iterations={} warmup={} in {:?}",
+                            path,
+                            bench.function,
+                            bench_iterations,
+                            bench_warmup,
+                            iterations,
+                            warmup,
+                            other
+                        );
+                    }
+                    Some(_) => {}
+                    None => {
+                        settings.insert(
+                            bench.function.clone(),
+                            (bench_iterations, bench_warmup, path.as_path()),
+                        );
+                    }
+                }
+            }
+            if !device_order.contains(&device.device) {
+                device_order.push(device.device.clone());
+            }
+            device_targets.insert(device.device.clone(), device.target.unwrap_or(report.target));
+            if !device.device_meta.is_empty() {
+                device_meta.insert(device.device.clone(), device.device_meta.clone());
+            }
+            if !device.members.is_empty() {
+                group_members.insert(device.device.clone(), device.members.clone());
+            }
+        }
+
+        for (device, benches) in summary_lookup(report) {
+            lookup.entry(device).or_default().extend(benches);
+        }
+        for device in &report.devices {
+            if !devices.contains(device) {
+                devices.push(device.clone());
+            }
+        }
+        if !functions.contains(&report.function) {
+            functions.push(report.function.clone());
         }
     }
-    output
+
+    let mixed_targets = reports.iter().any(|(_, report, _)| {
+        report.target != first.target
+            || report
+                .device_summaries
+                .iter()
+                .any(|device| device.target.is_some_and(|target| target != first.target))
+    });
+    let device_summaries = device_order
+        .into_iter()
+        .map(|device| DeviceSummary {
+            target: device_targets.remove(&device).filter(|_| mixed_targets),
+            device_meta: device_meta.remove(&device).unwrap_or_default(),
+            members: group_members.remove(&device).unwrap_or_default(),
+            benchmarks: lookup
+                .remove(&device)
+                .map(|benches| benches.into_values().collect())
+                .unwrap_or_default(),
+            device,
+        })
+        .collect();
+
+    let generated_at_unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("generating timestamp")?
+        .as_secs();
+    let generated_at = OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| generated_at_unix.to_string());
+
+    Ok(SummaryReport {
+        generated_at,
+        generated_at_unix,
+        target: first.target,
+        function: functions.join(", "),
+        iterations: first.iterations,
+        warmup: first.warmup,
+        devices,
+        device_summaries,
+        noisy_cv_threshold_pct: first.noisy_cv_threshold_pct,
+        budget_violations: Vec::new(),
+        device_retries: reports
+            .iter()
+            .flat_map(|(_, report, _)| report.device_retries.clone())
+            .collect(),
+        skipped_devices: reports
+            .iter()
+            .flat_map(|(_, report, _)| report.skipped_devices.clone())
+            .collect(),
+        device_failures: reports.iter().fold(DeviceFailures::default(), |mut all, (_, report, _)| {
+            all.extend(report.device_failures.clone());
+            all
+        }),
+        repeat_count: 1,
+        device_status: BTreeMap::new(),
+    })
 }
 
-/// Formats a duration in nanoseconds to a human-readable string.
-///
-/// The function picks the appropriate unit based on the magnitude:
-/// - Uses milliseconds (ms) by default
-/// - Switches to seconds (s) if the value is >= 1000ms (1 second)
+fn percent_delta(baseline: Option<u64>, candidate: Option<u64>) -> Option<f64> {
+    let baseline = baseline? as f64;
+    let candidate = candidate? as f64;
+    if baseline == 0.0 {
+        return None;
+    }
+    Some(((candidate - baseline) / baseline) * 100.0)
+}
+
+/// Thresholds that decide when a slowdown counts as a regression.
 ///
-/// Examples:
-/// - 500_000 ns -> "0.500ms"
-/// - 1_500_000 ns -> "1.500ms"
-/// - 1_500_000_000 ns -> "1.500s"
-fn format_duration_smart(ns: u64) -> String {
-    let ms = ns as f64 / 1_000_000.0;
-    if ms >= 1000.0 {
-        // Convert to seconds
-        let secs = ms / 1000.0;
-        format!("{:.3}s", secs)
-    } else {
-        format!("{:.3}ms", ms)
+/// The percent threshold always applies. When an absolute threshold is also
+/// given, `--regression-threshold-mode` decides whether a finding needs both
+/// (`and`, the default) or either (`or`) to be exceeded. With `and`, tiny
+/// functions need a meaningful absolute jump and large ones a meaningful
+/// relative jump before anything is reported.
+#[derive(clap::Args, Debug, Clone, Copy)]
+struct RegressionThresholds {
+    #[arg(
+        long = "regression-threshold-pct",
+        default_value_t = 5.0,
+        help = "Percent slowdown in the primary metric or p95 that counts as a regression"
+    )]
+    pct: f64,
+    #[arg(
+        long = "regression-threshold-abs-ns",
+        help = "Absolute slowdown in nanoseconds that counts as a regression (combined with the percent threshold per --regression-threshold-mode)"
+    )]
+    abs_ns: Option<u64>,
+    #[arg(
+        long = "regression-threshold-mode",
+        value_enum,
+        default_value_t = ThresholdMode::And,
+        help = "Whether both (and) or either (or) of the percent and absolute thresholds must be exceeded"
+    )]
+    mode: ThresholdMode,
+    #[arg(
+        long = "primary-metric",
+        value_enum,
+        default_value_t = PrimaryMetric::Median,
+        help = "Central statistic to gate regressions on, alongside p95 (median, or mom for median of means)"
+    )]
+    primary_metric: PrimaryMetric,
+    #[arg(
+        long = "fail-on-improvement",
+        value_name = "PCT",
+        num_args = 0..=1,
+        default_missing_value = "80",
+        help = "Fail when the primary metric gets faster by more than PCT percent (default 80), which usually means the benchmark was optimized away"
+    )]
+    suspicious_improvement_pct: Option<f64>,
+}
+
+/// Options for `compare --markdown-delta-colors`.
+#[derive(clap::Args, Debug, Clone, Copy)]
+struct DeltaColorArgs {
+    #[arg(
+        long,
+        help = "Mark each Markdown delta as an improvement, a regression beyond --regression-threshold-pct, or within noise"
+    )]
+    markdown_delta_colors: bool,
+    #[arg(
+        long = "improvement-threshold-pct",
+        default_value_t = 2.0,
+        requires = "markdown_delta_colors",
+        help = "Percent speedup marked as an improvement by --markdown-delta-colors"
+    )]
+    improvement_pct: f64,
+    #[arg(
+        long,
+        requires = "markdown_delta_colors",
+        help = "Use text markers instead of emoji for --markdown-delta-colors"
+    )]
+    plain: bool,
+}
+
+impl DeltaColorArgs {
+    /// The markers to render, if enabled, with `regression_pct` as the gate.
+    fn markers(&self, regression_pct: f64) -> Option<DeltaMarkers> {
+        self.markdown_delta_colors.then_some(DeltaMarkers {
+            improvement_pct: self.improvement_pct,
+            regression_pct,
+            plain: self.plain,
+        })
     }
 }
 
-fn format_ms(value: Option<u64>) -> String {
-    value
-        .map(format_duration_smart)
-        .unwrap_or_else(|| "-".to_string())
+/// Thresholds for the symbols on compare's Markdown deltas.
+///
+/// Deltas at or below `-improvement_pct` are improvements, deltas at or above
+/// `regression_pct` regressions, and everything between is noise.
+#[derive(Debug, Clone, Copy)]
+struct DeltaMarkers {
+    improvement_pct: f64,
+    regression_pct: f64,
+    plain: bool,
 }
 
-fn run_android_build(_ndk_home: &str, release: bool) -> Result<mobench_sdk::BuildResult> {
-    let root = repo_root()?;
-    let crate_name =
-        detect_bench_mobile_crate_name(&root).unwrap_or_else(|_| "bench-mobile".to_string());
+impl DeltaMarkers {
+    fn symbol(&self, delta_pct: f64) -> &'static str {
+        let (improved, regressed, noise) = if self.plain {
+            ("[better]", "[worse]", "[noise]")
+        } else {
+            ("\u{1f7e2}", "\u{1f534}", "\u{26aa}")
+        };
+        if delta_pct <= -self.improvement_pct {
+            improved
+        } else if delta_pct >= self.regression_pct {
+            regressed
+        } else {
+            noise
+        }
+    }
+}
 
-    let profile = if release {
-        mobench_sdk::BuildProfile::Release
-    } else {
-        mobench_sdk::BuildProfile::Debug
-    };
-    let cfg = mobench_sdk::BuildConfig {
-        target: mobench_sdk::Target::Android,
-        profile,
-        incremental: true,
-    };
-    let builder = mobench_sdk::builders::AndroidBuilder::new(&root, crate_name).verbose(true);
-    let result = builder.build(&cfg)?;
-    Ok(result)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+enum ThresholdMode {
+    And,
+    Or,
 }
 
-fn load_dotenv() {
-    if let Ok(root) = repo_root() {
-        let path = root.join(".env.local");
-        let _ = dotenvy::from_path(path);
+/// Central statistic compared for regressions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+#[clap(rename_all = "lowercase")]
+enum PrimaryMetric {
+    Median,
+    /// Median of means; see [`stats::median_of_means`].
+    Mom,
+}
+
+impl Default for RegressionThresholds {
+    fn default() -> Self {
+        Self {
+            pct: 5.0,
+            abs_ns: None,
+            mode: ThresholdMode::And,
+            primary_metric: PrimaryMetric::Median,
+            suspicious_improvement_pct: None,
+        }
     }
 }
 
-fn repo_root() -> Result<PathBuf> {
-    let cwd = std::env::current_dir().context("resolving repo root from current directory")?;
-    if let Some(root) = find_repo_root(&cwd) {
-        return Ok(root);
+impl RegressionThresholds {
+    /// Whether a slowdown of `delta_pct` percent / `delta_ns` nanoseconds is a regression.
+    fn exceeded(&self, delta_pct: f64, delta_ns: i64) -> bool {
+        let pct_exceeded = delta_pct > self.pct;
+        let Some(abs_ns) = self.abs_ns else {
+            return pct_exceeded;
+        };
+        let abs_exceeded = delta_ns > 0 && delta_ns as u64 > abs_ns;
+        match self.mode {
+            ThresholdMode::And => pct_exceeded && abs_exceeded,
+            ThresholdMode::Or => pct_exceeded || abs_exceeded,
+        }
     }
 
-    let compiled = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("..");
-    if let Ok(path) = compiled.canonicalize() {
-        if let Some(root) = find_repo_root(&path) {
-            return Ok(root);
+    fn describe(&self) -> String {
+        match self.abs_ns {
+            None => format!("{:.2}%", self.pct),
+            Some(abs_ns) => {
+                let joiner = match self.mode {
+                    ThresholdMode::And => "and",
+                    ThresholdMode::Or => "or",
+                };
+                format!("{:.2}% {} {}", self.pct, joiner, format_duration_smart(abs_ns))
+            }
         }
-        return Ok(path);
     }
+}
 
-    Ok(cwd)
+/// A metric that slowed down by more than the allowed threshold.
+#[derive(Debug, Clone, Serialize)]
+struct RegressionFinding {
+    device: String,
+    function: String,
+    /// Which statistic regressed (`median`, `mom` or `p95`).
+    metric: &'static str,
+    baseline_ns: u64,
+    candidate_ns: u64,
+    delta_pct: f64,
+    /// Absolute change (`candidate_ns - baseline_ns`).
+    delta_ns: i64,
+    /// Human-readable threshold that was exceeded, e.g. `5.00% and 0.050ms`.
+    threshold: String,
 }
 
-fn find_repo_root(start: &Path) -> Option<PathBuf> {
-    start
-        .ancestors()
-        .find(|candidate| is_repo_root(candidate))
-        .map(|root| root.to_path_buf())
+impl RegressionFinding {
+    fn describe(&self) -> String {
+        format!(
+            "{} / {}: {} {:+.2}% / +{} ({} -> {}, threshold {})",
+            self.device,
+            self.function,
+            self.metric,
+            self.delta_pct,
+            format_duration_smart(self.delta_ns.unsigned_abs()),
+            format_duration_smart(self.baseline_ns),
+            format_duration_smart(self.candidate_ns),
+            self.threshold
+        )
+    }
 }
 
-fn is_repo_root(candidate: &Path) -> bool {
-    candidate.join("bench-mobile").join("Cargo.toml").is_file()
-        || candidate
-            .join("crates")
-            .join("sample-fns")
-            .join("Cargo.toml")
-            .is_file()
+/// A speedup too large to be believable, from `--fail-on-improvement`.
+///
+/// A 10x "improvement" more often means the compiler removed the work (an
+/// unused result, a constant-folded input) than that the code got faster.
+#[derive(Debug, Clone, Serialize)]
+struct SuspiciousImprovement {
+    device: String,
+    function: String,
+    /// Which statistic improved (`median` or `mom`).
+    metric: &'static str,
+    baseline_ns: u64,
+    candidate_ns: u64,
+    /// Negative percent change, e.g. `-92.5`.
+    delta_pct: f64,
+    threshold_pct: f64,
 }
 
-fn ensure_can_write(path: &Path) -> Result<()> {
-    if path.exists() {
-        bail!("refusing to overwrite existing file: {:?}", path);
+impl SuspiciousImprovement {
+    fn describe(&self) -> String {
+        format!(
+            "{} / {}: {} {:+.2}% ({} -> {}), faster than the {:.2}% improvement limit; check the benchmark was not optimized away",
+            self.device,
+            self.function,
+            self.metric,
+            self.delta_pct,
+            format_duration_smart(self.baseline_ns),
+            format_duration_smart(self.candidate_ns),
+            self.threshold_pct
+        )
     }
-    if let Some(parent) = path.parent()
-        && !parent.as_os_str().is_empty()
-    {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("creating parent directory {:?}", parent))?;
+}
+
+/// Flag primary-metric speedups beyond `--fail-on-improvement`, if set.
+fn detect_suspicious_improvements(
+    report: &CompareReport,
+    thresholds: &RegressionThresholds,
+) -> Vec<SuspiciousImprovement> {
+    let Some(threshold_pct) = thresholds.suspicious_improvement_pct else {
+        return Vec::new();
+    };
+    report
+        .rows
+        .iter()
+        .filter_map(|row| {
+            let (metric, baseline, candidate, delta) = match thresholds.primary_metric {
+                PrimaryMetric::Median => (
+                    "median",
+                    row.baseline_median_ns,
+                    row.candidate_median_ns,
+                    row.median_delta_pct,
+                ),
+                PrimaryMetric::Mom => (
+                    "mom",
+                    row.baseline_mom_ns,
+                    row.candidate_mom_ns,
+                    row.mom_delta_pct,
+                ),
+            };
+            let (baseline_ns, candidate_ns, delta_pct) = (baseline?, candidate?, delta?);
+            (delta_pct < -threshold_pct).then(|| SuspiciousImprovement {
+                device: row.device.clone(),
+                function: row.function.clone(),
+                metric,
+                baseline_ns,
+                candidate_ns,
+                delta_pct,
+                threshold_pct,
+            })
+        })
+        .collect()
+}
+
+/// Print each suspicious improvement to stderr and log it as an event.
+fn report_suspicious_improvements(improvements: &[SuspiciousImprovement]) {
+    for improvement in improvements {
+        eprintln!("SUSPICIOUS IMPROVEMENT: {}", improvement.describe());
+        tracing::warn!(
+            event = "suspicious_improvement",
+            device = %improvement.device,
+            function = %improvement.function,
+            metric = improvement.metric,
+            baseline_ns = improvement.baseline_ns,
+            candidate_ns = improvement.candidate_ns,
+            delta_pct = improvement.delta_pct,
+        );
     }
-    Ok(())
 }
 
-fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
-    fs::write(path, contents).with_context(|| format!("writing file {:?}", path))
+/// Flag every primary metric/p95 that got slower than the configured thresholds.
+fn detect_regressions(
+    report: &CompareReport,
+    thresholds: &RegressionThresholds,
+) -> Vec<RegressionFinding> {
+    let mut findings = Vec::new();
+    for row in &report.rows {
+        let primary = match thresholds.primary_metric {
+            PrimaryMetric::Median => (
+                "median",
+                row.baseline_median_ns,
+                row.candidate_median_ns,
+                row.median_delta_pct,
+            ),
+            PrimaryMetric::Mom => (
+                "mom",
+                row.baseline_mom_ns,
+                row.candidate_mom_ns,
+                row.mom_delta_pct,
+            ),
+        };
+        let metrics = [
+            primary,
+            (
+                "p95",
+                row.baseline_p95_ns,
+                row.candidate_p95_ns,
+                row.p95_delta_pct,
+            ),
+        ];
+        for (metric, baseline, candidate, delta) in metrics {
+            let (Some(baseline_ns), Some(candidate_ns), Some(delta_pct)) =
+                (baseline, candidate, delta)
+            else {
+                continue;
+            };
+            let delta_ns = candidate_ns as i64 - baseline_ns as i64;
+            if thresholds.exceeded(delta_pct, delta_ns) {
+                findings.push(RegressionFinding {
+                    device: row.device.clone(),
+                    function: row.function.clone(),
+                    metric,
+                    baseline_ns,
+                    candidate_ns,
+                    delta_pct,
+                    delta_ns,
+                    threshold: thresholds.describe(),
+                });
+            }
+        }
+    }
+    findings
 }
 
-/// Initialize a new benchmark project using mobench-sdk (Phase 1 MVP)
-fn cmd_init_sdk(
-    target: SdkTarget,
-    project_name: String,
-    output_dir: PathBuf,
-    generate_examples: bool,
-) -> Result<()> {
-    println!("Initializing benchmark project with mobench-sdk...");
-    println!("  Project name: {}", project_name);
-    println!("  Target: {:?}", target);
-    println!("  Output directory: {:?}", output_dir);
+/// A benchmark whose median or p95 exceeded its `[budgets]` limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BudgetViolation {
+    device: String,
+    function: String,
+    /// Which statistic was over budget (`median` or `p95`).
+    metric: String,
+    budget_ns: u64,
+    actual_ns: u64,
+}
 
-    let sdk_config = mobench_sdk::InitConfig {
-        target: target.into(),
-        project_name: project_name.clone(),
-        output_dir: output_dir.clone(),
-        generate_examples,
-    };
+impl BudgetViolation {
+    fn describe(&self) -> String {
+        format!(
+            "{} / {}: {} {} exceeds budget {} (+{:.2}%)",
+            self.device,
+            self.function,
+            self.metric,
+            format_duration_smart(self.actual_ns),
+            format_duration_smart(self.budget_ns),
+            (self.actual_ns as f64 / self.budget_ns as f64 - 1.0) * 100.0
+        )
+    }
+}
 
-    mobench_sdk::codegen::generate_project(&sdk_config).context("Failed to generate project")?;
+fn load_budgets() -> Result<BTreeMap<String, config::FunctionBudget>> {
+    let resolver = config::ConfigResolver::new().context("loading budgets from mobench.toml")?;
+    Ok(resolver.budgets())
+}
 
-    // Generate mobench.toml configuration file
-    let mobench_toml_path = output_dir.join(config::CONFIG_FILE_NAME);
-    if !mobench_toml_path.exists() {
-        let toml_content = config::MobenchConfig::generate_starter_toml(&project_name);
-        fs::write(&mobench_toml_path, toml_content)
-            .with_context(|| format!("Failed to write {:?}", mobench_toml_path))?;
-        println!("  Generated mobench.toml configuration file");
+/// `(device, function, median_ns, p95_ns)` for every benchmark in `summary`.
+fn summary_budget_entries(
+    summary: &SummaryReport,
+) -> impl Iterator<Item = (&str, &str, Option<u64>, Option<u64>)> {
+    summary.device_summaries.iter().flat_map(|device| {
+        device.benchmarks.iter().map(move |bench| {
+            (
+                device.device.as_str(),
+                bench.function.as_str(),
+                bench.median_ns,
+                bench.p95_ns,
+            )
+        })
+    })
+}
+
+/// Check `(device, function, median_ns, p95_ns)` entries against the budgets.
+fn check_budgets<'a>(
+    budgets: &BTreeMap<String, config::FunctionBudget>,
+    entries: impl IntoIterator<Item = (&'a str, &'a str, Option<u64>, Option<u64>)>,
+) -> Vec<BudgetViolation> {
+    let mut violations = Vec::new();
+    if budgets.is_empty() {
+        return violations;
+    }
+    for (device, function, median_ns, p95_ns) in entries {
+        // Budgets apply to real devices; group rollups would double-report them
+        if device.starts_with(GROUP_PREFIX) {
+            continue;
+        }
+        let Some(budget) = budgets.get(function) else {
+            continue;
+        };
+        let (max_median, max_p95) = budget.limits_for(device);
+        for (metric, actual, limit) in [("median", median_ns, max_median), ("p95", p95_ns, max_p95)] {
+            if let (Some(actual_ns), Some(budget_ns)) = (actual, limit)
+                && actual_ns > budget_ns
+            {
+                violations.push(BudgetViolation {
+                    device: device.to_string(),
+                    function: function.to_string(),
+                    metric: metric.to_string(),
+                    budget_ns,
+                    actual_ns,
+                });
+            }
+        }
     }
+    violations
+}
 
-    println!("\n[checkmark] Project initialized successfully!");
-    println!("\nNext steps:");
-    println!("  1. Add benchmark functions to your code with #[benchmark]");
-    println!("  2. Edit mobench.toml to customize your project settings");
-    println!("  3. Run 'cargo mobench build --target <platform>' to build");
+/// Print each regression to stderr and log it as a `regression` event.
+fn report_regressions(findings: &[RegressionFinding]) {
+    for finding in findings {
+        eprintln!("REGRESSION: {}", finding.describe());
+        tracing::warn!(
+            event = "regression",
+            device = %finding.device,
+            function = %finding.function,
+            metric = finding.metric,
+            baseline_ns = finding.baseline_ns,
+            candidate_ns = finding.candidate_ns,
+            delta_pct = finding.delta_pct,
+        );
+    }
+}
 
+fn fail_on_suspicious_improvements(improvements: &[SuspiciousImprovement]) -> Result<()> {
+    if let Some(first) = improvements.first() {
+        bail!(MobenchError::ThresholdExceeded(format!(
+            "{} benchmark(s) improved by more than {:.2}%; this usually means the measured work was optimized away",
+            improvements.len(),
+            first.threshold_pct
+        )));
+    }
     Ok(())
 }
 
-/// Build mobile artifacts using mobench-sdk (Phase 1 MVP)
-fn cmd_build(
-    target: SdkTarget,
-    release: bool,
-    output_dir: Option<PathBuf>,
-    crate_path: Option<PathBuf>,
-    dry_run: bool,
-    verbose: bool,
-    progress: bool,
-) -> Result<()> {
-    // Load config file if present (mobench.toml)
-    let config_resolver = config::ConfigResolver::new().unwrap_or_default();
-
-    // Progress mode: simplified output
-    if progress {
-        let project_root = std::env::current_dir().context("Failed to get current directory")?;
-        let crate_name = detect_bench_mobile_crate_name(&project_root)
-            .unwrap_or_else(|_| "bench-mobile".to_string());
-        let effective_output_dir = output_dir.or_else(|| config_resolver.output_dir().map(|p| p.to_path_buf()));
-
-        let build_config = mobench_sdk::BuildConfig {
-            target: target.into(),
-            profile: if release {
-                mobench_sdk::BuildProfile::Release
-            } else {
-                mobench_sdk::BuildProfile::Debug
-            },
-            incremental: true,
-        };
-
-        match target {
-            SdkTarget::Android => {
-                println!("[1/3] Building Rust library...");
-                let mut builder =
-                    mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name)
-                        .verbose(false)
-                        .dry_run(dry_run);
-                if let Some(ref dir) = effective_output_dir {
-                    builder = builder.output_dir(dir);
-                }
-                if let Some(ref path) = crate_path {
-                    builder = builder.crate_dir(path);
-                }
-                println!("[2/3] Building Android APK...");
-                let result = builder.build(&build_config)?;
-                println!("[3/3] Done!");
-                if !dry_run {
-                    println!("\n\u{2713} APK: {:?}", result.app_path);
-                }
-            }
-            SdkTarget::Ios => {
-                println!("[1/3] Building Rust library...");
-                let mut builder =
-                    mobench_sdk::builders::IosBuilder::new(&project_root, crate_name)
-                        .verbose(false)
-                        .dry_run(dry_run);
-                if let Some(ref dir) = effective_output_dir {
-                    builder = builder.output_dir(dir);
-                }
-                if let Some(ref path) = crate_path {
-                    builder = builder.crate_dir(path);
-                }
-                println!("[2/3] Building iOS xcframework...");
-                let result = builder.build(&build_config)?;
-                println!("[3/3] Done!");
-                if !dry_run {
-                    println!("\n\u{2713} Framework: {:?}", result.app_path);
-                }
-            }
-            SdkTarget::Both => {
-                println!("[1/5] Building Rust library for Android...");
-                let mut android_builder =
-                    mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name.clone())
-                        .verbose(false)
-                        .dry_run(dry_run);
-                if let Some(ref dir) = effective_output_dir {
-                    android_builder = android_builder.output_dir(dir);
-                }
-                if let Some(ref path) = crate_path {
-                    android_builder = android_builder.crate_dir(path);
-                }
-                println!("[2/5] Building Android APK...");
-                let android_result = android_builder.build(&build_config)?;
-
-                println!("[3/5] Building Rust library for iOS...");
-                let mut ios_builder =
-                    mobench_sdk::builders::IosBuilder::new(&project_root, crate_name)
-                        .verbose(false)
-                        .dry_run(dry_run);
-                if let Some(ref dir) = effective_output_dir {
-                    ios_builder = ios_builder.output_dir(dir);
-                }
-                if let Some(ref path) = crate_path {
-                    ios_builder = ios_builder.crate_dir(path);
-                }
-                println!("[4/5] Building iOS xcframework...");
-                let ios_result = ios_builder.build(&build_config)?;
+fn fail_on_budget_violations(violations: &[BudgetViolation]) -> Result<()> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+    for violation in violations {
+        eprintln!("BUDGET: {}", violation.describe());
+        tracing::warn!(
+            event = "budget_violation",
+            device = %violation.device,
+            function = %violation.function,
+            metric = %violation.metric,
+            budget_ns = violation.budget_ns,
+            actual_ns = violation.actual_ns,
+        );
+    }
+    bail!(MobenchError::ThresholdExceeded(format!(
+        "{} benchmark metric(s) exceeded their budget in mobench.toml",
+        violations.len()
+    )));
+}
 
-                println!("[5/5] Done!");
-                if !dry_run {
-                    println!("\n\u{2713} APK: {:?}", android_result.app_path);
-                    println!("\u{2713} Framework: {:?}", ios_result.app_path);
-                }
+/// Compare each function of a finished run against its stored baseline.
+///
+/// Baselines are keyed by target and function, so each function is compared
+/// only against the entry stored for it; functions without an entry yet are
+/// reported and skipped.
+fn compare_with_baseline_store(
+    store: &baseline_store::BaselineStore,
+    run_summary: &RunSummary,
+    summary_path: &Path,
+    thresholds: &RegressionThresholds,
+) -> Result<(Vec<RegressionFinding>, Vec<SuspiciousImprovement>)> {
+    let target = format!("{:?}", run_summary.spec.target).to_lowercase();
+    let mut functions = run_summary.spec.all_functions();
+    functions.dedup();
+
+    let mut findings = Vec::new();
+    let mut suspicious = Vec::new();
+    for function in &functions {
+        println!();
+        match store.find(&target, function) {
+            Some(baseline_path) => {
+                println!("Comparing {} against stored baseline {:?}", function, baseline_path);
+                let compare_path = if functions.len() > 1 {
+                    summary_path.with_extension(format!("{}.compare.md", baseline_store::sanitize_key(function)))
+                } else {
+                    summary_path.with_extension("compare.md")
+                };
+                let (function_findings, function_suspicious) = compare_against_baseline_at(
+                    &baseline_path,
+                    summary_path,
+                    Some(function),
+                    thresholds,
+                    &compare_path,
+                )?;
+                findings.extend(function_findings);
+                suspicious.extend(function_suspicious);
             }
+            None => println!(
+                "No stored baseline for {} / {} in {:?}",
+                target,
+                function,
+                store.path_for(&target, function).parent().unwrap_or(Path::new("."))
+            ),
         }
-        return Ok(());
     }
+    Ok((findings, suspicious))
+}
 
-    // Normal (verbose) mode
-    if let Some(config_path) = &config_resolver.config_path {
-        println!("Using config file: {:?}", config_path);
+/// Functions of `summary` that every device completed, i.e. that can become
+/// a stored baseline. Empty when any requested device has no results.
+fn promotable_functions<'a>(spec: &'a RunSpec, summary: &SummaryReport) -> Vec<&'a str> {
+    let devices: Vec<&DeviceSummary> = summary
+        .device_summaries
+        .iter()
+        .filter(|device| !device.is_group())
+        .collect();
+    let missing_device = summary
+        .device_status
+        .values()
+        .any(|status| *status == DeviceStatus::Missing);
+    if devices.is_empty() || missing_device {
+        return Vec::new();
     }
+    let mut functions = spec.all_functions();
+    functions.dedup();
+    functions.retain(|function| {
+        devices.iter().all(|device| {
+            device.benchmarks.iter().any(|bench| {
+                bench.samples > 0
+                    && (bench.function == *function || bench.function.ends_with(&format!("::{function}")))
+            })
+        })
+    });
+    functions
+}
 
-    println!("Building mobile artifacts...");
-    println!("  Target: {:?}", target);
-    println!("  Profile: {}", if release { "release" } else { "debug" });
-    if dry_run {
-        println!("  Mode: dry-run (no changes will be made)");
+/// Promote a run that passed every gate to the stored baseline of each of
+/// its functions.
+///
+/// Promotion happens only when `promote_on` matches the current git branch,
+/// no regression or suspicious improvement was found (or no baseline exists
+/// yet), and every device returned results for the function.
+fn promote_to_baseline_store(
+    store: &baseline_store::BaselineStore,
+    run_summary: &RunSummary,
+    summary_path: &Path,
+    repo: &Path,
+    promote_branch: &str,
+    findings: &[RegressionFinding],
+) -> Result<()> {
+    println!();
+    match baseline_store::current_git_branch(repo) {
+        Some(branch) if branch == promote_branch => {}
+        Some(branch) => {
+            println!(
+                "Not promoting baseline: on branch '{}', promotion branch is '{}'",
+                branch, promote_branch
+            );
+            return Ok(());
+        }
+        None => {
+            println!("Not promoting baseline: could not determine the current git branch");
+            return Ok(());
+        }
     }
-    if verbose {
-        println!("  Verbose: enabled");
+    if !findings.is_empty() {
+        println!("Not promoting baseline: {} regression(s) found", findings.len());
+        return Ok(());
     }
 
-    let project_root = std::env::current_dir().context("Failed to get current directory")?;
-
-    // Use crate name from config if not auto-detected
-    let crate_name = detect_bench_mobile_crate_name(&project_root)
-        .or_else(|_| {
-            config_resolver
-                .crate_name()
-                .map(|s| s.to_string())
-                .ok_or_else(|| anyhow!("Could not detect crate name"))
-        })
-        .unwrap_or_else(|_| "bench-mobile".to_string()); // Fallback for legacy layouts
-
-    // CLI flags override config file values
-    let effective_output_dir = output_dir.or_else(|| config_resolver.output_dir().map(|p| p.to_path_buf()));
-
-    if let Some(ref dir) = effective_output_dir {
-        println!("  Output: {:?}", dir);
+    let target = format!("{:?}", run_summary.spec.target).to_lowercase();
+    let promotable = promotable_functions(&run_summary.spec, &run_summary.summary);
+    if promotable.is_empty() {
+        println!("Not promoting baseline: the run has no complete results");
+        return Ok(());
     }
-    if let Some(ref path) = crate_path {
-        println!("  Crate: {:?}", path);
+    let mut functions = run_summary.spec.all_functions();
+    functions.dedup();
+    for function in functions {
+        if promotable.contains(&function) {
+            let dest = store.promote(&target, function, summary_path)?;
+            println!("Promoted {} to baseline at {:?}", function, dest);
+        } else {
+            println!("Not promoting baseline for {}: not every device returned results", function);
+        }
     }
+    Ok(())
+}
 
-    let build_config = mobench_sdk::BuildConfig {
-        target: target.into(),
-        profile: if release {
-            mobench_sdk::BuildProfile::Release
-        } else {
-            mobench_sdk::BuildProfile::Debug
-        },
-        incremental: true,
-    };
+/// The `--notify-webhook` verdict for a run whose gates returned `verdict`.
+fn run_notification(
+    spec: &RunSpec,
+    findings: &[RegressionFinding],
+    verdict: &Result<()>,
+) -> notify::Notification {
+    let mut regressions: Vec<notify::RegressionDelta> = findings
+        .iter()
+        .map(|finding| notify::RegressionDelta {
+            device: finding.device.clone(),
+            function: finding.function.clone(),
+            metric: finding.metric,
+            baseline_ns: finding.baseline_ns,
+            candidate_ns: finding.candidate_ns,
+            delta_pct: finding.delta_pct,
+        })
+        .collect();
+    regressions.sort_by(|a, b| b.delta_pct.total_cmp(&a.delta_pct));
+    notify::Notification {
+        target: format!("{:?}", spec.target).to_lowercase(),
+        functions: spec.all_functions().into_iter().map(str::to_string).collect(),
+        devices: spec.devices.clone(),
+        passed: verdict.is_ok(),
+        failures: verdict.as_ref().err().map(|err| format!("{err:#}")).into_iter().collect(),
+        regressions,
+    }
+}
 
-    match target {
-        SdkTarget::Android => {
-            println!("\nBuilding for Android...");
-            println!("  Building Rust library for Android targets...");
-            let mut builder =
-                mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name.clone())
-                    .verbose(verbose)
-                    .dry_run(dry_run);
-            if let Some(ref dir) = effective_output_dir {
-                builder = builder.output_dir(dir);
-            }
-            if let Some(ref path) = crate_path {
-                builder = builder.crate_dir(path);
-            }
-            let result = builder.build(&build_config)?;
-            if !dry_run {
-                println!("\u{2713} Built Android APK");
-                println!("\n[checkmark] Android build completed!");
-                println!("  APK: {:?}", result.app_path);
-            }
-        }
-        SdkTarget::Ios => {
-            println!("\nBuilding for iOS...");
-            println!("  Building Rust library for iOS targets...");
-            let mut builder =
-                mobench_sdk::builders::IosBuilder::new(&project_root, crate_name.clone())
-                    .verbose(verbose)
-                    .dry_run(dry_run);
-            if let Some(ref dir) = effective_output_dir {
-                builder = builder.output_dir(dir);
-            }
-            if let Some(ref path) = crate_path {
-                builder = builder.crate_dir(path);
-            }
-            let result = builder.build(&build_config)?;
-            if !dry_run {
-                println!("\u{2713} Built iOS xcframework");
-                println!("\n[checkmark] iOS build completed!");
-                println!("  Framework: {:?}", result.app_path);
-            }
+fn write_compare_report(
+    report: &CompareReport,
+    findings: &[RegressionFinding],
+    suspicious: &[SuspiciousImprovement],
+    markers: Option<&DeltaMarkers>,
+    platform_matrix: Option<&str>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let mut markdown = render_compare_markdown(report, markers);
+    if let Some(matrix) = platform_matrix {
+        let _ = writeln!(markdown);
+        let _ = write!(markdown, "{}", matrix);
+    }
+    if !findings.is_empty() {
+        let _ = writeln!(markdown);
+        let _ = writeln!(markdown, "## Regressions");
+        let _ = writeln!(markdown);
+        for finding in findings {
+            let _ = writeln!(markdown, "- {}", finding.describe());
         }
-        SdkTarget::Both => {
-            // Build Android
-            println!("\nBuilding for Android...");
-            println!("  Building Rust library for Android targets...");
-            let mut android_builder =
-                mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name.clone())
-                    .verbose(verbose)
-                    .dry_run(dry_run);
-            if let Some(ref dir) = effective_output_dir {
-                android_builder = android_builder.output_dir(dir);
-            }
-            if let Some(ref path) = crate_path {
-                android_builder = android_builder.crate_dir(path);
-            }
-            let android_result = android_builder.build(&build_config)?;
-            if !dry_run {
-                println!("\u{2713} Built Android APK");
-                println!("\n[checkmark] Android build completed!");
-                println!("  APK: {:?}", android_result.app_path);
-            }
-
-            // Build iOS
-            println!("\nBuilding for iOS...");
-            println!("  Building Rust library for iOS targets...");
-            let mut ios_builder =
-                mobench_sdk::builders::IosBuilder::new(&project_root, crate_name)
-                    .verbose(verbose)
-                    .dry_run(dry_run);
-            if let Some(ref dir) = effective_output_dir {
-                ios_builder = ios_builder.output_dir(dir);
-            }
-            if let Some(ref path) = crate_path {
-                ios_builder = ios_builder.crate_dir(path);
-            }
-            let ios_result = ios_builder.build(&build_config)?;
-            if !dry_run {
-                println!("\u{2713} Built iOS xcframework");
-                println!("\n[checkmark] iOS build completed!");
-                println!("  Framework: {:?}", ios_result.app_path);
-            }
+    }
+    if !suspicious.is_empty() {
+        let _ = writeln!(markdown);
+        let _ = writeln!(markdown, "## Suspicious Improvements");
+        let _ = writeln!(markdown);
+        for improvement in suspicious {
+            let _ = writeln!(markdown, "- {}", improvement.describe());
         }
     }
-
-    if dry_run {
-        println!("\n[dry-run] Build simulation completed. No changes were made.");
+    if let Some(path) = output {
+        ensure_parent_dir(path)?;
+        write_file(path, markdown.as_bytes())?;
+        println!("Wrote compare report to {:?}", path);
+    } else {
+        println!("{markdown}");
     }
-
     Ok(())
 }
 
-fn detect_bench_mobile_crate_name(root: &Path) -> Result<String> {
-    // Try bench-mobile/ first (SDK projects)
-    let bench_mobile_path = root.join("bench-mobile").join("Cargo.toml");
-    if bench_mobile_path.exists() {
-        let contents = fs::read_to_string(&bench_mobile_path)
-            .with_context(|| format!("reading bench-mobile manifest at {:?}", bench_mobile_path))?;
-        let value: toml::Value = toml::from_str(&contents)
-            .with_context(|| format!("parsing bench-mobile manifest {:?}", bench_mobile_path))?;
-        let name = value
-            .get("package")
-            .and_then(|pkg| pkg.get("name"))
-            .and_then(|n| n.as_str())
-            .ok_or_else(|| {
-                anyhow!(
-                    "bench-mobile package.name missing in {:?}",
-                    bench_mobile_path
-                )
-            })?;
-        return Ok(name.to_string());
-    }
+/// `compare --platform-version-matrix`: the candidate's medians for
+/// `function` per device model and OS version, with the median Δ% vs the
+/// baseline in each cell, or a note when no model ran on several OS versions.
+fn render_compare_platform_matrix(report: &CompareReport, function: &str) -> String {
+    let rows = report.rows.iter().filter(|row| row.function == function);
+    let medians = rows
+        .clone()
+        .filter_map(|row| Some((row.device.clone(), row.candidate_median_ns?)))
+        .collect();
+    let deltas = rows
+        .filter_map(|row| Some((row.device.clone(), row.median_delta_pct?)))
+        .collect();
+    render_platform_version_matrix(function, &medians, Some(&deltas))
+        .unwrap_or_else(|| no_platform_matrix_note(function))
+}
 
-    // Fallback: Try crates/sample-fns (repository testing)
-    let sample_fns_path = root.join("crates").join("sample-fns").join("Cargo.toml");
-    if sample_fns_path.exists() {
-        let contents = fs::read_to_string(&sample_fns_path)
-            .with_context(|| format!("reading sample-fns manifest at {:?}", sample_fns_path))?;
-        let value: toml::Value = toml::from_str(&contents)
-            .with_context(|| format!("parsing sample-fns manifest {:?}", sample_fns_path))?;
-        let name = value
-            .get("package")
-            .and_then(|pkg| pkg.get("name"))
-            .and_then(|n| n.as_str())
-            .ok_or_else(|| anyhow!("sample-fns package.name missing in {:?}", sample_fns_path))?;
-        return Ok(name.to_string());
-    }
+/// Shown instead of a matrix when no model ran `function` on several OS versions.
+fn no_platform_matrix_note(function: &str) -> String {
+    format!("No device model ran {function} on more than one OS version; there is no OS version matrix to show.\n")
+}
 
-    bail!(
-        "No benchmark crate found. Expected bench-mobile/Cargo.toml or crates/sample-fns/Cargo.toml under the project root. Run from the project root or set [project].crate in mobench.toml."
-    )
+fn render_compare_markdown(report: &CompareReport, markers: Option<&DeltaMarkers>) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "# Benchmark Comparison");
+    let _ = writeln!(output);
+    let commit = |sha: &Option<String>| {
+        sha.as_deref()
+            .map_or_else(String::new, |sha| format!(" (commit `{}`)", short_sha(sha)))
+    };
+    let _ = writeln!(
+        output,
+        "- Baseline: {}{}",
+        report.baseline.display(),
+        commit(&report.baseline_commit)
+    );
+    let _ = writeln!(
+        output,
+        "- Candidate: {}{}",
+        report.candidate.display(),
+        commit(&report.candidate_commit)
+    );
+    let _ = writeln!(output);
+    for warning in &report.warnings {
+        let _ = writeln!(output, "> **Warning:** {}", warning);
+    }
+    if !report.warnings.is_empty() {
+        let _ = writeln!(output);
+    }
+    let unit = units::current().label();
+    let _ = writeln!(
+        output,
+        "| Device | Function | Median (base {unit}) | Median (cand {unit}) | Median Δ% | P95 (base {unit}) | P95 (cand {unit}) | P95 Δ% |"
+    );
+    let _ = writeln!(
+        output,
+        "| --- | --- | ---: | ---: | ---: | ---: | ---: | ---: |"
+    );
+    for row in &report.rows {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} | {} | {} |",
+            row.device,
+            row.function,
+            format_ms(row.baseline_median_ns),
+            format_ms(row.candidate_median_ns),
+            format_marked_delta(row.median_delta_pct, markers),
+            format_ms(row.baseline_p95_ns),
+            format_ms(row.candidate_p95_ns),
+            format_marked_delta(row.p95_delta_pct, markers)
+        );
+    }
+    output
 }
 
-/// List all discovered benchmark functions
+/// Render the candidate summary as JUnit XML for CI test dashboards.
 ///
-/// This uses source code scanning to find `#[benchmark]` functions, which works
-/// without requiring a full build. It also falls back to the inventory registry
-/// for any benchmarks that may be registered at runtime.
-fn cmd_list() -> Result<()> {
-    println!("Discovering benchmark functions...\n");
-
-    let project_root = repo_root()?;
-    let mut all_benchmarks = Vec::new();
+/// Each device/function pair becomes a `<testcase>` timed by its median.
+/// Regression findings, suspicious improvements, and budget violations for
+/// that pair are reported as `<failure>` elements.
+fn render_junit_report(
+    summary: &SummaryReport,
+    findings: &[RegressionFinding],
+    suspicious: &[SuspiciousImprovement],
+) -> String {
+    let ns_to_secs = |ns: Option<u64>| ns.unwrap_or(0) as f64 / 1_000_000_000.0;
+    let cases: Vec<(&str, &BenchmarkStats)> = summary
+        .device_summaries
+        .iter()
+        .flat_map(|device| {
+            device
+                .benchmarks
+                .iter()
+                .map(move |bench| (device.device.as_str(), bench))
+        })
+        .collect();
+    let total_time: f64 = cases.iter().map(|(_, b)| ns_to_secs(b.median_ns)).sum();
+    let failed_cases = cases
+        .iter()
+        .filter(|(device, bench)| {
+            findings
+                .iter()
+                .any(|f| f.device == *device && f.function == bench.function)
+                || suspicious
+                    .iter()
+                    .any(|s| s.device == *device && s.function == bench.function)
+                || summary
+                    .budget_violations
+                    .iter()
+                    .any(|v| v.device == *device && v.function == bench.function)
+        })
+        .count();
 
-    // Method 1: Source code scanning (works without build)
-    let search_dirs = [
-        ("bench-mobile", project_root.join("bench-mobile")),
-        ("sample-fns", project_root.join("crates/sample-fns")),
-        ("ffi-benchmark", project_root.join("crates/ffi-benchmark")),
-        ("", project_root.clone()),
-    ];
+    let mut output = String::new();
+    let _ = writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        output,
+        r#"<testsuites name="mobench" tests="{}" failures="{}" time="{:.6}">"#,
+        cases.len(),
+        failed_cases,
+        total_time
+    );
+    let _ = writeln!(
+        output,
+        r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" time="{:.6}" timestamp="{}">"#,
+        xml_escape(&summary.function),
+        cases.len(),
+        failed_cases,
+        total_time,
+        xml_escape(&summary.generated_at)
+    );
+    let _ = writeln!(output, "    <properties>");
+    for (name, value) in [
+        ("target", summary.target_label().to_lowercase()),
+        ("iterations", summary.iterations.to_string()),
+        ("warmup", summary.warmup.to_string()),
+    ] {
+        let _ = writeln!(
+            output,
+            r#"      <property name="{}" value="{}"/>"#,
+            name,
+            xml_escape(&value)
+        );
+    }
+    let _ = writeln!(output, "    </properties>");
 
-    for (default_crate_name, dir) in &search_dirs {
-        if !dir.join("Cargo.toml").exists() {
-            continue;
+    for (device, bench) in &cases {
+        let _ = writeln!(
+            output,
+            r#"    <testcase classname="{}" name="{}" time="{:.6}">"#,
+            xml_escape(device),
+            xml_escape(&bench.function),
+            ns_to_secs(bench.median_ns)
+        );
+        for finding in findings
+            .iter()
+            .filter(|f| f.device == *device && f.function == bench.function)
+        {
+            let _ = writeln!(
+                output,
+                r#"      <failure type="regression" message="{}"/>"#,
+                xml_escape(&finding.describe())
+            );
         }
-        let crate_name = if default_crate_name.is_empty() {
-            if let Ok(name) = get_crate_name_from_cargo_toml(&dir.join("Cargo.toml")) {
-                name
-            } else {
-                continue;
-            }
-        } else {
-            default_crate_name.to_string()
-        };
-        let benchmarks = mobench_sdk::codegen::detect_all_benchmarks(dir, &crate_name);
-        for bench in benchmarks {
-            if !all_benchmarks.contains(&bench) {
-                all_benchmarks.push(bench);
-            }
+        for improvement in suspicious
+            .iter()
+            .filter(|s| s.device == *device && s.function == bench.function)
+        {
+            let _ = writeln!(
+                output,
+                r#"      <failure type="suspicious_improvement" message="{}"/>"#,
+                xml_escape(&improvement.describe())
+            );
         }
-    }
-
-    // Method 2: Inventory registry (for runtime-registered benchmarks)
-    let registry_benchmarks = mobench_sdk::discover_benchmarks();
-    for bench in registry_benchmarks {
-        let name = bench.name.to_string();
-        if !all_benchmarks.contains(&name) {
-            all_benchmarks.push(name);
+        for violation in summary
+            .budget_violations
+            .iter()
+            .filter(|v| v.device == *device && v.function == bench.function)
+        {
+            let _ = writeln!(
+                output,
+                r#"      <failure type="budget" message="{}"/>"#,
+                xml_escape(&violation.describe())
+            );
         }
+        let _ = writeln!(
+            output,
+            "      <system-out>samples={} mean={} median={} p95={} p99={}</system-out>",
+            bench.samples,
+            format_with_unit(bench.mean_ns),
+            format_with_unit(bench.median_ns),
+            format_with_unit(bench.p95_ns),
+            format_with_unit(bench.p99_ns)
+        );
+        let _ = writeln!(output, "    </testcase>");
     }
 
-    all_benchmarks.sort();
+    let _ = writeln!(output, "  </testsuite>");
+    let _ = writeln!(output, "</testsuites>");
+    output
+}
 
-    if all_benchmarks.is_empty() {
-        println!("No benchmarks found.\n");
-        println!("Searched locations:");
-        for (name, dir) in &search_dirs {
-            if !name.is_empty() {
-                println!("  - {}: {}", name, dir.display());
-            }
-        }
-        println!("\nTo add benchmarks:");
-        println!("  1. Add #[benchmark] attribute to functions");
-        println!("  2. Make sure mobench-sdk is in your dependencies");
-        println!("  3. Run 'cargo mobench list' again");
-    } else {
-        println!("Found {} benchmark(s):", all_benchmarks.len());
-        for bench in &all_benchmarks {
-            println!("  {}", bench);
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
         }
-        println!();
-        println!("Usage:");
-        println!("  cargo mobench run --target android --function {} --iterations 100", all_benchmarks.first().unwrap());
     }
+    escaped
+}
 
-    Ok(())
+fn format_delta(value: Option<f64>) -> String {
+    value
+        .map(|delta| format!("{:+.2}%", delta))
+        .unwrap_or_else(|| "-".to_string())
 }
 
-fn get_crate_name_from_cargo_toml(cargo_toml: &Path) -> Result<String> {
-    let contents = fs::read_to_string(cargo_toml)?;
-    let value: toml::Value = toml::from_str(&contents)?;
-    let name = value
-        .get("package")
-        .and_then(|pkg| pkg.get("name"))
-        .and_then(|n| n.as_str())
-        .ok_or_else(|| anyhow!("package.name not found in {:?}", cargo_toml))?;
-    Ok(name.to_string())
+/// [`format_delta`], prefixed with its [`DeltaMarkers`] symbol when enabled.
+fn format_marked_delta(value: Option<f64>, markers: Option<&DeltaMarkers>) -> String {
+    match (value, markers) {
+        (Some(delta), Some(markers)) => format!("{} {}", markers.symbol(delta), format_delta(value)),
+        _ => format_delta(value),
+    }
 }
 
-/// Package iOS app as IPA for distribution or testing
-fn cmd_package_ipa(scheme: &str, method: IosSigningMethodArg, output_dir: Option<PathBuf>) -> Result<()> {
-    println!("Packaging iOS app as IPA...");
-    println!("  Scheme: {}", scheme);
-    println!("  Method: {:?}", method);
-    if let Some(ref dir) = output_dir {
-        println!("  Output: {:?}", dir);
+fn summarize_local_report(run_summary: &RunSummary) -> Option<DeviceSummary> {
+    let samples = extract_samples(&run_summary.local_report);
+    if samples.is_empty() {
+        return None;
     }
+    let stats = SampleStats::from_samples(&samples)?;
+    let function = run_summary
+        .local_report
+        .get("spec")
+        .and_then(|spec| spec.get("name"))
+        .and_then(|name| name.as_str())
+        .unwrap_or(&run_summary.spec.function)
+        .to_string();
 
-    let project_root = repo_root()?;
-    let crate_name = detect_bench_mobile_crate_name(&project_root)
-        .unwrap_or_else(|_| "bench-mobile".to_string());
+    Some(DeviceSummary {
+        device: "local".to_string(),
+        device_meta: Default::default(),
+        members: Vec::new(),
+        benchmarks: vec![BenchmarkStats {
+            function,
+            samples: samples.len(),
+            mean_ns: Some(stats.mean_ns),
+            median_ns: Some(stats.median_ns),
+            p95_ns: Some(stats.p95_ns),
+            p99_ns: Some(stats.p99_ns),
+            min_ns: Some(stats.min_ns),
+            max_ns: Some(stats.max_ns),
+            cv_pct: Some(stats.cv_pct),
+            mom_ns: Some(stats.mom_ns),
+            worst_device: None,
+            warmup_ns: extract_warmup_samples(&run_summary.local_report),
+            median_ci_pct: median_ci_pct(
+                &run_summary.local_report,
+                &samples,
+                run_summary.spec.sampling.as_ref(),
+            ),
+            cpu_median_ns: cpu_median_ns(&run_summary.local_report),
+            metrics: summarize_metrics(extract_metrics(&run_summary.local_report)),
+            timed_out: report_timed_out(&run_summary.local_report),
+            affinity: None,
+            repeat_cv_pct: None,
+        }],
+        target: None,
+    })
+}
 
-    let mut builder = mobench_sdk::builders::IosBuilder::new(&project_root, crate_name).verbose(true);
-    if let Some(ref dir) = output_dir {
-        builder = builder.output_dir(dir);
+/// Roll up a device group into a synthetic `group:<label>` device.
+///
+/// Each function's stats are computed over the pooled samples of every member
+/// device, and `worst_device` names the member with the slowest median.
+fn summarize_device_group(
+    group: &DeviceGroup,
+    results: &BTreeMap<String, Vec<Value>>,
+) -> Option<DeviceSummary> {
+    #[derive(Default)]
+    struct Pooled {
+        samples: Vec<u64>,
+        metrics: BTreeMap<String, Vec<f64>>,
+        timed_out: bool,
+        /// Slowest member device and its median.
+        slowest: Option<(String, u64)>,
+    }
+
+    let mut members = Vec::new();
+    let mut functions: BTreeMap<String, Pooled> = BTreeMap::new();
+    for (device, entries) in results {
+        if !group
+            .devices
+            .iter()
+            .any(|member| device_names_match(member, device))
+        {
+            continue;
+        }
+        members.push(device.clone());
+        for entry in entries {
+            let function = entry
+                .get("function")
+                .and_then(|f| f.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            let samples = extract_samples(entry);
+            let median = SampleStats::from_samples(&samples).map(|s| s.median_ns);
+            let pooled = functions.entry(function).or_default();
+            pooled.samples.extend(samples);
+            for (name, values) in extract_metrics(entry) {
+                pooled.metrics.entry(name).or_default().extend(values);
+            }
+            pooled.timed_out |= report_timed_out(entry);
+            if let Some(median) = median
+                && pooled.slowest.as_ref().is_none_or(|(_, slowest)| median > *slowest)
+            {
+                pooled.slowest = Some((device.clone(), median));
+            }
+        }
+    }
+    if members.is_empty() {
+        return None;
     }
 
-    let signing_method: mobench_sdk::builders::SigningMethod = method.into();
-    let ipa_path = builder
-        .package_ipa(scheme, signing_method)
-        .context("Failed to package IPA")?;
+    let benchmarks = functions
+        .into_iter()
+        .map(|(function, pooled)| {
+            let stats = SampleStats::from_samples(&pooled.samples);
+            BenchmarkStats {
+                function,
+                samples: pooled.samples.len(),
+                mean_ns: stats.as_ref().map(|s| s.mean_ns),
+                median_ns: stats.as_ref().map(|s| s.median_ns),
+                p95_ns: stats.as_ref().map(|s| s.p95_ns),
+                p99_ns: stats.as_ref().map(|s| s.p99_ns),
+                min_ns: stats.as_ref().map(|s| s.min_ns),
+                max_ns: stats.as_ref().map(|s| s.max_ns),
+                cv_pct: stats.as_ref().map(|s| s.cv_pct),
+                mom_ns: stats.as_ref().map(|s| s.mom_ns),
+                worst_device: pooled.slowest.map(|(device, _)| device),
+                warmup_ns: Vec::new(),
+                median_ci_pct: None,
+                cpu_median_ns: None,
+                metrics: summarize_metrics(pooled.metrics),
+                timed_out: pooled.timed_out,
+                affinity: None,
+                repeat_cv_pct: None,
+            }
+        })
+        .collect();
+    Some(DeviceSummary {
+        device: format!("{}{}", GROUP_PREFIX, group.label),
+        device_meta: Default::default(),
+        members,
+        benchmarks,
+        target: None,
+    })
+}
 
-    println!("\n[checkmark] IPA packaged successfully!");
-    println!("  Path: {:?}", ipa_path);
-    println!("\nYou can now:");
-    println!("  - Install on device: Use Xcode or ios-deploy");
-    println!(
-        "  - Test on BrowserStack: cargo mobench run --target ios --ios-app {:?}",
-        ipa_path
-    );
+/// The statistics of [`SampleStats::from_samples`] for a custom metric's values.
+fn compute_metric_stats(values: &[f64]) -> Option<MetricStats> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    Some(MetricStats {
+        samples: sorted.len(),
+        mean: stats::mean(&sorted)?,
+        median: stats::median_f64(&sorted)?,
+        p95: stats::percentile(&sorted, 0.95)?,
+        p99: stats::percentile(&sorted, 0.99)?,
+        min: *sorted.first()?,
+        max: *sorted.last()?,
+        cv_pct: stats::cv_pct(&sorted),
+    })
+}
 
-    Ok(())
+fn summarize_metrics(metrics: BTreeMap<String, Vec<f64>>) -> BTreeMap<String, MetricStats> {
+    metrics
+        .into_iter()
+        .filter_map(|(name, values)| Some((name, compute_metric_stats(&values)?)))
+        .collect()
 }
 
-/// Package XCUITest runner for BrowserStack testing
-fn cmd_package_xcuitest(scheme: &str, output_dir: Option<PathBuf>) -> Result<()> {
-    println!("Packaging XCUITest runner...");
-    println!("  Scheme: {}", scheme);
-    if let Some(ref dir) = output_dir {
-        println!("  Output: {:?}", dir);
-    }
+/// Whether a device report says the benchmark hit its per-benchmark timeout.
+fn report_timed_out(entry: &Value) -> bool {
+    entry.get("timed_out").and_then(Value::as_bool).unwrap_or(false)
+}
 
-    let project_root = repo_root()?;
-    let crate_name = detect_bench_mobile_crate_name(&project_root)
-        .unwrap_or_else(|_| "bench-mobile".to_string());
+fn report_affinity(entry: &Value) -> Option<String> {
+    entry.get("affinity").and_then(Value::as_str).map(str::to_string)
+}
 
-    let mut builder = mobench_sdk::builders::IosBuilder::new(&project_root, crate_name).verbose(true);
-    if let Some(ref dir) = output_dir {
-        builder = builder.output_dir(dir);
-    }
+/// Custom metric values from a report's `metrics` object, skipping
+/// anything that is not an array of numbers.
+fn extract_metrics(entry: &Value) -> BTreeMap<String, Vec<f64>> {
+    let Some(metrics) = entry.get("metrics").and_then(Value::as_object) else {
+        return BTreeMap::new();
+    };
+    metrics
+        .iter()
+        .filter_map(|(name, values)| {
+            let values: Vec<f64> = values.as_array()?.iter().filter_map(Value::as_f64).collect();
+            Some((name.clone(), values))
+        })
+        .collect()
+}
 
-    let zip_path = builder
-        .package_xcuitest(scheme)
-        .context("Failed to package XCUITest runner")?;
+fn extract_samples(value: &Value) -> Vec<u64> {
+    extract_durations(value, "samples")
+}
 
-    println!("\n[checkmark] XCUITest runner packaged successfully!");
-    println!("  Path: {:?}", zip_path);
-    println!("\nYou can now:");
-    println!(
-        "  - Test on BrowserStack: cargo mobench run --target ios --ios-test-suite {:?}",
-        zip_path
-    );
+/// Median of the per-iteration thread CPU times in a report, from
+/// `cpu_samples_ns` (Android) or `cpu_ns` on every sample object.
+fn cpu_median_ns(entry: &Value) -> Option<u64> {
+    let cpu: Vec<u64> = match entry.get("cpu_samples_ns").and_then(Value::as_array) {
+        Some(values) => values.iter().filter_map(Value::as_u64).collect(),
+        None => entry
+            .get("samples")
+            .and_then(Value::as_array)?
+            .iter()
+            .map(|sample| sample.get("cpu_ns").and_then(Value::as_u64))
+            .collect::<Option<_>>()?,
+    };
+    SampleStats::from_samples(&cpu).map(|stats| stats.median_ns)
+}
 
-    Ok(())
+/// Warmup durations captured with `BenchSpec::capture_warmup`.
+fn extract_warmup_samples(value: &Value) -> Vec<u64> {
+    extract_durations(value, "warmup_samples")
+}
+
+fn extract_durations(value: &Value, key: &str) -> Vec<u64> {
+    let Some(samples) = value.get(key).and_then(|s| s.as_array()) else {
+        return Vec::new();
+    };
+    let mut durations = Vec::with_capacity(samples.len());
+    for sample in samples {
+        if let Some(duration) = sample
+            .get("duration_ns")
+            .and_then(|duration| duration.as_u64())
+        {
+            durations.push(duration);
+        } else if let Some(duration) = sample.as_u64() {
+            durations.push(duration);
+        }
+    }
+    durations
+}
+
+fn render_markdown_summary(summary: &SummaryReport) -> String {
+    let mut output = String::new();
+    let devices = if summary.devices.is_empty() {
+        "none".to_string()
+    } else {
+        summary.devices.join(", ")
+    };
+
+    let _ = writeln!(output, "# Benchmark Summary");
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Generated: {}", summary.generated_at);
+    let _ = writeln!(output, "- Target: {}", summary.target_label());
+    let _ = writeln!(output, "- Function: {}", summary.function);
+    let _ = writeln!(
+        output,
+        "- Iterations/Warmup: {} / {}",
+        summary.iterations, summary.warmup
+    );
+    let _ = writeln!(output, "- Devices: {}", devices);
+    if summary.repeat_count > 1 {
+        let _ = writeln!(
+            output,
+            "- Repeats: {} (samples pooled per device and function)",
+            summary.repeat_count
+        );
+    }
+    let threshold = summary.noisy_cv_threshold_pct;
+    let noisy = summary
+        .device_summaries
+        .iter()
+        .flat_map(|device| &device.benchmarks)
+        .filter(|bench| bench.is_noisy(threshold))
+        .count();
+    if noisy > 0 {
+        let _ = writeln!(
+            output,
+            "- \u{26a0}\u{fe0f} {} noisy benchmark{} (CV > {}%)",
+            noisy,
+            if noisy == 1 { "" } else { "s" },
+            threshold
+        );
+    }
+    let incomplete = summary
+        .device_summaries
+        .iter()
+        .flat_map(|device| &device.benchmarks)
+        .filter(|bench| bench.timed_out)
+        .count();
+    if incomplete > 0 {
+        let _ = writeln!(
+            output,
+            "- \u{23f1}\u{fe0f} {} incomplete benchmark{} (per-benchmark timeout)",
+            incomplete,
+            if incomplete == 1 { "" } else { "s" }
+        );
+    }
+    let violations = &summary.budget_violations;
+    if !violations.is_empty() {
+        let _ = writeln!(
+            output,
+            "- \u{274c} {} budget violation{}",
+            violations.len(),
+            if violations.len() == 1 { "" } else { "s" }
+        );
+    }
+    let _ = writeln!(output);
+
+    if !violations.is_empty() {
+        let _ = writeln!(output, "## Budget Violations");
+        let _ = writeln!(output);
+        for violation in violations {
+            let _ = writeln!(output, "- {}", violation.describe());
+        }
+        let _ = writeln!(output);
+    }
+
+    if !summary.skipped_devices.is_empty() {
+        let _ = writeln!(output, "## Skipped Devices");
+        let _ = writeln!(output);
+        for (device, reason) in &summary.skipped_devices {
+            let _ = writeln!(output, "- {}: {}", device, reason);
+        }
+        let _ = writeln!(output);
+    }
+
+    if !summary.device_failures.is_empty() {
+        let _ = writeln!(output, "## Device Outcomes");
+        let _ = writeln!(output);
+        for line in device_outcome_lines(summary) {
+            let _ = writeln!(output, "- {}", line);
+        }
+        let _ = writeln!(output);
+    }
+
+    if !summary.device_retries.is_empty() {
+        let _ = writeln!(output, "## Device Retries");
+        let _ = writeln!(output);
+        for (device, retries) in &summary.device_retries {
+            let reported = summary
+                .device_summaries
+                .iter()
+                .any(|d| !d.is_group() && device_names_match(&d.device, device));
+            let _ = writeln!(
+                output,
+                "- {}: {} retr{}{}",
+                device,
+                retries,
+                if *retries == 1 { "y" } else { "ies" },
+                if reported { "" } else { ", no report" }
+            );
+        }
+        let _ = writeln!(output);
+    }
+
+    if summary.device_summaries.is_empty() {
+        let _ = writeln!(output, "No benchmark samples were collected.");
+        return output;
+    }
+
+    for device in &summary.device_summaries {
+        if device.is_group() {
+            let _ = writeln!(
+                output,
+                "## Group: {} (rollup of {})",
+                &device.device[GROUP_PREFIX.len()..],
+                device.members.join(", ")
+            );
+        } else {
+            let _ = writeln!(output, "## Device: {}", device.device);
+        }
+        let _ = writeln!(output);
+        let mut meta_lines: Vec<String> = device
+            .device_meta
+            .iter()
+            .map(|(key, value)| match value {
+                Value::String(text) => format!("{key}: {text}"),
+                other => format!("{key}: {other}"),
+            })
+            .collect();
+        // One affinity per device in practice: every benchmark shares the pinned thread
+        let affinities: BTreeSet<&str> = device
+            .benchmarks
+            .iter()
+            .filter_map(|bench| bench.affinity.as_deref())
+            .collect();
+        if !affinities.is_empty() {
+            meta_lines.push(format!(
+                "core pinning: {}",
+                affinities.into_iter().collect::<Vec<_>>().join("; ")
+            ));
+        }
+        if !meta_lines.is_empty() {
+            for line in &meta_lines {
+                let _ = writeln!(output, "- {}", line);
+            }
+            let _ = writeln!(output);
+        }
+        let (worst_header, worst_align) = if device.is_group() {
+            (" Worst Device |", " --- |")
+        } else {
+            ("", "")
+        };
+        let has_cpu = device.benchmarks.iter().any(|bench| bench.cpu_median_ns.is_some());
+        let unit = units::current().label();
+        let (cpu_header, cpu_align) = if has_cpu {
+            (format!(" CPU Median ({unit}) |"), " ---: |")
+        } else {
+            (String::new(), "")
+        };
+        let has_repeats = device.benchmarks.iter().any(|bench| bench.repeat_cv_pct.is_some());
+        let (repeat_header, repeat_align) = if has_repeats {
+            (" Repeat CV (%) |", " ---: |")
+        } else {
+            ("", "")
+        };
+        let _ = writeln!(
+            output,
+            "| Function | Samples | Mean ({unit}) | Median ({unit}) | P95 ({unit}) | P99 ({unit}) | Min ({unit}) | Max ({unit}) | CV (%) |{}{}{}",
+            repeat_header, cpu_header, worst_header
+        );
+        let _ = writeln!(
+            output,
+            "| --- | ---: | ---: | ---: | ---: | ---: | ---: | ---: | ---: |{}{}{}",
+            repeat_align, cpu_align, worst_align
+        );
+        for bench in &device.benchmarks {
+            let mut marker = if bench.is_noisy(threshold) {
+                " \u{26a0}\u{fe0f}[^noisy]".to_string()
+            } else {
+                String::new()
+            };
+            if bench.is_contended() {
+                marker.push_str(" \u{23f3}[^contention]");
+            }
+            if bench.timed_out {
+                marker.push_str(" \u{23f1}\u{fe0f}[^incomplete]");
+            }
+            let repeat_cv = if has_repeats {
+                let cv = bench.repeat_cv_pct.map_or_else(|| "-".to_string(), |cv| format!("{:.1}", cv));
+                format!(" {} |", cv)
+            } else {
+                String::new()
+            };
+            let cpu = if has_cpu {
+                format!(" {} |", format_ms(bench.cpu_median_ns))
+            } else {
+                String::new()
+            };
+            let worst = if device.is_group() {
+                format!(" {} |", bench.worst_device.as_deref().unwrap_or("-"))
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                output,
+                "| {}{} | {} | {} | {} | {} | {} | {} | {} | {} |{}{}{}",
+                bench.function,
+                marker,
+                bench.samples,
+                format_ms(bench.mean_ns),
+                format_median_with_ci(bench),
+                format_ms(bench.p95_ns),
+                format_ms(bench.p99_ns),
+                format_ms(bench.min_ns),
+                format_ms(bench.max_ns),
+                bench
+                    .cv_pct
+                    .map_or_else(|| "-".to_string(), |cv| format!("{:.1}", cv)),
+                repeat_cv,
+                cpu,
+                worst
+            );
+        }
+        let _ = writeln!(output);
+        if device.benchmarks.iter().any(|bench| !bench.metrics.is_empty()) {
+            let _ = writeln!(output, "Custom metrics:");
+            let _ = writeln!(output);
+            let _ = writeln!(
+                output,
+                "| Function | Metric | Samples | Mean | Median | P95 | Min | Max | CV (%) |"
+            );
+            let _ = writeln!(output, "| --- | --- | ---: | ---: | ---: | ---: | ---: | ---: | ---: |");
+            for bench in &device.benchmarks {
+                for (name, metric) in &bench.metrics {
+                    let _ = writeln!(
+                        output,
+                        "| {} | {} | {} | {} | {} | {} | {} | {} | {:.1} |",
+                        bench.function,
+                        name,
+                        metric.samples,
+                        format_metric(metric.mean),
+                        format_metric(metric.median),
+                        format_metric(metric.p95),
+                        format_metric(metric.min),
+                        format_metric(metric.max),
+                        metric.cv_pct
+                    );
+                }
+            }
+            let _ = writeln!(output);
+        }
+        let ramps: Vec<String> = device
+            .benchmarks
+            .iter()
+            .filter_map(render_warmup_ramp)
+            .collect();
+        if !ramps.is_empty() {
+            let _ = writeln!(output, "Warmup ramp (ms):");
+            let _ = writeln!(output);
+            for ramp in ramps {
+                let _ = writeln!(output, "- {}", ramp);
+            }
+            let _ = writeln!(output);
+        }
+    }
+
+    let mut functions: Vec<&str> = Vec::new();
+    for bench in summary.device_summaries.iter().flat_map(|device| &device.benchmarks) {
+        if !functions.contains(&bench.function.as_str()) {
+            functions.push(&bench.function);
+        }
+    }
+    for function in functions {
+        let medians = summary
+            .device_summaries
+            .iter()
+            .filter(|device| !device.is_group())
+            .filter_map(|device| {
+                let bench = device.benchmarks.iter().find(|bench| bench.function == function)?;
+                Some((device.device.clone(), bench.median_ns?))
+            })
+            .collect();
+        if let Some(matrix) = render_platform_version_matrix(function, &medians, None) {
+            let _ = writeln!(output, "{}", matrix);
+        }
+    }
+
+    if noisy > 0 {
+        let _ = writeln!(
+            output,
+            "[^noisy]: Coefficient of variation above {}%. Results this noisy are not reliable; increase iterations or check for thermal throttling before comparing.",
+            threshold
+        );
+    }
+    if summary
+        .device_summaries
+        .iter()
+        .flat_map(|device| &device.benchmarks)
+        .any(BenchmarkStats::is_contended)
+    {
+        let _ = writeln!(
+            output,
+            "[^contention]: Median wall time is more than {}x the median CPU time: the thread was preempted or blocked for much of each iteration, so a slowdown here may be contention on the device rather than a regression.",
+            CONTENTION_WALL_TO_CPU_RATIO
+        );
+    }
+    if incomplete > 0 {
+        let _ = writeln!(
+            output,
+            "[^incomplete]: The benchmark ran out of its per-benchmark timeout on the device; the statistics cover only the iterations that finished."
+        );
+    }
+
+    output
+}
+
+/// Split a device name such as `Google Pixel 8-14.0` into its model and OS
+/// version; `None` when the name has no numeric version suffix.
+fn split_os_version(device: &str) -> Option<(&str, &str)> {
+    let (model, version) = device.rsplit_once('-')?;
+    let numeric = version.starts_with(|c: char| c.is_ascii_digit())
+        && version.chars().all(|c| c.is_ascii_digit() || c == '.');
+    (numeric && !model.is_empty()).then_some((model, version))
+}
+
+/// One function's median pivoted into a markdown table: a row per device
+/// model, a column per OS version, from device names such as
+/// `Google Pixel 8-14.0`.
+///
+/// Only models that ran on more than one OS version are shown, and `None` is
+/// returned when there are none. Each cell carries its Δ% from `deltas`
+/// (keyed by device, e.g. against a baseline run) or, without them, from the
+/// model's oldest OS version, so a slower OS release stands out in its column.
+fn render_platform_version_matrix(
+    function: &str,
+    medians: &BTreeMap<String, u64>,
+    deltas: Option<&BTreeMap<String, f64>>,
+) -> Option<String> {
+    let mut models: BTreeMap<&str, BTreeMap<&str, (u64, Option<f64>)>> = BTreeMap::new();
+    for (device, &median) in medians {
+        if let Some((model, version)) = split_os_version(device) {
+            let delta = deltas.and_then(|deltas| deltas.get(device).copied());
+            models.entry(model).or_default().insert(version, (median, delta));
+        }
+    }
+    models.retain(|_, versions| versions.len() > 1);
+    if models.is_empty() {
+        return None;
+    }
+    let mut versions: Vec<&str> = models.values().flat_map(|versions| versions.keys().copied()).collect();
+    versions.sort_by(|a, b| os_version_key(a).cmp(&os_version_key(b)).then(a.cmp(b)));
+    versions.dedup();
+
+    let unit = units::current().label();
+    let mut output = String::new();
+    let _ = writeln!(output, "## OS Version Matrix: {}", function);
+    let _ = writeln!(output);
+    let _ = writeln!(
+        output,
+        "Median ({unit}) per device model and OS version, with the change from {}.",
+        if deltas.is_some() { "the baseline" } else { "the model's oldest OS version" }
+    );
+    let _ = writeln!(output);
+    let _ = writeln!(output, "| Device | {} |", versions.join(" | "));
+    let _ = writeln!(output, "| --- |{}", " ---: |".repeat(versions.len()));
+    for (model, cells) in &models {
+        let oldest = cells
+            .iter()
+            .min_by(|a, b| os_version_key(a.0).cmp(&os_version_key(b.0)))
+            .map(|(&version, &(median, _))| (version, median));
+        let row: Vec<String> = versions
+            .iter()
+            .map(|version| match cells.get(version) {
+                Some(&(median, delta)) => {
+                    let delta = if deltas.is_some() {
+                        delta
+                    } else {
+                        oldest
+                            .filter(|&(oldest, base)| oldest != *version && base > 0)
+                            .map(|(_, base)| (median as f64 - base as f64) / base as f64 * 100.0)
+                    };
+                    match delta {
+                        Some(_) => format!("{} ({})", format_ms(Some(median)), format_delta(delta)),
+                        None => format_ms(Some(median)),
+                    }
+                }
+                None => "-".to_string(),
+            })
+            .collect();
+        let _ = writeln!(output, "| {} | {} |", model, row.join(" | "));
+    }
+    Some(output)
+}
+
+/// Median cell for the markdown summary, with the CI half-width when known,
+/// e.g. `1.234ms ±0.8%`.
+fn format_median_with_ci(bench: &BenchmarkStats) -> String {
+    match bench.median_ci_pct {
+        Some(pct) => format!("{} ±{:.1}%", format_ms(bench.median_ns), pct),
+        None => format_ms(bench.median_ns),
+    }
+}
+
+/// Half-width of the median's 95% CI in percent: the interval the device
+/// recorded, or a host-side bootstrap when the run used median-CI sampling.
+fn median_ci_pct(
+    entry: &Value,
+    samples: &[u64],
+    sampling: Option<&mobench_sdk::SamplingMode>,
+) -> Option<f64> {
+    if let Some(ci) = entry
+        .get("median_ci")
+        .and_then(|ci| serde_json::from_value::<mobench_sdk::MedianCi>(ci.clone()).ok())
+    {
+        return Some(ci.half_width_pct());
+    }
+    if !matches!(sampling, Some(mobench_sdk::SamplingMode::MedianCi { .. })) {
+        return None;
+    }
+    mobench_sdk::timing::bootstrap_median_ci(samples, 0.95).map(|ci| ci.half_width_pct())
+}
+
+/// Sampling mode for `--min-samples`/`--max-samples`/`--ci-target`.
+fn resolve_sampling_mode(
+    min_samples: Option<u32>,
+    max_samples: Option<u32>,
+    ci_target: &str,
+) -> Result<Option<mobench_sdk::SamplingMode>> {
+    let (Some(min_samples), Some(max_samples)) = (min_samples, max_samples) else {
+        return Ok(None);
+    };
+    if min_samples < 2 {
+        bail!("--min-samples must be at least 2 to estimate a confidence interval");
+    }
+    if max_samples < min_samples {
+        bail!("--max-samples ({max_samples}) must be at least --min-samples ({min_samples})");
+    }
+    Ok(Some(mobench_sdk::SamplingMode::MedianCi {
+        min_samples,
+        max_samples,
+        target: parse_ci_target(ci_target)?,
+    }))
+}
+
+/// `--per-iteration-timeout` in the whole milliseconds the runners take,
+/// rounded up so a limit never shrinks to 0.
+fn iteration_timeout_ms(raw: &str) -> Result<u64> {
+    let limit = parse_duration_arg("--per-iteration-timeout", raw)?;
+    Ok((limit.as_secs_f64() * 1000.0).ceil().max(1.0) as u64)
+}
+
+/// Parse a `--warmup-time`/`--measure-time` value such as `3s`, `500ms`,
+/// `1.5s` or `2m`; a bare number is seconds.
+fn parse_duration_arg(flag: &str, raw: &str) -> Result<Duration> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(raw.len());
+    let (value, unit) = raw.split_at(split);
+    let value: f64 = value
+        .parse()
+        .with_context(|| format!("invalid {flag} {raw:?}; expected a duration such as 3s or 500ms"))?;
+    let secs = match unit.trim() {
+        "" | "s" => value,
+        "ms" => value / 1_000.0,
+        "us" => value / 1_000_000.0,
+        "m" | "min" => value * 60.0,
+        other => bail!("invalid {flag} unit {other:?} in {raw:?}; use ms, s or m"),
+    };
+    let duration = Duration::try_from_secs_f64(secs)
+        .with_context(|| format!("invalid {flag} {raw:?}"))?;
+    if duration.is_zero() {
+        bail!("{flag} must be greater than zero");
+    }
+    Ok(duration)
+}
+
+/// Variables the device runner may not be asked to set: mobench's own
+/// namespace and ones the process or dynamic loader relies on, which would
+/// break the runner or, for loader variables, have no effect once it runs.
+const RESERVED_ENV_PREFIXES: [&str; 3] = ["MOBENCH_", "LD_", "DYLD_"];
+const RESERVED_ENV_KEYS: [&str; 3] = ["PATH", "HOME", "TMPDIR"];
+
+/// Stood in for every `[env]` value in summaries written before values were
+/// digested; such a run cannot be resumed with `[env]` set.
+const REDACTED_ENV_VALUE: &str = "<redacted>";
+
+/// Stands in for an `[env]` value in run summaries and streamed reports: a
+/// 64-bit FNV-1a digest of `KEY=VALUE`, so `--resume` can tell two values
+/// apart without recording either.
+fn redacted_env_value(key: &str, value: &str) -> String {
+    // Results carried over from a resumed run were redacted already
+    if value.starts_with("<redacted") {
+        return value.to_string();
+    }
+    let hash = format!("{key}={value}").bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("<redacted:{hash:016x}>")
+}
+
+/// Replace each value of `env` with [`redacted_env_value`].
+fn redact_env_values(env: &mut BTreeMap<String, String>) {
+    for (key, value) in env.iter_mut() {
+        *value = redacted_env_value(key, value);
+    }
+}
+
+/// The device environment: `[env]` from `mobench.toml`, then each `--env
+/// KEY=VALUE` in order, adding to or replacing it.
+fn resolve_bench_env(mut env: BTreeMap<String, String>, flags: &[String]) -> Result<BTreeMap<String, String>> {
+    for flag in flags {
+        let Some((key, value)) = flag.split_once('=') else {
+            bail!("invalid --env {flag:?}; expected KEY=VALUE");
+        };
+        env.insert(key.to_string(), value.to_string());
+    }
+    for (key, value) in &env {
+        let mut chars = key.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            bail!("invalid environment variable name {key:?}; use letters, digits and '_', not starting with a digit");
+        }
+        if RESERVED_ENV_KEYS.contains(&key.as_str())
+            || RESERVED_ENV_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+        {
+            bail!("environment variable {key} is reserved and cannot be set for the device run");
+        }
+        if value.contains('\0') {
+            bail!("environment variable {key} contains a NUL byte");
+        }
+    }
+    Ok(env)
+}
+
+/// `KEY=VALUE` pairs as shown in the run configuration banner.
+fn describe_env(env: &BTreeMap<String, String>) -> String {
+    env.keys().map(String::as_str).collect::<Vec<_>>().join(", ")
+}
+
+/// Replace the `[env]` values a run summary would record, which may be
+/// secrets, with [`redacted_env_value`] digests: in the spec, the build
+/// metadata and the `env` each device report echoes. The names are kept.
+fn redact_env(run_summary: &mut RunSummary) {
+    redact_env_values(&mut run_summary.spec.env);
+    if let Some(meta) = &mut run_summary.build_meta {
+        redact_env_values(&mut meta.spec.env);
+    }
+    for report in run_summary.benchmark_results.iter_mut().flatten().flat_map(|(_, reports)| reports) {
+        redact_report_env(report);
+    }
+}
+
+/// Replace the values of the `env` a device report echoes.
+fn redact_report_env(report: &mut Value) {
+    if let Some(Value::Object(env)) = report.get_mut("env") {
+        for (key, value) in env.iter_mut() {
+            let raw = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+            *value = json!(redacted_env_value(key, &raw));
+        }
+    }
+}
+
+/// Run `f` with the spec's `[env]` set in this process, as the device
+/// runners do, restoring the previous values afterwards.
+fn with_bench_env<R>(env: &BTreeMap<String, String>, f: impl FnOnce() -> R) -> R {
+    let previous: Vec<(&String, Option<std::ffi::OsString>)> =
+        env.keys().map(|key| (key, std::env::var_os(key))).collect();
+    for (key, value) in env {
+        // SAFETY: `run` calls this on its main thread once the device runs
+        // finished, when no other thread reads the environment. Tests must
+        // not call it, since the test harness runs them in parallel.
+        unsafe { std::env::set_var(key, value) };
+    }
+    let result = f();
+    for (key, value) in previous {
+        // SAFETY: as above.
+        unsafe {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+    }
+    result
+}
+
+/// Iteration and warmup counts as shown in the run configuration banner.
+///
+/// Time-based and adaptive modes decide the count on the device, so they are
+/// described instead of showing the ignored `--iterations`/`--warmup`.
+fn describe_counts(spec: &RunSpec) -> (String, String) {
+    use mobench_sdk::{SamplingMode, WarmupPolicy};
+
+    let per_function = |count: u32, embedded: u32| {
+        if embedded == mobench_sdk::USE_BENCHMARK_DEFAULT {
+            format!("per function (default {count})")
+        } else {
+            count.to_string()
+        }
+    };
+    let (embedded_iterations, embedded_warmup) = spec.embedded_counts();
+    let mut iterations = match &spec.sampling {
+        None | Some(SamplingMode::FixedIterations) => per_function(spec.iterations, embedded_iterations),
+        Some(SamplingMode::FixedTime(budget)) => format!("as many as fit in {budget:?}"),
+        Some(SamplingMode::Adaptive { min_samples, max_time, .. }) => {
+            format!("at least {min_samples}, until precise or {max_time:?}")
+        }
+        Some(SamplingMode::MedianCi { min_samples, max_samples, .. }) => {
+            format!("{min_samples}-{max_samples}, until the median CI target is met")
+        }
+    };
+    if spec.drop_first_n > 0 {
+        iterations.push_str(&format!(", first {} dropped", spec.drop_first_n));
+    }
+    if !spec.order.is_declared() {
+        iterations.push_str(&format!(", {} order", spec.order));
+    }
+    if spec.sample_interleave {
+        iterations.push_str(", interleaved round-robin");
+    }
+    let warmup = match &spec.warmup_policy {
+        None | Some(WarmupPolicy::Fixed) => per_function(spec.warmup, embedded_warmup),
+        Some(WarmupPolicy::FixedTime(budget)) => format!("as many as fit in {budget:?}"),
+        Some(WarmupPolicy::UntilStable { max_warmup, .. }) => {
+            format!("at least {}, until stable (max {max_warmup})", per_function(spec.warmup, embedded_warmup))
+        }
+    };
+    (iterations, warmup)
+}
+
+/// Worst-case time one device spends on a function whose single iteration
+/// takes `per_iteration`, given the spec's warmup and sampling modes.
+///
+/// Time-bounded modes contribute their budget; count-bounded modes their
+/// maximum number of iterations.
+fn projected_run_time(spec: &RunSpec, function: &str, per_iteration: Duration) -> Duration {
+    use mobench_sdk::{SamplingMode, WarmupPolicy};
+
+    let (iterations, warmup) = (spec.iterations_for(function), spec.warmup_for(function));
+    let measure = match &spec.sampling {
+        None | Some(SamplingMode::FixedIterations) => per_iteration * iterations,
+        Some(SamplingMode::FixedTime(budget)) => *budget,
+        Some(SamplingMode::Adaptive { max_time, .. }) => *max_time,
+        Some(SamplingMode::MedianCi { max_samples, .. }) => per_iteration * *max_samples,
+    };
+    let warmup = match &spec.warmup_policy {
+        None | Some(WarmupPolicy::Fixed) => per_iteration * warmup,
+        Some(WarmupPolicy::FixedTime(budget)) => *budget,
+        Some(WarmupPolicy::UntilStable { max_warmup, .. }) => per_iteration * (*max_warmup).max(warmup),
+    };
+    measure + warmup
+}
+
+/// Whether [`projected_run_time`] is the same for any iteration time, i.e.
+/// both warmup and sampling are bounded by a time budget.
+fn run_time_is_time_bounded(spec: &RunSpec) -> bool {
+    use mobench_sdk::{SamplingMode, WarmupPolicy};
+
+    matches!(spec.sampling, Some(SamplingMode::FixedTime(_) | SamplingMode::Adaptive { .. }))
+        && matches!(spec.warmup_policy, Some(WarmupPolicy::FixedTime(_)))
+}
+
+/// Refuse device runs projected to outlast `budget` unless `force` is set.
+///
+/// Each function is timed for one iteration on this machine; host timings
+/// only approximate device speed. Functions not linked into this binary,
+/// which is every function of a user's project, cannot be timed. Their
+/// projection is exact only when warmup and sampling are time-bounded;
+/// otherwise the budget is enforced only if the timed functions alone
+/// exceed it, and skipped with a warning when they do not.
+fn check_projected_run_time(spec: &RunSpec, budget: Duration, force: bool) -> Result<()> {
+    let mut projected = Duration::ZERO;
+    let mut unmeasured = Vec::new();
+    for function in spec.all_functions() {
+        let probe = mobench_sdk::BenchSpec {
+            name: function.to_string(),
+            iterations: 1,
+            warmup: 0,
+            warmup_policy: Default::default(),
+            sampling: Default::default(),
+            per_iteration_timeout: None,
+            timeout: None,
+            capture_warmup: false,
+            seed: spec.seed,
+            drop_first_n: 0,
+        };
+        match mobench_sdk::run_benchmark(probe) {
+            Ok(report) => {
+                let per_iteration = report
+                    .samples
+                    .first()
+                    .map(|sample| Duration::from_nanos(sample.duration_ns))
+                    .unwrap_or_default();
+                projected += projected_run_time(spec, function, per_iteration);
+            }
+            Err(_) if run_time_is_time_bounded(spec) => {
+                projected += projected_run_time(spec, function, Duration::ZERO);
+            }
+            Err(_) => unmeasured.push(function),
+        }
+    }
+    if projected <= budget {
+        if !unmeasured.is_empty() {
+            println!(
+                "Warning: cannot time {} on this machine (not linked into mobench); --max-run-time is not enforced for this run",
+                unmeasured.join(", ")
+            );
+        }
+        return Ok(());
+    }
+    // Untimed functions only add to a projection that is already too long
+    let message = format!(
+        "projected run time of {}{:?} per device exceeds --max-run-time {:?}",
+        if unmeasured.is_empty() { "" } else { "at least " },
+        projected,
+        budget
+    );
+    if force {
+        println!("Warning: {} (continuing because of --force)", message);
+        return Ok(());
+    }
+    bail!(
+        "{}. Lower --iterations/--warmup, raise --max-run-time, or pass --force to run anyway.",
+        message
+    )
+}
+
+/// Parse `--ci-target`: `2%` is a percentage of the median, `5000` is nanoseconds.
+fn parse_ci_target(raw: &str) -> Result<mobench_sdk::CiTarget> {
+    let raw = raw.trim();
+    if let Some(pct) = raw.strip_suffix('%') {
+        let pct: f64 = pct
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid --ci-target percentage {raw:?}"))?;
+        if !pct.is_finite() || pct <= 0.0 {
+            bail!("--ci-target percentage must be positive, got {raw:?}");
+        }
+        return Ok(mobench_sdk::CiTarget::PercentOfMedian(pct));
+    }
+    let ns: u64 = raw
+        .strip_suffix("ns")
+        .unwrap_or(raw)
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid --ci-target {raw:?}; expected nanoseconds (e.g. 5000) or a percentage (e.g. 2%)"))?;
+    Ok(mobench_sdk::CiTarget::Nanos(ns))
+}
+
+/// Longest warmup series shown in the markdown summary.
+const MAX_WARMUP_RAMP_POINTS: usize = 12;
+
+/// One line showing a benchmark's warmup durations next to its measured
+/// median, e.g. `my::bench: 4.100 → 2.300 → 1.200 (median 1.150ms; first warmup 3.57x)`.
+fn render_warmup_ramp(bench: &BenchmarkStats) -> Option<String> {
+    let first = *bench.warmup_ns.first()?;
+    let mut series: Vec<String> = bench
+        .warmup_ns
+        .iter()
+        .take(MAX_WARMUP_RAMP_POINTS)
+        .map(|ns| format!("{:.3}", *ns as f64 / 1_000_000.0))
+        .collect();
+    if bench.warmup_ns.len() > MAX_WARMUP_RAMP_POINTS {
+        series.push(format!("… (+{} more)", bench.warmup_ns.len() - MAX_WARMUP_RAMP_POINTS));
+    }
+    let median = match bench.median_ns.filter(|median| *median > 0) {
+        Some(median) => format!(
+            " (median {}; first warmup {:.2}x)",
+            format_with_unit(Some(median)),
+            first as f64 / median as f64
+        ),
+        None => String::new(),
+    };
+    Some(format!("{}: {}{}", bench.function, series.join(" → "), median))
+}
+
+fn render_csv_summary(summary: &SummaryReport) -> String {
+    // One `<metric>_median` column per custom metric seen in any benchmark
+    let metric_names: BTreeSet<&str> = summary
+        .device_summaries
+        .iter()
+        .flat_map(|device| &device.benchmarks)
+        .flat_map(|bench| bench.metrics.keys().map(String::as_str))
+        .collect();
+    let mut output = String::new();
+    let _ = write!(
+        output,
+        "device,function,samples,mean_ns,median_ns,p95_ns,p99_ns,min_ns,max_ns,cpu_median_ns"
+    );
+    for name in &metric_names {
+        let _ = write!(output, ",{}_median", name);
+    }
+    let _ = writeln!(output);
+    for device in &summary.device_summaries {
+        for bench in &device.benchmarks {
+            let _ = write!(
+                output,
+                "{},{},{},{},{},{},{},{},{},{}",
+                device.device,
+                bench.function,
+                bench.samples,
+                bench.mean_ns.map_or(String::from(""), |v| v.to_string()),
+                bench.median_ns.map_or(String::from(""), |v| v.to_string()),
+                bench.p95_ns.map_or(String::from(""), |v| v.to_string()),
+                bench.p99_ns.map_or(String::from(""), |v| v.to_string()),
+                bench.min_ns.map_or(String::from(""), |v| v.to_string()),
+                bench.max_ns.map_or(String::from(""), |v| v.to_string()),
+                bench.cpu_median_ns.map_or(String::from(""), |v| v.to_string())
+            );
+            for name in &metric_names {
+                let median = bench.metrics.get(*name).map(|metric| metric.median);
+                let _ = write!(output, ",{}", median.map_or(String::new(), |v| v.to_string()));
+            }
+            let _ = writeln!(output);
+        }
+    }
+    output
+}
+
+/// Render summary entries in Google Benchmark's JSON schema.
+///
+/// Each device/function becomes a benchmark named `<function>/<device>` with
+/// one `iteration` run (mean time over `samples` iterations) followed by
+/// `median` and `p95` aggregates. Only wall-time statistics are summarized, so
+/// `cpu_time` repeats `real_time`. Entries without a mean are skipped, and
+/// only the first entry per name is kept when a report lists a device twice.
+fn render_gbench_json(data: &[SummaryData]) -> Value {
+    // Benchmarks per function so far, in first-seen order
+    let mut families: Vec<(&str, usize)> = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut benchmarks = Vec::new();
+    for entry in data {
+        let (Some(function), Some(mean_ns)) = (entry.function.as_deref(), entry.mean_ns) else {
+            continue;
+        };
+        let name = match entry.device.as_deref() {
+            Some(device) => format!("{}/{}", function, device),
+            None => function.to_string(),
+        };
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        let family_index = families.iter().position(|(f, _)| *f == function).unwrap_or_else(|| {
+            families.push((function, 0));
+            families.len() - 1
+        });
+        let instance_index = families[family_index].1;
+        families[family_index].1 += 1;
+        let run = |run_name: String, run_type: &str, time_ns: u64| {
+            json!({
+                "name": run_name,
+                "family_index": family_index,
+                "per_family_instance_index": instance_index,
+                "run_name": name,
+                "run_type": run_type,
+                "repetitions": 1,
+                "repetition_index": 0,
+                "threads": 1,
+                "iterations": entry.sample_count,
+                "real_time": time_ns,
+                "cpu_time": time_ns,
+                "time_unit": "ns",
+            })
+        };
+        benchmarks.push(run(name.clone(), "iteration", mean_ns));
+        for (aggregate, value) in [("median", entry.median_ns), ("p95", entry.p95_ns)] {
+            if let Some(time_ns) = value {
+                let mut aggregate_run = run(format!("{}_{}", name, aggregate), "aggregate", time_ns);
+                aggregate_run["aggregate_name"] = json!(aggregate);
+                aggregate_run["aggregate_unit"] = json!("time");
+                benchmarks.push(aggregate_run);
+            }
+        }
+    }
+    let date = OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default();
+    json!({
+        "context": {
+            "date": date,
+            "executable": "mobench",
+            "mobench_version": env!("CARGO_PKG_VERSION"),
+        },
+        "benchmarks": benchmarks,
+    })
+}
+
+fn write_gbench_json(path: &Path, data: &[SummaryData]) -> Result<()> {
+    let json = serde_json::to_string_pretty(&render_gbench_json(data))?;
+    ensure_parent_dir(path)?;
+    write_file(path, json.as_bytes())?;
+    println!("Wrote Google Benchmark JSON to {:?}", path);
+    Ok(())
+}
+
+/// Formats a duration in nanoseconds to a human-readable string.
+///
+/// The function picks the appropriate unit based on the magnitude:
+/// - Uses milliseconds (ms) by default
+/// - Switches to seconds (s) if the value is >= 1000ms (1 second)
+///
+/// Examples:
+/// - 500_000 ns -> "0.500ms"
+/// - 1_500_000 ns -> "1.500ms"
+/// - 1_500_000_000 ns -> "1.500s"
+fn format_duration_smart(ns: u64) -> String {
+    let ms = ns as f64 / 1_000_000.0;
+    if ms >= 1000.0 {
+        // Convert to seconds
+        let secs = ms / 1000.0;
+        format!("{:.3}s", secs)
+    } else {
+        format!("{:.3}ms", ms)
+    }
+}
+
+/// Custom metric values have no fixed unit; show up to three decimals.
+fn format_metric(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// A duration table cell in the `--unit` unit, or `-` when absent.
+fn format_ms(value: Option<u64>) -> String {
+    let unit = units::current();
+    value
+        .map(|ns| unit.cell(ns))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// A duration outside a table, always with its unit suffix.
+fn format_with_unit(value: Option<u64>) -> String {
+    let unit = units::current();
+    value
+        .map(|ns| unit.with_suffix(ns))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn run_android_build(
+    _ndk_home: &str,
+    release: bool,
+    cargo_profile: Option<&str>,
+    output_dir: &Path,
+    dry_run: bool,
+) -> Result<mobench_sdk::BuildResult> {
+    let root = repo_root()?;
+    let crate_name =
+        detect_bench_mobile_crate_name(&root).unwrap_or_else(|_| "bench-mobile".to_string());
+
+    let profile = if release {
+        mobench_sdk::BuildProfile::Release
+    } else {
+        mobench_sdk::BuildProfile::Debug
+    };
+    let cfg = mobench_sdk::BuildConfig {
+        target: mobench_sdk::Target::Android,
+        profile,
+        incremental: true,
+        cache: true,
+        cargo_profile: cargo_profile.map(str::to_string),
+    };
+    let builder = mobench_sdk::builders::AndroidBuilder::new(&root, crate_name)
+        .output_dir(output_dir)
+        .verbose(true)
+        .dry_run(dry_run)
+        .max_lib_size_kb(config::ConfigResolver::new().unwrap_or_default().max_lib_size_kb());
+    let _span = tracing::info_span!("build", target = "android", release).entered();
+    logging::step("build", || Ok(builder.build(&cfg).map_err(MobenchError::build_failed)?))
+}
+
+fn load_dotenv() {
+    if let Ok(root) = repo_root() {
+        let path = root.join(".env.local");
+        let _ = dotenvy::from_path(path);
+    }
+}
+
+fn repo_root() -> Result<PathBuf> {
+    let cwd = std::env::current_dir().context("resolving repo root from current directory")?;
+    if let Some(root) = find_repo_root(&cwd) {
+        return Ok(root);
+    }
+
+    let compiled = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("..");
+    if let Ok(path) = compiled.canonicalize() {
+        if let Some(root) = find_repo_root(&path) {
+            return Ok(root);
+        }
+        return Ok(path);
+    }
+
+    Ok(cwd)
+}
+
+fn find_repo_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|candidate| is_repo_root(candidate))
+        .map(|root| root.to_path_buf())
+}
+
+fn is_repo_root(candidate: &Path) -> bool {
+    candidate.join("bench-mobile").join("Cargo.toml").is_file()
+        || candidate
+            .join("crates")
+            .join("sample-fns")
+            .join("Cargo.toml")
+            .is_file()
+}
+
+fn ensure_can_write(path: &Path) -> Result<()> {
+    if path.exists() {
+        bail!("refusing to overwrite existing file: {:?}", path);
+    }
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating parent directory {:?}", parent))?;
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("writing file {:?}", path))
+}
+
+/// Initialize a new benchmark project using mobench-sdk (Phase 1 MVP)
+fn cmd_init_sdk(
+    target: SdkTarget,
+    project_name: String,
+    output_dir: PathBuf,
+    generate_examples: bool,
+    with_criterion: bool,
+) -> Result<()> {
+    println!("Initializing benchmark project with mobench-sdk...");
+    println!("  Project name: {}", project_name);
+    println!("  Target: {:?}", target);
+    println!("  Output directory: {:?}", output_dir);
+
+    let sdk_config = mobench_sdk::InitConfig {
+        target: target.into(),
+        project_name: project_name.clone(),
+        output_dir: output_dir.clone(),
+        generate_examples,
+        with_criterion,
+    };
+
+    mobench_sdk::codegen::generate_project(&sdk_config).context("Failed to generate project")?;
+
+    if with_criterion {
+        let bench_name = mobench_sdk::codegen::CRITERION_BENCH_NAME;
+        println!("  Generated criterion harness benches/{}.rs", bench_name);
+        if output_dir.join("Cargo.toml").exists() {
+            println!("  Added criterion dev-dependency and [[bench]] target to Cargo.toml");
+        } else {
+            println!(
+                "  No Cargo.toml in {:?}; add `criterion = \"0.5\"` to [dev-dependencies] and a [[bench]] target named {} with harness = false",
+                output_dir, bench_name
+            );
+        }
+    }
+
+    // Generate mobench.toml configuration file
+    let mobench_toml_path = output_dir.join(config::CONFIG_FILE_NAME);
+    if !mobench_toml_path.exists() {
+        let toml_content = config::MobenchConfig::generate_starter_toml(&project_name);
+        fs::write(&mobench_toml_path, toml_content)
+            .with_context(|| format!("Failed to write {:?}", mobench_toml_path))?;
+        println!("  Generated mobench.toml configuration file");
+    }
+
+    println!("\n[checkmark] Project initialized successfully!");
+    println!("\nNext steps:");
+    println!("  1. Add benchmark functions to your code with #[benchmark]");
+    println!("  2. Edit mobench.toml to customize your project settings");
+    println!("  3. Run 'cargo mobench build --target <platform>' to build");
+    if with_criterion {
+        println!("  4. Run 'cargo bench' to measure the same benchmarks locally with criterion");
+    }
+
+    Ok(())
+}
+
+/// Build mobile artifacts using mobench-sdk (Phase 1 MVP)
+#[allow(clippy::too_many_arguments)]
+fn cmd_build(
+    target: SdkTarget,
+    release: bool,
+    cargo_profile: Option<String>,
+    output_dir: Option<PathBuf>,
+    crate_path: Option<PathBuf>,
+    dry_run: bool,
+    verbose: bool,
+    progress: bool,
+    cache: bool,
+) -> Result<()> {
+    // Load config file if present (mobench.toml)
+    let config_resolver = config::ConfigResolver::new().unwrap_or_default();
+
+    // Progress mode: simplified output
+    if progress {
+        let project_root = std::env::current_dir().context("Failed to get current directory")?;
+        let crate_name = detect_bench_mobile_crate_name(&project_root)
+            .unwrap_or_else(|_| "bench-mobile".to_string());
+        let effective_output_dir = output_dir.or_else(|| config_resolver.output_dir().map(|p| p.to_path_buf()));
+
+        let build_config = mobench_sdk::BuildConfig {
+            target: target.into(),
+            profile: if release {
+                mobench_sdk::BuildProfile::Release
+            } else {
+                mobench_sdk::BuildProfile::Debug
+            },
+            incremental: true,
+            cache,
+            cargo_profile: cargo_profile.clone(),
+        };
+
+        match target {
+            SdkTarget::Android => {
+                println!("[1/3] Building Rust library...");
+                let mut builder =
+                    mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name)
+                        .verbose(false)
+                        .dry_run(dry_run)
+                        .max_lib_size_kb(config_resolver.max_lib_size_kb());
+                if let Some(ref dir) = effective_output_dir {
+                    builder = builder.output_dir(dir);
+                }
+                if let Some(ref path) = crate_path {
+                    builder = builder.crate_dir(path);
+                }
+                println!("[2/3] Building Android APK...");
+                let result = builder.build(&build_config).map_err(MobenchError::build_failed)?;
+                println!("[3/3] Done!");
+                if !dry_run {
+                    println!("\n{} APK: {:?}", term::ok(), result.app_path);
+                }
+            }
+            SdkTarget::Ios => {
+                println!("[1/3] Building Rust library...");
+                let mut builder =
+                    mobench_sdk::builders::IosBuilder::new(&project_root, crate_name)
+                        .verbose(false)
+                        .dry_run(dry_run)
+                        .app_options(config_resolver.ios_app_options()?)
+                        .max_lib_size_kb(config_resolver.max_lib_size_kb());
+                if let Some(ref dir) = effective_output_dir {
+                    builder = builder.output_dir(dir);
+                }
+                if let Some(ref path) = crate_path {
+                    builder = builder.crate_dir(path);
+                }
+                println!("[2/3] Building iOS xcframework...");
+                let result = builder.build(&build_config).map_err(MobenchError::build_failed)?;
+                println!("[3/3] Done!");
+                if !dry_run {
+                    println!("\n{} Framework: {:?}", term::ok(), result.app_path);
+                }
+            }
+            SdkTarget::Both => {
+                println!("[1/5] Building Rust library for Android...");
+                let mut android_builder =
+                    mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name.clone())
+                        .verbose(false)
+                        .dry_run(dry_run)
+                        .max_lib_size_kb(config_resolver.max_lib_size_kb());
+                if let Some(ref dir) = effective_output_dir {
+                    android_builder = android_builder.output_dir(dir);
+                }
+                if let Some(ref path) = crate_path {
+                    android_builder = android_builder.crate_dir(path);
+                }
+                println!("[2/5] Building Android APK...");
+                let android_result = android_builder.build(&build_config).map_err(MobenchError::build_failed)?;
+
+                println!("[3/5] Building Rust library for iOS...");
+                let mut ios_builder =
+                    mobench_sdk::builders::IosBuilder::new(&project_root, crate_name)
+                        .verbose(false)
+                        .dry_run(dry_run)
+                        .app_options(config_resolver.ios_app_options()?)
+                        .max_lib_size_kb(config_resolver.max_lib_size_kb());
+                if let Some(ref dir) = effective_output_dir {
+                    ios_builder = ios_builder.output_dir(dir);
+                }
+                if let Some(ref path) = crate_path {
+                    ios_builder = ios_builder.crate_dir(path);
+                }
+                println!("[4/5] Building iOS xcframework...");
+                let ios_result = ios_builder.build(&build_config).map_err(MobenchError::build_failed)?;
+
+                println!("[5/5] Done!");
+                if !dry_run {
+                    println!("\n{} APK: {:?}", term::ok(), android_result.app_path);
+                    println!("{} Framework: {:?}", term::ok(), ios_result.app_path);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    // Normal (verbose) mode
+    if let Some(config_path) = &config_resolver.config_path {
+        println!("Using config file: {:?}", config_path);
+    }
+
+    println!("Building mobile artifacts...");
+    println!("  Target: {:?}", target);
+    println!("  Profile: {}", if release { "release" } else { "debug" });
+    if dry_run {
+        println!("  Mode: dry-run (no changes will be made)");
+    }
+    if verbose {
+        println!("  Verbose: enabled");
+    }
+
+    let project_root = std::env::current_dir().context("Failed to get current directory")?;
+
+    // Use crate name from config if not auto-detected
+    let crate_name = detect_bench_mobile_crate_name(&project_root)
+        .or_else(|_| {
+            config_resolver
+                .crate_name()
+                .map(|s| s.to_string())
+                .ok_or_else(|| anyhow!("Could not detect crate name"))
+        })
+        .unwrap_or_else(|_| "bench-mobile".to_string()); // Fallback for legacy layouts
+
+    // CLI flags override config file values
+    let effective_output_dir = output_dir.or_else(|| config_resolver.output_dir().map(|p| p.to_path_buf()));
+
+    if let Some(ref dir) = effective_output_dir {
+        println!("  Output: {:?}", dir);
+    }
+    if let Some(ref path) = crate_path {
+        println!("  Crate: {:?}", path);
+    }
+
+    let build_config = mobench_sdk::BuildConfig {
+        target: target.into(),
+        profile: if release {
+            mobench_sdk::BuildProfile::Release
+        } else {
+            mobench_sdk::BuildProfile::Debug
+        },
+        incremental: true,
+        cache,
+        cargo_profile,
+    };
+
+    match target {
+        SdkTarget::Android => {
+            println!("\nBuilding for Android...");
+            println!("  Building Rust library for Android targets...");
+            let mut builder =
+                mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name.clone())
+                    .verbose(verbose)
+                    .dry_run(dry_run)
+                    .max_lib_size_kb(config_resolver.max_lib_size_kb());
+            if let Some(ref dir) = effective_output_dir {
+                builder = builder.output_dir(dir);
+            }
+            if let Some(ref path) = crate_path {
+                builder = builder.crate_dir(path);
+            }
+            let result = builder.build(&build_config).map_err(MobenchError::build_failed)?;
+            if !dry_run {
+                println!("{} Built Android APK", term::ok());
+                println!("\n[checkmark] Android build completed!");
+                println!("  APK: {:?}", result.app_path);
+            }
+        }
+        SdkTarget::Ios => {
+            println!("\nBuilding for iOS...");
+            println!("  Building Rust library for iOS targets...");
+            let mut builder =
+                mobench_sdk::builders::IosBuilder::new(&project_root, crate_name.clone())
+                    .verbose(verbose)
+                    .dry_run(dry_run)
+                    .app_options(config_resolver.ios_app_options()?)
+                    .max_lib_size_kb(config_resolver.max_lib_size_kb());
+            if let Some(ref dir) = effective_output_dir {
+                builder = builder.output_dir(dir);
+            }
+            if let Some(ref path) = crate_path {
+                builder = builder.crate_dir(path);
+            }
+            let result = builder.build(&build_config).map_err(MobenchError::build_failed)?;
+            if !dry_run {
+                println!("{} Built iOS xcframework", term::ok());
+                println!("\n[checkmark] iOS build completed!");
+                println!("  Framework: {:?}", result.app_path);
+            }
+        }
+        SdkTarget::Both => {
+            // Build Android
+            println!("\nBuilding for Android...");
+            println!("  Building Rust library for Android targets...");
+            let mut android_builder =
+                mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name.clone())
+                    .verbose(verbose)
+                    .dry_run(dry_run)
+                    .max_lib_size_kb(config_resolver.max_lib_size_kb());
+            if let Some(ref dir) = effective_output_dir {
+                android_builder = android_builder.output_dir(dir);
+            }
+            if let Some(ref path) = crate_path {
+                android_builder = android_builder.crate_dir(path);
+            }
+            let android_result = android_builder.build(&build_config).map_err(MobenchError::build_failed)?;
+            if !dry_run {
+                println!("{} Built Android APK", term::ok());
+                println!("\n[checkmark] Android build completed!");
+                println!("  APK: {:?}", android_result.app_path);
+            }
+
+            // Build iOS
+            println!("\nBuilding for iOS...");
+            println!("  Building Rust library for iOS targets...");
+            let mut ios_builder =
+                mobench_sdk::builders::IosBuilder::new(&project_root, crate_name)
+                    .verbose(verbose)
+                    .dry_run(dry_run)
+                    .app_options(config_resolver.ios_app_options()?)
+                    .max_lib_size_kb(config_resolver.max_lib_size_kb());
+            if let Some(ref dir) = effective_output_dir {
+                ios_builder = ios_builder.output_dir(dir);
+            }
+            if let Some(ref path) = crate_path {
+                ios_builder = ios_builder.crate_dir(path);
+            }
+            let ios_result = ios_builder.build(&build_config).map_err(MobenchError::build_failed)?;
+            if !dry_run {
+                println!("{} Built iOS xcframework", term::ok());
+                println!("\n[checkmark] iOS build completed!");
+                println!("  Framework: {:?}", ios_result.app_path);
+            }
+        }
+    }
+
+    if dry_run {
+        println!("\n[dry-run] Build simulation completed. No changes were made.");
+    }
+
+    Ok(())
+}
+
+fn detect_bench_mobile_crate_name(root: &Path) -> Result<String> {
+    // Try bench-mobile/ first (SDK projects)
+    let bench_mobile_path = root.join("bench-mobile").join("Cargo.toml");
+    if bench_mobile_path.exists() {
+        let contents = fs::read_to_string(&bench_mobile_path)
+            .with_context(|| format!("reading bench-mobile manifest at {:?}", bench_mobile_path))?;
+        let value: toml::Value = toml::from_str(&contents)
+            .with_context(|| format!("parsing bench-mobile manifest {:?}", bench_mobile_path))?;
+        let name = value
+            .get("package")
+            .and_then(|pkg| pkg.get("name"))
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| {
+                anyhow!(
+                    "bench-mobile package.name missing in {:?}",
+                    bench_mobile_path
+                )
+            })?;
+        return Ok(name.to_string());
+    }
+
+    // Fallback: Try crates/sample-fns (repository testing)
+    let sample_fns_path = root.join("crates").join("sample-fns").join("Cargo.toml");
+    if sample_fns_path.exists() {
+        let contents = fs::read_to_string(&sample_fns_path)
+            .with_context(|| format!("reading sample-fns manifest at {:?}", sample_fns_path))?;
+        let value: toml::Value = toml::from_str(&contents)
+            .with_context(|| format!("parsing sample-fns manifest {:?}", sample_fns_path))?;
+        let name = value
+            .get("package")
+            .and_then(|pkg| pkg.get("name"))
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| anyhow!("sample-fns package.name missing in {:?}", sample_fns_path))?;
+        return Ok(name.to_string());
+    }
+
+    bail!(
+        "No benchmark crate found. Expected bench-mobile/Cargo.toml or crates/sample-fns/Cargo.toml under the project root. Run from the project root or set [project].crate in mobench.toml."
+    )
+}
+
+/// A benchmark entry in `mobench list --json` output.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ListedBenchmark {
+    name: String,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    group: Option<String>,
+    params: Vec<String>,
+    ignored: bool,
+    skip_reason: Option<String>,
+    has_setup: bool,
+    sample_size: Option<u32>,
+    warmup: Option<u32>,
+}
+
+impl From<mobench_sdk::codegen::DetectedBenchmark> for ListedBenchmark {
+    fn from(bench: mobench_sdk::codegen::DetectedBenchmark) -> Self {
+        Self {
+            crate_name: crate_of(&bench.name),
+            name: bench.name,
+            group: bench.group,
+            params: bench.params,
+            ignored: bench.ignored,
+            skip_reason: bench.skip_reason,
+            has_setup: bench.has_setup,
+            sample_size: bench.sample_size,
+            warmup: bench.warmup,
+        }
+    }
+}
+
+impl From<&mobench_sdk::BenchFunction> for ListedBenchmark {
+    fn from(bench: &mobench_sdk::BenchFunction) -> Self {
+        Self {
+            name: bench.name.to_string(),
+            crate_name: crate_of(bench.name),
+            group: bench.group.map(str::to_string),
+            params: bench.params.iter().map(|p| p.to_string()).collect(),
+            ignored: bench.ignored,
+            skip_reason: bench.skip_reason.map(str::to_string),
+            has_setup: bench.has_setup,
+            sample_size: bench.sample_size,
+            warmup: bench.warmup,
+        }
+    }
+}
+
+/// The crate component of a `crate::path::function` benchmark name.
+fn crate_of(name: &str) -> String {
+    name.split("::").next().unwrap_or(name).to_string()
+}
+
+/// List all discovered benchmark functions
+///
+/// This uses source code scanning to find `#[benchmark]` functions, which works
+/// without requiring a full build. It also falls back to the inventory registry
+/// for any benchmarks that may be registered at runtime.
+fn cmd_list(json: bool, since: Option<&str>) -> Result<()> {
+    if !json {
+        println!("Discovering benchmark functions...\n");
+    }
+
+    let project_root = repo_root()?;
+    let changes = since
+        .map(|git_ref| changed::ChangedFiles::since(&project_root, git_ref))
+        .transpose()?;
+    let mut listed: Vec<ListedBenchmark> = Vec::new();
+
+    // Method 1: Source code scanning (works without build)
+    let search_dirs = benchmark_search_dirs(&project_root);
+
+    for (default_crate_name, dir) in &search_dirs {
+        let Some(benchmarks) = scan_benchmark_dir(default_crate_name, dir) else {
+            continue;
+        };
+        let selected: Vec<_> = match &changes {
+            Some(changes) => changes.affected(dir, &benchmarks).into_iter().cloned().collect(),
+            None => benchmarks,
+        };
+        for bench in selected {
+            if !listed.iter().any(|b| b.name == bench.name) {
+                listed.push(bench.into());
+            }
+        }
+    }
+
+    // Method 2: Inventory registry (for runtime-registered benchmarks).
+    // These have no source file to diff, so `--since` leaves them out.
+    if changes.is_none() {
+        let registry_benchmarks = mobench_sdk::discover_benchmarks();
+        for bench in registry_benchmarks {
+            if !listed.iter().any(|b| b.name == bench.name) {
+                listed.push(bench.into());
+            }
+        }
+    }
+
+    listed.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&listed)?);
+        return Ok(());
+    }
+
+    let all_benchmarks: Vec<&str> = listed.iter().map(|b| b.name.as_str()).collect();
+
+    if let Some(changes) = &changes
+        && all_benchmarks.is_empty()
+    {
+        println!("No benchmarks changed since {}.", changes.since);
+    } else if all_benchmarks.is_empty() {
+        println!("No benchmarks found.\n");
+        println!("Searched locations:");
+        for (name, dir) in &search_dirs {
+            if !name.is_empty() {
+                println!("  - {}: {}", name, dir.display());
+            }
+        }
+        println!("\nTo add benchmarks:");
+        println!("  1. Add #[benchmark] attribute to functions");
+        println!("  2. Make sure mobench-sdk is in your dependencies");
+        println!("  3. Run 'cargo mobench list' again");
+    } else {
+        println!("Found {} benchmark(s):", all_benchmarks.len());
+        for bench in &all_benchmarks {
+            println!("  {}", bench);
+        }
+        println!();
+        println!("Usage:");
+        println!("  cargo mobench run --target android --function {} --iterations 100", all_benchmarks.first().unwrap());
+    }
+
+    Ok(())
+}
+
+/// Crate directories scanned for `#[benchmark]` functions, with the crate name
+/// to use for each (empty means read it from the directory's Cargo.toml).
+fn benchmark_search_dirs(project_root: &Path) -> [(&'static str, PathBuf); 4] {
+    [
+        ("bench-mobile", project_root.join("bench-mobile")),
+        ("sample-fns", project_root.join("crates/sample-fns")),
+        ("ffi-benchmark", project_root.join("crates/ffi-benchmark")),
+        ("", project_root.to_path_buf()),
+    ]
+}
+
+/// Scan one search directory, or `None` if it is not a crate.
+fn scan_benchmark_dir(
+    default_crate_name: &str,
+    dir: &Path,
+) -> Option<Vec<mobench_sdk::codegen::DetectedBenchmark>> {
+    if !dir.join("Cargo.toml").exists() {
+        return None;
+    }
+    let crate_name = if default_crate_name.is_empty() {
+        get_crate_name_from_cargo_toml(&dir.join("Cargo.toml")).ok()?
+    } else {
+        default_crate_name.to_string()
+    };
+    Some(mobench_sdk::codegen::detect_benchmarks_with_metadata(
+        dir,
+        &crate_name,
+    ))
+}
+
+/// Every `#[benchmark]` function in the benchmark crate, for `run --all`,
+/// in source order: modules as declared, functions as they appear.
+fn discover_all_functions(project_root: &Path) -> Result<Vec<String>> {
+    let mut functions: Vec<String> = Vec::new();
+    for (default_crate_name, dir) in &benchmark_search_dirs(project_root) {
+        for bench in scan_benchmark_dir(default_crate_name, dir).into_iter().flatten() {
+            if !functions.contains(&bench.name) {
+                functions.push(bench.name);
+            }
+        }
+    }
+    if functions.is_empty() {
+        bail!(
+            "--all found no #[benchmark] functions under {}; check `cargo mobench list`",
+            project_root.display()
+        );
+    }
+    Ok(functions)
+}
+
+/// Whether `function`'s defining crate/module changed, or `None` when the
+/// function cannot be found in source (e.g. direct FFI dispatch), in which
+/// case the caller should run it rather than guess.
+fn function_changed(
+    project_root: &Path,
+    function: &str,
+    changes: &changed::ChangedFiles,
+) -> Option<bool> {
+    let mut found = false;
+    for (default_crate_name, dir) in &benchmark_search_dirs(project_root) {
+        let Some(benchmarks) = scan_benchmark_dir(default_crate_name, dir) else {
+            continue;
+        };
+        if !benchmarks.iter().any(|b| b.matches(function)) {
+            continue;
+        }
+        found = true;
+        if changes
+            .affected(dir, &benchmarks)
+            .iter()
+            .any(|b| b.matches(function))
+        {
+            return Some(true);
+        }
+    }
+    found.then_some(false)
+}
+
+/// Source metadata for `function`, e.g. its `sample_size`/`warmup` defaults.
+fn find_detected_benchmark(
+    project_root: &Path,
+    function: &str,
+) -> Option<mobench_sdk::codegen::DetectedBenchmark> {
+    benchmark_search_dirs(project_root)
+        .iter()
+        .filter_map(|(default_crate_name, dir)| scan_benchmark_dir(default_crate_name, dir))
+        .flatten()
+        .find(|bench| bench.matches(function))
+}
+
+/// The `#[benchmark(sample_size, warmup)]` defaults of a suite's functions,
+/// for the counts the device may resolve per function.
+///
+/// The runners fall back to the SDK's built-in counts for functions without
+/// a default, so a count is only left to them (`leave_iterations`,
+/// `leave_warmup`) when no flag, profile or `[benchmarks]` setting chose
+/// another one.
+fn suite_benchmark_defaults(
+    project_root: &Path,
+    functions: &[String],
+    leave_iterations: bool,
+    leave_warmup: bool,
+) -> BTreeMap<String, BenchmarkDefaults> {
+    if !leave_iterations && !leave_warmup {
+        return BTreeMap::new();
+    }
+    let detected: Vec<mobench_sdk::codegen::DetectedBenchmark> = benchmark_search_dirs(project_root)
+        .iter()
+        .filter_map(|(default_crate_name, dir)| scan_benchmark_dir(default_crate_name, dir))
+        .flatten()
+        .collect();
+    functions
+        .iter()
+        .filter_map(|function| {
+            let bench = detected.iter().find(|bench| bench.matches(function))?;
+            let defaults = BenchmarkDefaults {
+                sample_size: bench.sample_size.filter(|_| leave_iterations),
+                warmup: bench.warmup.filter(|_| leave_warmup),
+            };
+            (defaults != BenchmarkDefaults::default()).then(|| (function.clone(), defaults))
+        })
+        .collect()
+}
+
+fn get_crate_name_from_cargo_toml(cargo_toml: &Path) -> Result<String> {
+    let contents = fs::read_to_string(cargo_toml)?;
+    let value: toml::Value = toml::from_str(&contents)?;
+    let name = value
+        .get("package")
+        .and_then(|pkg| pkg.get("name"))
+        .and_then(|n| n.as_str())
+        .ok_or_else(|| anyhow!("package.name not found in {:?}", cargo_toml))?;
+    Ok(name.to_string())
+}
+
+/// Package iOS app as IPA for distribution or testing
+fn cmd_package_ipa(scheme: &str, method: IosSigningMethodArg, output_dir: Option<PathBuf>) -> Result<()> {
+    println!("Packaging iOS app as IPA...");
+    println!("  Scheme: {}", scheme);
+    println!("  Method: {:?}", method);
+    if let Some(ref dir) = output_dir {
+        println!("  Output: {:?}", dir);
+    }
+
+    let project_root = repo_root()?;
+    let crate_name = detect_bench_mobile_crate_name(&project_root)
+        .unwrap_or_else(|_| "bench-mobile".to_string());
+
+    let mut builder = mobench_sdk::builders::IosBuilder::new(&project_root, crate_name).verbose(true);
+    if let Some(ref dir) = output_dir {
+        builder = builder.output_dir(dir);
+    }
+
+    let signing_method: mobench_sdk::builders::SigningMethod = method.into();
+    let ipa_path = builder
+        .package_ipa(scheme, signing_method)
+        .context("Failed to package IPA")?;
+
+    println!("\n[checkmark] IPA packaged successfully!");
+    println!("  Path: {:?}", ipa_path);
+    println!("\nYou can now:");
+    println!("  - Install on device: Use Xcode or ios-deploy");
+    println!(
+        "  - Test on BrowserStack: cargo mobench run --target ios --ios-app {:?}",
+        ipa_path
+    );
+
+    Ok(())
+}
+
+/// Package Android app as an App Bundle (.aab)
+fn cmd_package_aab(variant: &str, output_dir: Option<PathBuf>) -> Result<()> {
+    println!("Packaging Android app as AAB...");
+    println!("  Variant: {}", variant);
+    if let Some(ref dir) = output_dir {
+        println!("  Output: {:?}", dir);
+    }
+
+    let project_root = repo_root()?;
+    let crate_name = detect_bench_mobile_crate_name(&project_root)
+        .unwrap_or_else(|_| "bench-mobile".to_string());
+
+    let mut builder =
+        mobench_sdk::builders::AndroidBuilder::new(&project_root, crate_name).verbose(true);
+    if let Some(ref dir) = output_dir {
+        builder = builder.output_dir(dir);
+    }
+
+    let aab_path = builder
+        .package_aab(variant)
+        .context("Failed to package AAB")?;
+
+    println!("\n[checkmark] AAB packaged successfully!");
+    println!("  Path: {:?}", aab_path);
+    println!("\nYou can now:");
+    println!("  - Upload to Play internal testing");
+    println!(
+        "  - Test on BrowserStack: cargo mobench run --target android --android-aab {:?}",
+        aab_path
+    );
+
+    Ok(())
+}
+
+/// Package XCUITest runner for BrowserStack testing
+fn cmd_package_xcuitest(scheme: &str, output_dir: Option<PathBuf>) -> Result<()> {
+    println!("Packaging XCUITest runner...");
+    println!("  Scheme: {}", scheme);
+    if let Some(ref dir) = output_dir {
+        println!("  Output: {:?}", dir);
+    }
+
+    let project_root = repo_root()?;
+    let crate_name = detect_bench_mobile_crate_name(&project_root)
+        .unwrap_or_else(|_| "bench-mobile".to_string());
+
+    let mut builder = mobench_sdk::builders::IosBuilder::new(&project_root, crate_name).verbose(true);
+    if let Some(ref dir) = output_dir {
+        builder = builder.output_dir(dir);
+    }
+
+    let zip_path = builder
+        .package_xcuitest(scheme)
+        .context("Failed to package XCUITest runner")?;
+
+    println!("\n[checkmark] XCUITest runner packaged successfully!");
+    println!("  Path: {:?}", zip_path);
+    println!("\nYou can now:");
+    println!(
+        "  - Test on BrowserStack: cargo mobench run --target ios --ios-test-suite {:?}",
+        zip_path
+    );
+
+    Ok(())
 }
 
 /// Verify benchmark setup: registry, spec, artifacts, and optional smoke test
+#[allow(clippy::too_many_arguments)]
 fn cmd_verify(
     target: Option<SdkTarget>,
-    spec_path: Option<PathBuf>,
-    check_artifacts: bool,
-    smoke_test: bool,
+    spec_path: Option<PathBuf>,
+    check_artifacts: bool,
+    check_bindings: bool,
+    smoke_test: bool,
+    smoke_all: bool,
+    function: Option<String>,
+    output_dir: Option<PathBuf>,
+) -> Result<()> {
+    println!("Verifying benchmark setup...\n");
+
+    let mut checks_passed = 0;
+    let mut checks_failed = 0;
+    let mut warnings = 0;
+    let mut smoke_outcomes: Vec<SmokeOutcome> = Vec::new();
+
+    // 1. Check benchmark registry
+    print!("  [1/5] Checking benchmark registry... ");
+    let benchmarks = mobench_sdk::discover_benchmarks();
+    if benchmarks.is_empty() {
+        println!("WARNING");
+        println!("        No benchmarks found in registry.");
+        println!("        This may be expected if benchmarks are in a separate crate.");
+        println!("        Tip: Add #[benchmark] attribute to functions and ensure mobench-sdk is linked.");
+        warnings += 1;
+    } else {
+        println!("OK ({} benchmark(s) found)", benchmarks.len());
+        for bench in &benchmarks {
+            println!("        - {}", bench.name);
+        }
+        checks_passed += 1;
+    }
+
+    // 2. Validate spec file if provided
+    print!("  [2/5] Checking spec file... ");
+    if let Some(ref path) = spec_path {
+        match validate_spec_file(path) {
+            Ok(spec) => {
+                println!("OK");
+                println!("        Function: {}", spec.name);
+                println!("        Iterations: {}", spec.iterations);
+                println!("        Warmup: {}", spec.warmup);
+                checks_passed += 1;
+            }
+            Err(e) => {
+                println!("FAILED");
+                println!("        Error: {}", e);
+                checks_failed += 1;
+            }
+        }
+    } else {
+        // Try default locations
+        let project_root = repo_root().unwrap_or_else(|_| PathBuf::from("."));
+        let output_base = output_dir.clone().unwrap_or_else(|| project_root.join("target/mobench"));
+        let default_paths = [
+            output_base.join("android/app/src/main/assets/bench_spec.json"),
+            output_base.join("ios/BenchRunner/BenchRunner/bench_spec.json"),
+            project_root.join("target/mobile-spec/android/bench_spec.json"),
+            project_root.join("target/mobile-spec/ios/bench_spec.json"),
+        ];
+
+        let mut found_any = false;
+        for path in &default_paths {
+            if path.exists() {
+                if !found_any {
+                    println!("OK (found at default locations)");
+                    found_any = true;
+                }
+                match validate_spec_file(path) {
+                    Ok(spec) => {
+                        println!("        {:?}", path);
+                        println!("          Function: {}, Iterations: {}, Warmup: {}",
+                            spec.name, spec.iterations, spec.warmup);
+                    }
+                    Err(e) => {
+                        println!("        {:?} - INVALID: {}", path, e);
+                    }
+                }
+            }
+        }
+        if found_any {
+            checks_passed += 1;
+        } else {
+            println!("SKIPPED (no spec file found, use --spec-path to specify)");
+            warnings += 1;
+        }
+    }
+
+    // 3. Check artifacts if requested
+    print!("  [3/5] Checking build artifacts... ");
+    if check_artifacts {
+        let project_root = repo_root().unwrap_or_else(|_| PathBuf::from("."));
+        let output_base = output_dir.clone().unwrap_or_else(|| project_root.join("target/mobench"));
+
+        let mut artifacts_ok = true;
+        let mut artifact_details = Vec::new();
+
+        if let Some(ref t) = target {
+            match t {
+                SdkTarget::Android | SdkTarget::Both => {
+                    let apk_path = output_base.join("android/app/build/outputs/apk/debug/app-debug.apk");
+                    let apk_release = output_base.join("android/app/build/outputs/apk/release/app-release-unsigned.apk");
+                    if apk_path.exists() {
+                        artifact_details.push(format!("Android APK (debug): {:?}", apk_path));
+                    } else if apk_release.exists() {
+                        artifact_details.push(format!("Android APK (release): {:?}", apk_release));
+                    } else {
+                        artifact_details.push("Android APK: NOT FOUND".to_string());
+                        artifacts_ok = false;
+                    }
+
+                    // Check JNI libs
+                    let jni_base = output_base.join("android/app/src/main/jniLibs");
+                    let abis = ["arm64-v8a", "armeabi-v7a", "x86_64"];
+                    for abi in abis {
+                        let lib_path = jni_base.join(abi).join("libsample_fns.so");
+                        if lib_path.exists() {
+                            artifact_details.push(format!("JNI lib ({}): OK", abi));
+                        }
+                    }
+                }
+                SdkTarget::Ios => {}
+            }
+
+            match t {
+                SdkTarget::Ios | SdkTarget::Both => {
+                    let xcframework = output_base.join("ios/sample_fns.xcframework");
+                    if xcframework.exists() {
+                        artifact_details.push(format!("iOS xcframework: {:?}", xcframework));
+                    } else {
+                        artifact_details.push("iOS xcframework: NOT FOUND".to_string());
+                        artifacts_ok = false;
+                    }
+
+                    let ipa_path = output_base.join("ios/BenchRunner.ipa");
+                    if ipa_path.exists() {
+                        artifact_details.push(format!("iOS IPA: {:?}", ipa_path));
+                    }
+
+                    let xcuitest_path = output_base.join("ios/BenchRunnerUITests.zip");
+                    if xcuitest_path.exists() {
+                        artifact_details.push(format!("XCUITest runner: {:?}", xcuitest_path));
+                    }
+                }
+                SdkTarget::Android => {}
+            }
+        } else {
+            // Check both platforms by default
+            let android_apk = output_base.join("android/app/build/outputs/apk/debug/app-debug.apk");
+            let ios_xcframework = output_base.join("ios/sample_fns.xcframework");
+
+            if android_apk.exists() {
+                artifact_details.push(format!("Android APK: {:?}", android_apk));
+            }
+            if ios_xcframework.exists() {
+                artifact_details.push(format!("iOS xcframework: {:?}", ios_xcframework));
+            }
+
+            if artifact_details.is_empty() {
+                artifacts_ok = false;
+                artifact_details.push("No artifacts found. Run 'cargo mobench build' first.".to_string());
+            }
+        }
+
+        if artifacts_ok {
+            println!("OK");
+            checks_passed += 1;
+        } else {
+            println!("FAILED");
+            checks_failed += 1;
+        }
+        for detail in &artifact_details {
+            println!("        {}", detail);
+        }
+    } else {
+        println!("SKIPPED (use --check-artifacts to enable)");
+    }
+
+    // 4. Check UniFFI bindings if requested
+    print!("  [4/5] Checking UniFFI bindings... ");
+    if check_bindings {
+        let project_root = repo_root().unwrap_or_else(|_| PathBuf::from("."));
+        let output_base = output_dir.clone().unwrap_or_else(|| project_root.join("target/mobench"));
+        match check_uniffi_bindings(&project_root, &output_base, target) {
+            Ok(results) if results.is_empty() => {
+                println!("SKIPPED (no generated bindings found; run 'cargo mobench build' first)");
+                warnings += 1;
+            }
+            Ok(results) => {
+                let stale = results.iter().any(|(_, drift)| !drift.is_empty());
+                if stale {
+                    println!("FAILED");
+                    checks_failed += 1;
+                } else {
+                    println!("OK");
+                    checks_passed += 1;
+                }
+                for (path, drift) in &results {
+                    if drift.is_empty() {
+                        println!("        {}: matches", path.display());
+                    } else {
+                        println!("        {}: out of date (- FFI crate, + bindings)", path.display());
+                        for line in drift {
+                            println!("          {}", line);
+                        }
+                    }
+                }
+                if stale {
+                    println!("        Tip: delete the stale bindings and rerun 'cargo mobench build' to regenerate them");
+                }
+            }
+            Err(e) => {
+                println!("FAILED");
+                println!("        Error: {:#}", e);
+                checks_failed += 1;
+            }
+        }
+    } else {
+        println!("SKIPPED (use --check-bindings to enable)");
+    }
+
+    // 5. Run smoke test if requested
+    print!("  [5/5] Running smoke test... ");
+    if smoke_all {
+        let names: Vec<&str> = benchmarks.iter().map(|bench| bench.name).collect();
+        if names.is_empty() {
+            println!("SKIPPED (no benchmarks registered)");
+            warnings += 1;
+        } else {
+            println!("{} benchmark(s), 1 iteration each", names.len());
+            smoke_outcomes = run_smoke_all(&names);
+            for outcome in &smoke_outcomes {
+                match &outcome.error {
+                    None => println!(
+                        "        PASS {} ({:.3} ms)",
+                        outcome.function,
+                        outcome.duration_ns as f64 / 1_000_000.0
+                    ),
+                    Some(err) => println!("        FAIL {}: {}", outcome.function, err),
+                }
+            }
+            if smoke_outcomes.iter().all(|outcome| outcome.error.is_none()) {
+                checks_passed += 1;
+            } else {
+                checks_failed += 1;
+            }
+        }
+    } else if smoke_test {
+        if let Some(ref func) = function {
+            match run_verify_smoke_test(func) {
+                Ok(report) => {
+                    println!("OK");
+                    let samples = report.samples.len();
+                    let mean_ns = if samples > 0 {
+                        report.samples.iter().map(|s| s.duration_ns).sum::<u64>() / samples as u64
+                    } else {
+                        0
+                    };
+                    println!("        Function: {}", func);
+                    println!("        Samples: {}", samples);
+                    println!("        Mean: {} ns ({:.3} ms)", mean_ns, mean_ns as f64 / 1_000_000.0);
+                    checks_passed += 1;
+                }
+                Err(e) => {
+                    println!("FAILED");
+                    println!("        Error: {}", e);
+                    checks_failed += 1;
+                }
+            }
+        } else if !benchmarks.is_empty() {
+            // Use first discovered benchmark
+            let func = &benchmarks[0].name;
+            match run_verify_smoke_test(func) {
+                Ok(report) => {
+                    println!("OK");
+                    let samples = report.samples.len();
+                    let mean_ns = if samples > 0 {
+                        report.samples.iter().map(|s| s.duration_ns).sum::<u64>() / samples as u64
+                    } else {
+                        0
+                    };
+                    println!("        Function: {} (auto-selected)", func);
+                    println!("        Samples: {}", samples);
+                    println!("        Mean: {} ns ({:.3} ms)", mean_ns, mean_ns as f64 / 1_000_000.0);
+                    checks_passed += 1;
+                }
+                Err(e) => {
+                    println!("FAILED");
+                    println!("        Error: {}", e);
+                    checks_failed += 1;
+                }
+            }
+        } else {
+            println!("SKIPPED (no benchmark function available)");
+            println!("        Tip: Use --function to specify a function, or add benchmarks with #[benchmark]");
+            warnings += 1;
+        }
+    } else {
+        println!("SKIPPED (use --smoke-test to enable)");
+    }
+
+    // Print summary
+    println!("\n----------------------------------------");
+    println!("Verification Summary:");
+    println!("  Passed:   {}", checks_passed);
+    println!("  Failed:   {}", checks_failed);
+    println!("  Warnings: {}", warnings);
+    if !smoke_outcomes.is_empty() {
+        let failed = smoke_outcomes
+            .iter()
+            .filter(|outcome| outcome.error.is_some())
+            .count();
+        println!(
+            "  Benchmarks: {} passed, {} failed",
+            smoke_outcomes.len() - failed,
+            failed
+        );
+    }
+
+    if checks_failed > 0 {
+        println!("\n[X] Verification failed with {} error(s)", checks_failed);
+        bail!("Verification failed");
+    } else if warnings > 0 {
+        println!("\n[!] Verification completed with {} warning(s)", warnings);
+    } else {
+        println!("\n[checkmark] All checks passed!");
+    }
+
+    Ok(())
+}
+
+/// Diff the generated UniFFI bindings for `target` (both platforms when
+/// unset) against the FFI crate's `src/lib.rs`, one entry per bindings file.
+/// Platforms without generated bindings are left out.
+fn check_uniffi_bindings(
+    project_root: &Path,
+    output_base: &Path,
+    target: Option<SdkTarget>,
+) -> Result<Vec<(PathBuf, Vec<String>)>> {
+    let crate_name = detect_bench_mobile_crate_name(project_root)?;
+    let crate_dir = [
+        project_root.join("bench-mobile"),
+        project_root.join("crates").join(&crate_name),
+    ]
+    .into_iter()
+    .find(|dir| dir.join("Cargo.toml").is_file())
+    .ok_or_else(|| anyhow!("FFI crate {crate_name} not found in bench-mobile/ or crates/"))?;
+    let source_path = crate_dir.join("src/lib.rs");
+    let source = fs::read_to_string(&source_path)
+        .with_context(|| format!("reading FFI crate source {:?}", source_path))?;
+    let expected = bindings::rust_shape(&source);
+    if expected.is_empty() {
+        bail!(
+            "{:?} declares none of BenchSpec, BenchSample, BenchReport or run_benchmark",
+            source_path
+        );
+    }
+
+    let module = crate_name.replace('-', "_");
+    type ShapeReader = fn(&str) -> bindings::FfiShape;
+    let mut generated: Vec<(PathBuf, ShapeReader)> = Vec::new();
+    if matches!(target, None | Some(SdkTarget::Android | SdkTarget::Both)) {
+        generated.push((
+            output_base
+                .join("android/app/src/main/java/uniffi")
+                .join(&module)
+                .join(format!("{module}.kt")),
+            bindings::kotlin_shape,
+        ));
+    }
+    if matches!(target, None | Some(SdkTarget::Ios | SdkTarget::Both)) {
+        generated.push((
+            output_base
+                .join("ios/BenchRunner/BenchRunner/Generated")
+                .join(format!("{module}.swift")),
+            bindings::swift_shape,
+        ));
+    }
+
+    let mut results = Vec::new();
+    for (path, shape) in generated {
+        if !path.is_file() {
+            continue;
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading generated bindings {:?}", path))?;
+        results.push((path, bindings::diff(&expected, &shape(&contents))));
+    }
+    Ok(results)
+}
+
+/// Validate a bench_spec.json file
+///
+/// Handles both "name" and "function" field names for compatibility
+/// with different spec file formats.
+fn validate_spec_file(path: &Path) -> Result<mobench_sdk::BenchSpec> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("reading spec file {:?}", path))?;
+
+    // Try parsing directly first (standard BenchSpec format with "name" field)
+    if let Ok(spec) = serde_json::from_str::<mobench_sdk::BenchSpec>(&contents) {
+        // Validate spec fields
+        if spec.name.trim().is_empty() {
+            bail!("spec.name is empty");
+        }
+        if spec.iterations == 0 {
+            bail!("spec.iterations must be > 0");
+        }
+        return Ok(spec);
+    }
+
+    // Fall back to generic Value parsing for "function" field format
+    // (used by persist_mobile_spec and some older formats)
+    let value: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("parsing spec file {:?}", path))?;
+
+    // Extract name from either "name" or "function" field
+    let name = value
+        .get("name")
+        .or_else(|| value.get("function"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("spec must have 'name' or 'function' field"))?
+        .to_string();
+
+    let iterations = value
+        .get("iterations")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(100);
+
+    let warmup = value
+        .get("warmup")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(10);
+
+    // Validate
+    if name.trim().is_empty() {
+        bail!("spec.name/function is empty");
+    }
+    if iterations == 0 {
+        bail!("spec.iterations must be > 0");
+    }
+
+    Ok(mobench_sdk::BenchSpec {
+        name,
+        iterations,
+        warmup,
+        warmup_policy: Default::default(),
+        sampling: Default::default(),
+        per_iteration_timeout: None,
+        timeout: None,
+        capture_warmup: false,
+        seed: None,
+        drop_first_n: 0,
+    })
+}
+
+/// Result of running one benchmark for `verify --smoke-all`
+#[derive(Debug)]
+struct SmokeOutcome {
+    function: String,
+    duration_ns: u64,
+    error: Option<String>,
+}
+
+/// Run each benchmark once with no warmup, catching panics so one broken
+/// function does not hide the results of the rest
+fn run_smoke_all(functions: &[&str]) -> Vec<SmokeOutcome> {
+    functions
+        .iter()
+        .map(|function| {
+            let spec = mobench_sdk::BenchSpec {
+                name: function.to_string(),
+                iterations: 1,
+                warmup: 0,
+                warmup_policy: Default::default(),
+                sampling: Default::default(),
+                per_iteration_timeout: None,
+                timeout: None,
+                capture_warmup: false,
+                seed: None,
+                drop_first_n: 0,
+            };
+            let result = std::panic::catch_unwind(|| mobench_sdk::run_benchmark(spec));
+            let (duration_ns, error) = match result {
+                Ok(Ok(report)) => (
+                    report.samples.iter().map(|s| s.duration_ns).sum(),
+                    None,
+                ),
+                Ok(Err(err)) => (0, Some(err.to_string())),
+                Err(panic) => {
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_string());
+                    (0, Some(format!("panicked: {}", message)))
+                }
+            };
+            SmokeOutcome {
+                function: function.to_string(),
+                duration_ns,
+                error,
+            }
+        })
+        .collect()
+}
+
+/// Pseudo-device name for results measured on this machine.
+fn host_device_name() -> String {
+    format!("host:{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Run every benchmark in `spec` on this machine with the same settings
+/// as the devices, in the entry shape `build_summary` reads.
+fn run_host_benchmarks(spec: &RunSpec) -> Result<Vec<Value>> {
+    let bench_specs: Vec<mobench_sdk::BenchSpec> = spec
+        .all_functions()
+        .into_iter()
+        .map(|function| mobench_sdk::BenchSpec {
+            name: function.to_string(),
+            iterations: spec.iterations_for(function),
+            warmup: spec.warmup_for(function),
+            warmup_policy: spec.warmup_policy.clone().unwrap_or_default(),
+            sampling: spec.sampling.clone().unwrap_or_default(),
+            per_iteration_timeout: spec.per_iteration_timeout_ms.map(Duration::from_millis),
+            timeout: spec.per_benchmark_timeout_secs.map(Duration::from_secs),
+            capture_warmup: spec.capture_warmup,
+            seed: spec.seed,
+            drop_first_n: spec.drop_first_n,
+        })
+        .collect();
+    let reports = with_bench_env(&spec.env, || -> Result<Vec<mobench_sdk::RunnerReport>> {
+        if spec.sample_interleave {
+            mobench_sdk::run_benchmarks_interleaved(bench_specs)
+                .map_err(|e| anyhow!("interleaved host run failed: {}", e))
+        } else {
+            bench_specs
+                .into_iter()
+                .map(|bench_spec| {
+                    let function = bench_spec.name.clone();
+                    mobench_sdk::run_benchmark(bench_spec)
+                        .map_err(|e| anyhow!("host run of {} failed: {}", function, e))
+                })
+                .collect()
+        }
+    })?;
+    reports
+        .into_iter()
+        .map(|report| {
+            let function = report.spec.name.clone();
+            let mut entry = serde_json::to_value(&report).context("serializing host report")?;
+            entry["function"] = json!(function);
+            // Echoed like the device runners do
+            if !spec.env.is_empty() {
+                entry["env"] = json!(spec.env);
+            }
+            Ok(entry)
+        })
+        .collect()
+}
+
+/// Refuse `--include-host` before anything is built when a function is not
+/// linked into this binary.
+///
+/// The host run calls the registered `#[benchmark]` functions in-process, and
+/// the `mobench` binary registers none of a project's benchmarks, so the flag
+/// only works when `mobench::run` is called from a binary that links them.
+fn check_host_benchmarks_linked(spec: &RunSpec) -> Result<()> {
+    let unlinked: Vec<&str> = spec
+        .all_functions()
+        .into_iter()
+        .filter(|function| mobench_sdk::registry::find_benchmark(function).is_none())
+        .collect();
+    if unlinked.is_empty() {
+        return Ok(());
+    }
+    bail!(
+        "--include-host runs benchmarks inside the mobench process, and {} is not linked into it; \
+         call mobench::run() from a binary that depends on your benchmark crate, or drop --include-host",
+        unlinked.join(", ")
+    )
+}
+
+/// Add this machine as a pseudo-device for `--include-host`, so summaries,
+/// baselines and regression checks treat it like any other device.
+///
+/// [`check_host_benchmarks_linked`] has vetted the functions; a host run
+/// that still fails only warns, since the device results are usable.
+fn add_host_results(run_summary: &mut RunSummary) {
+    let host = host_device_name();
+    println!("Running benchmarks on host ({})...", host);
+    match run_host_benchmarks(&run_summary.spec) {
+        Ok(entries) => {
+            run_summary
+                .benchmark_results
+                .get_or_insert_with(BTreeMap::new)
+                .insert(host.clone(), entries);
+            if !run_summary.spec.devices.contains(&host) {
+                run_summary.spec.devices.push(host);
+            }
+        }
+        Err(err) => println!("Warning: skipping host results: {:#}", err),
+    }
+}
+
+/// Run a minimal smoke test for verification
+fn run_verify_smoke_test(function: &str) -> Result<mobench_sdk::RunnerReport> {
+    let spec = mobench_sdk::BenchSpec {
+        name: function.to_string(),
+        iterations: 3, // Minimal iterations for smoke test
+        warmup: 1,
+        warmup_policy: Default::default(),
+        sampling: Default::default(),
+        per_iteration_timeout: None,
+        timeout: None,
+        capture_warmup: false,
+        seed: None,
+        drop_first_n: 0,
+    };
+
+    mobench_sdk::run_benchmark(spec)
+        .map_err(|e| anyhow!("smoke test failed: {}", e))
+}
+
+/// Extra files `summary` writes next to its printed output.
+#[derive(Debug, Clone, Copy, Default)]
+struct SummaryOutputs<'a> {
+    /// `--sample-out`: raw samples as CSV or Parquet.
+    sample_out: Option<&'a Path>,
+    /// `--gbench-json`: Google Benchmark JSON.
+    gbench_json: Option<&'a Path>,
+    /// `--histogram`: bucket count for per-entry sample histograms.
+    histogram_bins: Option<usize>,
+    /// `--platform-version-matrix`: function to pivot by device model and OS version.
+    platform_version_matrix: Option<&'a str>,
+}
+
+impl SummaryOutputs<'_> {
+    /// Write the requested files for `report` and the entries extracted from it.
+    fn write(&self, report: &Value, data: &[SummaryData]) -> Result<()> {
+        if let Some(path) = self.sample_out {
+            sample_out::write_sample_out(path, &sample_out::collect_raw_samples(report))?;
+        }
+        if let Some(path) = self.gbench_json {
+            write_gbench_json(path, data)?;
+        }
+        Ok(())
+    }
+
+    /// Reject text-only extras for another `format`, before anything is written.
+    fn check_format(&self, format: SummaryFormat) -> Result<()> {
+        if self.histogram_bins.is_some() && format != SummaryFormat::Text {
+            bail!("--histogram only applies to text output");
+        }
+        if self.platform_version_matrix.is_some() && format != SummaryFormat::Text {
+            bail!("--platform-version-matrix only applies to text output");
+        }
+        Ok(())
+    }
+
+    /// Print the requested histograms and OS version matrix, after the summary itself.
+    fn print_extras(&self, report: &Value, data: &[SummaryData]) -> Result<()> {
+        if let Some(bins) = self.histogram_bins {
+            print!(
+                "{}",
+                histogram::render(&sample_out::collect_raw_samples(report), bins)
+            );
+        }
+        if let Some(function) = self.platform_version_matrix {
+            // Reports that keep the OS version apart get the usual `<device>-<version>` name
+            let medians = data
+                .iter()
+                .filter(|entry| entry.function.as_deref() == Some(function))
+                .filter_map(|entry| {
+                    let device = entry.device.as_deref()?;
+                    let name = match &entry.os_version {
+                        Some(version) if split_os_version(device).is_none() => format!("{device}-{version}"),
+                        _ => device.to_string(),
+                    };
+                    Some((name, entry.median_ns?))
+                })
+                .collect();
+            println!();
+            print!(
+                "{}",
+                render_platform_version_matrix(function, &medians, None)
+                    .unwrap_or_else(|| no_platform_matrix_note(function))
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Display summary statistics from a benchmark report JSON file
+fn cmd_summary(
+    report_path: &Path,
+    format: Option<SummaryFormat>,
+    outputs: SummaryOutputs<'_>,
+    top: Option<(usize, TopSort)>,
+    baseline: Option<&Path>,
+) -> Result<()> {
+    let format = format.unwrap_or(SummaryFormat::Text);
+    outputs.check_format(format)?;
+    if let Some((_, TopSort::Regression)) = top {
+        bail!("summary --sort-by regression needs a baseline; use `compare --top` instead");
+    }
+    if baseline.is_some() && format != SummaryFormat::Text {
+        bail!("--baseline only applies to text output; use `compare` for machine-readable deltas");
+    }
+    let comparison = baseline
+        .map(|baseline| compare_summaries(baseline, report_path))
+        .transpose()?;
+
+    // Try to load the report in various formats
+    let contents = compress::read_to_string(report_path)
+        .with_context(|| format!("reading report file {:?}", report_path))?;
+
+    let value: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("parsing report file {:?}", report_path))?;
+
+    // Extract summary information
+    let mut summary_data = extract_summary_data(&value)?;
+    outputs.write(&value, &summary_data)?;
+
+    // Budgets are checked against every entry, not just the displayed ones
+    let violations = check_budgets(
+        &load_budgets()?,
+        summary_data.iter().filter_map(|data| {
+            Some((
+                data.device.as_deref()?,
+                data.function.as_deref()?,
+                data.median_ns,
+                data.p95_ns,
+            ))
+        }),
+    );
+    if let Some((n, sort_by)) = top {
+        limit_summary_data(&mut summary_data, n, sort_by, format);
+    }
+    match (format, &comparison) {
+        (SummaryFormat::Text, Some(comparison)) => {
+            print!("{}", render_summary_with_baseline(&summary_data, comparison, top.is_none()))
+        }
+        (SummaryFormat::Text, None) => print_summary_text(&summary_data),
+        (SummaryFormat::Json, _) => print_summary_json(&summary_data)?,
+        (SummaryFormat::Csv, _) => print_summary_csv(&summary_data),
+    }
+    outputs.print_extras(&value, &summary_data)?;
+    fail_on_budget_violations(&violations)
+}
+
+/// `summary --baseline`: the summary as one table, with the median and p95
+/// deltas from [`compare_summaries`] next to each entry. With `list_removed`,
+/// benchmarks only the baseline has are listed last.
+fn render_summary_with_baseline(data: &[SummaryData], comparison: &CompareReport, list_removed: bool) -> String {
+    let mut output = String::new();
+    let delta = term::delta_pct();
+    let _ = writeln!(output, "Benchmark Summary ({delta} vs {})", comparison.baseline.display());
+    let _ = writeln!(output, "=================\n");
+    for warning in &comparison.warnings {
+        let _ = writeln!(output, "Warning: {}", warning);
+    }
+    if !comparison.warnings.is_empty() {
+        let _ = writeln!(output);
+    }
+    let unit = units::current().label();
+    let _ = writeln!(
+        output,
+        "| Device | Function | Samples | Mean ({unit}) | Median ({unit}) | Median {delta} | P95 ({unit}) | P95 {delta} |"
+    );
+    let _ = writeln!(output, "| --- | --- | ---: | ---: | ---: | ---: | ---: | ---: |");
+    let row_for = |device: &str, function: &str| {
+        comparison
+            .rows
+            .iter()
+            .find(|row| row.device == device && row.function == function)
+    };
+    for entry in data {
+        let device = entry.device.as_deref().unwrap_or("-");
+        let function = entry.function.as_deref().unwrap_or("-");
+        let row = row_for(device, function);
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} | {} | {} |",
+            device,
+            function,
+            entry.sample_count,
+            format_ms(entry.mean_ns),
+            format_ms(entry.median_ns),
+            format_delta(row.and_then(|row| row.median_delta_pct)),
+            format_ms(entry.p95_ns),
+            format_delta(row.and_then(|row| row.p95_delta_pct)),
+        );
+    }
+    if list_removed {
+        for row in comparison.rows.iter().filter(|row| row.candidate_median_ns.is_none()) {
+            let _ = writeln!(
+                output,
+                "| {} | {} | - | - | - (base {}) | - | - | - |",
+                row.device,
+                row.function,
+                format_ms(row.baseline_median_ns)
+            );
+        }
+    }
+    output
+}
+
+/// Keep the `n` entries with the largest `key`, largest first, for `--top`.
+/// Entries without a value sort last; ties keep their original order.
+fn top_entries<T>(entries: &mut Vec<T>, n: usize, key: impl Fn(&T) -> Option<f64>) {
+    entries.sort_by(|a, b| match (key(a), key(b)) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    entries.truncate(n);
+}
+
+/// Apply `summary --top` to the extracted entries. The note about the cut
+/// is only printed for text output, so JSON and CSV stay parseable.
+fn limit_summary_data(data: &mut Vec<SummaryData>, n: usize, sort_by: TopSort, format: SummaryFormat) {
+    let total = data.len();
+    top_entries(data, n, |entry| {
+        let ns = match sort_by {
+            TopSort::P95 => entry.p95_ns,
+            TopSort::Median | TopSort::Regression => entry.median_ns,
+        };
+        ns.map(|ns| ns as f64)
+    });
+    if format == SummaryFormat::Text {
+        println!(
+            "Showing top {} of {} entries by {}",
+            data.len(),
+            total,
+            sort_by.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+        );
+    }
+}
+
+/// Write the HTML report for a run summary, then optionally serve it.
+fn cmd_report(summary_path: &Path, output: Option<&Path>, serve: bool, port: u16) -> Result<()> {
+    let run_summary = load_run_summary(summary_path)?;
+    let html = report::render_html(&run_summary.summary);
+
+    let output = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        // summary.json.zst -> summary.html, like the uncompressed summary
+        let plain = if compress::is_compressed(summary_path) {
+            summary_path.with_extension("")
+        } else {
+            summary_path.to_path_buf()
+        };
+        plain.with_extension("html")
+    });
+    write_file(&output, html.as_bytes())?;
+    println!("Wrote HTML report to {:?}", output);
+
+    if serve {
+        let listener = report::bind(port)?;
+        let addr = listener.local_addr()?;
+        println!("Serving report at http://{addr}/ (raw JSON at http://{addr}/summary.json)");
+        println!("Press Ctrl-C to stop.");
+        // The upgraded summary, so the JSON matches what the page shows
+        let json = serde_json::to_string_pretty(&run_summary)?;
+        report::serve(&listener, &html, &json)?;
+    }
+    Ok(())
+}
+
+/// Merge several run summaries and display (and optionally write) the result
+fn cmd_summary_merge(
+    paths: &[PathBuf],
+    output: Option<&Path>,
+    compact_json: bool,
+    format: Option<SummaryFormat>,
+    outputs: SummaryOutputs<'_>,
+    top: Option<(usize, TopSort)>,
+) -> Result<()> {
+    let format = format.unwrap_or(SummaryFormat::Text);
+    outputs.check_format(format)?;
+    if let Some((_, TopSort::Regression)) = top {
+        bail!("summary --sort-by regression needs a baseline; use `compare --top` instead");
+    }
+    let mut runs = Vec::with_capacity(paths.len());
+    for path in paths {
+        runs.push(load_run_summary(path)?);
+    }
+    let mut merged = merge_run_summaries(paths, runs)?;
+    merged.summary.budget_violations =
+        check_budgets(&load_budgets()?, summary_budget_entries(&merged.summary));
+
+    if let Some(output) = output {
+        write_summary(
+            &merged,
+            &resolve_summary_paths(Some(output))?,
+            SummaryFiles {
+                json: true,
+                csv: true,
+                compact_json,
+            },
+        )?;
+    }
+
+    let value = serde_json::to_value(&merged)?;
+    let mut summary_data = extract_summary_data(&value)?;
+    outputs.write(&value, &summary_data)?;
+    if let Some((n, sort_by)) = top {
+        limit_summary_data(&mut summary_data, n, sort_by, format);
+    }
+    match format {
+        SummaryFormat::Text => print_summary_text(&summary_data),
+        SummaryFormat::Json => print_summary_json(&summary_data)?,
+        SummaryFormat::Csv => print_summary_csv(&summary_data),
+    }
+    outputs.print_extras(&value, &summary_data)?;
+    fail_on_budget_violations(&merged.summary.budget_violations)
+}
+
+/// Merge the run summaries loaded from `paths` with [`merge_summary_reports`],
+/// keeping their raw per-device data.
+fn merge_run_summaries(paths: &[PathBuf], runs: Vec<RunSummary>) -> Result<RunSummary> {
+    let reports: Vec<_> = paths
+        .iter()
+        .cloned()
+        .zip(runs.iter())
+        .map(|(path, run)| (path, run.summary.clone(), run.spec.benchmark_defaults.clone()))
+        .collect();
+    let merged_summary = merge_summary_reports(&reports)?;
+
+    // Keep the raw per-device data too, so the merged file works with
+    // `compare` and later `summary` calls like any other run summary. The
+    // first file's spec describes the merged run, as its target does the
+    // merged summary; later files win on device+function conflicts.
+    let mut runs = runs.into_iter();
+    let mut merged = runs.next().context("no summaries to merge")?;
+    for run in runs {
+        let mut results = run.benchmark_results;
+        merge_missing_results(&mut results, merged.benchmark_results.take());
+        merged.benchmark_results = results;
+        let mut metrics = run.performance_metrics;
+        merge_missing(&mut metrics, merged.performance_metrics.take());
+        merged.performance_metrics = metrics;
+        let mut device_meta = run.device_meta;
+        merge_missing(&mut device_meta, merged.device_meta.take());
+        merged.device_meta = device_meta;
+    }
+    merged.summary = merged_summary;
+    Ok(merged)
+}
+
+/// Add device reports from an earlier run for the (device, function) pairs
+/// the merged (later) results lack.
+fn merge_missing_results(
+    target: &mut Option<BTreeMap<String, Vec<Value>>>,
+    earlier: Option<BTreeMap<String, Vec<Value>>>,
+) {
+    let Some(earlier) = earlier else {
+        return;
+    };
+    let target = target.get_or_insert_with(BTreeMap::new);
+    for (device, reports) in earlier {
+        let merged = target.entry(device).or_default();
+        for report in reports {
+            let function = report_function_name(&report);
+            if !merged
+                .iter()
+                .any(|seen| function.is_some() && report_function_name(seen) == function)
+            {
+                merged.push(report);
+            }
+        }
+    }
+}
+
+/// Add entries from an earlier run's map that the merged (later) map lacks.
+fn merge_missing<V>(target: &mut Option<BTreeMap<String, V>>, earlier: Option<BTreeMap<String, V>>) {
+    let Some(earlier) = earlier else {
+        return;
+    };
+    let target = target.get_or_insert_with(BTreeMap::new);
+    for (key, value) in earlier {
+        target.entry(key).or_insert(value);
+    }
+}
+
+/// Summary data extracted from various report formats
+#[derive(Debug, Serialize)]
+struct SummaryData {
+    source_file: String,
     function: Option<String>,
-    output_dir: Option<PathBuf>,
+    device: Option<String>,
+    os_version: Option<String>,
+    sample_count: usize,
+    mean_ns: Option<u64>,
+    median_ns: Option<u64>,
+    min_ns: Option<u64>,
+    max_ns: Option<u64>,
+    p95_ns: Option<u64>,
+    p99_ns: Option<u64>,
+    iterations: Option<u32>,
+    warmup: Option<u32>,
+}
+
+/// Extract summary data from various report formats
+fn extract_summary_data(value: &Value) -> Result<Vec<SummaryData>> {
+    let mut results = Vec::new();
+
+    // Check if this is a RunSummary format (from `mobench run`)
+    if value.get("summary").is_some() {
+        let summary = &value["summary"];
+        let function = summary.get("function").and_then(|f| f.as_str()).map(String::from);
+        let iterations = summary.get("iterations").and_then(|i| i.as_u64()).map(|i| i as u32);
+        let warmup = summary.get("warmup").and_then(|w| w.as_u64()).map(|w| w as u32);
+
+        if let Some(device_summaries) = summary.get("device_summaries").and_then(|d| d.as_array()) {
+            for device_summary in device_summaries {
+                let device = device_summary.get("device").and_then(|d| d.as_str()).map(String::from);
+
+                if let Some(benchmarks) = device_summary.get("benchmarks").and_then(|b| b.as_array()) {
+                    for bench in benchmarks {
+                        let bench_function = bench.get("function").and_then(|f| f.as_str()).map(String::from);
+                        results.push(SummaryData {
+                            source_file: "RunSummary".to_string(),
+                            function: bench_function.or_else(|| function.clone()),
+                            device: device.clone(),
+                            os_version: None, // RunSummary doesn't include OS version directly
+                            sample_count: bench.get("samples").and_then(|s| s.as_u64()).unwrap_or(0) as usize,
+                            mean_ns: bench.get("mean_ns").and_then(|m| m.as_u64()),
+                            median_ns: bench.get("median_ns").and_then(|m| m.as_u64()),
+                            min_ns: bench.get("min_ns").and_then(|m| m.as_u64()),
+                            max_ns: bench.get("max_ns").and_then(|m| m.as_u64()),
+                            p95_ns: bench.get("p95_ns").and_then(|p| p.as_u64()),
+                            p99_ns: bench.get("p99_ns").and_then(|p| p.as_u64()),
+                            iterations,
+                            warmup,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Check if this is a BenchReport format (direct timing output)
+    if let Some(spec) = value.get("spec") {
+        let samples = extract_samples(value);
+        let stats = SampleStats::from_samples(&samples);
+
+        results.push(SummaryData {
+            source_file: "BenchReport".to_string(),
+            function: spec.get("name").and_then(|n| n.as_str()).map(String::from),
+            device: Some("local".to_string()),
+            os_version: None,
+            sample_count: samples.len(),
+            mean_ns: stats.as_ref().map(|s| s.mean_ns),
+            median_ns: stats.as_ref().map(|s| s.median_ns),
+            min_ns: stats.as_ref().map(|s| s.min_ns),
+            max_ns: stats.as_ref().map(|s| s.max_ns),
+            p95_ns: stats.as_ref().map(|s| s.p95_ns),
+            p99_ns: stats.as_ref().map(|s| s.p99_ns),
+            iterations: spec.get("iterations").and_then(|i| i.as_u64()).map(|i| i as u32),
+            warmup: spec.get("warmup").and_then(|w| w.as_u64()).map(|w| w as u32),
+        });
+    }
+
+    // Check if this is benchmark_results format (from BrowserStack fetch)
+    if let Some(benchmark_results) = value.get("benchmark_results").and_then(|b| b.as_object()) {
+        for (device, entries) in benchmark_results {
+            if let Some(entries) = entries.as_array() {
+                for entry in entries {
+                    let samples = extract_samples(entry);
+                    let stats = SampleStats::from_samples(&samples);
+
+                    results.push(SummaryData {
+                        source_file: "BrowserStack".to_string(),
+                        function: entry.get("function").and_then(|f| f.as_str()).map(String::from),
+                        device: Some(device.clone()),
+                        os_version: entry.get("os_version").and_then(|o| o.as_str()).map(String::from),
+                        sample_count: samples.len(),
+                        mean_ns: entry.get("mean_ns").and_then(|m| m.as_u64()).or_else(|| stats.as_ref().map(|s| s.mean_ns)),
+                        median_ns: stats.as_ref().map(|s| s.median_ns),
+                        min_ns: stats.as_ref().map(|s| s.min_ns),
+                        max_ns: stats.as_ref().map(|s| s.max_ns),
+                        p95_ns: stats.as_ref().map(|s| s.p95_ns),
+                        p99_ns: stats.as_ref().map(|s| s.p99_ns),
+                        iterations: None,
+                        warmup: None,
+                    });
+                }
+            }
+        }
+    }
+
+    // Check if this is a session bench-report.json format
+    if value.get("samples").is_some() && value.get("spec").is_none() {
+        // Direct samples array without spec wrapper
+        let samples = extract_samples(value);
+        let stats = SampleStats::from_samples(&samples);
+
+        results.push(SummaryData {
+            source_file: "SessionReport".to_string(),
+            function: value.get("function").and_then(|f| f.as_str()).map(String::from),
+            device: value.get("device").and_then(|d| d.as_str()).map(String::from),
+            os_version: value.get("os_version").and_then(|o| o.as_str()).map(String::from),
+            sample_count: samples.len(),
+            mean_ns: value.get("mean_ns").and_then(|m| m.as_u64()).or_else(|| stats.as_ref().map(|s| s.mean_ns)),
+            median_ns: stats.as_ref().map(|s| s.median_ns),
+            min_ns: stats.as_ref().map(|s| s.min_ns),
+            max_ns: stats.as_ref().map(|s| s.max_ns),
+            p95_ns: stats.as_ref().map(|s| s.p95_ns),
+            p99_ns: stats.as_ref().map(|s| s.p99_ns),
+            iterations: value.get("iterations").and_then(|i| i.as_u64()).map(|i| i as u32),
+            warmup: value.get("warmup").and_then(|w| w.as_u64()).map(|w| w as u32),
+        });
+    }
+
+    if results.is_empty() {
+        bail!("Could not extract summary data from report. Unrecognized format.");
+    }
+
+    Ok(results)
+}
+
+/// Print summary in text format
+fn print_summary_text(data: &[SummaryData]) {
+    println!("Benchmark Summary");
+    println!("=================\n");
+
+    for (idx, entry) in data.iter().enumerate() {
+        if data.len() > 1 {
+            println!("--- Entry {} ---", idx + 1);
+        }
+
+        if let Some(ref func) = entry.function {
+            println!("Function:     {}", func);
+        }
+        if let Some(ref device) = entry.device {
+            println!("Device:       {}", device);
+        }
+        if let Some(ref os) = entry.os_version {
+            println!("OS Version:   {}", os);
+        }
+        println!("Sample Count: {}", entry.sample_count);
+        println!();
+
+        println!("Statistics (nanoseconds):");
+        let show = |value: Option<u64>| match (value, units::current()) {
+            (Some(ns), units::TimeUnit::Auto) => format!("{} ({:.3} ms)", ns, ns as f64 / 1_000_000.0),
+            (Some(ns), unit) => format!("{} ({})", ns, unit.with_suffix(ns)),
+            (None, _) => "-".to_string(),
+        };
+        println!("  Mean:   {}", show(entry.mean_ns));
+        println!("  Median: {}", show(entry.median_ns));
+        println!("  Min:    {}", show(entry.min_ns));
+        println!("  Max:    {}", show(entry.max_ns));
+        println!("  P95:    {}", show(entry.p95_ns));
+        println!("  P99:    {}", show(entry.p99_ns));
+
+        if entry.iterations.is_some() || entry.warmup.is_some() {
+            println!();
+            println!("Configuration:");
+            if let Some(iter) = entry.iterations {
+                println!("  Iterations: {}", iter);
+            }
+            if let Some(warm) = entry.warmup {
+                println!("  Warmup:     {}", warm);
+            }
+        }
+
+        if idx < data.len() - 1 {
+            println!();
+        }
+    }
+}
+
+/// Print summary in JSON format
+fn print_summary_json(data: &[SummaryData]) -> Result<()> {
+    let json = serde_json::to_string_pretty(data)?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Print summary in CSV format
+fn print_summary_csv(data: &[SummaryData]) {
+    println!("function,device,os_version,sample_count,mean_ns,median_ns,min_ns,max_ns,p95_ns,p99_ns,iterations,warmup");
+    for entry in data {
+        println!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            entry.function.as_deref().unwrap_or(""),
+            entry.device.as_deref().unwrap_or(""),
+            entry.os_version.as_deref().unwrap_or(""),
+            entry.sample_count,
+            entry.mean_ns.map(|v| v.to_string()).unwrap_or_default(),
+            entry.median_ns.map(|v| v.to_string()).unwrap_or_default(),
+            entry.min_ns.map(|v| v.to_string()).unwrap_or_default(),
+            entry.max_ns.map(|v| v.to_string()).unwrap_or_default(),
+            entry.p95_ns.map(|v| v.to_string()).unwrap_or_default(),
+            entry.p99_ns.map(|v| v.to_string()).unwrap_or_default(),
+            entry.iterations.map(|v| v.to_string()).unwrap_or_default(),
+            entry.warmup.map(|v| v.to_string()).unwrap_or_default(),
+        );
+    }
+}
+
+/// Resolves the device names of a matrix file, restricted to the given tags.
+fn resolve_matrix_device_specs(matrix_path: &Path, tags: &[String]) -> Result<Vec<String>> {
+    let matrix = load_device_matrix(matrix_path)?;
+    let names = filter_devices_by_tags(matrix.devices, tags)?;
+    if names.is_empty() {
+        bail!("device matrix {:?} does not contain any devices", matrix_path);
+    }
+    Ok(names)
+}
+
+/// Cache BrowserStack device lists under `target/mobench/.cache` for
+/// `[browserstack] device_cache_ttl_secs` (default: one hour; 0 disables the
+/// cache). `refresh` fetches fresh lists and re-caches them.
+fn with_device_cache(client: BrowserStackClient, refresh: bool) -> Result<BrowserStackClient> {
+    let ttl = config::ConfigResolver::new()
+        .context("loading mobench.toml")?
+        .browserstack()
+        .device_cache_ttl_secs
+        .map(Duration::from_secs)
+        .unwrap_or(browserstack::DEFAULT_DEVICE_CACHE_TTL);
+    if ttl.is_zero() {
+        return Ok(client);
+    }
+    Ok(client.with_device_cache(repo_root()?.join("target/mobench/.cache"), ttl, refresh))
+}
+
+/// List available BrowserStack devices and optionally validate device specs.
+///
+/// When a device matrix is given, its (tag-filtered) devices are validated
+/// instead, so CI can fail fast on devices BrowserStack cannot provide.
+fn cmd_devices(
+    platform: Option<DevicePlatform>,
+    output_json: bool,
+    validate: Vec<String>,
+    matrix: Option<&Path>,
+    tags: &[String],
+    refresh_devices: bool,
 ) -> Result<()> {
-    println!("Verifying benchmark setup...\n");
+    let from_matrix = matrix.is_some();
+    let validate = match matrix {
+        Some(path) => {
+            let mut specs = resolve_matrix_device_specs(path, tags)?;
+            specs.extend(validate);
+            specs
+        }
+        None => validate,
+    };
+
+    // Try to get credentials, but provide helpful error if missing
+    let creds = match resolve_browserstack_credentials(None) {
+        Ok(creds) => creds,
+        Err(_) => {
+            // Check what's missing and provide helpful guidance
+            let username = env::var("BROWSERSTACK_USERNAME").ok();
+            let access_key = env::var("BROWSERSTACK_ACCESS_KEY").ok();
+
+            let missing_username = username.is_none() || username.as_deref() == Some("");
+            let missing_access_key = access_key.is_none() || access_key.as_deref() == Some("");
+
+            let error_msg = browserstack::format_credentials_error(missing_username, missing_access_key);
+            bail!(MobenchError::AuthFailed(error_msg));
+        }
+    };
+
+    let client = BrowserStackClient::new(
+        BrowserStackAuth {
+            username: creds.username,
+            access_key: creds.access_key,
+        },
+        creds.project,
+    )?;
+    let client = with_device_cache(client, refresh_devices)?;
+
+    // If validating devices, do that and exit
+    if !validate.is_empty() {
+        let platform_str = platform.map(|p| match p {
+            DevicePlatform::Android => "android",
+            DevicePlatform::Ios => "ios",
+        });
+
+        let validation = client.validate_devices(&validate, platform_str)?;
+
+        let (ok_label, error_label) = if from_matrix {
+            ("AVAILABLE", "UNAVAILABLE")
+        } else {
+            ("OK", "ERROR")
+        };
+
+        if output_json {
+            let output = json!({
+                "valid": validation.valid,
+                "invalid": validation.invalid.iter().map(|e| {
+                    json!({
+                        "spec": e.spec,
+                        "reason": e.reason,
+                        "suggestions": e.suggestions
+                    })
+                }).collect::<Vec<_>>()
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            if !validation.valid.is_empty() {
+                println!("Valid devices ({}):", validation.valid.len());
+                for device in &validation.valid {
+                    println!("  [{}] {}", ok_label, device);
+                }
+            }
+
+            if !validation.invalid.is_empty() {
+                if !validation.valid.is_empty() {
+                    println!();
+                }
+                println!("Invalid devices ({}):", validation.invalid.len());
+                for error in &validation.invalid {
+                    println!("  [{}] {}: {}", error_label, error.spec, error.reason);
+                    if !error.suggestions.is_empty() {
+                        println!("          Suggestions:");
+                        for suggestion in &error.suggestions {
+                            println!("            - {}", suggestion);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Exit with error if any devices were invalid
+        if !validation.invalid.is_empty() {
+            if from_matrix {
+                bail!(MobenchError::DeviceInvalid(format!(
+                    "{} of {} matrix devices are unavailable on BrowserStack",
+                    validation.invalid.len(),
+                    validate.len()
+                )));
+            }
+            bail!(MobenchError::DeviceInvalid(format!(
+                "{} of {} device specs are invalid",
+                validation.invalid.len(),
+                validate.len()
+            )));
+        }
+
+        return Ok(());
+    }
+
+    // List devices
+    println!("Fetching available BrowserStack devices...\n");
+
+    let devices = match platform {
+        Some(DevicePlatform::Android) => client.list_espresso_devices()?,
+        Some(DevicePlatform::Ios) => client.list_xcuitest_devices()?,
+        None => client.list_all_devices()?,
+    };
+
+    if devices.is_empty() {
+        println!("No devices found.");
+        return Ok(());
+    }
+
+    if output_json {
+        println!("{}", serde_json::to_string_pretty(&devices)?);
+        return Ok(());
+    }
+
+    // Group devices by OS
+    let mut android_devices: Vec<_> = devices.iter().filter(|d| d.os == "android").collect();
+    let mut ios_devices: Vec<_> = devices.iter().filter(|d| d.os == "ios").collect();
+
+    // Sort by device name, then OS version (descending)
+    android_devices.sort_by(|a, b| {
+        a.device.cmp(&b.device).then_with(|| {
+            // Try to compare versions numerically
+            let av: f64 = a.os_version.parse().unwrap_or(0.0);
+            let bv: f64 = b.os_version.parse().unwrap_or(0.0);
+            bv.partial_cmp(&av).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+    ios_devices.sort_by(|a, b| {
+        a.device.cmp(&b.device).then_with(|| {
+            let av: f64 = a.os_version.parse().unwrap_or(0.0);
+            let bv: f64 = b.os_version.parse().unwrap_or(0.0);
+            bv.partial_cmp(&av).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+
+    if !android_devices.is_empty() {
+        println!("Android Devices ({}):", android_devices.len());
+        println!("{:-<60}", "");
+        for device in &android_devices {
+            println!("  {:40} OS {}", device.device, device.os_version);
+            println!("    --devices \"{}\"", device.identifier());
+        }
+        println!();
+    }
+
+    if !ios_devices.is_empty() {
+        println!("iOS Devices ({}):", ios_devices.len());
+        println!("{:-<60}", "");
+        for device in &ios_devices {
+            println!("  {:40} iOS {}", device.device, device.os_version);
+            println!("    --devices \"{}\"", device.identifier());
+        }
+        println!();
+    }
+
+    println!("Total: {} devices available", devices.len());
+    println!("\nUsage:");
+    println!("  cargo mobench run --target android --devices \"Google Pixel 7-13.0\" ...");
+    println!("  cargo mobench run --target ios --devices \"iPhone 14-16\" ...");
+
+    Ok(())
+}
+
+/// Check prerequisites for building mobile artifacts.
+///
+/// This validates that all required tools and configurations are in place
+/// before attempting a build.
+fn cmd_check(target: SdkTarget, format: CheckOutputFormat, fix: bool, yes: bool) -> Result<()> {
+    match target {
+        SdkTarget::Android => println!("Checking prerequisites for Android...\n"),
+        SdkTarget::Ios => println!("Checking prerequisites for iOS...\n"),
+        SdkTarget::Both => println!("Checking prerequisites for Android and iOS...\n"),
+    }
+
+    let mut checks = collect_prereq_checks(target);
+
+    if fix {
+        let fixed = apply_prereq_fixes(&checks, yes)?;
+        if !fixed.is_empty() {
+            // Re-run everything so the report reflects the post-fix state
+            checks = collect_prereq_checks(target);
+            eprintln!();
+            for name in &fixed {
+                let still_failing = checks.iter().any(|c| &c.name == name && !c.passed);
+                if still_failing {
+                    eprintln!("[WARN] Fix ran but check still fails: {}", name);
+                } else {
+                    eprintln!("[FIXED] {}", name);
+                }
+            }
+            eprintln!();
+        }
+    }
+
+    let mut issues: Vec<String> = Vec::new();
+    for check in &checks {
+        if !check.passed {
+            if let Some(ref fix) = check.fix_hint {
+                issues.push(fix.clone());
+            }
+        }
+    }
+
+    match format {
+        CheckOutputFormat::Text => print_check_results_text(&checks, &issues),
+        CheckOutputFormat::Json => print_check_results_json(&checks)?,
+    }
+
+    // Missing optional tools are reported but do not fail the check
+    let (errors, _) = count_check_failures(&checks);
+    if errors == 0 {
+        Ok(())
+    } else if !fix
+        && checks
+            .iter()
+            .any(|c| !c.passed && c.severity == CheckSeverity::Error && c.fix_command.is_some())
+    {
+        bail!(
+            "{} issue(s) found. Fix them or run 'cargo mobench check --target {:?} --fix', then check again.",
+            errors,
+            target
+        )
+    } else {
+        bail!("{} issue(s) found. Fix them and run 'cargo mobench check --target {:?}' again.", errors, target)
+    }
+}
+
+/// Run every prerequisite check relevant to `target`.
+fn collect_prereq_checks(target: SdkTarget) -> Vec<PrereqCheck> {
+    let mut checks: Vec<PrereqCheck> = Vec::new();
+
+    // Common checks for both platforms
+    checks.push(check_cargo());
+    checks.push(check_rustup());
+
+    match target {
+        SdkTarget::Android => {
+            checks.push(check_android_ndk_home());
+            checks.push(check_cargo_ndk());
+            checks.push(check_rust_target("aarch64-linux-android"));
+            checks.push(check_rust_target("armv7-linux-androideabi"));
+            checks.push(check_rust_target("x86_64-linux-android"));
+            checks.push(check_jdk());
+        }
+        SdkTarget::Ios => {
+            checks.push(check_xcode());
+            checks.push(check_xcodegen());
+            checks.push(check_rust_target("aarch64-apple-ios"));
+            checks.push(check_rust_target("aarch64-apple-ios-sim"));
+        }
+        SdkTarget::Both => {
+            // Android
+            checks.push(check_android_ndk_home());
+            checks.push(check_cargo_ndk());
+            checks.push(check_rust_target("aarch64-linux-android"));
+            checks.push(check_rust_target("armv7-linux-androideabi"));
+            checks.push(check_rust_target("x86_64-linux-android"));
+            checks.push(check_jdk());
+            // iOS
+            checks.push(check_xcode());
+            checks.push(check_xcodegen());
+            checks.push(check_rust_target("aarch64-apple-ios"));
+            checks.push(check_rust_target("aarch64-apple-ios-sim"));
+        }
+    }
+
+    checks
+}
+
+/// Run the fix command of every failing check that has one.
+///
+/// Each command is confirmed interactively unless `yes` is set; without a
+/// terminal and without `yes`, fixes are skipped. Checks without a fix command
+/// (Xcode, NDK, JDK, ...) only get their guidance printed. Returns the names of
+/// checks whose fix command ran successfully.
+fn apply_prereq_fixes(checks: &[PrereqCheck], yes: bool) -> Result<Vec<String>> {
+    use std::io::{BufRead, IsTerminal};
+
+    let mut fixed = Vec::new();
+    for check in checks.iter().filter(|c| !c.passed) {
+        let Some(command) = check.fix_command.as_ref().filter(|c| !c.is_empty()) else {
+            if let Some(hint) = &check.fix_hint {
+                eprintln!("[MANUAL] {}: {}", check.name, hint);
+            }
+            continue;
+        };
+        let display = command.join(" ");
+
+        if !yes {
+            if !std::io::stdin().is_terminal() {
+                eprintln!("[SKIP] {}: not a terminal, re-run with --yes to run `{}`", check.name, display);
+                continue;
+            }
+            eprint!("Run `{}` to fix \"{}\"? [y/N] ", display, check.name);
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer)?;
+            if !matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+                eprintln!("[SKIP] {}", check.name);
+                continue;
+            }
+        }
+
+        eprintln!("Running: {}", display);
+        let status = std::process::Command::new(&command[0])
+            .args(&command[1..])
+            .status()
+            .with_context(|| format!("failed to run `{}`", display))?;
+        if status.success() {
+            fixed.push(check.name.clone());
+        } else {
+            eprintln!("[ERROR] `{}` exited with {}", display, status);
+        }
+    }
+
+    Ok(fixed)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PrereqCheck {
+    name: String,
+    passed: bool,
+    detail: Option<String>,
+    fix_hint: Option<String>,
+    /// Command that installs the missing prerequisite, for checks that are
+    /// safe to fix automatically with `check --fix`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix_command: Option<Vec<String>>,
+    /// How much the outcome matters: `info` for passed checks, `error` for a
+    /// missing requirement, `warning` for a missing optional tool.
+    severity: CheckSeverity,
+    /// Where to read about installing or configuring the prerequisite.
+    docs_url: Option<String>,
+}
+
+/// Severity of a prerequisite check result in `check --format json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Counts of failed checks by severity: `(errors, warnings)`.
+fn count_check_failures(checks: &[PrereqCheck]) -> (usize, usize) {
+    let failed = |severity| {
+        checks
+            .iter()
+            .filter(|c| !c.passed && c.severity == severity)
+            .count()
+    };
+    (failed(CheckSeverity::Error), failed(CheckSeverity::Warning))
+}
+
+fn print_check_results_text(checks: &[PrereqCheck], issues: &[String]) {
+    for check in checks {
+        let status = match (check.passed, check.severity) {
+            (true, _) => term::ok(),
+            (false, CheckSeverity::Error) => term::error(),
+            (false, _) => term::warning(),
+        };
+        let detail = check.detail.as_deref().unwrap_or("");
+        if detail.is_empty() {
+            println!("{} {}", status, check.name);
+        } else {
+            println!("{} {} ({})", status, check.name, detail);
+        }
+    }
+
+    if !issues.is_empty() {
+        println!("\nTo fix:");
+        for issue in issues {
+            println!("  * {}", issue);
+        }
+        println!();
+        let (errors, warnings) = count_check_failures(checks);
+        if errors > 0 {
+            println!("{} issue(s) found, {} optional.", errors + warnings, warnings);
+        } else {
+            println!("{} optional tool(s) missing; required prerequisites are satisfied.", warnings);
+        }
+    } else {
+        println!("\nAll prerequisites satisfied!");
+    }
+}
+
+fn print_check_results_json(checks: &[PrereqCheck]) -> Result<()> {
+    let (errors, warnings) = count_check_failures(checks);
+    let status = match (errors, warnings) {
+        (0, 0) => "ok",
+        (0, _) => "warnings",
+        _ => "errors",
+    };
+    let output = json!({
+        "checks": checks,
+        "status": status,
+        "all_passed": checks.iter().all(|c| c.passed),
+        "passed_count": checks.iter().filter(|c| c.passed).count(),
+        "failed_count": checks.iter().filter(|c| !c.passed).count(),
+        "error_count": errors,
+        "warning_count": warnings,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn check_cargo() -> PrereqCheck {
+    let result = std::process::Command::new("cargo")
+        .arg("--version")
+        .output();
+
+    match result {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .to_string();
+            PrereqCheck {
+                name: "cargo installed".to_string(),
+                passed: true,
+                detail: Some(version),
+                fix_hint: None,
+                fix_command: None,
+                severity: CheckSeverity::Info,
+                docs_url: Some("https://rustup.rs".to_string()),
+            }
+        }
+        _ => PrereqCheck {
+            name: "cargo installed".to_string(),
+            passed: false,
+            detail: None,
+            fix_hint: Some("Install Rust: https://rustup.rs".to_string()),
+            fix_command: None,
+            severity: CheckSeverity::Error,
+            docs_url: Some("https://rustup.rs".to_string()),
+        },
+    }
+}
+
+fn check_rustup() -> PrereqCheck {
+    let result = std::process::Command::new("rustup")
+        .arg("--version")
+        .output();
+
+    match result {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_string();
+            PrereqCheck {
+                name: "rustup installed".to_string(),
+                passed: true,
+                detail: Some(version),
+                fix_hint: None,
+                fix_command: None,
+                severity: CheckSeverity::Info,
+                docs_url: Some("https://rustup.rs".to_string()),
+            }
+        }
+        _ => PrereqCheck {
+            name: "rustup installed".to_string(),
+            passed: false,
+            detail: None,
+            fix_hint: Some("Install rustup: https://rustup.rs".to_string()),
+            fix_command: None,
+            severity: CheckSeverity::Error,
+            docs_url: Some("https://rustup.rs".to_string()),
+        },
+    }
+}
+
+fn check_android_ndk_home() -> PrereqCheck {
+    match env::var("ANDROID_NDK_HOME") {
+        Ok(path) if !path.is_empty() => {
+            let path_exists = Path::new(&path).exists();
+            if path_exists {
+                PrereqCheck {
+                    name: "ANDROID_NDK_HOME set".to_string(),
+                    passed: true,
+                    detail: Some(path),
+                    fix_hint: None,
+                    fix_command: None,
+                    severity: CheckSeverity::Info,
+                    docs_url: Some("https://developer.android.com/ndk/downloads".to_string()),
+                }
+            } else {
+                PrereqCheck {
+                    name: "ANDROID_NDK_HOME set".to_string(),
+                    passed: false,
+                    detail: Some(format!("path does not exist: {}", path)),
+                    fix_hint: Some("Set ANDROID_NDK_HOME to a valid NDK path: export ANDROID_NDK_HOME=$ANDROID_SDK_ROOT/ndk/<version>".to_string()),
+                    fix_command: None,
+                    severity: CheckSeverity::Error,
+                    docs_url: Some("https://developer.android.com/ndk/downloads".to_string()),
+                }
+            }
+        }
+        _ => PrereqCheck {
+            name: "ANDROID_NDK_HOME set".to_string(),
+            passed: false,
+            detail: None,
+            fix_hint: Some("Set ANDROID_NDK_HOME: export ANDROID_NDK_HOME=$ANDROID_SDK_ROOT/ndk/<version>".to_string()),
+            fix_command: None,
+            severity: CheckSeverity::Error,
+            docs_url: Some("https://developer.android.com/ndk/downloads".to_string()),
+        },
+    }
+}
 
-    let mut checks_passed = 0;
-    let mut checks_failed = 0;
-    let mut warnings = 0;
+fn check_cargo_ndk() -> PrereqCheck {
+    let result = std::process::Command::new("cargo")
+        .args(["ndk", "--version"])
+        .output();
 
-    // 1. Check benchmark registry
-    print!("  [1/4] Checking benchmark registry... ");
-    let benchmarks = mobench_sdk::discover_benchmarks();
-    if benchmarks.is_empty() {
-        println!("WARNING");
-        println!("        No benchmarks found in registry.");
-        println!("        This may be expected if benchmarks are in a separate crate.");
-        println!("        Tip: Add #[benchmark] attribute to functions and ensure mobench-sdk is linked.");
-        warnings += 1;
-    } else {
-        println!("OK ({} benchmark(s) found)", benchmarks.len());
-        for bench in &benchmarks {
-            println!("        - {}", bench.name);
+    match result {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .to_string();
+            PrereqCheck {
+                name: "cargo-ndk installed".to_string(),
+                passed: true,
+                detail: Some(version),
+                fix_hint: None,
+                fix_command: None,
+                severity: CheckSeverity::Info,
+                docs_url: Some("https://github.com/bbqsrc/cargo-ndk".to_string()),
+            }
         }
-        checks_passed += 1;
+        _ => PrereqCheck {
+            name: "cargo-ndk installed".to_string(),
+            passed: false,
+            detail: None,
+            fix_hint: Some("Install cargo-ndk: cargo install cargo-ndk".to_string()),
+            fix_command: Some(vec!["cargo".into(), "install".into(), "cargo-ndk".into()]),
+            severity: CheckSeverity::Error,
+            docs_url: Some("https://github.com/bbqsrc/cargo-ndk".to_string()),
+        },
     }
+}
 
-    // 2. Validate spec file if provided
-    print!("  [2/4] Checking spec file... ");
-    if let Some(ref path) = spec_path {
-        match validate_spec_file(path) {
-            Ok(spec) => {
-                println!("OK");
-                println!("        Function: {}", spec.name);
-                println!("        Iterations: {}", spec.iterations);
-                println!("        Warmup: {}", spec.warmup);
-                checks_passed += 1;
-            }
-            Err(e) => {
-                println!("FAILED");
-                println!("        Error: {}", e);
-                checks_failed += 1;
+fn check_rust_target(target: &str) -> PrereqCheck {
+    let result = std::process::Command::new("rustup")
+        .args(["target", "list", "--installed"])
+        .output();
+
+    match result {
+        Ok(output) if output.status.success() => {
+            let installed = String::from_utf8_lossy(&output.stdout);
+            let has_target = installed.lines().any(|line| line.trim() == target);
+            if has_target {
+                PrereqCheck {
+                    name: format!("Rust target: {}", target),
+                    passed: true,
+                    detail: None,
+                    fix_hint: None,
+                    fix_command: None,
+                    severity: CheckSeverity::Info,
+                    docs_url: Some("https://rust-lang.github.io/rustup/cross-compilation.html".to_string()),
+                }
+            } else {
+                PrereqCheck {
+                    name: format!("Rust target: {}", target),
+                    passed: false,
+                    detail: Some("not installed".to_string()),
+                    fix_hint: Some(format!("Install target: rustup target add {}", target)),
+                    fix_command: Some(rustup_target_add(target)),
+                    severity: CheckSeverity::Error,
+                    docs_url: Some("https://rust-lang.github.io/rustup/cross-compilation.html".to_string()),
+                }
             }
         }
-    } else {
-        // Try default locations
-        let project_root = repo_root().unwrap_or_else(|_| PathBuf::from("."));
-        let output_base = output_dir.clone().unwrap_or_else(|| project_root.join("target/mobench"));
-        let default_paths = [
-            output_base.join("android/app/src/main/assets/bench_spec.json"),
-            output_base.join("ios/BenchRunner/BenchRunner/bench_spec.json"),
-            project_root.join("target/mobile-spec/android/bench_spec.json"),
-            project_root.join("target/mobile-spec/ios/bench_spec.json"),
-        ];
+        _ => PrereqCheck {
+            name: format!("Rust target: {}", target),
+            passed: false,
+            detail: Some("could not check".to_string()),
+            fix_hint: Some(format!("Install target: rustup target add {}", target)),
+            fix_command: Some(rustup_target_add(target)),
+            severity: CheckSeverity::Error,
+            docs_url: Some("https://rust-lang.github.io/rustup/cross-compilation.html".to_string()),
+        },
+    }
+}
 
-        let mut found_any = false;
-        for path in &default_paths {
-            if path.exists() {
-                if !found_any {
-                    println!("OK (found at default locations)");
-                    found_any = true;
+fn rustup_target_add(target: &str) -> Vec<String> {
+    vec!["rustup".into(), "target".into(), "add".into(), target.into()]
+}
+
+fn check_jdk() -> PrereqCheck {
+    // Try java -version
+    let result = std::process::Command::new("java")
+        .arg("-version")
+        .output();
+
+    match result {
+        Ok(output) => {
+            // Java outputs version to stderr
+            let version_output = String::from_utf8_lossy(&output.stderr);
+            let version_line = version_output.lines().next().unwrap_or("");
+
+            if output.status.success() || !version_line.is_empty() {
+                PrereqCheck {
+                    name: "JDK installed".to_string(),
+                    passed: true,
+                    detail: Some(version_line.trim().to_string()),
+                    fix_hint: None,
+                    fix_command: None,
+                    severity: CheckSeverity::Info,
+                    docs_url: Some("https://adoptium.net/".to_string()),
                 }
-                match validate_spec_file(path) {
-                    Ok(spec) => {
-                        println!("        {:?}", path);
-                        println!("          Function: {}, Iterations: {}, Warmup: {}",
-                            spec.name, spec.iterations, spec.warmup);
-                    }
-                    Err(e) => {
-                        println!("        {:?} - INVALID: {}", path, e);
-                    }
+            } else {
+                PrereqCheck {
+                    name: "JDK installed".to_string(),
+                    passed: false,
+                    detail: None,
+                    fix_hint: Some("Install JDK 17+: brew install openjdk@17".to_string()),
+                    fix_command: None,
+                    severity: CheckSeverity::Error,
+                    docs_url: Some("https://adoptium.net/".to_string()),
                 }
             }
         }
-        if found_any {
-            checks_passed += 1;
-        } else {
-            println!("SKIPPED (no spec file found, use --spec-path to specify)");
-            warnings += 1;
+        Err(_) => PrereqCheck {
+            name: "JDK installed".to_string(),
+            passed: false,
+            detail: None,
+            fix_hint: Some("Install JDK 17+: brew install openjdk@17".to_string()),
+            fix_command: None,
+            severity: CheckSeverity::Error,
+            docs_url: Some("https://adoptium.net/".to_string()),
+        },
+    }
+}
+
+fn check_xcode() -> PrereqCheck {
+    let result = std::process::Command::new("xcodebuild")
+        .arg("-version")
+        .output();
+
+    match result {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_string();
+            PrereqCheck {
+                name: "Xcode installed".to_string(),
+                passed: true,
+                detail: Some(version),
+                fix_hint: None,
+                fix_command: None,
+                severity: CheckSeverity::Info,
+                docs_url: Some("https://developer.apple.com/xcode/".to_string()),
+            }
+        }
+        _ => PrereqCheck {
+            name: "Xcode installed".to_string(),
+            passed: false,
+            detail: None,
+            fix_hint: Some("Install Xcode from the App Store or run: xcode-select --install".to_string()),
+            fix_command: None,
+            severity: CheckSeverity::Error,
+            docs_url: Some("https://developer.apple.com/xcode/".to_string()),
+        },
+    }
+}
+
+fn check_xcodegen() -> PrereqCheck {
+    let result = std::process::Command::new("xcodegen")
+        .arg("--version")
+        .output();
+
+    match result {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .to_string();
+            PrereqCheck {
+                name: "xcodegen installed".to_string(),
+                passed: true,
+                detail: Some(version),
+                fix_hint: None,
+                fix_command: None,
+                severity: CheckSeverity::Info,
+                docs_url: Some("https://github.com/yonaskolb/XcodeGen".to_string()),
+            }
+        }
+        _ => PrereqCheck {
+            name: "xcodegen installed".to_string(),
+            passed: false,
+            detail: None,
+            fix_hint: Some("Install xcodegen: brew install xcodegen".to_string()),
+            fix_command: None,
+            severity: CheckSeverity::Warning,
+            docs_url: Some("https://github.com/yonaskolb/XcodeGen".to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal Android spec for `function`; tests set what they need with
+    /// struct update syntax.
+    fn test_run_spec(function: &str) -> RunSpec {
+        RunSpec {
+            target: MobileTarget::Android,
+            function: function.into(),
+            iterations: 3,
+            warmup: 0,
+            devices: Vec::new(),
+            browserstack: None,
+            ios_xcuitest: None,
+            device_groups: Vec::new(),
+            sampling: None,
+            warmup_policy: None,
+            functions: Vec::new(),
+            seed: None,
+            per_benchmark_timeout_secs: None,
+            per_iteration_timeout_ms: None,
+            capture_warmup: false,
+            startup_metrics: false,
+            drop_first_n: 0,
+            pin_cores: CorePinning::None,
+            sample_interleave: false,
+            order: ExecutionOrder::Declared,
+            env: BTreeMap::new(),
+            benchmark_defaults: BTreeMap::new(),
         }
     }
 
-    // 3. Check artifacts if requested
-    print!("  [3/4] Checking build artifacts... ");
-    if check_artifacts {
-        let project_root = repo_root().unwrap_or_else(|_| PathBuf::from("."));
-        let output_base = output_dir.clone().unwrap_or_else(|| project_root.join("target/mobench"));
+    // Register a lightweight benchmark for tests so the inventory contains at least one entry.
+    #[mobench_sdk::benchmark]
+    fn noop_benchmark() {
+        std::hint::black_box(1u8);
+    }
+
+    #[test]
+    fn resolves_cli_spec() {
+        let spec = resolve_run_spec(
+            MobileTarget::Android,
+            "sample_fns::fibonacci".into(),
+            5,
+            1,
+            vec!["pixel".into()],
+            None,
+            None,
+            None,
+            false,
+            false, // release
+            None,  // cargo_profile
+            Path::new("target/mobench"),
+        )
+        .unwrap();
+        assert_eq!(spec.function, "sample_fns::fibonacci");
+        assert_eq!(spec.iterations, 5);
+        assert_eq!(spec.warmup, 1);
+        assert_eq!(spec.devices, vec!["pixel".to_string()]);
+        assert!(spec.browserstack.is_none());
+        assert!(spec.ios_xcuitest.is_none());
+    }
+
+    #[test]
+    fn local_smoke_produces_samples() {
+        let spec = RunSpec {
+            warmup: 1,
+            ..test_run_spec("noop_benchmark")
+        };
+        let report = run_local_smoke(&spec).expect("local harness");
+        assert!(report["samples"].is_array());
+        assert_eq!(report["spec"]["name"], "noop_benchmark");
+    }
+
+    #[test]
+    fn smoke_all_reports_each_function() {
+        let outcomes = run_smoke_all(&["noop_benchmark", "missing_benchmark"]);
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].function, "noop_benchmark");
+        assert!(outcomes[0].error.is_none());
+        assert!(
+            outcomes[1]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("unknown benchmark function")
+        );
+    }
 
-        let mut artifacts_ok = true;
-        let mut artifact_details = Vec::new();
+    #[test]
+    fn ab_self_compares_the_two_passes() {
+        let spec = RunSpec {
+            devices: vec!["Pixel 7".into(), "Pixel 8".into()],
+            functions: ["sample_fns::fibonacci", "sample_fns::checksum"].repeat(2).into_iter().map(String::from).collect(),
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        let result = |function: &str, ns: u64| {
+            json!({
+                "function": function,
+                "samples": [{ "duration_ns": ns }, { "duration_ns": ns }, { "duration_ns": ns }]
+            })
+        };
+        let pass = |fibonacci: u64, checksum: u64| {
+            vec![result("sample_fns::fibonacci", fibonacci), result("sample_fns::checksum", checksum)]
+        };
+        let mut run_summary = RunSummary {
+            schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+            summary: empty_summary(&spec),
+            spec,
+            artifacts: None,
+            local_report: json!({}),
+            remote_run: None,
+            benchmark_results: Some(BTreeMap::from([
+                ("Pixel 7".to_string(), [pass(1_000_000, 500_000), pass(1_010_000, 500_000)].concat()),
+                // A second pass 20% slower than the first is pure noise
+                ("Pixel 8".to_string(), [pass(1_000_000, 500_000), pass(1_200_000, 500_000)].concat()),
+            ])),
+            performance_metrics: None,
+            device_meta: None,
+            build_meta: None,
+            remote_runs: Vec::new(),
+        };
+        let second_pass = split_ab_self(&mut run_summary);
+        assert_eq!(run_summary.spec.functions, ["sample_fns::fibonacci", "sample_fns::checksum"]);
+        assert_eq!(run_summary.benchmark_results.as_ref().unwrap()["Pixel 7"].len(), 2);
+        assert_eq!(second_pass["Pixel 8"], pass(1_200_000, 500_000));
+
+        let dir = tempfile::tempdir().unwrap();
+        let summary_path = dir.path().join("run-summary.json");
+        run_summary.summary = build_summary(&run_summary).unwrap();
+        fs::write(&summary_path, serde_json::to_string(&run_summary).unwrap()).unwrap();
+        let findings = report_ab_self(
+            &run_summary,
+            second_pass,
+            &summary_path,
+            &RegressionThresholds::default(),
+        )
+        .unwrap();
+        assert_eq!(findings.len(), 2, "{findings:?}");
+        assert!(findings.iter().all(|finding| finding.device == "Pixel 8"));
+        let notification = run_notification(&run_summary.spec, &findings, &Ok(()));
+        assert_eq!(notification.regressions.len(), 2);
+        assert!(dir.path().join("run-summary.ab-self.json").exists());
+        assert!(dir.path().join("run-summary.ab-self.compare.md").exists());
+    }
 
-        if let Some(ref t) = target {
-            match t {
-                SdkTarget::Android | SdkTarget::Both => {
-                    let apk_path = output_base.join("android/app/build/outputs/apk/debug/app-debug.apk");
-                    let apk_release = output_base.join("android/app/build/outputs/apk/release/app-release-unsigned.apk");
-                    if apk_path.exists() {
-                        artifact_details.push(format!("Android APK (debug): {:?}", apk_path));
-                    } else if apk_release.exists() {
-                        artifact_details.push(format!("Android APK (release): {:?}", apk_release));
-                    } else {
-                        artifact_details.push("Android APK: NOT FOUND".to_string());
-                        artifacts_ok = false;
-                    }
+    #[test]
+    fn resume_runs_only_missing_devices_and_merges() {
+        let pass = |median: u64| json!({ "function": "sample_fns::fibonacci", "samples": [median] });
+        let spec = |devices: &[&str]| RunSpec {
+            iterations: 1,
+            devices: devices.iter().map(|d| d.to_string()).collect(),
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        let summary = |spec: RunSpec, results: Vec<(&str, Value)>| RunSummary {
+            schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+            summary: empty_summary(&spec),
+            spec,
+            artifacts: None,
+            local_report: json!({}),
+            remote_run: None,
+            benchmark_results: Some(
+                results.into_iter().map(|(device, report)| (device.to_string(), vec![report])).collect(),
+            ),
+            performance_metrics: None,
+            device_meta: None,
+            build_meta: None,
+            remote_runs: Vec::new(),
+        };
 
-                    // Check JNI libs
-                    let jni_base = output_base.join("android/app/src/main/jniLibs");
-                    let abis = ["arm64-v8a", "armeabi-v7a", "x86_64"];
-                    for abi in abis {
-                        let lib_path = jni_base.join(abi).join("libsample_fns.so");
-                        if lib_path.exists() {
-                            artifact_details.push(format!("JNI lib ({}): OK", abi));
-                        }
-                    }
-                }
-                SdkTarget::Ios => {}
-            }
+        let mut previous = summary(
+            spec(&["Pixel 7", "Pixel 8", "Galaxy S23"]),
+            vec![("Pixel 7", pass(100))],
+        );
+        let failures = DeviceFailures {
+            unscheduled: BTreeMap::new(),
+            no_results: BTreeMap::from([("Pixel 8".to_string(), "no benchmark report".to_string())]),
+        };
+        previous.summary.device_status = device_status(
+            &previous.spec.devices,
+            previous.benchmark_results.as_ref(),
+            &failures,
+        );
+        assert_eq!(previous.summary.device_status["Pixel 7"], DeviceStatus::Completed);
+        assert_eq!(previous.summary.device_status["Galaxy S23"], DeviceStatus::Missing);
+        assert_eq!(missing_devices(&previous), vec!["Pixel 8", "Galaxy S23"]);
+
+        let mut other = spec(&[]);
+        other.iterations = 5;
+        assert!(check_resumable(&previous.spec, &other).is_err());
+        assert!(check_resumable(&previous.spec, &spec(&[])).is_ok());
+
+        let build = |build_id: &str| RemoteRun::Android {
+            app_url: "bs://app".into(),
+            test_suite_url: "bs://suite".into(),
+            build_id: build_id.into(),
+        };
+        previous.remote_run = Some(build("build-1"));
+        previous.remote_runs = vec![build("build-1"), build("build-1-galaxy")];
+        let mut resumed = summary(
+            spec(&["Pixel 8", "Galaxy S23"]),
+            vec![("Pixel 8", pass(110)), ("Galaxy S23", pass(120))],
+        );
+        resumed.remote_run = Some(build("build-2"));
+        merge_resumed_run(&mut resumed, previous);
+        assert_eq!(resumed.remote_run, Some(build("build-2")));
+        assert_eq!(resumed.remote_runs, vec![build("build-1"), build("build-1-galaxy"), build("build-2")]);
+        assert_eq!(resumed.spec.devices, vec!["Pixel 7", "Pixel 8", "Galaxy S23"]);
+        assert_eq!(resumed.benchmark_results.as_ref().unwrap().len(), 3);
+        let status = device_status(
+            &resumed.spec.devices,
+            resumed.benchmark_results.as_ref(),
+            &DeviceFailures::default(),
+        );
+        assert!(status.values().all(|status| *status == DeviceStatus::Completed));
+        resumed.summary.device_status = status;
+        assert!(missing_devices(&resumed).is_empty());
+    }
 
-            match t {
-                SdkTarget::Ios | SdkTarget::Both => {
-                    let xcframework = output_base.join("ios/sample_fns.xcframework");
-                    if xcframework.exists() {
-                        artifact_details.push(format!("iOS xcframework: {:?}", xcframework));
-                    } else {
-                        artifact_details.push("iOS xcframework: NOT FOUND".to_string());
-                        artifacts_ok = false;
-                    }
+    #[test]
+    fn include_host_adds_host_device_to_summary() {
+        let spec = RunSpec {
+            warmup: 1,
+            devices: vec!["Pixel 7".into()],
+            ..test_run_spec("noop_benchmark")
+        };
+        let mut run_summary = RunSummary {
+            schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+            summary: empty_summary(&spec),
+            spec,
+            artifacts: None,
+            local_report: json!({}),
+            remote_run: None,
+            benchmark_results: Some(BTreeMap::from([(
+                "Pixel 7".to_string(),
+                vec![json!({"function": "noop_benchmark", "samples": [{"duration_ns": 100}]})],
+            )])),
+            performance_metrics: None,
+            device_meta: None,
+            build_meta: None,
+            remote_runs: Vec::new(),
+        };
+        add_host_results(&mut run_summary);
+
+        let host = host_device_name();
+        assert!(host.starts_with(&format!("host:{}-", std::env::consts::OS)));
+        let summary = build_summary(&run_summary).unwrap();
+        assert!(summary.devices.contains(&host));
+        let host_summary = summary
+            .device_summaries
+            .iter()
+            .find(|device| device.device == host)
+            .expect("host device summary");
+        assert_eq!(host_summary.benchmarks[0].function, "noop_benchmark");
+        assert_eq!(host_summary.benchmarks[0].samples, 3);
+        assert_eq!(summary.device_summaries.len(), 2);
+
+        check_host_benchmarks_linked(&run_summary.spec).unwrap();
+        let err = check_host_benchmarks_linked(&test_run_spec("my_crate::not_linked")).unwrap_err();
+        assert!(err.to_string().contains("my_crate::not_linked"), "{err}");
+    }
 
-                    let ipa_path = output_base.join("ios/BenchRunner.ipa");
-                    if ipa_path.exists() {
-                        artifact_details.push(format!("iOS IPA: {:?}", ipa_path));
-                    }
+    #[test]
+    fn ios_requires_artifacts_for_browserstack() {
+        let spec = resolve_run_spec(
+            MobileTarget::Ios,
+            "sample_fns::fibonacci".into(),
+            1,
+            0,
+            vec!["iphone".into()],
+            None,
+            None,
+            None,
+            false,
+            false, // release
+            None,  // cargo_profile
+            Path::new("target/mobench"),
+        )
+        .expect("should auto-package iOS artifacts when missing");
+        let ios_artifacts = spec
+            .ios_xcuitest
+            .expect("iOS artifacts should be populated");
+        assert!(ios_artifacts.app.exists(), "iOS app artifact missing");
+        assert!(
+            ios_artifacts.test_suite.exists(),
+            "iOS test suite artifact missing"
+        );
+    }
 
-                    let xcuitest_path = output_base.join("ios/BenchRunnerUITests.zip");
-                    if xcuitest_path.exists() {
-                        artifact_details.push(format!("XCUITest runner: {:?}", xcuitest_path));
-                    }
-                }
-                SdkTarget::Android => {}
-            }
-        } else {
-            // Check both platforms by default
-            let android_apk = output_base.join("android/app/build/outputs/apk/debug/app-debug.apk");
-            let ios_xcframework = output_base.join("ios/sample_fns.xcframework");
+    #[test]
+    fn skip_build_validates_prebuilt_android_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("app-release.apk");
+        let test_apk = dir.path().join("app-debug-androidTest.apk");
+        fs::write(&apk, b"apk").unwrap();
+
+        let err = prebuilt_android_artifacts(None, None, None, false).unwrap_err();
+        assert!(err.to_string().contains("--prebuilt-apk"));
+        let err = prebuilt_android_artifacts(Some(apk.clone()), None, None, true).unwrap_err();
+        assert!(err.to_string().contains("--prebuilt-test-apk"));
+        let err = prebuilt_android_artifacts(Some(apk.clone()), Some(test_apk.clone()), None, true)
+            .unwrap_err();
+        assert!(err.to_string().contains("Android test APK"));
+
+        fs::write(&test_apk, b"test").unwrap();
+        let (app, suite) =
+            prebuilt_android_artifacts(Some(apk.clone()), Some(test_apk.clone()), None, true).unwrap();
+        assert_eq!(app, apk);
+        assert_eq!(suite, Some(test_apk));
+    }
 
-            if android_apk.exists() {
-                artifact_details.push(format!("Android APK: {:?}", android_apk));
-            }
-            if ios_xcframework.exists() {
-                artifact_details.push(format!("iOS xcframework: {:?}", ios_xcframework));
-            }
+    #[test]
+    fn resolves_tagged_matrix_devices() {
+        let dir = tempfile::tempdir().unwrap();
+        let matrix_path = dir.path().join("device-matrix.yaml");
+        fs::write(
+            &matrix_path,
+            r#"devices:
+  - name: "Google Pixel 7-13.0"
+    os: android
+    os_version: "13.0"
+    tags: [default, pixel]
+  - name: "Samsung Galaxy S23-13.0"
+    os: android
+    os_version: "13.0"
+    tags: [extended]
+"#,
+        )
+        .unwrap();
 
-            if artifact_details.is_empty() {
-                artifacts_ok = false;
-                artifact_details.push("No artifacts found. Run 'cargo mobench build' first.".to_string());
-            }
-        }
+        let specs = resolve_matrix_device_specs(&matrix_path, &["Default".into()]).unwrap();
+        assert_eq!(specs, vec!["Google Pixel 7-13.0".to_string()]);
 
-        if artifacts_ok {
-            println!("OK");
-            checks_passed += 1;
-        } else {
-            println!("FAILED");
-            checks_failed += 1;
-        }
-        for detail in &artifact_details {
-            println!("        {}", detail);
-        }
-    } else {
-        println!("SKIPPED (use --check-artifacts to enable)");
+        let all = resolve_matrix_device_specs(&matrix_path, &[]).unwrap();
+        assert_eq!(all.len(), 2);
+
+        let err = resolve_matrix_device_specs(&matrix_path, &["missing".into()]).unwrap_err();
+        assert!(err.to_string().contains("Available tags"));
     }
 
-    // 4. Run smoke test if requested
-    print!("  [4/4] Running smoke test... ");
-    if smoke_test {
-        if let Some(ref func) = function {
-            match run_verify_smoke_test(func) {
-                Ok(report) => {
-                    println!("OK");
-                    let samples = report.samples.len();
-                    let mean_ns = if samples > 0 {
-                        report.samples.iter().map(|s| s.duration_ns).sum::<u64>() / samples as u64
-                    } else {
-                        0
-                    };
-                    println!("        Function: {}", func);
-                    println!("        Samples: {}", samples);
-                    println!("        Mean: {} ns ({:.3} ms)", mean_ns, mean_ns as f64 / 1_000_000.0);
-                    checks_passed += 1;
-                }
-                Err(e) => {
-                    println!("FAILED");
-                    println!("        Error: {}", e);
-                    checks_failed += 1;
-                }
-            }
-        } else if !benchmarks.is_empty() {
-            // Use first discovered benchmark
-            let func = &benchmarks[0].name;
-            match run_verify_smoke_test(func) {
-                Ok(report) => {
-                    println!("OK");
-                    let samples = report.samples.len();
-                    let mean_ns = if samples > 0 {
-                        report.samples.iter().map(|s| s.duration_ns).sum::<u64>() / samples as u64
-                    } else {
-                        0
-                    };
-                    println!("        Function: {} (auto-selected)", func);
-                    println!("        Samples: {}", samples);
-                    println!("        Mean: {} ns ({:.3} ms)", mean_ns, mean_ns as f64 / 1_000_000.0);
-                    checks_passed += 1;
-                }
-                Err(e) => {
-                    println!("FAILED");
-                    println!("        Error: {}", e);
-                    checks_failed += 1;
-                }
-            }
-        } else {
-            println!("SKIPPED (no benchmark function available)");
-            println!("        Tip: Use --function to specify a function, or add benchmarks with #[benchmark]");
-            warnings += 1;
-        }
-    } else {
-        println!("SKIPPED (use --smoke-test to enable)");
+    #[test]
+    fn plans_matrix_from_newest_browserstack_devices() {
+        let device = |name: &str, os: &str, version: &str, available| browserstack::BrowserStackDevice {
+            device: name.into(),
+            os: os.into(),
+            os_version: version.into(),
+            available,
+        };
+        let devices = vec![
+            device("Google Pixel 7", "android", "13.0", None),
+            device("Google Pixel 7", "android", "9.0", None),
+            device("Samsung Galaxy S23", "android", "14.0", Some(true)),
+            device("Google Pixel 8", "android", "14.0", Some(false)),
+            device("Google Pixel 6", "android", "12.0", None),
+            device("Motorola Moto G9", "android", "10.0", None),
+            device("iPhone 15", "ios", "17", None),
+        ];
+
+        let matrix = device_matrix_from_browserstack(devices.clone(), Some(2));
+        let names: Vec<String> = matrix
+            .devices
+            .iter()
+            .map(|d| format!("{}-{}", d.name, d.os_version))
+            .collect();
+        // Newest OS per model, newest first; unavailable devices are left out
+        assert_eq!(
+            names,
+            vec!["Samsung Galaxy S23-14.0", "Google Pixel 7-13.0", "iPhone 15-17"]
+        );
+        assert_eq!(
+            matrix.devices[1].tags.as_deref(),
+            Some(&["default".to_string(), "android".into(), "pixel".into()][..])
+        );
+        assert_eq!(matrix.devices[2].tags.as_deref().unwrap()[2], "iphone");
+        assert_eq!(matrix.groups.get("ios").map(String::as_str), Some("ios"));
+
+        // Without --top every model is kept; only the newest three are default
+        let all = device_matrix_from_browserstack(devices, None);
+        let moto = all.devices.iter().find(|d| d.name == "Motorola Moto G9").unwrap();
+        assert_eq!(moto.tags.as_deref().unwrap(), ["android", "moto"]);
+        assert!(os_version_key("13.0") > os_version_key("9.1"));
     }
 
-    // Print summary
-    println!("\n----------------------------------------");
-    println!("Verification Summary:");
-    println!("  Passed:   {}", checks_passed);
-    println!("  Failed:   {}", checks_failed);
-    println!("  Warnings: {}", warnings);
+    #[test]
+    fn expands_os_versions_into_devices() {
+        let dir = tempfile::tempdir().unwrap();
+        let matrix_path = dir.path().join("device-matrix.yaml");
+        fs::write(
+            &matrix_path,
+            r#"devices:
+  - name: "Google Pixel 7"
+    os: android
+    os_versions: [12, 13.0, "14"]
+    tags: [pixel]
+  - name: "iPhone 14"
+    os: ios
+    os_versions: [16, 17]
+    tags: [iphone]
+groups:
+  pixel: android
+"#,
+        )
+        .unwrap();
 
-    if checks_failed > 0 {
-        println!("\n[X] Verification failed with {} error(s)", checks_failed);
-        bail!("Verification failed");
-    } else if warnings > 0 {
-        println!("\n[!] Verification completed with {} warning(s)", warnings);
-    } else {
-        println!("\n[checkmark] All checks passed!");
+        let matrix = load_device_matrix(&matrix_path).unwrap();
+        let names: Vec<&str> = matrix.devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Google Pixel 7-12.0", "Google Pixel 7-13.0", "Google Pixel 7-14.0", "iPhone 14-16", "iPhone 14-17"]
+        );
+        assert_eq!(matrix.devices[1].os_version, "13.0");
+
+        // Tags and groups apply to every expanded version
+        let pixels = resolve_matrix_device_specs(&matrix_path, &["pixel".into()]).unwrap();
+        assert_eq!(pixels.len(), 3);
+        let groups = resolve_device_groups(&matrix, &pixels);
+        assert_eq!(groups[0].devices, pixels);
+
+        fs::write(
+            &matrix_path,
+            "devices:\n  - name: Pixel 7\n    os: android\n    os_version: \"13.0\"\n    os_versions: [14]\n",
+        )
+        .unwrap();
+        let err = load_device_matrix(&matrix_path).unwrap_err();
+        assert!(format!("{err:#}").contains("both os_version and os_versions"), "{err:#}");
     }
 
-    Ok(())
-}
+    #[test]
+    fn profile_devices_merge_list_and_tagged_matrix() {
+        let dir = tempfile::tempdir().unwrap();
+        let matrix = dir.path().join("device-matrix.yaml");
+        fs::write(
+            &matrix,
+            r#"devices:
+  - name: "Google Pixel 7-13.0"
+    os: android
+    os_version: "13.0"
+    tags: [nightly]
+  - name: "Samsung Galaxy S23-13.0"
+    os: android
+    os_version: "13.0"
+    tags: [release]
+"#,
+        )
+        .unwrap();
+        let mut profile = config::RunProfile {
+            devices: vec!["Google Pixel 7-13.0".into(), "Google Pixel 8-14.0".into()],
+            device_matrix: Some(matrix),
+            device_tags: vec!["nightly".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            profile_devices(&profile).unwrap(),
+            vec!["Google Pixel 7-13.0", "Google Pixel 8-14.0"]
+        );
 
-/// Validate a bench_spec.json file
-///
-/// Handles both "name" and "function" field names for compatibility
-/// with different spec file formats.
-fn validate_spec_file(path: &Path) -> Result<mobench_sdk::BenchSpec> {
-    let contents = fs::read_to_string(path)
-        .with_context(|| format!("reading spec file {:?}", path))?;
+        profile.device_tags.clear();
+        assert_eq!(profile_devices(&profile).unwrap().len(), 3);
+
+        // `--device-strategy first-available` picks per profile tag
+        profile.device_tags = vec!["nightly".into(), "release".into()];
+        assert_eq!(
+            device_tag_candidates(None, Some(&profile)).unwrap(),
+            vec![
+                ("nightly".to_string(), vec!["Google Pixel 7-13.0".to_string()]),
+                ("release".to_string(), vec!["Samsung Galaxy S23-13.0".to_string()]),
+            ]
+        );
+        assert!(device_tag_candidates(None, None).unwrap().is_empty());
 
-    // Try parsing directly first (standard BenchSpec format with "name" field)
-    if let Ok(spec) = serde_json::from_str::<mobench_sdk::BenchSpec>(&contents) {
-        // Validate spec fields
-        if spec.name.trim().is_empty() {
-            bail!("spec.name is empty");
-        }
-        if spec.iterations == 0 {
-            bail!("spec.iterations must be > 0");
+        profile.device_matrix = None;
+        profile.device_tags = vec!["nightly".into()];
+        assert!(profile_devices(&profile).is_err());
+    }
+
+    #[test]
+    fn loads_device_matrix_in_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("device-matrix.yml");
+        fs::write(
+            &yaml,
+            r#"devices:
+  - name: "Google Pixel 7-13.0"
+    os: android
+    os_version: "13.0"
+    tags: [default, pixel]
+  - name: "iPhone 14-16"
+    os: ios
+    os_version: "16"
+groups:
+  pixel: android
+"#,
+        )
+        .unwrap();
+        let toml = dir.path().join("device-matrix.toml");
+        fs::write(
+            &toml,
+            r#"[[devices]]
+name = "Google Pixel 7-13.0"
+os = "android"
+os_version = "13.0"
+tags = ["default", "pixel"]
+
+[[devices]]
+name = "iPhone 14-16"
+os = "ios"
+os_version = "16"
+
+[groups]
+pixel = "android"
+"#,
+        )
+        .unwrap();
+        let json = dir.path().join("device-matrix.JSON");
+        fs::write(
+            &json,
+            json!({
+                "devices": [
+                    {"name": "Google Pixel 7-13.0", "os": "android", "os_version": "13.0", "tags": ["default", "pixel"]},
+                    {"name": "iPhone 14-16", "os": "ios", "os_version": "16"}
+                ],
+                "groups": {"pixel": "android"}
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let expected = load_device_matrix(&yaml).unwrap();
+        assert_eq!(expected.devices.len(), 2);
+        for path in [&toml, &json] {
+            let matrix = load_device_matrix(path).unwrap();
+            assert_eq!(matrix.devices, expected.devices, "{path:?}");
+            assert_eq!(matrix.groups, expected.groups, "{path:?}");
         }
-        return Ok(spec);
-    }
 
-    // Fall back to generic Value parsing for "function" field format
-    // (used by persist_mobile_spec and some older formats)
-    let value: Value = serde_json::from_str(&contents)
-        .with_context(|| format!("parsing spec file {:?}", path))?;
+        fs::write(&toml, "devices = \"not a list\"").unwrap();
+        let err = load_device_matrix(&toml).unwrap_err();
+        assert!(format!("{err:#}").contains("device-matrix.toml"));
+    }
 
-    // Extract name from either "name" or "function" field
-    let name = value
-        .get("name")
-        .or_else(|| value.get("function"))
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow!("spec must have 'name' or 'function' field"))?
-        .to_string();
+    #[test]
+    fn rolls_up_device_groups() {
+        let matrix: DeviceMatrix = serde_yaml::from_str(
+            r#"devices:
+  - name: "Google Pixel 7-13.0"
+    os: android
+    os_version: "13.0"
+    tags: [pixel]
+  - name: "Samsung Galaxy S23-13.0"
+    os: android
+    os_version: "13.0"
+    tags: [galaxy]
+  - name: "iPhone 14-16"
+    os: ios
+    os_version: "16"
+    tags: [iphone]
+groups:
+  pixel: android
+  galaxy: android
+  iphone: ios
+"#,
+        )
+        .unwrap();
+        let selected = vec!["Google Pixel 7-13.0".to_string(), "Samsung Galaxy S23-13.0".to_string()];
+        let groups = resolve_device_groups(&matrix, &selected);
+        assert_eq!(
+            groups,
+            vec![DeviceGroup {
+                label: "android".into(),
+                devices: selected.clone(),
+            }]
+        );
 
-    let iterations = value
-        .get("iterations")
-        .and_then(|v| v.as_u64())
-        .map(|v| v as u32)
-        .unwrap_or(100);
+        let results = BTreeMap::from([
+            (
+                "Google Pixel 7".to_string(),
+                vec![json!({"function": "f", "samples": [10, 10, 10]})],
+            ),
+            (
+                "Samsung Galaxy S23".to_string(),
+                vec![json!({"function": "f", "samples": [30, 30, 30]})],
+            ),
+        ]);
+        let rollup = summarize_device_group(&groups[0], &results).unwrap();
+        assert_eq!(rollup.device, "group:android");
+        assert!(rollup.is_group());
+        assert_eq!(rollup.members.len(), 2);
+        let bench = &rollup.benchmarks[0];
+        assert_eq!(bench.samples, 6);
+        assert_eq!(bench.mean_ns, Some(20));
+        assert_eq!(bench.worst_device.as_deref(), Some("Samsung Galaxy S23"));
+
+        let mut summary = empty_summary(&RunSpec {
+            devices: selected,
+            device_groups: groups,
+            ..test_run_spec("f")
+        });
+        summary.device_summaries.push(rollup);
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("## Group: android (rollup of Google Pixel 7, Samsung Galaxy S23)"));
+        assert!(markdown.contains("| Worst Device |"));
+    }
 
-    let warmup = value
-        .get("warmup")
-        .and_then(|v| v.as_u64())
-        .map(|v| v as u32)
-        .unwrap_or(10);
+    #[test]
+    fn markdown_summary_includes_device_meta() {
+        let mut device_meta = browserstack::DeviceMeta::new();
+        device_meta.insert("device_details.cpu".into(), json!("Google Tensor G2"));
+        device_meta.insert("device_details.thermal_status".into(), json!("light"));
+        let summary = SummaryReport {
+            generated_at: "2026-01-01T00:00:00Z".into(),
+            generated_at_unix: 0,
+            target: MobileTarget::Android,
+            function: "sample_fns::fibonacci".into(),
+            iterations: 10,
+            warmup: 1,
+            devices: vec!["Google Pixel 7-13.0".into()],
+            device_summaries: vec![DeviceSummary {
+                device: "Google Pixel 7".into(),
+                device_meta,
+                members: Vec::new(),
+                benchmarks: Vec::new(),
+                target: None,
+            }],
+            noisy_cv_threshold_pct: DEFAULT_NOISY_CV_PCT,
+            budget_violations: Vec::new(),
+            device_retries: BTreeMap::new(),
+            skipped_devices: BTreeMap::new(),
+            device_failures: DeviceFailures::default(),
+            repeat_count: 1,
+            device_status: BTreeMap::new(),
+        };
 
-    // Validate
-    if name.trim().is_empty() {
-        bail!("spec.name/function is empty");
-    }
-    if iterations == 0 {
-        bail!("spec.iterations must be > 0");
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("- device_details.cpu: Google Tensor G2"));
+        assert!(markdown.contains("- device_details.thermal_status: light"));
     }
 
-    Ok(mobench_sdk::BenchSpec {
-        name,
-        iterations,
-        warmup,
-    })
-}
+    #[test]
+    fn reports_p99_tail_latency() {
+        let samples: Vec<u64> = (1..=200).collect();
+        let stats = SampleStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.p95_ns, 190);
+        assert_eq!(stats.p99_ns, 198);
+
+        // Summaries written before p99 was recorded still load
+        let legacy: BenchmarkStats = serde_json::from_value(json!({
+            "function": "sample_fns::fibonacci",
+            "samples": 10,
+            "mean_ns": 100,
+            "median_ns": 100,
+            "p95_ns": 120,
+            "min_ns": 90,
+            "max_ns": 130
+        }))
+        .unwrap();
+        assert_eq!(legacy.p99_ns, None);
 
-/// Run a minimal smoke test for verification
-fn run_verify_smoke_test(function: &str) -> Result<mobench_sdk::RunnerReport> {
-    let spec = mobench_sdk::BenchSpec {
-        name: function.to_string(),
-        iterations: 3, // Minimal iterations for smoke test
-        warmup: 1,
-    };
+        let summary = summary_with(
+            MobileTarget::Android,
+            10,
+            vec![("Pixel 7", vec![legacy])],
+        );
+        let csv = render_csv_summary(&summary);
+        assert!(csv.starts_with("device,function,samples,mean_ns,median_ns,p95_ns,p99_ns,min_ns,max_ns,cpu_median_ns\n"));
+        assert!(csv.contains("Pixel 7,sample_fns::fibonacci,10,100,100,120,,90,130,\n"));
+        assert!(render_markdown_summary(&summary).contains("| P95 (ms) | P99 (ms) |"));
+    }
 
-    mobench_sdk::run_benchmark(spec)
-        .map_err(|e| anyhow!("smoke test failed: {}", e))
-}
+    #[test]
+    fn migrates_unversioned_run_summaries() {
+        let unversioned = json!({
+            "spec": {
+                "target": "android",
+                "function": "sample_fns::fibonacci",
+                "iterations": 10,
+                "warmup": 1,
+                "devices": ["Pixel 7"]
+            },
+            "artifacts": null,
+            "remote_run": null,
+            "summary": {
+                "generated_at": "2025-01-01T00:00:00Z",
+                "target": "android",
+                "function": "sample_fns::fibonacci",
+                "iterations": 10,
+                "warmup": 1,
+                "device_summaries": [{ "device": "Pixel 7", "benchmarks": [] }]
+            }
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old-summary.json");
+        std::fs::write(&path, unversioned.to_string()).unwrap();
+        let summary = load_run_summary(&path).unwrap();
+        assert_eq!(summary.schema_version, RUN_SUMMARY_SCHEMA_VERSION);
+        assert_eq!(summary.summary.devices, vec!["Pixel 7"]);
+        assert_eq!(summary.local_report, Value::Null);
+
+        // Read directly, a missing version means the first one, as for migration
+        let mut raw = serde_json::to_value(&summary).unwrap();
+        raw.as_object_mut().unwrap().remove("schema_version");
+        assert_eq!(serde_json::from_value::<RunSummary>(raw).unwrap().schema_version, 1);
+
+        // `report` renders old summaries through the same migration
+        let html = dir.path().join("old-summary.html");
+        cmd_report(&path, Some(&html), false, 0).unwrap();
+        assert!(std::fs::read_to_string(&html).unwrap().contains("Pixel 7"));
+
+        let mut newer = unversioned.clone();
+        newer["schema_version"] = json!(RUN_SUMMARY_SCHEMA_VERSION + 1);
+        let err = migrate_run_summary(newer).unwrap_err().to_string();
+        assert!(err.contains("newer than this mobench supports"), "{err}");
+
+        let mut newer_report = unversioned;
+        newer_report["benchmark_results"] = json!({
+            "Pixel 7": [{ "schema_version": BENCH_REPORT_SCHEMA_VERSION + 1, "samples": [] }]
+        });
+        let err = migrate_run_summary(newer_report).unwrap_err().to_string();
+        assert!(err.contains("benchmark report schema version"), "{err}");
+    }
 
-/// Display summary statistics from a benchmark report JSON file
-fn cmd_summary(report_path: &Path, format: Option<SummaryFormat>) -> Result<()> {
-    let format = format.unwrap_or(SummaryFormat::Text);
+    #[test]
+    fn pivots_medians_by_model_and_os_version() {
+        assert_eq!(split_os_version("Google Pixel 8-14.0"), Some(("Google Pixel 8", "14.0")));
+        assert_eq!(split_os_version("local"), None);
+        assert_eq!(split_os_version("group:flagships"), None);
+
+        let medians = BTreeMap::from([
+            ("iPhone 15-9".to_string(), 1_000_000),
+            ("iPhone 15-10.0".to_string(), 1_200_000),
+            ("Google Pixel 8-14.0".to_string(), 2_000_000),
+            ("Samsung Galaxy S23-13.0".to_string(), 3_000_000),
+        ]);
+        let matrix = render_platform_version_matrix("fib", &medians, None).unwrap();
+        assert!(matrix.starts_with("## OS Version Matrix: fib"));
+        // Columns in numeric order; single-version models are left out
+        assert!(matrix.contains("| Device | 9 | 10.0 |"));
+        assert!(matrix.contains("| iPhone 15 | 1.000ms | 1.200ms (+20.00%) |"));
+        assert!(!matrix.contains("Pixel"));
+
+        let deltas = BTreeMap::from([("iPhone 15-9".to_string(), -1.5)]);
+        let matrix = render_platform_version_matrix("fib", &medians, Some(&deltas)).unwrap();
+        assert!(matrix.contains("| iPhone 15 | 1.000ms (-1.50%) | 1.200ms |"));
+
+        let single = BTreeMap::from([("Google Pixel 8-14.0".to_string(), 2_000_000)]);
+        assert!(render_platform_version_matrix("fib", &single, None).is_none());
+    }
 
-    // Try to load the report in various formats
-    let contents = fs::read_to_string(report_path)
-        .with_context(|| format!("reading report file {:?}", report_path))?;
+    #[test]
+    fn writes_compact_summary_json() {
+        let spec = RunSpec {
+            devices: vec!["Pixel 7".into()],
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        let mut summary = RunSummary {
+            schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+            summary: empty_summary(&spec),
+            spec,
+            artifacts: None,
+            local_report: json!({}),
+            remote_run: None,
+            benchmark_results: Some(BTreeMap::from([(
+                "Pixel 7".to_string(),
+                vec![json!({ "function": "sample_fns::fibonacci", "samples": [10, 11, 12] })],
+            )])),
+            performance_metrics: None,
+            device_meta: None,
+            build_meta: None,
+            remote_runs: Vec::new(),
+        };
+        summary.summary = build_summary(&summary).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let paths = resolve_summary_paths(Some(&dir.path().join("run-summary.json"))).unwrap();
+        let files = SummaryFiles {
+            json: true,
+            csv: false,
+            compact_json: true,
+        };
+        write_summary(&summary, &paths, files).unwrap();
+        let compact = fs::read_to_string(&paths.json).unwrap();
+        assert!(!compact.contains('\n'));
+        assert_eq!(load_run_summary(&paths.json).unwrap().summary.device_summaries[0].benchmarks[0].samples, 3);
+        write_summary(
+            &summary,
+            &paths,
+            SummaryFiles {
+                compact_json: false,
+                ..files
+            },
+        )
+        .unwrap();
+        assert!(fs::read_to_string(&paths.json).unwrap().len() > compact.len());
+    }
 
-    let value: Value = serde_json::from_str(&contents)
-        .with_context(|| format!("parsing report file {:?}", report_path))?;
+    #[test]
+    fn markdown_summary_flags_noisy_benchmarks() {
+        let stats = SampleStats::from_samples(&[100, 100, 100, 100]).unwrap();
+        assert_eq!(stats.cv_pct, 0.0);
+        let noisy_stats = SampleStats::from_samples(&[50, 150, 50, 150]).unwrap();
+        assert!((noisy_stats.cv_pct - 50.0).abs() < 1e-9);
 
-    // Extract summary information
-    let summary_data = extract_summary_data(&value)?;
+        let mut quiet = bench_stats("sample_fns::quiet", 100, 100);
+        quiet.cv_pct = Some(stats.cv_pct);
+        let mut noisy = bench_stats("sample_fns::noisy", 100, 150);
+        noisy.cv_pct = Some(noisy_stats.cv_pct);
+        let summary = summary_with(MobileTarget::Android, 10, vec![("Pixel 7", vec![noisy, quiet])]);
 
-    match format {
-        SummaryFormat::Text => print_summary_text(&summary_data),
-        SummaryFormat::Json => print_summary_json(&summary_data)?,
-        SummaryFormat::Csv => print_summary_csv(&summary_data),
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("1 noisy benchmark (CV > 15%)"), "{markdown}");
+        assert!(markdown.contains("| sample_fns::noisy \u{26a0}\u{fe0f}[^noisy] |"), "{markdown}");
+        assert!(markdown.contains("| sample_fns::quiet | "), "{markdown}");
+        assert!(markdown.contains("[^noisy]: Coefficient of variation above 15%"));
     }
 
-    Ok(())
-}
+    #[test]
+    fn summarizes_and_renders_custom_metrics() {
+        let entry = json!({
+            "function": "sample_fns::prove",
+            "samples": [{ "duration_ns": 10 }],
+            "metrics": { "proof_bytes": [190.0, 192, 194, 196], "bogus": "x" }
+        });
+        let metrics = summarize_metrics(extract_metrics(&entry));
+        assert_eq!(metrics.keys().collect::<Vec<_>>(), vec!["proof_bytes"]);
+        let proof = &metrics["proof_bytes"];
+        assert_eq!((proof.samples, proof.mean, proof.median), (4, 193.0, 193.0));
+        assert_eq!((proof.min, proof.p95, proof.max), (190.0, 196.0, 196.0));
+
+        let mut bench = bench_stats("sample_fns::prove", 100, 120);
+        bench.metrics = metrics;
+        let summary = summary_with(MobileTarget::Android, 4, vec![("Pixel 7", vec![bench])]);
+        let markdown = render_markdown_summary(&summary);
+        assert!(
+            markdown.contains("| sample_fns::prove | proof_bytes | 4 | 193 | 193 | 196 | 190 | 196 | 1.2 |"),
+            "{markdown}"
+        );
+        let csv = render_csv_summary(&summary);
+        assert!(csv.starts_with("device,function,samples,mean_ns,median_ns,p95_ns,p99_ns,min_ns,max_ns,cpu_median_ns,proof_bytes_median\n"), "{csv}");
+        assert!(csv.lines().nth(1).unwrap().ends_with(",193"), "{csv}");
+    }
 
-/// Summary data extracted from various report formats
-#[derive(Debug, Serialize)]
-struct SummaryData {
-    source_file: String,
-    function: Option<String>,
-    device: Option<String>,
-    os_version: Option<String>,
-    sample_count: usize,
-    mean_ns: Option<u64>,
-    median_ns: Option<u64>,
-    min_ns: Option<u64>,
-    max_ns: Option<u64>,
-    p95_ns: Option<u64>,
-    iterations: Option<u32>,
-    warmup: Option<u32>,
-}
+    #[test]
+    fn marks_timed_out_benchmarks_incomplete() {
+        let partial = json!({ "function": "sample_fns::slow", "timed_out": true, "samples_ns": [10, 12] });
+        assert!(report_timed_out(&partial));
+        assert!(!report_timed_out(&json!({ "samples_ns": [10] })));
 
-/// Extract summary data from various report formats
-fn extract_summary_data(value: &Value) -> Result<Vec<SummaryData>> {
-    let mut results = Vec::new();
+        let mut slow = bench_stats("sample_fns::slow", 11, 12);
+        slow.timed_out = report_timed_out(&partial);
+        let fast = bench_stats("sample_fns::fast", 1, 1);
+        assert!(!serde_json::to_string(&fast).unwrap().contains("timed_out"));
+        let summary = summary_with(MobileTarget::Android, 10, vec![("Pixel 7", vec![slow, fast])]);
 
-    // Check if this is a RunSummary format (from `mobench run`)
-    if value.get("summary").is_some() {
-        let summary = &value["summary"];
-        let function = summary.get("function").and_then(|f| f.as_str()).map(String::from);
-        let iterations = summary.get("iterations").and_then(|i| i.as_u64()).map(|i| i as u32);
-        let warmup = summary.get("warmup").and_then(|w| w.as_u64()).map(|w| w as u32);
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("1 incomplete benchmark (per-benchmark timeout)"), "{markdown}");
+        assert!(markdown.contains("| sample_fns::slow \u{23f1}\u{fe0f}[^incomplete] |"), "{markdown}");
+        assert!(markdown.contains("| sample_fns::fast | "), "{markdown}");
+        assert!(markdown.contains("[^incomplete]: The benchmark ran out of its per-benchmark timeout"));
+    }
 
-        if let Some(device_summaries) = summary.get("device_summaries").and_then(|d| d.as_array()) {
-            for device_summary in device_summaries {
-                let device = device_summary.get("device").and_then(|d| d.as_str()).map(String::from);
+    #[test]
+    fn prereq_checks_separate_errors_from_warnings() {
+        let check = |name: &str, passed, severity| PrereqCheck {
+            name: name.into(),
+            passed,
+            detail: None,
+            fix_hint: None,
+            fix_command: None,
+            severity,
+            docs_url: Some("https://rustup.rs".into()),
+        };
+        let mut checks = vec![
+            check("cargo installed", true, CheckSeverity::Info),
+            check("xcodegen installed", false, CheckSeverity::Warning),
+        ];
+        assert_eq!(count_check_failures(&checks), (0, 1));
+        checks.push(check("Xcode installed", false, CheckSeverity::Error));
+        assert_eq!(count_check_failures(&checks), (1, 1));
 
-                if let Some(benchmarks) = device_summary.get("benchmarks").and_then(|b| b.as_array()) {
-                    for bench in benchmarks {
-                        let bench_function = bench.get("function").and_then(|f| f.as_str()).map(String::from);
-                        results.push(SummaryData {
-                            source_file: "RunSummary".to_string(),
-                            function: bench_function.or_else(|| function.clone()),
-                            device: device.clone(),
-                            os_version: None, // RunSummary doesn't include OS version directly
-                            sample_count: bench.get("samples").and_then(|s| s.as_u64()).unwrap_or(0) as usize,
-                            mean_ns: bench.get("mean_ns").and_then(|m| m.as_u64()),
-                            median_ns: bench.get("median_ns").and_then(|m| m.as_u64()),
-                            min_ns: bench.get("min_ns").and_then(|m| m.as_u64()),
-                            max_ns: bench.get("max_ns").and_then(|m| m.as_u64()),
-                            p95_ns: bench.get("p95_ns").and_then(|p| p.as_u64()),
-                            iterations,
-                            warmup,
-                        });
-                    }
-                }
-            }
+        let json = serde_json::to_value(&checks[1]).unwrap();
+        assert_eq!(json["severity"], "warning");
+        assert_eq!(json["docs_url"], "https://rustup.rs");
+    }
+
+    #[test]
+    fn summarizes_cpu_time_and_flags_contention() {
+        let ios = json!({ "samples": [
+            { "duration_ns": 3_000_000, "cpu_ns": 1_000_000 },
+            { "duration_ns": 3_000_000, "cpu_ns": 1_000_000 },
+        ]});
+        assert_eq!(cpu_median_ns(&ios), Some(1_000_000));
+        let android = json!({ "samples_ns": [10, 20], "cpu_samples_ns": [8, 18] });
+        assert_eq!(cpu_median_ns(&android), Some(13));
+        // Wall-only reports, or partially measured ones, have no CPU median
+        assert_eq!(cpu_median_ns(&json!({ "samples": [{ "duration_ns": 10 }] })), None);
+
+        let mut contended = bench_stats("sample_fns::contended", 3_000_000, 3_000_000);
+        contended.cpu_median_ns = cpu_median_ns(&ios);
+        let mut busy = bench_stats("sample_fns::busy", 1_000_000, 1_000_000);
+        busy.cpu_median_ns = Some(950_000);
+        let summary = summary_with(MobileTarget::Ios, 10, vec![("iPhone 14", vec![contended, busy])]);
+
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("| CV (%) | CPU Median (ms) |"), "{markdown}");
+        assert!(markdown.contains("| sample_fns::contended \u{23f3}[^contention] |"), "{markdown}");
+        assert!(markdown.contains("| sample_fns::busy | "), "{markdown}");
+        assert!(markdown.contains("[^contention]: Median wall time is more than 1.5x"));
+        assert!(render_csv_summary(&summary).contains(",3000000,3000000,1000000\n"));
+    }
+
+    #[test]
+    fn aggregates_repeats_per_device_and_function() {
+        let round = |fib: [u64; 2], sort: [u64; 2]| {
+            BTreeMap::from([(
+                "Pixel 7".to_string(),
+                vec![
+                    json!({ "function": "sample_fns::fibonacci", "samples": fib }),
+                    json!({ "function": "sample_fns::sort", "samples": sort }),
+                ],
+            )])
+        };
+        let single = round([10, 10], [5, 5]);
+        assert_eq!(aggregate_repeats(vec![single.clone()]), single);
+
+        let pooled = aggregate_repeats(vec![round([10, 10], [5, 5]), round([30, 30], [5, 5])]);
+        let reports = &pooled["Pixel 7"];
+        assert_eq!(reports.len(), 2);
+        assert_eq!(extract_samples(&reports[0]), vec![10, 10, 30, 30]);
+        assert_eq!(reports[0]["repeat_medians_ns"], json!([10, 30]));
+        assert_eq!(repeat_cv_pct(&reports[0]), Some(50.0));
+        assert_eq!(repeat_cv_pct(&reports[1]), Some(0.0));
+        assert_eq!(repeat_cv_pct(&single["Pixel 7"][0]), None);
+
+        let with_extras = |allocs: f64| {
+            BTreeMap::from([(
+                "Pixel 7".to_string(),
+                vec![json!({
+                    "function": "sample_fns::sort",
+                    "samples": [5],
+                    "warmup_iterations": 2,
+                    "warmup_samples": [7, 6],
+                    "metrics": { "allocs": [allocs] },
+                })],
+            )])
+        };
+        let pooled = aggregate_repeats(vec![with_extras(1.0), with_extras(2.0)]);
+        let report = &pooled["Pixel 7"][0];
+        assert_eq!(report["metrics"]["allocs"], json!([1.0, 2.0]));
+        assert_eq!(report["warmup_samples"], json!([7, 6, 7, 6]));
+        assert_eq!(report["warmup_iterations"], 4);
+
+        let mut fib = bench_stats("sample_fns::fibonacci", 20_000_000, 30_000_000);
+        fib.repeat_cv_pct = repeat_cv_pct(&reports[0]);
+        let mut summary = summary_with(MobileTarget::Android, 2, vec![("Pixel 7", vec![fib])]);
+        summary.repeat_count = 2;
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("- Repeats: 2 (samples pooled per device and function)"), "{markdown}");
+        assert!(markdown.contains("| CV (%) | Repeat CV (%) |"), "{markdown}");
+        assert!(markdown.contains("| - | 50.0 |"), "{markdown}");
+    }
+
+    fn summary_with(
+        target: MobileTarget,
+        iterations: u32,
+        devices: Vec<(&str, Vec<BenchmarkStats>)>,
+    ) -> SummaryReport {
+        SummaryReport {
+            generated_at: "2026-01-01T00:00:00Z".into(),
+            generated_at_unix: 0,
+            target,
+            function: "sample_fns::fibonacci".into(),
+            iterations,
+            warmup: 1,
+            devices: devices.iter().map(|(d, _)| d.to_string()).collect(),
+            device_summaries: devices
+                .into_iter()
+                .map(|(device, benchmarks)| DeviceSummary {
+                    device: device.into(),
+                    device_meta: Default::default(),
+                    members: Vec::new(),
+                    benchmarks,
+                    target: None,
+                })
+                .collect(),
+            noisy_cv_threshold_pct: DEFAULT_NOISY_CV_PCT,
+            budget_violations: Vec::new(),
+            device_retries: BTreeMap::new(),
+            repeat_count: 1,
+            skipped_devices: BTreeMap::new(),
+            device_failures: DeviceFailures::default(),
+            device_status: BTreeMap::new(),
         }
     }
 
-    // Check if this is a BenchReport format (direct timing output)
-    if let Some(spec) = value.get("spec") {
-        let samples = extract_samples(value);
-        let stats = compute_sample_stats(&samples);
+    #[test]
+    fn merge_summaries_unions_devices_and_prefers_later_files() {
+        let android = summary_with(
+            MobileTarget::Android,
+            10,
+            vec![("Pixel 7", vec![bench_stats("sample_fns::fibonacci", 100, 120)])],
+        );
+        let ios_first = summary_with(
+            MobileTarget::Ios,
+            10,
+            vec![("iPhone 14", vec![bench_stats("sample_fns::fibonacci", 200, 220)])],
+        );
+        let ios_rerun = summary_with(
+            MobileTarget::Ios,
+            10,
+            vec![("iPhone 14", vec![bench_stats("sample_fns::fibonacci", 150, 170)])],
+        );
 
-        results.push(SummaryData {
-            source_file: "BenchReport".to_string(),
-            function: spec.get("name").and_then(|n| n.as_str()).map(String::from),
-            device: Some("local".to_string()),
-            os_version: None,
-            sample_count: samples.len(),
-            mean_ns: stats.as_ref().map(|s| s.mean_ns),
-            median_ns: stats.as_ref().map(|s| s.median_ns),
-            min_ns: stats.as_ref().map(|s| s.min_ns),
-            max_ns: stats.as_ref().map(|s| s.max_ns),
-            p95_ns: stats.as_ref().map(|s| s.p95_ns),
-            iterations: spec.get("iterations").and_then(|i| i.as_u64()).map(|i| i as u32),
-            warmup: spec.get("warmup").and_then(|w| w.as_u64()).map(|w| w as u32),
-        });
+        let merged = merge_summary_reports(&[
+            ("a.json".into(), android, BTreeMap::new()),
+            ("b.json".into(), ios_first, BTreeMap::new()),
+            ("c.json".into(), ios_rerun, BTreeMap::new()),
+        ])
+        .unwrap();
+
+        assert_eq!(merged.devices, vec!["Pixel 7", "iPhone 14"]);
+        assert_eq!(merged.device_summaries.len(), 2);
+        assert_eq!(merged.device_summaries[1].benchmarks.len(), 1);
+        assert_eq!(merged.device_summaries[1].benchmarks[0].median_ns, Some(150));
+        assert_eq!(merged.device_summaries[0].target, Some(MobileTarget::Android));
+        assert_eq!(merged.device_summaries[1].target, Some(MobileTarget::Ios));
+        assert_eq!(merged.target_label(), "Android, Ios");
+
+        let results = |functions: &[&str]| {
+            Some(BTreeMap::from([(
+                "Pixel 7".to_string(),
+                functions.iter().map(|f| json!({ "function": f, "samples": [1] })).collect(),
+            )]))
+        };
+        let mut later = results(&["sample_fns::sort"]);
+        merge_missing_results(&mut later, results(&["sample_fns::fibonacci", "sample_fns::sort"]));
+        let functions: Vec<_> = later.unwrap()["Pixel 7"]
+            .iter()
+            .filter_map(|report| report_function_name(report).map(String::from))
+            .collect();
+        assert_eq!(functions, vec!["sample_fns::sort", "sample_fns::fibonacci"]);
     }
 
-    // Check if this is benchmark_results format (from BrowserStack fetch)
-    if let Some(benchmark_results) = value.get("benchmark_results").and_then(|b| b.as_object()) {
-        for (device, entries) in benchmark_results {
-            if let Some(entries) = entries.as_array() {
-                for entry in entries {
-                    let samples = extract_samples(entry);
-                    let stats = compute_sample_stats(&samples);
+    #[test]
+    fn merge_summaries_rejects_mismatched_iterations() {
+        let a = summary_with(
+            MobileTarget::Android,
+            10,
+            vec![("Pixel 7", vec![bench_stats("sample_fns::fibonacci", 100, 120)])],
+        );
+        let b = summary_with(
+            MobileTarget::Ios,
+            20,
+            vec![("iPhone 14", vec![bench_stats("sample_fns::fibonacci", 200, 220)])],
+        );
+        let err = merge_summary_reports(&[
+            ("a.json".into(), a.clone(), BTreeMap::new()),
+            ("b.json".into(), b, BTreeMap::new()),
+        ])
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("iterations=20"), "{err}");
+        assert!(err.contains("sample_fns::fibonacci"), "{err}");
+
+        // The same report-level counts, but a #[benchmark] default in one run
+        let defaults = BTreeMap::from([(
+            "sample_fns::fibonacci".to_string(),
+            BenchmarkDefaults {
+                sample_size: Some(50),
+                warmup: None,
+            },
+        )]);
+        let err = merge_summary_reports(&[
+            ("a.json".into(), a.clone(), BTreeMap::new()),
+            ("c.json".into(), a, defaults),
+        ])
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("iterations=50"), "{err}");
+    }
 
-                    results.push(SummaryData {
-                        source_file: "BrowserStack".to_string(),
-                        function: entry.get("function").and_then(|f| f.as_str()).map(String::from),
-                        device: Some(device.clone()),
-                        os_version: entry.get("os_version").and_then(|o| o.as_str()).map(String::from),
-                        sample_count: samples.len(),
-                        mean_ns: entry.get("mean_ns").and_then(|m| m.as_u64()).or_else(|| stats.as_ref().map(|s| s.mean_ns)),
-                        median_ns: stats.as_ref().map(|s| s.median_ns),
-                        min_ns: stats.as_ref().map(|s| s.min_ns),
-                        max_ns: stats.as_ref().map(|s| s.max_ns),
-                        p95_ns: stats.as_ref().map(|s| s.p95_ns),
-                        iterations: None,
-                        warmup: None,
-                    });
-                }
+    #[test]
+    fn merged_run_summary_takes_spec_and_target_from_the_first_file() {
+        let run = |target: MobileTarget, device: &str, median: u64| {
+            let spec: RunSpec = serde_json::from_value(json!({
+                "target": target,
+                "function": "sample_fns::fibonacci",
+                "iterations": 10,
+                "warmup": 1,
+                "devices": [device],
+            }))
+            .unwrap();
+            RunSummary {
+                schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+                summary: summary_with(
+                    target,
+                    10,
+                    vec![(device, vec![bench_stats("sample_fns::fibonacci", median, median)])],
+                ),
+                spec,
+                artifacts: None,
+                local_report: json!({}),
+                remote_run: None,
+                remote_runs: Vec::new(),
+                benchmark_results: Some(BTreeMap::from([(
+                    device.to_string(),
+                    vec![json!({ "function": "sample_fns::fibonacci", "samples": [median] })],
+                )])),
+                performance_metrics: None,
+                device_meta: None,
+                build_meta: None,
             }
-        }
+        };
+        let merged = merge_run_summaries(
+            &["a.json".into(), "b.json".into(), "c.json".into()],
+            vec![
+                run(MobileTarget::Android, "Pixel 7", 100),
+                run(MobileTarget::Ios, "iPhone 14", 200),
+                run(MobileTarget::Ios, "iPhone 14", 150),
+            ],
+        )
+        .unwrap();
+        assert_eq!(merged.spec.target, MobileTarget::Android);
+        assert_eq!(merged.summary.target, merged.spec.target);
+        let results = merged.benchmark_results.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["iPhone 14"], vec![json!({ "function": "sample_fns::fibonacci", "samples": [150] })]);
     }
 
-    // Check if this is a session bench-report.json format
-    if value.get("samples").is_some() && value.get("spec").is_none() {
-        // Direct samples array without spec wrapper
-        let samples = extract_samples(value);
-        let stats = compute_sample_stats(&samples);
-
-        results.push(SummaryData {
-            source_file: "SessionReport".to_string(),
-            function: value.get("function").and_then(|f| f.as_str()).map(String::from),
-            device: value.get("device").and_then(|d| d.as_str()).map(String::from),
-            os_version: value.get("os_version").and_then(|o| o.as_str()).map(String::from),
-            sample_count: samples.len(),
-            mean_ns: value.get("mean_ns").and_then(|m| m.as_u64()).or_else(|| stats.as_ref().map(|s| s.mean_ns)),
-            median_ns: stats.as_ref().map(|s| s.median_ns),
-            min_ns: stats.as_ref().map(|s| s.min_ns),
-            max_ns: stats.as_ref().map(|s| s.max_ns),
-            p95_ns: stats.as_ref().map(|s| s.p95_ns),
-            iterations: value.get("iterations").and_then(|i| i.as_u64()).map(|i| i as u32),
-            warmup: value.get("warmup").and_then(|w| w.as_u64()).map(|w| w as u32),
-        });
+    #[test]
+    fn parses_common_logcat_formats() {
+        let expected = |pid| LogcatLine {
+            pid,
+            tag: "BenchRunner",
+            message: "hello: world",
+        };
+        assert_eq!(
+            parse_logcat_line("01-20 12:34:56.789  1234  5678 I BenchRunner: hello: world"),
+            Some(expected(Some("1234")))
+        );
+        assert_eq!(
+            parse_logcat_line(
+                "01-20 12:34:56.789 1234-5678/dev.world.bench I/BenchRunner: hello: world"
+            ),
+            Some(expected(Some("1234")))
+        );
+        assert_eq!(
+            parse_logcat_line("01-20 12:34:56.789 I/BenchRunner( 1234): hello: world"),
+            Some(expected(Some("1234")))
+        );
+        assert_eq!(
+            parse_logcat_line("2026-01-20 12:34:57 I/BenchRunner: hello: world"),
+            Some(expected(None))
+        );
+        assert_eq!(
+            parse_logcat_line("2026-01-20 12:34:56.789 BenchRunner[1234:5678] {\"a\": 1}"),
+            None
+        );
+        assert_eq!(parse_logcat_line("{\"a\": 1}"), None);
     }
 
-    if results.is_empty() {
-        bail!("Could not extract summary data from report. Unrecognized format.");
+    #[test]
+    fn extract_all_bench_json_collects_suite_reports() {
+        let block = |function: &str, sample: u64| {
+            format!(
+                "BENCH_REPORT_JSON_START\n{}\nBENCH_REPORT_JSON_END\n",
+                json!({ "function": function, "samples_ns": [sample] })
+            )
+        };
+        let logs = [
+            block("suite::a", 1),
+            block("suite::b", 2),
+            // A relaunch re-reports `a`; the later report wins
+            block("suite::a", 3),
+            "BENCH_REPORT_JSON_START\n{\"function\": \"suite::c\"".to_string(),
+        ]
+        .concat();
+        let reports = extract_all_bench_json(&logs);
+        let summary: Vec<(&str, &Value)> = reports
+            .iter()
+            .map(|r| (r["function"].as_str().unwrap(), &r["samples_ns"][0]))
+            .collect();
+        assert_eq!(summary, vec![("suite::b", &json!(2)), ("suite::a", &json!(3))]);
+        assert_eq!(extract_bench_json(&logs).unwrap()["function"], "suite::a");
     }
 
-    Ok(results)
-}
-
-/// Print summary in text format
-fn print_summary_text(data: &[SummaryData]) {
-    println!("Benchmark Summary");
-    println!("=================\n");
-
-    for (idx, entry) in data.iter().enumerate() {
-        if data.len() > 1 {
-            println!("--- Entry {} ---", idx + 1);
-        }
-
-        if let Some(ref func) = entry.function {
-            println!("Function:     {}", func);
-        }
-        if let Some(ref device) = entry.device {
-            println!("Device:       {}", device);
-        }
-        if let Some(ref os) = entry.os_version {
-            println!("OS Version:   {}", os);
-        }
-        println!("Sample Count: {}", entry.sample_count);
-        println!();
+    #[test]
+    fn extract_only_rebuilds_reports_from_saved_logs() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        write_json(
+            root.join("build.json"),
+            &json!({"devices": [{"device": "Google Pixel 7", "sessions": [{"id": "abc"}]}]}),
+        )
+        .unwrap();
+        let session = root.join("session-abc");
+        fs::create_dir_all(&session).unwrap();
+        let block = |function: &str| {
+            format!(
+                "BENCH_REPORT_JSON_START\n{}\nBENCH_REPORT_JSON_END\n",
+                json!({ "function": function, "samples_ns": [10, 20] })
+            )
+        };
+        fs::write(
+            session.join("device_logs.log"),
+            [block("suite::a"), block("suite::b")].concat(),
+        )
+        .unwrap();
+        // Output of an earlier, buggy extraction is replaced
+        fs::write(session.join("bench-report.json"), "{}").unwrap();
+        let unparsed = root.join("session-def");
+        fs::create_dir_all(&unparsed).unwrap();
+        fs::write(unparsed.join("device_logs.log"), "no reports here").unwrap();
+        fs::write(unparsed.join("bench-report.json"), "{}").unwrap();
+
+        assert_eq!(reextract_fetched_artifacts(root, None).unwrap(), 0);
+        let read = |path: PathBuf| -> Value {
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+        };
+        assert_eq!(read(session.join("bench-report.json"))["function"], "suite::b");
+        assert_eq!(read(session.join("bench-reports.json")).as_array().unwrap().len(), 2);
+        assert!(!unparsed.join("bench-report.json").exists());
+        let results = read(root.join("benchmark-results.json"));
+        assert_eq!(results["benchmark_results"]["Google Pixel 7"].as_array().unwrap().len(), 2);
 
-        println!("Statistics (nanoseconds):");
-        println!("  Mean:   {}", entry.mean_ns.map(|v| format!("{} ({:.3} ms)", v, v as f64 / 1_000_000.0)).unwrap_or_else(|| "-".to_string()));
-        println!("  Median: {}", entry.median_ns.map(|v| format!("{} ({:.3} ms)", v, v as f64 / 1_000_000.0)).unwrap_or_else(|| "-".to_string()));
-        println!("  Min:    {}", entry.min_ns.map(|v| format!("{} ({:.3} ms)", v, v as f64 / 1_000_000.0)).unwrap_or_else(|| "-".to_string()));
-        println!("  Max:    {}", entry.max_ns.map(|v| format!("{} ({:.3} ms)", v, v as f64 / 1_000_000.0)).unwrap_or_else(|| "-".to_string()));
-        println!("  P95:    {}", entry.p95_ns.map(|v| format!("{} ({:.3} ms)", v, v as f64 / 1_000_000.0)).unwrap_or_else(|| "-".to_string()));
+        assert!(reextract_fetched_artifacts(&session, None).is_err());
+    }
 
-        if entry.iterations.is_some() || entry.warmup.is_some() {
-            println!();
-            println!("Configuration:");
-            if let Some(iter) = entry.iterations {
-                println!("  Iterations: {}", iter);
-            }
-            if let Some(warm) = entry.warmup {
-                println!("  Warmup:     {}", warm);
-            }
-        }
+    #[test]
+    fn extract_only_reads_compressed_artifacts() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let build = json!({"devices": [{"device": "iPhone 15", "sessions": [{"id": "abc"}]}]});
+        write_artifact_json(&root.join("build.json"), &build, true).unwrap();
+        write_benchmark_results(root, &BTreeMap::new(), true).unwrap();
+        let session = root.join("session-abc");
+        fs::create_dir_all(&session).unwrap();
+        let log = format!(
+            "BENCH_REPORT_JSON_START\n{}\nBENCH_REPORT_JSON_END\n",
+            json!({ "function": "suite::a", "samples_ns": [10, 20] })
+        );
+        compress::write(&session.join("device_logs.log"), log.as_bytes(), true).unwrap();
 
-        if idx < data.len() - 1 {
-            println!();
-        }
+        assert_eq!(reextract_fetched_artifacts(root, None).unwrap(), 0);
+        assert!(!root.join(BENCHMARK_RESULTS_FILE).exists());
+        let results = read_json_if_exists(&root.join(BENCHMARK_RESULTS_FILE)).unwrap().unwrap();
+        assert_eq!(results["benchmark_results"]["iPhone 15"][0]["function"], "suite::a");
     }
-}
 
-/// Print summary in JSON format
-fn print_summary_json(data: &[SummaryData]) -> Result<()> {
-    let json = serde_json::to_string_pretty(data)?;
-    println!("{}", json);
-    Ok(())
-}
+    #[test]
+    fn fetch_keeps_sessions_that_download() {
+        use std::sync::{Arc, OnceLock};
+
+        // The session JSON links to a log on the same server, whose port is
+        // only known once it is running
+        let base_url = Arc::new(OnceLock::<String>::new());
+        let log_base = Arc::clone(&base_url);
+        let server = browserstack::mock::serve(move |path| match path {
+            "/app-automate/espresso/v2/builds/b1" => (
+                200,
+                json!({"status": "done", "devices": [{"device": "Google Pixel 7", "sessions": [{"id": "s1"}, {"id": "s2"}]}]})
+                    .to_string(),
+            ),
+            "/app-automate/espresso/v2/builds/b1/sessions/s2" => (
+                200,
+                json!({"automation_session": {"device_logs": format!("{}/logs/s2", log_base.get().unwrap())}})
+                    .to_string(),
+            ),
+            "/logs/s2" => (
+                200,
+                format!(
+                    "BENCH_REPORT_JSON_START\n{}\nBENCH_REPORT_JSON_END\n",
+                    json!({ "function": "suite::a", "samples_ns": [10, 20] })
+                ),
+            ),
+            // Session s1 keeps failing after every retry
+            _ => (502, "bad gateway".into()),
+        });
+        base_url.set(server.clone()).unwrap();
+        let client = BrowserStackClient::new(
+            browserstack::BrowserStackAuth {
+                username: "user".into(),
+                access_key: "key".into(),
+            },
+            None,
+        )
+        .unwrap()
+        .with_base_url(server)
+        .with_retry(2, Duration::ZERO);
+
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let fetch = |build_id: &str| {
+            fetch_browserstack_artifacts(&client, MobileTarget::Android, build_id, root, false, 1, 1, None, false)
+        };
+        assert_eq!(fetch("b1").unwrap(), 0);
+        assert!(root.join("session-s2/bench-report.json").exists());
+        assert!(!root.join("session-s1").exists());
+        let results: Value =
+            serde_json::from_str(&fs::read_to_string(root.join("benchmark-results.json")).unwrap())
+                .unwrap();
+        assert_eq!(results["benchmark_results"]["Google Pixel 7"][0]["function"], "suite::a");
 
-/// Print summary in CSV format
-fn print_summary_csv(data: &[SummaryData]) {
-    println!("function,device,os_version,sample_count,mean_ns,median_ns,min_ns,max_ns,p95_ns,iterations,warmup");
-    for entry in data {
-        println!(
-            "{},{},{},{},{},{},{},{},{},{},{}",
-            entry.function.as_deref().unwrap_or(""),
-            entry.device.as_deref().unwrap_or(""),
-            entry.os_version.as_deref().unwrap_or(""),
-            entry.sample_count,
-            entry.mean_ns.map(|v| v.to_string()).unwrap_or_default(),
-            entry.median_ns.map(|v| v.to_string()).unwrap_or_default(),
-            entry.min_ns.map(|v| v.to_string()).unwrap_or_default(),
-            entry.max_ns.map(|v| v.to_string()).unwrap_or_default(),
-            entry.p95_ns.map(|v| v.to_string()).unwrap_or_default(),
-            entry.iterations.map(|v| v.to_string()).unwrap_or_default(),
-            entry.warmup.map(|v| v.to_string()).unwrap_or_default(),
-        );
+        // A build whose status endpoint is down is still an error
+        assert!(fetch("b2").is_err());
     }
-}
-
-/// List available BrowserStack devices and optionally validate device specs.
-fn cmd_devices(
-    platform: Option<DevicePlatform>,
-    output_json: bool,
-    validate: Vec<String>,
-) -> Result<()> {
-    // Try to get credentials, but provide helpful error if missing
-    let creds = match resolve_browserstack_credentials(None) {
-        Ok(creds) => creds,
-        Err(_) => {
-            // Check what's missing and provide helpful guidance
-            let username = env::var("BROWSERSTACK_USERNAME").ok();
-            let access_key = env::var("BROWSERSTACK_ACCESS_KEY").ok();
 
-            let missing_username = username.is_none() || username.as_deref() == Some("");
-            let missing_access_key = access_key.is_none() || access_key.as_deref() == Some("");
+    #[test]
+    fn keep_going_drops_invalid_devices() {
+        let mut spec = RunSpec {
+            iterations: 10,
+            warmup: 1,
+            devices: vec!["Google Pixel 7-13.0".into(), "Retired Phone-9.0".into()],
+            device_groups: vec![
+                DeviceGroup {
+                    label: "pixel".into(),
+                    devices: vec!["Google Pixel 7-13.0".into()],
+                },
+                DeviceGroup {
+                    label: "legacy".into(),
+                    devices: vec!["Retired Phone-9.0".into()],
+                },
+            ],
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        let invalid = |spec: &str| browserstack::DeviceValidationError {
+            spec: spec.into(),
+            reason: "No matching device found".into(),
+            suggestions: Vec::new(),
+        };
 
-            let error_msg = browserstack::format_credentials_error(missing_username, missing_access_key);
-            bail!("{}", error_msg);
-        }
-    };
+        let skipped = drop_invalid_devices(&mut spec, &[invalid("Retired Phone-9.0")]).unwrap();
+        assert_eq!(spec.devices, vec!["Google Pixel 7-13.0".to_string()]);
+        assert_eq!(spec.device_groups.len(), 1);
+        assert_eq!(skipped["Retired Phone-9.0"], "No matching device found");
 
-    let client = BrowserStackClient::new(
-        BrowserStackAuth {
-            username: creds.username,
-            access_key: creds.access_key,
-        },
-        creds.project,
-    )?;
+        let mut summary = empty_summary(&spec);
+        summary.skipped_devices = skipped;
+        assert!(render_markdown_summary(&summary)
+            .contains("## Skipped Devices\n\n- Retired Phone-9.0: No matching device found\n"));
 
-    // If validating devices, do that and exit
-    if !validate.is_empty() {
-        let platform_str = platform.map(|p| match p {
-            DevicePlatform::Android => "android",
-            DevicePlatform::Ios => "ios",
-        });
+        let err = drop_invalid_devices(&mut spec, &[invalid("Google Pixel 7-13.0")]).unwrap_err();
+        assert_eq!(crate::exit_code(&err), 4);
+    }
 
-        let validation = client.validate_devices(&validate, platform_str)?;
+    #[test]
+    fn no_fail_fast_reports_device_outcomes() {
+        let spec = RunSpec {
+            iterations: 10,
+            warmup: 1,
+            devices: vec!["Google Pixel 7-13.0".into(), "Retired Phone-9.0".into(), "Pixel 8-14.0".into()],
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        let mut summary = empty_summary(&spec);
+        assert!(fail_on_device_failures(&summary.device_failures).is_ok());
+        assert!(!render_markdown_summary(&summary).contains("## Device Outcomes"));
+
+        summary.device_summaries = vec![DeviceSummary {
+            device: "Google Pixel 7-13.0".into(),
+            device_meta: Default::default(),
+            members: Vec::new(),
+            benchmarks: vec![bench_stats("sample_fns::fibonacci", 1_000_000, 1_200_000)],
+            target: None,
+        }];
+        summary
+            .device_failures
+            .unscheduled
+            .insert("Retired Phone-9.0".into(), "unknown device".into());
+        summary
+            .device_failures
+            .no_results
+            .insert("Pixel 8-14.0".into(), "no benchmark report".into());
+
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains(
+            "## Device Outcomes\n\n- Succeeded (1): Google Pixel 7-13.0\n- Failed to schedule (1): Retired Phone-9.0 (unknown device)\n- No results (1): Pixel 8-14.0 (no benchmark report)\n"
+        ), "{markdown}");
+        let value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(value["device_failures"]["unscheduled"]["Retired Phone-9.0"], "unknown device");
+
+        let err = fail_on_device_failures(&summary.device_failures).unwrap_err();
+        assert_eq!(crate::exit_code(&err), 9);
+        assert!(err.to_string().contains("Retired Phone-9.0, Pixel 8-14.0"), "{err}");
+    }
 
-        if output_json {
-            let output = json!({
-                "valid": validation.valid,
-                "invalid": validation.invalid.iter().map(|e| {
-                    json!({
-                        "spec": e.spec,
-                        "reason": e.reason,
-                        "suggestions": e.suggestions
-                    })
-                }).collect::<Vec<_>>()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        } else {
-            if !validation.valid.is_empty() {
-                println!("Valid devices ({}):", validation.valid.len());
-                for device in &validation.valid {
-                    println!("  [OK] {}", device);
-                }
-            }
+    #[test]
+    fn device_strategy_picks_available_devices() {
+        let devices: Vec<String> = ["Pixel 7-13.0", "Pixel 8-14.0", "Galaxy S23-13.0", "iPhone 15-17"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let unavailable: BTreeSet<String> = ["Pixel 7-13.0".to_string()].into();
+        let none = BTreeSet::new();
+
+        assert_eq!(select_devices(DeviceStrategy::All, &devices, &[], &unavailable), devices);
+        assert_eq!(
+            select_devices(DeviceStrategy::FirstAvailable, &devices, &[], &unavailable),
+            vec!["Pixel 8-14.0"]
+        );
+        assert_eq!(
+            select_devices(DeviceStrategy::OnePerOs, &devices, &[], &unavailable),
+            vec!["Pixel 8-14.0", "Galaxy S23-13.0", "iPhone 15-17"]
+        );
 
-            if !validation.invalid.is_empty() {
-                if !validation.valid.is_empty() {
-                    println!();
-                }
-                println!("Invalid devices ({}):", validation.invalid.len());
-                for error in &validation.invalid {
-                    println!("  [ERROR] {}: {}", error.spec, error.reason);
-                    if !error.suggestions.is_empty() {
-                        println!("          Suggestions:");
-                        for suggestion in &error.suggestions {
-                            println!("            - {}", suggestion);
-                        }
-                    }
-                }
-            }
-        }
+        let tags = vec![
+            ("pixel".to_string(), vec!["Pixel 7-13.0".to_string(), "Pixel 8-14.0".to_string()]),
+            ("samsung".to_string(), vec!["Galaxy S23-13.0".to_string()]),
+        ];
+        assert_eq!(
+            select_devices(DeviceStrategy::FirstAvailable, &devices, &tags, &none),
+            vec!["Pixel 7-13.0", "Galaxy S23-13.0"]
+        );
+        assert_eq!(
+            select_devices(DeviceStrategy::FirstAvailable, &devices, &tags, &unavailable),
+            vec!["Pixel 8-14.0", "Galaxy S23-13.0"]
+        );
+    }
 
-        // Exit with error if any devices were invalid
-        if !validation.invalid.is_empty() {
-            bail!(
-                "{} of {} device specs are invalid",
-                validation.invalid.len(),
-                validate.len()
-            );
-        }
+    #[test]
+    fn suite_spec_lists_every_function() {
+        let mut spec = resolve_run_spec(
+            MobileTarget::Android,
+            "suite::a".into(),
+            10,
+            1,
+            vec![],
+            None,
+            None,
+            None,
+            true,
+            false,
+            None,
+            Path::new("target/mobench"),
+        )
+        .unwrap();
+        assert_eq!(spec.all_functions(), vec!["suite::a"]);
+        assert!(mobile_spec_payload(&spec).unwrap().get("functions").is_none());
+
+        spec.functions = vec!["suite::a".into(), "suite::b".into()];
+        assert_eq!(spec.all_functions(), vec!["suite::a", "suite::b"]);
+        assert_eq!(
+            mobile_spec_payload(&spec).unwrap()["functions"],
+            json!(["suite::a", "suite::b"])
+        );
 
-        return Ok(());
+        assert!(mobile_spec_payload(&spec).unwrap().get("seed").is_none());
+        spec.seed = Some(42);
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["seed"], 42);
     }
 
-    // List devices
-    println!("Fetching available BrowserStack devices...\n");
-
-    let devices = match platform {
-        Some(DevicePlatform::Android) => client.list_espresso_devices()?,
-        Some(DevicePlatform::Ios) => client.list_xcuitest_devices()?,
-        None => client.list_all_devices()?,
-    };
+    #[test]
+    fn extract_bench_json_handles_interleaved_logcat() {
+        let logs = include_str!("../tests/fixtures/logcat_threadtime_interleaved.txt");
+        let report = extract_bench_json(logs).expect("report extracted");
+        assert_eq!(report["function"], "sample_fns::fibonacci");
+        assert_eq!(report["samples_ns"], json!([1000, 1200, 1100]));
+        assert_eq!(report["spec"]["iterations"], 3);
+    }
 
-    if devices.is_empty() {
-        println!("No devices found.");
-        return Ok(());
+    #[test]
+    fn extract_bench_json_handles_studio_logcat() {
+        let logs = include_str!("../tests/fixtures/logcat_studio_wrapped.txt");
+        let report = extract_bench_json(logs).expect("report extracted");
+        assert_eq!(report["function"], "sample_fns::checksum");
+        assert_eq!(report["samples_ns"], json!([2000, 2100]));
     }
 
-    if output_json {
-        println!("{}", serde_json::to_string_pretty(&devices)?);
-        return Ok(());
+    #[test]
+    fn extract_bench_json_reconstructs_from_streamed_samples() {
+        let logs = r#"
+01-20 12:34:56.100 I/BenchRunner: BENCH_SAMPLE_JSON {"name":"sample_fns::fibonacci","iteration":0,"duration_ns":1000}
+01-20 12:34:56.200 I/BenchRunner: BENCH_SAMPLE_JSON {"name":"sample_fns::fibonacci","iteration":1,"duration_ns":1200}
+01-20 12:34:56.300 I/BenchRunner: BENCH_SAMPLE_JSON {"name":"sample_fns::fibonacci","iteration":1,"duration_ns":1300}
+01-20 12:34:56.400 I/BenchRunner: BENCH_JSON {"spec": {"name": "sample_fns::fibo
+"#;
+
+        let report = extract_bench_json(logs).expect("report reconstructed");
+        assert_eq!(report["function"], "sample_fns::fibonacci");
+        assert_eq!(report["reconstructed_from_samples"], true);
+        assert_eq!(
+            report["samples"],
+            json!([{ "duration_ns": 1000 }, { "duration_ns": 1300 }])
+        );
+
+        // A complete report still wins over streamed samples.
+        let complete = format!("{logs}\nBENCH_JSON {{\"function\": \"done\", \"samples\": []}}\n");
+        assert_eq!(extract_bench_json(&complete).unwrap()["function"], "done");
     }
 
-    // Group devices by OS
-    let mut android_devices: Vec<_> = devices.iter().filter(|d| d.os == "android").collect();
-    let mut ios_devices: Vec<_> = devices.iter().filter(|d| d.os == "ios").collect();
+    #[test]
+    fn only_automatable_checks_have_fix_commands() {
+        let target = check_rust_target("not-a-real-target-triple");
+        assert!(!target.passed);
+        assert_eq!(
+            target.fix_command,
+            Some(rustup_target_add("not-a-real-target-triple"))
+        );
 
-    // Sort by device name, then OS version (descending)
-    android_devices.sort_by(|a, b| {
-        a.device.cmp(&b.device).then_with(|| {
-            // Try to compare versions numerically
-            let av: f64 = a.os_version.parse().unwrap_or(0.0);
-            let bv: f64 = b.os_version.parse().unwrap_or(0.0);
-            bv.partial_cmp(&av).unwrap_or(std::cmp::Ordering::Equal)
-        })
-    });
-    ios_devices.sort_by(|a, b| {
-        a.device.cmp(&b.device).then_with(|| {
-            let av: f64 = a.os_version.parse().unwrap_or(0.0);
-            let bv: f64 = b.os_version.parse().unwrap_or(0.0);
-            bv.partial_cmp(&av).unwrap_or(std::cmp::Ordering::Equal)
-        })
-    });
+        // Xcode and the NDK must never be installed automatically
+        assert!(check_xcode().fix_command.is_none());
+        assert!(check_android_ndk_home().fix_command.is_none());
+    }
 
-    if !android_devices.is_empty() {
-        println!("Android Devices ({}):", android_devices.len());
-        println!("{:-<60}", "");
-        for device in &android_devices {
-            println!("  {:40} OS {}", device.device, device.os_version);
-            println!("    --devices \"{}\"", device.identifier());
+    #[test]
+    fn listed_benchmarks_serialize_metadata() {
+        let listed: ListedBenchmark = mobench_sdk::codegen::DetectedBenchmark {
+            name: "sample_fns::sort".into(),
+            group: Some("sorting".into()),
+            params: vec!["setup=make_input".into()],
+            ignored: true,
+            skip_reason: Some("slow".into()),
+            has_setup: true,
+            sample_size: Some(1000),
+            ..Default::default()
         }
-        println!();
+        .into();
+        let value = serde_json::to_value(&listed).unwrap();
+        assert_eq!(value["crate"], "sample_fns");
+        assert_eq!(value["group"], "sorting");
+        assert_eq!(value["params"], json!(["setup=make_input"]));
+        assert_eq!(value["ignored"], true);
+        assert_eq!(value["skip_reason"], "slow");
+        assert_eq!(value["has_setup"], true);
+        assert_eq!(value["sample_size"], 1000);
+        assert_eq!(value["warmup"], Value::Null);
     }
 
-    if !ios_devices.is_empty() {
-        println!("iOS Devices ({}):", ios_devices.len());
-        println!("{:-<60}", "");
-        for device in &ios_devices {
-            println!("  {:40} iOS {}", device.device, device.os_version);
-            println!("    --devices \"{}\"", device.identifier());
+    #[test]
+    fn stale_reports_are_counted() {
+        let spec = mobench_sdk::builders::EmbeddedBenchSpec {
+            function: "sample_fns::fibonacci".to_string(),
+            iterations: 20,
+            warmup: 3,
+            sampling: None,
+            warmup_policy: None,
+            functions: Vec::new(),
+            seed: None,
+            per_benchmark_timeout_secs: None,
+            per_iteration_timeout_ms: None,
+            capture_warmup: false,
+            drop_first_n: 0,
+            startup_metrics: false,
+            pin_cores: Default::default(),
+            interleave: false,
+            env: BTreeMap::new(),
+        };
+        let local = mobench_sdk::builders::create_bench_meta(&spec, "android", "release");
+        let fresh = json!({ "build_meta": { "spec_hash": spec.spec_hash() } });
+        let stale = json!({ "build_meta": { "spec_hash": "0000000000000000" } });
+        let legacy = json!({ "samples_ns": [1, 2, 3] });
+
+        let reports = [("pixel", &fresh), ("iphone", &stale), ("galaxy", &legacy)];
+        assert_eq!(check_stale_reports(&local, reports), 1);
+    }
+
+    fn bench_stats(function: &str, median_ns: u64, p95_ns: u64) -> BenchmarkStats {
+        BenchmarkStats {
+            function: function.into(),
+            samples: 10,
+            mean_ns: Some(median_ns),
+            median_ns: Some(median_ns),
+            p95_ns: Some(p95_ns),
+            p99_ns: Some(p95_ns),
+            min_ns: Some(median_ns),
+            max_ns: Some(p95_ns),
+            cv_pct: None,
+            mom_ns: None,
+            worst_device: None,
+            warmup_ns: Vec::new(),
+            median_ci_pct: None,
+            cpu_median_ns: None,
+            metrics: BTreeMap::new(),
+            timed_out: false,
+            affinity: None,
+            repeat_cv_pct: None,
         }
-        println!();
     }
 
-    println!("Total: {} devices available", devices.len());
-    println!("\nUsage:");
-    println!("  cargo mobench run --target android --devices \"Google Pixel 7-13.0\" ...");
-    println!("  cargo mobench run --target ios --devices \"iPhone 14-16\" ...");
+    #[test]
+    fn detects_regressions_above_threshold() {
+        let report = CompareReport {
+            baseline: "base.json".into(),
+            candidate: "cand.json".into(),
+            baseline_commit: None,
+            candidate_commit: None,
+            warnings: Vec::new(),
+            rows: vec![CompareRow {
+                device: "Pixel 7".into(),
+                function: "sample_fns::fibonacci".into(),
+                baseline_median_ns: Some(1_000_000),
+                candidate_median_ns: Some(1_100_000),
+                median_delta_pct: Some(10.0),
+                baseline_p95_ns: Some(2_000_000),
+                candidate_p95_ns: Some(2_040_000),
+                p95_delta_pct: Some(2.0),
+                baseline_mom_ns: Some(1_000_000),
+                candidate_mom_ns: Some(1_020_000),
+                mom_delta_pct: Some(2.0),
+            }],
+        };
 
-    Ok(())
-}
+        let findings = detect_regressions(&report, &RegressionThresholds::default());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].metric, "median");
+        assert_eq!(findings[0].delta_ns, 100_000);
+        assert!(findings[0].describe().contains("+10.00% / +0.100ms"));
+
+        let strict = RegressionThresholds {
+            pct: 15.0,
+            ..Default::default()
+        };
+        assert!(detect_regressions(&report, &strict).is_empty());
+
+        // The median-of-means estimate only moved 2%, so gating on it passes
+        let mom = RegressionThresholds {
+            primary_metric: PrimaryMetric::Mom,
+            ..Default::default()
+        };
+        assert!(detect_regressions(&report, &mom).is_empty());
+    }
 
-/// Check prerequisites for building mobile artifacts.
-///
-/// This validates that all required tools and configurations are in place
-/// before attempting a build.
-fn cmd_check(target: SdkTarget, format: CheckOutputFormat) -> Result<()> {
-    let mut checks: Vec<PrereqCheck> = Vec::new();
-    let mut issues: Vec<String> = Vec::new();
+    #[test]
+    fn warns_about_meaningless_comparisons() {
+        let spec = |function: &str, iterations: u32| RunSpec {
+            iterations,
+            warmup: 5,
+            ..test_run_spec(function)
+        };
+        let mut summary = RunSummary {
+            schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+            spec: spec("sample_fns::checksum", 100),
+            artifacts: None,
+            local_report: json!({}),
+            remote_run: None,
+            summary: empty_summary(&spec("sample_fns::checksum", 100)),
+            benchmark_results: Some(BTreeMap::from([(
+                "Pixel 7".to_string(),
+                vec![json!({"function": "sample_fns::checksum", "build_meta": {"commit_hash": "0123456789abcdef"}})],
+            )])),
+            performance_metrics: None,
+            device_meta: None,
+            build_meta: None,
+            remote_runs: Vec::new(),
+        };
+        let echoed = summary_build(&summary);
+        assert_eq!(echoed.commit.as_deref(), Some("0123456789abcdef"));
+
+        let mut meta = mobench_sdk::builders::create_bench_meta(
+            &mobench_sdk::builders::EmbeddedBenchSpec {
+                function: "sample_fns::checksum".into(),
+                iterations: 100,
+                warmup: 5,
+                sampling: None,
+                warmup_policy: None,
+                functions: Vec::new(),
+                seed: None,
+                per_benchmark_timeout_secs: None,
+                per_iteration_timeout_ms: None,
+                capture_warmup: false,
+                drop_first_n: 0,
+                startup_metrics: false,
+                pin_cores: Default::default(),
+                interleave: false,
+                env: BTreeMap::new(),
+            },
+            "android",
+            "release",
+        );
+        meta.commit_hash = Some("fedcba9876543210".into());
+        meta.dirty = Some(false);
+        summary.build_meta = Some(meta);
+        let recorded = summary_build(&summary);
+        assert_eq!(recorded.commit.as_deref(), Some("fedcba9876543210"));
+
+        let base = spec("sample_fns::checksum", 100);
+        assert_eq!(
+            comparability_warnings((&base, &recorded), (&base, &echoed)),
+            Vec::<String>::new()
+        );
+        let warnings =
+            comparability_warnings((&base, &recorded), (&spec("sample_fns::fibonacci", 50), &recorded));
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("same commit (fedcba987654)"));
+        assert!(warnings[1].contains("different functions"));
+        assert!(warnings[2].contains("100 iterations / 5 warmup, candidate used 50 / 5"));
+
+        let dirty = SummaryBuild {
+            commit: recorded.commit.clone(),
+            dirty: true,
+            clocks: recorded.clocks.clone(),
+        };
+        assert!(comparability_warnings((&base, &recorded), (&base, &dirty)).is_empty());
 
-    // Common checks for both platforms
-    checks.push(check_cargo());
-    checks.push(check_rustup());
+        assert_eq!(echoed.clocks, BTreeSet::from(["instant".to_string()]));
+        let precise = SummaryBuild {
+            clocks: BTreeSet::from(["monotonic_raw".to_string()]),
+            ..dirty
+        };
+        let warnings = comparability_warnings((&base, &echoed), (&base, &precise));
+        assert_eq!(
+            warnings,
+            vec!["different clocks: baseline timed with instant, candidate with monotonic_raw; timer resolution may differ"]
+        );
 
-    match target {
-        SdkTarget::Android => {
-            println!("Checking prerequisites for Android...\n");
-            checks.push(check_android_ndk_home());
-            checks.push(check_cargo_ndk());
-            checks.push(check_rust_target("aarch64-linux-android"));
-            checks.push(check_rust_target("armv7-linux-androideabi"));
-            checks.push(check_rust_target("x86_64-linux-android"));
-            checks.push(check_jdk());
-        }
-        SdkTarget::Ios => {
-            println!("Checking prerequisites for iOS...\n");
-            checks.push(check_xcode());
-            checks.push(check_xcodegen());
-            checks.push(check_rust_target("aarch64-apple-ios"));
-            checks.push(check_rust_target("aarch64-apple-ios-sim"));
-        }
-        SdkTarget::Both => {
-            println!("Checking prerequisites for Android and iOS...\n");
-            // Android
-            checks.push(check_android_ndk_home());
-            checks.push(check_cargo_ndk());
-            checks.push(check_rust_target("aarch64-linux-android"));
-            checks.push(check_rust_target("armv7-linux-androideabi"));
-            checks.push(check_rust_target("x86_64-linux-android"));
-            checks.push(check_jdk());
-            // iOS
-            checks.push(check_xcode());
-            checks.push(check_xcodegen());
-            checks.push(check_rust_target("aarch64-apple-ios"));
-            checks.push(check_rust_target("aarch64-apple-ios-sim"));
-        }
+        let report = CompareReport {
+            baseline: "base.json".into(),
+            candidate: "cand.json".into(),
+            baseline_commit: recorded.commit.clone(),
+            candidate_commit: None,
+            warnings: vec!["different functions".into()],
+            rows: Vec::new(),
+        };
+        let markdown = render_compare_markdown(&report, None);
+        assert!(markdown.contains("- Baseline: base.json (commit `fedcba987654`)\n- Candidate: cand.json\n"));
+        assert!(markdown.contains("> **Warning:** different functions"));
     }
 
-    // Collect issues
-    for check in &checks {
-        if !check.passed {
-            if let Some(ref fix) = check.fix_hint {
-                issues.push(fix.clone());
-            }
-        }
+    #[test]
+    fn delta_colors_mark_deltas_by_threshold() {
+        let cli = Cli::try_parse_from([
+            "mobench",
+            "compare",
+            "--baseline",
+            "base.json",
+            "--candidate",
+            "cand.json",
+            "--markdown-delta-colors",
+            "--improvement-threshold-pct",
+            "3",
+        ])
+        .unwrap();
+        let Command::Compare { delta_colors, regression, .. } = cli.command else {
+            panic!("expected compare");
+        };
+        let markers = delta_colors.markers(regression.pct).unwrap();
+        assert_eq!(format_marked_delta(Some(-3.5), Some(&markers)), "\u{1f7e2} -3.50%");
+        assert_eq!(format_marked_delta(Some(5.0), Some(&markers)), "\u{1f534} +5.00%");
+        assert_eq!(format_marked_delta(Some(-2.0), Some(&markers)), "\u{26aa} -2.00%");
+        assert_eq!(format_marked_delta(None, Some(&markers)), "-");
+
+        let plain = DeltaMarkers { plain: true, ..markers };
+        assert_eq!(format_marked_delta(Some(12.0), Some(&plain)), "[worse] +12.00%");
+        assert_eq!(format_marked_delta(Some(1.0), None), "+1.00%");
+
+        let without = Cli::try_parse_from([
+            "mobench", "compare", "--baseline", "a.json", "--candidate", "b.json",
+        ])
+        .unwrap();
+        let Command::Compare { delta_colors, .. } = without.command else {
+            panic!("expected compare");
+        };
+        assert!(delta_colors.markers(5.0).is_none());
+        assert!(
+            Cli::try_parse_from([
+                "mobench", "compare", "--baseline", "a.json", "--candidate", "b.json", "--plain",
+            ])
+            .is_err()
+        );
     }
 
-    match format {
-        CheckOutputFormat::Text => print_check_results_text(&checks, &issues),
-        CheckOutputFormat::Json => print_check_results_json(&checks)?,
+    #[test]
+    fn summary_baseline_adds_delta_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, benchmarks: Value| {
+            let path = dir.path().join(name);
+            let summary = json!({
+                "spec": { "target": "android", "function": "f", "iterations": 10, "warmup": 2, "devices": [] },
+                "artifacts": null,
+                "local_report": {},
+                "remote_run": null,
+                "summary": {
+                    "generated_at": "2026-01-01T00:00:00Z",
+                    "generated_at_unix": 0,
+                    "target": "android",
+                    "function": "f",
+                    "iterations": 10,
+                    "warmup": 2,
+                    "devices": ["Pixel 7"],
+                    "device_summaries": [{ "device": "Pixel 7", "benchmarks": benchmarks }],
+                },
+            });
+            fs::write(&path, summary.to_string()).unwrap();
+            path
+        };
+        let bench = |function: &str, median_ns: u64| {
+            json!({ "function": function, "samples": 10, "mean_ns": median_ns, "median_ns": median_ns, "p95_ns": median_ns * 2 })
+        };
+        let baseline = write("base.json", json!([bench("a", 1_000_000), bench("gone", 1_000_000)]));
+        let candidate = write("cand.json", json!([bench("a", 1_100_000)]));
+
+        let comparison = compare_summaries(&baseline, &candidate).unwrap();
+        let value: Value = serde_json::from_str(&fs::read_to_string(&candidate).unwrap()).unwrap();
+        let data = extract_summary_data(&value).unwrap();
+        let table = render_summary_with_baseline(&data, &comparison, true);
+        assert!(table.contains("| Pixel 7 | a | 10 | 1.100ms | 1.100ms | +10.00% | 2.200ms | +10.00% |"), "{table}");
+        assert!(table.contains("| Pixel 7 | gone | - | - | - (base 1.000ms) |"), "{table}");
+        assert!(!render_summary_with_baseline(&data, &comparison, false).contains("gone"));
+        assert!(cmd_summary(&candidate, Some(SummaryFormat::Json), SummaryOutputs::default(), None, Some(&baseline)).is_err());
+
+        // Text-only extras fail before any file is written
+        let samples = dir.path().join("samples.csv");
+        let outputs = SummaryOutputs {
+            sample_out: Some(&samples),
+            histogram_bins: Some(5),
+            ..Default::default()
+        };
+        let err = cmd_summary(&candidate, Some(SummaryFormat::Json), outputs, None, None).unwrap_err();
+        assert!(err.to_string().contains("--histogram only applies to text output"), "{err}");
+        assert!(!samples.exists());
     }
 
-    if issues.is_empty() {
-        Ok(())
-    } else {
-        bail!("{} issue(s) found. Fix them and run 'cargo mobench check --target {:?}' again.", issues.len(), target)
+    #[test]
+    fn flags_suspicious_improvements_only_when_enabled() {
+        let report = CompareReport {
+            baseline: PathBuf::from("base.json"),
+            candidate: PathBuf::from("cand.json"),
+            baseline_commit: None,
+            candidate_commit: None,
+            warnings: Vec::new(),
+            rows: vec![CompareRow {
+                device: "Pixel 7".into(),
+                function: "sample_fns::checksum".into(),
+                baseline_median_ns: Some(1_000_000),
+                candidate_median_ns: Some(50_000),
+                median_delta_pct: Some(-95.0),
+                baseline_p95_ns: Some(2_000_000),
+                candidate_p95_ns: Some(60_000),
+                p95_delta_pct: Some(-97.0),
+                baseline_mom_ns: Some(1_000_000),
+                candidate_mom_ns: Some(500_000),
+                mom_delta_pct: Some(-50.0),
+            }],
+        };
+        assert!(detect_suspicious_improvements(&report, &RegressionThresholds::default()).is_empty());
+
+        let guarded = RegressionThresholds {
+            suspicious_improvement_pct: Some(80.0),
+            ..Default::default()
+        };
+        let suspicious = detect_suspicious_improvements(&report, &guarded);
+        assert_eq!(suspicious.len(), 1);
+        assert_eq!(suspicious[0].metric, "median");
+        assert!(suspicious[0].describe().contains("median -95.00% (1.000ms -> 0.050ms)"));
+        assert!(fail_on_suspicious_improvements(&suspicious).is_err());
+
+        // Gating on median of means: a 50% speedup is plausible
+        let mom = RegressionThresholds {
+            primary_metric: PrimaryMetric::Mom,
+            ..guarded
+        };
+        assert!(detect_suspicious_improvements(&report, &mom).is_empty());
     }
-}
 
-#[derive(Debug, Clone, Serialize)]
-struct PrereqCheck {
-    name: String,
-    passed: bool,
-    detail: Option<String>,
-    fix_hint: Option<String>,
-}
+    #[test]
+    fn median_of_means_discounts_outlier_bursts() {
+        // 16 samples -> 4 groups of 4; one group holds a GC pause
+        let mut samples = vec![100u64; 16];
+        samples[5] = 10_000;
+        let stats = SampleStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.mom_ns, 100);
+        assert!(stats.mean_ns > 700);
 
-fn print_check_results_text(checks: &[PrereqCheck], issues: &[String]) {
-    for check in checks {
-        let status = if check.passed { "\u{2713}" } else { "\u{2717}" };
-        let detail = check.detail.as_deref().unwrap_or("");
-        if detail.is_empty() {
-            println!("{} {}", status, check.name);
-        } else {
-            println!("{} {} ({})", status, check.name, detail);
-        }
+        // Uneven split: 10 samples -> 3 groups of 4, 3, 3
+        assert_eq!(stats::median_of_means(&[1, 1, 1, 1, 5, 5, 5, 9, 9, 9]), 5);
+        assert_eq!(stats::median_of_means(&[42]), 42);
     }
 
-    if !issues.is_empty() {
-        println!("\nTo fix:");
-        for issue in issues {
-            println!("  * {}", issue);
-        }
-        println!();
-        let failed_count = checks.iter().filter(|c| !c.passed).count();
-        println!("{} issue(s) found.", failed_count);
-    } else {
-        println!("\nAll prerequisites satisfied!");
+    #[test]
+    fn absolute_threshold_combines_with_percent() {
+        let both = RegressionThresholds {
+            pct: 5.0,
+            abs_ns: Some(50_000),
+            mode: ThresholdMode::And,
+            ..Default::default()
+        };
+        // +10% but only +20µs: noise for a fast function
+        assert!(!both.exceeded(10.0, 20_000));
+        assert!(both.exceeded(10.0, 60_000));
+        // +60µs on a slow function is under 5%
+        assert!(!both.exceeded(1.0, 60_000));
+
+        let either = RegressionThresholds {
+            mode: ThresholdMode::Or,
+            ..both
+        };
+        assert!(either.exceeded(10.0, 20_000));
+        assert!(either.exceeded(1.0, 60_000));
+        assert!(!either.exceeded(1.0, 20_000));
+        assert_eq!(either.describe(), "5.00% or 0.050ms");
     }
-}
 
-fn print_check_results_json(checks: &[PrereqCheck]) -> Result<()> {
-    let output = json!({
-        "checks": checks,
-        "all_passed": checks.iter().all(|c| c.passed),
-        "passed_count": checks.iter().filter(|c| c.passed).count(),
-        "failed_count": checks.iter().filter(|c| !c.passed).count(),
-    });
-    println!("{}", serde_json::to_string_pretty(&output)?);
-    Ok(())
-}
+    #[test]
+    fn junit_report_includes_suite_time_properties_and_failures() {
+        let summary = SummaryReport {
+            generated_at: "2026-01-01T00:00:00Z".into(),
+            generated_at_unix: 0,
+            target: MobileTarget::Android,
+            function: "sample_fns::fibonacci".into(),
+            iterations: 30,
+            warmup: 5,
+            devices: vec!["Pixel 7".into(), "Pixel 8".into()],
+            device_summaries: vec![
+                DeviceSummary {
+                    device: "Pixel 7".into(),
+                    device_meta: Default::default(),
+                    members: Vec::new(),
+                    benchmarks: vec![bench_stats("sample_fns::fibonacci", 1_500_000_000, 2_000_000_000)],
+                    target: None,
+                },
+                DeviceSummary {
+                    device: "Pixel 8".into(),
+                    device_meta: Default::default(),
+                    members: Vec::new(),
+                    benchmarks: vec![bench_stats("sample_fns::fibonacci", 500_000_000, 600_000_000)],
+                    target: None,
+                },
+            ],
+            noisy_cv_threshold_pct: DEFAULT_NOISY_CV_PCT,
+            budget_violations: Vec::new(),
+            device_retries: BTreeMap::new(),
+            skipped_devices: BTreeMap::new(),
+            device_failures: DeviceFailures::default(),
+            repeat_count: 1,
+            device_status: BTreeMap::new(),
+        };
+        let findings = vec![RegressionFinding {
+            device: "Pixel 8".into(),
+            function: "sample_fns::fibonacci".into(),
+            metric: "median",
+            baseline_ns: 400_000_000,
+            candidate_ns: 500_000_000,
+            delta_pct: 25.0,
+            delta_ns: 100_000_000,
+            threshold: "5.00%".into(),
+        }];
+
+        let xml = render_junit_report(&summary, &findings, &[]);
+        assert!(xml.contains(r#"tests="2" failures="1" errors="0" time="2.000000" timestamp="2026-01-01T00:00:00Z""#));
+        assert!(xml.contains(r#"<property name="iterations" value="30"/>"#));
+        assert!(xml.contains(r#"<property name="warmup" value="5"/>"#));
+        assert!(xml.contains(r#"<testcase classname="Pixel 7" name="sample_fns::fibonacci" time="1.500000">"#));
+        assert!(xml.contains("<system-out>samples=10 mean=1.500s median=1.500s p95=2.000s p99=2.000s</system-out>"));
+        assert_eq!(xml.matches("<failure").count(), 1);
+        assert!(xml.contains("Pixel 8 / sample_fns::fibonacci: median +25.00%"));
+    }
 
-fn check_cargo() -> PrereqCheck {
-    let result = std::process::Command::new("cargo")
-        .arg("--version")
-        .output();
+    #[test]
+    fn budget_violations_fail_the_junit_case() {
+        let budgets: BTreeMap<String, config::FunctionBudget> = toml::from_str(
+            r#"
+["sample_fns::fibonacci"]
+max_median_ms = 1000.0
+
+["sample_fns::fibonacci".devices."Pixel 8"]
+max_p95_ms = 550.0
+"#,
+        )
+        .unwrap();
+        let mut summary = SummaryReport {
+            generated_at: "2026-01-01T00:00:00Z".into(),
+            generated_at_unix: 0,
+            target: MobileTarget::Android,
+            function: "sample_fns::fibonacci".into(),
+            iterations: 30,
+            warmup: 5,
+            devices: vec!["Pixel 7".into(), "Pixel 8".into()],
+            device_summaries: vec![
+                DeviceSummary {
+                    device: "Pixel 7".into(),
+                    device_meta: Default::default(),
+                    members: Vec::new(),
+                    benchmarks: vec![bench_stats("sample_fns::fibonacci", 1_500_000_000, 2_000_000_000)],
+                    target: None,
+                },
+                DeviceSummary {
+                    device: "Pixel 8".into(),
+                    device_meta: Default::default(),
+                    members: Vec::new(),
+                    benchmarks: vec![bench_stats("sample_fns::fibonacci", 500_000_000, 600_000_000)],
+                    target: None,
+                },
+            ],
+            noisy_cv_threshold_pct: DEFAULT_NOISY_CV_PCT,
+            budget_violations: Vec::new(),
+            device_retries: BTreeMap::new(),
+            skipped_devices: BTreeMap::new(),
+            device_failures: DeviceFailures::default(),
+            repeat_count: 1,
+            device_status: BTreeMap::new(),
+        };
 
-    match result {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .to_string();
-            PrereqCheck {
-                name: "cargo installed".to_string(),
-                passed: true,
-                detail: Some(version),
-                fix_hint: None,
-            }
-        }
-        _ => PrereqCheck {
-            name: "cargo installed".to_string(),
-            passed: false,
-            detail: None,
-            fix_hint: Some("Install Rust: https://rustup.rs".to_string()),
-        },
+        let violations = check_budgets(&budgets, summary_budget_entries(&summary));
+        let described: Vec<String> = violations.iter().map(|v| v.describe()).collect();
+        assert_eq!(
+            described,
+            [
+                "Pixel 7 / sample_fns::fibonacci: median 1.500s exceeds budget 1.000s (+50.00%)",
+                "Pixel 8 / sample_fns::fibonacci: p95 600.000ms exceeds budget 550.000ms (+9.09%)",
+            ]
+        );
+        assert!(fail_on_budget_violations(&violations).is_err());
+
+        summary.budget_violations = violations;
+        let xml = render_junit_report(&summary, &[], &[]);
+        assert!(xml.contains(r#"tests="2" failures="2""#));
+        assert_eq!(xml.matches(r#"<failure type="budget""#).count(), 2);
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("## Budget Violations"));
     }
-}
 
-fn check_rustup() -> PrereqCheck {
-    let result = std::process::Command::new("rustup")
-        .arg("--version")
-        .output();
+    #[test]
+    fn device_retries_target_unreported_devices() {
+        let results = BTreeMap::from([("Google Pixel 7".to_string(), vec![json!({"samples": [1]})])]);
+        let requested = vec!["Google Pixel 7-13.0".to_string(), "Samsung Galaxy S23-13.0".to_string()];
+        assert_eq!(
+            orchestrate::missing_devices(&requested, &results),
+            vec!["Samsung Galaxy S23-13.0"]
+        );
 
-    match result {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout)
-                .lines()
-                .next()
-                .unwrap_or("")
-                .trim()
-                .to_string();
-            PrereqCheck {
-                name: "rustup installed".to_string(),
-                passed: true,
-                detail: Some(version),
-                fix_hint: None,
-            }
-        }
-        _ => PrereqCheck {
-            name: "rustup installed".to_string(),
-            passed: false,
-            detail: None,
-            fix_hint: Some("Install rustup: https://rustup.rs".to_string()),
-        },
+        let mut summary = summary_with(
+            MobileTarget::Android,
+            10,
+            vec![("Google Pixel 7", vec![bench_stats("sample_fns::fibonacci", 100, 120)])],
+        );
+        summary.device_retries = BTreeMap::from([
+            ("Google Pixel 7-13.0".to_string(), 1),
+            ("Samsung Galaxy S23-13.0".to_string(), 2),
+        ]);
+        let markdown = render_markdown_summary(&summary);
+        assert!(markdown.contains("## Device Retries"));
+        assert!(markdown.contains("- Google Pixel 7-13.0: 1 retry\n"));
+        assert!(markdown.contains("- Samsung Galaxy S23-13.0: 2 retries, no report\n"));
     }
-}
 
-fn check_android_ndk_home() -> PrereqCheck {
-    match env::var("ANDROID_NDK_HOME") {
-        Ok(path) if !path.is_empty() => {
-            let path_exists = Path::new(&path).exists();
-            if path_exists {
-                PrereqCheck {
-                    name: "ANDROID_NDK_HOME set".to_string(),
-                    passed: true,
-                    detail: Some(path),
-                    fix_hint: None,
-                }
-            } else {
-                PrereqCheck {
-                    name: "ANDROID_NDK_HOME set".to_string(),
-                    passed: false,
-                    detail: Some(format!("path does not exist: {}", path)),
-                    fix_hint: Some("Set ANDROID_NDK_HOME to a valid NDK path: export ANDROID_NDK_HOME=$ANDROID_SDK_ROOT/ndk/<version>".to_string()),
-                }
-            }
-        }
-        _ => PrereqCheck {
-            name: "ANDROID_NDK_HOME set".to_string(),
-            passed: false,
-            detail: None,
-            fix_hint: Some("Set ANDROID_NDK_HOME: export ANDROID_NDK_HOME=$ANDROID_SDK_ROOT/ndk/<version>".to_string()),
-        },
+    #[test]
+    fn reports_session_state_transitions() {
+        let status = |states: &[(&str, &str)]| browserstack::BuildStatus {
+            build_id: "b1".into(),
+            status: "running".into(),
+            duration: None,
+            devices: states
+                .iter()
+                .map(|(id, state)| browserstack::DeviceSession {
+                    device: format!("Pixel {}", id),
+                    session_id: id.to_string(),
+                    status: state.to_string(),
+                    device_logs: None,
+                })
+                .collect(),
+        };
+        let mut last = BTreeMap::new();
+        assert_eq!(
+            session_transitions(&mut last, &status(&[("7", "queued"), ("8", "running")])),
+            vec!["Pixel 7: queued", "Pixel 8: running"]
+        );
+        assert_eq!(
+            session_transitions(&mut last, &status(&[("7", "Running"), ("8", "running")])),
+            vec!["Pixel 7: queued -> running"]
+        );
     }
-}
 
-fn check_cargo_ndk() -> PrereqCheck {
-    let result = std::process::Command::new("cargo")
-        .args(["ndk", "--version"])
-        .output();
+    #[test]
+    fn renders_captured_warmup_ramp() {
+        let mut bench = bench_stats("my::bench", 1_000_000, 1_200_000);
+        assert_eq!(render_warmup_ramp(&bench), None);
+
+        let entry = json!({"warmup_samples": [{"duration_ns": 4_000_000}, {"duration_ns": 1_500_000}]});
+        bench.warmup_ns = extract_warmup_samples(&entry);
+        assert_eq!(
+            render_warmup_ramp(&bench).unwrap(),
+            "my::bench: 4.000 → 1.500 (median 1.000ms; first warmup 4.00x)"
+        );
+    }
 
-    match result {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .to_string();
-            PrereqCheck {
-                name: "cargo-ndk installed".to_string(),
-                passed: true,
-                detail: Some(version),
-                fix_hint: None,
-            }
-        }
-        _ => PrereqCheck {
-            name: "cargo-ndk installed".to_string(),
-            passed: false,
-            detail: None,
-            fix_hint: Some("Install cargo-ndk: cargo install cargo-ndk".to_string()),
-        },
+    #[test]
+    fn resolves_median_ci_sampling_flags() {
+        assert!(resolve_sampling_mode(None, None, "1%").unwrap().is_none());
+        assert_eq!(
+            resolve_sampling_mode(Some(10), Some(200), "2.5%").unwrap(),
+            Some(mobench_sdk::SamplingMode::MedianCi {
+                min_samples: 10,
+                max_samples: 200,
+                target: mobench_sdk::CiTarget::PercentOfMedian(2.5),
+            })
+        );
+        assert_eq!(
+            parse_ci_target("5000ns").unwrap(),
+            mobench_sdk::CiTarget::Nanos(5_000)
+        );
+        assert!(resolve_sampling_mode(Some(50), Some(10), "1%").is_err());
+        assert!(parse_ci_target("-1%").is_err());
+    }
+
+    #[test]
+    fn gbench_json_names_entries_by_function_and_device() {
+        let summary = summary_with(
+            MobileTarget::Android,
+            10,
+            vec![
+                (
+                    "Pixel 7",
+                    vec![bench_stats("fns::a", 100, 150), bench_stats("fns::b", 300, 400)],
+                ),
+                ("iPhone 15", vec![bench_stats("fns::a", 80, 90)]),
+            ],
+        );
+        let mut data = extract_summary_data(&json!({ "summary": summary })).unwrap();
+        // A repeated device/function is rendered once
+        data.push(extract_summary_data(&json!({ "summary": summary })).unwrap().remove(0));
+        data[2].mean_ns = Some(85);
+
+        let gbench = render_gbench_json(&data);
+        assert_eq!(gbench["context"]["executable"], "mobench");
+        let benchmarks = gbench["benchmarks"].as_array().unwrap();
+        assert_eq!(benchmarks.len(), 9);
+        let names: Vec<_> = benchmarks.iter().map(|b| b["name"].as_str().unwrap()).collect();
+        assert_eq!(
+            &names[..3],
+            ["fns::a/Pixel 7", "fns::a/Pixel 7_median", "fns::a/Pixel 7_p95"]
+        );
+
+        let iphone = &benchmarks[6];
+        assert_eq!(iphone["name"], "fns::a/iPhone 15");
+        assert_eq!(iphone["run_type"], "iteration");
+        assert_eq!(iphone["family_index"], 0);
+        assert_eq!(iphone["per_family_instance_index"], 1);
+        assert_eq!(iphone["iterations"], 10);
+        assert_eq!(iphone["real_time"], 85);
+        assert_eq!(iphone["cpu_time"], 85);
+        assert_eq!(iphone["time_unit"], "ns");
+        assert_eq!(benchmarks[3]["family_index"], 1);
+        assert_eq!(benchmarks[8]["aggregate_name"], "p95");
+        assert_eq!(benchmarks[8]["real_time"], 90);
+    }
+
+    #[test]
+    fn top_keeps_worst_entries() {
+        let mut indexed: Vec<(usize, Option<f64>)> =
+            [Some(3.0), None, Some(9.0), Some(-2.0), Some(9.0), Some(5.0)].into_iter().enumerate().collect();
+        top_entries(&mut indexed, 4, |(_, value)| *value);
+        // Ties keep their order; entries without a value never outrank real ones
+        assert_eq!(indexed.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![2, 4, 5, 0]);
+
+        let entry = |function: &str, median_ns: u64, p95_ns: u64| SummaryData {
+            source_file: "summary.json".into(),
+            function: Some(function.into()),
+            device: Some("Pixel 7".into()),
+            os_version: None,
+            sample_count: 10,
+            mean_ns: None,
+            median_ns: Some(median_ns),
+            min_ns: None,
+            max_ns: None,
+            p95_ns: Some(p95_ns),
+            p99_ns: None,
+            iterations: None,
+            warmup: None,
+        };
+        let all = || vec![entry("a", 100, 900), entry("b", 300, 400), entry("c", 200, 500)];
+        let mut data = all();
+        limit_summary_data(&mut data, 2, TopSort::Median, SummaryFormat::Json);
+        let names: Vec<_> = data.iter().map(|d| d.function.clone().unwrap()).collect();
+        assert_eq!(names, vec!["b", "c"]);
+
+        let mut data = all();
+        limit_summary_data(&mut data, 1, TopSort::P95, SummaryFormat::Json);
+        assert_eq!(data[0].function.as_deref(), Some("a"));
+
+        let mut data = all();
+        limit_summary_data(&mut data, 10, TopSort::Median, SummaryFormat::Json);
+        assert_eq!(data.len(), 3);
     }
-}
 
-fn check_rust_target(target: &str) -> PrereqCheck {
-    let result = std::process::Command::new("rustup")
-        .args(["target", "list", "--installed"])
-        .output();
+    #[test]
+    fn resolves_time_based_flags() {
+        assert_eq!(parse_duration_arg("--measure-time", "3s").unwrap(), Duration::from_secs(3));
+        assert_eq!(parse_duration_arg("--warmup-time", "500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration_arg("--measure-time", "1.5").unwrap(), Duration::from_millis(1_500));
+        assert_eq!(parse_duration_arg("--measure-time", "2m").unwrap(), Duration::from_secs(120));
+        assert!(parse_duration_arg("--measure-time", "0s").is_err());
+        assert!(parse_duration_arg("--measure-time", "3h").is_err());
+        assert!(parse_duration_arg("--measure-time", "fast").is_err());
+
+        let mut spec = RunSpec {
+            iterations: 100,
+            warmup: 10,
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        assert_eq!(describe_counts(&spec), ("100".to_string(), "10".to_string()));
+        spec.drop_first_n = 5;
+        assert_eq!(describe_counts(&spec).0, "100, first 5 dropped");
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["drop_first_n"], 5);
+        spec.drop_first_n = 0;
+
+        spec.sampling = Some(mobench_sdk::SamplingMode::FixedTime(Duration::from_secs(3)));
+        spec.warmup_policy = Some(mobench_sdk::WarmupPolicy::FixedTime(Duration::from_secs(1)));
+        assert_eq!(
+            describe_counts(&spec),
+            ("as many as fit in 3s".to_string(), "as many as fit in 1s".to_string())
+        );
+        let payload = mobile_spec_payload(&spec).unwrap();
+        assert_eq!(payload["sampling"]["mode"], "fixed_time");
+        assert_eq!(payload["warmup_policy"]["mode"], "fixed_time");
 
-    match result {
-        Ok(output) if output.status.success() => {
-            let installed = String::from_utf8_lossy(&output.stdout);
-            let has_target = installed.lines().any(|line| line.trim() == target);
-            if has_target {
-                PrereqCheck {
-                    name: format!("Rust target: {}", target),
-                    passed: true,
-                    detail: None,
-                    fix_hint: None,
-                }
-            } else {
-                PrereqCheck {
-                    name: format!("Rust target: {}", target),
-                    passed: false,
-                    detail: Some("not installed".to_string()),
-                    fix_hint: Some(format!("Install target: rustup target add {}", target)),
-                }
-            }
-        }
-        _ => PrereqCheck {
-            name: format!("Rust target: {}", target),
-            passed: false,
-            detail: Some("could not check".to_string()),
-            fix_hint: Some(format!("Install target: rustup target add {}", target)),
-        },
+        let err = Cli::try_parse_from([
+            "mobench", "run", "--target", "android", "--function", "f",
+            "--measure-time", "3s", "--min-samples", "10", "--max-samples", "20",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
     }
-}
 
-fn check_jdk() -> PrereqCheck {
-    // Try java -version
-    let result = std::process::Command::new("java")
-        .arg("-version")
-        .output();
+    #[test]
+    fn suites_leave_unset_counts_to_each_functions_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"suite\"\n").unwrap();
+        fs::write(
+            dir.path().join("src/lib.rs"),
+            "#[benchmark(sample_size = 20)]\nfn slow() {}\n\n#[benchmark]\nfn fast() {}\n",
+        )
+        .unwrap();
+        let functions = vec!["suite::slow".to_string(), "suite::fast".to_string()];
+        let defaults = suite_benchmark_defaults(dir.path(), &functions, true, true);
+        assert_eq!(defaults.len(), 1);
+        assert!(suite_benchmark_defaults(dir.path(), &functions, false, false).is_empty());
+
+        let mut spec: RunSpec = serde_json::from_value(json!({
+            "target": "android",
+            "function": "suite::slow",
+            "functions": functions,
+            "iterations": 100,
+            "warmup": 10,
+            "devices": ["Pixel 7"],
+        }))
+        .unwrap();
+        spec.benchmark_defaults = defaults;
+        assert_eq!(spec.iterations_for("suite::slow"), 20);
+        assert_eq!(spec.iterations_for("suite::fast"), 100);
+        assert_eq!(spec.warmup_for("suite::slow"), 10);
 
-    match result {
-        Ok(output) => {
-            // Java outputs version to stderr
-            let version_output = String::from_utf8_lossy(&output.stderr);
-            let version_line = version_output.lines().next().unwrap_or("");
+        let payload = mobile_spec_payload(&spec).unwrap();
+        assert_eq!(payload["iterations"], mobench_sdk::USE_BENCHMARK_DEFAULT);
+        assert_eq!(payload["warmup"], 10);
+        assert_eq!(describe_counts(&spec).0, "per function (default 100)");
 
-            if output.status.success() || !version_line.is_empty() {
-                PrereqCheck {
-                    name: "JDK installed".to_string(),
-                    passed: true,
-                    detail: Some(version_line.trim().to_string()),
-                    fix_hint: None,
-                }
-            } else {
-                PrereqCheck {
-                    name: "JDK installed".to_string(),
-                    passed: false,
-                    detail: None,
-                    fix_hint: Some("Install JDK 17+: brew install openjdk@17".to_string()),
-                }
-            }
+        let mut summary = summary_with(
+            MobileTarget::Android,
+            100,
+            vec![("Pixel 7", vec![bench_stats("suite::slow", 1, 1), bench_stats("suite::fast", 1, 1)])],
+        );
+        for bench in &mut summary.device_summaries[0].benchmarks {
+            bench.samples = if bench.function == "suite::slow" { 20 } else { 100 };
         }
-        Err(_) => PrereqCheck {
-            name: "JDK installed".to_string(),
-            passed: false,
-            detail: None,
-            fix_hint: Some("Install JDK 17+: brew install openjdk@17".to_string()),
-        },
+        assert!(sample_shortfalls(&spec, &summary).is_empty());
     }
-}
 
-fn check_xcode() -> PrereqCheck {
-    let result = std::process::Command::new("xcodebuild")
-        .arg("-version")
-        .output();
+    #[test]
+    fn startup_metrics_reach_the_android_spec() {
+        let mut spec = RunSpec {
+            iterations: 10,
+            warmup: 2,
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        assert!(mobile_spec_payload(&spec).unwrap().get("startup_metrics").is_none());
+        spec.startup_metrics = true;
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["startup_metrics"], true);
 
-    match result {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout)
-                .lines()
-                .next()
-                .unwrap_or("")
-                .trim()
-                .to_string();
-            PrereqCheck {
-                name: "Xcode installed".to_string(),
-                passed: true,
-                detail: Some(version),
-                fix_hint: None,
-            }
-        }
-        _ => PrereqCheck {
-            name: "Xcode installed".to_string(),
-            passed: false,
-            detail: None,
-            fix_hint: Some("Install Xcode from the App Store or run: xcode-select --install".to_string()),
-        },
+        let cli = Cli::try_parse_from([
+            "mobench", "run", "--target", "android", "--function", "f", "--startup-metrics",
+        ])
+        .unwrap();
+        assert!(matches!(cli.command, Command::Run { startup_metrics: true, .. }));
     }
-}
 
-fn check_xcodegen() -> PrereqCheck {
-    let result = std::process::Command::new("xcodegen")
-        .arg("--version")
-        .output();
+    #[test]
+    fn sample_interleave_reaches_the_payload() {
+        let cli = Cli::try_parse_from([
+            "mobench", "run", "--target", "android", "--function", "a", "--function", "b",
+            "--sample-interleave",
+        ])
+        .unwrap();
+        let Command::Run { sample_interleave, .. } = cli.command else {
+            panic!("expected run");
+        };
+        assert!(sample_interleave);
+        // Interleaving needs a fixed sample count per function
+        assert!(
+            Cli::try_parse_from([
+                "mobench", "run", "--target", "android", "--function", "a", "--function", "b",
+                "--sample-interleave", "--measure-time", "2s",
+            ])
+            .is_err()
+        );
 
-    match result {
-        Ok(output) if output.status.success() => {
-            let version = String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .to_string();
-            PrereqCheck {
-                name: "xcodegen installed".to_string(),
-                passed: true,
-                detail: Some(version),
-                fix_hint: None,
-            }
-        }
-        _ => PrereqCheck {
-            name: "xcodegen installed".to_string(),
-            passed: false,
-            detail: None,
-            fix_hint: Some("Install xcodegen: brew install xcodegen".to_string()),
-        },
+        let mut spec: RunSpec = serde_json::from_value(json!({
+            "target": "android",
+            "function": "a",
+            "functions": ["a", "b"],
+            "iterations": 10,
+            "warmup": 2,
+            "devices": [],
+        }))
+        .unwrap();
+        assert!(mobile_spec_payload(&spec).unwrap().get("interleave").is_none());
+        spec.sample_interleave = true;
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["interleave"], true);
+        assert!(describe_counts(&spec).0.ends_with("interleaved round-robin"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn strict_lists_devices_short_of_samples() {
+        let spec: RunSpec = serde_json::from_value(json!({
+            "target": "android",
+            "function": "fib",
+            "functions": ["fib", "sort"],
+            "iterations": 12,
+            "warmup": 2,
+            "drop_first_n": 2,
+            "devices": ["Pixel 7", "Pixel 8"],
+        }))
+        .unwrap();
+        let mut truncated = bench_stats("fib", 100, 120);
+        truncated.samples = 7;
+        let summary = summary_with(
+            MobileTarget::Android,
+            12,
+            vec![
+                ("Pixel 7", vec![bench_stats("fib", 100, 120), bench_stats("sort", 100, 120)]),
+                ("Pixel 8", vec![truncated]),
+            ],
+        );
+        let shortfalls = sample_shortfalls(&spec, &summary);
+        assert_eq!(
+            shortfalls,
+            vec!["Pixel 8 / fib: 7 of 10 samples", "Pixel 8 / sort: 0 of 10 samples"]
+        );
 
-    // Register a lightweight benchmark for tests so the inventory contains at least one entry.
-    #[mobench_sdk::benchmark]
-    fn noop_benchmark() {
-        std::hint::black_box(1u8);
+        // A device that returned nothing has no summary, and is not a device
+        // failure under the default --fail-fast
+        let mut three_devices = spec.clone();
+        three_devices.devices.push("Galaxy S23".to_string());
+        assert_eq!(
+            sample_shortfalls(&three_devices, &summary)[2..],
+            ["Galaxy S23 / fib: 0 of 10 samples", "Galaxy S23 / sort: 0 of 10 samples"]
+        );
+        let nothing = summary_with(MobileTarget::Android, 12, Vec::new());
+        assert_eq!(sample_shortfalls(&three_devices, &nothing).len(), 6);
+        let err = fail_on_sample_shortfalls(&shortfalls).unwrap_err();
+        assert_eq!(exit_code(&err), 9);
+
+        let mut timed = spec.clone();
+        timed.sampling = Some(mobench_sdk::SamplingMode::FixedTime(Duration::from_secs(1)));
+        assert!(sample_shortfalls(&timed, &summary).is_empty());
+        assert!(
+            Cli::try_parse_from([
+                "mobench", "run", "--target", "android", "--function", "f", "--strict", "--measure-time", "2s",
+            ])
+            .is_err()
+        );
     }
 
     #[test]
-    fn resolves_cli_spec() {
-        let spec = resolve_run_spec(
+    fn promotes_only_functions_every_device_completed() {
+        let spec: RunSpec = serde_json::from_value(json!({
+            "target": "android",
+            "function": "fib",
+            "functions": ["fib", "sort"],
+            "iterations": 10,
+            "warmup": 1,
+            "devices": ["Pixel 7", "Pixel 8"],
+        }))
+        .unwrap();
+        let mut summary = summary_with(
             MobileTarget::Android,
-            "sample_fns::fibonacci".into(),
-            5,
-            1,
-            vec!["pixel".into()],
-            None,
-            None,
-            None,
-            false,
-            false, // release
-        )
+            10,
+            vec![
+                ("Pixel 7", vec![bench_stats("fib", 100, 120), bench_stats("sort", 100, 120)]),
+                ("Pixel 8", vec![bench_stats("fib", 100, 120)]),
+            ],
+        );
+        assert_eq!(promotable_functions(&spec, &summary), vec!["fib"]);
+
+        // A device without results keeps the whole run out of the store
+        summary.device_status.insert("Pixel 9".to_string(), DeviceStatus::Missing);
+        assert!(promotable_functions(&spec, &summary).is_empty());
+        let empty = summary_with(MobileTarget::Android, 10, Vec::new());
+        assert!(promotable_functions(&spec, &empty).is_empty());
+    }
+
+    #[test]
+    fn execution_order_is_recorded_and_replayable() {
+        let cli = Cli::try_parse_from([
+            "mobench", "run", "--target", "android", "--function", "a", "--function", "b",
+            "--order", "shuffle:3",
+        ])
         .unwrap();
-        assert_eq!(spec.function, "sample_fns::fibonacci");
-        assert_eq!(spec.iterations, 5);
-        assert_eq!(spec.warmup, 1);
-        assert_eq!(spec.devices, vec!["pixel".to_string()]);
-        assert!(spec.browserstack.is_none());
-        assert!(spec.ios_xcuitest.is_none());
+        let Command::Run { order, .. } = cli.command else {
+            panic!("expected run");
+        };
+        assert_eq!(order, ExecutionOrder::Shuffle(Some(3)));
+        assert!(
+            Cli::try_parse_from(["mobench", "run", "--target", "android", "--function", "a", "--order", "random"])
+                .is_err()
+        );
+
+        let previous: RunSpec = serde_json::from_value(json!({
+            "target": "android",
+            "function": "b",
+            "functions": ["b", "c", "a"],
+            "iterations": 10,
+            "warmup": 2,
+            "devices": [],
+            "order": "shuffle:3",
+        }))
+        .unwrap();
+        assert_eq!(previous.order, order);
+        assert!(describe_counts(&previous).0.ends_with("shuffle:3 order"));
+
+        // Resuming with the functions in another order is the same suite
+        let mut resumed = previous.clone();
+        resumed.functions = vec!["a".into(), "b".into(), "c".into()];
+        resumed.order = ExecutionOrder::Declared;
+        assert!(check_resumable(&previous, &resumed).is_ok());
+        resumed.functions.pop();
+        assert!(check_resumable(&previous, &resumed).is_err());
     }
 
     #[test]
-    fn local_smoke_produces_samples() {
-        let spec = RunSpec {
-            target: MobileTarget::Android,
-            function: "noop_benchmark".into(),
-            iterations: 3,
-            warmup: 1,
-            devices: vec![],
-            browserstack: None,
-            ios_xcuitest: None,
+    fn env_flags_override_config_and_reach_the_spec() {
+        let cli = Cli::try_parse_from([
+            "mobench", "run", "--target", "ios", "--function", "f", "--env", "MODE=fast", "--env", "INPUT=a=b",
+        ])
+        .unwrap();
+        let Command::Run { env_vars, .. } = cli.command else {
+            panic!("expected run");
         };
-        let report = run_local_smoke(&spec).expect("local harness");
-        assert!(report["samples"].is_array());
-        assert_eq!(report["spec"]["name"], "noop_benchmark");
+        let config = BTreeMap::from([("MODE".to_string(), "slow".to_string()), ("FLAG".to_string(), "1".to_string())]);
+        let env = resolve_bench_env(config, &env_vars).unwrap();
+        assert_eq!(env["INPUT"], "a=b");
+        assert_eq!(describe_env(&env), "FLAG, INPUT, MODE");
+
+        for bad in ["NOVALUE", "1ST=x", "MY-FLAG=x", "=x", "MOBENCH_SPEC=x", "PATH=/tmp", "DYLD_INSERT_LIBRARIES=x"] {
+            assert!(resolve_bench_env(BTreeMap::new(), &[bad.to_string()]).is_err(), "{bad}");
+        }
+
+        let mut spec: RunSpec = serde_json::from_value(json!({
+            "target": "ios",
+            "function": "f",
+            "iterations": 10,
+            "warmup": 2,
+            "devices": [],
+        }))
+        .unwrap();
+        assert!(mobile_spec_payload(&spec).unwrap().get("env").is_none());
+        let previous = spec.clone();
+        spec.env = env;
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["env"]["INPUT"], "a=b");
+        assert!(check_resumable(&previous, &spec).is_err());
+
+        // Summaries keep the names and a digest of each value
+        let mut run_summary = RunSummary {
+            schema_version: RUN_SUMMARY_SCHEMA_VERSION,
+            summary: empty_summary(&spec),
+            spec: spec.clone(),
+            artifacts: None,
+            local_report: json!({}),
+            remote_run: None,
+            remote_runs: Vec::new(),
+            benchmark_results: Some(BTreeMap::from([(
+                "iPhone 15".to_string(),
+                vec![json!({ "function": "f", "samples": [1], "env": { "INPUT": "a=b" } })],
+            )])),
+            performance_metrics: None,
+            device_meta: None,
+            build_meta: None,
+        };
+        redact_env(&mut run_summary);
+        let written = serde_json::to_string(&run_summary).unwrap();
+        assert!(!written.contains("a=b"), "{written}");
+        assert_eq!(run_summary.spec.env["INPUT"], redacted_env_value("INPUT", "a=b"));
+        let report_env = &run_summary.benchmark_results.as_ref().unwrap()["iPhone 15"][0]["env"];
+        assert_eq!(report_env["INPUT"], json!(redacted_env_value("INPUT", "a=b")));
+        assert!(check_resumable(&run_summary.spec, &spec).is_ok());
+
+        // A resumed run's results are not digested twice
+        let summary_spec = run_summary.spec.clone();
+        redact_env(&mut run_summary);
+        assert_eq!(run_summary.spec.env, summary_spec.env);
+
+        // Same names, different value: the samples would not be comparable
+        let mut changed = spec.clone();
+        changed.env.insert("MODE".to_string(), "slow".to_string());
+        let err = check_resumable(&run_summary.spec, &changed).unwrap_err();
+        assert!(err.to_string().contains("MODE"), "{err}");
+
+        // Summaries from before digests cannot vouch for their values
+        let mut legacy = run_summary.spec.clone();
+        legacy.env.values_mut().for_each(|value| *value = REDACTED_ENV_VALUE.to_string());
+        assert!(check_resumable(&legacy, &spec).is_err());
     }
 
     #[test]
-    fn ios_requires_artifacts_for_browserstack() {
-        let spec = resolve_run_spec(
-            MobileTarget::Ios,
-            "sample_fns::fibonacci".into(),
-            1,
-            0,
-            vec!["iphone".into()],
-            None,
-            None,
-            None,
-            false,
-            false, // release
-        )
-        .expect("should auto-package iOS artifacts when missing");
-        let ios_artifacts = spec
-            .ios_xcuitest
-            .expect("iOS artifacts should be populated");
-        assert!(ios_artifacts.app.exists(), "iOS app artifact missing");
+    fn dry_run_plan_redacts_env_values() {
+        let mut spec: RunSpec = serde_json::from_value(json!({
+            "target": "android",
+            "function": "f",
+            "iterations": 10,
+            "warmup": 2,
+            "devices": [],
+        }))
+        .unwrap();
+        spec.env = BTreeMap::from([("API_TOKEN".to_string(), "s3cr3t-value".to_string())]);
+        let plan = render_dry_run_spec(&spec).unwrap();
+        assert!(!plan.contains("s3cr3t-value"), "{plan}");
+        assert!(plan.contains("API_TOKEN"));
+        assert!(plan.contains("Would write bench_spec.json"));
+    }
+
+    #[test]
+    fn per_iteration_timeout_reaches_bench_spec() {
+        let cli = Cli::try_parse_from([
+            "mobench", "run", "--target", "ios", "--function", "f", "--per-iteration-timeout", "1.5ms",
+        ])
+        .unwrap();
+        let Command::Run { per_iteration_timeout, .. } = cli.command else {
+            panic!("expected run");
+        };
+        let mut spec = test_run_spec("f");
+        assert!(mobile_spec_payload(&spec).unwrap().get("per_iteration_timeout_ms").is_none());
+        spec.per_iteration_timeout_ms = Some(iteration_timeout_ms(per_iteration_timeout.as_deref().unwrap()).unwrap());
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["per_iteration_timeout_ms"], 2);
+        assert_eq!(iteration_timeout_ms("2s").unwrap(), 2000);
+        assert!(iteration_timeout_ms("soon").is_err());
+    }
+
+    #[test]
+    fn capture_warmup_reaches_bench_spec() {
+        let cli = Cli::try_parse_from(["mobench", "run", "--target", "android", "--function", "f", "--capture-warmup"])
+            .unwrap();
+        let Command::Run { capture_warmup, .. } = cli.command else {
+            panic!("expected run");
+        };
+        let mut spec = test_run_spec("f");
+        assert!(mobile_spec_payload(&spec).unwrap().get("capture_warmup").is_none());
+        spec.capture_warmup = capture_warmup;
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["capture_warmup"], true);
+    }
+
+    #[test]
+    fn pinned_cores_reach_the_spec_and_summary() {
+        let cli = Cli::try_parse_from([
+            "mobench", "run", "--target", "android", "--function", "f", "--pin-cores", "big",
+        ])
+        .unwrap();
+        let Command::Run { pin_cores, .. } = cli.command else {
+            panic!("expected run");
+        };
+        assert_eq!(pin_cores, CorePinning::Big);
         assert!(
-            ios_artifacts.test_suite.exists(),
-            "iOS test suite artifact missing"
+            Cli::try_parse_from([
+                "mobench", "run", "--target", "android", "--function", "f", "--pin-cores", "mid",
+            ])
+            .is_err()
+        );
+
+        let mut spec: RunSpec = serde_json::from_value(json!({
+            "target": "android",
+            "function": "sample_fns::fibonacci",
+            "iterations": 10,
+            "warmup": 2,
+            "devices": [],
+        }))
+        .unwrap();
+        assert!(mobile_spec_payload(&spec).unwrap().get("pin_cores").is_none());
+        spec.pin_cores = pin_cores;
+        assert_eq!(mobile_spec_payload(&spec).unwrap()["pin_cores"], "big");
+
+        let entry = json!({ "function": "f", "samples_ns": [10], "affinity": "big cores 4-7" });
+        assert_eq!(report_affinity(&entry).as_deref(), Some("big cores 4-7"));
+        let bench: BenchmarkStats = serde_json::from_value(json!({
+            "function": "f",
+            "samples": 1,
+            "affinity": report_affinity(&entry),
+        }))
+        .unwrap();
+        let summary = SummaryReport {
+            generated_at: "2026-01-01T00:00:00Z".into(),
+            generated_at_unix: 0,
+            target: MobileTarget::Android,
+            function: "f".into(),
+            iterations: 10,
+            warmup: 2,
+            devices: vec!["Google Pixel 7-13.0".into()],
+            device_summaries: vec![DeviceSummary {
+                device: "Google Pixel 7".into(),
+                device_meta: Default::default(),
+                members: Vec::new(),
+                benchmarks: vec![bench],
+                target: None,
+            }],
+            noisy_cv_threshold_pct: DEFAULT_NOISY_CV_PCT,
+            budget_violations: Vec::new(),
+            device_retries: BTreeMap::new(),
+            skipped_devices: BTreeMap::new(),
+            device_failures: DeviceFailures::default(),
+            repeat_count: 1,
+            device_status: BTreeMap::new(),
+        };
+        assert!(render_markdown_summary(&summary).contains("- core pinning: big cores 4-7\n"));
+    }
+
+    #[test]
+    fn materializes_browserstack_baseline() {
+        let spec = RunSpec {
+            iterations: 100,
+            warmup: 10,
+            devices: vec!["Google Pixel 7-13.0".into(), "Samsung Galaxy S23-13.0".into()],
+            ..test_run_spec("sample_fns::fibonacci")
+        };
+        let report = json!({
+            "function": "sample_fns::fibonacci",
+            "spec": { "name": "sample_fns::fibonacci", "iterations": 50, "warmup": 5 },
+            "samples": [{ "duration_ns": 1_000 }, { "duration_ns": 3_000 }],
+        });
+        let results = (
+            std::collections::HashMap::from([("Google Pixel 7-13.0".to_string(), vec![report])]),
+            Default::default(),
+            Default::default(),
         );
+
+        let baseline = browserstack_baseline_summary(&spec, results).unwrap();
+        assert_eq!(baseline.spec.devices, vec!["Google Pixel 7-13.0".to_string()]);
+        assert_eq!((baseline.spec.iterations, baseline.spec.warmup), (50, 5));
+        let bench = &baseline.summary.device_summaries[0].benchmarks[0];
+        assert_eq!(bench.function, "sample_fns::fibonacci");
+        assert_eq!(bench.mean_ns, Some(2_000));
+
+        let err = Cli::try_parse_from([
+            "mobench", "run", "--target", "android", "--function", "f",
+            "--baseline-store", "baselines", "--baseline-from-browserstack", "abc123",
+        ])
+        .unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn projects_run_time_from_one_iteration() {
+        let mut spec = RunSpec {
+            iterations: 10_000_000,
+            warmup: 10,
+            devices: vec!["Google Pixel 7-13.0".into()],
+            ..test_run_spec("missing::bench")
+        };
+        let per_iteration = Duration::from_millis(50);
+        assert_eq!(projected_run_time(&spec, "f", per_iteration), Duration::from_millis(50 * 10_000_010));
+
+        spec.sampling = Some(mobench_sdk::SamplingMode::FixedTime(Duration::from_secs(3)));
+        spec.warmup_policy = Some(mobench_sdk::WarmupPolicy::FixedTime(Duration::from_secs(1)));
+        assert_eq!(projected_run_time(&spec, "f", per_iteration), Duration::from_secs(4));
+
+        // Time-bounded runs project the same without timing the function
+        assert!(check_projected_run_time(&spec, Duration::from_secs(1), false).is_err());
+        check_projected_run_time(&spec, Duration::from_secs(5), false).unwrap();
+
+        // Count-bounded functions that cannot be timed locally skip the cap
+        spec.sampling = None;
+        check_projected_run_time(&spec, Duration::from_secs(1), false).unwrap();
+    }
+
+    #[test]
+    fn surfaces_median_ci_in_markdown() {
+        let sampling = mobench_sdk::SamplingMode::MedianCi {
+            min_samples: 10,
+            max_samples: 100,
+            target: mobench_sdk::CiTarget::PercentOfMedian(1.0),
+        };
+        let samples: Vec<u64> = (0..40).map(|i| 1_000_000 + (i % 5) * 10_000).collect();
+        assert_eq!(median_ci_pct(&json!({}), &samples, None), None);
+        let pct = median_ci_pct(&json!({}), &samples, Some(&sampling)).unwrap();
+        assert!(pct > 0.0 && pct < 5.0, "{pct}");
+
+        let entry = json!({"median_ci": {
+            "median_ns": 1_000_000, "lower_ns": 990_000, "upper_ns": 1_010_000, "confidence": 0.95
+        }});
+        let mut bench = bench_stats("my::bench", 1_000_000, 1_200_000);
+        bench.median_ci_pct = median_ci_pct(&entry, &samples, None);
+        assert_eq!(format_median_with_ci(&bench), "1.000ms ±1.0%");
     }
 
     #[test]
@@ -0,0 +1,414 @@
+//! Local device backend for `--devices local` and `[backend] provider = "local"`.
+//!
+//! Runs benchmarks on hardware attached to this machine instead of
+//! BrowserStack:
+//!
+//! - **Android**: installs the app and test APKs with `adb install`, runs the
+//!   Espresso instrumentation with `adb shell am instrument -w`, then reads the
+//!   report from `adb logcat`.
+//! - **iOS**: installs and launches the app with `ios-deploy`, reading the
+//!   report from the app's console output. The app keeps running after it
+//!   reports, so the process is stopped once the end marker is seen or the
+//!   timeout expires.
+//!
+//! Reports are parsed with the same marker extraction as BrowserStack device
+//! logs. Devices are addressed by serial (Android) or UDID (iOS); `local`
+//! selects every attached device.
+
+use crate::MobileTarget;
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Device name that selects every attached device.
+pub(crate) const ALL_LOCAL_DEVICES: &str = "local";
+
+const INSTRUMENTATION_RUNNER: &str = "androidx.test.runner.AndroidJUnitRunner";
+const REPORT_END_MARKER: &str = "BENCH_REPORT_JSON_END";
+
+/// Reports and failures from [`LocalDeviceBackend::run`].
+#[derive(Debug, Default)]
+pub(crate) struct LocalRun {
+    /// Reports keyed by [`LocalDevice::label`].
+    pub(crate) results: BTreeMap<String, Vec<Value>>,
+    /// Devices that failed to install, run or report, with the error.
+    pub(crate) failures: BTreeMap<String, String>,
+}
+
+/// A device attached over USB (or a running emulator).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LocalDevice {
+    /// `adb` serial or iOS UDID.
+    pub(crate) id: String,
+    /// Human-readable model name, if the tool reported one.
+    pub(crate) model: Option<String>,
+}
+
+impl LocalDevice {
+    /// Name used as the device key in run summaries.
+    pub(crate) fn label(&self) -> String {
+        match &self.model {
+            Some(model) => format!("{} ({})", model, self.id),
+            None => self.id.clone(),
+        }
+    }
+
+    fn matches(&self, requested: &str) -> bool {
+        requested == self.id
+            || self
+                .model
+                .as_deref()
+                .is_some_and(|model| model.eq_ignore_ascii_case(requested))
+    }
+}
+
+/// Artifacts to install on each device.
+pub(crate) enum LocalArtifacts<'a> {
+    Android {
+        apk: &'a Path,
+        test_apk: &'a Path,
+        /// Application ID of the app under test (e.g. `dev.world.benchmobile`).
+        app_id: &'a str,
+    },
+    Ios {
+        /// `.app` bundle or `.ipa`.
+        app: &'a Path,
+    },
+}
+
+/// Runs benchmarks on locally attached devices.
+pub(crate) struct LocalDeviceBackend {
+    target: MobileTarget,
+    timeout: Duration,
+}
+
+impl LocalDeviceBackend {
+    pub(crate) fn new(target: MobileTarget, timeout: Duration) -> Self {
+        Self { target, timeout }
+    }
+
+    /// List attached devices that are ready to run benchmarks.
+    pub(crate) fn discover(&self) -> Result<Vec<LocalDevice>> {
+        match self.target {
+            MobileTarget::Android => {
+                let output = run_tool("adb", &["devices", "-l"])?;
+                Ok(parse_adb_devices(&output))
+            }
+            MobileTarget::Ios => {
+                let output = run_tool("ios-deploy", &["--detect", "--timeout", "2"])?;
+                Ok(parse_ios_deploy_devices(&output))
+            }
+        }
+    }
+
+    /// Resolve `requested` device names against the attached devices.
+    ///
+    /// `local` selects every attached device; other entries match a serial,
+    /// UDID or model name.
+    pub(crate) fn select(&self, requested: &[String]) -> Result<Vec<LocalDevice>> {
+        let attached = self.discover()?;
+        if attached.is_empty() {
+            bail!(
+                "no local {:?} devices attached; check `{}`",
+                self.target,
+                match self.target {
+                    MobileTarget::Android => "adb devices",
+                    MobileTarget::Ios => "ios-deploy --detect",
+                }
+            );
+        }
+        if requested.iter().any(|name| name == ALL_LOCAL_DEVICES) {
+            return Ok(attached);
+        }
+        requested
+            .iter()
+            .map(|name| {
+                attached
+                    .iter()
+                    .find(|device| device.matches(name))
+                    .cloned()
+                    .with_context(|| {
+                        let ids: Vec<String> = attached.iter().map(LocalDevice::label).collect();
+                        format!(
+                            "local device {:?} is not attached; attached: {}",
+                            name,
+                            ids.join(", ")
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Install and run the benchmark on each device, returning reports keyed
+    /// by [`LocalDevice::label`].
+    ///
+    /// With `fail_fast` the first failing device aborts the run; otherwise
+    /// every device runs and failures are returned in [`LocalRun::failures`].
+    /// Each device's reports are also streamed to `json_lines` once it
+    /// finishes, and the run keeps what the writer retains of them.
+    pub(crate) fn run(
+        &self,
+        devices: &[LocalDevice],
+        artifacts: &LocalArtifacts<'_>,
+        fail_fast: bool,
+        json_lines: Option<&crate::json_lines::JsonLinesWriter>,
+    ) -> Result<LocalRun> {
+        let mut run = LocalRun::default();
+        for device in devices {
+            let label = device.label();
+            println!("Running on local device {}...", label);
+            match self.run_device(device, artifacts) {
+                Ok(reports) => {
+                    let reports = match json_lines {
+                        Some(writer) => writer.stream(&label, None, reports),
+                        None => reports,
+                    };
+                    run.results.insert(label, reports);
+                }
+                Err(error) if !fail_fast => {
+                    println!("Warning: {} failed: {:#}", label, error);
+                    run.failures.insert(label, format!("{:#}", error));
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(run)
+    }
+
+    fn run_device(&self, device: &LocalDevice, artifacts: &LocalArtifacts<'_>) -> Result<Vec<Value>> {
+        let label = device.label();
+        let start = Instant::now();
+        let logs = match artifacts {
+            LocalArtifacts::Android {
+                apk,
+                test_apk,
+                app_id,
+            } => self.run_android(device, apk, test_apk, app_id)?,
+            LocalArtifacts::Ios { app } => self.run_ios(device, app)?,
+        };
+        let reports = crate::extract_all_bench_json(&logs);
+        if reports.is_empty() {
+            bail!("no benchmark report found in {} logs", label);
+        }
+        tracing::info!(
+            event = "local_run",
+            device = %label,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "ran benchmark on local device"
+        );
+        Ok(reports.into_iter().map(normalize_report).collect())
+    }
+
+    fn run_android(
+        &self,
+        device: &LocalDevice,
+        apk: &Path,
+        test_apk: &Path,
+        app_id: &str,
+    ) -> Result<String> {
+        let adb = |args: &[&str]| {
+            let mut full = vec!["-s", device.id.as_str()];
+            full.extend_from_slice(args);
+            run_tool("adb", &full)
+        };
+        for path in [apk, test_apk] {
+            let path = path.to_str().context("APK path is not valid UTF-8")?;
+            adb(&["install", "-r", "-t", path])?;
+        }
+        adb(&["logcat", "-c"])?;
+        let instrumentation = format!("{}.test/{}", app_id, INSTRUMENTATION_RUNNER);
+        let output = adb(&["shell", "am", "instrument", "-w", &instrumentation])?;
+        // `am instrument` exits 0 even when tests fail; the status is in its output
+        if output.contains("FAILURES!!!") || output.contains("INSTRUMENTATION_FAILED") {
+            println!(
+                "Warning: instrumentation reported failures on {}:\n{}",
+                device.label(),
+                output.trim()
+            );
+        }
+        adb(&["logcat", "-d", "-v", "threadtime"])
+    }
+
+    fn run_ios(&self, device: &LocalDevice, app: &Path) -> Result<String> {
+        let app = app.to_str().context("iOS app path is not valid UTF-8")?;
+        let mut child = Command::new("ios-deploy")
+            .args(["--id", &device.id, "--bundle", app, "--noninteractive"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("running ios-deploy; install it with `brew install ios-deploy`")?;
+
+        let mut stdout = child.stdout.take().context("capturing ios-deploy output")?;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            while let Ok(n) = stdout.read(&mut buf) {
+                if n == 0 || tx.send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let deadline = Instant::now() + self.timeout;
+        let mut logs = Vec::new();
+        while !String::from_utf8_lossy(&logs).contains(REPORT_END_MARKER) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(chunk) => logs.extend(chunk),
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let _ = child.kill();
+                    bail!(
+                        "timed out after {}s waiting for a benchmark report from {}",
+                        self.timeout.as_secs(),
+                        device.label()
+                    );
+                }
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+        Ok(String::from_utf8_lossy(&logs).into_owned())
+    }
+}
+
+/// Read the application ID from a generated `app/build.gradle`.
+pub(crate) fn android_application_id(build_gradle: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(build_gradle)
+        .with_context(|| format!("reading {:?} to find the application ID", build_gradle))?;
+    parse_application_id(&contents)
+        .with_context(|| format!("no applicationId found in {:?}", build_gradle))
+}
+
+fn parse_application_id(gradle: &str) -> Option<String> {
+    gradle.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("applicationId")?;
+        let id = rest.trim().trim_start_matches('=').trim().trim_matches(['"', '\'']);
+        (!id.is_empty()).then(|| id.to_string())
+    })
+}
+
+/// Parse `adb devices -l`, keeping only devices in the `device` state.
+fn parse_adb_devices(output: &str) -> Vec<LocalDevice> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("List of devices"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let id = fields.next()?;
+            if fields.next()? != "device" {
+                return None;
+            }
+            let model = fields
+                .find_map(|field| field.strip_prefix("model:"))
+                .map(|model| model.replace('_', " "));
+            Some(LocalDevice {
+                id: id.to_string(),
+                model,
+            })
+        })
+        .collect()
+}
+
+/// Parse `ios-deploy --detect` lines such as
+/// `[....] Found 00008030-001A (D421AP, iPhone 11 Pro, iphoneos, arm64e, 17.4) a.k.a. ...`.
+fn parse_ios_deploy_devices(output: &str) -> Vec<LocalDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = &line[line.find("Found ")? + "Found ".len()..];
+            let (id, details) = rest.split_once(" (")?;
+            let model = details
+                .split(',')
+                .nth(1)
+                .map(|model| model.trim().to_string())
+                .filter(|model| !model.is_empty());
+            Some(LocalDevice {
+                id: id.trim().to_string(),
+                model,
+            })
+        })
+        .collect()
+}
+
+/// Shape a raw device report like a BrowserStack result entry: the Android
+/// runner logs `samples_ns` and no `function`, which summaries expect.
+fn normalize_report(mut report: Value) -> Value {
+    let Some(object) = report.as_object_mut() else {
+        return report;
+    };
+    if !object.contains_key("function")
+        && let Some(name) = object.get("spec").and_then(|spec| spec.get("name")).cloned()
+    {
+        object.insert("function".into(), name);
+    }
+    if !object.contains_key("samples")
+        && let Some(samples) = object.get("samples_ns").cloned()
+    {
+        object.insert("samples".into(), samples);
+    }
+    report
+}
+
+fn run_tool(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("running {}; is it installed and on PATH?", program))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_attached_devices() {
+        let adb = "* daemon started successfully\n\
+                   List of devices attached\n\
+                   R58M12345      device usb:1-1 product:beyond1 model:SM_G973F device:beyond1 transport_id:1\n\
+                   emulator-5554  offline\n\
+                   0123ABCD       unauthorized usb:1-2 transport_id:3\n\n";
+        assert_eq!(
+            parse_adb_devices(adb),
+            vec![LocalDevice {
+                id: "R58M12345".into(),
+                model: Some("SM G973F".into()),
+            }]
+        );
+
+        let ios = "[....] Waiting up to 2 seconds for iOS device to be connected\n\
+                   [....] Found 00008030-001A2C3E0C38802E (D421AP, iPhone 11 Pro, iphoneos, arm64e, 17.4, 21E219) a.k.a. 'Desk Phone' connected through USB.\n";
+        let devices = parse_ios_deploy_devices(ios);
+        assert_eq!(devices[0].label(), "iPhone 11 Pro (00008030-001A2C3E0C38802E)");
+        assert!(devices[0].matches("iphone 11 pro"));
+
+        let gradle = "android {\n    defaultConfig {\n        applicationId \"dev.world.benchmobile\"\n";
+        assert_eq!(parse_application_id(gradle).as_deref(), Some("dev.world.benchmobile"));
+    }
+
+    #[test]
+    fn normalizes_android_reports() {
+        let report = normalize_report(json!({
+            "spec": {"name": "sample_fns::fibonacci", "iterations": 2},
+            "samples_ns": [1000, 1200]
+        }));
+        assert_eq!(report["function"], "sample_fns::fibonacci");
+        assert_eq!(crate::extract_samples(&report), vec![1000, 1200]);
+    }
+}
@@ -0,0 +1,74 @@
+//! Structured logging for CI and observability pipelines.
+//!
+//! The CLI's human-readable progress output stays on stdout. Alongside it,
+//! key lifecycle steps emit `tracing` events:
+//!
+//! | `event` | Fields |
+//! |---------|--------|
+//! | `build` | `target`, `elapsed_ms` (in a `build` span) |
+//! | `upload` | `kind`, `bytes`, `elapsed_ms` |
+//! | `schedule` | `platform`, `build_id`, `devices` |
+//! | `fetch` | `build_id`, `status`, `devices` |
+//! | `http_retry` | `attempt`, `delay_ms`, `error` (warn level) |
+//! | `device_retry` | `attempt`, `build_id`, `devices` |
+//! | `local_run` | `device`, `elapsed_ms` |
+//! | `regression` | `device`, `function`, `metric`, `baseline_ns`, `candidate_ns`, `delta_pct` |
+//! | `suspicious_improvement` | `device`, `function`, `metric`, `baseline_ns`, `candidate_ns`, `delta_pct` |
+//! | `budget_violation` | `device`, `function`, `metric`, `budget_ns`, `actual_ns` |
+//!
+//! Events go to stderr. With `--log-format json` they are JSON lines at
+//! `info` and above; with the default `--log-format text` they are off so the
+//! human output is unchanged. `RUST_LOG` overrides the level in both modes,
+//! e.g. `RUST_LOG=mobench=debug`.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use std::time::Instant;
+use tracing_subscriber::EnvFilter;
+
+/// Log output format for `--log-format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum LogFormat {
+    /// Human-readable output only; tracing events are off unless `RUST_LOG` is set
+    #[default]
+    Text,
+    /// Lifecycle events as JSON lines on stderr
+    Json,
+}
+
+/// Install the global tracing subscriber for `format`.
+///
+/// Safe to call more than once; later calls are ignored.
+pub(crate) fn init(format: LogFormat) {
+    let default_level = match format {
+        LogFormat::Text => "off",
+        LogFormat::Json => "info",
+    };
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    let _ = match format {
+        LogFormat::Text => builder.with_ansi(false).try_init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .try_init(),
+    };
+}
+
+/// Run `f` as a lifecycle step, logging its duration or its error.
+pub(crate) fn step<T>(event: &'static str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    tracing::info!(event, "{} started", event);
+    let start = Instant::now();
+    let result = f();
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    match &result {
+        Ok(_) => tracing::info!(event, elapsed_ms, "{} finished", event),
+        Err(err) => tracing::error!(event, elapsed_ms, error = %format!("{err:#}"), "{} failed", event),
+    }
+    result
+}
@@ -0,0 +1,328 @@
+//! Metrics export for `run --otlp-endpoint` and `run --prometheus-out`.
+//!
+//! Turns a run summary into one gauge per statistic (`mobench_median_ns`,
+//! `mobench_p95_ns`, ...), with a data point per device and function, so
+//! results land in an existing metrics backend as a time series. Every point
+//! is labeled with:
+//!
+//! - `target`, `device` and `function`
+//! - `os_version`, from the device metadata or the `<device>-<version>` name
+//! - `git_sha`, the commit the benchmarks were built from
+//!
+//! The module is only compiled with the `metrics-export` feature. Two sinks
+//! are supported:
+//!
+//! - `--otlp-endpoint` POSTs OTLP/HTTP JSON to `<endpoint>/v1/metrics`
+//!   (the path is appended unless the URL already ends with it). Delivery
+//!   problems are reported as warnings and never fail the run.
+//! - `--prometheus-out` writes the Prometheus text format, ready for
+//!   `curl --data-binary @file <pushgateway>/metrics/job/mobench`. Samples
+//!   carry no timestamps, since the Pushgateway rejects them.
+
+use crate::{BenchmarkStats, RunSummary};
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// OTLP/HTTP path for metrics, appended to `--otlp-endpoint`.
+const OTLP_METRICS_PATH: &str = "/v1/metrics";
+
+/// A statistic exported as a gauge: name, unit, help text, and how to read it.
+type Statistic = (&'static str, &'static str, &'static str, fn(&BenchmarkStats) -> Option<f64>);
+
+const STATISTICS: &[Statistic] = &[
+    ("mobench_mean_ns", "ns", "Mean sample duration in nanoseconds.", |b| b.mean_ns.map(|v| v as f64)),
+    ("mobench_median_ns", "ns", "Median sample duration in nanoseconds.", |b| b.median_ns.map(|v| v as f64)),
+    ("mobench_p95_ns", "ns", "95th percentile sample duration in nanoseconds.", |b| b.p95_ns.map(|v| v as f64)),
+    ("mobench_p99_ns", "ns", "99th percentile sample duration in nanoseconds.", |b| b.p99_ns.map(|v| v as f64)),
+    ("mobench_min_ns", "ns", "Fastest sample in nanoseconds.", |b| b.min_ns.map(|v| v as f64)),
+    ("mobench_max_ns", "ns", "Slowest sample in nanoseconds.", |b| b.max_ns.map(|v| v as f64)),
+    ("mobench_cv_percent", "%", "Coefficient of variation of the samples in percent.", |b| b.cv_pct),
+    ("mobench_samples", "1", "Number of measured samples.", |b| Some(b.samples as f64)),
+];
+
+/// Where to send the exported metrics.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ExportTargets<'a> {
+    /// `--otlp-endpoint`: OTLP/HTTP collector base URL.
+    pub(crate) otlp_endpoint: Option<&'a str>,
+    /// `--prometheus-out`: Prometheus text-format file.
+    pub(crate) prometheus_out: Option<&'a Path>,
+}
+
+impl ExportTargets<'_> {
+    fn is_empty(&self) -> bool {
+        self.otlp_endpoint.is_none() && self.prometheus_out.is_none()
+    }
+}
+
+/// One gauge and its data points.
+#[derive(Debug, Clone, PartialEq)]
+struct Gauge {
+    name: &'static str,
+    unit: &'static str,
+    help: &'static str,
+    points: Vec<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Point {
+    labels: Vec<(&'static str, String)>,
+    value: f64,
+}
+
+/// Export `run` to every sink in `targets`.
+pub(crate) fn export(run: &RunSummary, targets: ExportTargets<'_>) -> Result<()> {
+    if targets.is_empty() {
+        return Ok(());
+    }
+    let gauges = gauges(run);
+    if let Some(path) = targets.prometheus_out {
+        crate::ensure_parent_dir(path)?;
+        std::fs::write(path, prometheus_text(&gauges))
+            .with_context(|| format!("writing Prometheus metrics to {:?}", path))?;
+        println!("Wrote Prometheus metrics to {}", path.display());
+    }
+    if let Some(endpoint) = targets.otlp_endpoint {
+        let time_unix_nano = run.summary.generated_at_unix.saturating_mul(1_000_000_000);
+        match push_otlp(endpoint, &otlp_payload(&gauges, time_unix_nano)) {
+            Ok(url) => println!("Exported metrics to {}", url),
+            Err(e) => println!("Warning: Failed to export OTLP metrics: {:#}", e),
+        }
+    }
+    Ok(())
+}
+
+/// One gauge per statistic, with a point per device and benchmark that has it.
+fn gauges(run: &RunSummary) -> Vec<Gauge> {
+    let target = match run.spec.target {
+        crate::MobileTarget::Android => "android",
+        crate::MobileTarget::Ios => "ios",
+    };
+    let git_sha = run
+        .build_meta
+        .as_ref()
+        .and_then(|meta| meta.commit_hash.clone())
+        .or_else(mobench_sdk::builders::common::get_git_commit);
+    let git_sha = git_sha.as_deref();
+    STATISTICS
+        .iter()
+        .map(|&(name, unit, help, read)| {
+            let points = run
+                .summary
+                .device_summaries
+                .iter()
+                .flat_map(|device| {
+                    let os_version = os_version(&device.device, &device.device_meta);
+                    device.benchmarks.iter().filter_map(move |bench| {
+                        let mut labels = vec![
+                            ("target", target.to_string()),
+                            ("device", device.device.clone()),
+                            ("function", bench.function.clone()),
+                        ];
+                        if let Some(version) = &os_version {
+                            labels.push(("os_version", version.clone()));
+                        }
+                        if let Some(sha) = git_sha {
+                            labels.push(("git_sha", sha.to_string()));
+                        }
+                        Some(Point { labels, value: read(bench)? })
+                    })
+                })
+                .collect();
+            Gauge { name, unit, help, points }
+        })
+        .filter(|gauge| !gauge.points.is_empty())
+        .collect()
+}
+
+/// OS version from the device metadata, or the suffix of a `<device>-<version>` name.
+fn os_version(device: &str, meta: &crate::browserstack::DeviceMeta) -> Option<String> {
+    if let Some(version) = meta.get("os_version").and_then(Value::as_str) {
+        return Some(version.to_string());
+    }
+    device
+        .rsplit_once('-')
+        .map(|(_, version)| version)
+        .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+/// The Prometheus text exposition format, without timestamps.
+fn prometheus_text(gauges: &[Gauge]) -> String {
+    let mut text = String::new();
+    for gauge in gauges {
+        let _ = writeln!(text, "# HELP {} {}", gauge.name, gauge.help);
+        let _ = writeln!(text, "# TYPE {} gauge", gauge.name);
+        for point in &gauge.points {
+            let labels: Vec<String> = point
+                .labels
+                .iter()
+                .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+                .collect();
+            let _ = writeln!(text, "{}{{{}}} {}", gauge.name, labels.join(","), point.value);
+        }
+    }
+    text
+}
+
+/// Escape a Prometheus label value: backslash, double quote and newline.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// An OTLP `ExportMetricsServiceRequest` in its JSON encoding.
+fn otlp_payload(gauges: &[Gauge], time_unix_nano: u64) -> Value {
+    let metrics: Vec<Value> = gauges
+        .iter()
+        .map(|gauge| {
+            let data_points: Vec<Value> = gauge
+                .points
+                .iter()
+                .map(|point| {
+                    let attributes: Vec<Value> = point
+                        .labels
+                        .iter()
+                        .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+                        .collect();
+                    // OTLP JSON encodes 64-bit integers as strings
+                    json!({
+                        "attributes": attributes,
+                        "timeUnixNano": time_unix_nano.to_string(),
+                        "asDouble": point.value,
+                    })
+                })
+                .collect();
+            json!({
+                "name": gauge.name,
+                "unit": gauge.unit,
+                "description": gauge.help,
+                "gauge": { "dataPoints": data_points },
+            })
+        })
+        .collect();
+    json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": "mobench" } }],
+            },
+            "scopeMetrics": [{
+                "scope": { "name": "mobench", "version": env!("CARGO_PKG_VERSION") },
+                "metrics": metrics,
+            }],
+        }],
+    })
+}
+
+/// POST `payload` to the collector, returning the URL it was sent to.
+fn push_otlp(endpoint: &str, payload: &Value) -> Result<String> {
+    let base = endpoint.trim_end_matches('/');
+    let url = if base.ends_with(OTLP_METRICS_PATH) {
+        base.to_string()
+    } else {
+        format!("{base}{OTLP_METRICS_PATH}")
+    };
+    let response = crate::browserstack::http_client()?
+        .post(&url)
+        .json(payload)
+        .send()
+        .with_context(|| format!("posting metrics to {url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().unwrap_or_default();
+        bail!("collector responded with {}: {}", status, body.trim());
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_summary() -> RunSummary {
+        serde_json::from_value(json!({
+            "spec": {
+                "target": "android",
+                "function": "sample_fns::fibonacci",
+                "iterations": 10,
+                "warmup": 2,
+                "devices": ["Google Pixel 7-13.0"],
+            },
+            "artifacts": null,
+            "local_report": {},
+            "remote_run": null,
+            "summary": {
+                "generated_at": "2026-01-01T00:00:00Z",
+                "generated_at_unix": 1_767_225_600,
+                "target": "android",
+                "function": "sample_fns::fibonacci",
+                "iterations": 10,
+                "warmup": 2,
+                "devices": ["Google Pixel 7-13.0"],
+                "device_summaries": [{
+                    "device": "Google Pixel 7-13.0",
+                    "benchmarks": [{
+                        "function": "sample_fns::fibonacci",
+                        "samples": 10,
+                        "mean_ns": 1_100,
+                        "median_ns": 1_000,
+                        "p95_ns": 1_500,
+                        "min_ns": 900,
+                        "max_ns": 1_600,
+                    }],
+                }],
+            },
+            "build_meta": {
+                "spec": { "function": "sample_fns::fibonacci", "iterations": 10, "warmup": 2 },
+                "commit_hash": "abc1234",
+                "build_time": "2026-01-01T00:00:00Z",
+                "build_time_unix": 1_767_225_600,
+                "target": "android",
+                "profile": "debug",
+                "mobench_version": "0.1.13",
+                "host_os": "linux",
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn exports_labeled_gauges() {
+        let gauges = gauges(&run_summary());
+        // p99 and CV were not recorded, so they have no gauge
+        let names: Vec<&str> = gauges.iter().map(|gauge| gauge.name).collect();
+        assert_eq!(
+            names,
+            ["mobench_mean_ns", "mobench_median_ns", "mobench_p95_ns", "mobench_min_ns", "mobench_max_ns", "mobench_samples"]
+        );
+
+        let text = prometheus_text(&gauges);
+        assert!(text.contains("# TYPE mobench_median_ns gauge\n"));
+        assert!(text.contains(
+            "mobench_median_ns{target=\"android\",device=\"Google Pixel 7-13.0\",function=\"sample_fns::fibonacci\",os_version=\"13.0\",git_sha=\"abc1234\"} 1000\n"
+        ));
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+
+        let payload = otlp_payload(&gauges, 5);
+        let metric = &payload["resourceMetrics"][0]["scopeMetrics"][0]["metrics"][1];
+        assert_eq!(metric["name"], "mobench_median_ns");
+        let point = &metric["gauge"]["dataPoints"][0];
+        assert_eq!(point["asDouble"], 1000.0);
+        assert_eq!(point["timeUnixNano"], "5");
+        assert_eq!(point["attributes"][4], json!({ "key": "git_sha", "value": { "stringValue": "abc1234" } }));
+    }
+
+    #[test]
+    fn pushes_to_the_metrics_path() {
+        let base_url = crate::browserstack::mock::serve(|path| match path {
+            "/v1/metrics" => (200, "{}".into()),
+            _ => (404, "not found".into()),
+        });
+        let payload = otlp_payload(&gauges(&run_summary()), 0);
+        assert_eq!(push_otlp(&base_url, &payload).unwrap(), format!("{base_url}/v1/metrics"));
+        push_otlp(&format!("{base_url}/v1/metrics"), &payload).unwrap();
+        let err = push_otlp(&format!("{base_url}/otel"), &payload).unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+    }
+}
@@ -0,0 +1,170 @@
+//! Run notifications for `run --notify-webhook`.
+//!
+//! After the summary is written, the run's verdict is POSTed to a webhook as
+//! JSON. `--notify-format json` sends [`Notification`] as is:
+//!
+//! ```json
+//! {
+//!   "target": "android",
+//!   "functions": ["sample_fns::fibonacci"],
+//!   "devices": ["Google Pixel 7-13.0"],
+//!   "passed": false,
+//!   "failures": ["1 regression(s) against the stored baseline exceeded ..."],
+//!   "regressions": [{"device": "...", "function": "...", "metric": "median",
+//!                    "baseline_ns": 1000, "candidate_ns": 1200, "delta_pct": 20.0}]
+//! }
+//! ```
+//!
+//! `--notify-format slack` sends a Slack incoming-webhook message instead
+//! (`text` plus a mrkdwn `blocks` section) listing the worst regressions.
+//! Delivery problems are reported as warnings and never fail the run.
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// Regressions listed in the Slack message; the JSON payload has all of them.
+const SLACK_TOP_REGRESSIONS: usize = 5;
+
+/// Payload shape for `--notify-format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum NotifyFormat {
+    /// The run verdict as plain JSON
+    #[default]
+    Json,
+    /// A Slack incoming-webhook message (`text` and `blocks`)
+    Slack,
+}
+
+/// One regressed metric, as reported to the webhook.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct RegressionDelta {
+    pub(crate) device: String,
+    pub(crate) function: String,
+    pub(crate) metric: &'static str,
+    pub(crate) baseline_ns: u64,
+    pub(crate) candidate_ns: u64,
+    pub(crate) delta_pct: f64,
+}
+
+/// Verdict of a finished run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Notification {
+    pub(crate) target: String,
+    pub(crate) functions: Vec<String>,
+    pub(crate) devices: Vec<String>,
+    /// Whether the run passed every gate, i.e. exits with 0.
+    pub(crate) passed: bool,
+    /// Why the run failed, when it did.
+    pub(crate) failures: Vec<String>,
+    /// Regressions against the stored baseline, largest delta first.
+    pub(crate) regressions: Vec<RegressionDelta>,
+}
+
+impl Notification {
+    /// The request body for `format`.
+    pub(crate) fn payload(&self, format: NotifyFormat) -> Value {
+        match format {
+            NotifyFormat::Json => serde_json::to_value(self).unwrap_or(Value::Null),
+            NotifyFormat::Slack => {
+                let text = self.slack_text();
+                json!({
+                    "text": text,
+                    "blocks": [{
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": text },
+                    }],
+                })
+            }
+        }
+    }
+
+    fn slack_text(&self) -> String {
+        let verdict = if self.passed { ":white_check_mark: passed" } else { ":x: failed" };
+        let mut text = format!(
+            "*mobench {}* {} on {} device(s): {}",
+            self.target,
+            verdict,
+            self.devices.len(),
+            self.functions.join(", ")
+        );
+        for failure in &self.failures {
+            text.push_str(&format!("\n• {}", failure));
+        }
+        if !self.regressions.is_empty() {
+            text.push_str(&format!("\n*Top regressions* ({} total):", self.regressions.len()));
+            for regression in self.regressions.iter().take(SLACK_TOP_REGRESSIONS) {
+                text.push_str(&format!(
+                    "\n• `{}` on {}: {} {:+.2}%",
+                    regression.function, regression.device, regression.metric, regression.delta_pct
+                ));
+            }
+        }
+        text
+    }
+}
+
+/// POST `payload` to `url`, failing on transport errors and non-2xx responses.
+pub(crate) fn send(url: &str, payload: &Value) -> Result<()> {
+    let response = crate::browserstack::http_client()?
+        .post(url)
+        .json(payload)
+        .send()
+        .with_context(|| format!("posting notification to {url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().unwrap_or_default();
+        bail!("webhook responded with {}: {}", status, body.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification() -> Notification {
+        Notification {
+            target: "android".into(),
+            functions: vec!["sample_fns::fibonacci".into()],
+            devices: vec!["Google Pixel 7-13.0".into()],
+            passed: false,
+            failures: vec!["1 regression(s) against the stored baseline".into()],
+            regressions: vec![RegressionDelta {
+                device: "Google Pixel 7-13.0".into(),
+                function: "sample_fns::fibonacci".into(),
+                metric: "median",
+                baseline_ns: 1_000,
+                candidate_ns: 1_200,
+                delta_pct: 20.0,
+            }],
+        }
+    }
+
+    #[test]
+    fn payload_formats() {
+        let json = notification().payload(NotifyFormat::Json);
+        assert_eq!(json["passed"], false);
+        assert_eq!(json["regressions"][0]["delta_pct"], 20.0);
+
+        let slack = notification().payload(NotifyFormat::Slack);
+        let text = slack["text"].as_str().unwrap();
+        assert!(text.starts_with("*mobench android* :x: failed on 1 device(s)"));
+        assert!(text.contains("`sample_fns::fibonacci` on Google Pixel 7-13.0: median +20.00%"));
+        assert_eq!(slack["blocks"][0]["text"]["text"], text);
+    }
+
+    #[test]
+    fn send_reports_rejected_webhooks() {
+        let base_url = crate::browserstack::mock::serve(|path| match path {
+            "/ok" => (200, "ok".into()),
+            _ => (500, "no_service".into()),
+        });
+        let payload = notification().payload(NotifyFormat::Slack);
+        send(&format!("{base_url}/ok"), &payload).unwrap();
+        let err = send(&format!("{base_url}/gone"), &payload).unwrap_err();
+        assert!(err.to_string().contains("500"), "{err}");
+        assert!(err.to_string().contains("no_service"), "{err}");
+    }
+}
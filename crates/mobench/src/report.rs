@@ -0,0 +1,242 @@
+//! HTML report for `report`, and the tiny local server behind `report --serve`.
+//!
+//! The report is a single self-contained page: a median bar chart per
+//! function (inline SVG, no scripts or external assets) followed by the full
+//! statistics table. `--serve` binds `127.0.0.1` and answers plain HTTP/1.0
+//! style requests with std only:
+//!
+//! - `/` and `/index.html` — the HTML report
+//! - `/summary.json` — the raw run summary it was rendered from
+//!
+//! Anything else gets a 404. The server runs until the process is interrupted.
+
+use crate::{SummaryReport, format_duration_smart};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write as _};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::time::Duration;
+
+/// Width in pixels of the longest bar in a chart.
+const BAR_WIDTH: u64 = 480;
+
+/// Height in pixels of one bar row.
+const ROW_HEIGHT: u64 = 22;
+
+/// How long a connection may take to send its request line.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Escape text for HTML element content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn cell(ns: Option<u64>) -> String {
+    ns.map(format_duration_smart).unwrap_or_else(|| "-".to_string())
+}
+
+/// Render `summary` as a standalone HTML page.
+pub(crate) fn render_html(summary: &SummaryReport) -> String {
+    // function -> [(device, median)] for the charts
+    let mut medians: BTreeMap<&str, Vec<(&str, u64)>> = BTreeMap::new();
+    for device in &summary.device_summaries {
+        for bench in &device.benchmarks {
+            if let Some(median) = bench.median_ns {
+                medians
+                    .entry(bench.function.as_str())
+                    .or_default()
+                    .push((device.device.as_str(), median));
+            }
+        }
+    }
+
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, "<html lang=\"en\"><head><meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>mobench report: {}</title>", escape(&summary.function));
+    let _ = writeln!(
+        html,
+        "<style>body{{font-family:sans-serif;margin:2em;color:#222}}\
+         table{{border-collapse:collapse}}th,td{{border:1px solid #ccc;padding:4px 8px;text-align:right}}\
+         th:first-child,td:first-child,td:nth-child(2){{text-align:left}}\
+         svg text{{font-size:12px}}</style>"
+    );
+    let _ = writeln!(html, "</head><body>");
+    let _ = writeln!(html, "<h1>Benchmark Summary</h1>");
+    let _ = writeln!(html, "<ul>");
+    let _ = writeln!(html, "<li>Generated: {}</li>", escape(&summary.generated_at));
+    let _ = writeln!(html, "<li>Target: {}</li>", summary.target_label());
+    let _ = writeln!(
+        html,
+        "<li>Iterations/Warmup: {} / {}</li>",
+        summary.iterations, summary.warmup
+    );
+    let _ = writeln!(html, "<li>Devices: {}</li>", escape(&summary.devices.join(", ")));
+    let _ = writeln!(html, "<li><a href=\"summary.json\">Raw JSON</a></li>");
+    let _ = writeln!(html, "</ul>");
+
+    for (function, rows) in &medians {
+        let slowest = rows.iter().map(|(_, ns)| *ns).max().unwrap_or(0).max(1);
+        let height = ROW_HEIGHT * rows.len() as u64;
+        let _ = writeln!(html, "<h2>{}</h2>", escape(function));
+        let _ = writeln!(
+            html,
+            "<svg width=\"{}\" height=\"{}\" role=\"img\" aria-label=\"Median per device\">",
+            BAR_WIDTH + 420,
+            height
+        );
+        for (index, (device, median)) in rows.iter().enumerate() {
+            let y = ROW_HEIGHT * index as u64;
+            let width = (median * BAR_WIDTH).div_ceil(slowest);
+            let _ = writeln!(
+                html,
+                "<text x=\"0\" y=\"{}\">{}</text>\
+                 <rect x=\"260\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#4a7bd0\"/>\
+                 <text x=\"{}\" y=\"{}\">{}</text>",
+                y + 15,
+                escape(device),
+                y + 3,
+                width,
+                ROW_HEIGHT - 6,
+                260 + width + 6,
+                y + 15,
+                format_duration_smart(*median)
+            );
+        }
+        let _ = writeln!(html, "</svg>");
+    }
+
+    let _ = writeln!(html, "<h2>All results</h2>");
+    let _ = writeln!(
+        html,
+        "<table><tr><th>Device</th><th>Function</th><th>Samples</th><th>Mean</th>\
+         <th>Median</th><th>P95</th><th>P99</th><th>Min</th><th>Max</th><th>CV</th></tr>"
+    );
+    for device in &summary.device_summaries {
+        for bench in &device.benchmarks {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&device.device),
+                escape(&bench.function),
+                bench.samples,
+                cell(bench.mean_ns),
+                cell(bench.median_ns),
+                cell(bench.p95_ns),
+                cell(bench.p99_ns),
+                cell(bench.min_ns),
+                cell(bench.max_ns),
+                bench
+                    .cv_pct
+                    .map(|cv| format!("{cv:.1}%"))
+                    .unwrap_or_else(|| "-".to_string())
+            );
+        }
+    }
+    let _ = writeln!(html, "</table>");
+    let _ = writeln!(html, "</body></html>");
+    html
+}
+
+/// Bind `127.0.0.1:port`; port 0 picks a free one.
+pub(crate) fn bind(port: u16) -> Result<TcpListener> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .with_context(|| format!("binding 127.0.0.1:{port} for report --serve"))
+}
+
+/// Serve `html` and `json` on `listener` until the process is interrupted.
+pub(crate) fn serve(listener: &TcpListener, html: &str, json: &str) -> Result<()> {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = handle(stream, html, json) {
+                    tracing::debug!("report --serve: {err:#}");
+                }
+            }
+            Err(err) => tracing::debug!("report --serve: accept failed: {err}"),
+        }
+    }
+    Ok(())
+}
+
+/// Answer one request. Only the request line is looked at.
+fn handle(mut stream: TcpStream, html: &str, json: &str) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    // Ignore any query string, e.g. cache busters
+    let path = parts.next().unwrap_or_default().split('?').next().unwrap_or_default();
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET" | "HEAD", "/" | "/index.html") => ("200 OK", "text/html; charset=utf-8", html),
+        ("GET" | "HEAD", "/summary.json") => ("200 OK", "application/json", json),
+        ("GET" | "HEAD", _) => ("404 Not Found", "text/plain; charset=utf-8", "not found\n"),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "method not allowed\n",
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes())?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn serves_report_and_raw_json() {
+        let html = "<html>report</html>";
+        let json = "{\"summary\":{}}";
+        let listener = bind(0).unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert!(addr.ip().is_loopback());
+
+        let fetch = |path: &str| {
+            let mut client = TcpStream::connect(addr).unwrap();
+            write!(client, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            let (stream, _) = listener.accept().unwrap();
+            handle(stream, html, json).unwrap();
+            let mut response = String::new();
+            client.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let index = fetch("/");
+        assert!(index.starts_with("HTTP/1.1 200 OK"), "{index}");
+        assert!(index.contains("text/html"), "{index}");
+        assert!(index.ends_with(html), "{index}");
+        let raw = fetch("/summary.json?t=1");
+        assert!(raw.contains("application/json"), "{raw}");
+        assert!(raw.ends_with(json), "{raw}");
+        assert!(fetch("/etc/passwd").starts_with("HTTP/1.1 404"));
+
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
@@ -0,0 +1,188 @@
+//! Raw per-iteration sample export for `--sample-out`.
+//!
+//! Run summaries only keep aggregate statistics; this module flattens the raw
+//! samples in `benchmark_results` into a long-format table for histograms and
+//! custom analysis.
+//!
+//! ## Schema
+//!
+//! One row per measured iteration:
+//!
+//! | Column | Type | Description |
+//! |--------|------|-------------|
+//! | `device` | string | Device name (`local` for host reports) |
+//! | `function` | string | Fully-qualified benchmark function |
+//! | `iteration_index` | u64 | Zero-based index of the sample within its run |
+//! | `duration_ns` | u64 | Measured duration in nanoseconds |
+//!
+//! The format follows the file extension: `.parquet` writes Parquet (requires
+//! the `arrow` feature), anything else writes CSV with a header row.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// One measured iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RawSample {
+    pub(crate) device: String,
+    pub(crate) function: String,
+    pub(crate) iteration_index: u64,
+    pub(crate) duration_ns: u64,
+}
+
+/// Flatten every sample in a run summary or bench report.
+///
+/// Reads `benchmark_results` (device → reports) when present, and otherwise
+/// a single `BenchReport` (`spec` + `samples`), which is attributed to the
+/// `local` device.
+pub(crate) fn collect_raw_samples(report: &Value) -> Vec<RawSample> {
+    let mut rows = Vec::new();
+    if let Some(results) = report.get("benchmark_results").and_then(Value::as_object) {
+        for (device, entries) in results {
+            for entry in entries.as_array().into_iter().flatten() {
+                let function = entry
+                    .get("function")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown");
+                push_samples(&mut rows, device, function, entry);
+            }
+        }
+    } else if let Some(spec) = report.get("spec") {
+        let function = spec.get("name").and_then(Value::as_str).unwrap_or("unknown");
+        push_samples(&mut rows, "local", function, report);
+    }
+    rows
+}
+
+fn push_samples(rows: &mut Vec<RawSample>, device: &str, function: &str, entry: &Value) {
+    for (index, duration_ns) in crate::extract_samples(entry).into_iter().enumerate() {
+        rows.push(RawSample {
+            device: device.to_string(),
+            function: function.to_string(),
+            iteration_index: index as u64,
+            duration_ns,
+        });
+    }
+}
+
+/// Write `samples` to `path` as CSV or, for `.parquet` paths, Parquet.
+pub(crate) fn write_sample_out(path: &Path, samples: &[RawSample]) -> Result<()> {
+    crate::ensure_parent_dir(path)?;
+    let is_parquet = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"));
+    if is_parquet {
+        write_parquet(path, samples)?;
+    } else {
+        crate::write_file(path, render_csv(samples).as_bytes())?;
+    }
+    println!("Wrote {} raw sample(s) to {:?}", samples.len(), path);
+    Ok(())
+}
+
+fn render_csv(samples: &[RawSample]) -> String {
+    let mut output = String::from("device,function,iteration_index,duration_ns\n");
+    for sample in samples {
+        let _ = writeln!(
+            output,
+            "{},{},{},{}",
+            csv_field(&sample.device),
+            csv_field(&sample.function),
+            sample.iteration_index,
+            sample.duration_ns
+        );
+    }
+    output
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(feature = "arrow")]
+fn write_parquet(path: &Path, samples: &[RawSample]) -> Result<()> {
+    use anyhow::Context;
+    use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("device", DataType::Utf8, false),
+        Field::new("function", DataType::Utf8, false),
+        Field::new("iteration_index", DataType::UInt64, false),
+        Field::new("duration_ns", DataType::UInt64, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(samples.iter().map(|s| &s.device))),
+        Arc::new(StringArray::from_iter_values(samples.iter().map(|s| &s.function))),
+        Arc::new(UInt64Array::from_iter_values(samples.iter().map(|s| s.iteration_index))),
+        Arc::new(UInt64Array::from_iter_values(samples.iter().map(|s| s.duration_ns))),
+    ];
+    let batch = RecordBatch::try_new(Arc::clone(&schema), columns)
+        .context("building Parquet record batch")?;
+
+    let file = std::fs::File::create(path).with_context(|| format!("creating {:?}", path))?;
+    let mut writer =
+        ArrowWriter::try_new(file, schema, None).context("creating Parquet writer")?;
+    writer.write(&batch).context("writing Parquet samples")?;
+    writer.close().context("finishing Parquet file")?;
+    Ok(())
+}
+
+#[cfg(not(feature = "arrow"))]
+fn write_parquet(path: &Path, _samples: &[RawSample]) -> Result<()> {
+    anyhow::bail!(
+        "cannot write {:?}: Parquet output requires mobench built with the `arrow` feature (cargo install mobench --features arrow); use a .csv path instead",
+        path
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flattens_benchmark_results_to_csv() {
+        let summary = json!({
+            "benchmark_results": {
+                "Pixel 7, 13.0": [
+                    {"function": "my::bench", "samples": [{"duration_ns": 10}, {"duration_ns": 12}]}
+                ],
+                "iPhone 14": [{"function": "my::bench", "samples": [7]}]
+            }
+        });
+
+        let samples = collect_raw_samples(&summary);
+        assert_eq!(samples.len(), 3);
+        assert_eq!(
+            render_csv(&samples),
+            "device,function,iteration_index,duration_ns\n\
+             \"Pixel 7, 13.0\",my::bench,0,10\n\
+             \"Pixel 7, 13.0\",my::bench,1,12\n\
+             iPhone 14,my::bench,0,7\n"
+        );
+    }
+
+    #[test]
+    fn reads_local_bench_report() {
+        let report = json!({
+            "spec": {"name": "my::bench", "iterations": 2, "warmup": 0},
+            "samples": [{"duration_ns": 5}, {"duration_ns": 6}]
+        });
+
+        let samples = collect_raw_samples(&report);
+        assert_eq!(samples[1].device, "local");
+        assert_eq!(samples[1].iteration_index, 1);
+        assert_eq!(samples[1].duration_ns, 6);
+    }
+}
@@ -0,0 +1,56 @@
+//! Status markers for console output.
+//!
+//! Interactive terminals get Unicode check marks. Some CI log viewers
+//! (Jenkins, GitLab) garble those, so `--no-color`/`--ascii`, a non-empty
+//! `NO_COLOR`, or a stdout that is not a terminal switch every marker to a
+//! plain ASCII tag. Written reports (Markdown, JSON, CSV) are not affected.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Decide between Unicode and ASCII markers for the rest of the process.
+pub(crate) fn init(no_color: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    ASCII.store(
+        no_color || no_color_env || !std::io::stdout().is_terminal(),
+        Ordering::Relaxed,
+    );
+}
+
+fn ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Marker for a step or check that succeeded.
+pub(crate) fn ok() -> &'static str {
+    if ascii() { "[ok]" } else { "\u{2713}" }
+}
+
+/// Marker for a required check that failed.
+pub(crate) fn error() -> &'static str {
+    if ascii() { "[FAIL]" } else { "\u{2717}" }
+}
+
+/// Marker for an optional check that failed.
+pub(crate) fn warning() -> &'static str {
+    if ascii() { "[WARN]" } else { "!" }
+}
+
+/// Column label for a percentage change, `Δ%` or `d%`.
+pub(crate) fn delta_pct() -> &'static str {
+    if ascii() { "d%" } else { "\u{394}%" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_color_selects_ascii_markers() {
+        init(true);
+        assert_eq!((ok(), error(), warning()), ("[ok]", "[FAIL]", "[WARN]"));
+        assert_eq!(delta_pct(), "d%");
+    }
+}
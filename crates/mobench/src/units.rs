@@ -0,0 +1,96 @@
+//! Time unit for rendered tables, chosen with `--unit`.
+//!
+//! By default (`auto`) every duration picks ms or s for itself, which can
+//! leave one table column mixing both. A fixed unit renders every value in a
+//! table as a bare number in that unit and names the unit in the header
+//! instead. CSV and JSON output always carry raw nanoseconds, and one-off
+//! values such as the completion banner keep the automatic unit.
+
+use clap::ValueEnum;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Unit used for durations in summary and comparison tables.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum TimeUnit {
+    /// Nanoseconds
+    Ns,
+    /// Microseconds
+    Us,
+    /// Milliseconds
+    Ms,
+    /// Seconds
+    S,
+    /// Milliseconds, or seconds from 1s up, chosen per value
+    #[default]
+    Auto,
+}
+
+static UNIT: AtomicU8 = AtomicU8::new(TimeUnit::Auto as u8);
+
+/// Select the table unit for the rest of the process.
+pub(crate) fn init(unit: TimeUnit) {
+    UNIT.store(unit as u8, Ordering::Relaxed);
+}
+
+/// The unit selected with [`init`].
+pub(crate) fn current() -> TimeUnit {
+    match UNIT.load(Ordering::Relaxed) {
+        0 => TimeUnit::Ns,
+        1 => TimeUnit::Us,
+        2 => TimeUnit::Ms,
+        3 => TimeUnit::S,
+        _ => TimeUnit::Auto,
+    }
+}
+
+impl TimeUnit {
+    /// Unit named in table headers; `auto` tables keep their `ms` header.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Ns => "ns",
+            Self::Us => "\u{b5}s",
+            Self::Ms | Self::Auto => "ms",
+            Self::S => "s",
+        }
+    }
+
+    /// A table cell: a bare number in this unit, or for `auto` the value
+    /// with its own suffix.
+    pub(crate) fn cell(self, ns: u64) -> String {
+        let value = ns as f64;
+        match self {
+            Self::Ns => ns.to_string(),
+            Self::Us => format!("{:.3}", value / 1_000.0),
+            Self::Ms => format!("{:.3}", value / 1_000_000.0),
+            Self::S => format!("{:.3}", value / 1_000_000_000.0),
+            Self::Auto => crate::format_duration_smart(ns),
+        }
+    }
+
+    /// A standalone value, always with its unit suffix.
+    pub(crate) fn with_suffix(self, ns: u64) -> String {
+        match self {
+            Self::Auto => self.cell(ns),
+            unit => format!("{}{}", unit.cell(ns), unit.label()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_units_render_bare_numbers() {
+        assert_eq!(TimeUnit::Us.cell(1_500_000), "1500.000");
+        assert_eq!(TimeUnit::Ms.cell(2_000_000_000), "2000.000");
+        assert_eq!(TimeUnit::S.cell(1_500_000), "0.002");
+        assert_eq!(TimeUnit::Ns.cell(1_500), "1500");
+        assert_eq!(TimeUnit::Auto.cell(2_000_000_000), "2.000s");
+
+        assert_eq!(TimeUnit::Us.with_suffix(1_500), "1.500\u{b5}s");
+        assert_eq!(TimeUnit::Auto.with_suffix(1_500_000), "1.500ms");
+        assert_eq!(TimeUnit::Auto.label(), "ms");
+        assert_eq!(current(), TimeUnit::Auto);
+    }
+}
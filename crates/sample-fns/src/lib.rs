@@ -59,6 +59,13 @@ impl From<BenchSpec> for mobench_sdk::timing::BenchSpec {
             name: spec.name,
             iterations: spec.iterations,
             warmup: spec.warmup,
+            warmup_policy: Default::default(),
+            sampling: Default::default(),
+            per_iteration_timeout: None,
+            timeout: None,
+            capture_warmup: false,
+            seed: None,
+            drop_first_n: 0,
         }
     }
 }
@@ -85,6 +92,11 @@ impl From<TimingError> for BenchError {
         match err {
             TimingError::NoIterations { .. } => BenchError::InvalidIterations,
             TimingError::Execution(msg) => BenchError::ExecutionFailed { reason: msg },
+            err @ (TimingError::WarmupUnstable { .. } | TimingError::Timeout { .. }) => {
+                BenchError::ExecutionFailed {
+                    reason: err.to_string(),
+                }
+            }
         }
     }
 }
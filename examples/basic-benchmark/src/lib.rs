@@ -92,6 +92,13 @@ mod tests {
             name: "basic_benchmark::bench_fibonacci".to_string(),
             iterations: 3,
             warmup: 1,
+            warmup_policy: Default::default(),
+            sampling: Default::default(),
+            per_iteration_timeout: None,
+            timeout: None,
+            capture_warmup: false,
+            seed: None,
+            drop_first_n: 0,
         };
         let report = mobench_sdk::run_benchmark(spec).unwrap();
         assert_eq!(report.samples.len(), 3);
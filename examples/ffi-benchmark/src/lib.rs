@@ -63,6 +63,13 @@ impl From<BenchSpec> for mobench_sdk::BenchSpec {
             name: spec.name,
             iterations: spec.iterations,
             warmup: spec.warmup,
+            warmup_policy: Default::default(),
+            sampling: Default::default(),
+            per_iteration_timeout: None,
+            timeout: None,
+            capture_warmup: false,
+            seed: None,
+            drop_first_n: 0,
         }
     }
 }